@@ -104,6 +104,12 @@ contract Deferred is ERC721, Ownable {
     /// @dev Event emitted when the contract is closed
     event ContractClosed(uint256 indexed sellContractId);
 
+    /// @dev Event emitted when the Merkle root of the documents of a contract is anchored
+    event DocumentsAnchored(
+        uint256 indexed sellContractId,
+        bytes32 documentsRoot
+    );
+
     /// @dev Event emitted when a token is transferred
     event TokenTransferred(
         uint256 indexed sellContractId,
@@ -268,6 +274,22 @@ contract Deferred is ERC721, Ownable {
         emit ContractClosed(_contractId);
     }
 
+    /// @notice Anchor the Merkle root of the documents of a sell contract.
+    /// @dev The leaves are the hashes of the documents, as computed by the deferred data canister
+    /// @param _contractId The id of the contract
+    /// @param _documentsRoot The Merkle root of the documents of the contract
+    function anchorDocuments(
+        uint256 _contractId,
+        bytes32 _documentsRoot
+    ) external onlyMinter {
+        require(
+            sellContracts[_contractId].created,
+            "Deferred: contract does not exist"
+        );
+
+        emit DocumentsAnchored(_contractId, _documentsRoot);
+    }
+
     /// @notice Get the next token id to buy for a sell contract for the caller
     /// @param _contractId The id of the contract
     /// @param _caller The address of the caller
@@ -369,6 +369,38 @@ describe("Deferred", () => {
     await deferred.connect(minter).closeContract(contractId);
   });
 
+  it("Should anchor the documents of a contract", async () => {
+    const { deferred, minter, alice, charlie } = deploy;
+
+    await deferred.connect(minter).createContract({
+      contractId: 1,
+      sellers: [
+        {
+          seller: alice.address,
+          quota: 100,
+        },
+      ],
+      metadataUri: "metadataUri",
+      buyers: [charlie.address],
+      ekokeReward: 1_000,
+      tokenPriceUsd: 100,
+      tokensAmount: 40_000,
+    });
+
+    const root = ethers.keccak256(ethers.toUtf8Bytes("documents"));
+    await expect(deferred.connect(minter).anchorDocuments(1, root))
+      .to.emit(deferred, "DocumentsAnchored")
+      .withArgs(1, root);
+
+    // only the minter can anchor documents of existing contracts
+    await expect(
+      deferred.connect(alice).anchorDocuments(1, root)
+    ).to.be.revertedWith("Deferred: caller is not the minter");
+    await expect(
+      deferred.connect(minter).anchorDocuments(2, root)
+    ).to.be.revertedWith("Deferred: contract does not exist");
+  });
+
   it("Should not return closed contracts", async () => {
     const { deferred, minter, alice, bob, charlie } = deploy;
 
@@ -1,5 +1,5 @@
 use candid::{Encode, Principal};
-use did::deferred::{Contract, DeferredDataResult, GenericValue};
+use did::deferred::{Contract, DeferredDataResult, GenericValue, MigrationStatus, RealEstate};
 use did::ID;
 
 use crate::actor::admin;
@@ -64,4 +64,31 @@ where
             .await
             .unwrap()
     }
+
+    pub async fn get_real_estate(&self, id: &ID) -> DeferredDataResult<RealEstate> {
+        self.env
+            .query(
+                self.env.deferred_data(),
+                admin(),
+                "get_real_estate",
+                Encode!(id).unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    pub async fn admin_migration_status(
+        &self,
+        caller: Principal,
+    ) -> DeferredDataResult<MigrationStatus> {
+        self.env
+            .query(
+                self.env.deferred_data(),
+                caller,
+                "admin_migration_status",
+                Encode!(&()).unwrap(),
+            )
+            .await
+            .unwrap()
+    }
 }
@@ -1,5 +1,8 @@
 use candid::{Encode, Principal};
-use did::deferred::{Agency, ContractRegistration, DeferredMinterResult, RealEstate};
+use did::deferred::{
+    Agency, AgencyId, AgencyMember, AgencyReport, AgencyRole, ContractRegistration,
+    DeferredMinterResult, MigrationStatus, Proposal, RealEstate, RealEstateV1,
+};
 use did::{H160, ID};
 
 use crate::actor::admin;
@@ -96,6 +99,27 @@ where
         res
     }
 
+    /// Create a real estate on a deferred minter running the wasm released before the agency ids,
+    /// which identifies the agency of the real estate by a principal
+    pub async fn create_real_estate_previous(
+        &self,
+        caller: Principal,
+        real_estate: RealEstateV1,
+    ) -> DeferredMinterResult<ID> {
+        let res: DeferredMinterResult<ID> = self
+            .env
+            .update(
+                self.env.deferred_minter(),
+                caller,
+                "create_real_estate",
+                Encode!(&real_estate).unwrap(),
+            )
+            .await
+            .expect("Failed to create real estate");
+
+        res
+    }
+
     pub async fn delete_real_estate(
         &self,
         caller: Principal,
@@ -136,8 +160,9 @@ where
     }
 
     pub async fn set_custodians(&self, principals: Vec<Principal>) {
-        self.env
-            .update::<()>(
+        let result: DeferredMinterResult<Proposal> = self
+            .env
+            .update(
                 self.env.deferred_minter(),
                 admin(),
                 "admin_set_custodians",
@@ -145,10 +170,29 @@ where
             )
             .await
             .expect("Failed to set custodians");
+
+        result.expect("Failed to set custodians");
+    }
+
+    pub async fn admin_register_agency(&self, wallet: Principal, agency: Agency) -> AgencyId {
+        let result: DeferredMinterResult<AgencyId> = self
+            .env
+            .update(
+                self.env.deferred_minter(),
+                admin(),
+                "admin_register_agency",
+                Encode!(&wallet, &agency).unwrap(),
+            )
+            .await
+            .expect("Failed to register agency");
+
+        result.expect("Failed to register agency")
     }
 
-    pub async fn admin_register_agency(&self, wallet: Principal, agency: Agency) {
-        let _: () = self
+    /// Register an agency on a deferred minter running the wasm released before the agency ids,
+    /// which doesn't return the id of the agency
+    pub async fn admin_register_agency_previous(&self, wallet: Principal, agency: Agency) {
+        let result: DeferredMinterResult<()> = self
             .env
             .update(
                 self.env.deferred_minter(),
@@ -158,17 +202,81 @@ where
             )
             .await
             .expect("Failed to register agency");
+
+        result.expect("Failed to register agency");
     }
 
-    pub async fn remove_agency(&self, wallet: Principal) -> DeferredMinterResult<()> {
+    pub async fn remove_agency(
+        &self,
+        caller: Principal,
+        agency: AgencyId,
+    ) -> DeferredMinterResult<AgencyReport> {
         self.env
             .update(
                 self.env.deferred_minter(),
-                wallet,
+                caller,
                 "remove_agency",
-                Encode!(&wallet).unwrap(),
+                Encode!(&agency).unwrap(),
             )
             .await
             .expect("Failed to remove agency")
     }
+
+    pub async fn add_agency_member(
+        &self,
+        caller: Principal,
+        agency: AgencyId,
+        member: Principal,
+        role: AgencyRole,
+    ) -> DeferredMinterResult<()> {
+        self.env
+            .update(
+                self.env.deferred_minter(),
+                caller,
+                "add_agency_member",
+                Encode!(&agency, &member, &role).unwrap(),
+            )
+            .await
+            .expect("Failed to add agency member")
+    }
+
+    pub async fn get_agency_members(
+        &self,
+        caller: Principal,
+        agency: AgencyId,
+    ) -> DeferredMinterResult<Vec<AgencyMember>> {
+        self.env
+            .query(
+                self.env.deferred_minter(),
+                caller,
+                "get_agency_members",
+                Encode!(&agency).unwrap(),
+            )
+            .await
+            .expect("Failed to get agency members")
+    }
+
+    pub async fn get_agencies(&self) -> Vec<Agency> {
+        self.env
+            .query(
+                self.env.deferred_minter(),
+                admin(),
+                "get_agencies",
+                Encode!(&()).unwrap(),
+            )
+            .await
+            .expect("Failed to get agencies")
+    }
+
+    pub async fn admin_migration_status(&self) -> DeferredMinterResult<MigrationStatus> {
+        self.env
+            .query(
+                self.env.deferred_minter(),
+                admin(),
+                "admin_migration_status",
+                Encode!(&()).unwrap(),
+            )
+            .await
+            .expect("Failed to get migration status")
+    }
 }
@@ -198,6 +198,8 @@ impl DfxTestEnv {
             evm_rpc,
             evm_rpc_api: Some(evm.url.clone()),
             reward_pool: evm.reward_pool,
+            // the local chain is scanned from the genesis block
+            reward_pool_deploy_block: 0,
             log_settings: LogSettingsV2 {
                 enable_console: true,
                 in_memory_records: 128,
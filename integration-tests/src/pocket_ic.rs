@@ -2,7 +2,7 @@ use std::io::Read as _;
 use std::path::PathBuf;
 
 use candid::{CandidType, Decode, Encode, Principal};
-use did::deferred::{DeferredDataInitData, DeferredMinterInitData, EcdsaKey};
+use did::deferred::{ConfigurationUpdate, DeferredDataInitData, DeferredMinterInitData, EcdsaKey};
 use ic_log::LogSettingsV2;
 use pocket_ic::nonblocking::PocketIc;
 use pocket_ic::{PocketIcBuilder, WasmResult};
@@ -98,6 +98,36 @@ impl TestEnv for PocketIcTestEnv {
 impl PocketIcTestEnv {
     /// Install the canisters needed for the tests
     pub async fn init() -> Self {
+        Self::init_with(Canister::DeferredData, Canister::DeferredMinter).await
+    }
+
+    /// Install the canisters of the previous release, to test the upgrade to the current wasm
+    /// with [`PocketIcTestEnv::upgrade_canisters`].
+    ///
+    /// The previous wasm is built by `scripts/previous-wasm.sh`: panics if it is missing
+    pub async fn init_previous() -> Self {
+        let missing = [
+            Canister::DeferredDataPrevious,
+            Canister::DeferredMinterPrevious,
+        ]
+        .into_iter()
+        .map(Self::wasm_path)
+        .find(|path| !path.exists());
+        if let Some(path) = missing {
+            panic!(
+                "{} not found: build it with scripts/previous-wasm.sh",
+                path.display()
+            );
+        }
+
+        Self::init_with(
+            Canister::DeferredDataPrevious,
+            Canister::DeferredMinterPrevious,
+        )
+        .await
+    }
+
+    async fn init_with(deferred_data_wasm: Canister, deferred_minter_wasm: Canister) -> Self {
         let pic = PocketIcBuilder::new()
             .with_nns_subnet()
             .with_ii_subnet() // To have ECDSA keys
@@ -118,8 +148,16 @@ impl PocketIcTestEnv {
         let evm = EvmBuilder::setup().await.expect("Failed to setup EVM");
 
         // install canisters
-        Self::install_deferred_data(&pic, deferred_data, deferred_minter).await;
-        Self::install_deferred_minter(&pic, deferred_minter, deferred_data, evm_rpc, &evm).await;
+        Self::install_deferred_data(&pic, deferred_data, deferred_minter, deferred_data_wasm).await;
+        Self::install_deferred_minter(
+            &pic,
+            deferred_minter,
+            deferred_data,
+            evm_rpc,
+            &evm,
+            deferred_minter_wasm,
+        )
+        .await;
         Self::configure_evm_rpc_canister(&pic, evm_rpc).await;
 
         let env = Self {
@@ -153,9 +191,10 @@ impl PocketIcTestEnv {
         pic: &PocketIc,
         canister_id: Principal,
         deferred_minter: Principal,
+        wasm: Canister,
     ) {
         pic.add_cycles(canister_id, DEFAULT_CYCLES).await;
-        let wasm_bytes = Self::load_wasm(Canister::DeferredData);
+        let wasm_bytes = Self::load_wasm(wasm);
 
         let init_arg = DeferredDataInitData {
             log_settings: LogSettingsV2 {
@@ -178,9 +217,10 @@ impl PocketIcTestEnv {
         deferred_data: Principal,
         evm_rpc: Principal,
         evm: &Evm,
+        wasm: Canister,
     ) {
         pic.add_cycles(canister_id, DEFAULT_CYCLES).await;
-        let wasm_bytes = Self::load_wasm(Canister::DeferredMinter);
+        let wasm_bytes = Self::load_wasm(wasm);
 
         let init_args = DeferredMinterInitData {
             allowed_currencies: vec!["USD".to_string()],
@@ -192,6 +232,8 @@ impl PocketIcTestEnv {
             evm_rpc,
             evm_rpc_api: Some(evm.url.clone()),
             reward_pool: evm.reward_pool,
+            // the local chain is scanned from the genesis block
+            reward_pool_deploy_block: 0,
             log_settings: LogSettingsV2 {
                 enable_console: true,
                 in_memory_records: 128,
@@ -223,11 +265,39 @@ impl PocketIcTestEnv {
             .await;
     }
 
-    fn load_wasm(canister: Canister) -> Vec<u8> {
+    /// Upgrade the deferred data and the deferred minter to the current wasm
+    pub async fn upgrade_canisters(&self) {
+        self.pic
+            .upgrade_canister(
+                self.deferred_data,
+                Self::load_wasm(Canister::DeferredData),
+                Encode!().unwrap(),
+                None,
+            )
+            .await
+            .expect("Failed to upgrade deferred data");
+
+        let update: Option<ConfigurationUpdate> = None;
+        self.pic
+            .upgrade_canister(
+                self.deferred_minter,
+                Self::load_wasm(Canister::DeferredMinter),
+                Encode!(&update).unwrap(),
+                None,
+            )
+            .await
+            .expect("Failed to upgrade deferred minter");
+    }
+
+    fn wasm_path(canister: Canister) -> PathBuf {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push(canister.as_path());
 
-        let mut file = std::fs::File::open(path).unwrap();
+        path
+    }
+
+    fn load_wasm(canister: Canister) -> Vec<u8> {
+        let mut file = std::fs::File::open(Self::wasm_path(canister)).unwrap();
         let mut wasm_bytes = Vec::new();
         file.read_to_end(&mut wasm_bytes).unwrap();
 
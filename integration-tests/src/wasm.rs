@@ -4,6 +4,10 @@ pub enum Canister {
     DeferredData,
     DeferredMinter,
     EvmRpc,
+    /// Deferred data of the previous release, built by `scripts/previous-wasm.sh`
+    DeferredDataPrevious,
+    /// Deferred minter of the previous release, built by `scripts/previous-wasm.sh`
+    DeferredMinterPrevious,
 }
 
 impl Canister {
@@ -16,6 +20,8 @@ impl Canister {
                 Path::new("../.dfx/local/canisters/deferred_minter/deferred_minter.wasm")
             }
             Canister::EvmRpc => Path::new("../assets/wasm/evm_rpc.wasm.gz"),
+            Canister::DeferredDataPrevious => Path::new("../.dfx/previous/deferred_data.wasm"),
+            Canister::DeferredMinterPrevious => Path::new("../.dfx/previous/deferred_minter.wasm"),
         }
     }
 }
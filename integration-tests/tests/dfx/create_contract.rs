@@ -1,7 +1,8 @@
 use std::time::Duration;
 
-use candid::Principal;
-use did::deferred::{Agency, ContractRegistration, ContractType, GenericValue, RealEstate, Seller};
+use did::deferred::{
+    Agency, AgencyId, ContractRegistration, ContractType, GenericValue, RealEstate, Seller,
+};
 use integration_tests::client::{DeferredDataClient, DeferredMinterClient};
 use integration_tests::eth_rpc_client::{DeferredErc721Client, EthRpcClient};
 use integration_tests::{DfxTestEnv, WalletName};
@@ -15,7 +16,7 @@ async fn test_should_create_and_close_contract() {
     let client = DeferredMinterClient::new(&env);
 
     // create agent
-    client
+    let agency = client
         .admin_register_agency(
             admin,
             Agency {
@@ -28,7 +29,7 @@ async fn test_should_create_and_close_contract() {
     tokio::time::sleep(Duration::from_secs(5)).await;
 
     // create real estate
-    let real_estate = real_estate(admin);
+    let real_estate = real_estate(agency);
     let real_estate_id = client
         .create_real_estate(admin, real_estate)
         .await
@@ -115,7 +116,7 @@ async fn test_should_create_and_close_contract() {
     assert_eq!(data_client.get_contract(&contract_id).await, None);
 }
 
-fn real_estate(agency: Principal) -> RealEstate {
+fn real_estate(agency: AgencyId) -> RealEstate {
     RealEstate {
         name: "Beautiful house".to_string(),
         address: Some("Via Roma 10".to_string()),
@@ -0,0 +1,107 @@
+use candid::Principal;
+use did::deferred::{Agency, AgencyId, AgencyRole, RealEstateV1};
+use integration_tests::actor::alice;
+use integration_tests::client::{DeferredDataClient, DeferredMinterClient};
+use integration_tests::PocketIcTestEnv;
+use pretty_assertions::assert_eq;
+
+/// Ticks to wait for the migrations run by the timers to complete
+const MIGRATION_TICKS: usize = 10;
+
+#[tokio::test]
+#[ignore = "requires the previous release wasm, run with `cargo make upgrade-tests`"]
+async fn test_should_migrate_records_on_upgrade_from_previous_wasm() {
+    let env = PocketIcTestEnv::init_previous().await;
+    let minter_client = DeferredMinterClient::new(&env);
+    let data_client = DeferredDataClient::new(&env);
+
+    // store the records with the previous wasm
+    minter_client
+        .admin_register_agency_previous(
+            alice(),
+            Agency {
+                owner: alice(),
+                ..Default::default()
+            },
+        )
+        .await;
+    let real_estate_id = minter_client
+        .create_real_estate_previous(alice(), real_estate(alice()))
+        .await
+        .expect("Failed to create real estate");
+
+    env.upgrade_canisters().await;
+    for _ in 0..MIGRATION_TICKS {
+        env.pic.tick().await;
+    }
+
+    let status = minter_client
+        .admin_migration_status()
+        .await
+        .expect("Failed to get migration status");
+    assert!(status.is_completed());
+    assert_eq!(status.collections[0].version, 1);
+
+    // the deferred data is installed by the anonymous principal, which is its owner
+    let status = data_client
+        .admin_migration_status(Principal::anonymous())
+        .await
+        .expect("Failed to get migration status");
+    assert!(status.is_completed());
+    assert!(status
+        .collections
+        .iter()
+        .all(|schema| schema.version == schema.target_version));
+
+    // the migrated records are still readable and the agency is identified by its legacy id
+    let agency = AgencyId::legacy(alice());
+    let agencies = minter_client.get_agencies().await;
+    assert_eq!(agencies.len(), 1);
+    assert_eq!(agencies[0].id, agency);
+    let members = minter_client
+        .get_agency_members(alice(), agency)
+        .await
+        .expect("Failed to get agency members");
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].principal, alice());
+    assert_eq!(members[0].role, AgencyRole::Admin);
+
+    let real_estate = data_client
+        .get_real_estate(&real_estate_id)
+        .await
+        .expect("Failed to get real estate");
+    assert_eq!(real_estate.agency, agency);
+}
+
+fn real_estate(agency: Principal) -> RealEstateV1 {
+    RealEstateV1 {
+        name: "Beautiful house".to_string(),
+        address: Some("Via Roma 10".to_string()),
+        agency,
+        deleted: false,
+        description: "Beautiful house in the center of Rome".to_string(),
+        image: None,
+        continent: None,
+        country: None,
+        region: None,
+        city: None,
+        zip_code: None,
+        zone: None,
+        latitude: None,
+        longitude: None,
+        square_meters: None,
+        rooms: None,
+        bathrooms: None,
+        floors: None,
+        bedrooms: None,
+        year_of_construction: None,
+        energy_class: None,
+        garage: None,
+        garden: None,
+        balconies: None,
+        pool: None,
+        parking: None,
+        elevator: None,
+        youtube: None,
+    }
+}
@@ -1,4 +1,5 @@
 mod get_eth_address;
+mod migrations;
 
 use integration_tests::PocketIcTestEnv;
 
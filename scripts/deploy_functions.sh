@@ -33,6 +33,7 @@ deploy_deferred_minter() {
   ADMIN_PRINCIPAL="$7"
   EVM_RPC_PRINCIPAL="$8"
   REWARD_POOL="$9"
+  REWARD_POOL_DEPLOY_BLOCK="${10}"
 
   echo "deploying deferred minter canister"
 
@@ -45,6 +46,7 @@ deploy_deferred_minter() {
     evm_rpc = principal \"$EVM_RPC_PRINCIPAL\";
     custodians = vec { principal \"$ADMIN_PRINCIPAL\" };
     reward_pool = \"$REWARD_POOL\";
+    reward_pool_deploy_block = $REWARD_POOL_DEPLOY_BLOCK;
     log_settings = record {
       enable_console = false;
       in_memory_records = 128;
@@ -11,6 +11,8 @@ DEFERRED_DATA="2m6dw-uaaaa-aaaal-arumq-cai"
 DEFERRED_MINTER="2f5ik-ciaaa-aaaal-aruna-cai"
 DEFERRED_ERC721="0xA0939B965AE2683DA136cFF37FC856Ca46c66Cd6"
 REWARD_POOL="0x161b3061b67C77bb866ECbA67Fa29936A51011F0"
+# block where the reward pool has been deployed; its events are read from here
+REWARD_POOL_DEPLOY_BLOCK="${REWARD_POOL_DEPLOY_BLOCK:?REWARD_POOL_DEPLOY_BLOCK must be set}"
 EVM_RPC_PRINCIPAL="7hfb6-caaaa-aaaar-qadga-cai"
 ECDSA_KEY="Production"
 
@@ -47,7 +49,8 @@ case "$CANISTER" in
       "$DEFERRED_DATA" \
       "$ADMIN_PRINCIPAL" \
       "$EVM_RPC_PRINCIPAL" \
-      "$REWARD_POOL"
+      "$REWARD_POOL" \
+      "$REWARD_POOL_DEPLOY_BLOCK"
     ;;
 
   *)
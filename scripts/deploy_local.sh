@@ -17,6 +17,7 @@ DEFERRED_ERC721="0xc08e14F47382BCc1dA6c3Ff366018cAb1c77091F"
 ECDSA_KEY="Dfx"
 EVM_RPC_PRINCIPAL="7hfb6-caaaa-aaaar-qadga-cai"
 REWARD_POOL="0xc08e14F47382BCc1dA6c3Ff366018cAb1c77091F"
+REWARD_POOL_DEPLOY_BLOCK="0"
 
 dfx stop
 dfx start --background
@@ -36,7 +37,8 @@ deploy_deferred_minter \
     $DEFERRED_DATA_PRINCIPAL \
     $ADMIN_PRINCIPAL \
     $EVM_RPC_PRINCIPAL \
-    $REWARD_POOL
+    $REWARD_POOL \
+    $REWARD_POOL_DEPLOY_BLOCK
 
 
 set +e
@@ -0,0 +1,58 @@
+#!/bin/bash
+
+# Build the wasm of the canisters of the previous release into .dfx/previous,
+# to test the upgrade to the current wasm.
+#
+# Usage: ./scripts/previous-wasm.sh [git ref]
+# The ref defaults to the latest release tag (v*). Without any release tag
+# the script fails, since there is nothing to test the upgrade from.
+# The wasm is built again only when the ref points to another commit.
+
+PREVIOUS_REF=${1:-$(git describe --tags --abbrev=0 --match 'v[0-9]*' 2>/dev/null)}
+if [ -z "$PREVIOUS_REF" ]; then
+  echo "No release tag found: provide the git ref of the previous release"
+  exit 1
+fi
+
+PREVIOUS_COMMIT=$(git rev-parse --verify --quiet "$PREVIOUS_REF^{commit}")
+if [ -z "$PREVIOUS_COMMIT" ]; then
+  echo "Invalid git ref: $PREVIOUS_REF"
+  exit 1
+fi
+
+ROOT_DIR=$(pwd)
+OUT_DIR="$ROOT_DIR/.dfx/previous"
+COMMIT_FILE="$OUT_DIR/COMMIT"
+
+if [ -f "$OUT_DIR/deferred_data.wasm" ] \
+  && [ -f "$OUT_DIR/deferred_minter.wasm" ] \
+  && [ "$(cat "$COMMIT_FILE" 2>/dev/null)" = "$PREVIOUS_COMMIT" ]; then
+  echo "Using the $PREVIOUS_REF wasm cached in $OUT_DIR"
+  exit 0
+fi
+
+# the wasm of another release must not be tested if the build fails
+rm -f "$OUT_DIR/deferred_data.wasm" "$OUT_DIR/deferred_minter.wasm" "$COMMIT_FILE"
+
+WORKTREE_DIR=$(mktemp -d)
+# the dependencies built for a previous release are reused by the next builds
+TARGET_DIR="$ROOT_DIR/target/previous"
+
+git worktree add --detach "$WORKTREE_DIR" "$PREVIOUS_COMMIT" || exit 1
+
+cd "$WORKTREE_DIR"
+CARGO_TARGET_DIR="$TARGET_DIR" cargo build --target wasm32-unknown-unknown --release -p deferred_data -p deferred_minter
+RC=$?
+cd "$ROOT_DIR"
+
+if [ $RC -eq 0 ]; then
+  mkdir -p "$OUT_DIR"
+  cp "$TARGET_DIR/wasm32-unknown-unknown/release/deferred_data.wasm" "$OUT_DIR/"
+  cp "$TARGET_DIR/wasm32-unknown-unknown/release/deferred_minter.wasm" "$OUT_DIR/"
+  echo "$PREVIOUS_COMMIT" > "$COMMIT_FILE"
+  echo "Built $PREVIOUS_REF wasm into $OUT_DIR"
+fi
+
+git worktree remove --force "$WORKTREE_DIR"
+
+exit $RC
@@ -2,8 +2,46 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export interface AuditEntry {
+  'id' : bigint,
+  'method' : string,
+  'args' : string,
+  'hash' : string,
+  'result' : AuditResult,
+  'timestamp' : bigint,
+  'caller' : Principal,
+  'prev_hash' : string,
+}
+export interface AuditFilter {
+  'to' : [] | [bigint],
+  'method' : [] | [string],
+  'from' : [] | [bigint],
+  'caller' : [] | [Principal],
+}
+export interface AuditIntegrity {
+  'head' : string,
+  'entries' : bigint,
+  'first_invalid_entry' : [] | [bigint],
+}
+export type AuditResult = { 'Ok' : null } |
+  { 'Err' : string };
+export interface CollectionDigest {
+  'next' : [] | [Uint8Array | number[]],
+  'records' : bigint,
+  'hash' : string,
+  'after' : [] | [Uint8Array | number[]],
+  'collection' : ExportCollection,
+}
+export interface CollectionSchema {
+  'collection' : string,
+  'version' : number,
+  'target_version' : number,
+}
 export type ConfigurationError = { 'AnonymousOwner' : null } |
-  { 'AnonymousMinter' : null };
+  { 'AnonymousSnsGovernance' : null } |
+  { 'InvalidMimeType' : string } |
+  { 'AnonymousMinter' : null } |
+  { 'InvalidDomain' : string };
 export type Continent = { 'Africa' : null } |
   { 'Antarctica' : null } |
   { 'Asia' : null } |
@@ -17,7 +55,7 @@ export interface Contract {
   'documents' : Array<[bigint, ContractDocument]>,
   'value' : bigint,
   'type' : ContractType,
-  'agency' : Principal,
+  'agency' : bigint,
   'restricted_properties' : Array<[string, RestrictedProperty]>,
   'properties' : Array<[string, GenericValue]>,
   'deposit' : bigint,
@@ -42,13 +80,31 @@ export interface ContractDocumentData {
 export type ContractError = { 'DocumentNotFound' : bigint } |
   { 'ContractNotFound' : bigint } |
   { 'DocumentSizeMismatch' : [bigint, bigint] } |
+  { 'DocumentHashMismatch' : [string, string] } |
+  { 'InvalidDocumentHash' : string } |
+  { 'UploadNotFound' : bigint } |
+  { 'DocumentVersionNotFound' : [bigint, bigint] } |
+  { 'DocumentPurged' : [bigint, bigint] } |
+  { 'DocumentNotAnchored' : bigint } |
+  { 'DocumentsRootMismatch' : [string, string] } |
+  { 'AgencyHasOpenContracts' : Array<bigint> } |
+  { 'NotOwnedByAgency' : bigint } |
+  { 'AgencyQuotaExceeded' : [bigint, AgencyQuota] } |
+  { 'DocumentPreviewNotFound' : bigint } |
+  { 'MimeTypeNotAllowed' : string } |
+  { 'MimeTypeMismatch' : [string, string] } |
+  { 'AgencyNotFound' : bigint } |
   { 'BadContractProperty' : null };
 export type ContractType = { 'Sell' : null } |
   { 'Financing' : null };
 export type DeferredDataError = { 'Configuration' : ConfigurationError } |
   { 'Contract' : ContractError } |
   { 'RealEstate' : RealEstateError } |
+  { 'MissingPermission' : Permission } |
   { 'InvalidSignature' : null } |
+  { 'ReadOnly' : null } |
+  { 'Import' : ImportError } |
+  { 'SignedMessage' : SignedMessageError } |
   { 'Unauthorized' : null } |
   { 'StorageError' : null } |
   { 'CanisterCall' : [RejectionCode, string] };
@@ -56,6 +112,75 @@ export interface DeferredDataInitData {
   'minter' : Principal,
   'log_settings' : LogSettingsV2,
 }
+export interface DocumentHistory {
+  'deleted_at' : [] | [bigint],
+  'versions' : Array<DocumentVersion>,
+  'contract_id' : bigint,
+}
+export interface DocumentLeaf { 'sha256' : string, 'document_id' : bigint }
+export interface DocumentProof {
+  'sha256' : string,
+  'root' : string,
+  'transaction_hash' : string,
+  'chain_id' : bigint,
+  'proof' : Array<string>,
+  'document_id' : bigint,
+  'anchored_at' : bigint,
+}
+export interface DocumentVersion {
+  'created_at' : bigint,
+  'created_by' : Principal,
+  'data_id' : bigint,
+  'document' : ContractDocument,
+  'version' : bigint,
+  'purged' : boolean,
+}
+export interface DocumentsAnchor {
+  'root' : string,
+  'transaction_hash' : string,
+  'chain_id' : bigint,
+  'contract_id' : bigint,
+  'leaves' : Array<DocumentLeaf>,
+  'anchored_at' : bigint,
+}
+export interface ExportChunk {
+  'last' : boolean,
+  'records' : Array<ExportRecord>,
+  'collection' : ExportCollection,
+  'after' : [] | [ExportCursor],
+  'checksum' : string,
+}
+export type ExportCollection = { 'RealEstates' : null } |
+  { 'NextDocumentId' : null } |
+  { 'DocumentHistory' : null } |
+  { 'DocumentAnchors' : null } |
+  { 'DocumentHashes' : null } |
+  { 'DocumentContents' : null } |
+  { 'Documents' : null } |
+  { 'DocumentReferences' : null } |
+  { 'AgencyQuotas' : null } |
+  { 'AgencyUsage' : null } |
+  { 'ContractUsage' : null } |
+  { 'DocumentPreviews' : null } |
+  { 'DocumentContentChunks' : null } |
+  { 'Contracts' : null } |
+  { 'AgencyMembers' : null } |
+  { 'AgenciesStatus' : null } |
+  { 'AgencyTransfersAudit' : null } |
+  { 'ConsumedNonces' : null } |
+  { 'SchemaVersions' : null } |
+  { 'AllowedMimeTypes' : null } |
+  { 'CustomDomains' : null };
+export interface ExportCursor {
+  'key' : Uint8Array | number[],
+  'offset' : [] | [bigint],
+}
+export interface ExportRecord {
+  'key' : Uint8Array | number[],
+  'value' : Uint8Array | number[],
+  'size' : bigint,
+  'offset' : bigint,
+}
 export type GenericValue = { 'Nat64Content' : bigint } |
   { 'Nat32Content' : number } |
   { 'BoolContent' : boolean } |
@@ -80,8 +205,13 @@ export interface HttpResponse {
   'body' : Uint8Array | number[],
   'headers' : Array<[string, string]>,
   'upgrade' : [] | [boolean],
+  'streaming_strategy' : [] | [StreamingStrategy],
   'status_code' : number,
 }
+export type ImportError = { 'InvalidRecord' : ExportCollection } |
+  { 'CollectionNotEmpty' : ExportCollection } |
+  { 'InvalidChecksum' : null } |
+  { 'UnexpectedChunk' : ExportCollection };
 export interface Log { 'log' : string, 'offset' : bigint }
 export interface LogSettingsV2 {
   'log_filter' : string,
@@ -90,7 +220,27 @@ export interface LogSettingsV2 {
   'max_record_length' : bigint,
 }
 export interface Logs { 'logs' : Array<Log>, 'all_logs_count' : bigint }
+export interface MigrationProgress {
+  'to_version' : number,
+  'total' : bigint,
+  'collection' : string,
+  'started_at' : bigint,
+  'cursor' : [] | [Uint8Array | number[]],
+  'from_version' : number,
+  'migrated' : bigint,
+}
+export interface MigrationStatus {
+  'pending' : Array<MigrationProgress>,
+  'collections' : Array<CollectionSchema>,
+}
 export interface Pagination { 'count' : bigint, 'offset' : bigint }
+export type Permission = { 'Minter' : null } |
+  { 'Owner' : null };
+export interface ReadOnlyMode {
+  'enabled_at' : bigint,
+  'enabled_by' : Principal,
+  'reason' : string,
+}
 export interface RealEstate {
   'region' : [] | [string],
   'latitude' : [] | [number],
@@ -107,7 +257,7 @@ export interface RealEstate {
   'zone' : [] | [string],
   'garage' : [] | [boolean],
   'garden' : [] | [boolean],
-  'agency' : Principal,
+  'agency' : bigint,
   'continent' : [] | [Continent],
   'description' : string,
   'longitude' : [] | [number],
@@ -121,7 +271,8 @@ export interface RealEstate {
   'parking' : [] | [boolean],
   'rooms' : [] | [bigint],
 }
-export type RealEstateError = { 'NotFound' : bigint };
+export type RealEstateError = { 'NotOwnedByAgency' : bigint } |
+  { 'NotFound' : bigint };
 export type RejectionCode = { 'NoError' : null } |
   { 'CanisterError' : null } |
   { 'SysTransient' : null } |
@@ -145,23 +296,191 @@ export type Result_2 = { 'Ok' : RealEstate } |
   { 'Err' : DeferredDataError };
 export type Result_3 = { 'Ok' : bigint } |
   { 'Err' : DeferredDataError };
+export interface AgencyQuota {
+  'max_documents' : bigint,
+  'max_document_bytes' : bigint,
+}
+export interface AgencyReport {
+  'real_estates' : Array<bigint>,
+  'open_contracts' : Array<bigint>,
+  'agency' : bigint,
+}
+export interface AgencyTransfer {
+  'id' : bigint,
+  'to' : bigint,
+  'status' : AgencyTransferStatus,
+  'from' : bigint,
+  'requested_by' : Principal,
+  'approved_by' : [] | [Principal],
+  'real_estates' : Array<bigint>,
+  'from_accepted_by' : [] | [Principal],
+  'requested_at' : bigint,
+  'contracts' : Array<bigint>,
+  'approved_at' : [] | [bigint],
+  'to_accepted_by' : [] | [Principal],
+}
+export interface AgencyTransferAuditEntry {
+  'to' : bigint,
+  'transfer_id' : bigint,
+  'from' : bigint,
+  'real_estates' : Array<bigint>,
+  'timestamp' : bigint,
+  'contracts' : Array<bigint>,
+}
+export type AgencyTransferStatus = { 'Rejected' : null } |
+  { 'Completed' : null } |
+  { 'Pending' : null };
+export interface AgencyUsage {
+  'storage_cycles_per_day' : bigint,
+  'usage' : StorageUsage,
+  'real_estates' : bigint,
+  'agency' : bigint,
+  'quota' : [] | [AgencyQuota],
+  'contracts' : bigint,
+}
+export type AgencyStatus = { 'Active' : null } |
+  { 'Suspended' : null };
 export type Result_4 = { 'Ok' : bigint } |
   { 'Err' : DeferredDataError };
+export type Result_5 = { 'Ok' : AgencyReport } |
+  { 'Err' : DeferredDataError };
+export type Result_6 = { 'Ok' : AgencyTransferAuditEntry } |
+  { 'Err' : DeferredDataError };
+export type Result_7 = { 'Ok' : Logs } |
+  { 'Err' : DeferredDataError };
+export type Result_8 = { 'Ok' : string } |
+  { 'Err' : string };
+export type Result_9 = { 'Ok' : Array<AuditEntry> } |
+  { 'Err' : DeferredDataError };
+export type Result_10 = { 'Ok' : AuditIntegrity } |
+  { 'Err' : DeferredDataError };
+export type Result_11 = { 'Ok' : MigrationStatus } |
+  { 'Err' : DeferredDataError };
+export type Result_12 = { 'Ok' : ExportChunk } |
+  { 'Err' : DeferredDataError };
+export type Result_13 = { 'Ok' : CollectionDigest } |
+  { 'Err' : DeferredDataError };
+export type Result_14 = { 'Ok' : boolean } |
+  { 'Err' : DeferredDataError };
+export type Result_15 = { 'Ok' : DocumentHistory } |
+  { 'Err' : DeferredDataError };
+export type Result_16 = { 'Ok' : Array<DocumentLeaf> } |
+  { 'Err' : DeferredDataError };
+export type Result_17 = { 'Ok' : DocumentProof } |
+  { 'Err' : DeferredDataError };
+export type Result_18 = { 'Ok' : AgencyUsage } |
+  { 'Err' : DeferredDataError };
 export interface Seller { 'quota' : number, 'address' : string }
+export type SignedMessageError = { 'NonceAlreadyUsed' : string } |
+  { 'NotYetValid' : null } |
+  { 'Expired' : null } |
+  { 'Malformed' : string } |
+  { 'DomainMismatch' : string } |
+  { 'ContractMismatch' : bigint } |
+  { 'SignerMismatch' : string };
+export interface StorageUsage {
+  'documents' : bigint,
+  'document_bytes' : bigint,
+}
+export interface StreamingCallbackHttpResponse {
+  'token' : [] | [StreamingCallbackToken],
+  'body' : Uint8Array | number[],
+}
+export interface StreamingCallbackToken {
+  'end' : bigint,
+  'url' : string,
+  'offset' : bigint,
+}
+export type StreamingStrategy = {
+    'Callback' : {
+      'token' : StreamingCallbackToken,
+      'callback' : [Principal, string],
+    }
+  };
 export interface _SERVICE {
-  'admin_cycles' : ActorMethod<[], bigint>,
-  'admin_ic_logs' : ActorMethod<[Pagination], Logs>,
+  'admin_audit_log' : ActorMethod<[AuditFilter, Pagination], Result_9>,
+  'admin_cycles' : ActorMethod<[], Result_3>,
+  'admin_export' : ActorMethod<
+    [ExportCollection, [] | [ExportCursor]],
+    Result_12
+  >,
+  'admin_export_digest' : ActorMethod<
+    [ExportCollection, [] | [Uint8Array | number[]]],
+    Result_13
+  >,
+  'admin_ic_logs' : ActorMethod<[Pagination], Result_7>,
+  'admin_import' : ActorMethod<[ExportChunk], Result>,
+  'admin_migration_status' : ActorMethod<[], Result_11>,
+  'admin_set_agency_quota' : ActorMethod<
+    [bigint, [] | [AgencyQuota]],
+    Result
+  >,
+  'admin_set_allowed_mime_types' : ActorMethod<[Array<string>], Result>,
+  'admin_set_custom_domains' : ActorMethod<[Array<string>], Result>,
   'admin_set_minter' : ActorMethod<[Principal], Result>,
+  'admin_set_read_only' : ActorMethod<[[] | [string]], Result>,
+  'admin_set_sns_governance' : ActorMethod<[[] | [Principal]], Result>,
+  'admin_verify_audit_log' : ActorMethod<[Pagination], Result_10>,
+  'admin_verify_import' : ActorMethod<[CollectionDigest], Result_14>,
+  'begin_contract_document_upload' : ActorMethod<
+    [bigint, ContractDocument, string],
+    Result_4
+  >,
+  'delete_contract_document' : ActorMethod<[bigint, bigint, boolean], Result>,
+  'finalize_contract_document_upload' : ActorMethod<[bigint], Result_4>,
   'get_contract' : ActorMethod<[bigint], [] | [Contract]>,
   'get_contract_document' : ActorMethod<[bigint, bigint], Result_1>,
+  'get_contract_document_history' : ActorMethod<[bigint, bigint], Result_15>,
+  'get_contract_document_version' : ActorMethod<
+    [bigint, bigint, bigint],
+    Result_1
+  >,
+  'get_agency_transfers_audit' : ActorMethod<
+    [bigint],
+    Array<AgencyTransferAuditEntry>
+  >,
+  'get_agency_usage' : ActorMethod<[bigint], Result_18>,
+  'get_allowed_mime_types' : ActorMethod<[], Array<string>>,
   'get_contracts' : ActorMethod<[], Array<bigint>>,
+  'get_custom_domains' : ActorMethod<[], Array<string>>,
+  'get_read_only_mode' : ActorMethod<[], [] | [ReadOnlyMode]>,
   'get_real_estate' : ActorMethod<[bigint], Result_2>,
+  'get_sns_governance' : ActorMethod<[], [] | [Principal]>,
   'http_request' : ActorMethod<[HttpRequest], HttpResponse>,
+  'http_request_streaming_callback' : ActorMethod<
+    [StreamingCallbackToken],
+    StreamingCallbackHttpResponse
+  >,
+  'http_request_update' : ActorMethod<[HttpRequest], HttpResponse>,
   'minter_close_contract' : ActorMethod<[bigint], Result>,
   'minter_create_contract' : ActorMethod<[Contract], Result>,
   'minter_create_real_estate' : ActorMethod<[RealEstate], Result_3>,
   'minter_delete_real_estate' : ActorMethod<[bigint], Result>,
+  'minter_get_contract_document_leaves' : ActorMethod<[bigint], Result_16>,
+  'minter_remove_agency' : ActorMethod<[bigint], Result_5>,
+  'minter_set_agency_member' : ActorMethod<
+    [Principal, [] | [bigint]],
+    Result
+  >,
+  'minter_set_agency_quota' : ActorMethod<
+    [bigint, [] | [AgencyQuota]],
+    Result
+  >,
+  'minter_set_agency_status' : ActorMethod<
+    [bigint, AgencyStatus],
+    Result_5
+  >,
+  'minter_set_documents_anchor' : ActorMethod<[DocumentsAnchor], Result>,
+  'minter_transfer_agency_records' : ActorMethod<[AgencyTransfer], Result_6>,
   'minter_update_real_estate' : ActorMethod<[bigint, RealEstate], Result>,
+  'replace_contract_document' : ActorMethod<
+    [bigint, bigint, ContractDocument, Uint8Array | number[]],
+    Result_4
+  >,
+  'update_contract_document' : ActorMethod<
+    [bigint, bigint, ContractDocument],
+    Result_4
+  >,
   'update_contract_property' : ActorMethod<
     [bigint, string, GenericValue],
     Result
@@ -174,6 +493,29 @@ export interface _SERVICE {
     [bigint, ContractDocument, Uint8Array | number[]],
     Result_4
   >,
+  'upload_contract_document_chunk' : ActorMethod<
+    [bigint, Uint8Array | number[]],
+    Result
+  >,
+  'validate_admin_set_agency_quota' : ActorMethod<
+    [bigint, [] | [AgencyQuota]],
+    Result_8
+  >,
+  'validate_admin_set_allowed_mime_types' : ActorMethod<
+    [Array<string>],
+    Result_8
+  >,
+  'validate_admin_set_custom_domains' : ActorMethod<
+    [Array<string>],
+    Result_8
+  >,
+  'validate_admin_set_minter' : ActorMethod<[Principal], Result_8>,
+  'validate_admin_set_read_only' : ActorMethod<[[] | [string]], Result_8>,
+  'validate_admin_set_sns_governance' : ActorMethod<
+    [[] | [Principal]],
+    Result_8
+  >,
+  'verify_contract_document' : ActorMethod<[bigint, bigint], Result_17>,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
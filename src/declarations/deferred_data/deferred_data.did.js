@@ -17,15 +17,39 @@ export const idlFactory = ({ IDL }) => {
   });
   const ConfigurationError = IDL.Variant({
     'AnonymousOwner' : IDL.Null,
+    'AnonymousSnsGovernance' : IDL.Null,
+    'InvalidMimeType' : IDL.Text,
     'AnonymousMinter' : IDL.Null,
+    'InvalidDomain' : IDL.Text,
+  });
+  const AgencyQuota = IDL.Record({
+    'max_documents' : IDL.Nat64,
+    'max_document_bytes' : IDL.Nat64,
   });
   const ContractError = IDL.Variant({
     'DocumentNotFound' : IDL.Nat64,
     'ContractNotFound' : IDL.Nat,
     'DocumentSizeMismatch' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'DocumentHashMismatch' : IDL.Tuple(IDL.Text, IDL.Text),
+    'InvalidDocumentHash' : IDL.Text,
+    'UploadNotFound' : IDL.Nat64,
+    'DocumentVersionNotFound' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'DocumentPurged' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'DocumentNotAnchored' : IDL.Nat64,
+    'DocumentsRootMismatch' : IDL.Tuple(IDL.Text, IDL.Text),
+    'AgencyHasOpenContracts' : IDL.Vec(IDL.Nat),
+    'NotOwnedByAgency' : IDL.Nat,
+    'AgencyQuotaExceeded' : IDL.Tuple(IDL.Nat64, AgencyQuota),
+    'DocumentPreviewNotFound' : IDL.Nat64,
+    'MimeTypeNotAllowed' : IDL.Text,
+    'MimeTypeMismatch' : IDL.Tuple(IDL.Text, IDL.Text),
+    'AgencyNotFound' : IDL.Nat64,
     'BadContractProperty' : IDL.Null,
   });
-  const RealEstateError = IDL.Variant({ 'NotFound' : IDL.Nat });
+  const RealEstateError = IDL.Variant({
+    'NotOwnedByAgency' : IDL.Nat,
+    'NotFound' : IDL.Nat,
+  });
   const RejectionCode = IDL.Variant({
     'NoError' : IDL.Null,
     'CanisterError' : IDL.Null,
@@ -35,11 +59,54 @@ export const idlFactory = ({ IDL }) => {
     'SysFatal' : IDL.Null,
     'CanisterReject' : IDL.Null,
   });
+  const Permission = IDL.Variant({ 'Minter' : IDL.Null, 'Owner' : IDL.Null });
+  const ExportCollection = IDL.Variant({
+    'RealEstates' : IDL.Null,
+    'NextDocumentId' : IDL.Null,
+    'DocumentHistory' : IDL.Null,
+    'DocumentAnchors' : IDL.Null,
+    'DocumentHashes' : IDL.Null,
+    'DocumentContents' : IDL.Null,
+    'Documents' : IDL.Null,
+    'DocumentReferences' : IDL.Null,
+    'AgencyQuotas' : IDL.Null,
+    'AgencyUsage' : IDL.Null,
+    'ContractUsage' : IDL.Null,
+    'DocumentPreviews' : IDL.Null,
+    'DocumentContentChunks' : IDL.Null,
+    'Contracts' : IDL.Null,
+    'AgencyMembers' : IDL.Null,
+    'AgenciesStatus' : IDL.Null,
+    'AgencyTransfersAudit' : IDL.Null,
+    'ConsumedNonces' : IDL.Null,
+    'SchemaVersions' : IDL.Null,
+    'AllowedMimeTypes' : IDL.Null,
+    'CustomDomains' : IDL.Null,
+  });
+  const ImportError = IDL.Variant({
+    'InvalidRecord' : ExportCollection,
+    'CollectionNotEmpty' : ExportCollection,
+    'InvalidChecksum' : IDL.Null,
+    'UnexpectedChunk' : ExportCollection,
+  });
+  const SignedMessageError = IDL.Variant({
+    'NonceAlreadyUsed' : IDL.Text,
+    'NotYetValid' : IDL.Null,
+    'Expired' : IDL.Null,
+    'Malformed' : IDL.Text,
+    'DomainMismatch' : IDL.Text,
+    'ContractMismatch' : IDL.Nat,
+    'SignerMismatch' : IDL.Text,
+  });
   const DeferredDataError = IDL.Variant({
     'Configuration' : ConfigurationError,
     'Contract' : ContractError,
     'RealEstate' : RealEstateError,
+    'MissingPermission' : Permission,
     'InvalidSignature' : IDL.Null,
+    'ReadOnly' : IDL.Null,
+    'Import' : ImportError,
+    'SignedMessage' : SignedMessageError,
     'Unauthorized' : IDL.Null,
     'StorageError' : IDL.Null,
     'CanisterCall' : IDL.Tuple(RejectionCode, IDL.Text),
@@ -88,7 +155,7 @@ export const idlFactory = ({ IDL }) => {
     'documents' : IDL.Vec(IDL.Tuple(IDL.Nat64, ContractDocument)),
     'value' : IDL.Nat64,
     'type' : ContractType,
-    'agency' : IDL.Principal,
+    'agency' : IDL.Nat64,
     'restricted_properties' : IDL.Vec(IDL.Tuple(IDL.Text, RestrictedProperty)),
     'properties' : IDL.Vec(IDL.Tuple(IDL.Text, GenericValue)),
     'deposit' : IDL.Nat64,
@@ -117,6 +184,11 @@ export const idlFactory = ({ IDL }) => {
     'Oceania' : IDL.Null,
     'NorthAmerica' : IDL.Null,
   });
+  const ReadOnlyMode = IDL.Record({
+    'enabled_at' : IDL.Nat64,
+    'enabled_by' : IDL.Principal,
+    'reason' : IDL.Text,
+  });
   const RealEstate = IDL.Record({
     'region' : IDL.Opt(IDL.Text),
     'latitude' : IDL.Opt(IDL.Float64),
@@ -133,7 +205,7 @@ export const idlFactory = ({ IDL }) => {
     'zone' : IDL.Opt(IDL.Text),
     'garage' : IDL.Opt(IDL.Bool),
     'garden' : IDL.Opt(IDL.Bool),
-    'agency' : IDL.Principal,
+    'agency' : IDL.Nat64,
     'continent' : IDL.Opt(Continent),
     'description' : IDL.Text,
     'longitude' : IDL.Opt(IDL.Float64),
@@ -157,32 +229,359 @@ export const idlFactory = ({ IDL }) => {
     'body' : IDL.Vec(IDL.Nat8),
     'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
   });
+  const StreamingCallbackToken = IDL.Record({
+    'end' : IDL.Nat64,
+    'url' : IDL.Text,
+    'offset' : IDL.Nat64,
+  });
+  const StreamingCallbackHttpResponse = IDL.Record({
+    'token' : IDL.Opt(StreamingCallbackToken),
+    'body' : IDL.Vec(IDL.Nat8),
+  });
+  const StreamingStrategy = IDL.Variant({
+    'Callback' : IDL.Record({
+      'token' : StreamingCallbackToken,
+      'callback' : IDL.Func(
+          [StreamingCallbackToken],
+          [StreamingCallbackHttpResponse],
+          ['query'],
+        ),
+    }),
+  });
   const HttpResponse = IDL.Record({
     'body' : IDL.Vec(IDL.Nat8),
     'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
     'upgrade' : IDL.Opt(IDL.Bool),
+    'streaming_strategy' : IDL.Opt(StreamingStrategy),
     'status_code' : IDL.Nat16,
   });
   const Result_3 = IDL.Variant({ 'Ok' : IDL.Nat, 'Err' : DeferredDataError });
   const Result_4 = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : DeferredDataError });
+  const AgencyReport = IDL.Record({
+    'real_estates' : IDL.Vec(IDL.Nat),
+    'open_contracts' : IDL.Vec(IDL.Nat),
+    'agency' : IDL.Nat64,
+  });
+  const Result_5 = IDL.Variant({
+    'Ok' : AgencyReport,
+    'Err' : DeferredDataError,
+  });
+  const AgencyStatus = IDL.Variant({
+    'Active' : IDL.Null,
+    'Suspended' : IDL.Null,
+  });
+  const AgencyTransferAuditEntry = IDL.Record({
+    'to' : IDL.Nat64,
+    'transfer_id' : IDL.Nat64,
+    'from' : IDL.Nat64,
+    'real_estates' : IDL.Vec(IDL.Nat),
+    'timestamp' : IDL.Nat64,
+    'contracts' : IDL.Vec(IDL.Nat),
+  });
+  const AgencyTransferStatus = IDL.Variant({
+    'Rejected' : IDL.Null,
+    'Completed' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const AgencyTransfer = IDL.Record({
+    'id' : IDL.Nat64,
+    'to' : IDL.Nat64,
+    'status' : AgencyTransferStatus,
+    'from' : IDL.Nat64,
+    'requested_by' : IDL.Principal,
+    'approved_by' : IDL.Opt(IDL.Principal),
+    'real_estates' : IDL.Vec(IDL.Nat),
+    'from_accepted_by' : IDL.Opt(IDL.Principal),
+    'requested_at' : IDL.Nat64,
+    'contracts' : IDL.Vec(IDL.Nat),
+    'approved_at' : IDL.Opt(IDL.Nat64),
+    'to_accepted_by' : IDL.Opt(IDL.Principal),
+  });
+  const Result_6 = IDL.Variant({
+    'Ok' : AgencyTransferAuditEntry,
+    'Err' : DeferredDataError,
+  });
+  const Result_7 = IDL.Variant({ 'Ok' : Logs, 'Err' : DeferredDataError });
+  const Result_8 = IDL.Variant({ 'Ok' : IDL.Text, 'Err' : IDL.Text });
+  const AuditResult = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const AuditEntry = IDL.Record({
+    'id' : IDL.Nat64,
+    'method' : IDL.Text,
+    'args' : IDL.Text,
+    'hash' : IDL.Text,
+    'result' : AuditResult,
+    'timestamp' : IDL.Nat64,
+    'caller' : IDL.Principal,
+    'prev_hash' : IDL.Text,
+  });
+  const AuditFilter = IDL.Record({
+    'to' : IDL.Opt(IDL.Nat64),
+    'method' : IDL.Opt(IDL.Text),
+    'from' : IDL.Opt(IDL.Nat64),
+    'caller' : IDL.Opt(IDL.Principal),
+  });
+  const AuditIntegrity = IDL.Record({
+    'head' : IDL.Text,
+    'entries' : IDL.Nat64,
+    'first_invalid_entry' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_9 = IDL.Variant({
+    'Ok' : IDL.Vec(AuditEntry),
+    'Err' : DeferredDataError,
+  });
+  const Result_10 = IDL.Variant({
+    'Ok' : AuditIntegrity,
+    'Err' : DeferredDataError,
+  });
+  const MigrationProgress = IDL.Record({
+    'to_version' : IDL.Nat32,
+    'total' : IDL.Nat64,
+    'collection' : IDL.Text,
+    'started_at' : IDL.Nat64,
+    'cursor' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'from_version' : IDL.Nat32,
+    'migrated' : IDL.Nat64,
+  });
+  const CollectionSchema = IDL.Record({
+    'collection' : IDL.Text,
+    'version' : IDL.Nat32,
+    'target_version' : IDL.Nat32,
+  });
+  const MigrationStatus = IDL.Record({
+    'pending' : IDL.Vec(MigrationProgress),
+    'collections' : IDL.Vec(CollectionSchema),
+  });
+  const Result_11 = IDL.Variant({
+    'Ok' : MigrationStatus,
+    'Err' : DeferredDataError,
+  });
+  const ExportRecord = IDL.Record({
+    'key' : IDL.Vec(IDL.Nat8),
+    'value' : IDL.Vec(IDL.Nat8),
+    'size' : IDL.Nat64,
+    'offset' : IDL.Nat64,
+  });
+  const ExportCursor = IDL.Record({
+    'key' : IDL.Vec(IDL.Nat8),
+    'offset' : IDL.Opt(IDL.Nat64),
+  });
+  const ExportChunk = IDL.Record({
+    'last' : IDL.Bool,
+    'records' : IDL.Vec(ExportRecord),
+    'collection' : ExportCollection,
+    'after' : IDL.Opt(ExportCursor),
+    'checksum' : IDL.Text,
+  });
+  const Result_12 = IDL.Variant({
+    'Ok' : ExportChunk,
+    'Err' : DeferredDataError,
+  });
+  const CollectionDigest = IDL.Record({
+    'next' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'records' : IDL.Nat64,
+    'hash' : IDL.Text,
+    'after' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'collection' : ExportCollection,
+  });
+  const Result_13 = IDL.Variant({
+    'Ok' : CollectionDigest,
+    'Err' : DeferredDataError,
+  });
+  const Result_14 = IDL.Variant({ 'Ok' : IDL.Bool, 'Err' : DeferredDataError });
+  const DocumentVersion = IDL.Record({
+    'created_at' : IDL.Nat64,
+    'created_by' : IDL.Principal,
+    'data_id' : IDL.Nat64,
+    'document' : ContractDocument,
+    'version' : IDL.Nat64,
+    'purged' : IDL.Bool,
+  });
+  const DocumentHistory = IDL.Record({
+    'deleted_at' : IDL.Opt(IDL.Nat64),
+    'versions' : IDL.Vec(DocumentVersion),
+    'contract_id' : IDL.Nat,
+  });
+  const Result_15 = IDL.Variant({
+    'Ok' : DocumentHistory,
+    'Err' : DeferredDataError,
+  });
+  const DocumentLeaf = IDL.Record({
+    'sha256' : IDL.Text,
+    'document_id' : IDL.Nat64,
+  });
+  const Result_16 = IDL.Variant({
+    'Ok' : IDL.Vec(DocumentLeaf),
+    'Err' : DeferredDataError,
+  });
+  const DocumentsAnchor = IDL.Record({
+    'root' : IDL.Text,
+    'transaction_hash' : IDL.Text,
+    'chain_id' : IDL.Nat64,
+    'contract_id' : IDL.Nat,
+    'leaves' : IDL.Vec(DocumentLeaf),
+    'anchored_at' : IDL.Nat64,
+  });
+  const DocumentProof = IDL.Record({
+    'sha256' : IDL.Text,
+    'root' : IDL.Text,
+    'transaction_hash' : IDL.Text,
+    'chain_id' : IDL.Nat64,
+    'proof' : IDL.Vec(IDL.Text),
+    'document_id' : IDL.Nat64,
+    'anchored_at' : IDL.Nat64,
+  });
+  const Result_17 = IDL.Variant({
+    'Ok' : DocumentProof,
+    'Err' : DeferredDataError,
+  });
+  const StorageUsage = IDL.Record({
+    'documents' : IDL.Nat64,
+    'document_bytes' : IDL.Nat64,
+  });
+  const AgencyUsage = IDL.Record({
+    'storage_cycles_per_day' : IDL.Nat64,
+    'usage' : StorageUsage,
+    'real_estates' : IDL.Nat64,
+    'agency' : IDL.Nat64,
+    'quota' : IDL.Opt(AgencyQuota),
+    'contracts' : IDL.Nat64,
+  });
+  const Result_18 = IDL.Variant({
+    'Ok' : AgencyUsage,
+    'Err' : DeferredDataError,
+  });
   return IDL.Service({
-    'admin_cycles' : IDL.Func([], [IDL.Nat], ['query']),
-    'admin_ic_logs' : IDL.Func([Pagination], [Logs], ['query']),
+    'admin_audit_log' : IDL.Func(
+        [AuditFilter, Pagination],
+        [Result_9],
+        ['query'],
+      ),
+    'admin_cycles' : IDL.Func([], [Result_3], ['query']),
+    'admin_export' : IDL.Func(
+        [ExportCollection, IDL.Opt(ExportCursor)],
+        [Result_12],
+        ['query'],
+      ),
+    'admin_export_digest' : IDL.Func(
+        [ExportCollection, IDL.Opt(IDL.Vec(IDL.Nat8))],
+        [Result_13],
+        ['query'],
+      ),
+    'admin_ic_logs' : IDL.Func([Pagination], [Result_7], ['query']),
+    'admin_import' : IDL.Func([ExportChunk], [Result], []),
+    'admin_migration_status' : IDL.Func([], [Result_11], ['query']),
+    'admin_set_agency_quota' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(AgencyQuota)],
+        [Result],
+        [],
+      ),
+    'admin_set_allowed_mime_types' : IDL.Func(
+        [IDL.Vec(IDL.Text)],
+        [Result],
+        [],
+      ),
+    'admin_set_custom_domains' : IDL.Func([IDL.Vec(IDL.Text)], [Result], []),
     'admin_set_minter' : IDL.Func([IDL.Principal], [Result], []),
+    'admin_set_read_only' : IDL.Func([IDL.Opt(IDL.Text)], [Result], []),
+    'admin_set_sns_governance' : IDL.Func(
+        [IDL.Opt(IDL.Principal)],
+        [Result],
+        [],
+      ),
+    'admin_verify_audit_log' : IDL.Func([Pagination], [Result_10], ['query']),
+    'admin_verify_import' : IDL.Func(
+        [CollectionDigest],
+        [Result_14],
+        ['query'],
+      ),
+    'begin_contract_document_upload' : IDL.Func(
+        [IDL.Nat, ContractDocument, IDL.Text],
+        [Result_4],
+        [],
+      ),
+    'delete_contract_document' : IDL.Func(
+        [IDL.Nat, IDL.Nat64, IDL.Bool],
+        [Result],
+        [],
+      ),
+    'finalize_contract_document_upload' : IDL.Func([IDL.Nat64], [Result_4], []),
     'get_contract' : IDL.Func([IDL.Nat], [IDL.Opt(Contract)], ['query']),
     'get_contract_document' : IDL.Func(
         [IDL.Nat, IDL.Nat64],
         [Result_1],
         ['query'],
       ),
+    'get_contract_document_history' : IDL.Func(
+        [IDL.Nat, IDL.Nat64],
+        [Result_15],
+        ['query'],
+      ),
+    'get_contract_document_version' : IDL.Func(
+        [IDL.Nat, IDL.Nat64, IDL.Nat64],
+        [Result_1],
+        ['query'],
+      ),
+    'get_agency_transfers_audit' : IDL.Func(
+        [IDL.Nat64],
+        [IDL.Vec(AgencyTransferAuditEntry)],
+        ['query'],
+      ),
+    'get_agency_usage' : IDL.Func([IDL.Nat64], [Result_18], ['query']),
+    'get_allowed_mime_types' : IDL.Func([], [IDL.Vec(IDL.Text)], ['query']),
     'get_contracts' : IDL.Func([], [IDL.Vec(IDL.Nat)], ['query']),
+    'get_custom_domains' : IDL.Func([], [IDL.Vec(IDL.Text)], ['query']),
+    'get_read_only_mode' : IDL.Func([], [IDL.Opt(ReadOnlyMode)], ['query']),
     'get_real_estate' : IDL.Func([IDL.Nat], [Result_2], ['query']),
+    'get_sns_governance' : IDL.Func([], [IDL.Opt(IDL.Principal)], ['query']),
     'http_request' : IDL.Func([HttpRequest], [HttpResponse], ['query']),
+    'http_request_streaming_callback' : IDL.Func(
+        [StreamingCallbackToken],
+        [StreamingCallbackHttpResponse],
+        ['query'],
+      ),
+    'http_request_update' : IDL.Func([HttpRequest], [HttpResponse], []),
     'minter_close_contract' : IDL.Func([IDL.Nat], [Result], []),
     'minter_create_contract' : IDL.Func([Contract], [Result], []),
     'minter_create_real_estate' : IDL.Func([RealEstate], [Result_3], []),
     'minter_delete_real_estate' : IDL.Func([IDL.Nat], [Result], []),
+    'minter_get_contract_document_leaves' : IDL.Func(
+        [IDL.Nat],
+        [Result_16],
+        ['query'],
+      ),
+    'minter_remove_agency' : IDL.Func([IDL.Nat64], [Result_5], []),
+    'minter_set_agency_member' : IDL.Func(
+        [IDL.Principal, IDL.Opt(IDL.Nat64)],
+        [Result],
+        [],
+      ),
+    'minter_set_agency_quota' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(AgencyQuota)],
+        [Result],
+        [],
+      ),
+    'minter_set_agency_status' : IDL.Func(
+        [IDL.Nat64, AgencyStatus],
+        [Result_5],
+        [],
+      ),
+    'minter_set_documents_anchor' : IDL.Func([DocumentsAnchor], [Result], []),
+    'minter_transfer_agency_records' : IDL.Func(
+        [AgencyTransfer],
+        [Result_6],
+        [],
+      ),
     'minter_update_real_estate' : IDL.Func([IDL.Nat, RealEstate], [Result], []),
+    'replace_contract_document' : IDL.Func(
+        [IDL.Nat, IDL.Nat64, ContractDocument, IDL.Vec(IDL.Nat8)],
+        [Result_4],
+        [],
+      ),
+    'update_contract_document' : IDL.Func(
+        [IDL.Nat, IDL.Nat64, ContractDocument],
+        [Result_4],
+        [],
+      ),
     'update_contract_property' : IDL.Func(
         [IDL.Nat, IDL.Text, GenericValue],
         [Result],
@@ -198,6 +597,46 @@ export const idlFactory = ({ IDL }) => {
         [Result_4],
         [],
       ),
+    'upload_contract_document_chunk' : IDL.Func(
+        [IDL.Nat64, IDL.Vec(IDL.Nat8)],
+        [Result],
+        [],
+      ),
+    'validate_admin_set_agency_quota' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(AgencyQuota)],
+        [Result_8],
+        ['query'],
+      ),
+    'validate_admin_set_allowed_mime_types' : IDL.Func(
+        [IDL.Vec(IDL.Text)],
+        [Result_8],
+        ['query'],
+      ),
+    'validate_admin_set_custom_domains' : IDL.Func(
+        [IDL.Vec(IDL.Text)],
+        [Result_8],
+        ['query'],
+      ),
+    'validate_admin_set_minter' : IDL.Func(
+        [IDL.Principal],
+        [Result_8],
+        ['query'],
+      ),
+    'validate_admin_set_read_only' : IDL.Func(
+        [IDL.Opt(IDL.Text)],
+        [Result_8],
+        ['query'],
+      ),
+    'validate_admin_set_sns_governance' : IDL.Func(
+        [IDL.Opt(IDL.Principal)],
+        [Result_8],
+        ['query'],
+      ),
+    'verify_contract_document' : IDL.Func(
+        [IDL.Nat, IDL.Nat64],
+        [Result_17],
+        ['query'],
+      ),
   });
 };
 export const init = ({ IDL }) => {
@@ -3,6 +3,7 @@ import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
 export interface Agency {
+  'id' : bigint,
   'lat' : [] | [string],
   'lng' : [] | [string],
   'vat' : string,
@@ -20,12 +21,195 @@ export interface Agency {
   'address' : string,
   'mobile' : string,
 }
+export interface AgencyApplication {
+  'id' : bigint,
+  'status' : AgencyApplicationStatus,
+  'documents' : Array<AgencyApplicationDocumentInfo>,
+  'agency' : Agency,
+  'submitted_at' : bigint,
+  'reviewer' : [] | [Principal],
+  'comments' : Array<AgencyApplicationComment>,
+  'applicant' : Principal,
+  'reviewed_at' : [] | [bigint],
+}
+export interface AgencyApplicationComment {
+  'author' : Principal,
+  'comment' : string,
+  'timestamp' : bigint,
+}
+export interface AgencyApplicationDocument {
+  'data' : Uint8Array | number[],
+  'name' : string,
+  'mime_type' : string,
+}
+export interface AgencyApplicationDocumentInfo {
+  'name' : string,
+  'size' : bigint,
+  'mime_type' : string,
+}
+export type AgencyApplicationStatus = { 'Approved' : null } |
+  { 'Rejected' : null } |
+  { 'Expired' : null } |
+  { 'Pending' : null };
+export interface AgencyChange {
+  'changes' : Array<AgencyFieldChange>,
+  'author' : Principal,
+  'timestamp' : bigint,
+}
+export type AgencyError = { 'InvalidLatitude' : string } |
+  { 'NotAMember' : Principal } |
+  { 'NotAnAdmin' : Principal } |
+  { 'NotTheOwner' : Principal } |
+  { 'CannotOperateAgency' : [Principal, bigint] } |
+  { 'InvalidUrl' : string } |
+  { 'LastAdmin' : null } |
+  { 'InvalidEmail' : string } |
+  { 'AgencyNotFound' : bigint } |
+  { 'AlreadyMember' : Principal } |
+  { 'AgencySuspended' : bigint } |
+  { 'AgencyNotSuspended' : bigint } |
+  { 'TransferNotFound' : bigint } |
+  { 'TransferNotPending' : bigint } |
+  { 'TransferNotAccepted' : bigint } |
+  { 'InvalidTransfer' : string } |
+  { 'NotPartOfTransfer' : [bigint, bigint] } |
+  { 'ApplicationNotFound' : bigint } |
+  { 'ApplicationAlreadyPending' : Principal } |
+  { 'AgencyAlreadyRegistered' : Principal } |
+  { 'NotTheApplicant' : Principal } |
+  { 'ApplicationAlreadyReviewed' : bigint } |
+  { 'ApplicationDocumentsTooLarge' : bigint } |
+  { 'ApplicationDocumentNotFound' : [bigint, bigint] } |
+  { 'TooManyPendingApplications' : null } |
+  { 'TooManyApplications' : Principal } |
+  { 'InvalidLongitude' : string } |
+  { 'EmptyField' : string };
+export interface AgencyFieldChange {
+  'field' : string,
+  'previous' : string,
+  'current' : string,
+}
+export interface AgencyMember {
+  'principal' : Principal,
+  'role' : AgencyRole,
+  'agency' : bigint,
+}
+export interface AgencyQuota {
+  'max_documents' : bigint,
+  'max_document_bytes' : bigint,
+}
+export interface AgencyReport {
+  'real_estates' : Array<bigint>,
+  'open_contracts' : Array<bigint>,
+  'agency' : bigint,
+}
+export type AgencyRole = { 'Admin' : null } |
+  { 'ReadOnly' : null } |
+  { 'Agent' : null };
+export type AgencyStatus = { 'Active' : null } |
+  { 'Suspended' : null };
+export interface AgencyTransfer {
+  'id' : bigint,
+  'to' : bigint,
+  'status' : AgencyTransferStatus,
+  'from' : bigint,
+  'requested_by' : Principal,
+  'approved_by' : [] | [Principal],
+  'real_estates' : Array<bigint>,
+  'from_accepted_by' : [] | [Principal],
+  'requested_at' : bigint,
+  'contracts' : Array<bigint>,
+  'approved_at' : [] | [bigint],
+  'to_accepted_by' : [] | [Principal],
+}
+export interface AgencyTransferAuditEntry {
+  'to' : bigint,
+  'transfer_id' : bigint,
+  'from' : bigint,
+  'real_estates' : Array<bigint>,
+  'timestamp' : bigint,
+  'contracts' : Array<bigint>,
+}
+export type AgencyTransferStatus = { 'Rejected' : null } |
+  { 'Completed' : null } |
+  { 'Pending' : null };
+export interface AgencyUpdate {
+  'lat' : [] | [string],
+  'lng' : [] | [string],
+  'region' : [] | [string],
+  'zip_code' : [] | [string],
+  'country' : [] | [string],
+  'agent' : [] | [string],
+  'city' : [] | [string],
+  'logo' : [] | [string],
+  'name' : [] | [string],
+  'continent' : [] | [Continent],
+  'email' : [] | [string],
+  'website' : [] | [string],
+  'address' : [] | [string],
+  'mobile' : [] | [string],
+}
+export interface AuditEntry {
+  'id' : bigint,
+  'method' : string,
+  'args' : string,
+  'hash' : string,
+  'result' : AuditResult,
+  'timestamp' : bigint,
+  'caller' : Principal,
+  'prev_hash' : string,
+}
+export interface AuditFilter {
+  'to' : [] | [bigint],
+  'method' : [] | [string],
+  'from' : [] | [bigint],
+  'caller' : [] | [Principal],
+}
+export interface AuditIntegrity {
+  'head' : string,
+  'entries' : bigint,
+  'first_invalid_entry' : [] | [bigint],
+}
+export type AuditResult = { 'Ok' : null } |
+  { 'Err' : string };
 export type CloseContractError = { 'ContractNotFound' : bigint } |
   { 'ContractNotExpired' : bigint };
-export type ConfigurationError = { 'CustodialsCantBeEmpty' : null } |
-  { 'AnonymousCustodial' : null };
+export interface CollectionSchema {
+  'collection' : string,
+  'version' : number,
+  'target_version' : number,
+}
+export interface ConfigurationChange {
+  'old_value' : string,
+  'field' : string,
+  'author' : Principal,
+  'new_value' : string,
+  'timestamp' : bigint,
+}
+export type ConfigurationError = { 'InvalidRoleName' : string } |
+  { 'CustodialsCantBeEmpty' : null } |
+  { 'UnsupportedChainId' : bigint } |
+  { 'UnknownRole' : string } |
+  { 'AnonymousPrincipal' : string } |
+  { 'AnonymousCustodial' : null } |
+  { 'InvalidEvmRpcApi' : string } |
+  { 'ZeroAddress' : string } |
+  { 'InvalidRewardParameters' : string };
 export type ConfigurationError_1 = { 'AnonymousOwner' : null } |
+  { 'AnonymousSnsGovernance' : null } |
+  { 'InvalidMimeType' : string } |
   { 'AnonymousMinter' : null };
+export interface ConfigurationUpdate {
+  'deferred_erc721' : [] | [string],
+  'evm_rpc_api' : [] | [string],
+  'deferred_data' : [] | [Principal],
+  'reward_pool_deploy_block' : [] | [bigint],
+  'reward_pool' : [] | [string],
+  'chain_id' : [] | [bigint],
+  'evm_rpc' : [] | [Principal],
+  'ecdsa_key' : [] | [EcdsaKey],
+  'log_settings' : [] | [LogSettingsV2],
+}
 export type Continent = { 'Africa' : null } |
   { 'Antarctica' : null } |
   { 'Asia' : null } |
@@ -45,10 +229,24 @@ export type ContractError = { 'CurrencyNotAllowed' : string } |
   { 'BadContractExpiration' : null } |
   { 'ContractHasNoTokens' : null } |
   { 'BadRealEstateId' : null } |
+  { 'ContractHasNoDocuments' : bigint } |
   { 'BadContractProperty' : null };
 export type ContractError_1 = { 'DocumentNotFound' : bigint } |
   { 'ContractNotFound' : bigint } |
   { 'DocumentSizeMismatch' : [bigint, bigint] } |
+  { 'DocumentHashMismatch' : [string, string] } |
+  { 'InvalidDocumentHash' : string } |
+  { 'UploadNotFound' : bigint } |
+  { 'DocumentVersionNotFound' : [bigint, bigint] } |
+  { 'DocumentPurged' : [bigint, bigint] } |
+  { 'DocumentNotAnchored' : bigint } |
+  { 'DocumentsRootMismatch' : [string, string] } |
+  { 'AgencyHasOpenContracts' : Array<bigint> } |
+  { 'NotOwnedByAgency' : bigint } |
+  { 'AgencyQuotaExceeded' : [bigint, AgencyQuota] } |
+  { 'DocumentPreviewNotFound' : bigint } |
+  { 'MimeTypeNotAllowed' : string } |
+  { 'MimeTypeMismatch' : [string, string] } |
   { 'BadContractProperty' : null };
 export interface ContractRegistration {
   'value' : bigint,
@@ -69,25 +267,47 @@ export type ContractType = { 'Sell' : null } |
 export type DeferredDataError = { 'Configuration' : ConfigurationError_1 } |
   { 'Contract' : ContractError_1 } |
   { 'RealEstate' : RealEstateError } |
+  { 'MissingPermission' : Permission_1 } |
   { 'InvalidSignature' : null } |
+  { 'ReadOnly' : null } |
+  { 'Import' : ImportError } |
+  { 'SignedMessage' : SignedMessageError } |
   { 'Unauthorized' : null } |
   { 'StorageError' : null } |
   { 'CanisterCall' : [RejectionCode, string] };
 export type DeferredMinterError = { 'Configuration' : ConfigurationError } |
+  { 'Proposal' : ProposalError } |
+  { 'Agency' : AgencyError } |
   { 'Contract' : ContractError } |
   { 'CloseContract' : CloseContractError } |
+  { 'MissingPermission' : Permission } |
   { 'Unauthorized' : null } |
+  { 'Paused' : PausableOperation } |
   { 'FailedToDecodeOutput' : string } |
   { 'EvmRpc' : string } |
   { 'DataCanister' : DeferredDataError } |
   { 'StorageError' : null } |
   { 'CanisterCall' : [RejectionCode, string] } |
   { 'Ecdsa' : EcdsaError };
+export interface DeferredMinterConfiguration {
+  'deferred_erc721' : string,
+  'evm_rpc_api' : [] | [string],
+  'allowed_currencies' : Array<string>,
+  'deferred_data' : Principal,
+  'reward_pool_deploy_block' : bigint,
+  'reward_pool' : string,
+  'chain_id' : bigint,
+  'gas_price' : bigint,
+  'evm_rpc' : Principal,
+  'ecdsa_key' : EcdsaKey,
+  'log_settings' : LogSettingsV2,
+}
 export interface DeferredMinterInitData {
   'deferred_erc721' : string,
   'evm_rpc_api' : [] | [string],
   'allowed_currencies' : Array<string>,
   'deferred_data' : Principal,
+  'reward_pool_deploy_block' : bigint,
   'reward_pool' : string,
   'custodians' : Array<Principal>,
   'chain_id' : bigint,
@@ -95,12 +315,35 @@ export interface DeferredMinterInitData {
   'ecdsa_key' : EcdsaKey,
   'log_settings' : LogSettingsV2,
 }
+export interface DocumentLeaf { 'sha256' : string, 'document_id' : bigint }
+export interface DocumentsAnchor {
+  'root' : string,
+  'transaction_hash' : string,
+  'chain_id' : bigint,
+  'contract_id' : bigint,
+  'leaves' : Array<DocumentLeaf>,
+  'anchored_at' : bigint,
+}
 export type EcdsaError = { 'RecoveryIdError' : string } |
   { 'InvalidSignature' : string } |
   { 'InvalidPublicKey' : string };
 export type EcdsaKey = { 'Dfx' : null } |
   { 'Production' : null } |
   { 'Test' : null };
+export type ExportCollection = { 'RealEstates' : null } |
+  { 'NextDocumentId' : null } |
+  { 'DocumentHistory' : null } |
+  { 'DocumentAnchors' : null } |
+  { 'DocumentHashes' : null } |
+  { 'DocumentContents' : null } |
+  { 'Documents' : null } |
+  { 'DocumentReferences' : null } |
+  { 'AgencyQuotas' : null } |
+  { 'AgencyUsage' : null } |
+  { 'ContractUsage' : null } |
+  { 'DocumentPreviews' : null } |
+  { 'DocumentContentChunks' : null } |
+  { 'Contracts' : null };
 export type GenericValue = { 'Nat64Content' : bigint } |
   { 'Nat32Content' : number } |
   { 'BoolContent' : boolean } |
@@ -125,8 +368,13 @@ export interface HttpResponse {
   'body' : Uint8Array | number[],
   'headers' : Array<[string, string]>,
   'upgrade' : [] | [boolean],
+  'streaming_strategy' : [] | [StreamingStrategy],
   'status_code' : number,
 }
+export type ImportError = { 'InvalidRecord' : ExportCollection } |
+  { 'CollectionNotEmpty' : ExportCollection } |
+  { 'InvalidChecksum' : null } |
+  { 'UnexpectedChunk' : ExportCollection };
 export interface Log { 'log' : string, 'offset' : bigint }
 export interface LogSettingsV2 {
   'log_filter' : string,
@@ -135,7 +383,78 @@ export interface LogSettingsV2 {
   'max_record_length' : bigint,
 }
 export interface Logs { 'logs' : Array<Log>, 'all_logs_count' : bigint }
+export interface MigrationProgress {
+  'to_version' : number,
+  'total' : bigint,
+  'collection' : string,
+  'started_at' : bigint,
+  'cursor' : [] | [Uint8Array | number[]],
+  'from_version' : number,
+  'migrated' : bigint,
+}
+export interface MigrationStatus {
+  'pending' : Array<MigrationProgress>,
+  'collections' : Array<CollectionSchema>,
+}
 export interface Pagination { 'count' : bigint, 'offset' : bigint }
+export type PausableOperation = { 'ContractClosing' : null } |
+  { 'EvmTransactions' : null } |
+  { 'ContractCreation' : null } |
+  { 'RealEstateWrites' : null };
+export interface PauseState {
+  'operation' : PausableOperation,
+  'paused_at' : bigint,
+  'paused_by' : Principal,
+  'reason' : string,
+}
+export type Permission = { 'ManageConfig' : null } |
+  { 'CloseAnyContract' : null } |
+  { 'ReadLogs' : null } |
+  { 'ManageCurrencies' : null } |
+  { 'ManageAgencies' : null } |
+  { 'SetGas' : null } |
+  { 'ManageRoles' : null } |
+  { 'CreateContracts' : null };
+export type Permission_1 = { 'Minter' : null } |
+  { 'Owner' : null };
+export interface Proposal {
+  'id' : bigint,
+  'status' : ProposalStatus,
+  'failure_reason' : [] | [string],
+  'action' : ProposalAction,
+  'proposed_at' : bigint,
+  'proposed_by' : Principal,
+  'closed_at' : [] | [bigint],
+  'rejected_by' : [] | [Principal],
+  'approvals' : Array<Principal>,
+  'expires_at' : bigint,
+}
+export type ProposalAction = { 'SetCustodians' : Array<Principal> } |
+  { 'SetRole' : { 'principal' : Principal, 'role' : Role } } |
+  { 'RemoveRole' : { 'principal' : Principal, 'role' : Role } } |
+  {
+    'DefineRole' : { 'permissions' : Array<Permission>, 'name' : string }
+  } |
+  { 'DeleteRole' : string } |
+  { 'SetAllowedCurrencies' : Array<string> } |
+  { 'SetProposalConfig' : ProposalConfig } |
+  { 'SetSnsGovernance' : [] | [Principal] } |
+  { 'UpdateConfiguration' : ConfigurationUpdate } |
+  { 'Resume' : PausableOperation } |
+  { 'SetRewardParameters' : RewardParameters };
+export interface ProposalConfig { 'threshold' : number, 'timeout' : bigint }
+export type ProposalError = { 'ProposalNotPending' : bigint } |
+  { 'InvalidProposal' : string } |
+  { 'ProposalExpired' : bigint } |
+  { 'NotACustodian' : Principal } |
+  { 'InvalidThreshold' : number } |
+  { 'ProposalNotFound' : bigint } |
+  { 'AlreadyApproved' : bigint };
+export type ProposalStatus = { 'Failed' : null } |
+  { 'Executed' : null } |
+  { 'Rejected' : null } |
+  { 'Expired' : null } |
+  { 'Pending' : null };
 export interface RealEstate {
   'region' : [] | [string],
   'latitude' : [] | [number],
@@ -152,7 +471,7 @@ export interface RealEstate {
   'zone' : [] | [string],
   'garage' : [] | [boolean],
   'garden' : [] | [boolean],
-  'agency' : Principal,
+  'agency' : bigint,
   'continent' : [] | [Continent],
   'description' : string,
   'longitude' : [] | [number],
@@ -166,7 +485,8 @@ export interface RealEstate {
   'parking' : [] | [boolean],
   'rooms' : [] | [bigint],
 }
-export type RealEstateError = { 'NotFound' : bigint };
+export type RealEstateError = { 'NotOwnedByAgency' : bigint } |
+  { 'NotFound' : bigint };
 export type RejectionCode = { 'NoError' : null } |
   { 'CanisterError' : null } |
   { 'SysTransient' : null } |
@@ -188,29 +508,253 @@ export type Result_1 = { 'Ok' : bigint } |
   { 'Err' : DeferredMinterError };
 export type Result_2 = { 'Ok' : string } |
   { 'Err' : DeferredMinterError };
-export type Role = { 'Custodian' : null } |
+export type Result_3 = { 'Ok' : Array<AgencyChange> } |
+  { 'Err' : DeferredMinterError };
+export type Result_4 = { 'Ok' : AgencyApplication } |
+  { 'Err' : DeferredMinterError };
+export type Result_5 = { 'Ok' : bigint } |
+  { 'Err' : DeferredMinterError };
+export type Result_6 = { 'Ok' : Array<AgencyMember> } |
+  { 'Err' : DeferredMinterError };
+export type Result_7 = { 'Ok' : AgencyReport } |
+  { 'Err' : DeferredMinterError };
+export type Result_8 = { 'Ok' : AgencyTransfer } |
+  { 'Err' : DeferredMinterError };
+export type Result_9 = { 'Ok' : AgencyTransferAuditEntry } |
+  { 'Err' : DeferredMinterError };
+export type Result_10 = { 'Ok' : Array<AgencyTransfer> } |
+  { 'Err' : DeferredMinterError };
+export type Result_11 = { 'Ok' : Array<AgencyApplication> } |
+  { 'Err' : DeferredMinterError };
+export type Result_12 = { 'Ok' : Logs } |
+  { 'Err' : DeferredMinterError };
+export type Result_13 = { 'Ok' : Array<RewardPoolAlert> } |
+  { 'Err' : DeferredMinterError };
+export type Result_14 = { 'Ok' : RewardPoolStatus } |
+  { 'Err' : DeferredMinterError };
+export type Result_15 = { 'Ok' : Proposal } |
+  { 'Err' : DeferredMinterError };
+export type Result_16 = { 'Ok' : string } |
+  { 'Err' : string };
+export type Result_17 = { 'Ok' : Array<AuditEntry> } |
+  { 'Err' : DeferredMinterError };
+export type Result_18 = { 'Ok' : AuditIntegrity } |
+  { 'Err' : DeferredMinterError };
+export type Result_19 = { 'Ok' : DeferredMinterConfiguration } |
+  { 'Err' : DeferredMinterError };
+export type Result_20 = { 'Ok' : Array<ConfigurationChange> } |
+  { 'Err' : DeferredMinterError };
+export type Result_21 = { 'Ok' : PauseState } |
+  { 'Err' : DeferredMinterError };
+export type Result_22 = { 'Ok' : MigrationStatus } |
+  { 'Err' : DeferredMinterError };
+export type Result_23 = { 'Ok' : DocumentsAnchor } |
+  { 'Err' : DeferredMinterError };
+export type Result_24 = { 'Ok' : AgencyApplicationDocument } |
+  { 'Err' : DeferredMinterError };
+export interface RewardParameters {
+  'rmc' : number,
+  'avidity' : number,
+  'next_halving' : bigint,
+}
+export interface RewardPoolAlert {
+  'kind' : RewardPoolAlertKind,
+  'timestamp' : bigint,
+}
+export type RewardPoolAlertKind = {
+    'AvailableRewardIncreased' : { 'previous' : bigint, 'current' : bigint }
+  } |
+  { 'MissingReservation' : { 'contract_id' : bigint } } |
+  {
+    'UnknownReservation' : {
+      'block' : [] | [bigint],
+      'tokens' : bigint,
+      'reward' : bigint,
+    }
+  };
+export interface RewardPoolStatus {
+  'available_reward_updated_at' : bigint,
+  'rewards_sent' : bigint,
+  'available_reward' : bigint,
+  'pending_reserved' : bigint,
+  'reserved_on_chain' : bigint,
+  'last_synced_block' : bigint,
+  'alerts' : bigint,
+  'reserved_by_minter' : bigint,
+}
+export type Role = { 'Custom' : string } |
+  { 'Custodian' : null } |
   { 'Agent' : null } |
   { 'GasStation' : null };
+export interface RoleDefinition {
+  'permissions' : Array<Permission>,
+  'role' : Role,
+}
 export interface Seller { 'quota' : number, 'address' : string }
+export type SignedMessageError = { 'NonceAlreadyUsed' : string } |
+  { 'NotYetValid' : null } |
+  { 'Expired' : null } |
+  { 'Malformed' : string } |
+  { 'DomainMismatch' : string } |
+  { 'ContractMismatch' : bigint } |
+  { 'SignerMismatch' : string };
+export interface StreamingCallbackHttpResponse {
+  'token' : [] | [StreamingCallbackToken],
+  'body' : Uint8Array | number[],
+}
+export interface StreamingCallbackToken {
+  'end' : bigint,
+  'url' : string,
+  'offset' : bigint,
+}
+export type StreamingStrategy = {
+    'Callback' : {
+      'token' : StreamingCallbackToken,
+      'callback' : [Principal, string],
+    }
+  };
 export interface _SERVICE {
-  'admin_cycles' : ActorMethod<[], bigint>,
-  'admin_ic_logs' : ActorMethod<[Pagination], Logs>,
-  'admin_register_agency' : ActorMethod<[Principal, Agency], undefined>,
-  'admin_remove_role' : ActorMethod<[Principal, Role], Result>,
-  'admin_set_allowed_currencies' : ActorMethod<[Array<string>], undefined>,
-  'admin_set_custodians' : ActorMethod<[Array<Principal>], Result>,
-  'admin_set_role' : ActorMethod<[Principal, Role], undefined>,
+  'accept_agency_transfer' : ActorMethod<[bigint], Result_8>,
+  'add_agency_member' : ActorMethod<[bigint, Principal, AgencyRole], Result>,
+  'admin_agency_applications' : ActorMethod<
+    [[] | [AgencyApplicationStatus], Pagination],
+    Result_11
+  >,
+  'admin_approve_agency_application' : ActorMethod<
+    [bigint, [] | [string]],
+    Result
+  >,
+  'admin_approve_agency_transfer' : ActorMethod<[bigint], Result_9>,
+  'admin_approve_proposal' : ActorMethod<[bigint], Result_15>,
+  'admin_configuration_history' : ActorMethod<[Pagination], Result_20>,
+  'admin_cycles' : ActorMethod<[], Result_1>,
+  'admin_define_role' : ActorMethod<[string, Array<Permission>], Result_15>,
+  'admin_delete_role' : ActorMethod<[string], Result_15>,
+  'admin_comment_agency_application' : ActorMethod<[bigint, string], Result>,
+  'admin_audit_log' : ActorMethod<[AuditFilter, Pagination], Result_17>,
+  'admin_ic_logs' : ActorMethod<[Pagination], Result_12>,
+  'admin_migration_status' : ActorMethod<[], Result_22>,
+  'admin_pause' : ActorMethod<[PausableOperation, string], Result_21>,
+  'admin_propose' : ActorMethod<[ProposalAction], Result_15>,
+  'admin_reactivate_agency' : ActorMethod<[bigint], Result_7>,
+  'admin_register_agency' : ActorMethod<[Principal, Agency], Result_5>,
+  'admin_reject_agency_application' : ActorMethod<[bigint, string], Result>,
+  'admin_reject_proposal' : ActorMethod<[bigint], Result_15>,
+  'admin_remove_role' : ActorMethod<[Principal, Role], Result_15>,
+  'admin_resume' : ActorMethod<[PausableOperation], Result_15>,
+  'admin_reward_pool_alerts' : ActorMethod<[], Result_13>,
+  'admin_reward_pool_status' : ActorMethod<[], Result_14>,
+  'admin_set_agency_quota' : ActorMethod<
+    [bigint, [] | [AgencyQuota]],
+    Result
+  >,
+  'admin_set_allowed_currencies' : ActorMethod<[Array<string>], Result_15>,
+  'admin_set_custodians' : ActorMethod<[Array<Principal>], Result_15>,
+  'admin_set_reward_parameters' : ActorMethod<[RewardParameters], Result_15>,
+  'admin_set_role' : ActorMethod<[Principal, Role], Result_15>,
+  'admin_suspend_agency' : ActorMethod<[bigint], Result_7>,
+  'admin_sync_reward_pool' : ActorMethod<[], Result>,
+  'admin_update_configuration' : ActorMethod<[ConfigurationUpdate], Result_15>,
+  'admin_verify_audit_log' : ActorMethod<[Pagination], Result_18>,
+  'anchor_contract_documents' : ActorMethod<[bigint], Result_23>,
   'close_contract' : ActorMethod<[bigint], Result>,
   'create_contract' : ActorMethod<[ContractRegistration], Result_1>,
   'create_real_estate' : ActorMethod<[RealEstate], Result_1>,
   'delete_real_estate' : ActorMethod<[bigint], Result>,
   'gas_station_set_gas_price' : ActorMethod<[bigint], Result>,
   'get_agencies' : ActorMethod<[], Array<Agency>>,
-  'get_agency' : ActorMethod<[Principal], [] | [Agency]>,
+  'get_agency' : ActorMethod<[bigint], [] | [Agency]>,
+  'get_agency_application' : ActorMethod<[bigint], Result_4>,
+  'get_agency_application_document' : ActorMethod<
+    [bigint, bigint],
+    Result_24
+  >,
+  'get_agency_history' : ActorMethod<[bigint], Result_3>,
+  'get_agency_members' : ActorMethod<[bigint], Result_6>,
+  'get_agency_status' : ActorMethod<[bigint], AgencyStatus>,
+  'get_agency_transfer' : ActorMethod<[bigint], Result_8>,
+  'get_agency_transfers' : ActorMethod<[bigint], Result_10>,
+  'get_configuration' : ActorMethod<[], Result_19>,
   'get_eth_address' : ActorMethod<[], Result_2>,
+  'get_member_agency' : ActorMethod<[Principal], [] | [Agency]>,
+  'get_paused_operations' : ActorMethod<[], Array<PauseState>>,
+  'get_permissions' : ActorMethod<[Principal], Array<Permission>>,
+  'get_proposal' : ActorMethod<[bigint], [] | [Proposal]>,
+  'get_proposal_config' : ActorMethod<[], ProposalConfig>,
+  'get_proposals' : ActorMethod<
+    [[] | [ProposalStatus], Pagination],
+    Array<Proposal>
+  >,
+  'get_reward_parameters' : ActorMethod<[], RewardParameters>,
+  'get_roles' : ActorMethod<[], Array<RoleDefinition>>,
+  'get_sns_governance' : ActorMethod<[], [] | [Principal]>,
   'http_request' : ActorMethod<[HttpRequest], HttpResponse>,
-  'remove_agency' : ActorMethod<[Principal], Result>,
+  'remove_agency' : ActorMethod<[bigint], Result_7>,
+  'reject_agency_transfer' : ActorMethod<[bigint], Result>,
+  'remove_agency_member' : ActorMethod<[bigint, Principal], Result>,
+  'request_agency_transfer' : ActorMethod<
+    [bigint, bigint, Array<bigint>, Array<bigint>],
+    Result_5
+  >,
+  'submit_agency_application' : ActorMethod<
+    [Agency, Array<AgencyApplicationDocument>],
+    Result_5
+  >,
+  'update_agency' : ActorMethod<[AgencyUpdate], Result>,
   'update_real_estate' : ActorMethod<[bigint, RealEstate], Result>,
+  'validate_admin_approve_agency_application' : ActorMethod<
+    [bigint, [] | [string]],
+    Result_16
+  >,
+  'validate_admin_approve_agency_transfer' : ActorMethod<[bigint], Result_16>,
+  'validate_admin_approve_proposal' : ActorMethod<[bigint], Result_16>,
+  'validate_admin_comment_agency_application' : ActorMethod<
+    [bigint, string],
+    Result_16
+  >,
+  'validate_admin_define_role' : ActorMethod<
+    [string, Array<Permission>],
+    Result_16
+  >,
+  'validate_admin_delete_role' : ActorMethod<[string], Result_16>,
+  'validate_admin_pause' : ActorMethod<
+    [PausableOperation, string],
+    Result_16
+  >,
+  'validate_admin_propose' : ActorMethod<[ProposalAction], Result_16>,
+  'validate_admin_reactivate_agency' : ActorMethod<[bigint], Result_16>,
+  'validate_admin_register_agency' : ActorMethod<
+    [Principal, Agency],
+    Result_16
+  >,
+  'validate_admin_reject_agency_application' : ActorMethod<
+    [bigint, string],
+    Result_16
+  >,
+  'validate_admin_reject_proposal' : ActorMethod<[bigint], Result_16>,
+  'validate_admin_remove_role' : ActorMethod<[Principal, Role], Result_16>,
+  'validate_admin_resume' : ActorMethod<[PausableOperation], Result_16>,
+  'validate_admin_set_agency_quota' : ActorMethod<
+    [bigint, [] | [AgencyQuota]],
+    Result_16
+  >,
+  'validate_admin_set_allowed_currencies' : ActorMethod<
+    [Array<string>],
+    Result_16
+  >,
+  'validate_admin_set_custodians' : ActorMethod<[Array<Principal>], Result_16>,
+  'validate_admin_set_reward_parameters' : ActorMethod<
+    [RewardParameters],
+    Result_16
+  >,
+  'validate_admin_set_role' : ActorMethod<[Principal, Role], Result_16>,
+  'validate_admin_suspend_agency' : ActorMethod<[bigint], Result_16>,
+  'validate_admin_sync_reward_pool' : ActorMethod<[], Result_16>,
+  'validate_admin_update_configuration' : ActorMethod<
+    [ConfigurationUpdate],
+    Result_16
+  >,
+  'validate_gas_station_set_gas_price' : ActorMethod<[bigint], Result_16>,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
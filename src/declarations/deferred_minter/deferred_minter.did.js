@@ -15,6 +15,7 @@ export const idlFactory = ({ IDL }) => {
     'evm_rpc_api' : IDL.Opt(IDL.Text),
     'allowed_currencies' : IDL.Vec(IDL.Text),
     'deferred_data' : IDL.Principal,
+    'reward_pool_deploy_block' : IDL.Nat64,
     'reward_pool' : IDL.Text,
     'custodians' : IDL.Vec(IDL.Principal),
     'chain_id' : IDL.Nat64,
@@ -38,6 +39,7 @@ export const idlFactory = ({ IDL }) => {
     'NorthAmerica' : IDL.Null,
   });
   const Agency = IDL.Record({
+    'id' : IDL.Nat64,
     'lat' : IDL.Opt(IDL.Text),
     'lng' : IDL.Opt(IDL.Text),
     'vat' : IDL.Text,
@@ -55,14 +57,83 @@ export const idlFactory = ({ IDL }) => {
     'address' : IDL.Text,
     'mobile' : IDL.Text,
   });
+  const Permission = IDL.Variant({
+    'ManageConfig' : IDL.Null,
+    'CloseAnyContract' : IDL.Null,
+    'ReadLogs' : IDL.Null,
+    'ManageCurrencies' : IDL.Null,
+    'ManageAgencies' : IDL.Null,
+    'SetGas' : IDL.Null,
+    'ManageRoles' : IDL.Null,
+    'CreateContracts' : IDL.Null,
+  });
   const Role = IDL.Variant({
+    'Custom' : IDL.Text,
     'Custodian' : IDL.Null,
     'Agent' : IDL.Null,
     'GasStation' : IDL.Null,
   });
+  const ProposalConfig = IDL.Record({
+    'threshold' : IDL.Nat32,
+    'timeout' : IDL.Nat64,
+  });
+  const PausableOperation = IDL.Variant({
+    'ContractClosing' : IDL.Null,
+    'EvmTransactions' : IDL.Null,
+    'ContractCreation' : IDL.Null,
+    'RealEstateWrites' : IDL.Null,
+  });
+  const ConfigurationUpdate = IDL.Record({
+    'deferred_erc721' : IDL.Opt(IDL.Text),
+    'evm_rpc_api' : IDL.Opt(IDL.Text),
+    'deferred_data' : IDL.Opt(IDL.Principal),
+    'reward_pool_deploy_block' : IDL.Opt(IDL.Nat64),
+    'reward_pool' : IDL.Opt(IDL.Text),
+    'chain_id' : IDL.Opt(IDL.Nat64),
+    'evm_rpc' : IDL.Opt(IDL.Principal),
+    'ecdsa_key' : IDL.Opt(EcdsaKey),
+    'log_settings' : IDL.Opt(LogSettingsV2),
+  });
+  const RewardParameters = IDL.Record({
+    'rmc' : IDL.Float64,
+    'avidity' : IDL.Float64,
+    'next_halving' : IDL.Nat64,
+  });
+  const ProposalAction = IDL.Variant({
+    'SetCustodians' : IDL.Vec(IDL.Principal),
+    'SetRole' : IDL.Record({ 'principal' : IDL.Principal, 'role' : Role }),
+    'RemoveRole' : IDL.Record({ 'principal' : IDL.Principal, 'role' : Role }),
+    'DefineRole' : IDL.Record({
+      'permissions' : IDL.Vec(Permission),
+      'name' : IDL.Text,
+    }),
+    'DeleteRole' : IDL.Text,
+    'SetAllowedCurrencies' : IDL.Vec(IDL.Text),
+    'SetProposalConfig' : ProposalConfig,
+    'SetSnsGovernance' : IDL.Opt(IDL.Principal),
+    'UpdateConfiguration' : ConfigurationUpdate,
+    'Resume' : PausableOperation,
+    'SetRewardParameters' : RewardParameters,
+  });
+  const ProposalError = IDL.Variant({
+    'ProposalNotPending' : IDL.Nat64,
+    'InvalidProposal' : IDL.Text,
+    'ProposalExpired' : IDL.Nat64,
+    'NotACustodian' : IDL.Principal,
+    'InvalidThreshold' : IDL.Nat32,
+    'ProposalNotFound' : IDL.Nat64,
+    'AlreadyApproved' : IDL.Nat64,
+  });
   const ConfigurationError = IDL.Variant({
+    'InvalidRoleName' : IDL.Text,
     'CustodialsCantBeEmpty' : IDL.Null,
+    'UnsupportedChainId' : IDL.Nat64,
+    'UnknownRole' : IDL.Text,
+    'AnonymousPrincipal' : IDL.Text,
     'AnonymousCustodial' : IDL.Null,
+    'InvalidEvmRpcApi' : IDL.Text,
+    'ZeroAddress' : IDL.Text,
+    'InvalidRewardParameters' : IDL.Text,
   });
   const ContractError = IDL.Variant({
     'CurrencyNotAllowed' : IDL.Text,
@@ -77,6 +148,7 @@ export const idlFactory = ({ IDL }) => {
     'BadContractExpiration' : IDL.Null,
     'ContractHasNoTokens' : IDL.Null,
     'BadRealEstateId' : IDL.Null,
+    'ContractHasNoDocuments' : IDL.Nat,
     'BadContractProperty' : IDL.Null,
   });
   const CloseContractError = IDL.Variant({
@@ -85,15 +157,37 @@ export const idlFactory = ({ IDL }) => {
   });
   const ConfigurationError_1 = IDL.Variant({
     'AnonymousOwner' : IDL.Null,
+    'AnonymousSnsGovernance' : IDL.Null,
+    'InvalidMimeType' : IDL.Text,
     'AnonymousMinter' : IDL.Null,
   });
+  const AgencyQuota = IDL.Record({
+    'max_documents' : IDL.Nat64,
+    'max_document_bytes' : IDL.Nat64,
+  });
   const ContractError_1 = IDL.Variant({
     'DocumentNotFound' : IDL.Nat64,
     'ContractNotFound' : IDL.Nat,
     'DocumentSizeMismatch' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'DocumentHashMismatch' : IDL.Tuple(IDL.Text, IDL.Text),
+    'InvalidDocumentHash' : IDL.Text,
+    'UploadNotFound' : IDL.Nat64,
+    'DocumentVersionNotFound' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'DocumentPurged' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'DocumentNotAnchored' : IDL.Nat64,
+    'DocumentsRootMismatch' : IDL.Tuple(IDL.Text, IDL.Text),
+    'AgencyHasOpenContracts' : IDL.Vec(IDL.Nat),
+    'NotOwnedByAgency' : IDL.Nat,
+    'AgencyQuotaExceeded' : IDL.Tuple(IDL.Nat64, AgencyQuota),
+    'DocumentPreviewNotFound' : IDL.Nat64,
+    'MimeTypeNotAllowed' : IDL.Text,
+    'MimeTypeMismatch' : IDL.Tuple(IDL.Text, IDL.Text),
     'BadContractProperty' : IDL.Null,
   });
-  const RealEstateError = IDL.Variant({ 'NotFound' : IDL.Nat });
+  const RealEstateError = IDL.Variant({
+    'NotOwnedByAgency' : IDL.Nat,
+    'NotFound' : IDL.Nat,
+  });
   const RejectionCode = IDL.Variant({
     'NoError' : IDL.Null,
     'CanisterError' : IDL.Null,
@@ -103,11 +197,47 @@ export const idlFactory = ({ IDL }) => {
     'SysFatal' : IDL.Null,
     'CanisterReject' : IDL.Null,
   });
+  const Permission_1 = IDL.Variant({ 'Minter' : IDL.Null, 'Owner' : IDL.Null });
+  const ExportCollection = IDL.Variant({
+    'RealEstates' : IDL.Null,
+    'NextDocumentId' : IDL.Null,
+    'DocumentHistory' : IDL.Null,
+    'DocumentAnchors' : IDL.Null,
+    'DocumentHashes' : IDL.Null,
+    'DocumentContents' : IDL.Null,
+    'Documents' : IDL.Null,
+    'DocumentReferences' : IDL.Null,
+    'AgencyQuotas' : IDL.Null,
+    'AgencyUsage' : IDL.Null,
+    'ContractUsage' : IDL.Null,
+    'DocumentPreviews' : IDL.Null,
+    'DocumentContentChunks' : IDL.Null,
+    'Contracts' : IDL.Null,
+  });
+  const ImportError = IDL.Variant({
+    'InvalidRecord' : ExportCollection,
+    'CollectionNotEmpty' : ExportCollection,
+    'InvalidChecksum' : IDL.Null,
+    'UnexpectedChunk' : ExportCollection,
+  });
+  const SignedMessageError = IDL.Variant({
+    'NonceAlreadyUsed' : IDL.Text,
+    'NotYetValid' : IDL.Null,
+    'Expired' : IDL.Null,
+    'Malformed' : IDL.Text,
+    'DomainMismatch' : IDL.Text,
+    'ContractMismatch' : IDL.Nat,
+    'SignerMismatch' : IDL.Text,
+  });
   const DeferredDataError = IDL.Variant({
     'Configuration' : ConfigurationError_1,
     'Contract' : ContractError_1,
     'RealEstate' : RealEstateError,
+    'MissingPermission' : Permission_1,
     'InvalidSignature' : IDL.Null,
+    'ReadOnly' : IDL.Null,
+    'Import' : ImportError,
+    'SignedMessage' : SignedMessageError,
     'Unauthorized' : IDL.Null,
     'StorageError' : IDL.Null,
     'CanisterCall' : IDL.Tuple(RejectionCode, IDL.Text),
@@ -117,11 +247,45 @@ export const idlFactory = ({ IDL }) => {
     'InvalidSignature' : IDL.Text,
     'InvalidPublicKey' : IDL.Text,
   });
+  const AgencyError = IDL.Variant({
+    'InvalidLatitude' : IDL.Text,
+    'NotAMember' : IDL.Principal,
+    'NotAnAdmin' : IDL.Principal,
+    'NotTheOwner' : IDL.Principal,
+    'CannotOperateAgency' : IDL.Tuple(IDL.Principal, IDL.Nat64),
+    'InvalidUrl' : IDL.Text,
+    'LastAdmin' : IDL.Null,
+    'InvalidEmail' : IDL.Text,
+    'AgencyNotFound' : IDL.Nat64,
+    'AlreadyMember' : IDL.Principal,
+    'AgencySuspended' : IDL.Nat64,
+    'AgencyNotSuspended' : IDL.Nat64,
+    'TransferNotFound' : IDL.Nat64,
+    'TransferNotPending' : IDL.Nat64,
+    'TransferNotAccepted' : IDL.Nat64,
+    'InvalidTransfer' : IDL.Text,
+    'NotPartOfTransfer' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'ApplicationNotFound' : IDL.Nat64,
+    'ApplicationAlreadyPending' : IDL.Principal,
+    'AgencyAlreadyRegistered' : IDL.Principal,
+    'NotTheApplicant' : IDL.Principal,
+    'ApplicationAlreadyReviewed' : IDL.Nat64,
+    'ApplicationDocumentsTooLarge' : IDL.Nat64,
+    'ApplicationDocumentNotFound' : IDL.Tuple(IDL.Nat64, IDL.Nat64),
+    'TooManyPendingApplications' : IDL.Null,
+    'TooManyApplications' : IDL.Principal,
+    'InvalidLongitude' : IDL.Text,
+    'EmptyField' : IDL.Text,
+  });
   const DeferredMinterError = IDL.Variant({
     'Configuration' : ConfigurationError,
+    'Proposal' : ProposalError,
+    'Agency' : AgencyError,
     'Contract' : ContractError,
     'CloseContract' : CloseContractError,
+    'MissingPermission' : Permission,
     'Unauthorized' : IDL.Null,
+    'Paused' : PausableOperation,
     'FailedToDecodeOutput' : IDL.Text,
     'EvmRpc' : IDL.Text,
     'DataCanister' : DeferredDataError,
@@ -192,7 +356,7 @@ export const idlFactory = ({ IDL }) => {
     'zone' : IDL.Opt(IDL.Text),
     'garage' : IDL.Opt(IDL.Bool),
     'garden' : IDL.Opt(IDL.Bool),
-    'agency' : IDL.Principal,
+    'agency' : IDL.Nat64,
     'continent' : IDL.Opt(Continent),
     'description' : IDL.Text,
     'longitude' : IDL.Opt(IDL.Float64),
@@ -210,37 +374,601 @@ export const idlFactory = ({ IDL }) => {
     'Ok' : IDL.Text,
     'Err' : DeferredMinterError,
   });
+  const AgencyApplicationStatus = IDL.Variant({
+    'Approved' : IDL.Null,
+    'Rejected' : IDL.Null,
+    'Expired' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const AgencyApplicationDocumentInfo = IDL.Record({
+    'name' : IDL.Text,
+    'size' : IDL.Nat64,
+    'mime_type' : IDL.Text,
+  });
+  const AgencyApplicationDocument = IDL.Record({
+    'data' : IDL.Vec(IDL.Nat8),
+    'name' : IDL.Text,
+    'mime_type' : IDL.Text,
+  });
+  const AgencyApplicationComment = IDL.Record({
+    'author' : IDL.Principal,
+    'comment' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+  });
+  const AgencyApplication = IDL.Record({
+    'id' : IDL.Nat64,
+    'status' : AgencyApplicationStatus,
+    'documents' : IDL.Vec(AgencyApplicationDocumentInfo),
+    'agency' : Agency,
+    'submitted_at' : IDL.Nat64,
+    'reviewer' : IDL.Opt(IDL.Principal),
+    'comments' : IDL.Vec(AgencyApplicationComment),
+    'applicant' : IDL.Principal,
+    'reviewed_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_4 = IDL.Variant({
+    'Ok' : AgencyApplication,
+    'Err' : DeferredMinterError,
+  });
+  const Result_5 = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : DeferredMinterError });
+  const AgencyFieldChange = IDL.Record({
+    'field' : IDL.Text,
+    'previous' : IDL.Text,
+    'current' : IDL.Text,
+  });
+  const AgencyChange = IDL.Record({
+    'changes' : IDL.Vec(AgencyFieldChange),
+    'author' : IDL.Principal,
+    'timestamp' : IDL.Nat64,
+  });
+  const Result_3 = IDL.Variant({
+    'Ok' : IDL.Vec(AgencyChange),
+    'Err' : DeferredMinterError,
+  });
+  const AgencyUpdate = IDL.Record({
+    'lat' : IDL.Opt(IDL.Text),
+    'lng' : IDL.Opt(IDL.Text),
+    'region' : IDL.Opt(IDL.Text),
+    'zip_code' : IDL.Opt(IDL.Text),
+    'country' : IDL.Opt(IDL.Text),
+    'agent' : IDL.Opt(IDL.Text),
+    'city' : IDL.Opt(IDL.Text),
+    'logo' : IDL.Opt(IDL.Text),
+    'name' : IDL.Opt(IDL.Text),
+    'continent' : IDL.Opt(Continent),
+    'email' : IDL.Opt(IDL.Text),
+    'website' : IDL.Opt(IDL.Text),
+    'address' : IDL.Opt(IDL.Text),
+    'mobile' : IDL.Opt(IDL.Text),
+  });
+  const RewardPoolAlertKind = IDL.Variant({
+    'AvailableRewardIncreased' : IDL.Record({
+      'previous' : IDL.Nat,
+      'current' : IDL.Nat,
+    }),
+    'MissingReservation' : IDL.Record({ 'contract_id' : IDL.Nat }),
+    'UnknownReservation' : IDL.Record({
+      'block' : IDL.Opt(IDL.Nat64),
+      'tokens' : IDL.Nat64,
+      'reward' : IDL.Nat,
+    }),
+  });
+  const RewardPoolAlert = IDL.Record({
+    'kind' : RewardPoolAlertKind,
+    'timestamp' : IDL.Nat64,
+  });
+  const RewardPoolStatus = IDL.Record({
+    'available_reward_updated_at' : IDL.Nat64,
+    'rewards_sent' : IDL.Nat,
+    'available_reward' : IDL.Nat,
+    'pending_reserved' : IDL.Nat,
+    'reserved_on_chain' : IDL.Nat,
+    'last_synced_block' : IDL.Nat64,
+    'alerts' : IDL.Nat64,
+    'reserved_by_minter' : IDL.Nat,
+  });
+  const RoleDefinition = IDL.Record({
+    'permissions' : IDL.Vec(Permission),
+    'role' : Role,
+  });
   const HttpRequest = IDL.Record({
     'url' : IDL.Text,
     'method' : IDL.Text,
     'body' : IDL.Vec(IDL.Nat8),
     'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
   });
+  const StreamingCallbackToken = IDL.Record({
+    'end' : IDL.Nat64,
+    'url' : IDL.Text,
+    'offset' : IDL.Nat64,
+  });
+  const StreamingCallbackHttpResponse = IDL.Record({
+    'token' : IDL.Opt(StreamingCallbackToken),
+    'body' : IDL.Vec(IDL.Nat8),
+  });
+  const StreamingStrategy = IDL.Variant({
+    'Callback' : IDL.Record({
+      'token' : StreamingCallbackToken,
+      'callback' : IDL.Func(
+          [StreamingCallbackToken],
+          [StreamingCallbackHttpResponse],
+          ['query'],
+        ),
+    }),
+  });
   const HttpResponse = IDL.Record({
     'body' : IDL.Vec(IDL.Nat8),
     'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
     'upgrade' : IDL.Opt(IDL.Bool),
+    'streaming_strategy' : IDL.Opt(StreamingStrategy),
     'status_code' : IDL.Nat16,
   });
+  const AgencyRole = IDL.Variant({
+    'Admin' : IDL.Null,
+    'ReadOnly' : IDL.Null,
+    'Agent' : IDL.Null,
+  });
+  const AgencyMember = IDL.Record({
+    'principal' : IDL.Principal,
+    'role' : AgencyRole,
+    'agency' : IDL.Nat64,
+  });
+  const Result_6 = IDL.Variant({
+    'Ok' : IDL.Vec(AgencyMember),
+    'Err' : DeferredMinterError,
+  });
+  const AgencyReport = IDL.Record({
+    'real_estates' : IDL.Vec(IDL.Nat),
+    'open_contracts' : IDL.Vec(IDL.Nat),
+    'agency' : IDL.Nat64,
+  });
+  const Result_7 = IDL.Variant({
+    'Ok' : AgencyReport,
+    'Err' : DeferredMinterError,
+  });
+  const AgencyStatus = IDL.Variant({
+    'Active' : IDL.Null,
+    'Suspended' : IDL.Null,
+  });
+  const AgencyTransferStatus = IDL.Variant({
+    'Rejected' : IDL.Null,
+    'Completed' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const AgencyTransfer = IDL.Record({
+    'id' : IDL.Nat64,
+    'to' : IDL.Nat64,
+    'status' : AgencyTransferStatus,
+    'from' : IDL.Nat64,
+    'requested_by' : IDL.Principal,
+    'approved_by' : IDL.Opt(IDL.Principal),
+    'real_estates' : IDL.Vec(IDL.Nat),
+    'from_accepted_by' : IDL.Opt(IDL.Principal),
+    'requested_at' : IDL.Nat64,
+    'contracts' : IDL.Vec(IDL.Nat),
+    'approved_at' : IDL.Opt(IDL.Nat64),
+    'to_accepted_by' : IDL.Opt(IDL.Principal),
+  });
+  const Result_8 = IDL.Variant({
+    'Ok' : AgencyTransfer,
+    'Err' : DeferredMinterError,
+  });
+  const AgencyTransferAuditEntry = IDL.Record({
+    'to' : IDL.Nat64,
+    'transfer_id' : IDL.Nat64,
+    'from' : IDL.Nat64,
+    'real_estates' : IDL.Vec(IDL.Nat),
+    'timestamp' : IDL.Nat64,
+    'contracts' : IDL.Vec(IDL.Nat),
+  });
+  const Result_9 = IDL.Variant({
+    'Ok' : AgencyTransferAuditEntry,
+    'Err' : DeferredMinterError,
+  });
+  const Result_10 = IDL.Variant({
+    'Ok' : IDL.Vec(AgencyTransfer),
+    'Err' : DeferredMinterError,
+  });
+  const Result_11 = IDL.Variant({
+    'Ok' : IDL.Vec(AgencyApplication),
+    'Err' : DeferredMinterError,
+  });
+  const Result_12 = IDL.Variant({ 'Ok' : Logs, 'Err' : DeferredMinterError });
+  const Result_13 = IDL.Variant({
+    'Ok' : IDL.Vec(RewardPoolAlert),
+    'Err' : DeferredMinterError,
+  });
+  const Result_14 = IDL.Variant({
+    'Ok' : RewardPoolStatus,
+    'Err' : DeferredMinterError,
+  });
+  const ProposalStatus = IDL.Variant({
+    'Failed' : IDL.Null,
+    'Executed' : IDL.Null,
+    'Rejected' : IDL.Null,
+    'Expired' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const Proposal = IDL.Record({
+    'id' : IDL.Nat64,
+    'status' : ProposalStatus,
+    'failure_reason' : IDL.Opt(IDL.Text),
+    'action' : ProposalAction,
+    'proposed_at' : IDL.Nat64,
+    'proposed_by' : IDL.Principal,
+    'closed_at' : IDL.Opt(IDL.Nat64),
+    'rejected_by' : IDL.Opt(IDL.Principal),
+    'approvals' : IDL.Vec(IDL.Principal),
+    'expires_at' : IDL.Nat64,
+  });
+  const Result_15 = IDL.Variant({ 'Ok' : Proposal, 'Err' : DeferredMinterError });
+  const Result_16 = IDL.Variant({ 'Ok' : IDL.Text, 'Err' : IDL.Text });
+  const AuditResult = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const AuditEntry = IDL.Record({
+    'id' : IDL.Nat64,
+    'method' : IDL.Text,
+    'args' : IDL.Text,
+    'hash' : IDL.Text,
+    'result' : AuditResult,
+    'timestamp' : IDL.Nat64,
+    'caller' : IDL.Principal,
+    'prev_hash' : IDL.Text,
+  });
+  const AuditFilter = IDL.Record({
+    'to' : IDL.Opt(IDL.Nat64),
+    'method' : IDL.Opt(IDL.Text),
+    'from' : IDL.Opt(IDL.Nat64),
+    'caller' : IDL.Opt(IDL.Principal),
+  });
+  const AuditIntegrity = IDL.Record({
+    'head' : IDL.Text,
+    'entries' : IDL.Nat64,
+    'first_invalid_entry' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_17 = IDL.Variant({
+    'Ok' : IDL.Vec(AuditEntry),
+    'Err' : DeferredMinterError,
+  });
+  const Result_18 = IDL.Variant({
+    'Ok' : AuditIntegrity,
+    'Err' : DeferredMinterError,
+  });
+  const DeferredMinterConfiguration = IDL.Record({
+    'deferred_erc721' : IDL.Text,
+    'evm_rpc_api' : IDL.Opt(IDL.Text),
+    'allowed_currencies' : IDL.Vec(IDL.Text),
+    'deferred_data' : IDL.Principal,
+    'reward_pool_deploy_block' : IDL.Nat64,
+    'reward_pool' : IDL.Text,
+    'chain_id' : IDL.Nat64,
+    'gas_price' : IDL.Nat64,
+    'evm_rpc' : IDL.Principal,
+    'ecdsa_key' : EcdsaKey,
+    'log_settings' : LogSettingsV2,
+  });
+  const Result_19 = IDL.Variant({
+    'Ok' : DeferredMinterConfiguration,
+    'Err' : DeferredMinterError,
+  });
+  const ConfigurationChange = IDL.Record({
+    'old_value' : IDL.Text,
+    'field' : IDL.Text,
+    'author' : IDL.Principal,
+    'new_value' : IDL.Text,
+    'timestamp' : IDL.Nat64,
+  });
+  const Result_20 = IDL.Variant({
+    'Ok' : IDL.Vec(ConfigurationChange),
+    'Err' : DeferredMinterError,
+  });
+  const PauseState = IDL.Record({
+    'operation' : PausableOperation,
+    'paused_at' : IDL.Nat64,
+    'paused_by' : IDL.Principal,
+    'reason' : IDL.Text,
+  });
+  const Result_21 = IDL.Variant({ 'Ok' : PauseState, 'Err' : DeferredMinterError });
+  const MigrationProgress = IDL.Record({
+    'to_version' : IDL.Nat32,
+    'total' : IDL.Nat64,
+    'collection' : IDL.Text,
+    'started_at' : IDL.Nat64,
+    'cursor' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'from_version' : IDL.Nat32,
+    'migrated' : IDL.Nat64,
+  });
+  const CollectionSchema = IDL.Record({
+    'collection' : IDL.Text,
+    'version' : IDL.Nat32,
+    'target_version' : IDL.Nat32,
+  });
+  const MigrationStatus = IDL.Record({
+    'pending' : IDL.Vec(MigrationProgress),
+    'collections' : IDL.Vec(CollectionSchema),
+  });
+  const Result_22 = IDL.Variant({
+    'Ok' : MigrationStatus,
+    'Err' : DeferredMinterError,
+  });
+  const DocumentLeaf = IDL.Record({
+    'sha256' : IDL.Text,
+    'document_id' : IDL.Nat64,
+  });
+  const DocumentsAnchor = IDL.Record({
+    'root' : IDL.Text,
+    'transaction_hash' : IDL.Text,
+    'chain_id' : IDL.Nat64,
+    'contract_id' : IDL.Nat,
+    'leaves' : IDL.Vec(DocumentLeaf),
+    'anchored_at' : IDL.Nat64,
+  });
+  const Result_23 = IDL.Variant({
+    'Ok' : DocumentsAnchor,
+    'Err' : DeferredMinterError,
+  });
+  const Result_24 = IDL.Variant({
+    'Ok' : AgencyApplicationDocument,
+    'Err' : DeferredMinterError,
+  });
   return IDL.Service({
-    'admin_cycles' : IDL.Func([], [IDL.Nat], ['query']),
-    'admin_ic_logs' : IDL.Func([Pagination], [Logs], ['query']),
-    'admin_register_agency' : IDL.Func([IDL.Principal, Agency], [], []),
-    'admin_remove_role' : IDL.Func([IDL.Principal, Role], [Result], []),
-    'admin_set_allowed_currencies' : IDL.Func([IDL.Vec(IDL.Text)], [], []),
-    'admin_set_custodians' : IDL.Func([IDL.Vec(IDL.Principal)], [Result], []),
-    'admin_set_role' : IDL.Func([IDL.Principal, Role], [], []),
+    'accept_agency_transfer' : IDL.Func([IDL.Nat64], [Result_8], []),
+    'add_agency_member' : IDL.Func(
+        [IDL.Nat64, IDL.Principal, AgencyRole],
+        [Result],
+        [],
+      ),
+    'admin_agency_applications' : IDL.Func(
+        [IDL.Opt(AgencyApplicationStatus), Pagination],
+        [Result_11],
+        ['query'],
+      ),
+    'admin_approve_agency_application' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(IDL.Text)],
+        [Result],
+        [],
+      ),
+    'admin_approve_agency_transfer' : IDL.Func([IDL.Nat64], [Result_9], []),
+    'admin_approve_proposal' : IDL.Func([IDL.Nat64], [Result_15], []),
+    'admin_configuration_history' : IDL.Func(
+        [Pagination],
+        [Result_20],
+        ['query'],
+      ),
+    'admin_cycles' : IDL.Func([], [Result_1], ['query']),
+    'admin_define_role' : IDL.Func(
+        [IDL.Text, IDL.Vec(Permission)],
+        [Result_15],
+        [],
+      ),
+    'admin_delete_role' : IDL.Func([IDL.Text], [Result_15], []),
+    'admin_comment_agency_application' : IDL.Func(
+        [IDL.Nat64, IDL.Text],
+        [Result],
+        [],
+      ),
+    'admin_audit_log' : IDL.Func(
+        [AuditFilter, Pagination],
+        [Result_17],
+        ['query'],
+      ),
+    'admin_ic_logs' : IDL.Func([Pagination], [Result_12], ['query']),
+    'admin_migration_status' : IDL.Func([], [Result_22], ['query']),
+    'admin_pause' : IDL.Func([PausableOperation, IDL.Text], [Result_21], []),
+    'admin_propose' : IDL.Func([ProposalAction], [Result_15], []),
+    'admin_reactivate_agency' : IDL.Func([IDL.Nat64], [Result_7], []),
+    'admin_register_agency' : IDL.Func(
+        [IDL.Principal, Agency],
+        [Result_5],
+        [],
+      ),
+    'admin_reject_agency_application' : IDL.Func(
+        [IDL.Nat64, IDL.Text],
+        [Result],
+        [],
+      ),
+    'admin_reject_proposal' : IDL.Func([IDL.Nat64], [Result_15], []),
+    'admin_remove_role' : IDL.Func([IDL.Principal, Role], [Result_15], []),
+    'admin_resume' : IDL.Func([PausableOperation], [Result_15], []),
+    'admin_reward_pool_alerts' : IDL.Func([], [Result_13], ['query']),
+    'admin_reward_pool_status' : IDL.Func([], [Result_14], ['query']),
+    'admin_set_agency_quota' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(AgencyQuota)],
+        [Result],
+        [],
+      ),
+    'admin_set_allowed_currencies' : IDL.Func(
+        [IDL.Vec(IDL.Text)],
+        [Result_15],
+        [],
+      ),
+    'admin_set_custodians' : IDL.Func(
+        [IDL.Vec(IDL.Principal)],
+        [Result_15],
+        [],
+      ),
+    'admin_set_reward_parameters' : IDL.Func(
+        [RewardParameters],
+        [Result_15],
+        [],
+      ),
+    'admin_set_role' : IDL.Func([IDL.Principal, Role], [Result_15], []),
+    'admin_suspend_agency' : IDL.Func([IDL.Nat64], [Result_7], []),
+    'admin_sync_reward_pool' : IDL.Func([], [Result], []),
+    'admin_update_configuration' : IDL.Func(
+        [ConfigurationUpdate],
+        [Result_15],
+        [],
+      ),
+    'admin_verify_audit_log' : IDL.Func([Pagination], [Result_18], ['query']),
+    'anchor_contract_documents' : IDL.Func([IDL.Nat], [Result_23], []),
     'close_contract' : IDL.Func([IDL.Nat], [Result], []),
     'create_contract' : IDL.Func([ContractRegistration], [Result_1], []),
     'create_real_estate' : IDL.Func([RealEstate], [Result_1], []),
     'delete_real_estate' : IDL.Func([IDL.Nat], [Result], []),
     'gas_station_set_gas_price' : IDL.Func([IDL.Nat64], [Result], []),
     'get_agencies' : IDL.Func([], [IDL.Vec(Agency)], ['query']),
-    'get_agency' : IDL.Func([IDL.Principal], [IDL.Opt(Agency)], ['query']),
+    'get_agency' : IDL.Func([IDL.Nat64], [IDL.Opt(Agency)], ['query']),
+    'get_agency_application' : IDL.Func([IDL.Nat64], [Result_4], ['query']),
+    'get_agency_application_document' : IDL.Func(
+        [IDL.Nat64, IDL.Nat64],
+        [Result_24],
+        ['query'],
+      ),
+    'get_agency_history' : IDL.Func([IDL.Nat64], [Result_3], ['query']),
+    'get_agency_members' : IDL.Func([IDL.Nat64], [Result_6], ['query']),
+    'get_agency_status' : IDL.Func([IDL.Nat64], [AgencyStatus], ['query']),
+    'get_agency_transfer' : IDL.Func([IDL.Nat64], [Result_8], ['query']),
+    'get_agency_transfers' : IDL.Func([IDL.Nat64], [Result_10], ['query']),
+    'get_configuration' : IDL.Func([], [Result_19], ['query']),
     'get_eth_address' : IDL.Func([], [Result_2], []),
+    'get_member_agency' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Opt(Agency)],
+        ['query'],
+      ),
+    'get_paused_operations' : IDL.Func([], [IDL.Vec(PauseState)], ['query']),
+    'get_permissions' : IDL.Func(
+        [IDL.Principal],
+        [IDL.Vec(Permission)],
+        ['query'],
+      ),
+    'get_proposal' : IDL.Func([IDL.Nat64], [IDL.Opt(Proposal)], ['query']),
+    'get_proposal_config' : IDL.Func([], [ProposalConfig], ['query']),
+    'get_proposals' : IDL.Func(
+        [IDL.Opt(ProposalStatus), Pagination],
+        [IDL.Vec(Proposal)],
+        ['query'],
+      ),
+    'get_reward_parameters' : IDL.Func([], [RewardParameters], ['query']),
+    'get_roles' : IDL.Func([], [IDL.Vec(RoleDefinition)], ['query']),
+    'get_sns_governance' : IDL.Func([], [IDL.Opt(IDL.Principal)], ['query']),
     'http_request' : IDL.Func([HttpRequest], [HttpResponse], ['query']),
-    'remove_agency' : IDL.Func([IDL.Principal], [Result], []),
+    'remove_agency' : IDL.Func([IDL.Nat64], [Result_7], []),
+    'reject_agency_transfer' : IDL.Func([IDL.Nat64], [Result], []),
+    'remove_agency_member' : IDL.Func(
+        [IDL.Nat64, IDL.Principal],
+        [Result],
+        [],
+      ),
+    'request_agency_transfer' : IDL.Func(
+        [IDL.Nat64, IDL.Nat64, IDL.Vec(IDL.Nat), IDL.Vec(IDL.Nat)],
+        [Result_5],
+        [],
+      ),
+    'submit_agency_application' : IDL.Func(
+        [Agency, IDL.Vec(AgencyApplicationDocument)],
+        [Result_5],
+        [],
+      ),
+    'update_agency' : IDL.Func([AgencyUpdate], [Result], []),
     'update_real_estate' : IDL.Func([IDL.Nat, RealEstate], [Result], []),
+    'validate_admin_approve_agency_application' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(IDL.Text)],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_approve_agency_transfer' : IDL.Func(
+        [IDL.Nat64],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_approve_proposal' : IDL.Func(
+        [IDL.Nat64],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_comment_agency_application' : IDL.Func(
+        [IDL.Nat64, IDL.Text],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_define_role' : IDL.Func(
+        [IDL.Text, IDL.Vec(Permission)],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_delete_role' : IDL.Func([IDL.Text], [Result_16], ['query']),
+    'validate_admin_pause' : IDL.Func(
+        [PausableOperation, IDL.Text],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_propose' : IDL.Func(
+        [ProposalAction],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_reactivate_agency' : IDL.Func(
+        [IDL.Nat64],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_register_agency' : IDL.Func(
+        [IDL.Principal, Agency],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_reject_agency_application' : IDL.Func(
+        [IDL.Nat64, IDL.Text],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_reject_proposal' : IDL.Func(
+        [IDL.Nat64],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_remove_role' : IDL.Func(
+        [IDL.Principal, Role],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_resume' : IDL.Func(
+        [PausableOperation],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_set_agency_quota' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(AgencyQuota)],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_set_allowed_currencies' : IDL.Func(
+        [IDL.Vec(IDL.Text)],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_set_custodians' : IDL.Func(
+        [IDL.Vec(IDL.Principal)],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_set_reward_parameters' : IDL.Func(
+        [RewardParameters],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_set_role' : IDL.Func(
+        [IDL.Principal, Role],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_suspend_agency' : IDL.Func(
+        [IDL.Nat64],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_admin_sync_reward_pool' : IDL.Func([], [Result_16], ['query']),
+    'validate_admin_update_configuration' : IDL.Func(
+        [ConfigurationUpdate],
+        [Result_16],
+        ['query'],
+      ),
+    'validate_gas_station_set_gas_price' : IDL.Func(
+        [IDL.Nat64],
+        [Result_16],
+        ['query'],
+      ),
   });
 };
 export const init = ({ IDL }) => {
@@ -260,6 +988,7 @@ export const init = ({ IDL }) => {
     'evm_rpc_api' : IDL.Opt(IDL.Text),
     'allowed_currencies' : IDL.Vec(IDL.Text),
     'deferred_data' : IDL.Principal,
+    'reward_pool_deploy_block' : IDL.Nat64,
     'reward_pool' : IDL.Text,
     'custodians' : IDL.Vec(IDL.Principal),
     'chain_id' : IDL.Nat64,
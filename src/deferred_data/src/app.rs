@@ -1,15 +1,25 @@
+mod audit_log;
 mod configuration;
+mod images;
 mod inspect;
 mod memory;
+mod metrics;
+mod migrations;
+mod mime_types;
+mod siwe;
 mod storage;
 #[cfg(test)]
 pub mod test_utils;
 
 use candid::{Nat, Principal};
 use did::deferred::{
-    Contract, ContractDocument, ContractDocumentData, DataContractError, DeferredDataError,
-    DeferredDataInitData, DeferredDataResult, GenericValue, RealEstate, RestrictedProperty,
-    RestrictionLevel,
+    AgencyId, AgencyQuota, AgencyReport, AgencyStatus, AgencyTransfer, AgencyTransferAuditEntry,
+    AgencyUsage, AuditEntry, AuditFilter, AuditIntegrity, CollectionDigest, Contract,
+    ContractDocument, ContractDocumentData, DataConfigurationError, DataContractError,
+    DeferredDataError, DeferredDataInitData, DeferredDataResult, DocumentHistory, DocumentLeaf,
+    DocumentProof, DocumentUpload, DocumentsAnchor, DocumentsMerkleTree, ExportChunk,
+    ExportCollection, ExportCursor, GenericValue, ImageSize, MigrationStatus, ReadOnlyMode,
+    RealEstate, RestrictedProperty, RestrictionLevel, StorageUsage, StreamingSession,
 };
 use did::ID;
 use ethers_core::abi::ethereum_types::H520;
@@ -17,17 +27,42 @@ use ic_log::did::Pagination;
 use ic_log::writer::Logs;
 use ic_log::{init_log, take_memory_records};
 
+pub use self::audit_log::AuditLog;
 use self::configuration::Configuration;
 pub use self::inspect::Inspect;
-pub use self::storage::{ContractStorage, RealEstateStorage};
-use crate::utils::{caller, cycles};
+pub use self::metrics::Metrics;
+use self::migrations::Migrations;
+use self::mime_types::{MimeTypes, MIME_SNIFF_SIZE};
+use self::siwe::CLOCK_SKEW;
+pub use self::storage::{AgencyMemberStorage, AgencyStorage, ContractStorage, RealEstateStorage};
+use self::storage::{
+    DocumentAnchorStorage, ExportStorage, NonceStorage, StreamingSessionStorage, UploadStorage,
+    UsageStorage,
+};
+use crate::utils::{caller, cycles, time};
+
+/// Time after which an unfinished document upload is discarded (24 hours, nanoseconds)
+const UPLOAD_TIMEOUT: u64 = 24 * 60 * 60 * 1_000_000_000;
+/// Interval between two removals of the expired document uploads (1 hour)
+const UPLOAD_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Interval between two removals of the expired nonces (1 hour)
+const NONCE_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Cycles burned to store a GiB for a second on a 13-node subnet
+const STORAGE_CYCLES_PER_GIB_SECOND: u128 = 127_000;
 
 /// A message used to verify the ownership of a contract (seller or buyer)
 pub struct SignedMessage {
     pub message: String,
     pub signature: H520,
+    /// Id of the streaming session which a chunk of a streamed document is requested within, by
+    /// sending the message again once its nonce has been consumed by the first request
+    pub session: Option<String>,
 }
 
+/// Deferred data canister API.
+///
+/// The permissions required by the `admin_` and the minter methods are checked by the endpoint
+/// guard before the call is dispatched here.
 pub struct DeferredData;
 
 impl DeferredData {
@@ -42,44 +77,283 @@ impl DeferredData {
         // set the log settings
         Configuration::set_log_settings(init_args.log_settings)
             .expect("failed to set log settings");
+
+        Migrations::init();
+
+        Self::set_timers();
     }
 
     pub fn post_upgrade() {
         init_log(&Configuration::get_log_settings()).expect("failed to init log");
+
+        // migrate the collections stored with an older schema
+        Migrations::start();
+        // audit log entries recorded before the audit log was indexed
+        AuditLog::index_entries();
+
+        Self::set_timers();
     }
 
-    /// Set the minter of the deferred data canister.
-    pub fn admin_set_minter(minter: Principal) -> DeferredDataResult<()> {
-        if !Inspect::inspect_is_owner(caller()) {
-            return Err(DeferredDataError::Unauthorized);
+    /// Set the timers to periodically remove the expired document uploads and nonces
+    fn set_timers() {
+        if cfg!(test) {
+            return;
         }
 
+        ic_cdk_timers::set_timer_interval(UPLOAD_CLEANUP_INTERVAL, Self::remove_expired_uploads);
+        ic_cdk_timers::set_timer_interval(NONCE_CLEANUP_INTERVAL, Self::remove_expired_nonces);
+    }
+
+    /// Remove the document uploads which haven't been finalized within [`UPLOAD_TIMEOUT`]
+    fn remove_expired_uploads() {
+        let expired = UploadStorage::remove_expired(time().saturating_sub(UPLOAD_TIMEOUT));
+        if !expired.is_empty() {
+            log::info!("Removed expired document uploads {expired:?}");
+        }
+    }
+
+    /// Remove the consumed nonces and the streaming sessions of the signed messages which have
+    /// expired
+    fn remove_expired_nonces() {
+        let removed = NonceStorage::remove_expired(time());
+        if removed > 0 {
+            log::info!("Removed {removed} expired nonces");
+        }
+        let removed = StreamingSessionStorage::remove_expired(time());
+        if removed > 0 {
+            log::info!("Removed {removed} expired streaming sessions");
+        }
+    }
+
+    /// Get the schema version of the stored collections and the pending migrations
+    pub fn admin_migration_status() -> MigrationStatus {
+        Migrations::status()
+    }
+
+    /// Set the minter of the deferred data canister.
+    pub fn admin_set_minter(minter: Principal) -> DeferredDataResult<()> {
         log::info!("Set minter to {minter}");
 
         Configuration::set_minter(minter)
     }
 
-    pub fn admin_cycles() -> Nat {
-        if !Inspect::inspect_is_owner(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Set the SNS governance canister, which is granted the owner permission.
+    pub fn admin_set_sns_governance(governance: Option<Principal>) -> DeferredDataResult<()> {
+        Self::validate_admin_set_sns_governance(governance)?;
+        log::info!("Set SNS governance to {governance:?}");
+
+        Configuration::set_sns_governance(governance)
+    }
+
+    pub fn get_sns_governance() -> Option<Principal> {
+        Configuration::get_sns_governance()
+    }
+
+    /// Enable the read-only mode with the provided reason, or disable it if `None`.
+    ///
+    /// While the read-only mode is enabled, every write but the `admin_` ones is rejected
+    pub fn admin_set_read_only(reason: Option<String>) -> DeferredDataResult<()> {
+        let mode = reason.map(|reason| ReadOnlyMode {
+            enabled_by: caller(),
+            enabled_at: time(),
+            reason,
+        });
+        match &mode {
+            Some(mode) => log::warn!(
+                "Read-only mode enabled by {}: {}",
+                mode.enabled_by,
+                mode.reason
+            ),
+            None => log::info!("Read-only mode disabled"),
         }
-        cycles()
+
+        Configuration::set_read_only(mode)
     }
 
-    pub fn admin_ic_logs(pagination: Pagination) -> Logs {
-        if !Inspect::inspect_is_owner(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Get the read-only mode, if enabled
+    pub fn get_read_only_mode() -> Option<ReadOnlyMode> {
+        Configuration::get_read_only()
+    }
+
+    /// Set the MIME types allowed for the uploaded documents.
+    ///
+    /// The documents already stored are kept, whatever their type
+    pub fn admin_set_allowed_mime_types(mime_types: Vec<String>) -> DeferredDataResult<()> {
+        Self::validate_admin_set_allowed_mime_types(mime_types.clone())?;
+        let mut mime_types = mime_types
+            .iter()
+            .map(|mime_type| MimeTypes::normalize(mime_type))
+            .collect::<Vec<_>>();
+        mime_types.sort();
+        mime_types.dedup();
+        log::info!("Set allowed MIME types to {mime_types:?}");
+
+        Configuration::set_allowed_mime_types(mime_types)
+    }
+
+    /// Set the custom domains of the canister, on which the signed messages can be addressed to it
+    /// besides the domains of the Internet Computer
+    pub fn admin_set_custom_domains(domains: Vec<String>) -> DeferredDataResult<()> {
+        Self::validate_admin_set_custom_domains(domains.clone())?;
+        let mut domains = domains
+            .iter()
+            .map(|domain| domain.to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        domains.sort();
+        domains.dedup();
+        log::info!("Set custom domains to {domains:?}");
+
+        Configuration::set_custom_domains(domains)
+    }
+
+    /// Get the custom domains of the canister
+    pub fn get_custom_domains() -> Vec<String> {
+        Configuration::get_custom_domains()
+    }
+
+    /// Set the storage quota of the agency as the owner, or remove it if `None`.
+    ///
+    /// The call goes through the same checks of the SNS proposal
+    pub fn admin_set_agency_quota(
+        agency: AgencyId,
+        quota: Option<AgencyQuota>,
+    ) -> DeferredDataResult<()> {
+        Self::validate_admin_set_agency_quota(agency, quota)?;
+
+        Self::set_agency_quota(agency, quota)
+    }
+
+    /// Get the MIME types allowed for the uploaded documents
+    pub fn get_allowed_mime_types() -> Vec<String> {
+        Configuration::get_allowed_mime_types()
+    }
+
+    /// Validate the SNS proposal to set the minter and render its payload.
+    pub fn validate_admin_set_minter(minter: Principal) -> DeferredDataResult<String> {
+        if minter == Principal::anonymous() {
+            return Err(DataConfigurationError::AnonymousMinter.into());
         }
 
+        Ok(format!("Set minter to {minter}"))
+    }
+
+    /// Validate the SNS proposal to set the allowed MIME types and render its payload.
+    pub fn validate_admin_set_allowed_mime_types(
+        mime_types: Vec<String>,
+    ) -> DeferredDataResult<String> {
+        if let Some(invalid) = mime_types
+            .iter()
+            .find(|mime_type| !MimeTypes::is_valid(mime_type))
+        {
+            return Err(DataConfigurationError::InvalidMimeType(invalid.clone()).into());
+        }
+
+        Ok(format!("Set allowed MIME types: {}", mime_types.join(", ")))
+    }
+
+    /// Validate the SNS proposal to set the custom domains and render its payload.
+    pub fn validate_admin_set_custom_domains(domains: Vec<String>) -> DeferredDataResult<String> {
+        if let Some(invalid) = domains
+            .iter()
+            .find(|domain| !siwe::is_valid_domain(&domain.to_ascii_lowercase()))
+        {
+            return Err(DataConfigurationError::InvalidDomain(invalid.clone()).into());
+        }
+
+        Ok(format!("Set custom domains: {}", domains.join(", ")))
+    }
+
+    /// Validate the SNS proposal to set the storage quota of the agency and render its payload.
+    ///
+    /// The agency must be known to the canister, which learns it from the members pushed by the
+    /// minter.
+    pub fn validate_admin_set_agency_quota(
+        agency: AgencyId,
+        quota: Option<AgencyQuota>,
+    ) -> DeferredDataResult<String> {
+        if !AgencyMemberStorage::has_members(agency) {
+            return Err(DataContractError::AgencyNotFound(agency).into());
+        }
+
+        Ok(AgencyQuota::describe_change(agency, quota.as_ref()))
+    }
+
+    /// Validate the SNS proposal to enable or disable the read-only mode and render its payload.
+    pub fn validate_admin_set_read_only(reason: Option<String>) -> DeferredDataResult<String> {
+        match reason {
+            Some(reason) => Ok(format!("Enable read-only mode: {reason}")),
+            None => Ok("Disable read-only mode".to_string()),
+        }
+    }
+
+    /// Validate the SNS proposal to set the SNS governance and render its payload.
+    pub fn validate_admin_set_sns_governance(
+        governance: Option<Principal>,
+    ) -> DeferredDataResult<String> {
+        match governance {
+            Some(governance) if governance == Principal::anonymous() => {
+                Err(DataConfigurationError::AnonymousSnsGovernance.into())
+            }
+            Some(governance) => Ok(format!("Set SNS governance to {governance}")),
+            None => Ok("Unset SNS governance".to_string()),
+        }
+    }
+
+    pub fn admin_cycles() -> Nat {
+        cycles()
+    }
+
+    pub fn admin_ic_logs(pagination: Pagination) -> Logs {
         take_memory_records(pagination.count, pagination.offset)
     }
 
+    /// Get the audit log entries matching the filter, from the oldest one
+    pub fn admin_audit_log(filter: AuditFilter, pagination: Pagination) -> Vec<AuditEntry> {
+        AuditLog::get_entries(&filter, pagination)
+    }
+
+    /// Verify the hash chain of the audit log entries in the provided page
+    pub fn admin_verify_audit_log(pagination: Pagination) -> AuditIntegrity {
+        AuditLog::verify(pagination)
+    }
+
+    /// Export the chunk of records of the collection following the position `after`.
+    ///
+    /// The position of the next chunk is returned by [`ExportChunk::next`]
+    pub fn admin_export(collection: ExportCollection, after: Option<ExportCursor>) -> ExportChunk {
+        ExportStorage::export_chunk(collection, after)
+    }
+
+    /// Import a chunk exported by another deferred data canister with [`Self::admin_export`].
+    pub fn admin_import(chunk: ExportChunk) -> DeferredDataResult<()> {
+        let (collection, records) = (chunk.collection, chunk.records.len());
+        ExportStorage::import_chunk(chunk)?;
+        log::info!("Imported {records} records of {collection:?}");
+
+        Ok(())
+    }
+
+    /// Get the number of records and the hash of the page of the collection following the key
+    /// `after`.
+    ///
+    /// The key of the next page is returned in [`CollectionDigest::next`]
+    pub fn admin_export_digest(
+        collection: ExportCollection,
+        after: Option<Vec<u8>>,
+    ) -> CollectionDigest {
+        ExportStorage::digest(collection, after)
+    }
+
+    /// Compare a page of a collection with its digest computed by the canister it has been
+    /// exported from.
+    ///
+    /// Returns whether the page matches
+    pub fn admin_verify_import(expected: CollectionDigest) -> bool {
+        ExportStorage::verify_digest(&expected)
+    }
+
     /// Insert a contract into the ledger
     pub fn create_contract(contract: Contract) -> DeferredDataResult<()> {
-        if !Inspect::inspect_is_minter(caller()) {
-            return Err(DeferredDataError::Unauthorized);
-        }
-
         let contract_id = contract.id.clone();
         log::debug!("Creating contract {contract_id}");
         ContractStorage::insert_contract(contract);
@@ -90,10 +364,6 @@ impl DeferredData {
 
     /// Close a contract
     pub fn close_contract(id: ID) -> DeferredDataResult<()> {
-        if !Inspect::inspect_is_minter(caller()) {
-            return Err(DeferredDataError::Unauthorized);
-        }
-
         log::info!("Closing contract {id}");
 
         ContractStorage::close_contract(&id)
@@ -148,8 +418,248 @@ impl DeferredData {
         data: Vec<u8>,
     ) -> DeferredDataResult<u64> {
         Inspect::inspect_modify_contract(caller(), &contract_id)?;
+        MimeTypes::validate(&document.mime_type, &data)?;
 
-        ContractStorage::upload_contract_document(&contract_id, document, data)
+        ContractStorage::upload_contract_document(&contract_id, document, data, caller())
+    }
+
+    /// Begin the upload in chunks of a contract document, declaring its size and SHA-256 (hex).
+    ///
+    /// Returns the ID of the upload.
+    pub fn begin_contract_document_upload(
+        contract_id: ID,
+        document: ContractDocument,
+        sha256: String,
+    ) -> DeferredDataResult<u64> {
+        Inspect::inspect_modify_contract(caller(), &contract_id)?;
+        // reject the upload early, rather than once all of its chunks have been received
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(DeferredDataError::Contract(
+                DataContractError::InvalidDocumentHash(sha256),
+            ));
+        }
+        MimeTypes::require_allowed(&document.mime_type)?;
+        let contract = ContractStorage::get_contract(&contract_id).ok_or(
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone())),
+        )?;
+        UsageStorage::require_within_quota(&contract, StorageUsage::document(document.size))?;
+
+        let upload_id = UploadStorage::begin(DocumentUpload {
+            contract_id: contract_id.clone(),
+            document,
+            sha256: sha256.to_lowercase(),
+            uploaded_by: caller(),
+            started_at: time(),
+            received: 0,
+            chunks: 0,
+        })?;
+        log::debug!("Began upload {upload_id} of a document of contract {contract_id}");
+
+        Ok(upload_id)
+    }
+
+    /// Append a chunk to a contract document upload
+    pub fn upload_contract_document_chunk(upload_id: u64, data: Vec<u8>) -> DeferredDataResult<()> {
+        Inspect::inspect_upload(caller(), upload_id)?;
+
+        UploadStorage::append_chunk(upload_id, data)
+    }
+
+    /// Finalize a contract document upload, storing the document if the received data matches
+    /// the declared size and SHA-256.
+    ///
+    /// The data is hashed a chunk at a time and its type is detected from its first bytes, so
+    /// that it's loaded whole only once it has been verified. The upload is discarded once
+    /// verified, or if its hash or type doesn't match. Returns the ID of the document.
+    pub fn finalize_contract_document_upload(upload_id: u64) -> DeferredDataResult<u64> {
+        let upload = Inspect::inspect_upload(caller(), upload_id)?;
+        Inspect::inspect_modify_contract(caller(), &upload.contract_id)?;
+
+        if upload.received != upload.document.size {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(upload.document.size, upload.received),
+            ));
+        }
+
+        let sha256 = UploadStorage::sha256(upload_id);
+        if sha256 != upload.sha256 {
+            UploadStorage::remove(upload_id);
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentHashMismatch(upload.sha256, sha256),
+            ));
+        }
+        let prefix = UploadStorage::prefix(upload_id, MIME_SNIFF_SIZE);
+        if let Err(err) = MimeTypes::validate(&upload.document.mime_type, &prefix) {
+            UploadStorage::remove(upload_id);
+            return Err(err);
+        }
+
+        // the upload is removed before storing the document, so that the quota doesn't count it
+        // both as pending and as stored
+        let data = UploadStorage::data(upload_id);
+        UploadStorage::remove(upload_id);
+
+        ContractStorage::upload_contract_document(
+            &upload.contract_id,
+            upload.document,
+            data,
+            upload.uploaded_by,
+        )
+    }
+
+    /// Update the metadata of a contract document, such as its name or access list.
+    ///
+    /// A new MIME type must match the data of the document. Returns the number of the new
+    /// version.
+    pub fn update_contract_document(
+        contract_id: ID,
+        document_id: u64,
+        document: ContractDocument,
+    ) -> DeferredDataResult<u64> {
+        Inspect::inspect_modify_contract(caller(), &contract_id)?;
+        let changes_type = ContractStorage::get_contract(&contract_id)
+            .and_then(|contract| {
+                contract
+                    .documents
+                    .into_iter()
+                    .find(|(id, _)| *id == document_id)
+            })
+            .is_some_and(|(_, current)| current.mime_type != document.mime_type);
+        if changes_type {
+            let current = ContractStorage::get_contract_document(&contract_id, document_id)?;
+            MimeTypes::validate(&document.mime_type, &current.data)?;
+        }
+
+        let version = ContractStorage::update_contract_document(
+            &contract_id,
+            document_id,
+            document,
+            caller(),
+        )?;
+        log::info!("Document {document_id} of contract {contract_id} updated to version {version}");
+
+        Ok(version)
+    }
+
+    /// Replace the data of a contract document, keeping the previous versions.
+    ///
+    /// Returns the number of the new version.
+    pub fn replace_contract_document(
+        contract_id: ID,
+        document_id: u64,
+        document: ContractDocument,
+        data: Vec<u8>,
+    ) -> DeferredDataResult<u64> {
+        Inspect::inspect_modify_contract(caller(), &contract_id)?;
+        MimeTypes::validate(&document.mime_type, &data)?;
+
+        let version = ContractStorage::replace_contract_document(
+            &contract_id,
+            document_id,
+            document,
+            data,
+            caller(),
+        )?;
+        log::info!(
+            "Document {document_id} of contract {contract_id} replaced with version {version}"
+        );
+
+        Ok(version)
+    }
+
+    /// Delete a contract document, purging the data of its versions if `purge` is set
+    pub fn delete_contract_document(
+        contract_id: ID,
+        document_id: u64,
+        purge: bool,
+    ) -> DeferredDataResult<()> {
+        Inspect::inspect_modify_contract(caller(), &contract_id)?;
+
+        ContractStorage::delete_contract_document(&contract_id, document_id, purge)?;
+        log::info!("Document {document_id} of contract {contract_id} deleted (purge: {purge})");
+
+        Ok(())
+    }
+
+    /// Get the version history of a contract document.
+    ///
+    /// Only the minter and the members of the agency of the contract can read it.
+    pub fn get_contract_document_history(
+        contract_id: ID,
+        document_id: u64,
+    ) -> DeferredDataResult<DocumentHistory> {
+        let contract = ContractStorage::get_contract(&contract_id).ok_or(
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone())),
+        )?;
+        if !Inspect::inspect_is_minter(caller())
+            && !AgencyMemberStorage::is_member(caller(), contract.agency)
+        {
+            return Err(DeferredDataError::Unauthorized);
+        }
+
+        ContractStorage::get_contract_document_history(&contract_id, document_id)
+    }
+
+    /// Get the leaves of the Merkle tree of the documents of a contract, whose root is anchored
+    /// by the minter
+    pub fn get_contract_document_leaves(contract_id: ID) -> DeferredDataResult<Vec<DocumentLeaf>> {
+        ContractStorage::get_contract_document_leaves(&contract_id)
+    }
+
+    /// Store the anchor of the documents of a contract, once the minter has anchored its root on
+    /// Ethereum.
+    ///
+    /// The root must match the one of the leaves of the anchor.
+    pub fn set_documents_anchor(anchor: DocumentsAnchor) -> DeferredDataResult<()> {
+        ContractStorage::get_contract(&anchor.contract_id).ok_or(DeferredDataError::Contract(
+            DataContractError::ContractNotFound(anchor.contract_id.clone()),
+        ))?;
+
+        let root = DocumentsMerkleTree::new(&anchor.leaves)
+            .map(|tree| tree.root())
+            .unwrap_or_default();
+        if root != anchor.root {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentsRootMismatch(anchor.root, root),
+            ));
+        }
+
+        log::info!(
+            "Documents of contract {} anchored with root {} in transaction {}",
+            anchor.contract_id,
+            anchor.root,
+            anchor.transaction_hash
+        );
+        DocumentAnchorStorage::insert(anchor);
+
+        Ok(())
+    }
+
+    /// Get the proof that a document is included in the last anchored root of the documents of
+    /// its contract, with the anchoring transaction
+    pub fn verify_contract_document(
+        contract_id: ID,
+        document_id: u64,
+    ) -> DeferredDataResult<DocumentProof> {
+        let not_anchored =
+            DeferredDataError::Contract(DataContractError::DocumentNotAnchored(document_id));
+        let anchor = DocumentAnchorStorage::get(&contract_id).ok_or(not_anchored.clone())?;
+        let index = anchor
+            .leaves
+            .iter()
+            .position(|leaf| leaf.document_id == document_id)
+            .ok_or(not_anchored.clone())?;
+        let tree = DocumentsMerkleTree::new(&anchor.leaves).ok_or(not_anchored)?;
+
+        Ok(DocumentProof {
+            document_id,
+            sha256: anchor.leaves[index].sha256.clone(),
+            proof: tree.proof(index),
+            root: anchor.root,
+            transaction_hash: anchor.transaction_hash,
+            chain_id: anchor.chain_id,
+            anchored_at: anchor.anchored_at,
+        })
     }
 
     /// Get a contract document
@@ -158,8 +668,117 @@ impl DeferredData {
         document_id: u64,
         signature: Option<SignedMessage>,
     ) -> DeferredDataResult<ContractDocumentData> {
+        Self::inspect_document_access(&contract_id, document_id, signature)?;
+
+        ContractStorage::get_contract_document(&contract_id, document_id)
+    }
+
+    /// Check the access to a contract document and get the metadata of its current version, whose
+    /// data can then be read a range at a time with [`DeferredData::read_contract_document`]
+    pub fn get_contract_document_metadata(
+        contract_id: ID,
+        document_id: u64,
+        signature: Option<SignedMessage>,
+    ) -> DeferredDataResult<ContractDocument> {
+        Self::inspect_document_access(&contract_id, document_id, signature)
+    }
+
+    /// Read the bytes in the range `[start, end)` of a contract document, whose access has been
+    /// checked by [`DeferredData::get_contract_document_metadata`].
+    ///
+    /// Only the chunks of the data which contain the range are read
+    pub fn read_contract_document(
+        contract_id: &ID,
+        document_id: u64,
+        start: u64,
+        end: u64,
+    ) -> DeferredDataResult<Vec<u8>> {
+        ContractStorage::get_contract_document_range(contract_id, document_id, start, end)
+    }
+
+    /// Open the streaming session of a restricted document with the id `session`, once the
+    /// request has consumed the nonce of the signed message, or get the one already open.
+    ///
+    /// Every request of the document with the message, such as a `Range` request, is then served
+    /// until the message expires, while the chunks of the streamed bodies are served only to the
+    /// continuations with the session id. The HTTP streaming callback is a query, so the session
+    /// can't be closed once the last chunk is served: it's removed when the signed message
+    /// expires.
+    /// Nothing is opened for the documents which don't consume the nonce, such as the public ones.
+    ///
+    /// Returns the id of the session to be sent in the streaming tokens
+    pub fn open_streaming_session(
+        contract_id: ID,
+        document_id: u64,
+        signature: SignedMessage,
+        session: String,
+    ) -> DeferredDataResult<String> {
+        let (_, message) =
+            Inspect::inspect_signature(&contract_id, signature.signature, signature.message)?;
+        if let Some(open) = StreamingSessionStorage::get(&message.nonce) {
+            return Ok(open.id);
+        }
+        if !NonceStorage::is_consumed(&message.nonce) {
+            return Ok(session);
+        }
+
+        StreamingSessionStorage::open(
+            &message.nonce,
+            StreamingSession {
+                id: session.clone(),
+                contract_id,
+                document_id,
+                expires_at: message.expiration_time.saturating_add(CLOCK_SKEW),
+            },
+        );
+
+        Ok(session)
+    }
+
+    /// Get a version of a contract document.
+    ///
+    /// The access is granted by the access list of the current version.
+    pub fn get_contract_document_version(
+        contract_id: ID,
+        document_id: u64,
+        version: u64,
+        signature: Option<SignedMessage>,
+    ) -> DeferredDataResult<ContractDocumentData> {
+        Self::inspect_document_access(&contract_id, document_id, signature)?;
+
+        ContractStorage::get_contract_document_version(&contract_id, document_id, version)
+    }
+
+    /// Get the thumbnail or the preview of an image contract document.
+    ///
+    /// The access is granted by the access list of the document
+    pub fn get_contract_document_preview(
+        contract_id: ID,
+        document_id: u64,
+        size: ImageSize,
+        signature: Option<SignedMessage>,
+    ) -> DeferredDataResult<ContractDocumentData> {
+        let document = Self::inspect_document_access(&contract_id, document_id, signature)?;
+        let image =
+            ContractStorage::get_contract_document_preview(&contract_id, document_id, size)?;
+
+        Ok(ContractDocumentData {
+            data: image.data,
+            mime_type: image.mime_type,
+            name: document.name,
+        })
+    }
+
+    /// Check whether the caller can access a document listed in the contract.
+    ///
+    /// Returns the properties of the document
+    fn inspect_document_access(
+        contract_id: &ID,
+        document_id: u64,
+        signature: Option<SignedMessage>,
+    ) -> DeferredDataResult<ContractDocument> {
         // check if we can access document
-        let contract = ContractStorage::get_contract(&contract_id).ok_or(
+        let contract = ContractStorage::get_contract(contract_id).ok_or(
             DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone())),
         )?;
 
@@ -167,26 +786,59 @@ impl DeferredData {
             .documents
             .iter()
             .find(|(id, _)| id == &document_id)
-            .map(|(_, props)| props)
+            .map(|(_, props)| props.clone())
             .ok_or(DeferredDataError::Contract(
                 DataContractError::DocumentNotFound(document_id),
             ))?;
 
         // get caller access level
-        let access_level = if contract.agency == caller() {
-            RestrictionLevel::Agent
-        } else if let Some(signature) = signature {
-            Inspect::inspect_signature(&contract.id, signature.signature, signature.message)?
-        } else {
-            RestrictionLevel::Public
-        };
+        let (access_level, signed_message) =
+            if AgencyMemberStorage::is_member(caller(), contract.agency) {
+                (RestrictionLevel::Agent, None)
+            } else if let Some(signature) = signature {
+                let (access_level, message) = Inspect::inspect_signature(
+                    &contract.id,
+                    signature.signature,
+                    signature.message,
+                )?;
+                (access_level, Some((message, signature.session)))
+            } else {
+                (RestrictionLevel::Public, None)
+            };
 
         // check if we have access
-        if document_props.access_list.contains(&access_level) {
-            ContractStorage::get_contract_document(&contract_id, document_id)
-        } else {
-            Err(DeferredDataError::Unauthorized)
+        if !document_props.access_list.contains(&access_level) {
+            return Err(DeferredDataError::Unauthorized);
+        }
+
+        // the first request of a restricted document with a signed message consumes its nonce,
+        // then the document is served with the message only within the session opened by it
+        match signed_message {
+            Some(_)
+                if document_props
+                    .access_list
+                    .contains(&RestrictionLevel::Public) => {}
+            Some((message, session)) => {
+                let open = StreamingSessionStorage::get(&message.nonce)
+                    .filter(|open| open.grants(contract_id, document_id));
+                match (open, session) {
+                    // a new request of the document, such as a `Range` request
+                    (Some(_), None) => {}
+                    // a chunk of a streamed body
+                    (Some(open), Some(session)) if open.id == session => {}
+                    (None, None) => {
+                        NonceStorage::consume(
+                            &message.nonce,
+                            message.expiration_time.saturating_add(CLOCK_SKEW),
+                        )?;
+                    }
+                    _ => return Err(DeferredDataError::Unauthorized),
+                }
+            }
+            None => {}
         }
+
+        Ok(document_props)
     }
 
     /// Redact restricted properties from contract based on access level
@@ -198,10 +850,12 @@ impl DeferredData {
         let mut redacted_properties = Vec::with_capacity(contract.restricted_properties.len());
 
         // get caller access level
-        let access_level = if contract.agency == caller {
+        let access_level = if AgencyMemberStorage::is_member(caller, contract.agency) {
             Some(RestrictionLevel::Agent)
         } else if let Some(signature) = signature {
-            Inspect::inspect_signature(&contract.id, signature.signature, signature.message).ok()
+            Inspect::inspect_signature(&contract.id, signature.signature, signature.message)
+                .ok()
+                .map(|(access_level, _)| access_level)
         } else {
             Some(RestrictionLevel::Public)
         };
@@ -223,112 +877,711 @@ impl DeferredData {
         contract.restricted_properties = redacted_properties;
     }
 
-    /// Create a real estate
-    pub fn create_real_estate(real_estate: RealEstate) -> DeferredDataResult<ID> {
-        if !Inspect::inspect_is_minter(caller()) {
-            return Err(DeferredDataError::Unauthorized);
+    /// Set the agency of a member, or remove the membership if `agency` is `None`.
+    ///
+    /// Members of an agency have the same access level of the agency to its contracts
+    pub fn set_agency_member(
+        member: Principal,
+        agency: Option<AgencyId>,
+    ) -> DeferredDataResult<()> {
+        match agency {
+            Some(agency) => log::info!("{member} is now a member of agency {agency}"),
+            None => log::info!("{member} is no longer a member of any agency"),
+        }
+        AgencyMemberStorage::set(member, agency);
+
+        Ok(())
+    }
+
+    /// Set the status of the agency.
+    ///
+    /// The real estates of a suspended agency are not listed.
+    /// Returns the records of the agency affected by the change.
+    pub fn set_agency_status(
+        agency: AgencyId,
+        status: AgencyStatus,
+    ) -> DeferredDataResult<AgencyReport> {
+        AgencyStorage::set_status(agency, status);
+        log::info!("Agency {agency} status set to {status:?}");
+
+        Ok(AgencyStorage::report(agency))
+    }
+
+    /// Remove the agency, deleting all of its real estates.
+    ///
+    /// The agency must not have open contracts.
+    pub fn remove_agency(agency: AgencyId) -> DeferredDataResult<AgencyReport> {
+        let report = AgencyStorage::remove(agency)?;
+        log::info!(
+            "Agency {agency} removed; deleted {} real estates",
+            report.real_estates.len()
+        );
+
+        Ok(report)
+    }
+
+    /// Move the real estates and the open contracts of an approved transfer to the gaining agency
+    pub fn transfer_agency_records(
+        transfer: AgencyTransfer,
+    ) -> DeferredDataResult<AgencyTransferAuditEntry> {
+        let entry = AgencyStorage::transfer(&transfer)?;
+        log::info!(
+            "Agency transfer {}: moved {} real estates and {} contracts from {} to {}",
+            entry.transfer_id,
+            entry.real_estates.len(),
+            entry.contracts.len(),
+            entry.from,
+            entry.to
+        );
+
+        Ok(entry)
+    }
+
+    /// Get the audit entries of the transfers involving the agency
+    pub fn get_agency_transfers_audit(agency: AgencyId) -> Vec<AgencyTransferAuditEntry> {
+        AgencyStorage::get_transfers_audit(agency)
+    }
+
+    /// Set the storage quota of the agency, or remove it if `None`.
+    ///
+    /// The documents already stored are kept even if the agency exceeds the new quota.
+    pub fn set_agency_quota(
+        agency: AgencyId,
+        quota: Option<AgencyQuota>,
+    ) -> DeferredDataResult<()> {
+        match quota {
+            Some(quota) => log::info!("Agency {agency} storage quota set to {quota:?}"),
+            None => log::info!("Agency {agency} storage quota removed"),
+        }
+        UsageStorage::set_quota(agency, quota);
+
+        Ok(())
+    }
+
+    /// Get the storage usage of the agency, with its quota and the estimated cost of storing its
+    /// documents.
+    ///
+    /// Only the owner, the minter and the members of the agency can read it.
+    pub fn get_agency_usage(agency: AgencyId) -> DeferredDataResult<AgencyUsage> {
+        if !Inspect::inspect_is_owner(caller())
+            && !Inspect::inspect_is_minter(caller())
+            && !AgencyMemberStorage::is_member(caller(), agency)
+        {
+            return Err(DeferredDataError::Unauthorized);
+        }
+
+        let usage = UsageStorage::get_agency_usage(agency);
+        let report = AgencyStorage::report(agency);
+        let storage_cycles_per_day =
+            u128::from(usage.document_bytes) * STORAGE_CYCLES_PER_GIB_SECOND * 86_400 / (1 << 30);
+
+        Ok(AgencyUsage {
+            agency,
+            usage,
+            quota: UsageStorage::get_quota(agency),
+            contracts: report.open_contracts.len() as u64,
+            real_estates: report.real_estates.len() as u64,
+            storage_cycles_per_day: storage_cycles_per_day.try_into().unwrap_or(u64::MAX),
+        })
+    }
+
+    /// Create a real estate
+    pub fn create_real_estate(real_estate: RealEstate) -> DeferredDataResult<ID> {
+        log::debug!("creating real estate: {real_estate:?}");
+        let id = RealEstateStorage::insert(real_estate)?;
+        log::info!("Real estate {id} created");
+
+        Ok(id)
+    }
+
+    /// Get a real estate by ID
+    pub fn get_real_estate(id: &ID) -> DeferredDataResult<RealEstate> {
+        RealEstateStorage::get(id)
+    }
+
+    /// Update a real estate by ID
+    pub fn update_real_estate(id: &ID, real_estate: RealEstate) -> DeferredDataResult<()> {
+        log::debug!("Updating real estate {id}");
+        RealEstateStorage::update(id, real_estate)?;
+        log::info!("Real estate {id} updated");
+
+        Ok(())
+    }
+
+    /// Delete a real estate by ID
+    pub fn delete_real_estate(id: &ID) -> DeferredDataResult<()> {
+        log::debug!("Deleting real estate {id}");
+        RealEstateStorage::delete(id)?;
+        log::info!("Real estate {id} deleted");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Nat;
+    use did::deferred::{AgencyId, RealEstateError, RestrictionLevel, SignedMessageError};
+    use did::H160;
+    use ic_log::LogSettingsV2;
+    use pretty_assertions::assert_eq;
+    use sha2::{Digest as _, Sha256};
+    use test_utils::{
+        mock_contract, mock_jpeg_with_gps, mock_pdf, mock_signed_message, store_mock_contract_with,
+        with_mock_contract, MOCK_SIGNER,
+    };
+
+    use super::*;
+    use crate::app::images::Images;
+    use crate::app::test_utils::mock_real_estate;
+
+    #[test]
+    fn test_should_init() {
+        let data = DeferredDataInitData {
+            minter: caller(),
+            log_settings: Default::default(),
+        };
+
+        DeferredData::init(data);
+
+        assert_eq!(Configuration::get_minter(), caller());
+    }
+
+    #[test]
+    fn test_should_set_sns_governance() {
+        init();
+
+        assert!(DeferredData::admin_set_sns_governance(Some(test_utils::bob())).is_ok());
+        assert_eq!(DeferredData::get_sns_governance(), Some(test_utils::bob()));
+        assert!(Inspect::inspect_is_owner(test_utils::bob()));
+
+        assert_eq!(
+            DeferredData::admin_set_sns_governance(Some(Principal::anonymous())),
+            Err(DataConfigurationError::AnonymousSnsGovernance.into())
+        );
+        assert!(DeferredData::admin_set_sns_governance(None).is_ok());
+        assert_eq!(DeferredData::get_sns_governance(), None);
+    }
+
+    #[test]
+    fn test_should_set_read_only_mode() {
+        init();
+
+        assert!(DeferredData::admin_set_read_only(Some("migration".to_string())).is_ok());
+        let mode = DeferredData::get_read_only_mode().unwrap();
+        assert_eq!(mode.enabled_by, caller());
+        assert_eq!(mode.reason, "migration");
+
+        assert!(DeferredData::admin_set_read_only(None).is_ok());
+        assert_eq!(DeferredData::get_read_only_mode(), None);
+    }
+
+    #[test]
+    fn test_should_validate_sns_proposals() {
+        assert_eq!(
+            DeferredData::validate_admin_set_minter(test_utils::alice()).unwrap(),
+            format!("Set minter to {}", test_utils::alice())
+        );
+        assert_eq!(
+            DeferredData::validate_admin_set_minter(Principal::anonymous()),
+            Err(DataConfigurationError::AnonymousMinter.into())
+        );
+        assert_eq!(
+            DeferredData::validate_admin_set_sns_governance(None).unwrap(),
+            "Unset SNS governance"
+        );
+        assert_eq!(
+            DeferredData::validate_admin_set_sns_governance(Some(Principal::anonymous())),
+            Err(DataConfigurationError::AnonymousSnsGovernance.into())
+        );
+        assert_eq!(
+            DeferredData::validate_admin_set_read_only(Some("migration".to_string())).unwrap(),
+            "Enable read-only mode: migration"
+        );
+        assert_eq!(Configuration::get_minter(), Principal::anonymous());
+    }
+
+    #[test]
+    fn test_should_create_contract() {
+        init();
+
+        let contract = mock_contract(1, 100);
+
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+
+        let stored_contract =
+            ContractStorage::get_contract(&contract.id).expect("Failed to get contract");
+        assert_eq!(contract, stored_contract);
+    }
+
+    #[test]
+    fn test_should_close_contract() {
+        init();
+
+        let contract = mock_contract(1, 100);
+
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+
+        DeferredData::close_contract(contract.id.clone()).expect("Failed to close contract");
+
+        let stored_contract = ContractStorage::get_contract(&contract.id);
+        assert_eq!(stored_contract, None);
+    }
+
+    #[test]
+    fn test_should_upload_contract_document_in_chunks() {
+        init();
+
+        let contract = mock_contract(1, 100);
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+
+        let data = mock_pdf(&[1, 2, 3, 4, 5]);
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Public],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: data.len() as u64,
+        };
+        let sha256 = hex::encode(Sha256::digest(&data));
+
+        // the hash must be a SHA-256 in hex
+        for invalid in [
+            String::new(),
+            sha256[..62].to_string(),
+            format!("{}zz", &sha256[..62]),
+        ] {
+            assert_eq!(
+                DeferredData::begin_contract_document_upload(
+                    contract.id.clone(),
+                    document.clone(),
+                    invalid.clone(),
+                ),
+                Err(DeferredDataError::Contract(
+                    DataContractError::InvalidDocumentHash(invalid)
+                ))
+            );
         }
 
-        log::debug!("creating real estate: {real_estate:?}");
-        let id = RealEstateStorage::insert(real_estate)?;
-        log::info!("Real estate {id} created");
+        let upload_id = DeferredData::begin_contract_document_upload(
+            contract.id.clone(),
+            document.clone(),
+            sha256.to_uppercase(),
+        )
+        .unwrap();
+        DeferredData::upload_contract_document_chunk(upload_id, data[..3].to_vec()).unwrap();
+        // not finalized until every byte is received
+        assert_eq!(
+            DeferredData::finalize_contract_document_upload(upload_id),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(10, 3)
+            ))
+        );
+        DeferredData::upload_contract_document_chunk(upload_id, data[3..].to_vec()).unwrap();
 
-        Ok(id)
-    }
+        let document_id = DeferredData::finalize_contract_document_upload(upload_id).unwrap();
+        assert_eq!(
+            ContractStorage::get_contract_document(&contract.id, document_id)
+                .unwrap()
+                .data,
+            data
+        );
+        assert!(UploadStorage::get(upload_id).is_err());
 
-    /// Get a real estate by ID
-    pub fn get_real_estate(id: &ID) -> DeferredDataResult<RealEstate> {
-        RealEstateStorage::get(id)
+        // the upload is discarded if the hash doesn't match
+        let upload_id =
+            DeferredData::begin_contract_document_upload(contract.id.clone(), document, sha256)
+                .unwrap();
+        DeferredData::upload_contract_document_chunk(upload_id, mock_pdf(&[5, 4, 3, 2, 1]))
+            .unwrap();
+        assert!(matches!(
+            DeferredData::finalize_contract_document_upload(upload_id),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentHashMismatch(_, _)
+            ))
+        ));
+        assert!(UploadStorage::get(upload_id).is_err());
     }
 
-    /// Update a real estate by ID
-    pub fn update_real_estate(id: &ID, real_estate: RealEstate) -> DeferredDataResult<()> {
-        if !Inspect::inspect_is_minter(caller()) {
-            return Err(DeferredDataError::Unauthorized);
-        }
+    #[test]
+    fn test_should_validate_mime_type_of_contract_documents() {
+        init();
 
-        log::debug!("Updating real estate {id}");
-        RealEstateStorage::update(id, real_estate)?;
-        log::info!("Real estate {id} updated");
+        let contract = mock_contract(1, 100);
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
 
-        Ok(())
-    }
+        let data = mock_pdf(&[1, 2, 3]);
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Public],
+            mime_type: "image/png".to_string(),
+            name: "deed.pdf".to_string(),
+            size: data.len() as u64,
+        };
+        assert_eq!(
+            DeferredData::upload_contract_document(
+                contract.id.clone(),
+                document.clone(),
+                data.clone()
+            ),
+            Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeMismatch(
+                    "image/png".to_string(),
+                    "application/pdf".to_string()
+                )
+            ))
+        );
 
-    /// Delete a real estate by ID
-    pub fn delete_real_estate(id: &ID) -> DeferredDataResult<()> {
-        if !Inspect::inspect_is_minter(caller()) {
-            return Err(DeferredDataError::Unauthorized);
-        }
+        let mut html = document.clone();
+        html.mime_type = "text/html".to_string();
+        assert_eq!(
+            DeferredData::begin_contract_document_upload(
+                contract.id.clone(),
+                html,
+                hex::encode(Sha256::digest(&data))
+            ),
+            Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeNotAllowed("text/html".to_string())
+            ))
+        );
 
-        log::debug!("Deleting real estate {id}");
-        RealEstateStorage::delete(id)?;
-        log::info!("Real estate {id} deleted");
+        // the type can't be changed to one which doesn't match the data
+        let mut pdf = document;
+        pdf.mime_type = "application/pdf".to_string();
+        let document_id =
+            DeferredData::upload_contract_document(contract.id.clone(), pdf.clone(), data).unwrap();
+        let mut png = pdf;
+        png.mime_type = "image/png".to_string();
+        assert!(
+            DeferredData::update_contract_document(contract.id.clone(), document_id, png).is_err()
+        );
 
-        Ok(())
+        // the owner sets the allowlist
+        assert_eq!(
+            DeferredData::validate_admin_set_allowed_mime_types(vec!["text".to_string()]),
+            Err(DataConfigurationError::InvalidMimeType("text".to_string()).into())
+        );
+        DeferredData::admin_set_allowed_mime_types(vec![
+            "Text/Plain".to_string(),
+            "text/plain".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            DeferredData::get_allowed_mime_types(),
+            vec!["text/plain".to_string()]
+        );
     }
-}
 
-#[cfg(test)]
-mod test {
+    #[test]
+    fn test_should_manage_contract_document_versions() {
+        init();
 
-    use std::str::FromStr as _;
+        let contract = mock_contract(1, 100);
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
 
-    use candid::Nat;
-    use did::deferred::{RealEstateError, RestrictionLevel};
-    use did::H160;
-    use ic_log::LogSettingsV2;
-    use pretty_assertions::assert_eq;
-    use test_utils::{mock_contract, store_mock_contract_with, with_mock_contract};
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Public],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: 8,
+        };
+        let document_id = DeferredData::upload_contract_document(
+            contract.id.clone(),
+            document.clone(),
+            mock_pdf(&[1, 2, 3]),
+        )
+        .unwrap();
 
-    use super::*;
-    use crate::app::test_utils::mock_real_estate;
+        // restrict the access to the agents
+        let mut restricted = document.clone();
+        restricted.access_list = vec![RestrictionLevel::Agent];
+        assert_eq!(
+            DeferredData::update_contract_document(contract.id.clone(), document_id, restricted),
+            Ok(2)
+        );
+        assert_eq!(
+            DeferredData::get_contract_document(contract.id.clone(), document_id, None),
+            Err(DeferredDataError::Unauthorized)
+        );
 
-    #[test]
-    fn test_should_init() {
-        let data = DeferredDataInitData {
-            minter: caller(),
-            log_settings: Default::default(),
-        };
+        // publish a revised deed
+        let mut revised = document.clone();
+        revised.size = 7;
+        assert_eq!(
+            DeferredData::replace_contract_document(
+                contract.id.clone(),
+                document_id,
+                revised,
+                mock_pdf(&[4, 5])
+            ),
+            Ok(3)
+        );
+        assert_eq!(
+            DeferredData::get_contract_document(contract.id.clone(), document_id, None)
+                .unwrap()
+                .data,
+            mock_pdf(&[4, 5])
+        );
+        assert_eq!(
+            DeferredData::get_contract_document_version(contract.id.clone(), document_id, 1, None)
+                .unwrap()
+                .data,
+            mock_pdf(&[1, 2, 3])
+        );
 
-        DeferredData::init(data);
+        // the history is reserved to the minter and the agency
+        let history =
+            DeferredData::get_contract_document_history(contract.id.clone(), document_id).unwrap();
+        assert_eq!(history.versions.len(), 3);
+        assert_eq!(history.versions[0].created_by, caller());
 
-        assert_eq!(Configuration::get_minter(), caller());
+        DeferredData::delete_contract_document(contract.id.clone(), document_id, true).unwrap();
+        assert_eq!(
+            DeferredData::get_contract_document(contract.id.clone(), document_id, None),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id)
+            ))
+        );
+        let history =
+            DeferredData::get_contract_document_history(contract.id.clone(), document_id).unwrap();
+        assert!(history.deleted_at.is_some());
+        assert!(history.versions.iter().all(|version| version.purged));
     }
 
     #[test]
-    fn test_should_create_contract() {
+    fn test_should_serve_previews_and_strip_gps_of_images() {
         init();
 
         let contract = mock_contract(1, 100);
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+
+        let data = mock_jpeg_with_gps(2048, 1024);
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Public],
+            mime_type: "image/jpeg".to_string(),
+            name: "house.jpg".to_string(),
+            size: data.len() as u64,
+        };
+        let document_id = DeferredData::upload_contract_document(
+            contract.id.clone(),
+            document.clone(),
+            data.clone(),
+        )
+        .unwrap();
+
+        // the GPS position is removed before the image is stored, so the served image is the
+        // anchored one
+        let served =
+            DeferredData::get_contract_document(contract.id.clone(), document_id, None).unwrap();
+        assert_eq!(served.data.len(), data.len());
+        assert_ne!(served.data, data);
+        assert!(!Images::strip_gps("image/jpeg", &mut served.data.clone()));
+        assert_eq!(
+            DeferredData::get_contract_document_leaves(contract.id.clone()).unwrap()[0].sha256,
+            hex::encode(Sha256::digest(&served.data))
+        );
+
+        let thumbnail = DeferredData::get_contract_document_preview(
+            contract.id.clone(),
+            document_id,
+            ImageSize::Thumbnail,
+            None,
+        )
+        .unwrap();
+        assert_eq!(thumbnail.mime_type, "image/jpeg");
+        assert_eq!(thumbnail.name, "house.jpg");
+
+        // the previews are restricted as the document
+        let mut restricted = document;
+        restricted.access_list = vec![RestrictionLevel::Agent];
+        DeferredData::update_contract_document(contract.id.clone(), document_id, restricted)
+            .unwrap();
+        assert_eq!(
+            DeferredData::get_contract_document_preview(
+                contract.id.clone(),
+                document_id,
+                ImageSize::Preview,
+                None
+            ),
+            Err(DeferredDataError::Unauthorized)
+        );
+    }
+
+    #[test]
+    fn test_should_serve_restricted_documents_within_the_session_of_each_signed_message() {
+        init();
 
+        let (buyer, _) = signature();
+        let contract = with_mock_contract(1, 100, |contract| contract.buyers = vec![buyer]);
         DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
 
-        let stored_contract =
-            ContractStorage::get_contract(&contract.id).expect("Failed to get contract");
-        assert_eq!(contract, stored_contract);
+        let data = mock_pdf(&[1, 2, 3]);
+        let mut document = ContractDocument {
+            access_list: vec![RestrictionLevel::Public, RestrictionLevel::Buyer],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: data.len() as u64,
+        };
+        let public_id = DeferredData::upload_contract_document(
+            contract.id.clone(),
+            document.clone(),
+            data.clone(),
+        )
+        .unwrap();
+        document.access_list = vec![RestrictionLevel::Buyer];
+        let restricted_id = DeferredData::upload_contract_document(
+            contract.id.clone(),
+            document.clone(),
+            data.clone(),
+        )
+        .unwrap();
+        let other_id =
+            DeferredData::upload_contract_document(contract.id.clone(), document, data).unwrap();
+
+        let signed_message = |session: Option<&str>| {
+            let (_, mut signed_message) = signature();
+            signed_message.session = session.map(str::to_string);
+            Some(signed_message)
+        };
+        let get_restricted = |session: Option<&str>| {
+            DeferredData::get_contract_document(
+                contract.id.clone(),
+                restricted_id,
+                signed_message(session),
+            )
+        };
+        let open_session = |session: &str| {
+            DeferredData::open_streaming_session(
+                contract.id.clone(),
+                restricted_id,
+                signature().1,
+                session.to_string(),
+            )
+        };
+
+        // the nonce is not consumed by the public documents
+        for _ in 0..2 {
+            assert!(DeferredData::get_contract_document(
+                contract.id.clone(),
+                public_id,
+                signed_message(None)
+            )
+            .is_ok());
+        }
+        assert_eq!(open_session("a1"), Ok("a1".to_string()));
+
+        // the chunks of a streamed document are served only within the session opened by the
+        // request which consumed the nonce
+        assert_eq!(
+            get_restricted(Some("a1")),
+            Err(DeferredDataError::Unauthorized)
+        );
+        assert!(get_restricted(None).is_ok());
+        assert_eq!(
+            get_restricted(Some("a1")),
+            Err(DeferredDataError::Unauthorized)
+        );
+        assert_eq!(open_session("a1"), Ok("a1".to_string()));
+        // the session already open is kept
+        assert_eq!(open_session("a2"), Ok("a1".to_string()));
+        assert!(get_restricted(Some("a1")).is_ok());
+        assert_eq!(
+            get_restricted(Some("a2")),
+            Err(DeferredDataError::Unauthorized)
+        );
+
+        // every new request of the document, such as a `Range` request, is served within the
+        // session without consuming the nonce again
+        for _ in 0..3 {
+            assert!(get_restricted(None).is_ok());
+        }
+
+        // the message can't be used for other documents
+        assert_eq!(
+            DeferredData::get_contract_document(
+                contract.id.clone(),
+                other_id,
+                signed_message(None)
+            ),
+            Err(SignedMessageError::NonceAlreadyUsed("Mj5fQ2xWkp".to_string()).into())
+        );
+        assert_eq!(
+            DeferredData::get_contract_document(
+                contract.id.clone(),
+                other_id,
+                signed_message(Some("a1"))
+            ),
+            Err(DeferredDataError::Unauthorized)
+        );
     }
 
     #[test]
-    fn test_should_close_contract() {
+    fn test_should_anchor_and_verify_contract_documents() {
         init();
 
         let contract = mock_contract(1, 100);
-
         DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Public],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: 8,
+        };
+        for data in [[1, 2, 3], [4, 5, 6], [7, 8, 9]].map(|content| mock_pdf(&content)) {
+            DeferredData::upload_contract_document(contract.id.clone(), document.clone(), data)
+                .unwrap();
+        }
 
-        DeferredData::close_contract(contract.id.clone()).expect("Failed to close contract");
+        let leaves = DeferredData::get_contract_document_leaves(contract.id.clone()).unwrap();
+        assert_eq!(leaves.len(), 3);
+        assert_eq!(
+            leaves[1].sha256,
+            hex::encode(Sha256::digest(mock_pdf(&[4, 5, 6])))
+        );
+        assert_eq!(
+            DeferredData::verify_contract_document(contract.id.clone(), leaves[1].document_id),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotAnchored(leaves[1].document_id)
+            ))
+        );
 
-        let stored_contract = ContractStorage::get_contract(&contract.id);
-        assert_eq!(stored_contract, None);
+        let mut anchor = DocumentsAnchor {
+            contract_id: contract.id.clone(),
+            root: DocumentsMerkleTree::new(&leaves).unwrap().root(),
+            leaves: leaves.clone(),
+            transaction_hash: format!("0x{}", "11".repeat(32)),
+            chain_id: 1,
+            anchored_at: 1_000,
+        };
+        let root = anchor.root.clone();
+        anchor.root = format!("0x{}", "00".repeat(32));
+        assert_eq!(
+            DeferredData::set_documents_anchor(anchor.clone()),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentsRootMismatch(anchor.root.clone(), root.clone())
+            ))
+        );
+        anchor.root = root.clone();
+        DeferredData::set_documents_anchor(anchor.clone()).unwrap();
+
+        let proof =
+            DeferredData::verify_contract_document(contract.id.clone(), leaves[1].document_id)
+                .unwrap();
+        assert_eq!(proof.sha256, leaves[1].sha256);
+        assert_eq!(proof.root, root);
+        assert_eq!(proof.transaction_hash, anchor.transaction_hash);
+        assert!(DocumentsMerkleTree::verify(
+            &leaves[1],
+            &proof.proof,
+            &proof.root
+        ));
     }
 
     #[test]
     fn test_should_get_contract() {
         init();
 
-        let contract = with_mock_contract(1, 100, |contract| {
-            contract.agency = caller();
-        });
+        let contract = mock_contract(1, 100);
+        AgencyMemberStorage::set(caller(), Some(contract.agency));
 
         DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
 
@@ -368,9 +1621,8 @@ mod test {
     fn test_should_set_restricted_property() {
         init();
 
-        let contract = with_mock_contract(1, 100, |contract| {
-            contract.agency = caller();
-        });
+        let contract = mock_contract(1, 100);
+        AgencyMemberStorage::set(caller(), Some(contract.agency));
 
         DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
 
@@ -426,6 +1678,248 @@ mod test {
         assert_eq!(contract.restricted_properties.len(), 1);
     }
 
+    #[test]
+    fn test_should_show_restricted_properties_to_agency_members() {
+        init();
+        let agency = AgencyId(1);
+        store_mock_contract_with(1, 100, |contract| {
+            contract.agency = agency;
+            contract.restricted_properties.push((
+                "contract::restricted".to_string(),
+                RestrictedProperty {
+                    value: GenericValue::TextContent("value".to_string()),
+                    access_list: vec![RestrictionLevel::Agent],
+                },
+            ));
+        });
+
+        let contract = DeferredData::get_contract(&Nat::from(1u64), None).unwrap();
+        assert!(contract.restricted_properties.is_empty());
+
+        DeferredData::set_agency_member(caller(), Some(agency)).unwrap();
+        let contract = DeferredData::get_contract(&Nat::from(1u64), None).unwrap();
+        assert_eq!(contract.restricted_properties.len(), 1);
+
+        DeferredData::set_agency_member(caller(), None).unwrap();
+        let contract = DeferredData::get_contract(&Nat::from(1u64), None).unwrap();
+        assert!(contract.restricted_properties.is_empty());
+    }
+
+    #[test]
+    fn test_should_suspend_and_remove_agency() {
+        init();
+
+        let real_estate = mock_real_estate();
+        let agency = real_estate.agency;
+        let id = DeferredData::create_real_estate(real_estate).expect("Failed to create");
+
+        let report = DeferredData::set_agency_status(agency, AgencyStatus::Suspended)
+            .expect("Failed to suspend agency");
+        assert_eq!(report.real_estates, vec![id.clone()]);
+        assert!(AgencyStorage::is_suspended(agency));
+
+        let report = DeferredData::remove_agency(agency).expect("Failed to remove agency");
+        assert_eq!(report.real_estates, vec![id.clone()]);
+        assert!(!AgencyStorage::is_suspended(agency));
+        assert!(DeferredData::get_real_estate(&id).is_err());
+    }
+
+    #[test]
+    fn test_should_limit_agency_storage_with_quota() {
+        init();
+
+        let agency = AgencyId(1);
+        let mut contract = mock_contract(1, 100);
+        contract.agency = agency;
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Agent],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: 9,
+        };
+        DeferredData::upload_contract_document(
+            contract.id.clone(),
+            document.clone(),
+            mock_pdf(&[1, 2, 3, 4]),
+        )
+        .unwrap();
+
+        let quota = AgencyQuota {
+            max_documents: 10,
+            max_document_bytes: 14,
+        };
+        DeferredData::set_agency_quota(agency, Some(quota)).unwrap();
+        let exceeded: DeferredDataResult<u64> = Err(DeferredDataError::Contract(
+            DataContractError::AgencyQuotaExceeded(agency, quota),
+        ));
+        assert_eq!(
+            DeferredData::upload_contract_document(
+                contract.id.clone(),
+                document.clone(),
+                mock_pdf(&[5, 6, 7, 8]),
+            ),
+            exceeded.clone()
+        );
+        assert_eq!(
+            DeferredData::begin_contract_document_upload(
+                contract.id.clone(),
+                document,
+                hex::encode(Sha256::digest(mock_pdf(&[5, 6, 7, 8]))),
+            ),
+            exceeded
+        );
+
+        let usage = DeferredData::get_agency_usage(agency).unwrap();
+        assert_eq!(usage.usage, StorageUsage::document(9));
+        assert_eq!(usage.quota, Some(quota));
+        assert_eq!(usage.contracts, 1);
+        assert_eq!(usage.real_estates, 0);
+
+        DeferredData::set_agency_quota(agency, None).unwrap();
+        assert_eq!(DeferredData::get_agency_usage(agency).unwrap().quota, None);
+    }
+
+    #[test]
+    fn test_should_set_custom_domains() {
+        init();
+
+        for invalid in [
+            "https://docs.ekoke.io",
+            "docs.ekoke.io:443",
+            "127.0.0.1",
+            "",
+        ] {
+            assert_eq!(
+                DeferredData::validate_admin_set_custom_domains(vec![invalid.to_string()]),
+                Err(DataConfigurationError::InvalidDomain(invalid.to_string()).into())
+            );
+        }
+        assert_eq!(
+            DeferredData::validate_admin_set_custom_domains(vec!["docs.ekoke.io".to_string()])
+                .unwrap(),
+            "Set custom domains: docs.ekoke.io"
+        );
+
+        DeferredData::admin_set_custom_domains(vec![
+            "Docs.Ekoke.io".to_string(),
+            "docs.ekoke.io".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            DeferredData::get_custom_domains(),
+            vec!["docs.ekoke.io".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_should_set_agency_quota_as_owner() {
+        init();
+
+        let agency = AgencyId(1);
+        let quota = AgencyQuota {
+            max_documents: 10,
+            max_document_bytes: 1024,
+        };
+        let not_found: DeferredDataResult<()> = Err(DeferredDataError::Contract(
+            DataContractError::AgencyNotFound(agency),
+        ));
+        assert_eq!(
+            DeferredData::validate_admin_set_agency_quota(agency, Some(quota)).map(|_| ()),
+            not_found
+        );
+        assert_eq!(
+            DeferredData::admin_set_agency_quota(agency, Some(quota)),
+            not_found
+        );
+
+        AgencyMemberStorage::set(test_utils::bob(), Some(agency));
+        assert_eq!(
+            DeferredData::validate_admin_set_agency_quota(agency, Some(quota)).unwrap(),
+            "Set storage quota of agency 1 to 10 documents and 1024 bytes"
+        );
+        assert_eq!(
+            DeferredData::validate_admin_set_agency_quota(agency, None).unwrap(),
+            "Remove storage quota of agency 1"
+        );
+        DeferredData::admin_set_agency_quota(agency, Some(quota)).unwrap();
+        assert_eq!(
+            DeferredData::get_agency_usage(agency).unwrap().quota,
+            Some(quota)
+        );
+
+        DeferredData::admin_set_agency_quota(agency, None).unwrap();
+        assert_eq!(DeferredData::get_agency_usage(agency).unwrap().quota, None);
+    }
+
+    #[test]
+    fn test_should_count_pending_uploads_in_quota() {
+        init();
+
+        let agency = AgencyId(1);
+        let mut contract = mock_contract(1, 100);
+        contract.agency = agency;
+        DeferredData::create_contract(contract.clone()).expect("Failed to create contract");
+        let quota = AgencyQuota {
+            max_documents: 10,
+            max_document_bytes: 20,
+        };
+        DeferredData::set_agency_quota(agency, Some(quota)).unwrap();
+
+        let data = mock_pdf(&[1, 2, 3, 4]);
+        let document = ContractDocument {
+            access_list: vec![RestrictionLevel::Agent],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: data.len() as u64,
+        };
+        let sha256 = hex::encode(Sha256::digest(&data));
+        let begin = || {
+            DeferredData::begin_contract_document_upload(
+                contract.id.clone(),
+                document.clone(),
+                sha256.clone(),
+            )
+        };
+        let upload_id = begin().unwrap();
+        begin().unwrap();
+        // the uploads which haven't been finalized yet count towards the quota
+        assert_eq!(
+            begin(),
+            Err(DeferredDataError::Contract(
+                DataContractError::AgencyQuotaExceeded(agency, quota)
+            ))
+        );
+
+        // a finalized upload is counted once
+        DeferredData::upload_contract_document_chunk(upload_id, data).unwrap();
+        assert!(DeferredData::finalize_contract_document_upload(upload_id).is_ok());
+        assert_eq!(
+            DeferredData::get_agency_usage(agency).unwrap().usage,
+            StorageUsage::document(9)
+        );
+        assert!(begin().is_err());
+    }
+
+    #[test]
+    fn test_should_estimate_agency_storage_cost() {
+        init();
+
+        let agency = AgencyId(1);
+        let contract = with_mock_contract(1, 100, |contract| {
+            contract.agency = agency;
+        });
+        UsageStorage::add(&contract, StorageUsage::document(1 << 30));
+
+        assert_eq!(
+            DeferredData::get_agency_usage(agency)
+                .unwrap()
+                .storage_cycles_per_day,
+            127_000 * 86_400
+        );
+    }
+
     #[test]
     fn test_should_create_and_get_real_estate() {
         init();
@@ -476,12 +1970,15 @@ mod test {
     }
 
     fn signature() -> (H160, SignedMessage) {
-        let message = "Hello, Ethereum!".to_string();
-        let signature = H520::from_str("0x0e9293c16d57e3ea35118a52cc7209871d07db4b74183fbd6758306c2475586a2f64a5837cd7b787bff49e9432aab76de43080b9d98675e8890e16ffc669e6cb1b").unwrap();
+        let (message, signature) = mock_signed_message();
 
         (
-            H160::from_hex_str("0x8fd379246834eac74B8419FfdA202CF8051F7A03").unwrap(),
-            SignedMessage { message, signature },
+            H160::from_hex_str(MOCK_SIGNER).unwrap(),
+            SignedMessage {
+                message,
+                signature,
+                session: None,
+            },
         )
     }
 
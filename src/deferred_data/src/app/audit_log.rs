@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use did::deferred::{AuditEntry, AuditFilter, AuditIntegrity, AuditResult, StableAuditLog};
+use ic_log::did::Pagination;
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::DefaultMemoryImpl;
+
+use crate::app::memory::{
+    AUDIT_LOG_BY_CALLER_MEMORY_ID, AUDIT_LOG_BY_METHOD_MEMORY_ID, AUDIT_LOG_MEMORY_ID,
+    MEMORY_MANAGER,
+};
+use crate::utils::time;
+
+thread_local! {
+    /// Audit log entries and their indexes
+    static AUDIT_LOG: RefCell<StableAuditLog<VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(MEMORY_MANAGER.with(|mm| StableAuditLog::new(
+            mm.get(AUDIT_LOG_MEMORY_ID),
+            mm.get(AUDIT_LOG_BY_CALLER_MEMORY_ID),
+            mm.get(AUDIT_LOG_BY_METHOD_MEMORY_ID),
+        )));
+}
+
+/// Audit log of the state-changing calls of the canister
+pub struct AuditLog;
+
+impl AuditLog {
+    /// Append a new entry chained to the last one
+    pub fn append(caller: Principal, method: &str, args: &str, result: AuditResult) -> AuditEntry {
+        AUDIT_LOG.with_borrow_mut(|log| log.append(caller, method, args, result, time()))
+    }
+
+    /// Index the entries recorded before the audit log was indexed
+    pub fn index_entries() -> u64 {
+        AUDIT_LOG.with_borrow_mut(|log| log.index_entries())
+    }
+
+    /// Get the entries matching the filter, from the oldest one
+    pub fn get_entries(filter: &AuditFilter, pagination: Pagination) -> Vec<AuditEntry> {
+        AUDIT_LOG.with_borrow(|log| log.get_entries(filter, pagination))
+    }
+
+    /// Verify the hash chain of the entries in the provided page
+    pub fn verify(pagination: Pagination) -> AuditIntegrity {
+        AUDIT_LOG.with_borrow(|log| log.verify(pagination))
+    }
+}
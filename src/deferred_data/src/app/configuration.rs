@@ -1,14 +1,18 @@
 use std::cell::RefCell;
 
 use candid::Principal;
-use did::deferred::{DeferredDataError, DeferredDataResult};
+use did::deferred::{
+    DeferredDataError, DeferredDataResult, ReadOnlyMode, StorableDomains, StorableMimeTypes,
+    StorableReadOnlyMode,
+};
 use did::{StorableLogSettings, StorablePrincipal};
 use ic_log::LogSettingsV2;
 use ic_stable_structures::memory_manager::VirtualMemory;
 use ic_stable_structures::{DefaultMemoryImpl, StableCell};
 
 use crate::app::memory::{
-    LOG_SETTINGS_MEMORY_ID, MEMORY_MANAGER, MINTER_MEMORY_ID, OWNER_MEMORY_ID,
+    ALLOWED_MIME_TYPES_MEMORY_ID, CUSTOM_DOMAINS_MEMORY_ID, LOG_SETTINGS_MEMORY_ID, MEMORY_MANAGER,
+    MINTER_MEMORY_ID, OWNER_MEMORY_ID, READ_ONLY_MEMORY_ID, SNS_GOVERNANCE_MEMORY_ID,
 };
 
 thread_local! {
@@ -22,11 +26,31 @@ thread_local! {
         RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(OWNER_MEMORY_ID)), Principal::anonymous().into()).unwrap()
     );
 
+    /// SNS governance canister memory ID
+    static SNS_GOVERNANCE: RefCell<StableCell<StorablePrincipal, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(SNS_GOVERNANCE_MEMORY_ID)), Principal::anonymous().into()).unwrap()
+    );
+
     /// log settings
     static LOG_SETTINGS: RefCell<StableCell<StorableLogSettings, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(LOG_SETTINGS_MEMORY_ID)), StorableLogSettings::default()).unwrap()
     );
 
+    /// Read-only mode
+    static READ_ONLY: RefCell<StableCell<StorableReadOnlyMode, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(READ_ONLY_MEMORY_ID)), StorableReadOnlyMode::default()).unwrap()
+    );
+
+    /// MIME types allowed for the uploaded documents
+    static ALLOWED_MIME_TYPES: RefCell<StableCell<StorableMimeTypes, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(ALLOWED_MIME_TYPES_MEMORY_ID)), StorableMimeTypes::default()).unwrap()
+    );
+
+    /// Custom domains of the canister, besides the ones of the Internet Computer
+    static CUSTOM_DOMAINS: RefCell<StableCell<StorableDomains, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(CUSTOM_DOMAINS_MEMORY_ID)), StorableDomains::default()).unwrap()
+    );
+
 }
 
 pub struct Configuration;
@@ -58,6 +82,22 @@ impl Configuration {
         Ok(())
     }
 
+    /// Get the SNS governance canister, if set
+    pub fn get_sns_governance() -> Option<Principal> {
+        let principal = SNS_GOVERNANCE.with_borrow(|cell| cell.get().0);
+        (principal != Principal::anonymous()).then_some(principal)
+    }
+
+    /// Set the SNS governance canister, or unset it if `None`
+    pub fn set_sns_governance(principal: Option<Principal>) -> DeferredDataResult<()> {
+        SNS_GOVERNANCE.with_borrow_mut(|cell| {
+            cell.set(principal.unwrap_or(Principal::anonymous()).into())
+                .map_err(|_| DeferredDataError::StorageError)
+        })?;
+
+        Ok(())
+    }
+
     pub fn set_log_settings(settings: LogSettingsV2) -> DeferredDataResult<()> {
         LOG_SETTINGS.with_borrow_mut(|cell| {
             cell.set(StorableLogSettings(settings))
@@ -70,6 +110,51 @@ impl Configuration {
     pub fn get_log_settings() -> LogSettingsV2 {
         LOG_SETTINGS.with_borrow(|cell| cell.get().0.clone())
     }
+
+    /// Get the read-only mode, if enabled
+    pub fn get_read_only() -> Option<ReadOnlyMode> {
+        READ_ONLY.with_borrow(|cell| cell.get().0.clone())
+    }
+
+    /// Enable the read-only mode, or disable it if `None`
+    pub fn set_read_only(mode: Option<ReadOnlyMode>) -> DeferredDataResult<()> {
+        READ_ONLY.with_borrow_mut(|cell| {
+            cell.set(StorableReadOnlyMode(mode))
+                .map_err(|_| DeferredDataError::StorageError)
+        })?;
+
+        Ok(())
+    }
+
+    /// Get the MIME types allowed for the uploaded documents
+    pub fn get_allowed_mime_types() -> Vec<String> {
+        ALLOWED_MIME_TYPES.with_borrow(|cell| cell.get().0.clone())
+    }
+
+    /// Set the MIME types allowed for the uploaded documents
+    pub fn set_allowed_mime_types(mime_types: Vec<String>) -> DeferredDataResult<()> {
+        ALLOWED_MIME_TYPES.with_borrow_mut(|cell| {
+            cell.set(StorableMimeTypes(mime_types))
+                .map_err(|_| DeferredDataError::StorageError)
+        })?;
+
+        Ok(())
+    }
+
+    /// Get the custom domains of the canister
+    pub fn get_custom_domains() -> Vec<String> {
+        CUSTOM_DOMAINS.with_borrow(|cell| cell.get().0.clone())
+    }
+
+    /// Set the custom domains of the canister
+    pub fn set_custom_domains(domains: Vec<String>) -> DeferredDataResult<()> {
+        CUSTOM_DOMAINS.with_borrow_mut(|cell| {
+            cell.set(StorableDomains(domains))
+                .map_err(|_| DeferredDataError::StorageError)
+        })?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -99,6 +184,52 @@ mod test {
         assert_eq!(Configuration::get_owner(), principal);
     }
 
+    #[test]
+    fn test_should_get_and_set_sns_governance() {
+        let principal =
+            Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
+                .unwrap();
+        assert_eq!(Configuration::get_sns_governance(), None);
+        assert!(Configuration::set_sns_governance(Some(principal)).is_ok());
+        assert_eq!(Configuration::get_sns_governance(), Some(principal));
+        assert!(Configuration::set_sns_governance(None).is_ok());
+        assert_eq!(Configuration::get_sns_governance(), None);
+    }
+
+    #[test]
+    fn test_should_get_and_set_read_only_mode() {
+        let mode = ReadOnlyMode {
+            enabled_by: Principal::management_canister(),
+            enabled_at: 1_000,
+            reason: "migration".to_string(),
+        };
+        assert_eq!(Configuration::get_read_only(), None);
+        assert!(Configuration::set_read_only(Some(mode.clone())).is_ok());
+        assert_eq!(Configuration::get_read_only(), Some(mode));
+        assert!(Configuration::set_read_only(None).is_ok());
+        assert_eq!(Configuration::get_read_only(), None);
+    }
+
+    #[test]
+    fn test_should_get_and_set_allowed_mime_types() {
+        assert!(Configuration::get_allowed_mime_types().contains(&"application/pdf".to_string()));
+        assert!(Configuration::set_allowed_mime_types(vec!["text/plain".to_string()]).is_ok());
+        assert_eq!(
+            Configuration::get_allowed_mime_types(),
+            vec!["text/plain".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_should_get_and_set_custom_domains() {
+        assert!(Configuration::get_custom_domains().is_empty());
+        assert!(Configuration::set_custom_domains(vec!["docs.ekoke.io".to_string()]).is_ok());
+        assert_eq!(
+            Configuration::get_custom_domains(),
+            vec!["docs.ekoke.io".to_string()]
+        );
+    }
+
     #[test]
     fn test_should_set_and_get_log_settings() {
         let settings = LogSettingsV2 {
@@ -0,0 +1,477 @@
+//! # Images
+//!
+//! Processing of the contract documents which are JPEG, PNG or WebP images: generation of their
+//! thumbnail and preview, and removal of the GPS position from their EXIF metadata.
+
+use std::io::Cursor;
+
+use did::deferred::{DocumentImage, DocumentPreviews, ImageSize};
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits};
+
+/// Maximum width and height of the images which are decoded to generate the previews.
+///
+/// The previews are generated while the upload is finalized, so decoding and scaling the image
+/// must fit in the instructions of a single call: 16 megapixels cover the photos of most phones,
+/// while bigger images are stored with no previews
+const MAX_IMAGE_DIMENSION: u32 = 4096;
+/// Maximum memory allocated to decode an image
+const MAX_IMAGE_ALLOC: u64 = 96 * 1024 * 1024;
+/// Quality of the JPEG encoded previews
+const JPEG_QUALITY: u8 = 80;
+
+/// EXIF tag of the pointer to the GPS IFD
+const GPS_IFD_TAG: u16 = 0x8825;
+
+pub struct Images;
+
+impl Images {
+    /// Generate the thumbnail and the preview of an image document.
+    ///
+    /// The images are encoded as PNG if they have an alpha channel, as JPEG otherwise, and carry
+    /// no metadata. Returns `None` if the document is not a supported image or can't be decoded
+    pub fn previews(mime_type: &str, data: &[u8]) -> Option<DocumentPreviews> {
+        let format = image_format(mime_type)?;
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+        limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+        limits.max_alloc = Some(MAX_IMAGE_ALLOC);
+
+        let mut reader = ImageReader::with_format(Cursor::new(data), format);
+        reader.limits(limits);
+        let image = match reader.decode() {
+            Ok(image) => image,
+            Err(err) => {
+                log::warn!("Failed to decode {mime_type} image: {err}");
+                return None;
+            }
+        };
+
+        let preview = resize(&image, ImageSize::Preview);
+        // the thumbnail is scaled from the preview, which is much smaller than the original
+        let thumbnail = resize(&preview, ImageSize::Thumbnail);
+
+        Some(DocumentPreviews {
+            thumbnail: encode(&thumbnail)?,
+            preview: encode(&preview)?,
+        })
+    }
+
+    /// Remove the GPS position from the EXIF metadata of a JPEG, PNG or WebP image, in place.
+    ///
+    /// The GPS tags are zeroed, so the size of the image doesn't change.
+    /// Returns whether the image had a GPS position
+    pub fn strip_gps(mime_type: &str, data: &mut [u8]) -> bool {
+        match image_format(mime_type) {
+            Some(ImageFormat::Jpeg) => strip_jpeg_gps(data),
+            Some(ImageFormat::Png) => strip_png_gps(data),
+            Some(ImageFormat::WebP) => strip_webp_gps(data),
+            _ => false,
+        }
+    }
+}
+
+/// Get the format of the images with the provided MIME type, if supported
+fn image_format(mime_type: &str) -> Option<ImageFormat> {
+    match mime_type.trim().to_ascii_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" => Some(ImageFormat::Jpeg),
+        "image/png" => Some(ImageFormat::Png),
+        "image/webp" => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+/// Scale the image down to fit the provided size, keeping its aspect ratio
+fn resize(image: &DynamicImage, size: ImageSize) -> DynamicImage {
+    let max = size.max_dimension();
+    if image.width() <= max && image.height() <= max {
+        return image.clone();
+    }
+
+    image.thumbnail(max, max)
+}
+
+fn encode(image: &DynamicImage) -> Option<DocumentImage> {
+    let mut data = Vec::new();
+    let (mime_type, result) = if image.color().has_alpha() {
+        (
+            "image/png",
+            image.write_to(Cursor::new(&mut data), ImageFormat::Png),
+        )
+    } else {
+        (
+            "image/jpeg",
+            JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY).encode_image(&image.to_rgb8()),
+        )
+    };
+    if let Err(err) = result {
+        log::warn!("Failed to encode {mime_type} preview: {err}");
+        return None;
+    }
+
+    Some(DocumentImage {
+        mime_type: mime_type.to_string(),
+        width: image.width(),
+        height: image.height(),
+        data,
+    })
+}
+
+/// Strip the GPS position from the `Exif` APP1 segments of a JPEG image
+fn strip_jpeg_gps(data: &mut [u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+
+    let mut stripped = false;
+    let mut offset = 2;
+    while offset + 4 <= data.len() && data[offset] == 0xFF {
+        let marker = data[offset + 1];
+        // start of scan: the compressed data follows, with no more metadata
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        // markers without a payload
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) || marker == 0xFF {
+            offset += if marker == 0xFF { 1 } else { 2 };
+            continue;
+        }
+
+        let length = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        let end = (offset + 2 + length).min(data.len());
+        if length < 2 {
+            break;
+        }
+        let payload = &mut data[offset + 4..end];
+        if marker == 0xE1 && payload.starts_with(b"Exif\0\0") {
+            stripped |= strip_tiff_gps(&mut payload[6..]);
+        }
+        offset = end;
+    }
+
+    stripped
+}
+
+/// Strip the GPS position from the `eXIf` chunk of a PNG image, updating its CRC
+fn strip_png_gps(data: &mut [u8]) -> bool {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    if !data.starts_with(&SIGNATURE) {
+        return false;
+    }
+
+    let mut stripped = false;
+    let mut offset = SIGNATURE.len();
+    while offset + 12 <= data.len() {
+        let length = u32::from_be_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]) as usize;
+        let chunk_type = offset + 4;
+        let chunk_data = chunk_type + 4;
+        let Some(crc) = chunk_data
+            .checked_add(length)
+            .filter(|crc| crc.checked_add(4).is_some_and(|end| end <= data.len()))
+        else {
+            break;
+        };
+
+        if &data[chunk_type..chunk_data] == b"IEND" {
+            break;
+        }
+        if &data[chunk_type..chunk_data] == b"eXIf" && strip_tiff_gps(&mut data[chunk_data..crc]) {
+            let checksum = crc32(&data[chunk_type..crc]);
+            data[crc..crc + 4].copy_from_slice(&checksum.to_be_bytes());
+            stripped = true;
+        }
+        offset = crc + 4;
+    }
+
+    stripped
+}
+
+/// Strip the GPS position from the `EXIF` chunk of a WebP image
+fn strip_webp_gps(data: &mut [u8]) -> bool {
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return false;
+    }
+
+    let mut stripped = false;
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let length = u32::from_le_bytes([
+            data[offset + 4],
+            data[offset + 5],
+            data[offset + 6],
+            data[offset + 7],
+        ]) as usize;
+        let start = offset + 8;
+        let end = start.saturating_add(length).min(data.len());
+
+        if &data[offset..offset + 4] == b"EXIF" {
+            let payload = &mut data[start..end];
+            // some encoders keep the JPEG `Exif` header
+            let tiff = if payload.starts_with(b"Exif\0\0") {
+                &mut payload[6..]
+            } else {
+                payload
+            };
+            stripped |= strip_tiff_gps(tiff);
+        }
+        // chunks are padded to an even size
+        offset = end + (length & 1);
+    }
+
+    stripped
+}
+
+/// Remove the GPS IFD of the EXIF metadata, encoded as TIFF, by zeroing its tags and their
+/// values.
+///
+/// Returns whether the metadata had a GPS IFD with any tag
+fn strip_tiff_gps(tiff: &mut [u8]) -> bool {
+    let big_endian = match tiff.get(..2) {
+        Some(b"MM") => true,
+        Some(b"II") => false,
+        _ => return false,
+    };
+    let reader = TiffReader { big_endian };
+    if reader.u16(tiff, 2) != Some(42) {
+        return false;
+    }
+
+    let Some(ifd0) = reader.u32(tiff, 4) else {
+        return false;
+    };
+    let Some(entries) = reader.u16(tiff, ifd0) else {
+        return false;
+    };
+    let gps_ifd = (0..entries as usize)
+        .map_while(|index| ifd_entry(ifd0, index))
+        .find(|entry| reader.u16(tiff, *entry) == Some(GPS_IFD_TAG))
+        .and_then(|entry| reader.u32(tiff, entry.checked_add(8)?));
+    let Some(gps_ifd) = gps_ifd else {
+        return false;
+    };
+    let Some(gps_entries) = reader.u16(tiff, gps_ifd) else {
+        return false;
+    };
+
+    for index in 0..gps_entries as usize {
+        let Some(entry) = ifd_entry(gps_ifd, index) else {
+            break;
+        };
+        let (Some(value_type), Some(count)) = (
+            entry
+                .checked_add(2)
+                .and_then(|offset| reader.u16(tiff, offset)),
+            entry
+                .checked_add(4)
+                .and_then(|offset| reader.u32(tiff, offset)),
+        ) else {
+            break;
+        };
+        // values bigger than 4 bytes are stored out of the entry
+        let size = tiff_type_size(value_type).saturating_mul(count);
+        if size > 4 {
+            if let Some(value) = entry
+                .checked_add(8)
+                .and_then(|offset| reader.u32(tiff, offset))
+            {
+                zero(tiff, value, size);
+            }
+        }
+    }
+    // an IFD with no entries, followed by no other IFD
+    zero(tiff, gps_ifd, 2 + gps_entries as usize * 12 + 4);
+
+    gps_entries > 0
+}
+
+/// Offset of the entry at `index` of the IFD at offset `ifd`, if it doesn't overflow
+fn ifd_entry(ifd: usize, index: usize) -> Option<usize> {
+    ifd.checked_add(2)?.checked_add(index.checked_mul(12)?)
+}
+
+/// Size in bytes of a value of the provided TIFF type
+fn tiff_type_size(value_type: u16) -> usize {
+    match value_type {
+        // BYTE, ASCII, SBYTE, UNDEFINED
+        1 | 2 | 6 | 7 => 1,
+        // SHORT, SSHORT
+        3 | 8 => 2,
+        // LONG, SLONG, FLOAT
+        4 | 9 | 11 => 4,
+        // RATIONAL, SRATIONAL, DOUBLE
+        5 | 10 | 12 => 8,
+        _ => 0,
+    }
+}
+
+/// Zero `len` bytes from `offset`, within the bounds of `data`
+fn zero(data: &mut [u8], offset: usize, len: usize) {
+    let start = offset.min(data.len());
+    let end = offset.saturating_add(len).min(data.len());
+    data[start..end].fill(0);
+}
+
+/// Reads the integers of a TIFF with its byte order
+struct TiffReader {
+    big_endian: bool,
+}
+
+impl TiffReader {
+    fn u16(&self, data: &[u8], offset: usize) -> Option<u16> {
+        let bytes = data.get(offset..offset.checked_add(2)?)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, data: &[u8], offset: usize) -> Option<usize> {
+        let bytes = data.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+        let value = if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        };
+
+        Some(value as usize)
+    }
+}
+
+/// CRC-32 of the PNG chunks
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+
+    use image::{ImageBuffer, Rgb, Rgba};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{mock_gps_exif, mock_jpeg_with_gps};
+
+    #[test]
+    fn test_should_strip_gps_from_jpeg() {
+        let mut jpeg = mock_jpeg_with_gps(8, 8);
+        let size = jpeg.len();
+
+        assert!(Images::strip_gps("image/jpeg", &mut jpeg));
+        assert_eq!(jpeg.len(), size);
+        // the orientation is kept, while the GPS IFD and its values are zeroed
+        let tiff = &jpeg[12..];
+        assert_eq!(&tiff[18..20], &[0, 1]);
+        assert!(tiff[38..92].iter().all(|byte| *byte == 0));
+
+        // nothing left to strip
+        assert!(!Images::strip_gps("image/jpeg", &mut jpeg));
+    }
+
+    #[test]
+    fn test_should_strip_gps_from_png_and_webp() {
+        let exif = mock_gps_exif();
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png.extend_from_slice(&(exif.len() as u32).to_be_bytes());
+        png.extend_from_slice(b"eXIf");
+        png.extend_from_slice(&exif);
+        png.extend_from_slice(&crc32(&png[12..]).to_be_bytes());
+        assert!(Images::strip_gps("image/png", &mut png));
+        assert_eq!(
+            u32::from_be_bytes(png[png.len() - 4..].try_into().unwrap()),
+            crc32(&png[12..png.len() - 4])
+        );
+
+        let mut webp = b"RIFF\0\0\0\0WEBPEXIF".to_vec();
+        webp.extend_from_slice(&(exif.len() as u32).to_le_bytes());
+        webp.extend_from_slice(&exif);
+        assert!(Images::strip_gps("image/webp", &mut webp));
+        assert!(!webp.contains(&b'N'));
+
+        assert!(!Images::strip_gps("application/pdf", &mut exif.clone()));
+    }
+
+    #[test]
+    fn test_should_generate_previews() {
+        let image = ImageBuffer::from_pixel(2048, 1024, Rgb([200u8, 100, 50]));
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg).encode_image(&image).unwrap();
+
+        let previews = Images::previews("image/jpeg", &jpeg).unwrap();
+        assert_eq!(previews.thumbnail.mime_type, "image/jpeg");
+        assert_eq!(
+            (previews.thumbnail.width, previews.thumbnail.height),
+            (256, 128)
+        );
+        assert_eq!(
+            (previews.preview.width, previews.preview.height),
+            (1024, 512)
+        );
+        let thumbnail = image::load_from_memory(&previews.thumbnail.data).unwrap();
+        assert_eq!(thumbnail.width(), 256);
+    }
+
+    #[test]
+    fn test_should_keep_small_images_and_alpha() {
+        let image = ImageBuffer::from_pixel(100, 50, Rgba([200u8, 100, 50, 128]));
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let previews = Images::previews("image/png", &png).unwrap();
+        assert_eq!(previews.thumbnail.mime_type, "image/png");
+        assert_eq!((previews.preview.width, previews.preview.height), (100, 50));
+    }
+
+    #[test]
+    fn test_should_not_strip_gps_with_out_of_bounds_offsets() {
+        // PNG chunk whose length overflows the offset of its CRC
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png.extend_from_slice(&u32::MAX.to_be_bytes());
+        png.extend_from_slice(b"eXIf");
+        png.extend_from_slice(&[0; 8]);
+        assert!(!Images::strip_gps("image/png", &mut png));
+
+        // TIFF whose IFD0 is at the end of the address space
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&u32::MAX.to_le_bytes());
+        tiff.extend_from_slice(&[0xFF; 16]);
+        assert!(!strip_tiff_gps(&mut tiff));
+    }
+
+    #[test]
+    fn test_should_not_generate_previews_of_images_over_the_limit() {
+        let image = ImageBuffer::from_pixel(MAX_IMAGE_DIMENSION + 1, 1, Rgb([200u8, 100, 50]));
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        assert!(Images::previews("image/png", &png).is_none());
+    }
+
+    #[test]
+    fn test_should_not_generate_previews_of_invalid_images() {
+        assert!(Images::previews("image/png", &[1, 2, 3]).is_none());
+        assert!(Images::previews("application/pdf", &[1, 2, 3]).is_none());
+    }
+}
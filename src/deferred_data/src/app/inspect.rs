@@ -1,11 +1,18 @@
+use std::str::FromStr;
+
 use candid::Principal;
-use did::deferred::{DataContractError, DeferredDataError, DeferredDataResult, RestrictionLevel};
+use did::deferred::{
+    DataContractError, DataPermission, DeferredDataError, DeferredDataResult, DocumentUpload,
+    RestrictionLevel, SignedMessageError,
+};
 use did::ID;
 use ethers_core::abi::ethereum_types::H520;
 use ethers_core::types::{Signature, H160};
 
 use super::configuration::Configuration;
-use super::storage::ContractStorage;
+use super::siwe::SiweMessage;
+use super::storage::{ContractStorage, UploadStorage};
+use crate::utils::{canister_id, time};
 
 pub struct Inspect;
 
@@ -15,15 +22,15 @@ impl Inspect {
         caller == Configuration::get_minter()
     }
 
-    /// Returns true if the caller is the owner.
+    /// Returns true if the caller is the owner or the SNS governance canister.
     pub fn inspect_is_owner(caller: Principal) -> bool {
-        caller == Configuration::get_owner()
+        caller == Configuration::get_owner() || Configuration::get_sns_governance() == Some(caller)
     }
 
-    /// Inspects if the caller is the minter.
+    /// Inspects if the caller is the minter and the contract exists.
     pub fn inspect_modify_contract(caller: Principal, contract: &ID) -> DeferredDataResult<()> {
         if !Inspect::inspect_is_minter(caller) {
-            return Err(DeferredDataError::Unauthorized);
+            return Err(DeferredDataError::MissingPermission(DataPermission::Minter));
         }
 
         ContractStorage::get_contract(contract).ok_or(DeferredDataError::Contract(
@@ -33,20 +40,48 @@ impl Inspect {
         Ok(())
     }
 
-    /// Inspect whether a signature is valid for a contract.
+    /// Inspects if the caller began the upload.
+    ///
+    /// Returns the upload.
+    pub fn inspect_upload(caller: Principal, upload_id: u64) -> DeferredDataResult<DocumentUpload> {
+        let upload = UploadStorage::get(upload_id)?;
+        if upload.uploaded_by != caller {
+            return Err(DeferredDataError::Unauthorized);
+        }
+
+        Ok(upload)
+    }
+
+    /// Inspect whether a signed message grants access to a contract.
+    ///
+    /// The message must be a Sign-In with Ethereum message for the contract on this canister,
+    /// valid at the current time and signed by its address.
     ///
-    /// Returns the restriction level of the caller.
+    /// Returns the restriction level of the signer with the parsed message.
     pub fn inspect_signature(
         contract: &ID,
         signature: H520,
         message: String,
-    ) -> DeferredDataResult<RestrictionLevel> {
+    ) -> DeferredDataResult<(RestrictionLevel, SiweMessage)> {
         let contract = ContractStorage::get_contract(contract).ok_or(
             DeferredDataError::Contract(DataContractError::ContractNotFound(contract.clone())),
         )?;
 
+        let siwe_message = SiweMessage::from_str(&message)?;
+        siwe_message.validate(
+            canister_id(),
+            &Configuration::get_custom_domains(),
+            &contract.id,
+            time(),
+        )?;
+
         // try to get the pubkey from the signature
         let sender_address = Inspect::recover_address(message, signature)?;
+        if sender_address != siwe_message.address {
+            return Err(
+                SignedMessageError::SignerMismatch(format!("{:#x}", siwe_message.address)).into(),
+            );
+        }
 
         // check if the sender is a buyer or seller
         if contract
@@ -54,7 +89,7 @@ impl Inspect {
             .iter()
             .any(|seller| seller.address.0 == sender_address)
         {
-            return Ok(RestrictionLevel::Seller);
+            return Ok((RestrictionLevel::Seller, siwe_message));
         }
 
         if contract
@@ -62,7 +97,7 @@ impl Inspect {
             .iter()
             .any(|buyer| buyer.0 == sender_address)
         {
-            return Ok(RestrictionLevel::Buyer);
+            return Ok((RestrictionLevel::Buyer, siwe_message));
         }
 
         Err(DeferredDataError::Unauthorized)
@@ -83,14 +118,15 @@ impl Inspect {
 #[cfg(test)]
 mod test {
 
-    use std::str::FromStr;
-
     use candid::Nat;
-    use did::deferred::Seller;
+    use did::deferred::{ContractDocument, Seller};
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::app::test_utils::{alice, store_mock_contract, store_mock_contract_with};
+    use crate::app::test_utils::{
+        alice, bob, mock_signed_message, mock_siwe_message, store_mock_contract,
+        store_mock_contract_with, MOCK_SIGNER,
+    };
 
     #[test]
     fn test_should_inspect_if_minter() {
@@ -104,6 +140,10 @@ mod test {
         Configuration::set_owner(alice()).expect("Failed to set owner");
         assert_eq!(Inspect::inspect_is_owner(alice()), true);
         assert_eq!(Inspect::inspect_is_owner(Principal::anonymous()), false);
+        assert_eq!(Inspect::inspect_is_owner(bob()), false);
+
+        Configuration::set_sns_governance(Some(bob())).expect("Failed to set SNS governance");
+        assert_eq!(Inspect::inspect_is_owner(bob()), true);
     }
 
     #[test]
@@ -119,7 +159,7 @@ mod test {
         );
         assert_eq!(
             Inspect::inspect_modify_contract(Principal::anonymous(), &Nat::from(1u64)),
-            Err(DeferredDataError::Unauthorized)
+            Err(DeferredDataError::MissingPermission(DataPermission::Minter))
         );
 
         assert_eq!(
@@ -142,11 +182,41 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_should_inspect_upload() {
+        let upload_id = UploadStorage::begin(DocumentUpload {
+            contract_id: 1u64.into(),
+            document: ContractDocument {
+                access_list: vec![],
+                mime_type: "application/pdf".to_string(),
+                name: "deed.pdf".to_string(),
+                size: 1,
+            },
+            sha256: String::default(),
+            uploaded_by: alice(),
+            started_at: 0,
+            received: 0,
+            chunks: 0,
+        })
+        .unwrap();
+
+        assert!(Inspect::inspect_upload(alice(), upload_id).is_ok());
+        assert_eq!(
+            Inspect::inspect_upload(bob(), upload_id),
+            Err(DeferredDataError::Unauthorized)
+        );
+        assert_eq!(
+            Inspect::inspect_upload(alice(), upload_id + 1),
+            Err(DeferredDataError::Contract(
+                DataContractError::UploadNotFound(upload_id + 1)
+            ))
+        );
+    }
+
     #[test]
     fn test_should_verify_signature_if_seller() {
         // private key is: 0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
-        let eth_address =
-            did::H160::from_hex_str("0x8fd379246834eac74B8419FfdA202CF8051F7A03").unwrap();
+        let eth_address = did::H160::from_hex_str(MOCK_SIGNER).unwrap();
 
         store_mock_contract_with(1, 60, |contract| {
             contract.sellers = vec![Seller {
@@ -155,27 +225,23 @@ mod test {
             }]
         });
 
-        let message = "Hello, Ethereum!".to_string();
-        let signature = H520::from_str("0x0e9293c16d57e3ea35118a52cc7209871d07db4b74183fbd6758306c2475586a2f64a5837cd7b787bff49e9432aab76de43080b9d98675e8890e16ffc669e6cb1b").unwrap();
-
-        assert_eq!(
-            Inspect::inspect_signature(&Nat::from(1u64), signature, message),
-            Ok(RestrictionLevel::Seller)
-        );
+        let (message, signature) = mock_signed_message();
+        let (access_level, siwe_message) =
+            Inspect::inspect_signature(&Nat::from(1u64), signature, message).unwrap();
+        assert_eq!(access_level, RestrictionLevel::Seller);
+        assert_eq!(siwe_message.nonce, "Mj5fQ2xWkp");
     }
 
     #[test]
     fn test_should_verify_signature_if_buyer() {
-        let eth_address =
-            did::H160::from_hex_str("0x8fd379246834eac74B8419FfdA202CF8051F7A03").unwrap();
+        let eth_address = did::H160::from_hex_str(MOCK_SIGNER).unwrap();
 
         store_mock_contract_with(1, 60, |contract| contract.buyers = vec![eth_address]);
 
-        let message = "Hello, Ethereum!".to_string();
-        let signature = H520::from_str("0x0e9293c16d57e3ea35118a52cc7209871d07db4b74183fbd6758306c2475586a2f64a5837cd7b787bff49e9432aab76de43080b9d98675e8890e16ffc669e6cb1b").unwrap();
-
+        let (message, signature) = mock_signed_message();
         assert_eq!(
-            Inspect::inspect_signature(&Nat::from(1u64), signature, message),
+            Inspect::inspect_signature(&Nat::from(1u64), signature, message)
+                .map(|(access_level, _)| access_level),
             Ok(RestrictionLevel::Buyer)
         );
     }
@@ -184,12 +250,54 @@ mod test {
     fn test_should_verify_signature_if_invalid() {
         store_mock_contract(1, 60);
 
+        let (message, signature) = mock_signed_message();
+        assert_eq!(
+            Inspect::inspect_signature(&Nat::from(1u64), signature, message),
+            Err(DeferredDataError::Unauthorized)
+        );
+    }
+
+    #[test]
+    fn test_should_reject_signed_message_not_granting_access() {
+        let eth_address = did::H160::from_hex_str(MOCK_SIGNER).unwrap();
+        store_mock_contract_with(1, 60, |contract| contract.buyers = vec![eth_address]);
+
+        // any message signed by the buyer is not enough
         let message = "Hello, Ethereum!".to_string();
         let signature = H520::from_str("0x0e9293c16d57e3ea35118a52cc7209871d07db4b74183fbd6758306c2475586a2f64a5837cd7b787bff49e9432aab76de43080b9d98675e8890e16ffc669e6cb1b").unwrap();
+        assert_eq!(
+            Inspect::inspect_signature(&Nat::from(1u64), signature, message),
+            Err(SignedMessageError::Malformed("missing header".to_string()).into())
+        );
 
+        // the message has expired
+        let message = mock_siwe_message(
+            1,
+            "Tq8ZrV3nLd",
+            "2024-10-01T00:00:00Z",
+            "2024-10-02T00:00:00Z",
+        );
+        let signature = H520::from_str("0x47ce73e494812720563e8408a50d93fcfe50c1f9697610d40cfeb5332cefbcca4918fafa3681beebed35d096f96cad3eb1fd0b8074e7d6006f5396363da007ad1b").unwrap();
         assert_eq!(
             Inspect::inspect_signature(&Nat::from(1u64), signature, message),
-            Err(DeferredDataError::Unauthorized)
+            Err(SignedMessageError::Expired.into())
+        );
+
+        // the message is signed by another address
+        let other = "0x0000000000000000000000000000000000000001";
+        let message = mock_signed_message().0.replace(MOCK_SIGNER, other);
+        let signature = H520::from_str("0xe75d81718d6a469f36fbbb97793751be0bde3fafbc9167fbe43ab46f320f51186c6ed3134c078d4472cf37c7f6a6eb8f249d3d60850a98723447b777516d6ac11c").unwrap();
+        assert_eq!(
+            Inspect::inspect_signature(&Nat::from(1u64), signature, message),
+            Err(SignedMessageError::SignerMismatch(other.to_string()).into())
+        );
+
+        // the message is for another contract
+        store_mock_contract(2, 60);
+        let (message, signature) = mock_signed_message();
+        assert_eq!(
+            Inspect::inspect_signature(&Nat::from(2u64), signature, message),
+            Err(SignedMessageError::ContractMismatch(Nat::from(2u64)).into())
         );
     }
 }
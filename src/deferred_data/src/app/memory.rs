@@ -1,16 +1,59 @@
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager as IcMemoryManager};
-use ic_stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{DefaultMemoryImpl, Memory as _};
+
+/// Size of a stable memory page
+const WASM_PAGE_SIZE: u64 = 65536;
 
 pub const CONTRACTS_MEMORY_ID: MemoryId = MemoryId::new(10);
 pub const DOCUMENTS_MEMORY_ID: MemoryId = MemoryId::new(11);
 pub const NEXT_DOCUMENT_ID_MEMORY_ID: MemoryId = MemoryId::new(12);
 pub const REAL_ESTATE_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const AGENCY_MEMBERS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const AGENCIES_STATUS_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const AGENCY_TRANSFERS_AUDIT_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const UPLOADS_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const UPLOAD_CHUNKS_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const NEXT_UPLOAD_ID_MEMORY_ID: MemoryId = MemoryId::new(19);
 
 pub const MINTER_MEMORY_ID: MemoryId = MemoryId::new(20);
 pub const OWNER_MEMORY_ID: MemoryId = MemoryId::new(21);
 pub const LOG_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(22);
+pub const SNS_GOVERNANCE_MEMORY_ID: MemoryId = MemoryId::new(23);
+pub const READ_ONLY_MEMORY_ID: MemoryId = MemoryId::new(24);
+pub const ALLOWED_MIME_TYPES_MEMORY_ID: MemoryId = MemoryId::new(25);
+pub const CUSTOM_DOMAINS_MEMORY_ID: MemoryId = MemoryId::new(26);
+
+pub const AUDIT_LOG_MEMORY_ID: MemoryId = MemoryId::new(30);
+pub const AUDIT_LOG_BY_CALLER_MEMORY_ID: MemoryId = MemoryId::new(31);
+pub const AUDIT_LOG_BY_METHOD_MEMORY_ID: MemoryId = MemoryId::new(32);
+
+pub const SCHEMA_VERSIONS_MEMORY_ID: MemoryId = MemoryId::new(40);
+pub const MIGRATIONS_MEMORY_ID: MemoryId = MemoryId::new(41);
+
+pub const DOCUMENT_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(50);
+pub const DOCUMENT_HASHES_MEMORY_ID: MemoryId = MemoryId::new(51);
+pub const DOCUMENT_ANCHORS_MEMORY_ID: MemoryId = MemoryId::new(52);
+pub const DOCUMENT_CONTENTS_MEMORY_ID: MemoryId = MemoryId::new(53);
+pub const DOCUMENT_REFERENCES_MEMORY_ID: MemoryId = MemoryId::new(54);
+pub const AGENCY_QUOTAS_MEMORY_ID: MemoryId = MemoryId::new(55);
+pub const AGENCY_USAGE_MEMORY_ID: MemoryId = MemoryId::new(56);
+pub const CONTRACT_USAGE_MEMORY_ID: MemoryId = MemoryId::new(57);
+pub const DOCUMENT_PREVIEWS_MEMORY_ID: MemoryId = MemoryId::new(58);
+pub const DOCUMENT_CONTENT_CHUNKS_MEMORY_ID: MemoryId = MemoryId::new(59);
+pub const CONSUMED_NONCES_MEMORY_ID: MemoryId = MemoryId::new(60);
+pub const STREAMING_SESSIONS_MEMORY_ID: MemoryId = MemoryId::new(61);
 
 thread_local! {
     /// Memory manager
     pub static MEMORY_MANAGER: IcMemoryManager<DefaultMemoryImpl> = IcMemoryManager::init(DefaultMemoryImpl::default());
 }
+
+/// Returns the size in bytes of the stable memory of each [`MemoryId`] in use
+pub fn stable_memory_sizes() -> Vec<(u8, u64)> {
+    MEMORY_MANAGER.with(|mm| {
+        (0..u8::MAX)
+            .map(|id| (id, mm.get(MemoryId::new(id)).size() * WASM_PAGE_SIZE))
+            .filter(|(_, size)| *size > 0)
+            .collect()
+    })
+}
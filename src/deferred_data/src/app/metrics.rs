@@ -0,0 +1,117 @@
+//! # Metrics
+//!
+//! Operational metrics of the data canister, exported in the Prometheus text exposition format.
+
+use did::MetricsEncoder;
+use num_traits::cast::ToPrimitive;
+
+use crate::app::memory::stable_memory_sizes;
+use crate::app::storage::{
+    AgencyMemberStorage, AgencyStorage, ContractStorage, DocumentStorage, RealEstateStorage,
+    UploadStorage, UsageStorage,
+};
+use crate::utils::cycles;
+
+pub struct Metrics;
+
+impl Metrics {
+    /// Encode the metrics in the Prometheus text exposition format
+    pub fn encode() -> String {
+        let mut encoder = MetricsEncoder::default();
+        encoder
+            .gauge(
+                "deferred_data_cycles_balance",
+                "Cycles balance of the canister",
+                cycles().0.to_f64().unwrap_or_default(),
+            )
+            .gauge_vec(
+                "deferred_data_stable_memory_bytes",
+                "Size of the stable memory by memory id",
+                "memory_id",
+                stable_memory_sizes()
+                    .into_iter()
+                    .map(|(id, size)| (id.to_string(), size as f64)),
+            )
+            .gauge_vec(
+                "deferred_data_contracts",
+                "Amount of contracts by status",
+                "status",
+                [
+                    ("open", ContractStorage::count(false) as f64),
+                    ("closed", ContractStorage::count(true) as f64),
+                ],
+            )
+            .gauge(
+                "deferred_data_real_estates",
+                "Amount of real estates which are not deleted",
+                RealEstateStorage::count() as f64,
+            )
+            .gauge(
+                "deferred_data_documents",
+                "Amount of stored contract documents, counting once those with the same data",
+                DocumentStorage::count() as f64,
+            )
+            .gauge(
+                "deferred_data_document_uploads",
+                "Amount of contract documents being uploaded in chunks",
+                UploadStorage::count() as f64,
+            )
+            .gauge(
+                "deferred_data_agencies_suspended",
+                "Amount of suspended agencies",
+                AgencyStorage::count_suspended() as f64,
+            )
+            .gauge(
+                "deferred_data_agency_members",
+                "Amount of agency members",
+                AgencyMemberStorage::count() as f64,
+            )
+            .gauge_vec(
+                "deferred_data_agency_document_bytes",
+                "Bytes of the contract documents stored by each agency",
+                "agency",
+                UsageStorage::agencies_usage()
+                    .into_iter()
+                    .map(|(agency, usage)| (agency.to_string(), usage.document_bytes as f64)),
+            );
+
+        encoder.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::{AgencyId, AgencyStatus, StorageUsage};
+
+    use super::*;
+    use crate::app::test_utils::{bob, mock_real_estate, store_mock_contract, with_mock_contract};
+
+    #[test]
+    fn test_should_encode_metrics() {
+        store_mock_contract(1, 2);
+        store_mock_contract(2, 2);
+        ContractStorage::close_contract(&2u64.into()).unwrap();
+        RealEstateStorage::insert(mock_real_estate()).unwrap();
+        DocumentStorage::upload_document(vec![1, 2, 3]).unwrap();
+        AgencyStorage::set_status(AgencyId(1), AgencyStatus::Suspended);
+        AgencyMemberStorage::set(bob(), Some(AgencyId(1)));
+        let contract = with_mock_contract(3, 2, |contract| contract.agency = AgencyId(1));
+        UsageStorage::add(&contract, StorageUsage::document(3));
+
+        let metrics = Metrics::encode();
+        assert!(metrics.contains("deferred_data_cycles_balance 30000000000\n"));
+        assert!(metrics.contains("deferred_data_contracts{status=\"open\"} 1\n"));
+        assert!(metrics.contains("deferred_data_contracts{status=\"closed\"} 1\n"));
+        assert!(metrics.contains("deferred_data_real_estates 1\n"));
+        assert!(metrics.contains("deferred_data_documents 1\n"));
+        assert!(metrics.contains("deferred_data_document_uploads 0\n"));
+        assert!(metrics.contains("deferred_data_agencies_suspended 1\n"));
+        assert!(metrics.contains("deferred_data_agency_members 1\n"));
+        assert!(metrics.contains(&format!(
+            "deferred_data_agency_document_bytes{{agency=\"{}\"}} 3\n",
+            AgencyId(1)
+        )));
+        assert!(metrics.contains("deferred_data_stable_memory_bytes{memory_id=\"10\"}"));
+    }
+}
@@ -0,0 +1,371 @@
+//! Versioned schema migrations of the collections of Candid-encoded records.
+//!
+//! A schema version is stored alongside each collection. On upgrade, the collections whose
+//! version is behind the one listed in [`COLLECTIONS`] are queued for migration, and their records
+//! are migrated in batches of [`MIGRATION_BATCH_SIZE`], one batch per timer tick, so that a large
+//! collection never exceeds the instruction limit of a single message.
+//!
+//! Migrating a record consists of decoding it and encoding it again with the current schema.
+//! To make an incompatible change to a record:
+//!
+//! 1. keep the previous definition of the record (e.g. `ContractV1`) and make the
+//!    `Storable::from_bytes` of the record fall back to decoding the previous definition and
+//!    converting it, when decoding the current one fails;
+//! 2. bump the target version of the collection in [`COLLECTIONS`].
+//!
+//! Once every canister has migrated the collection, the fallback can be removed.
+//!
+//! The `documents` collection is migrated differently: its records are moved from the storage by
+//! ID to the content-addressed one, so that documents with the same data are stored once, and
+//! since version 2 the contents stored whole are split in chunks, so that a range of a document
+//! can be read without loading all of it.
+//! The `agency_usage` migration accounts the storage used by the documents of the contracts
+//! stored before the usage accounting was introduced.
+//!
+//! The `contracts` and `real_estates` collections are at version 2 since their agency is
+//! identified by the id assigned by the deferred minter rather than by the wallet of its owner;
+//! the records stored with the wallet are converted to its legacy agency id when decoded.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use did::deferred::{CollectionSchema, MigrationProgress, MigrationStatus};
+use did::{StorableNat, ID};
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, Storable as _};
+
+use super::storage::{ContractStorage, DocumentStorage, RealEstateStorage, UsageStorage};
+use crate::app::memory::{MEMORY_MANAGER, MIGRATIONS_MEMORY_ID, SCHEMA_VERSIONS_MEMORY_ID};
+use crate::utils::time;
+
+const AGENCY_USAGE: &str = "agency_usage";
+const CONTRACTS: &str = "contracts";
+const DOCUMENTS: &str = "documents";
+const REAL_ESTATES: &str = "real_estates";
+
+/// Collections with the schema version of the records written by this wasm
+const COLLECTIONS: &[(&str, u32)] = &[
+    (CONTRACTS, 2),
+    (DOCUMENTS, 2),
+    (REAL_ESTATES, 2),
+    (AGENCY_USAGE, 1),
+];
+
+/// Number of records migrated on each tick
+const MIGRATION_BATCH_SIZE: usize = 100;
+
+/// Number of documents migrated on each tick, lower since each one may be a few megabytes
+const DOCUMENTS_MIGRATION_BATCH_SIZE: usize = 10;
+
+thread_local! {
+    /// Schema version of the records stored in each collection
+    pub(super) static SCHEMA_VERSIONS: RefCell<BTreeMap<String, u32, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(SCHEMA_VERSIONS_MEMORY_ID))));
+
+    /// Pending migrations by collection
+    static MIGRATIONS: RefCell<BTreeMap<String, MigrationProgress, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(MIGRATIONS_MEMORY_ID))));
+}
+
+pub struct Migrations;
+
+impl Migrations {
+    /// Set every collection at its target schema version, on a fresh install
+    pub fn init() {
+        SCHEMA_VERSIONS.with_borrow_mut(|versions| {
+            for (collection, target) in COLLECTIONS {
+                versions.insert(collection.to_string(), *target);
+            }
+        });
+    }
+
+    /// Queue the migration of the collections behind their target schema version and schedule
+    /// the first batch.
+    ///
+    /// The collections stored before the schema versions were introduced are at version 0.
+    /// A migration left pending by the previous wasm is restarted from the first record
+    pub fn start() {
+        for (collection, target) in COLLECTIONS {
+            let version = Self::version(collection);
+            if version > *target {
+                log::warn!(
+                    "{collection} schema version {version} is newer than the supported one {target}"
+                );
+                continue;
+            }
+            if version == *target {
+                continue;
+            }
+
+            log::info!("Migrating {collection} from schema version {version} to {target}");
+            let progress = MigrationProgress {
+                collection: collection.to_string(),
+                from_version: version,
+                to_version: *target,
+                migrated: 0,
+                total: collection_len(collection),
+                started_at: time(),
+                cursor: None,
+            };
+            MIGRATIONS.with_borrow_mut(|migrations| {
+                migrations.insert(collection.to_string(), progress);
+            });
+        }
+
+        Self::schedule();
+    }
+
+    /// Migrate the next batch of records of the first pending migration.
+    ///
+    /// Once all the records of the collection have been migrated, its schema version is set to
+    /// the target one. The next tick is scheduled as long as there are pending migrations
+    pub fn tick() {
+        let Some((collection, mut progress)) =
+            MIGRATIONS.with_borrow(|migrations| migrations.first_key_value())
+        else {
+            return;
+        };
+
+        let after = progress
+            .cursor
+            .as_ref()
+            .map(|cursor| StorableNat::from_bytes(Cow::Borrowed(cursor)).0);
+        let (migrated, last) = migrate_batch(&collection, after.as_ref());
+        progress.migrated += migrated;
+
+        if migrated < batch_size(&collection) as u64 {
+            log::info!(
+                "Migrated {} {collection} to schema version {}",
+                progress.migrated,
+                progress.to_version
+            );
+            MIGRATIONS.with_borrow_mut(|migrations| migrations.remove(&collection));
+            SCHEMA_VERSIONS.with_borrow_mut(|versions| {
+                versions.insert(collection, progress.to_version);
+            });
+        } else {
+            progress.cursor = last.map(|key| StorableNat::from(key).to_bytes().into_owned());
+            MIGRATIONS.with_borrow_mut(|migrations| migrations.insert(collection, progress));
+        }
+
+        Self::schedule();
+    }
+
+    /// Get the schema version of the collections and the pending migrations
+    pub fn status() -> MigrationStatus {
+        MigrationStatus {
+            collections: COLLECTIONS
+                .iter()
+                .map(|(collection, target)| CollectionSchema {
+                    collection: collection.to_string(),
+                    version: Self::version(collection),
+                    target_version: *target,
+                })
+                .collect(),
+            pending: MIGRATIONS.with_borrow(|migrations| {
+                migrations.iter().map(|(_, progress)| progress).collect()
+            }),
+        }
+    }
+
+    fn version(collection: &str) -> u32 {
+        SCHEMA_VERSIONS
+            .with_borrow(|versions| versions.get(&collection.to_string()))
+            .unwrap_or_default()
+    }
+
+    /// Schedule the next tick if there are pending migrations
+    fn schedule() {
+        if cfg!(test) || MIGRATIONS.with_borrow(|migrations| migrations.is_empty()) {
+            return;
+        }
+
+        ic_cdk_timers::set_timer(Duration::ZERO, Self::tick);
+    }
+}
+
+fn collection_len(collection: &str) -> u64 {
+    match collection {
+        AGENCY_USAGE | CONTRACTS => ContractStorage::len(),
+        DOCUMENTS => DocumentStorage::count_stored_whole(),
+        REAL_ESTATES => RealEstateStorage::len(),
+        _ => 0,
+    }
+}
+
+/// Number of records of the collection migrated on each tick
+fn batch_size(collection: &str) -> usize {
+    match collection {
+        DOCUMENTS => DOCUMENTS_MIGRATION_BATCH_SIZE,
+        _ => MIGRATION_BATCH_SIZE,
+    }
+}
+
+/// Migrate the batch of records of the collection following the key `after`.
+///
+/// Returns the number of migrated records and the key of the last one
+fn migrate_batch(collection: &str, after: Option<&ID>) -> (u64, Option<ID>) {
+    match collection {
+        AGENCY_USAGE => UsageStorage::migrate(after, MIGRATION_BATCH_SIZE),
+        CONTRACTS => ContractStorage::migrate(after, MIGRATION_BATCH_SIZE),
+        DOCUMENTS => DocumentStorage::migrate(after, DOCUMENTS_MIGRATION_BATCH_SIZE),
+        REAL_ESTATES => RealEstateStorage::migrate(after, MIGRATION_BATCH_SIZE),
+        _ => {
+            log::warn!("Unknown collection {collection} in the pending migrations");
+            (0, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Encode;
+    use did::deferred::{AgencyId, Contract, ContractV1};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{
+        alice, mock_real_estate, store_mock_contract, with_mock_contract,
+    };
+
+    #[test]
+    fn test_should_set_target_versions_on_init() {
+        Migrations::init();
+        Migrations::start();
+
+        let status = Migrations::status();
+        assert!(status.is_completed());
+        assert_eq!(
+            status.collections,
+            vec![
+                CollectionSchema {
+                    collection: CONTRACTS.to_string(),
+                    version: 2,
+                    target_version: 2,
+                },
+                CollectionSchema {
+                    collection: DOCUMENTS.to_string(),
+                    version: 2,
+                    target_version: 2,
+                },
+                CollectionSchema {
+                    collection: REAL_ESTATES.to_string(),
+                    version: 2,
+                    target_version: 2,
+                },
+                CollectionSchema {
+                    collection: AGENCY_USAGE.to_string(),
+                    version: 1,
+                    target_version: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_migrate_collections_in_batches() {
+        for id in 0..250 {
+            store_mock_contract(id, 2);
+        }
+        RealEstateStorage::insert(mock_real_estate()).unwrap();
+
+        Migrations::start();
+        let status = Migrations::status();
+        assert_eq!(status.pending.len(), 4);
+        assert_eq!(status.pending[0].collection, AGENCY_USAGE);
+        assert_eq!(status.pending[0].total, 250);
+        assert_eq!(status.pending[1].collection, CONTRACTS);
+        assert_eq!(status.pending[1].from_version, 0);
+        assert_eq!(status.pending[1].total, 250);
+        assert_eq!(status.pending[2].collection, DOCUMENTS);
+        assert_eq!(status.pending[2].total, 0);
+        assert_eq!(status.pending[3].total, 1);
+
+        Migrations::tick();
+        let progress = Migrations::status().pending[0].clone();
+        assert_eq!(progress.migrated, 100);
+        assert!(progress.cursor.is_some());
+
+        for _ in 0..6 {
+            Migrations::tick();
+        }
+        let status = Migrations::status();
+        assert_eq!(status.pending.len(), 1);
+        assert_eq!(status.pending[0].collection, REAL_ESTATES);
+        assert_eq!(status.collections[0].version, 2);
+        assert_eq!(status.collections[1].version, 2);
+        assert_eq!(status.collections[3].version, 1);
+
+        Migrations::tick();
+        assert!(Migrations::status().is_completed());
+        assert_eq!(ContractStorage::len(), 250);
+        assert!(ContractStorage::get_contract(&ID::from(249u64)).is_some());
+    }
+
+    #[test]
+    fn test_should_migrate_documents_in_batches() {
+        let whole = DocumentStorage::insert_stored_whole(vec![9]);
+        for id in 1..=25u64 {
+            DocumentStorage::insert_stored_by_id(id, vec![(id % 5) as u8]);
+        }
+
+        Migrations::start();
+        assert_eq!(Migrations::status().pending[2].total, 26);
+        for _ in 0..6 {
+            Migrations::tick();
+        }
+        assert!(Migrations::status().is_completed());
+        assert_eq!(DocumentStorage::count_stored_whole(), 0);
+        assert_eq!(DocumentStorage::count(), 6);
+        assert_eq!(DocumentStorage::get_document(24).unwrap(), vec![4]);
+        assert_eq!(DocumentStorage::get_document(whole).unwrap(), vec![9]);
+    }
+
+    #[test]
+    fn test_should_migrate_agency_wallets_to_agency_ids() {
+        let contract = with_mock_contract(1, 2, |_| {});
+        let legacy = ContractV1 {
+            id: contract.id,
+            r#type: contract.r#type,
+            sellers: contract.sellers,
+            buyers: contract.buyers,
+            installments: contract.installments,
+            value: contract.value,
+            deposit: contract.deposit,
+            currency: contract.currency,
+            properties: contract.properties,
+            restricted_properties: contract.restricted_properties,
+            documents: contract.documents,
+            agency: alice(),
+            real_estate: contract.real_estate,
+            expiration: contract.expiration,
+            closed: contract.closed,
+        };
+        ContractStorage::insert_contract(Contract::from_bytes(Encode!(&legacy).unwrap().into()));
+
+        Migrations::start();
+        for _ in 0..4 {
+            Migrations::tick();
+        }
+        assert!(Migrations::status().is_completed());
+        assert_eq!(
+            ContractStorage::get_contract(&ID::from(1u64))
+                .unwrap()
+                .agency,
+            AgencyId::legacy(alice())
+        );
+    }
+
+    #[test]
+    fn test_should_complete_migration_of_empty_collection() {
+        Migrations::start();
+        Migrations::tick();
+        Migrations::tick();
+        Migrations::tick();
+        Migrations::tick();
+
+        assert!(Migrations::status().is_completed());
+    }
+}
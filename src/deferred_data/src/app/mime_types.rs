@@ -0,0 +1,235 @@
+//! # MIME types
+//!
+//! Validation of the MIME type declared for the uploaded documents, which must be allowed and
+//! match the type detected from the magic bytes of their data.
+
+use did::deferred::{DataContractError, DeferredDataError, DeferredDataResult};
+
+use super::configuration::Configuration;
+
+const PDF: &str = "application/pdf";
+const JPEG: &str = "image/jpeg";
+const PNG: &str = "image/png";
+const GIF: &str = "image/gif";
+const WEBP: &str = "image/webp";
+const DOC: &str = "application/msword";
+const XLS: &str = "application/vnd.ms-excel";
+const PPT: &str = "application/vnd.ms-powerpoint";
+const DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+const XLSX: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+const PPTX: &str = "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+const ODT: &str = "application/vnd.oasis.opendocument.text";
+const ODS: &str = "application/vnd.oasis.opendocument.spreadsheet";
+const ODP: &str = "application/vnd.oasis.opendocument.presentation";
+const ZIP: &str = "application/zip";
+/// Type reported for the data which isn't recognized
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Bytes at the start of a document read to detect its type when it's uploaded in chunks,
+/// covering the magic bytes and the local headers of the first entries of a ZIP archive
+pub const MIME_SNIFF_SIZE: usize = 64 * 1024;
+
+/// Types which are detected from their magic bytes; documents declared with one of them must
+/// have matching data
+const DETECTED_TYPES: &[&str] = &[
+    PDF, JPEG, PNG, GIF, WEBP, DOC, XLS, PPT, DOCX, XLSX, PPTX, ODT, ODS, ODP, ZIP,
+];
+
+pub struct MimeTypes;
+
+impl MimeTypes {
+    /// Check whether documents of the declared type can be uploaded
+    pub fn require_allowed(mime_type: &str) -> DeferredDataResult<()> {
+        let mime_type = Self::normalize(mime_type);
+        if Configuration::get_allowed_mime_types().contains(&mime_type) {
+            Ok(())
+        } else {
+            Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeNotAllowed(mime_type),
+            ))
+        }
+    }
+
+    /// Check whether a document of the declared type can be uploaded with the provided data.
+    ///
+    /// Types which are not detected from the magic bytes are accepted, as long as they are
+    /// allowed and the data isn't recognized as another type
+    pub fn validate(mime_type: &str, data: &[u8]) -> DeferredDataResult<()> {
+        Self::require_allowed(mime_type)?;
+
+        let mime_type = Self::normalize(mime_type);
+        let detected = Self::detect(data);
+        if detected.contains(&mime_type.as_str()) {
+            return Ok(());
+        }
+        match detected.first() {
+            None if !DETECTED_TYPES.contains(&mime_type.as_str()) => Ok(()),
+            actual => Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeMismatch(
+                    mime_type,
+                    actual.unwrap_or(&OCTET_STREAM).to_string(),
+                ),
+            )),
+        }
+    }
+
+    /// Normalize a MIME type, lowercasing it and removing its parameters
+    pub fn normalize(mime_type: &str) -> String {
+        mime_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+    }
+
+    /// Check whether the MIME type is a valid `type/subtype`, with no parameters
+    pub fn is_valid(mime_type: &str) -> bool {
+        let is_token = |token: &str| {
+            !token.is_empty()
+                && token
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+        };
+
+        mime_type
+            .split_once('/')
+            .is_some_and(|(kind, subtype)| is_token(kind) && is_token(subtype))
+    }
+
+    /// Detect the types which the data can be, from its magic bytes.
+    ///
+    /// The legacy office documents share the same container, so all of them are returned
+    fn detect(data: &[u8]) -> &'static [&'static str] {
+        const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        const OLE_SIGNATURE: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+        if data.starts_with(b"%PDF-") {
+            &[PDF]
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            &[JPEG, "image/jpg"]
+        } else if data.starts_with(PNG_SIGNATURE) {
+            &[PNG]
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            &[GIF]
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+            &[WEBP]
+        } else if data.starts_with(OLE_SIGNATURE) {
+            &[DOC, XLS, PPT]
+        } else if data.starts_with(b"PK\x03\x04") {
+            Self::detect_zip(data)
+        } else {
+            &[]
+        }
+    }
+
+    /// Detect the office documents stored in a ZIP archive.
+    ///
+    /// OpenDocument files start with the uncompressed `mimetype` entry, while Office Open XML
+    /// files are recognized by the name of their main part, listed in the central directory
+    fn detect_zip(data: &[u8]) -> &'static [&'static str] {
+        const OPEN_DOCUMENTS: &[&[&str]] = &[&[ODT], &[ODS], &[ODP]];
+
+        if data.get(30..38) == Some(b"mimetype".as_slice()) {
+            let content = data.get(38..).unwrap_or_default();
+            if let Some(detected) = OPEN_DOCUMENTS
+                .iter()
+                .copied()
+                .find(|detected| content.starts_with(detected[0].as_bytes()))
+            {
+                return detected;
+            }
+        }
+
+        let contains = |name: &[u8]| data.windows(name.len()).any(|window| window == name);
+        if contains(b"word/document.xml") {
+            &[DOCX]
+        } else if contains(b"xl/workbook.xml") {
+            &[XLSX]
+        } else if contains(b"ppt/presentation.xml") {
+            &[PPTX]
+        } else {
+            &[ZIP]
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{mock_jpeg, mock_pdf};
+
+    /// A ZIP archive with a single stored entry
+    fn zip(name: &str, content: &[u8]) -> Vec<u8> {
+        let mut data = b"PK\x03\x04".to_vec();
+        data.extend_from_slice(&[0; 14]);
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(content);
+
+        data
+    }
+
+    #[test]
+    fn test_should_detect_mime_types() {
+        assert_eq!(MimeTypes::detect(&mock_pdf(&[1, 2, 3])), &[PDF]);
+        assert_eq!(MimeTypes::detect(&mock_jpeg(2, 2)), &[JPEG, "image/jpg"]);
+        assert_eq!(MimeTypes::detect(b"GIF89a\x01\x00"), &[GIF]);
+        assert_eq!(MimeTypes::detect(b"RIFF\x04\x00\x00\x00WEBPVP8 "), &[WEBP]);
+        assert_eq!(
+            MimeTypes::detect(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0]),
+            &[DOC, XLS, PPT]
+        );
+        assert_eq!(MimeTypes::detect(&zip("mimetype", ODS.as_bytes())), &[ODS]);
+        assert_eq!(
+            MimeTypes::detect(&zip("word/document.xml", b"<w/>")),
+            &[DOCX]
+        );
+        assert_eq!(MimeTypes::detect(&zip("notes.txt", b"hello")), &[ZIP]);
+        assert!(MimeTypes::detect(b"<html></html>").is_empty());
+    }
+
+    #[test]
+    fn test_should_validate_declared_mime_type() {
+        assert!(MimeTypes::validate("application/pdf", &mock_pdf(&[1, 2, 3])).is_ok());
+        assert!(MimeTypes::validate("Application/PDF; version=1.7", &mock_pdf(&[1])).is_ok());
+        assert_eq!(
+            MimeTypes::validate("application/pdf", &mock_jpeg(2, 2)),
+            Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeMismatch(PDF.to_string(), JPEG.to_string())
+            ))
+        );
+        assert_eq!(
+            MimeTypes::validate("image/png", b"<html></html>"),
+            Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeMismatch(PNG.to_string(), OCTET_STREAM.to_string())
+            ))
+        );
+        assert_eq!(
+            MimeTypes::validate("text/html", b"<html></html>"),
+            Err(DeferredDataError::Contract(
+                DataContractError::MimeTypeNotAllowed("text/html".to_string())
+            ))
+        );
+
+        // allowed types which are not detected are accepted, unless the data is another type
+        Configuration::set_allowed_mime_types(vec!["text/plain".to_string()]).unwrap();
+        assert!(MimeTypes::validate("text/plain", b"hello").is_ok());
+        assert!(MimeTypes::validate("text/plain", &mock_pdf(&[1])).is_err());
+    }
+
+    #[test]
+    fn test_should_check_mime_type_format() {
+        assert!(MimeTypes::is_valid("application/vnd.ms-excel"));
+        assert!(MimeTypes::is_valid("image/svg+xml"));
+        assert!(!MimeTypes::is_valid("application"));
+        assert!(!MimeTypes::is_valid("text/plain; charset=utf-8"));
+        assert!(!MimeTypes::is_valid("/pdf"));
+    }
+}
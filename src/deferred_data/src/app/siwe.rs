@@ -0,0 +1,401 @@
+//! # Sign-In with Ethereum
+//!
+//! Messages signed by the sellers and the buyers to access the restricted data of a contract,
+//! following the [EIP-4361](https://eips.ethereum.org/EIPS/eip-4361) format.
+
+use std::iter::Peekable;
+use std::str::{FromStr, Lines};
+
+use candid::Principal;
+use did::deferred::{DeferredDataError, DeferredDataResult, SignedMessageError};
+use did::ID;
+use ethers_core::types::H160;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use url::{Host, Url};
+
+/// Allowance for the clock of the signer being ahead or behind the canister (nanoseconds)
+pub const CLOCK_SKEW: u64 = 5 * 60 * 1_000_000_000;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// A Sign-In with Ethereum message.
+///
+/// Only the fields which are checked by the canister are kept; the expiration time, which is
+/// optional in EIP-4361, is required so that a message can't be used forever
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiweMessage {
+    /// Domain requesting the signing, with its port if any
+    pub domain: String,
+    /// Address which signed the message
+    pub address: H160,
+    /// Resource which the message grants access to
+    pub uri: Url,
+    /// Random token which can be used once to access the restricted documents
+    pub nonce: String,
+    /// Time when the message has been signed (nanoseconds)
+    pub issued_at: u64,
+    /// Time when the message expires (nanoseconds)
+    pub expiration_time: u64,
+    /// Time when the message becomes valid (nanoseconds), if other than `issued_at`
+    pub not_before: Option<u64>,
+}
+
+impl SiweMessage {
+    /// Check whether the message grants access to a contract of the canister at `now`
+    /// (nanoseconds).
+    ///
+    /// The domain must be exactly one of the canister: `<canister-id>.icp0.io`,
+    /// `<canister-id>.raw.icp0.io`, `<canister-id>.ic0.app` or one of its `custom_domains`, with
+    /// no port. The URI must be the one of the contract on that domain, such as
+    /// `https://<canister-id>.icp0.io/contract/<id>`. The times are checked allowing for
+    /// [`CLOCK_SKEW`].
+    pub fn validate(
+        &self,
+        canister: Principal,
+        custom_domains: &[String],
+        contract: &ID,
+        now: u64,
+    ) -> DeferredDataResult<()> {
+        let domain = self.domain.to_ascii_lowercase();
+        let is_canister_domain = ["icp0.io", "raw.icp0.io", "ic0.app"]
+            .iter()
+            .any(|suffix| domain == format!("{canister}.{suffix}"))
+            || custom_domains.contains(&domain);
+        if !is_canister_domain {
+            return Err(SignedMessageError::DomainMismatch(self.domain.clone()).into());
+        }
+        if self.uri.port().is_some() || self.uri.host_str() != Some(domain.as_str()) {
+            return Err(SignedMessageError::DomainMismatch(self.uri.to_string()).into());
+        }
+
+        let contract_path = format!("/contract/{}", contract.0);
+        let path = self.uri.path();
+        if path != contract_path && !path.starts_with(&format!("{contract_path}/")) {
+            return Err(SignedMessageError::ContractMismatch(contract.clone()).into());
+        }
+
+        let valid_from = self
+            .not_before
+            .unwrap_or(self.issued_at)
+            .max(self.issued_at);
+        if now.saturating_add(CLOCK_SKEW) < valid_from {
+            return Err(SignedMessageError::NotYetValid.into());
+        }
+        if now.saturating_sub(CLOCK_SKEW) >= self.expiration_time {
+            return Err(SignedMessageError::Expired.into());
+        }
+
+        Ok(())
+    }
+
+    /// Get the value of the next line if it is the `name` field
+    fn optional_field<'a>(lines: &mut Peekable<Lines<'a>>, name: &str) -> Option<&'a str> {
+        let line: &'a str = *lines.peek()?;
+        let value = line.strip_prefix(name)?.strip_prefix(": ")?;
+        lines.next();
+
+        Some(value)
+    }
+
+    /// Get the value of the next line, which must be the `name` field
+    fn field<'a>(lines: &mut Peekable<Lines<'a>>, name: &str) -> DeferredDataResult<&'a str> {
+        Self::optional_field(lines, name).ok_or_else(|| malformed(format!("missing {name}")))
+    }
+
+    /// Consume the next line, which must be blank
+    fn blank_line(lines: &mut Peekable<Lines>) -> DeferredDataResult<()> {
+        lines
+            .next_if(|line| line.is_empty())
+            .map(|_| ())
+            .ok_or_else(|| malformed("missing blank line"))
+    }
+
+    /// Parse an RFC 3339 timestamp into nanoseconds
+    fn timestamp(value: &str) -> DeferredDataResult<u64> {
+        OffsetDateTime::parse(value, &Rfc3339)
+            .ok()
+            .and_then(|datetime| u64::try_from(datetime.unix_timestamp_nanos()).ok())
+            .ok_or_else(|| malformed(format!("invalid timestamp {value}")))
+    }
+}
+
+impl FromStr for SiweMessage {
+    type Err = DeferredDataError;
+
+    fn from_str(message: &str) -> Result<Self, Self::Err> {
+        let mut lines = message.lines().peekable();
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(HEADER_SUFFIX))
+            .map(|origin| {
+                origin
+                    .split_once("://")
+                    .map_or(origin, |(_, domain)| domain)
+            })
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| malformed("missing header"))?
+            .to_string();
+        let address = lines
+            .next()
+            .and_then(|line| line.strip_prefix("0x"))
+            .filter(|address| address.len() == 40)
+            .and_then(|address| H160::from_str(address).ok())
+            .ok_or_else(|| malformed("invalid address"))?;
+
+        // the statement is optional, and surrounded by blank lines
+        Self::blank_line(&mut lines)?;
+        lines.next_if(|line| !line.is_empty());
+        Self::blank_line(&mut lines)?;
+
+        let uri = Self::field(&mut lines, "URI")?;
+        let uri = Url::parse(uri).map_err(|_| malformed(format!("invalid URI {uri}")))?;
+        if Self::field(&mut lines, "Version")? != "1" {
+            return Err(malformed("unsupported version"));
+        }
+        // the chain ID is required by EIP-4361, but the access doesn't depend on the chain of the
+        // signer, whose address is recovered from the signature alone
+        Self::field(&mut lines, "Chain ID")?;
+        let nonce = Self::field(&mut lines, "Nonce")?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(malformed(format!("invalid nonce {nonce}")));
+        }
+        let issued_at = Self::timestamp(Self::field(&mut lines, "Issued At")?)?;
+        let expiration_time = Self::timestamp(Self::field(&mut lines, "Expiration Time")?)?;
+        let not_before = Self::optional_field(&mut lines, "Not Before")
+            .map(Self::timestamp)
+            .transpose()?;
+        Self::optional_field(&mut lines, "Request ID");
+        if lines.next_if_eq(&"Resources:").is_some() {
+            while lines.next_if(|line| line.starts_with("- ")).is_some() {}
+        }
+        if lines.next().is_some() {
+            return Err(malformed("unexpected content"));
+        }
+
+        Ok(Self {
+            domain,
+            address,
+            uri,
+            nonce: nonce.to_string(),
+            issued_at,
+            expiration_time,
+            not_before,
+        })
+    }
+}
+
+/// Whether `domain` is a host name which can be set as a custom domain of the canister, with no
+/// scheme, port or path
+pub fn is_valid_domain(domain: &str) -> bool {
+    matches!(Host::parse(domain), Ok(Host::Domain(host)) if host == domain) && domain.contains('.')
+}
+
+fn malformed(reason: impl Into<String>) -> DeferredDataError {
+    SignedMessageError::Malformed(reason.into()).into()
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::mock_siwe_message;
+    use crate::utils::canister_id;
+
+    /// 2024-10-01T00:00:00Z
+    const ISSUED_AT: u64 = 1_727_740_800_000_000_000;
+    /// 2024-10-02T00:00:00Z
+    const EXPIRATION_TIME: u64 = 1_727_827_200_000_000_000;
+
+    #[test]
+    fn test_should_parse_message() {
+        let message = SiweMessage::from_str(&mock_siwe_message(
+            1,
+            "Mj5fQ2xWkp",
+            "2024-10-01T00:00:00Z",
+            "2024-10-02T02:00:00+02:00",
+        ))
+        .unwrap();
+
+        assert_eq!(message.domain, "v5vof-zqaaa-aaaal-ai5cq-cai.icp0.io");
+        assert_eq!(
+            message.address,
+            H160::from_str("8fd379246834eac74B8419FfdA202CF8051F7A03").unwrap()
+        );
+        assert_eq!(message.uri.path(), "/contract/1");
+        assert_eq!(message.nonce, "Mj5fQ2xWkp");
+        assert_eq!(message.issued_at, ISSUED_AT);
+        assert_eq!(message.expiration_time, EXPIRATION_TIME);
+        assert_eq!(message.not_before, None);
+    }
+
+    #[test]
+    fn test_should_parse_message_with_optional_fields() {
+        let message = "https://v5vof-zqaaa-aaaal-ai5cq-cai.icp0.io wants you to sign in with your Ethereum account:\n\
+            0x8fd379246834eac74B8419FfdA202CF8051F7A03\n\
+            \n\
+            \n\
+            URI: https://v5vof-zqaaa-aaaal-ai5cq-cai.icp0.io/contract/1\n\
+            Version: 1\n\
+            Chain ID: 11155111\n\
+            Nonce: Mj5fQ2xWkp\n\
+            Issued At: 2024-10-01T00:00:00Z\n\
+            Expiration Time: 2024-10-02T00:00:00Z\n\
+            Not Before: 2024-10-01T12:00:00Z\n\
+            Request ID: 42\n\
+            Resources:\n\
+            - https://v5vof-zqaaa-aaaal-ai5cq-cai.icp0.io/contract/1/document/1";
+
+        let message = SiweMessage::from_str(message).unwrap();
+        assert_eq!(message.domain, "v5vof-zqaaa-aaaal-ai5cq-cai.icp0.io");
+        assert_eq!(
+            message.not_before,
+            Some(ISSUED_AT + 12 * 60 * 60 * 1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_should_reject_malformed_message() {
+        let valid = mock_siwe_message(
+            1,
+            "Mj5fQ2xWkp",
+            "2024-10-01T00:00:00Z",
+            "2024-10-02T00:00:00Z",
+        );
+
+        for (message, reason) in [
+            ("Hello, Ethereum!".to_string(), "missing header"),
+            (valid.replace("0x8fd3", "0x8fd"), "invalid address"),
+            (
+                valid.replace("Version: 1", "Version: 2"),
+                "unsupported version",
+            ),
+            (valid.replace("Mj5fQ2xWkp", "Mj5f"), "invalid nonce Mj5f"),
+            (
+                valid.replace("2024-10-01T00:00:00Z", "yesterday"),
+                "invalid timestamp yesterday",
+            ),
+            (
+                valid.replace("\nExpiration Time: 2024-10-02T00:00:00Z", ""),
+                "missing Expiration Time",
+            ),
+            (format!("{valid}\nSigned by: bob"), "unexpected content"),
+        ] {
+            assert_eq!(
+                SiweMessage::from_str(&message),
+                Err(SignedMessageError::Malformed(reason.to_string()).into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_should_validate_message() {
+        let contract = ID::from(1u64);
+        let message = SiweMessage::from_str(&mock_siwe_message(
+            1,
+            "Mj5fQ2xWkp",
+            "2024-10-01T00:00:00Z",
+            "2024-10-02T00:00:00Z",
+        ))
+        .unwrap();
+
+        assert!(message
+            .validate(canister_id(), &[], &contract, ISSUED_AT)
+            .is_ok());
+        // the clocks can be skewed
+        assert!(message
+            .validate(canister_id(), &[], &contract, ISSUED_AT - CLOCK_SKEW)
+            .is_ok());
+        assert!(message
+            .validate(
+                canister_id(),
+                &[],
+                &contract,
+                EXPIRATION_TIME + CLOCK_SKEW - 1
+            )
+            .is_ok());
+        assert_eq!(
+            message.validate(canister_id(), &[], &contract, ISSUED_AT - CLOCK_SKEW - 1),
+            Err(SignedMessageError::NotYetValid.into())
+        );
+        assert_eq!(
+            message.validate(canister_id(), &[], &contract, EXPIRATION_TIME + CLOCK_SKEW),
+            Err(SignedMessageError::Expired.into())
+        );
+
+        // the message is bound to the canister and the contract
+        assert_eq!(
+            message.validate(Principal::management_canister(), &[], &contract, ISSUED_AT),
+            Err(SignedMessageError::DomainMismatch(message.domain.clone()).into())
+        );
+        assert_eq!(
+            message.validate(canister_id(), &[], &ID::from(10u64), ISSUED_AT),
+            Err(SignedMessageError::ContractMismatch(ID::from(10u64)).into())
+        );
+        let mut other_host = message.clone();
+        other_host.uri = Url::parse("https://example.com/contract/1").unwrap();
+        assert_eq!(
+            other_host.validate(canister_id(), &[], &contract, ISSUED_AT),
+            Err(
+                SignedMessageError::DomainMismatch("https://example.com/contract/1".to_string())
+                    .into()
+            )
+        );
+    }
+    #[test]
+    fn test_should_validate_domain_of_message() {
+        let contract = ID::from(1u64);
+        let valid = mock_siwe_message(
+            1,
+            "Mj5fQ2xWkp",
+            "2024-10-01T00:00:00Z",
+            "2024-10-02T00:00:00Z",
+        );
+        let custom_domains = ["docs.ekoke.io".to_string()];
+        let with_domain = |domain: &str| {
+            SiweMessage::from_str(&valid.replace(&format!("{}.icp0.io", canister_id()), domain))
+                .unwrap()
+        };
+
+        for domain in [
+            format!("{}.raw.icp0.io", canister_id()),
+            format!("{}.ic0.app", canister_id()),
+            "docs.ekoke.io".to_string(),
+        ] {
+            assert!(with_domain(&domain)
+                .validate(canister_id(), &custom_domains, &contract, ISSUED_AT)
+                .is_ok());
+        }
+        // only the exact domains of the canister are accepted
+        for domain in [
+            format!("{}.evil.com", canister_id()),
+            format!("{}.icp0.io.evil.com", canister_id()),
+            format!("{}.icp0.io:8080", canister_id()),
+            "evil.ekoke.io".to_string(),
+        ] {
+            let message = with_domain(&domain);
+            assert_eq!(
+                message.validate(canister_id(), &custom_domains, &contract, ISSUED_AT),
+                Err(SignedMessageError::DomainMismatch(message.domain.clone()).into())
+            );
+        }
+        // the custom domains must be set
+        assert!(with_domain("docs.ekoke.io")
+            .validate(canister_id(), &[], &contract, ISSUED_AT)
+            .is_err());
+    }
+
+    #[test]
+    fn test_should_validate_custom_domain() {
+        assert!(is_valid_domain("docs.ekoke.io"));
+        assert!(!is_valid_domain("localhost"));
+        assert!(!is_valid_domain("docs.ekoke.io:443"));
+        assert!(!is_valid_domain("https://docs.ekoke.io"));
+        assert!(!is_valid_domain("docs.ekoke.io/contract"));
+        assert!(!is_valid_domain("127.0.0.1"));
+        assert!(!is_valid_domain(""));
+    }
+}
@@ -1,25 +1,52 @@
 use std::cell::RefCell;
+use std::ops::Bound;
 
 use did::deferred::{
-    Contract, DataContractError, DeferredDataError, DeferredDataResult, RealEstate, RealEstateError,
+    AgencyId, AgencyQuota, AgencyStatus, AgencyTransferAuditEntry, Contract, DataContractError,
+    DeferredDataError, DeferredDataResult, DocumentHistory, DocumentPreviews, DocumentUpload,
+    DocumentsAnchor, RealEstate, RealEstateError, StorageUsage, StreamingSession,
 };
-use did::{StorableNat, ID};
+use did::{StorableNat, StorablePrincipal, ID};
 use ic_stable_structures::memory_manager::VirtualMemory;
-use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell, Storable};
 
 use crate::app::memory::{
-    CONTRACTS_MEMORY_ID, DOCUMENTS_MEMORY_ID, MEMORY_MANAGER, NEXT_DOCUMENT_ID_MEMORY_ID,
-    REAL_ESTATE_MEMORY_ID,
+    AGENCIES_STATUS_MEMORY_ID, AGENCY_MEMBERS_MEMORY_ID, AGENCY_QUOTAS_MEMORY_ID,
+    AGENCY_TRANSFERS_AUDIT_MEMORY_ID, AGENCY_USAGE_MEMORY_ID, CONSUMED_NONCES_MEMORY_ID,
+    CONTRACTS_MEMORY_ID, CONTRACT_USAGE_MEMORY_ID, DOCUMENTS_MEMORY_ID, DOCUMENT_ANCHORS_MEMORY_ID,
+    DOCUMENT_CONTENTS_MEMORY_ID, DOCUMENT_CONTENT_CHUNKS_MEMORY_ID, DOCUMENT_HASHES_MEMORY_ID,
+    DOCUMENT_HISTORY_MEMORY_ID, DOCUMENT_PREVIEWS_MEMORY_ID, DOCUMENT_REFERENCES_MEMORY_ID,
+    MEMORY_MANAGER, NEXT_DOCUMENT_ID_MEMORY_ID, NEXT_UPLOAD_ID_MEMORY_ID, REAL_ESTATE_MEMORY_ID,
+    STREAMING_SESSIONS_MEMORY_ID, UPLOADS_MEMORY_ID, UPLOAD_CHUNKS_MEMORY_ID,
 };
 
+mod agencies;
+mod agency_members;
 mod contracts;
+mod document_anchors;
+mod document_history;
+mod document_previews;
 mod documents;
+mod export;
+mod nonces;
 mod real_estate;
+mod streaming_sessions;
+mod uploads;
+mod usage;
 
-use documents::DocumentStorage;
-
+pub use self::agencies::AgencyStorage;
+pub use self::agency_members::AgencyMemberStorage;
 pub use self::contracts::ContractStorage;
+pub use self::document_anchors::DocumentAnchorStorage;
+pub use self::document_history::DocumentHistoryStorage;
+pub use self::document_previews::DocumentPreviewStorage;
+pub use self::documents::DocumentStorage;
+pub use self::export::ExportStorage;
+pub use self::nonces::NonceStorage;
 pub use self::real_estate::RealEstateStorage;
+pub use self::streaming_sessions::StreamingSessionStorage;
+pub use self::uploads::UploadStorage;
+pub use self::usage::UsageStorage;
 
 thread_local! {
 
@@ -27,10 +54,41 @@ thread_local! {
     static CONTRACTS: RefCell<BTreeMap<StorableNat, Contract, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(CONTRACTS_MEMORY_ID))));
 
-    /// Documents storage storage (assoc between ID and document data)
+    /// Documents stored before the deduplication (assoc between ID and document data), moved to
+    /// [`DOCUMENT_CONTENT_CHUNKS`] by the `documents` migration
     static DOCUMENTS: RefCell<BTreeMap<u64, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENTS_MEMORY_ID))));
 
+    /// Version history of the contract documents (assoc between document id and history)
+    static DOCUMENT_HISTORY: RefCell<BTreeMap<u64, DocumentHistory, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_HISTORY_MEMORY_ID))));
+
+    /// SHA-256 of the stored documents (assoc between document id and hex hash)
+    static DOCUMENT_HASHES: RefCell<BTreeMap<u64, String, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_HASHES_MEMORY_ID))));
+
+    /// Content-addressed documents data stored whole, moved to [`DOCUMENT_CONTENT_CHUNKS`] by the
+    /// `documents` migration (assoc between hex SHA-256 and document data)
+    static DOCUMENT_CONTENTS: RefCell<BTreeMap<String, Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_CONTENTS_MEMORY_ID))));
+
+    /// Content-addressed documents data in chunks, so that a range of a document can be read
+    /// without loading all of it (assoc between SHA-256 and chunk index and data)
+    static DOCUMENT_CONTENT_CHUNKS: RefCell<BTreeMap<([u8; 32], u64), Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_CONTENT_CHUNKS_MEMORY_ID))));
+
+    /// Amount of documents referencing each content (assoc between hex SHA-256 and count)
+    static DOCUMENT_REFERENCES: RefCell<BTreeMap<String, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_REFERENCES_MEMORY_ID))));
+
+    /// Anchored Merkle roots of the documents of the contracts
+    static DOCUMENT_ANCHORS: RefCell<BTreeMap<StorableNat, DocumentsAnchor, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_ANCHORS_MEMORY_ID))));
+
+    /// Thumbnails and previews of the image documents (assoc between data id and previews)
+    static DOCUMENT_PREVIEWS: RefCell<BTreeMap<u64, DocumentPreviews, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(DOCUMENT_PREVIEWS_MEMORY_ID))));
+
     /// Next document ID
     static NEXT_DOCUMENT_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NEXT_DOCUMENT_ID_MEMORY_ID)), 0u64).unwrap()
@@ -40,6 +98,51 @@ thread_local! {
     static REAL_ESTATES: RefCell<BTreeMap<StorableNat, RealEstate, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(REAL_ESTATE_MEMORY_ID))));
 
+    /// Agency members storage (assoc between member and agency id), kept in sync by the minter
+    static AGENCY_MEMBERS: RefCell<BTreeMap<StorablePrincipal, AgencyId, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_MEMBERS_MEMORY_ID))));
+
+    /// Status of the agencies which are not active, kept in sync by the minter
+    static AGENCIES_STATUS: RefCell<BTreeMap<AgencyId, AgencyStatus, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCIES_STATUS_MEMORY_ID))));
+
+    /// Audit log of the records moved between agencies (assoc between transfer id and entry)
+    static AGENCY_TRANSFERS_AUDIT: RefCell<BTreeMap<u64, AgencyTransferAuditEntry, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_TRANSFERS_AUDIT_MEMORY_ID))));
+
+    /// Documents being uploaded in chunks (assoc between upload id and upload)
+    static UPLOADS: RefCell<BTreeMap<u64, DocumentUpload, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(UPLOADS_MEMORY_ID))));
+
+    /// Chunks of the documents being uploaded (assoc between upload id and chunk index and data)
+    static UPLOAD_CHUNKS: RefCell<BTreeMap<(u64, u64), Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(UPLOAD_CHUNKS_MEMORY_ID))));
+
+    /// Next upload ID
+    static NEXT_UPLOAD_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NEXT_UPLOAD_ID_MEMORY_ID)), 0u64).unwrap()
+    );
+
+    /// Storage quotas of the agencies which have one
+    static AGENCY_QUOTAS: RefCell<BTreeMap<AgencyId, AgencyQuota, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_QUOTAS_MEMORY_ID))));
+
+    /// Storage used by the documents of each agency
+    static AGENCY_USAGE: RefCell<BTreeMap<AgencyId, StorageUsage, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_USAGE_MEMORY_ID))));
+
+    /// Storage used by the documents of each contract which has been accounted
+    static CONTRACT_USAGE: RefCell<BTreeMap<StorableNat, StorageUsage, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(CONTRACT_USAGE_MEMORY_ID))));
+
+    /// Nonces of the signed messages which have been used (assoc between nonce and expiration)
+    static CONSUMED_NONCES: RefCell<BTreeMap<String, u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(CONSUMED_NONCES_MEMORY_ID))));
+
+    /// Sessions of the restricted documents being streamed (assoc between nonce and session)
+    static STREAMING_SESSIONS: RefCell<BTreeMap<String, StreamingSession, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(STREAMING_SESSIONS_MEMORY_ID))));
+
 }
 
 fn with_contract<T, F>(id: &ID, f: F) -> DeferredDataResult<T>
@@ -137,3 +240,73 @@ where
 {
     REAL_ESTATES.with_borrow_mut(|contracts| f(contracts))
 }
+
+fn with_agency_members<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<StorablePrincipal, AgencyId, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AGENCY_MEMBERS.with_borrow(|members| f(members))
+}
+
+fn with_agency_members_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<StorablePrincipal, AgencyId, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AGENCY_MEMBERS.with_borrow_mut(|members| f(members))
+}
+
+fn with_agencies_status<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<AgencyId, AgencyStatus, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AGENCIES_STATUS.with_borrow(|status| f(status))
+}
+
+fn with_agencies_status_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<AgencyId, AgencyStatus, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AGENCIES_STATUS.with_borrow_mut(|status| f(status))
+}
+
+fn with_agency_transfers_audit<T, F>(f: F) -> T
+where
+    F: FnOnce(&BTreeMap<u64, AgencyTransferAuditEntry, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AGENCY_TRANSFERS_AUDIT.with_borrow(|audit| f(audit))
+}
+
+fn with_agency_transfers_audit_mut<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut BTreeMap<u64, AgencyTransferAuditEntry, VirtualMemory<DefaultMemoryImpl>>) -> T,
+{
+    AGENCY_TRANSFERS_AUDIT.with_borrow_mut(|audit| f(audit))
+}
+
+/// Re-encode up to `count` records following the key `after` with the current schema.
+///
+/// Returns the number of re-encoded records and the key of the last one
+fn reencode_batch<V>(
+    storage: &mut BTreeMap<StorableNat, V, VirtualMemory<DefaultMemoryImpl>>,
+    after: Option<&ID>,
+    count: usize,
+) -> (u64, Option<ID>)
+where
+    V: Storable,
+{
+    let start = match after {
+        Some(key) => Bound::Excluded(StorableNat::from(key.clone())),
+        None => Bound::Unbounded,
+    };
+    let batch = storage
+        .range((start, Bound::Unbounded))
+        .take(count)
+        .collect::<Vec<_>>();
+    let migrated = batch.len() as u64;
+    let last = batch.last().map(|(key, _)| key.0.clone());
+    for (key, record) in batch {
+        storage.insert(key, record);
+    }
+
+    (migrated, last)
+}
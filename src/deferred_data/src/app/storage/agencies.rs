@@ -0,0 +1,308 @@
+use did::deferred::{
+    AgencyId, AgencyReport, AgencyStatus, AgencyTransfer, AgencyTransferAuditEntry,
+    DataContractError, DeferredDataError, DeferredDataResult, RealEstateError,
+};
+use did::StorableNat;
+
+use super::{
+    with_agencies_status, with_agencies_status_mut, with_agency_transfers_audit,
+    with_agency_transfers_audit_mut, with_contracts, with_contracts_mut,
+    with_real_estate_storage_mut, with_real_estates, UsageStorage,
+};
+use crate::utils::time;
+
+/// Agencies storage interface.
+///
+/// Agencies are owned by the minter; this storage only keeps their status and provides the
+/// operations on the records of an agency.
+pub struct AgencyStorage;
+
+impl AgencyStorage {
+    /// Set the status of the agency
+    pub fn set_status(agency: AgencyId, status: AgencyStatus) {
+        with_agencies_status_mut(|agencies| match status {
+            AgencyStatus::Active => {
+                agencies.remove(&agency);
+            }
+            status => {
+                agencies.insert(agency, status);
+            }
+        })
+    }
+
+    /// Get the status of the agency
+    pub fn get_status(agency: AgencyId) -> AgencyStatus {
+        with_agencies_status(|agencies| agencies.get(&agency).unwrap_or_default())
+    }
+
+    /// Returns whether the agency is suspended
+    pub fn is_suspended(agency: AgencyId) -> bool {
+        Self::get_status(agency) == AgencyStatus::Suspended
+    }
+
+    /// Count the suspended agencies
+    pub fn count_suspended() -> u64 {
+        with_agencies_status(|agencies| {
+            agencies
+                .iter()
+                .filter(|(_, status)| *status == AgencyStatus::Suspended)
+                .count() as u64
+        })
+    }
+
+    /// Get the real estates and the open contracts of the agency
+    pub fn report(agency: AgencyId) -> AgencyReport {
+        let real_estates = with_real_estates(|real_estates| {
+            real_estates
+                .iter()
+                .filter(|(_, real_estate)| !real_estate.deleted && real_estate.agency == agency)
+                .map(|(id, _)| id.0)
+                .collect()
+        });
+        let open_contracts = with_contracts(|contracts| {
+            contracts
+                .iter()
+                .filter(|(_, contract)| !contract.closed && contract.agency == agency)
+                .map(|(id, _)| id.0)
+                .collect()
+        });
+
+        AgencyReport {
+            agency,
+            real_estates,
+            open_contracts,
+        }
+    }
+
+    /// Remove the agency, deleting all of its real estates.
+    ///
+    /// Fails if the agency still has open contracts.
+    /// Returns the report of the deleted real estates.
+    pub fn remove(agency: AgencyId) -> DeferredDataResult<AgencyReport> {
+        let report = Self::report(agency);
+        if !report.open_contracts.is_empty() {
+            return Err(DeferredDataError::Contract(
+                DataContractError::AgencyHasOpenContracts(report.open_contracts),
+            ));
+        }
+
+        with_real_estate_storage_mut(|storage| {
+            for id in &report.real_estates {
+                let key = id.clone().into();
+                if let Some(mut real_estate) = storage.get(&key) {
+                    real_estate.deleted = true;
+                    storage.insert(key, real_estate);
+                }
+            }
+        });
+        Self::set_status(agency, AgencyStatus::Active);
+
+        Ok(report)
+    }
+
+    /// Move the real estates and the open contracts of the transfer to the gaining agency,
+    /// writing an audit entry.
+    ///
+    /// All the records must belong to the losing agency, otherwise nothing is moved.
+    /// If the transfer has already been executed, the existing audit entry is returned.
+    pub fn transfer(transfer: &AgencyTransfer) -> DeferredDataResult<AgencyTransferAuditEntry> {
+        if let Some(entry) = with_agency_transfers_audit(|audit| audit.get(&transfer.id)) {
+            return Ok(entry);
+        }
+
+        // check all the records before moving them
+        with_real_estates(|real_estates| {
+            transfer.real_estates.iter().try_for_each(|id| {
+                match real_estates.get(&StorableNat::from(id.clone())) {
+                    Some(real_estate) if real_estate.deleted => Err(DeferredDataError::RealEstate(
+                        RealEstateError::NotFound(id.clone()),
+                    )),
+                    Some(real_estate) if real_estate.agency != transfer.from => {
+                        Err(DeferredDataError::RealEstate(
+                            RealEstateError::NotOwnedByAgency(id.clone()),
+                        ))
+                    }
+                    Some(_) => Ok(()),
+                    None => Err(DeferredDataError::RealEstate(RealEstateError::NotFound(
+                        id.clone(),
+                    ))),
+                }
+            })
+        })?;
+        with_contracts(|contracts| {
+            transfer.contracts.iter().try_for_each(|id| {
+                match contracts.get(&StorableNat::from(id.clone())) {
+                    Some(contract) if contract.closed => Err(DeferredDataError::Contract(
+                        DataContractError::ContractNotFound(id.clone()),
+                    )),
+                    Some(contract) if contract.agency != transfer.from => {
+                        Err(DeferredDataError::Contract(
+                            DataContractError::NotOwnedByAgency(id.clone()),
+                        ))
+                    }
+                    Some(_) => Ok(()),
+                    None => Err(DeferredDataError::Contract(
+                        DataContractError::ContractNotFound(id.clone()),
+                    )),
+                }
+            })
+        })?;
+
+        with_real_estate_storage_mut(|storage| {
+            for id in &transfer.real_estates {
+                let key = StorableNat::from(id.clone());
+                if let Some(mut real_estate) = storage.get(&key) {
+                    real_estate.agency = transfer.to;
+                    storage.insert(key, real_estate);
+                }
+            }
+        });
+        with_contracts_mut(|contracts| {
+            for id in &transfer.contracts {
+                let key = StorableNat::from(id.clone());
+                if let Some(mut contract) = contracts.get(&key) {
+                    UsageStorage::move_contract(&contract, transfer.to);
+                    contract.agency = transfer.to;
+                    contracts.insert(key, contract);
+                }
+            }
+        });
+
+        let entry = AgencyTransferAuditEntry {
+            contracts: transfer.contracts.clone(),
+            from: transfer.from,
+            real_estates: transfer.real_estates.clone(),
+            timestamp: time(),
+            to: transfer.to,
+            transfer_id: transfer.id,
+        };
+        with_agency_transfers_audit_mut(|audit| audit.insert(transfer.id, entry.clone()));
+
+        Ok(entry)
+    }
+
+    /// Get the audit entries of the transfers involving the agency
+    pub fn get_transfers_audit(agency: AgencyId) -> Vec<AgencyTransferAuditEntry> {
+        with_agency_transfers_audit(|audit| {
+            audit
+                .iter()
+                .map(|(_, entry)| entry)
+                .filter(|entry| entry.from == agency || entry.to == agency)
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Nat;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::storage::{ContractStorage, RealEstateStorage};
+    use crate::app::test_utils::{alice, bob, mock_real_estate, store_mock_contract_with};
+
+    #[test]
+    fn test_should_set_agency_status() {
+        assert_eq!(AgencyStorage::get_status(AgencyId(1)), AgencyStatus::Active);
+
+        AgencyStorage::set_status(AgencyId(1), AgencyStatus::Suspended);
+        assert!(AgencyStorage::is_suspended(AgencyId(1)));
+        assert!(!AgencyStorage::is_suspended(AgencyId(2)));
+
+        AgencyStorage::set_status(AgencyId(1), AgencyStatus::Active);
+        assert!(!AgencyStorage::is_suspended(AgencyId(1)));
+    }
+
+    #[test]
+    fn test_should_report_agency_records() {
+        let mut real_estate = mock_real_estate();
+        real_estate.agency = AgencyId(1);
+        let real_estate_id = RealEstateStorage::insert(real_estate).unwrap();
+        store_mock_contract_with(1, 100, |contract| contract.agency = AgencyId(1));
+        store_mock_contract_with(2, 100, |contract| contract.agency = AgencyId(2));
+
+        let report = AgencyStorage::report(AgencyId(1));
+        assert_eq!(report.agency, AgencyId(1));
+        assert_eq!(report.real_estates, vec![real_estate_id]);
+        assert_eq!(report.open_contracts, vec![Nat::from(1u64)]);
+    }
+
+    #[test]
+    fn test_should_transfer_agency_records() {
+        let mut real_estate = mock_real_estate();
+        real_estate.agency = AgencyId(1);
+        let real_estate_id = RealEstateStorage::insert(real_estate).unwrap();
+        store_mock_contract_with(1, 100, |contract| contract.agency = AgencyId(1));
+        store_mock_contract_with(2, 100, |contract| contract.agency = AgencyId(3));
+
+        let mut transfer = AgencyTransfer {
+            approved_at: None,
+            approved_by: None,
+            contracts: vec![Nat::from(1u64), Nat::from(2u64)],
+            from: AgencyId(1),
+            from_accepted_by: Some(alice()),
+            id: 1,
+            real_estates: vec![real_estate_id.clone()],
+            requested_at: 0,
+            requested_by: alice(),
+            status: did::deferred::AgencyTransferStatus::Pending,
+            to: AgencyId(2),
+            to_accepted_by: Some(bob()),
+        };
+
+        // contract 2 doesn't belong to alice; nothing is moved
+        assert_eq!(
+            AgencyStorage::transfer(&transfer),
+            Err(DeferredDataError::Contract(
+                DataContractError::NotOwnedByAgency(Nat::from(2u64))
+            ))
+        );
+        assert_eq!(
+            RealEstateStorage::get(&real_estate_id).unwrap().agency,
+            AgencyId(1)
+        );
+
+        transfer.contracts = vec![Nat::from(1u64)];
+        let entry = AgencyStorage::transfer(&transfer).unwrap();
+        assert_eq!(entry.transfer_id, 1);
+        assert_eq!(
+            RealEstateStorage::get(&real_estate_id).unwrap().agency,
+            AgencyId(2)
+        );
+        assert_eq!(
+            ContractStorage::get_contract(&Nat::from(1u64))
+                .unwrap()
+                .agency,
+            AgencyId(2)
+        );
+
+        assert_eq!(
+            AgencyStorage::get_transfers_audit(AgencyId(1)),
+            vec![entry.clone()]
+        );
+        assert_eq!(AgencyStorage::get_transfers_audit(AgencyId(2)), vec![entry]);
+        assert!(AgencyStorage::get_transfers_audit(AgencyId(3)).is_empty());
+    }
+
+    #[test]
+    fn test_should_remove_agency_without_open_contracts() {
+        let mut real_estate = mock_real_estate();
+        real_estate.agency = AgencyId(1);
+        let real_estate_id = RealEstateStorage::insert(real_estate).unwrap();
+        store_mock_contract_with(1, 100, |contract| contract.agency = AgencyId(1));
+
+        assert_eq!(
+            AgencyStorage::remove(AgencyId(1)),
+            Err(DeferredDataError::Contract(
+                DataContractError::AgencyHasOpenContracts(vec![Nat::from(1u64)])
+            ))
+        );
+
+        ContractStorage::close_contract(&1u64.into()).unwrap();
+        let report = AgencyStorage::remove(AgencyId(1)).unwrap();
+        assert_eq!(report.real_estates, vec![real_estate_id.clone()]);
+        assert!(RealEstateStorage::get(&real_estate_id).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use candid::Principal;
+use did::deferred::AgencyId;
+use did::StorablePrincipal;
+
+use super::{with_agency_members, with_agency_members_mut};
+
+/// Agency members storage interface.
+///
+/// Memberships are pushed by the deferred minter, which is the source of truth for the members of
+/// the agencies, including their owners.
+pub struct AgencyMemberStorage;
+
+impl AgencyMemberStorage {
+    /// Set the agency of `member`; `None` removes the membership
+    pub fn set(member: Principal, agency: Option<AgencyId>) {
+        with_agency_members_mut(|members| match agency {
+            Some(agency) => {
+                members.insert(member.into(), agency);
+            }
+            None => {
+                members.remove(&StorablePrincipal::from(member));
+            }
+        })
+    }
+
+    /// Count the members of all the agencies
+    pub fn count() -> u64 {
+        with_agency_members(|members| members.len())
+    }
+
+    /// Returns whether `principal` belongs to `agency`
+    pub fn is_member(principal: Principal, agency: AgencyId) -> bool {
+        with_agency_members(|members| {
+            members
+                .get(&StorablePrincipal::from(principal))
+                .is_some_and(|member_agency| member_agency == agency)
+        })
+    }
+
+    /// Returns whether `agency` has any member
+    pub fn has_members(agency: AgencyId) -> bool {
+        with_agency_members(|members| {
+            members
+                .iter()
+                .any(|(_, member_agency)| member_agency == agency)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::app::test_utils::bob;
+
+    #[test]
+    fn test_should_set_agency_member() {
+        let agency = AgencyId(1);
+        assert!(!AgencyMemberStorage::is_member(bob(), agency));
+        assert!(!AgencyMemberStorage::has_members(agency));
+
+        AgencyMemberStorage::set(bob(), Some(agency));
+        assert!(AgencyMemberStorage::is_member(bob(), agency));
+        assert!(!AgencyMemberStorage::is_member(bob(), AgencyId(2)));
+        assert!(AgencyMemberStorage::has_members(agency));
+        assert!(!AgencyMemberStorage::has_members(AgencyId(2)));
+
+        AgencyMemberStorage::set(bob(), None);
+        assert!(!AgencyMemberStorage::is_member(bob(), agency));
+        assert!(!AgencyMemberStorage::has_members(agency));
+    }
+}
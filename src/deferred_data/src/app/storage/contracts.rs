@@ -1,12 +1,17 @@
+use candid::Principal;
 use did::deferred::{
     Contract, ContractDocument, ContractDocumentData, DataContractError, DeferredDataError,
-    DeferredDataResult, GenericValue, RestrictedProperty,
+    DeferredDataResult, DocumentHistory, DocumentImage, DocumentLeaf, GenericValue, ImageSize,
+    RestrictedProperty, StorageUsage,
 };
 use did::ID;
 
 use super::{
-    with_contract, with_contract_mut, with_contracts, with_contracts_mut, DocumentStorage,
+    reencode_batch, with_contract, with_contract_mut, with_contracts, with_contracts_mut,
+    DocumentHistoryStorage, DocumentPreviewStorage, DocumentStorage, UsageStorage,
 };
+use crate::app::images::Images;
+use crate::utils::time;
 
 pub struct ContractStorage;
 
@@ -49,6 +54,28 @@ impl ContractStorage {
         })
     }
 
+    /// Count the contracts, either the closed or the open ones
+    pub fn count(closed: bool) -> u64 {
+        with_contracts(|contracts| {
+            contracts
+                .iter()
+                .filter(|(_, contract)| contract.closed == closed)
+                .count() as u64
+        })
+    }
+
+    /// Count all the stored contracts, without decoding them
+    pub fn len() -> u64 {
+        with_contracts(|contracts| contracts.len())
+    }
+
+    /// Re-encode up to `count` contracts following `after` with the current schema.
+    ///
+    /// Returns the number of re-encoded contracts and the id of the last one
+    pub fn migrate(after: Option<&ID>, count: usize) -> (u64, Option<ID>) {
+        with_contracts_mut(|contracts| reencode_batch(contracts, after, count))
+    }
+
     /// get contracts by filter
     pub fn get_contracts_filter(filter: impl Fn(&Contract) -> bool) -> Vec<ID> {
         with_contracts(|contracts| {
@@ -109,13 +136,12 @@ impl ContractStorage {
         contract_id: &ID,
         document: ContractDocument,
         data: Vec<u8>,
+        uploaded_by: Principal,
     ) -> DeferredDataResult<u64> {
         // check if contract exists
-        if Self::get_contract(contract_id).is_none() {
-            return Err(DeferredDataError::Contract(
-                DataContractError::ContractNotFound(contract_id.clone()),
-            ));
-        }
+        let contract = Self::get_contract(contract_id).ok_or_else(|| {
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone()))
+        })?;
 
         // check if document size matches data size
         if document.size != data.len() as u64 {
@@ -123,20 +149,281 @@ impl ContractStorage {
                 DataContractError::DocumentSizeMismatch(document.size, data.len() as u64),
             ));
         }
+        let usage = StorageUsage::document(document.size);
+        UsageStorage::require_within_quota(&contract, usage)?;
 
         // insert document into document storage
-        let document_id = DocumentStorage::upload_document(data)?;
+        let document_id = Self::store_document_data(&document.mime_type, data)?;
+        UsageStorage::add(&contract, usage);
 
         // update contract with document id
         with_contract_mut(contract_id, |contract| {
-            contract.documents.push((document_id, document));
+            contract.documents.push((document_id, document.clone()));
 
             Ok(())
         })?;
 
+        // the first version of the document is stored under the document id
+        let mut history = DocumentHistory {
+            contract_id: contract_id.clone(),
+            versions: vec![],
+            deleted_at: None,
+        };
+        history.push(document_id, document, time(), uploaded_by);
+        DocumentHistoryStorage::insert(document_id, history);
+
         Ok(document_id)
     }
 
+    /// Update the metadata of a contract document, keeping its data.
+    ///
+    /// The size of the document can't change. Returns the number of the new version.
+    pub fn update_contract_document(
+        contract_id: &ID,
+        document_id: u64,
+        document: ContractDocument,
+        updated_by: Principal,
+    ) -> DeferredDataResult<u64> {
+        let mut history = Self::get_contract_document_history(contract_id, document_id)?;
+        if history.deleted_at.is_some() {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id),
+            ));
+        }
+        let Some(current) = history.current().cloned() else {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id),
+            ));
+        };
+
+        // check if document size matches the size of the current data
+        if document.size != current.document.size {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(document.size, current.document.size),
+            ));
+        }
+
+        let version = history.push(current.data_id, document.clone(), time(), updated_by);
+        Self::set_contract_document(contract_id, document_id, document)?;
+        DocumentHistoryStorage::insert(document_id, history);
+
+        Ok(version)
+    }
+
+    /// Replace the data of a contract document, keeping the previous versions.
+    ///
+    /// Returns the number of the new version.
+    pub fn replace_contract_document(
+        contract_id: &ID,
+        document_id: u64,
+        document: ContractDocument,
+        data: Vec<u8>,
+        replaced_by: Principal,
+    ) -> DeferredDataResult<u64> {
+        let mut history = Self::get_contract_document_history(contract_id, document_id)?;
+        if history.deleted_at.is_some() {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id),
+            ));
+        }
+
+        // check if document size matches data size
+        if document.size != data.len() as u64 {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(document.size, data.len() as u64),
+            ));
+        }
+        let contract = Self::get_contract(contract_id).ok_or_else(|| {
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone()))
+        })?;
+        let usage = StorageUsage::document(document.size);
+        UsageStorage::require_within_quota(&contract, usage)?;
+
+        let data_id = Self::store_document_data(&document.mime_type, data)?;
+        UsageStorage::add(&contract, usage);
+        let version = history.push(data_id, document.clone(), time(), replaced_by);
+        Self::set_contract_document(contract_id, document_id, document)?;
+        DocumentHistoryStorage::insert(document_id, history);
+
+        Ok(version)
+    }
+
+    /// Delete a contract document, which is no longer listed in the contract.
+    ///
+    /// The history of the document is kept; if `purge` is set, the data of all of its versions is
+    /// removed from the documents storage too. A deleted document can still be purged.
+    pub fn delete_contract_document(
+        contract_id: &ID,
+        document_id: u64,
+        purge: bool,
+    ) -> DeferredDataResult<()> {
+        let mut history = Self::get_contract_document_history(contract_id, document_id)?;
+
+        let contract = with_contract_mut(contract_id, |contract| {
+            let previous = contract.clone();
+            contract.documents.retain(|(id, _)| *id != document_id);
+
+            Ok(previous)
+        })?;
+
+        if history.deleted_at.is_none() {
+            history.deleted_at = Some(time());
+        }
+        if purge {
+            UsageStorage::remove(&contract, UsageStorage::history_usage(&history));
+            for version in history.versions.iter_mut() {
+                DocumentStorage::remove_document(version.data_id);
+                DocumentPreviewStorage::remove(version.data_id);
+                version.purged = true;
+            }
+        }
+        DocumentHistoryStorage::insert(document_id, history);
+
+        Ok(())
+    }
+
+    /// Get the version history of a contract document, including the deleted ones
+    pub fn get_contract_document_history(
+        contract_id: &ID,
+        document_id: u64,
+    ) -> DeferredDataResult<DocumentHistory> {
+        // check if contract exists
+        let contract = Self::get_contract(contract_id).ok_or_else(|| {
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone()))
+        })?;
+
+        match DocumentHistoryStorage::get(document_id) {
+            Some(history) if &history.contract_id == contract_id => Ok(history),
+            Some(_) => Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id),
+            )),
+            None => {
+                // documents uploaded before the history was introduced have a single version
+                let (_, document) = contract
+                    .documents
+                    .into_iter()
+                    .find(|(id, _)| *id == document_id)
+                    .ok_or(DeferredDataError::Contract(
+                        DataContractError::DocumentNotFound(document_id),
+                    ))?;
+                let mut history = DocumentHistory {
+                    contract_id: contract_id.clone(),
+                    versions: vec![],
+                    deleted_at: None,
+                };
+                history.push(document_id, document, 0, Principal::anonymous());
+
+                Ok(history)
+            }
+        }
+    }
+
+    /// Get a version of a contract document
+    pub fn get_contract_document_version(
+        contract_id: &ID,
+        document_id: u64,
+        version: u64,
+    ) -> DeferredDataResult<ContractDocumentData> {
+        let history = Self::get_contract_document_history(contract_id, document_id)?;
+        let version = history.version(version).ok_or(DeferredDataError::Contract(
+            DataContractError::DocumentVersionNotFound(document_id, version),
+        ))?;
+        if version.purged {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentPurged(document_id, version.version),
+            ));
+        }
+
+        Ok(ContractDocumentData {
+            data: DocumentStorage::get_document(version.data_id)?,
+            mime_type: version.document.mime_type.clone(),
+            name: version.document.name.clone(),
+        })
+    }
+
+    /// Get the leaves of the Merkle tree of the documents listed in the contract, with the
+    /// SHA-256 of their current version
+    pub fn get_contract_document_leaves(contract_id: &ID) -> DeferredDataResult<Vec<DocumentLeaf>> {
+        let contract = Self::get_contract(contract_id).ok_or_else(|| {
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone()))
+        })?;
+
+        contract
+            .documents
+            .iter()
+            .map(|(document_id, _)| {
+                let data_id = DocumentHistoryStorage::current_data_id(*document_id);
+                Ok(DocumentLeaf {
+                    document_id: *document_id,
+                    sha256: DocumentStorage::get_document_hash(data_id)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the thumbnail or the preview of the current version of a contract document
+    pub fn get_contract_document_preview(
+        contract_id: &ID,
+        document_id: u64,
+        size: ImageSize,
+    ) -> DeferredDataResult<DocumentImage> {
+        let contract = Self::get_contract(contract_id).ok_or_else(|| {
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone()))
+        })?;
+        // check if `document_id` belongs to `contract_id`
+        if !contract.documents.iter().any(|(id, _)| *id == document_id) {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id),
+            ));
+        }
+
+        DocumentPreviewStorage::get(DocumentHistoryStorage::current_data_id(document_id))
+            .map(|previews| previews.into_image(size))
+            .ok_or(DeferredDataError::Contract(
+                DataContractError::DocumentPreviewNotFound(document_id),
+            ))
+    }
+
+    /// Store the data of a document, with the previews of the images.
+    ///
+    /// The GPS position is removed from the images before they are stored, so that the data
+    /// served, hashed and anchored are the same bytes.
+    /// Returns the ID of the data
+    fn store_document_data(mime_type: &str, mut data: Vec<u8>) -> DeferredDataResult<u64> {
+        if Images::strip_gps(mime_type, &mut data) {
+            log::debug!("Removed GPS position from {mime_type} document");
+        }
+        let previews = Images::previews(mime_type, &data);
+        let data_id = DocumentStorage::upload_document(data)?;
+        if let Some(previews) = previews {
+            DocumentPreviewStorage::insert(data_id, previews);
+        }
+
+        Ok(data_id)
+    }
+
+    /// Set the metadata of a document listed in the contract
+    fn set_contract_document(
+        contract_id: &ID,
+        document_id: u64,
+        document: ContractDocument,
+    ) -> DeferredDataResult<()> {
+        with_contract_mut(contract_id, |contract| {
+            let Some((_, properties)) = contract
+                .documents
+                .iter_mut()
+                .find(|(id, _)| *id == document_id)
+            else {
+                return Err(DeferredDataError::Contract(
+                    DataContractError::DocumentNotFound(document_id),
+                ));
+            };
+            *properties = document;
+
+            Ok(())
+        })
+    }
+
     /// Get contract document
     pub fn get_contract_document(
         contract_id: &ID,
@@ -159,8 +446,9 @@ impl ContractStorage {
             ));
         };
 
-        // get document from the storage
-        let document_data = DocumentStorage::get_document(document_id)?;
+        // get the current version of the document from the storage
+        let document_data =
+            DocumentStorage::get_document(DocumentHistoryStorage::current_data_id(document_id))?;
 
         Ok(ContractDocumentData {
             data: document_data,
@@ -168,6 +456,30 @@ impl ContractStorage {
             name: document_properties.name.clone(),
         })
     }
+
+    /// Get the bytes in the range `[start, end)` of the current version of a contract document,
+    /// reading only the chunks of its data which contain them
+    pub fn get_contract_document_range(
+        contract_id: &ID,
+        document_id: u64,
+        start: u64,
+        end: u64,
+    ) -> DeferredDataResult<Vec<u8>> {
+        let contract = Self::get_contract(contract_id).ok_or_else(|| {
+            DeferredDataError::Contract(DataContractError::ContractNotFound(contract_id.clone()))
+        })?;
+        if !contract.documents.iter().any(|(id, _)| *id == document_id) {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id),
+            ));
+        }
+
+        DocumentStorage::get_document_range(
+            DocumentHistoryStorage::current_data_id(document_id),
+            start,
+            end,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -177,9 +489,10 @@ mod test {
     use did::deferred::{RestrictionLevel, Seller};
     use did::H160;
     use pretty_assertions::assert_eq;
+    use sha2::{Digest as _, Sha256};
 
     use super::*;
-    use crate::app::test_utils::with_mock_contract;
+    use crate::app::test_utils::{alice, bob, mock_jpeg, with_mock_contract};
 
     #[test]
     fn test_should_insert_and_get_contract() {
@@ -385,9 +698,13 @@ mod test {
             size: 4,
         };
 
-        let document_id =
-            ContractStorage::upload_contract_document(&1_u64.into(), document, vec![1, 2, 3, 4])
-                .expect("Failed to upload document");
+        let document_id = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            document,
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .expect("Failed to upload document");
 
         // get contract document
         let contract_document = ContractStorage::get_contract_document(&1_u64.into(), document_id)
@@ -397,6 +714,60 @@ mod test {
         assert_eq!(contract_document.data, vec![1, 2, 3, 4]);
     }
 
+    #[test]
+    fn test_should_store_previews_of_image_documents() {
+        ContractStorage::insert_contract(with_mock_contract(1, 1, |_| {}));
+        let image = mock_jpeg(2048, 1024);
+        let document = ContractDocument {
+            mime_type: "image/jpeg".to_string(),
+            access_list: vec![RestrictionLevel::Public],
+            name: "house.jpg".to_string(),
+            size: image.len() as u64,
+        };
+        let document_id =
+            ContractStorage::upload_contract_document(&1_u64.into(), document, image, alice())
+                .unwrap();
+
+        let thumbnail = ContractStorage::get_contract_document_preview(
+            &1_u64.into(),
+            document_id,
+            ImageSize::Thumbnail,
+        )
+        .unwrap();
+        assert_eq!(thumbnail.mime_type, "image/jpeg");
+        assert_eq!((thumbnail.width, thumbnail.height), (256, 128));
+        let preview = ContractStorage::get_contract_document_preview(
+            &1_u64.into(),
+            document_id,
+            ImageSize::Preview,
+        )
+        .unwrap();
+        assert_eq!((preview.width, preview.height), (1024, 512));
+
+        // documents which are not images have no previews
+        let pdf_id = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+        assert_eq!(
+            ContractStorage::get_contract_document_preview(
+                &1_u64.into(),
+                pdf_id,
+                ImageSize::Thumbnail
+            ),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentPreviewNotFound(pdf_id)
+            ))
+        );
+
+        // previews are removed with the document data
+        ContractStorage::delete_contract_document(&1_u64.into(), document_id, true).unwrap();
+        assert!(DocumentPreviewStorage::get(document_id).is_none());
+    }
+
     #[test]
     fn test_should_not_upload_contract_document_if_size_is_wrong() {
         let contract = with_mock_contract(1, 1, |_| {});
@@ -409,12 +780,272 @@ mod test {
             size: 100,
         };
 
-        let result =
-            ContractStorage::upload_contract_document(&1_u64.into(), document, vec![1, 2, 3, 4]);
+        let result = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            document,
+            vec![1, 2, 3, 4],
+            alice(),
+        );
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
             DeferredDataError::Contract(DataContractError::DocumentSizeMismatch(100, 4))
         );
     }
+
+    fn mock_document(size: u64) -> ContractDocument {
+        ContractDocument {
+            mime_type: "application/pdf".to_string(),
+            access_list: vec![RestrictionLevel::Seller],
+            name: "contract.pdf".to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_should_update_contract_document() {
+        ContractStorage::insert_contract(with_mock_contract(1, 1, |_| {}));
+        let document_id = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+
+        let mut document = mock_document(4);
+        document.access_list = vec![RestrictionLevel::Seller, RestrictionLevel::Buyer];
+        assert_eq!(
+            ContractStorage::update_contract_document(
+                &1_u64.into(),
+                document_id,
+                document.clone(),
+                bob()
+            ),
+            Ok(2)
+        );
+        assert_eq!(
+            ContractStorage::update_contract_document(
+                &1_u64.into(),
+                document_id,
+                mock_document(5),
+                bob()
+            ),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(5, 4)
+            ))
+        );
+
+        let contract = ContractStorage::get_contract(&1_u64.into()).unwrap();
+        assert_eq!(contract.documents, vec![(document_id, document)]);
+
+        let history =
+            ContractStorage::get_contract_document_history(&1_u64.into(), document_id).unwrap();
+        assert_eq!(history.versions.len(), 2);
+        assert_eq!(history.versions[0].created_by, alice());
+        assert_eq!(history.versions[1].created_by, bob());
+        assert_eq!(history.versions[1].data_id, document_id);
+    }
+
+    #[test]
+    fn test_should_replace_contract_document() {
+        ContractStorage::insert_contract(with_mock_contract(1, 1, |_| {}));
+        let document_id = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+
+        let mut document = mock_document(2);
+        document.name = "contract-v2.pdf".to_string();
+        assert_eq!(
+            ContractStorage::replace_contract_document(
+                &1_u64.into(),
+                document_id,
+                document.clone(),
+                vec![5, 6],
+                alice()
+            ),
+            Ok(2)
+        );
+
+        let current = ContractStorage::get_contract_document(&1_u64.into(), document_id).unwrap();
+        assert_eq!(current.data, vec![5, 6]);
+        assert_eq!(current.name, "contract-v2.pdf");
+
+        let previous =
+            ContractStorage::get_contract_document_version(&1_u64.into(), document_id, 1).unwrap();
+        assert_eq!(previous.data, vec![1, 2, 3, 4]);
+        assert_eq!(previous.name, "contract.pdf");
+        assert_eq!(
+            ContractStorage::get_contract_document_version(&1_u64.into(), document_id, 3),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentVersionNotFound(document_id, 3)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_delete_and_purge_contract_document() {
+        ContractStorage::insert_contract(with_mock_contract(1, 1, |_| {}));
+        let document_id = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+        ContractStorage::replace_contract_document(
+            &1_u64.into(),
+            document_id,
+            mock_document(2),
+            vec![5, 6],
+            alice(),
+        )
+        .unwrap();
+
+        // soft delete
+        assert!(
+            ContractStorage::delete_contract_document(&1_u64.into(), document_id, false).is_ok()
+        );
+        assert!(ContractStorage::get_contract(&1_u64.into())
+            .unwrap()
+            .documents
+            .is_empty());
+        assert_eq!(
+            ContractStorage::get_contract_document(&1_u64.into(), document_id),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id)
+            ))
+        );
+        assert_eq!(
+            ContractStorage::update_contract_document(
+                &1_u64.into(),
+                document_id,
+                mock_document(2),
+                alice()
+            ),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(document_id)
+            ))
+        );
+        let history =
+            ContractStorage::get_contract_document_history(&1_u64.into(), document_id).unwrap();
+        assert!(history.deleted_at.is_some());
+        assert!(history.versions.iter().all(|version| !version.purged));
+        assert_eq!(DocumentStorage::count(), 2);
+
+        // purge
+        assert!(
+            ContractStorage::delete_contract_document(&1_u64.into(), document_id, true).is_ok()
+        );
+        let purged =
+            ContractStorage::get_contract_document_history(&1_u64.into(), document_id).unwrap();
+        assert_eq!(purged.deleted_at, history.deleted_at);
+        assert!(purged.versions.iter().all(|version| version.purged));
+        assert_eq!(DocumentStorage::count(), 0);
+        assert_eq!(
+            ContractStorage::get_contract_document_version(&1_u64.into(), document_id, 1),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentPurged(document_id, 1)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_share_data_of_same_document_between_contracts() {
+        ContractStorage::insert_contract(with_mock_contract(1, 1, |_| {}));
+        ContractStorage::insert_contract(with_mock_contract(2, 1, |_| {}));
+        let first = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+        let second = ContractStorage::upload_contract_document(
+            &2_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(DocumentStorage::count(), 1);
+
+        assert!(ContractStorage::delete_contract_document(&1_u64.into(), first, true).is_ok());
+        assert_eq!(DocumentStorage::count(), 1);
+        assert_eq!(
+            ContractStorage::get_contract_document(&2_u64.into(), second)
+                .unwrap()
+                .data,
+            vec![1, 2, 3, 4]
+        );
+
+        assert!(ContractStorage::delete_contract_document(&2_u64.into(), second, true).is_ok());
+        assert_eq!(DocumentStorage::count(), 0);
+    }
+
+    #[test]
+    fn test_should_get_history_of_document_without_history() {
+        let contract = with_mock_contract(1, 1, |contract| {
+            contract.documents = vec![(7, mock_document(4))];
+        });
+        ContractStorage::insert_contract(contract);
+
+        let history = ContractStorage::get_contract_document_history(&1_u64.into(), 7).unwrap();
+        assert_eq!(history.versions.len(), 1);
+        assert_eq!(history.versions[0].data_id, 7);
+        assert_eq!(history.versions[0].created_by, Principal::anonymous());
+        assert_eq!(
+            ContractStorage::get_contract_document_history(&1_u64.into(), 8),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(8)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_get_contract_document_leaves() {
+        ContractStorage::insert_contract(with_mock_contract(1, 1, |_| {}));
+        let first = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(4),
+            vec![1, 2, 3, 4],
+            alice(),
+        )
+        .unwrap();
+        let second = ContractStorage::upload_contract_document(
+            &1_u64.into(),
+            mock_document(2),
+            vec![5, 6],
+            alice(),
+        )
+        .unwrap();
+        ContractStorage::replace_contract_document(
+            &1_u64.into(),
+            first,
+            mock_document(1),
+            vec![7],
+            alice(),
+        )
+        .unwrap();
+
+        let leaves = ContractStorage::get_contract_document_leaves(&1_u64.into()).unwrap();
+        assert_eq!(
+            leaves,
+            vec![
+                DocumentLeaf {
+                    document_id: first,
+                    sha256: hex::encode(Sha256::digest([7])),
+                },
+                DocumentLeaf {
+                    document_id: second,
+                    sha256: hex::encode(Sha256::digest([5, 6])),
+                },
+            ]
+        );
+    }
 }
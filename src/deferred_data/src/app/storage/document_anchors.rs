@@ -0,0 +1,45 @@
+use did::deferred::DocumentsAnchor;
+use did::{StorableNat, ID};
+
+use super::DOCUMENT_ANCHORS;
+
+/// Storage of the Merkle roots of the contract documents anchored on Ethereum
+pub struct DocumentAnchorStorage;
+
+impl DocumentAnchorStorage {
+    /// Get the last anchor of the documents of a contract
+    pub fn get(contract_id: &ID) -> Option<DocumentsAnchor> {
+        DOCUMENT_ANCHORS.with_borrow(|anchors| anchors.get(&StorableNat::from(contract_id.clone())))
+    }
+
+    /// Insert the anchor of the documents of a contract, replacing the previous one
+    pub fn insert(anchor: DocumentsAnchor) {
+        DOCUMENT_ANCHORS.with_borrow_mut(|anchors| {
+            anchors.insert(StorableNat::from(anchor.contract_id.clone()), anchor);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_insert_and_get_anchor() {
+        let anchor = DocumentsAnchor {
+            contract_id: 1u64.into(),
+            root: format!("0x{}", "00".repeat(32)),
+            leaves: vec![],
+            transaction_hash: format!("0x{}", "11".repeat(32)),
+            chain_id: 1,
+            anchored_at: 1_000,
+        };
+        DocumentAnchorStorage::insert(anchor.clone());
+
+        assert_eq!(DocumentAnchorStorage::get(&1u64.into()), Some(anchor));
+        assert_eq!(DocumentAnchorStorage::get(&2u64.into()), None);
+    }
+}
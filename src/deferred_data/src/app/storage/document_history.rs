@@ -0,0 +1,63 @@
+use did::deferred::DocumentHistory;
+
+use super::DOCUMENT_HISTORY;
+
+/// Storage of the version history of the contract documents
+pub struct DocumentHistoryStorage;
+
+impl DocumentHistoryStorage {
+    /// Get the history of a document
+    pub fn get(document_id: u64) -> Option<DocumentHistory> {
+        DOCUMENT_HISTORY.with_borrow(|history| history.get(&document_id))
+    }
+
+    /// Insert or replace the history of a document
+    pub fn insert(document_id: u64, history: DocumentHistory) {
+        DOCUMENT_HISTORY.with_borrow_mut(|storage| {
+            storage.insert(document_id, history);
+        });
+    }
+
+    /// Get the ID of the data of the current version of a document.
+    ///
+    /// Documents uploaded before the history was introduced have a single version, stored under
+    /// the ID of the document.
+    pub fn current_data_id(document_id: u64) -> u64 {
+        Self::get(document_id)
+            .and_then(|history| history.current().map(|version| version.data_id))
+            .unwrap_or(document_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use did::deferred::ContractDocument;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_get_current_data_id() {
+        assert_eq!(DocumentHistoryStorage::current_data_id(1), 1);
+
+        let mut history = DocumentHistory {
+            contract_id: 1u64.into(),
+            versions: vec![],
+            deleted_at: None,
+        };
+        let document = ContractDocument {
+            access_list: vec![],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size: 4,
+        };
+        history.push(1, document.clone(), 0, Principal::anonymous());
+        history.push(5, document, 0, Principal::anonymous());
+        DocumentHistoryStorage::insert(1, history.clone());
+
+        assert_eq!(DocumentHistoryStorage::get(1), Some(history));
+        assert_eq!(DocumentHistoryStorage::current_data_id(1), 5);
+    }
+}
@@ -0,0 +1,57 @@
+use did::deferred::DocumentPreviews;
+
+use super::DOCUMENT_PREVIEWS;
+
+/// Storage of the thumbnails and previews of the image documents
+pub struct DocumentPreviewStorage;
+
+impl DocumentPreviewStorage {
+    /// Get the previews of the document data with the provided ID
+    pub fn get(data_id: u64) -> Option<DocumentPreviews> {
+        DOCUMENT_PREVIEWS.with_borrow(|previews| previews.get(&data_id))
+    }
+
+    /// Insert the previews of the document data with the provided ID
+    pub fn insert(data_id: u64, previews: DocumentPreviews) {
+        DOCUMENT_PREVIEWS.with_borrow_mut(|storage| {
+            storage.insert(data_id, previews);
+        });
+    }
+
+    /// Remove the previews of the document data with the provided ID, if any
+    pub fn remove(data_id: u64) {
+        DOCUMENT_PREVIEWS.with_borrow_mut(|storage| {
+            storage.remove(&data_id);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::DocumentImage;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_insert_get_and_remove_previews() {
+        let image = DocumentImage {
+            mime_type: "image/jpeg".to_string(),
+            width: 2,
+            height: 1,
+            data: vec![1, 2, 3],
+        };
+        let previews = DocumentPreviews {
+            thumbnail: image.clone(),
+            preview: image,
+        };
+        DocumentPreviewStorage::insert(1, previews.clone());
+
+        assert_eq!(DocumentPreviewStorage::get(1), Some(previews));
+        assert_eq!(DocumentPreviewStorage::get(2), None);
+
+        DocumentPreviewStorage::remove(1);
+        assert_eq!(DocumentPreviewStorage::get(1), None);
+    }
+}
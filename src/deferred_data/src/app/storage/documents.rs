@@ -1,32 +1,279 @@
+use std::ops::Bound;
+
 use did::deferred::{DataContractError, DeferredDataError, DeferredDataResult};
+use did::ID;
+use num_traits::cast::ToPrimitive;
+use sha2::{Digest as _, Sha256};
+
+use super::{
+    DOCUMENTS, DOCUMENT_CONTENTS, DOCUMENT_CONTENT_CHUNKS, DOCUMENT_HASHES, DOCUMENT_REFERENCES,
+    NEXT_DOCUMENT_ID,
+};
 
-use super::{DOCUMENTS, NEXT_DOCUMENT_ID};
+/// Size of the chunks the documents data is stored in
+pub const CONTENT_CHUNK_SIZE: u64 = 1024 * 1024;
 
+/// Storage of the documents data.
+///
+/// Each document ID references the SHA-256 of its data, which is stored once for all the
+/// documents with the same data, along with the amount of documents referencing it.
+/// The data is stored in chunks of [`CONTENT_CHUNK_SIZE`] bytes, so that a range of it can be read
+/// without loading the whole document
 pub struct DocumentStorage;
 
 impl DocumentStorage {
     /// Get a document from the storage
     pub fn get_document(id: u64) -> DeferredDataResult<Vec<u8>> {
-        DOCUMENTS.with_borrow(|documents| {
-            documents.get(&id).ok_or(DeferredDataError::Contract(
+        // documents which haven't been migrated yet are still stored by ID
+        if let Some(data) = DOCUMENTS.with_borrow(|documents| documents.get(&id)) {
+            return Ok(data);
+        }
+
+        DOCUMENT_HASHES
+            .with_borrow(|hashes| hashes.get(&id))
+            .and_then(|hash| Self::read_content(&hash))
+            .ok_or(DeferredDataError::Contract(
                 DataContractError::DocumentNotFound(id),
             ))
-        })
     }
 
-    /// Upload a document into the storage.
+    /// Get the bytes of a document in the range `[start, end)`, reading only the chunks which
+    /// contain it.
+    ///
+    /// The range must be within the size of the document
+    pub fn get_document_range(id: u64, start: u64, end: u64) -> DeferredDataResult<Vec<u8>> {
+        // documents which haven't been migrated yet are stored whole
+        if let Some(data) = DOCUMENTS.with_borrow(|documents| documents.get(&id)) {
+            return Ok(slice(&data, start, end));
+        }
+
+        DOCUMENT_HASHES
+            .with_borrow(|hashes| hashes.get(&id))
+            .and_then(|hash| Self::read_content_range(&hash, start, end))
+            .ok_or(DeferredDataError::Contract(
+                DataContractError::DocumentNotFound(id),
+            ))
+    }
+
+    /// Get the SHA-256 of a document (hex).
+    ///
+    /// The hash of the documents stored before the hashes were introduced is computed from their
+    /// data.
+    pub fn get_document_hash(id: u64) -> DeferredDataResult<String> {
+        if let Some(hash) = DOCUMENT_HASHES.with_borrow(|hashes| hashes.get(&id)) {
+            return Ok(hash);
+        }
+
+        Self::get_document(id).map(|data| hex::encode(Sha256::digest(data)))
+    }
+
+    /// Upload a document into the storage, with its SHA-256.
     ///
+    /// The data is stored only if no other document has the same data.
     /// Returns the ID of the uploaded document.
     pub fn upload_document(data: Vec<u8>) -> DeferredDataResult<u64> {
-        // insert document
         let next_id = Self::next_document_id()?;
-        DOCUMENTS.with_borrow_mut(|documents| {
-            documents.insert(next_id, data);
+        let hash = hex::encode(Sha256::digest(&data));
+        Self::reference_content(hash.clone(), &data);
+        DOCUMENT_HASHES.with_borrow_mut(|hashes| {
+            hashes.insert(next_id, hash);
         });
 
         Ok(next_id)
     }
 
+    /// Remove a document from the storage.
+    ///
+    /// Its data is removed only when no other document references it
+    pub fn remove_document(id: u64) {
+        let stored_by_id = DOCUMENTS
+            .with_borrow_mut(|documents| documents.remove(&id))
+            .is_some();
+        let Some(hash) = DOCUMENT_HASHES.with_borrow_mut(|hashes| hashes.remove(&id)) else {
+            return;
+        };
+        if !stored_by_id {
+            Self::release_content(&hash);
+        }
+    }
+
+    /// Count the stored documents data, counting once the documents with the same data
+    pub fn count() -> u64 {
+        DOCUMENT_REFERENCES.with_borrow(|refs| refs.len()) + Self::count_stored_by_id()
+    }
+
+    /// Count the documents stored by ID, which haven't been migrated yet
+    pub fn count_stored_by_id() -> u64 {
+        DOCUMENTS.with_borrow(|documents| documents.len())
+    }
+
+    /// Count the documents data stored whole, either by ID or by SHA-256, which haven't been
+    /// moved to the chunked storage yet
+    pub fn count_stored_whole() -> u64 {
+        DOCUMENT_CONTENTS.with_borrow(|contents| contents.len()) + Self::count_stored_by_id()
+    }
+
+    /// Move up to `count` documents data stored whole to the chunked storage: first the documents
+    /// stored by ID following the key `after`, then the contents stored by SHA-256.
+    ///
+    /// Returns the number of migrated documents and the key of the last one stored by ID
+    pub fn migrate(after: Option<&ID>, count: usize) -> (u64, Option<ID>) {
+        let start = match after.and_then(|id| id.0.to_u64()) {
+            Some(id) => Bound::Excluded(id),
+            None => Bound::Unbounded,
+        };
+        let ids = DOCUMENTS.with_borrow(|documents| {
+            documents
+                .range((start, Bound::Unbounded))
+                .take(count)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        });
+
+        for id in &ids {
+            let Some(data) = DOCUMENTS.with_borrow_mut(|documents| documents.remove(id)) else {
+                continue;
+            };
+            let hash = DOCUMENT_HASHES
+                .with_borrow(|hashes| hashes.get(id))
+                .unwrap_or_else(|| hex::encode(Sha256::digest(&data)));
+            Self::reference_content(hash.clone(), &data);
+            DOCUMENT_HASHES.with_borrow_mut(|hashes| {
+                hashes.insert(*id, hash);
+            });
+        }
+
+        // the contents are removed once chunked, so the next one is always the first
+        let mut migrated = ids.len();
+        while migrated < count {
+            let Some((hash, data)) =
+                DOCUMENT_CONTENTS.with_borrow_mut(|contents| contents.pop_first())
+            else {
+                break;
+            };
+            Self::write_content(&hash, &data);
+            migrated += 1;
+        }
+
+        let last = ids.last().copied().map(ID::from).or_else(|| after.cloned());
+        (migrated as u64, last)
+    }
+
+    /// Store a document by ID, as before the deduplication
+    #[cfg(test)]
+    pub fn insert_stored_by_id(id: u64, data: Vec<u8>) {
+        DOCUMENTS.with_borrow_mut(|documents| documents.insert(id, data));
+    }
+
+    /// Store a content whole, as before it was stored in chunks
+    #[cfg(test)]
+    pub fn insert_stored_whole(data: Vec<u8>) -> u64 {
+        let id = Self::next_document_id().unwrap();
+        let hash = hex::encode(Sha256::digest(&data));
+        DOCUMENT_CONTENTS.with_borrow_mut(|contents| contents.insert(hash.clone(), data));
+        DOCUMENT_REFERENCES.with_borrow_mut(|refs| refs.insert(hash.clone(), 1));
+        DOCUMENT_HASHES.with_borrow_mut(|hashes| hashes.insert(id, hash));
+
+        id
+    }
+
+    /// Add a reference to the content with the provided hash, storing its data if it's the first
+    fn reference_content(hash: String, data: &[u8]) {
+        let references = Self::references(&hash);
+        if references == 0 {
+            Self::write_content(&hash, data);
+        }
+        DOCUMENT_REFERENCES.with_borrow_mut(|refs| {
+            refs.insert(hash, references + 1);
+        });
+    }
+
+    /// Remove a reference to the content with the provided hash, removing its data if it was the
+    /// last one
+    fn release_content(hash: &String) {
+        let references = Self::references(hash);
+        if references > 1 {
+            DOCUMENT_REFERENCES.with_borrow_mut(|refs| {
+                refs.insert(hash.clone(), references - 1);
+            });
+            return;
+        }
+
+        DOCUMENT_REFERENCES.with_borrow_mut(|refs| refs.remove(hash));
+        DOCUMENT_CONTENTS.with_borrow_mut(|contents| contents.remove(hash));
+        let key = content_key(hash);
+        DOCUMENT_CONTENT_CHUNKS.with_borrow_mut(|chunks| {
+            let mut index = 0;
+            while chunks.remove(&(key, index)).is_some() {
+                index += 1;
+            }
+        });
+    }
+
+    /// Get the amount of documents referencing the content with the provided hash
+    fn references(hash: &String) -> u64 {
+        DOCUMENT_REFERENCES
+            .with_borrow(|refs| refs.get(hash))
+            .unwrap_or_default()
+    }
+
+    /// Write the data of a content in chunks.
+    ///
+    /// An empty content is stored as a single empty chunk, so that it can be told from a missing
+    /// one
+    fn write_content(hash: &str, data: &[u8]) {
+        let key = content_key(hash);
+        DOCUMENT_CONTENT_CHUNKS.with_borrow_mut(|chunks| {
+            if data.is_empty() {
+                chunks.insert((key, 0), vec![]);
+            }
+            for (index, chunk) in data.chunks(CONTENT_CHUNK_SIZE as usize).enumerate() {
+                chunks.insert((key, index as u64), chunk.to_vec());
+            }
+        });
+    }
+
+    /// Read the whole data of a content
+    fn read_content(hash: &String) -> Option<Vec<u8>> {
+        if let Some(data) = DOCUMENT_CONTENTS.with_borrow(|contents| contents.get(hash)) {
+            return Some(data);
+        }
+
+        let key = content_key(hash);
+        let chunks = DOCUMENT_CONTENT_CHUNKS.with_borrow(|chunks| {
+            chunks
+                .range((key, 0)..=(key, u64::MAX))
+                .map(|(_, chunk)| chunk)
+                .collect::<Vec<_>>()
+        });
+        if chunks.is_empty() {
+            return None;
+        }
+
+        Some(chunks.concat())
+    }
+
+    /// Read the bytes of a content in the range `[start, end)`
+    fn read_content_range(hash: &String, start: u64, end: u64) -> Option<Vec<u8>> {
+        if let Some(data) = DOCUMENT_CONTENTS.with_borrow(|contents| contents.get(hash)) {
+            return Some(slice(&data, start, end));
+        }
+
+        let key = content_key(hash);
+        let first = start / CONTENT_CHUNK_SIZE;
+        let last = end.saturating_sub(1).max(start) / CONTENT_CHUNK_SIZE;
+        DOCUMENT_CONTENT_CHUNKS.with_borrow(|chunks| {
+            let mut data = Vec::with_capacity(end.saturating_sub(start) as usize);
+            for index in first..=last {
+                let chunk = chunks.get(&(key, index))?;
+                let offset = index * CONTENT_CHUNK_SIZE;
+                data.extend_from_slice(&slice(&chunk, start.saturating_sub(offset), end - offset));
+            }
+
+            Some(data)
+        })
+    }
+
     /// Get next document ID and increment it
     fn next_document_id() -> DeferredDataResult<u64> {
         NEXT_DOCUMENT_ID.with_borrow_mut(|id| {
@@ -39,6 +286,24 @@ impl DocumentStorage {
     }
 }
 
+/// Get the key of the chunks of a content from its hex SHA-256
+fn content_key(hash: &str) -> [u8; 32] {
+    let mut key = [0; 32];
+    if hex::decode_to_slice(hash, &mut key).is_err() {
+        log::warn!("Invalid content hash {hash}");
+    }
+
+    key
+}
+
+/// Copy the bytes of `data` in the range `[start, end)`, clamped to its length
+fn slice(data: &[u8], start: u64, end: u64) -> Vec<u8> {
+    let end = (end as usize).min(data.len());
+    let start = (start as usize).min(end);
+
+    data[start..end].to_vec()
+}
+
 #[cfg(test)]
 mod test {
 
@@ -54,5 +319,199 @@ mod test {
 
         assert_eq!(data, stored_data);
         assert_eq!(DocumentStorage::next_document_id().unwrap(), 1);
+
+        assert_eq!(
+            DocumentStorage::get_document_hash(id).unwrap(),
+            hex::encode(Sha256::digest(&data))
+        );
+
+        DocumentStorage::remove_document(id);
+        assert!(DocumentStorage::get_document(id).is_err());
+        assert!(DocumentStorage::get_document_hash(id).is_err());
+        assert_eq!(DocumentStorage::count(), 0);
+    }
+
+    #[test]
+    fn test_should_store_documents_with_same_data_once() {
+        let first = DocumentStorage::upload_document(vec![1, 2, 3]).unwrap();
+        let second = DocumentStorage::upload_document(vec![1, 2, 3]).unwrap();
+        let other = DocumentStorage::upload_document(vec![4, 5, 6]).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(DocumentStorage::count(), 2);
+        let hash = DocumentStorage::get_document_hash(first).unwrap();
+        assert_eq!(DocumentStorage::get_document_hash(second).unwrap(), hash);
+        assert_eq!(DocumentStorage::references(&hash), 2);
+
+        // data is kept while it's referenced
+        DocumentStorage::remove_document(first);
+        assert!(DocumentStorage::get_document(first).is_err());
+        assert_eq!(
+            DocumentStorage::get_document(second).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(DocumentStorage::references(&hash), 1);
+        assert_eq!(DocumentStorage::count(), 2);
+
+        // removing twice doesn't release the data
+        DocumentStorage::remove_document(first);
+        assert_eq!(DocumentStorage::references(&hash), 1);
+
+        DocumentStorage::remove_document(second);
+        assert_eq!(DocumentStorage::references(&hash), 0);
+        assert_eq!(DocumentStorage::count(), 1);
+        assert_eq!(DocumentStorage::get_document(other).unwrap(), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_should_compute_hash_of_documents_stored_without_hash() {
+        DOCUMENTS.with_borrow_mut(|documents| documents.insert(10, vec![1, 2, 3]));
+
+        assert_eq!(
+            DocumentStorage::get_document_hash(10).unwrap(),
+            hex::encode(Sha256::digest([1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_should_migrate_documents_stored_by_id() {
+        DOCUMENTS.with_borrow_mut(|documents| {
+            documents.insert(0, vec![1, 2, 3]);
+            documents.insert(1, vec![1, 2, 3]);
+            documents.insert(2, vec![4, 5, 6]);
+        });
+        // documents uploaded after the hashes were introduced
+        DOCUMENT_HASHES.with_borrow_mut(|hashes| {
+            hashes.insert(2, hex::encode(Sha256::digest([4, 5, 6])));
+        });
+        NEXT_DOCUMENT_ID.with_borrow_mut(|id| id.set(3).unwrap());
+        let id = DocumentStorage::upload_document(vec![4, 5, 6]).unwrap();
+        assert_eq!(DocumentStorage::count(), 4);
+        assert_eq!(DocumentStorage::count_stored_by_id(), 3);
+
+        assert_eq!(DocumentStorage::migrate(None, 2), (2, Some(ID::from(1u64))));
+        assert_eq!(DocumentStorage::count_stored_by_id(), 1);
+        assert_eq!(
+            DocumentStorage::migrate(Some(&ID::from(1u64)), 2),
+            (1, Some(ID::from(2u64)))
+        );
+        assert_eq!(
+            DocumentStorage::migrate(Some(&ID::from(2u64)), 2),
+            (0, Some(ID::from(2u64)))
+        );
+
+        assert_eq!(DocumentStorage::count(), 2);
+        assert_eq!(DocumentStorage::count_stored_by_id(), 0);
+        for (id, data) in [
+            (0, vec![1, 2, 3]),
+            (1, vec![1, 2, 3]),
+            (2, vec![4, 5, 6]),
+            (id, vec![4, 5, 6]),
+        ] {
+            assert_eq!(DocumentStorage::get_document(id).unwrap(), data);
+            assert_eq!(
+                DocumentStorage::get_document_hash(id).unwrap(),
+                hex::encode(Sha256::digest(&data))
+            );
+        }
+        assert_eq!(
+            DocumentStorage::references(&hex::encode(Sha256::digest([4, 5, 6]))),
+            2
+        );
+    }
+
+    #[test]
+    fn test_should_read_range_of_chunked_document() {
+        let size = CONTENT_CHUNK_SIZE as usize * 2 + 10;
+        let data = (0..size).map(|i| i as u8).collect::<Vec<_>>();
+        let id = DocumentStorage::upload_document(data.clone()).unwrap();
+        let key = content_key(&DocumentStorage::get_document_hash(id).unwrap());
+        assert_eq!(
+            DOCUMENT_CONTENT_CHUNKS.with_borrow(|chunks| chunks.len()),
+            3
+        );
+        assert!(DOCUMENT_CONTENT_CHUNKS.with_borrow(|chunks| chunks.contains_key(&(key, 2))));
+
+        assert_eq!(DocumentStorage::get_document(id).unwrap(), data);
+        for (start, end) in [
+            (0, 10),
+            (5, CONTENT_CHUNK_SIZE + 5),
+            (CONTENT_CHUNK_SIZE, CONTENT_CHUNK_SIZE * 2),
+            (CONTENT_CHUNK_SIZE - 1, size as u64),
+            (size as u64 - 1, size as u64),
+        ] {
+            assert_eq!(
+                DocumentStorage::get_document_range(id, start, end).unwrap(),
+                data[start as usize..end as usize]
+            );
+        }
+
+        DocumentStorage::remove_document(id);
+        assert_eq!(
+            DOCUMENT_CONTENT_CHUNKS.with_borrow(|chunks| chunks.len()),
+            0
+        );
+        assert!(DocumentStorage::get_document_range(id, 0, 10).is_err());
+    }
+
+    #[test]
+    fn test_should_store_empty_document() {
+        let id = DocumentStorage::upload_document(vec![]).unwrap();
+
+        assert_eq!(DocumentStorage::get_document(id).unwrap(), Vec::<u8>::new());
+        assert_eq!(
+            DocumentStorage::get_document_range(id, 0, 0).unwrap(),
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn test_should_migrate_documents_stored_whole() {
+        let whole = DocumentStorage::insert_stored_whole(vec![1, 2, 3]);
+        DocumentStorage::insert_stored_by_id(10, vec![4, 5, 6]);
+        assert_eq!(DocumentStorage::count_stored_whole(), 2);
+        assert_eq!(
+            DocumentStorage::get_document_range(whole, 1, 3).unwrap(),
+            vec![2, 3]
+        );
+        assert_eq!(
+            DocumentStorage::get_document_range(10, 0, 2).unwrap(),
+            vec![4, 5]
+        );
+
+        assert_eq!(
+            DocumentStorage::migrate(None, 1),
+            (1, Some(ID::from(10u64)))
+        );
+        assert_eq!(
+            DocumentStorage::migrate(Some(&ID::from(10u64)), 2),
+            (1, Some(ID::from(10u64)))
+        );
+        assert_eq!(DocumentStorage::count_stored_whole(), 0);
+        assert_eq!(DOCUMENT_CONTENTS.with_borrow(|contents| contents.len()), 0);
+        assert_eq!(DocumentStorage::count(), 2);
+        assert_eq!(DocumentStorage::get_document(whole).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            DocumentStorage::get_document_range(10, 1, 3).unwrap(),
+            vec![5, 6]
+        );
+
+        DocumentStorage::remove_document(whole);
+        assert_eq!(DocumentStorage::count(), 1);
+        assert!(DocumentStorage::get_document(whole).is_err());
+    }
+
+    #[test]
+    fn test_should_remove_document_stored_by_id() {
+        DOCUMENTS.with_borrow_mut(|documents| documents.insert(0, vec![1, 2, 3]));
+        DOCUMENT_HASHES.with_borrow_mut(|hashes| {
+            hashes.insert(0, hex::encode(Sha256::digest([1, 2, 3])));
+        });
+        NEXT_DOCUMENT_ID.with_borrow_mut(|id| id.set(1).unwrap());
+        let id = DocumentStorage::upload_document(vec![1, 2, 3]).unwrap();
+
+        DocumentStorage::remove_document(0);
+        assert!(DocumentStorage::get_document(0).is_err());
+        assert_eq!(DocumentStorage::get_document(id).unwrap(), vec![1, 2, 3]);
+        assert_eq!(DocumentStorage::count(), 1);
     }
 }
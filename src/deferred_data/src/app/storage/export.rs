@@ -0,0 +1,932 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ops::Bound;
+
+use did::deferred::{
+    CollectionDigest, DeferredDataError, DeferredDataResult, ExportChunk, ExportCollection,
+    ExportCursor, ExportRecord, ImportError, StorableDomains, StorableMimeTypes,
+};
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::storable::Bound as StorableBound;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, Storable};
+
+use super::{
+    AGENCIES_STATUS, AGENCY_MEMBERS, AGENCY_QUOTAS, AGENCY_TRANSFERS_AUDIT, AGENCY_USAGE,
+    CONSUMED_NONCES, CONTRACTS, CONTRACT_USAGE, DOCUMENTS, DOCUMENT_ANCHORS, DOCUMENT_CONTENTS,
+    DOCUMENT_CONTENT_CHUNKS, DOCUMENT_HASHES, DOCUMENT_HISTORY, DOCUMENT_PREVIEWS,
+    DOCUMENT_REFERENCES, NEXT_DOCUMENT_ID, REAL_ESTATES,
+};
+use crate::app::configuration::Configuration;
+use crate::app::migrations::{Migrations, SCHEMA_VERSIONS};
+
+/// Maximum size of the records of an exported chunk, to stay below the response size limit of the
+/// export and the ingress message size limit of the import.
+///
+/// A value bigger than this is exported in parts, each one in a chunk of its own
+const EXPORT_CHUNK_MAX_SIZE: usize = 1024 * 1024;
+
+/// Maximum size of the records hashed for a page of a digest, to stay below the instruction limit
+/// of a query.
+///
+/// A page always contains at least one record, even if it's bigger
+const DIGEST_PAGE_MAX_SIZE: usize = 32 * 1024 * 1024;
+
+thread_local! {
+    /// The record whose parts are being imported, until its last part is imported.
+    ///
+    /// It's kept on the heap, so after an upgrade the record must be imported again from its
+    /// first part
+    static IMPORTING_RECORD: RefCell<Option<ImportingRecord>> = const { RefCell::new(None) };
+}
+
+/// A record imported in parts
+struct ImportingRecord {
+    collection: ExportCollection,
+    key: Vec<u8>,
+    /// The parts of the value imported so far
+    value: Vec<u8>,
+    size: u64,
+}
+
+/// Export and import of the collections of the canister, with the records encoded as they are
+/// stored
+pub struct ExportStorage;
+
+impl ExportStorage {
+    /// Export the chunk of records of the collection following the position `after`
+    pub fn export_chunk(collection: ExportCollection, after: Option<ExportCursor>) -> ExportChunk {
+        let cursor = after.as_ref();
+        let (records, last) = match collection {
+            ExportCollection::Contracts => {
+                CONTRACTS.with_borrow(|contracts| export_records(contracts, cursor))
+            }
+            ExportCollection::Documents => {
+                DOCUMENTS.with_borrow(|documents| export_records(documents, cursor))
+            }
+            ExportCollection::RealEstates => {
+                REAL_ESTATES.with_borrow(|real_estates| export_records(real_estates, cursor))
+            }
+            ExportCollection::NextDocumentId if after.is_none() => (
+                vec![ExportRecord::new(
+                    vec![],
+                    Self::next_document_id().to_bytes().into_owned(),
+                )],
+                true,
+            ),
+            ExportCollection::NextDocumentId => (vec![], true),
+            ExportCollection::DocumentHistory => {
+                DOCUMENT_HISTORY.with_borrow(|history| export_records(history, cursor))
+            }
+            ExportCollection::DocumentHashes => {
+                DOCUMENT_HASHES.with_borrow(|hashes| export_records(hashes, cursor))
+            }
+            ExportCollection::DocumentAnchors => {
+                DOCUMENT_ANCHORS.with_borrow(|anchors| export_records(anchors, cursor))
+            }
+            ExportCollection::DocumentContents => {
+                DOCUMENT_CONTENTS.with_borrow(|contents| export_records(contents, cursor))
+            }
+            ExportCollection::DocumentReferences => {
+                DOCUMENT_REFERENCES.with_borrow(|refs| export_records(refs, cursor))
+            }
+            ExportCollection::AgencyQuotas => {
+                AGENCY_QUOTAS.with_borrow(|quotas| export_records(quotas, cursor))
+            }
+            ExportCollection::AgencyUsage => {
+                AGENCY_USAGE.with_borrow(|usage| export_records(usage, cursor))
+            }
+            ExportCollection::ContractUsage => {
+                CONTRACT_USAGE.with_borrow(|usage| export_records(usage, cursor))
+            }
+            ExportCollection::DocumentPreviews => {
+                DOCUMENT_PREVIEWS.with_borrow(|previews| export_records(previews, cursor))
+            }
+            ExportCollection::DocumentContentChunks => {
+                DOCUMENT_CONTENT_CHUNKS.with_borrow(|chunks| export_records(chunks, cursor))
+            }
+            ExportCollection::AgencyMembers => {
+                AGENCY_MEMBERS.with_borrow(|members| export_records(members, cursor))
+            }
+            ExportCollection::AgenciesStatus => {
+                AGENCIES_STATUS.with_borrow(|status| export_records(status, cursor))
+            }
+            ExportCollection::AgencyTransfersAudit => {
+                AGENCY_TRANSFERS_AUDIT.with_borrow(|audit| export_records(audit, cursor))
+            }
+            ExportCollection::ConsumedNonces => {
+                CONSUMED_NONCES.with_borrow(|nonces| export_records(nonces, cursor))
+            }
+            ExportCollection::SchemaVersions => {
+                SCHEMA_VERSIONS.with_borrow(|versions| export_records(versions, cursor))
+            }
+            ExportCollection::AllowedMimeTypes if after.is_none() => (
+                vec![ExportRecord::new(
+                    vec![],
+                    Self::allowed_mime_types().to_bytes().into_owned(),
+                )],
+                true,
+            ),
+            ExportCollection::AllowedMimeTypes => (vec![], true),
+            ExportCollection::CustomDomains if after.is_none() => (
+                vec![ExportRecord::new(
+                    vec![],
+                    Self::custom_domains().to_bytes().into_owned(),
+                )],
+                true,
+            ),
+            ExportCollection::CustomDomains => (vec![], true),
+        };
+
+        ExportChunk::new(collection, after, records, last)
+    }
+
+    /// Import a chunk exported by [`ExportStorage::export_chunk`].
+    ///
+    /// The chunks of a collection must be imported in order, starting from the first one into
+    /// an empty collection. A chunk is either imported entirely or not at all, while a record
+    /// exported in parts is stored once its last part is imported
+    pub fn import_chunk(chunk: ExportChunk) -> DeferredDataResult<()> {
+        if !chunk.is_valid() {
+            return Err(DeferredDataError::Import(ImportError::InvalidChecksum));
+        }
+
+        match chunk.collection {
+            ExportCollection::Contracts => {
+                CONTRACTS.with_borrow_mut(|contracts| import_records(contracts, chunk))
+            }
+            ExportCollection::Documents => {
+                DOCUMENTS.with_borrow_mut(|documents| import_records(documents, chunk))
+            }
+            ExportCollection::RealEstates => {
+                REAL_ESTATES.with_borrow_mut(|real_estates| import_records(real_estates, chunk))
+            }
+            ExportCollection::NextDocumentId => Self::import_next_document_id(chunk),
+            ExportCollection::DocumentHistory => {
+                DOCUMENT_HISTORY.with_borrow_mut(|history| import_records(history, chunk))
+            }
+            ExportCollection::DocumentHashes => {
+                DOCUMENT_HASHES.with_borrow_mut(|hashes| import_records(hashes, chunk))
+            }
+            ExportCollection::DocumentAnchors => {
+                DOCUMENT_ANCHORS.with_borrow_mut(|anchors| import_records(anchors, chunk))
+            }
+            ExportCollection::DocumentContents => {
+                DOCUMENT_CONTENTS.with_borrow_mut(|contents| import_records(contents, chunk))
+            }
+            ExportCollection::DocumentReferences => {
+                DOCUMENT_REFERENCES.with_borrow_mut(|refs| import_records(refs, chunk))
+            }
+            ExportCollection::AgencyQuotas => {
+                AGENCY_QUOTAS.with_borrow_mut(|quotas| import_records(quotas, chunk))
+            }
+            ExportCollection::AgencyUsage => {
+                AGENCY_USAGE.with_borrow_mut(|usage| import_records(usage, chunk))
+            }
+            ExportCollection::ContractUsage => {
+                CONTRACT_USAGE.with_borrow_mut(|usage| import_records(usage, chunk))
+            }
+            ExportCollection::DocumentPreviews => {
+                DOCUMENT_PREVIEWS.with_borrow_mut(|previews| import_records(previews, chunk))
+            }
+            ExportCollection::DocumentContentChunks => {
+                DOCUMENT_CONTENT_CHUNKS.with_borrow_mut(|chunks| import_records(chunks, chunk))
+            }
+            ExportCollection::AgencyMembers => {
+                AGENCY_MEMBERS.with_borrow_mut(|members| import_records(members, chunk))
+            }
+            ExportCollection::AgenciesStatus => {
+                AGENCIES_STATUS.with_borrow_mut(|status| import_records(status, chunk))
+            }
+            ExportCollection::AgencyTransfersAudit => {
+                AGENCY_TRANSFERS_AUDIT.with_borrow_mut(|audit| import_records(audit, chunk))
+            }
+            ExportCollection::ConsumedNonces => {
+                CONSUMED_NONCES.with_borrow_mut(|nonces| import_records(nonces, chunk))
+            }
+            ExportCollection::SchemaVersions => Self::import_schema_versions(chunk),
+            ExportCollection::AllowedMimeTypes => {
+                Configuration::set_allowed_mime_types(Self::import_configuration_list(chunk)?)
+            }
+            ExportCollection::CustomDomains => {
+                Configuration::set_custom_domains(Self::import_configuration_list(chunk)?)
+            }
+        }
+    }
+
+    /// Compute the digest of the page of records of the collection following the key `after`.
+    ///
+    /// The key of the next page is returned in [`CollectionDigest::next`]
+    pub fn digest(collection: ExportCollection, after: Option<Vec<u8>>) -> CollectionDigest {
+        let (records, more) = Self::page(collection, after.as_deref(), None);
+        let next = records.last().filter(|_| more).map(|(key, _)| key.clone());
+
+        CollectionDigest::compute(collection, after, records, next)
+    }
+
+    /// Whether the page of records of the collection matches the digest computed by the canister
+    /// it has been exported from
+    pub fn verify_digest(expected: &CollectionDigest) -> bool {
+        let (records, more) = Self::page(
+            expected.collection,
+            expected.after.as_deref(),
+            Some(expected.records),
+        );
+        // the last page must not be followed by other records
+        if more != expected.next.is_some() {
+            return false;
+        }
+        let next = records.last().filter(|_| more).map(|(key, _)| key.clone());
+
+        CollectionDigest::compute(expected.collection, expected.after.clone(), records, next)
+            == *expected
+    }
+
+    /// Get the encoded records of the page of the collection following the key `after`, up to
+    /// `max_records` records if provided, or up to [`DIGEST_PAGE_MAX_SIZE`] bytes otherwise.
+    ///
+    /// Returns the records and whether other records follow them
+    fn page(
+        collection: ExportCollection,
+        after: Option<&[u8]>,
+        max_records: Option<u64>,
+    ) -> (Vec<(Vec<u8>, Vec<u8>)>, bool) {
+        match collection {
+            ExportCollection::Contracts => {
+                CONTRACTS.with_borrow(|contracts| page_records(contracts, after, max_records))
+            }
+            ExportCollection::Documents => {
+                DOCUMENTS.with_borrow(|documents| page_records(documents, after, max_records))
+            }
+            ExportCollection::RealEstates => REAL_ESTATES
+                .with_borrow(|real_estates| page_records(real_estates, after, max_records)),
+            ExportCollection::NextDocumentId if after.is_none() => (
+                vec![(vec![], Self::next_document_id().to_bytes().into_owned())],
+                false,
+            ),
+            ExportCollection::NextDocumentId => (vec![], false),
+            ExportCollection::DocumentHistory => {
+                DOCUMENT_HISTORY.with_borrow(|history| page_records(history, after, max_records))
+            }
+            ExportCollection::DocumentHashes => {
+                DOCUMENT_HASHES.with_borrow(|hashes| page_records(hashes, after, max_records))
+            }
+            ExportCollection::DocumentAnchors => {
+                DOCUMENT_ANCHORS.with_borrow(|anchors| page_records(anchors, after, max_records))
+            }
+            ExportCollection::DocumentContents => {
+                DOCUMENT_CONTENTS.with_borrow(|contents| page_records(contents, after, max_records))
+            }
+            ExportCollection::DocumentReferences => {
+                DOCUMENT_REFERENCES.with_borrow(|refs| page_records(refs, after, max_records))
+            }
+            ExportCollection::AgencyQuotas => {
+                AGENCY_QUOTAS.with_borrow(|quotas| page_records(quotas, after, max_records))
+            }
+            ExportCollection::AgencyUsage => {
+                AGENCY_USAGE.with_borrow(|usage| page_records(usage, after, max_records))
+            }
+            ExportCollection::ContractUsage => {
+                CONTRACT_USAGE.with_borrow(|usage| page_records(usage, after, max_records))
+            }
+            ExportCollection::DocumentPreviews => {
+                DOCUMENT_PREVIEWS.with_borrow(|previews| page_records(previews, after, max_records))
+            }
+            ExportCollection::DocumentContentChunks => DOCUMENT_CONTENT_CHUNKS
+                .with_borrow(|chunks| page_records(chunks, after, max_records)),
+            ExportCollection::AgencyMembers => {
+                AGENCY_MEMBERS.with_borrow(|members| page_records(members, after, max_records))
+            }
+            ExportCollection::AgenciesStatus => {
+                AGENCIES_STATUS.with_borrow(|status| page_records(status, after, max_records))
+            }
+            ExportCollection::AgencyTransfersAudit => {
+                AGENCY_TRANSFERS_AUDIT.with_borrow(|audit| page_records(audit, after, max_records))
+            }
+            ExportCollection::ConsumedNonces => {
+                CONSUMED_NONCES.with_borrow(|nonces| page_records(nonces, after, max_records))
+            }
+            ExportCollection::SchemaVersions => {
+                SCHEMA_VERSIONS.with_borrow(|versions| page_records(versions, after, max_records))
+            }
+            ExportCollection::AllowedMimeTypes if after.is_none() => (
+                vec![(vec![], Self::allowed_mime_types().to_bytes().into_owned())],
+                false,
+            ),
+            ExportCollection::AllowedMimeTypes => (vec![], false),
+            ExportCollection::CustomDomains if after.is_none() => (
+                vec![(vec![], Self::custom_domains().to_bytes().into_owned())],
+                false,
+            ),
+            ExportCollection::CustomDomains => (vec![], false),
+        }
+    }
+
+    fn next_document_id() -> u64 {
+        NEXT_DOCUMENT_ID.with_borrow(|id| *id.get())
+    }
+
+    fn allowed_mime_types() -> StorableMimeTypes {
+        StorableMimeTypes(Configuration::get_allowed_mime_types())
+    }
+
+    fn custom_domains() -> StorableDomains {
+        StorableDomains(Configuration::get_custom_domains())
+    }
+
+    /// Import the schema versions of the collections.
+    ///
+    /// The first chunk replaces the versions set by the install of the canister, which are set
+    /// again if the chunk can't be imported
+    fn import_schema_versions(chunk: ExportChunk) -> DeferredDataResult<()> {
+        let first = chunk.after.is_none();
+        if first {
+            SCHEMA_VERSIONS.with_borrow_mut(|versions| versions.clear_new());
+        }
+
+        let result = SCHEMA_VERSIONS.with_borrow_mut(|versions| import_records(versions, chunk));
+        if first && result.is_err() {
+            Migrations::init();
+        }
+
+        result
+    }
+
+    /// Decode a configuration list exported as a single record, such as the allowed MIME types,
+    /// which replaces the one set by the install of the canister
+    fn import_configuration_list(chunk: ExportChunk) -> DeferredDataResult<Vec<String>> {
+        let collection = chunk.collection;
+        if chunk.after.is_some() {
+            return Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                collection,
+            )));
+        }
+        let [record] = chunk.records.as_slice() else {
+            return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+                collection,
+            )));
+        };
+        // the record is decoded here, since a malformed one would trap while decoding it as stored
+        let Some(list) = record
+            .is_whole()
+            .then(|| candid::decode_one::<Vec<String>>(&record.value).ok())
+            .flatten()
+        else {
+            return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+                collection,
+            )));
+        };
+
+        Ok(list)
+    }
+
+    /// Import the ID of the next document, which can only be set before any document is uploaded
+    fn import_next_document_id(chunk: ExportChunk) -> DeferredDataResult<()> {
+        let collection = chunk.collection;
+        if chunk.after.is_some() {
+            return Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                collection,
+            )));
+        }
+        let [record] = chunk.records.as_slice() else {
+            return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+                collection,
+            )));
+        };
+        if !record.is_whole() || !fits_bound(u64::BOUND, record.value.len()) {
+            return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+                collection,
+            )));
+        }
+        if Self::next_document_id() != 0 {
+            return Err(DeferredDataError::Import(ImportError::CollectionNotEmpty(
+                collection,
+            )));
+        }
+
+        let next_id = u64::from_bytes(Cow::Borrowed(&record.value));
+        NEXT_DOCUMENT_ID
+            .with_borrow_mut(|id| id.set(next_id).map_err(|_| DeferredDataError::StorageError))?;
+
+        Ok(())
+    }
+}
+
+/// Export the records following the position `after`, up to [`EXPORT_CHUNK_MAX_SIZE`] bytes.
+///
+/// A value bigger than a chunk is exported in parts, one per chunk.
+/// Returns the records and whether they are the last ones
+fn export_records<K, V>(
+    storage: &BTreeMap<K, V, VirtualMemory<DefaultMemoryImpl>>,
+    after: Option<&ExportCursor>,
+) -> (Vec<ExportRecord>, bool)
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+{
+    let start = match after {
+        Some(cursor) => Bound::Excluded(K::from_bytes(Cow::Borrowed(&cursor.key))),
+        None => Bound::Unbounded,
+    };
+
+    // continue the record exported in parts
+    if let Some(ExportCursor {
+        key,
+        offset: Some(offset),
+    }) = after
+    {
+        if let Some(value) = storage.get(&K::from_bytes(Cow::Borrowed(key))) {
+            let part = export_part(key.clone(), &value.to_bytes(), *offset as usize);
+            let last = part.is_end() && storage.range((start, Bound::Unbounded)).next().is_none();
+            return (vec![part], last);
+        }
+    }
+
+    let mut records = Vec::new();
+    let mut size = 0;
+    for (key, value) in storage.range((start, Bound::Unbounded)) {
+        let key = key.to_bytes().into_owned();
+        let value = value.to_bytes();
+        let record_size = key.len() + value.len();
+        if size + record_size > EXPORT_CHUNK_MAX_SIZE {
+            // a record which doesn't fit a chunk comes in a chunk of its own, in parts if its
+            // value doesn't fit either
+            if records.is_empty() {
+                return (vec![export_part(key, &value, 0)], false);
+            }
+            return (records, false);
+        }
+
+        size += record_size;
+        records.push(ExportRecord::new(key, value.into_owned()));
+    }
+
+    (records, true)
+}
+
+/// Export the part of the value starting at `offset`, up to [`EXPORT_CHUNK_MAX_SIZE`] bytes
+fn export_part(key: Vec<u8>, value: &[u8], offset: usize) -> ExportRecord {
+    let offset = offset.min(value.len());
+    let end = (offset + EXPORT_CHUNK_MAX_SIZE).min(value.len());
+
+    ExportRecord {
+        key,
+        value: value[offset..end].to_vec(),
+        offset: offset as u64,
+        size: value.len() as u64,
+    }
+}
+
+/// Import the records of the chunk, which must follow the last record of the collection
+fn import_records<K, V>(
+    storage: &mut BTreeMap<K, V, VirtualMemory<DefaultMemoryImpl>>,
+    chunk: ExportChunk,
+) -> DeferredDataResult<()>
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+{
+    if let Some(offset) = chunk.after.as_ref().and_then(|after| after.offset) {
+        return import_part(storage, chunk, offset);
+    }
+
+    let collection = chunk.collection;
+    let last_key = storage.last_key_value().map(|(key, _)| key);
+    match (&chunk.after, &last_key) {
+        (None, Some(_)) => {
+            return Err(DeferredDataError::Import(ImportError::CollectionNotEmpty(
+                collection,
+            )))
+        }
+        (Some(after), Some(last_key)) if after.key.as_slice() == &*last_key.to_bytes() => {}
+        (Some(_), _) => {
+            return Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                collection,
+            )))
+        }
+        (None, None) => {}
+    }
+
+    // validate every record before inserting any
+    if chunk.records.iter().any(|record| {
+        !fits_bound(K::BOUND, record.key.len())
+            || !fits_bound(V::BOUND, record.size as usize)
+            || record.offset + record.value.len() as u64 > record.size
+    }) {
+        return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+            collection,
+        )));
+    }
+    // the first part of a record exported in parts comes in a chunk of its own
+    let starts_record = chunk.records.iter().any(|record| !record.is_whole());
+    if starts_record && (chunk.records.len() != 1 || chunk.records[0].offset != 0) {
+        return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+            collection,
+        )));
+    }
+    let keys = chunk
+        .records
+        .iter()
+        .map(|record| K::from_bytes(Cow::Borrowed(&record.key)))
+        .collect::<Vec<_>>();
+    let mut previous = last_key.as_ref();
+    for key in &keys {
+        if previous.is_some_and(|previous| previous >= key) {
+            return Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                collection,
+            )));
+        }
+        previous = Some(key);
+    }
+
+    // a chunk following a whole record discards the parts of any record left incomplete
+    IMPORTING_RECORD.with_borrow_mut(|importing| {
+        *importing = None;
+        if starts_record {
+            let record = chunk.records.into_iter().next().expect("checked above");
+            *importing = Some(ImportingRecord {
+                collection,
+                key: record.key,
+                value: record.value,
+                size: record.size,
+            });
+        } else {
+            for (key, record) in keys.into_iter().zip(chunk.records) {
+                storage.insert(key, V::from_bytes(Cow::Owned(record.value)));
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Import the part of the record being imported starting at `offset`, storing the record once
+/// its last part is imported
+fn import_part<K, V>(
+    storage: &mut BTreeMap<K, V, VirtualMemory<DefaultMemoryImpl>>,
+    chunk: ExportChunk,
+    offset: u64,
+) -> DeferredDataResult<()>
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+{
+    let collection = chunk.collection;
+    let [part] = chunk.records.as_slice() else {
+        return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+            collection,
+        )));
+    };
+
+    IMPORTING_RECORD.with_borrow_mut(|importing| {
+        let Some(record) = importing.as_mut().filter(|record| {
+            record.collection == collection
+                && chunk.after.as_ref().map(|after| &after.key) == Some(&record.key)
+                && record.value.len() as u64 == offset
+        }) else {
+            return Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                collection,
+            )));
+        };
+        if part.key != record.key
+            || part.offset != offset
+            || part.size != record.size
+            || part.value.is_empty()
+            || part.offset + part.value.len() as u64 > part.size
+        {
+            return Err(DeferredDataError::Import(ImportError::InvalidRecord(
+                collection,
+            )));
+        }
+
+        record.value.extend_from_slice(&part.value);
+        if part.is_end() {
+            let record = importing.take().expect("checked above");
+            storage.insert(
+                K::from_bytes(Cow::Owned(record.key)),
+                V::from_bytes(Cow::Owned(record.value)),
+            );
+        }
+
+        Ok(())
+    })
+}
+
+/// Get the encoded records following the key `after`, up to `max_records` records if provided,
+/// or up to [`DIGEST_PAGE_MAX_SIZE`] bytes otherwise.
+///
+/// Returns the records and whether other records follow them
+fn page_records<K, V>(
+    storage: &BTreeMap<K, V, VirtualMemory<DefaultMemoryImpl>>,
+    after: Option<&[u8]>,
+    max_records: Option<u64>,
+) -> (Vec<(Vec<u8>, Vec<u8>)>, bool)
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+{
+    let start = match after {
+        Some(key) => Bound::Excluded(K::from_bytes(Cow::Borrowed(key))),
+        None => Bound::Unbounded,
+    };
+
+    let mut records = Vec::new();
+    let mut size = 0;
+    for (key, value) in storage.range((start, Bound::Unbounded)) {
+        let (key, value) = (key.to_bytes().into_owned(), value.to_bytes().into_owned());
+        let record_size = key.len() + value.len();
+        let full = match max_records {
+            Some(max_records) => records.len() as u64 == max_records,
+            None => !records.is_empty() && size + record_size > DIGEST_PAGE_MAX_SIZE,
+        };
+        if full {
+            return (records, true);
+        }
+
+        size += record_size;
+        records.push((key, value));
+    }
+
+    (records, false)
+}
+
+/// Whether an encoded value of the provided length respects the bound of its type
+fn fits_bound(bound: StorableBound, len: usize) -> bool {
+    match bound {
+        StorableBound::Bounded {
+            max_size,
+            is_fixed_size: true,
+        } => len == max_size as usize,
+        StorableBound::Bounded { max_size, .. } => len <= max_size as usize,
+        StorableBound::Unbounded => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::{AgencyId, AgencyQuota, AgencyStatus, DocumentImage, DocumentPreviews};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::storage::{
+        AgencyMemberStorage, AgencyStorage, ContractStorage, DocumentPreviewStorage,
+        DocumentStorage, NonceStorage, UsageStorage,
+    };
+    use crate::app::test_utils::{alice, store_mock_contract};
+
+    /// Export every chunk of the collection
+    fn export_all(collection: ExportCollection) -> Vec<ExportChunk> {
+        let mut chunks = vec![ExportStorage::export_chunk(collection, None)];
+        while let Some(next) = chunks.last().unwrap().next() {
+            chunks.push(ExportStorage::export_chunk(collection, Some(next)));
+        }
+
+        chunks
+    }
+
+    /// Compute every page of the digest of the collection
+    fn digest_all(collection: ExportCollection) -> Vec<CollectionDigest> {
+        let mut pages = vec![ExportStorage::digest(collection, None)];
+        while let Some(next) = pages.last().unwrap().next.clone() {
+            pages.push(ExportStorage::digest(collection, Some(next)));
+        }
+
+        pages
+    }
+
+    /// Remove every record, as in a fresh canister
+    fn clear() {
+        CONTRACTS.with_borrow_mut(|contracts| contracts.clear_new());
+        DOCUMENTS.with_borrow_mut(|documents| documents.clear_new());
+        REAL_ESTATES.with_borrow_mut(|real_estates| real_estates.clear_new());
+        DOCUMENT_HISTORY.with_borrow_mut(|history| history.clear_new());
+        DOCUMENT_HASHES.with_borrow_mut(|hashes| hashes.clear_new());
+        DOCUMENT_ANCHORS.with_borrow_mut(|anchors| anchors.clear_new());
+        DOCUMENT_CONTENTS.with_borrow_mut(|contents| contents.clear_new());
+        DOCUMENT_CONTENT_CHUNKS.with_borrow_mut(|chunks| chunks.clear_new());
+        DOCUMENT_REFERENCES.with_borrow_mut(|refs| refs.clear_new());
+        AGENCY_QUOTAS.with_borrow_mut(|quotas| quotas.clear_new());
+        AGENCY_USAGE.with_borrow_mut(|usage| usage.clear_new());
+        CONTRACT_USAGE.with_borrow_mut(|usage| usage.clear_new());
+        DOCUMENT_PREVIEWS.with_borrow_mut(|previews| previews.clear_new());
+        AGENCY_MEMBERS.with_borrow_mut(|members| members.clear_new());
+        AGENCIES_STATUS.with_borrow_mut(|status| status.clear_new());
+        AGENCY_TRANSFERS_AUDIT.with_borrow_mut(|audit| audit.clear_new());
+        CONSUMED_NONCES.with_borrow_mut(|nonces| nonces.clear_new());
+        Migrations::init();
+        Configuration::set_allowed_mime_types(vec![]).unwrap();
+        Configuration::set_custom_domains(vec![]).unwrap();
+        NEXT_DOCUMENT_ID.with_borrow_mut(|id| id.set(0).unwrap());
+        IMPORTING_RECORD.with_borrow_mut(|importing| *importing = None);
+    }
+
+    #[test]
+    fn test_should_export_and_import_collections() {
+        store_mock_contract(1, 2);
+        store_mock_contract(2, 2);
+        // documents bigger than the chunk size are exported in parts
+        let big_document = 2 * EXPORT_CHUNK_MAX_SIZE + 1;
+        DocumentStorage::upload_document(vec![1; big_document]).unwrap();
+        DocumentStorage::upload_document(vec![2; big_document]).unwrap();
+        DocumentStorage::upload_document(vec![2; big_document]).unwrap();
+        let quota = AgencyQuota {
+            max_documents: 10,
+            max_document_bytes: 1024,
+        };
+        UsageStorage::set_quota(AgencyId(1), Some(quota));
+        let image = DocumentImage {
+            mime_type: "image/jpeg".to_string(),
+            width: 2,
+            height: 1,
+            data: vec![1, 2, 3],
+        };
+        let previews = DocumentPreviews {
+            thumbnail: image.clone(),
+            preview: image,
+        };
+        DocumentPreviewStorage::insert(0, previews.clone());
+        AgencyMemberStorage::set(alice(), Some(AgencyId(1)));
+        AgencyStorage::set_status(AgencyId(2), AgencyStatus::Suspended);
+        NonceStorage::consume("nonce", u64::MAX).unwrap();
+        Configuration::set_allowed_mime_types(vec!["application/pdf".to_string()]).unwrap();
+        Configuration::set_custom_domains(vec!["docs.ekoke.io".to_string()]).unwrap();
+        // a collection behind its schema version is migrated by the next upgrade
+        SCHEMA_VERSIONS.with_borrow_mut(|versions| versions.insert("contracts".to_string(), 1));
+
+        let digests = ExportCollection::all()
+            .into_iter()
+            .flat_map(digest_all)
+            .collect::<Vec<_>>();
+        let exported = ExportCollection::all()
+            .into_iter()
+            .map(export_all)
+            .collect::<Vec<_>>();
+        // the documents with the same data are stored once, each one in three chunks
+        assert_eq!(
+            exported[13]
+                .iter()
+                .map(|chunk| chunk.records.len())
+                .sum::<usize>(),
+            6
+        );
+        assert!(exported.iter().flatten().all(|chunk| {
+            chunk
+                .records
+                .iter()
+                .map(|record| record.value.len())
+                .sum::<usize>()
+                <= EXPORT_CHUNK_MAX_SIZE
+        }));
+
+        clear();
+        for chunk in exported.into_iter().flatten() {
+            ExportStorage::import_chunk(chunk).unwrap();
+        }
+
+        assert!(digests.iter().all(ExportStorage::verify_digest));
+        assert!(ContractStorage::get_contract(&2u64.into()).is_some());
+        assert_eq!(
+            DocumentStorage::get_document(2).unwrap(),
+            vec![2; big_document]
+        );
+        assert_eq!(DocumentStorage::upload_document(vec![3]).unwrap(), 3);
+        assert_eq!(UsageStorage::get_quota(AgencyId(1)), Some(quota));
+        assert_eq!(DocumentPreviewStorage::get(0), Some(previews));
+        assert!(AgencyMemberStorage::is_member(alice(), AgencyId(1)));
+        assert!(AgencyStorage::is_suspended(AgencyId(2)));
+        assert!(NonceStorage::is_consumed("nonce"));
+        assert_eq!(
+            Configuration::get_allowed_mime_types(),
+            vec!["application/pdf".to_string()]
+        );
+        assert_eq!(
+            Configuration::get_custom_domains(),
+            vec!["docs.ekoke.io".to_string()]
+        );
+        assert_eq!(
+            SCHEMA_VERSIONS.with_borrow(|versions| versions.get(&"contracts".to_string())),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_should_keep_installed_schema_versions_if_import_fails() {
+        Migrations::init();
+        let installed = digest_all(ExportCollection::SchemaVersions);
+        let mut chunk = ExportStorage::export_chunk(ExportCollection::SchemaVersions, None);
+        chunk.records[0].value = vec![1];
+        chunk.records[0].size = 1;
+        let chunk = ExportChunk::new(chunk.collection, chunk.after, chunk.records, chunk.last);
+
+        assert!(ExportStorage::import_chunk(chunk).is_err());
+        assert!(installed.iter().all(ExportStorage::verify_digest));
+    }
+
+    #[test]
+    fn test_should_reject_invalid_chunks() {
+        store_mock_contract(1, 2);
+        store_mock_contract(2, 2);
+        let chunk = ExportStorage::export_chunk(ExportCollection::Contracts, None);
+
+        // not into a populated collection
+        assert_eq!(
+            ExportStorage::import_chunk(chunk.clone()),
+            Err(DeferredDataError::Import(ImportError::CollectionNotEmpty(
+                ExportCollection::Contracts
+            )))
+        );
+
+        clear();
+        let mut tampered = chunk.clone();
+        tampered.records[0].value = vec![0];
+        assert_eq!(
+            ExportStorage::import_chunk(tampered),
+            Err(DeferredDataError::Import(ImportError::InvalidChecksum))
+        );
+
+        // chunks must be imported in order
+        let next = ExportChunk::new(
+            ExportCollection::Contracts,
+            Some(ExportCursor {
+                key: chunk.records[0].key.clone(),
+                offset: None,
+            }),
+            vec![chunk.records[1].clone()],
+            true,
+        );
+        assert_eq!(
+            ExportStorage::import_chunk(next.clone()),
+            Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                ExportCollection::Contracts
+            )))
+        );
+
+        let first = ExportChunk::new(
+            ExportCollection::Contracts,
+            None,
+            vec![chunk.records[0].clone()],
+            false,
+        );
+        assert!(ExportStorage::import_chunk(first).is_ok());
+        assert!(ExportStorage::import_chunk(next).is_ok());
+        assert_eq!(ContractStorage::len(), 2);
+    }
+
+    #[test]
+    fn test_should_import_record_in_parts_in_order() {
+        let big_document = EXPORT_CHUNK_MAX_SIZE + 1;
+        DocumentStorage::insert_stored_whole(vec![1; big_document]);
+        let chunks = export_all(ExportCollection::DocumentContents);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].records[0].value.len(), EXPORT_CHUNK_MAX_SIZE);
+        assert_eq!(chunks[1].records[0].offset, EXPORT_CHUNK_MAX_SIZE as u64);
+        assert!(chunks[1].last);
+
+        clear();
+        // the second part can't be imported before the first one
+        assert_eq!(
+            ExportStorage::import_chunk(chunks[1].clone()),
+            Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                ExportCollection::DocumentContents
+            )))
+        );
+
+        // the record is stored with its last part
+        ExportStorage::import_chunk(chunks[0].clone()).unwrap();
+        assert_eq!(
+            ExportStorage::digest(ExportCollection::DocumentContents, None).records,
+            0
+        );
+        ExportStorage::import_chunk(chunks[1].clone()).unwrap();
+        assert_eq!(
+            DOCUMENT_CONTENTS.with_borrow(|contents| contents.first_key_value().unwrap().1),
+            vec![1; big_document]
+        );
+        assert_eq!(
+            ExportStorage::import_chunk(chunks[1].clone()),
+            Err(DeferredDataError::Import(ImportError::UnexpectedChunk(
+                ExportCollection::DocumentContents
+            )))
+        );
+    }
+
+    #[test]
+    fn test_should_page_and_verify_digest() {
+        store_mock_contract(1, 2);
+        let digest = digest_all(ExportCollection::Contracts);
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].records, 1);
+        assert!(ExportStorage::verify_digest(&digest[0]));
+
+        // records added after the last page are detected
+        store_mock_contract(2, 2);
+        assert!(!ExportStorage::verify_digest(&digest[0]));
+
+        clear();
+        let empty = ExportStorage::digest(ExportCollection::Contracts, None);
+        assert_eq!(empty.records, 0);
+        assert_eq!(empty.next, None);
+        store_mock_contract(1, 2);
+        assert!(!ExportStorage::verify_digest(&empty));
+    }
+}
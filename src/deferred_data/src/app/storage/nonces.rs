@@ -0,0 +1,81 @@
+use did::deferred::{DeferredDataResult, SignedMessageError};
+
+use super::CONSUMED_NONCES;
+
+/// Storage of the nonces of the signed messages which have been used to access the restricted
+/// documents
+pub struct NonceStorage;
+
+impl NonceStorage {
+    /// Consume the nonce of a signed message, which expires at `expires_at` (nanoseconds).
+    ///
+    /// Fails if the nonce has already been consumed
+    pub fn consume(nonce: &str, expires_at: u64) -> DeferredDataResult<()> {
+        if Self::is_consumed(nonce) {
+            return Err(SignedMessageError::NonceAlreadyUsed(nonce.to_string()).into());
+        }
+        CONSUMED_NONCES.with_borrow_mut(|nonces| {
+            nonces.insert(nonce.to_string(), expires_at);
+        });
+
+        Ok(())
+    }
+
+    /// Returns whether the nonce has been consumed
+    pub fn is_consumed(nonce: &str) -> bool {
+        CONSUMED_NONCES.with_borrow(|nonces| nonces.contains_key(&nonce.to_string()))
+    }
+
+    /// Remove the nonces of the messages expired before `expired_before` (nanoseconds), which
+    /// can't be used anymore.
+    ///
+    /// Returns the amount of removed nonces.
+    pub fn remove_expired(expired_before: u64) -> usize {
+        let expired = CONSUMED_NONCES.with_borrow(|nonces| {
+            nonces
+                .iter()
+                .filter(|(_, expires_at)| *expires_at < expired_before)
+                .map(|(nonce, _)| nonce)
+                .collect::<Vec<_>>()
+        });
+        CONSUMED_NONCES.with_borrow_mut(|nonces| {
+            for nonce in &expired {
+                nonces.remove(nonce);
+            }
+        });
+
+        expired.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::DeferredDataError;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_consume_nonce_once() {
+        assert!(!NonceStorage::is_consumed("Mj5fQ2xW"));
+        assert!(NonceStorage::consume("Mj5fQ2xW", 100).is_ok());
+        assert!(NonceStorage::is_consumed("Mj5fQ2xW"));
+        assert_eq!(
+            NonceStorage::consume("Mj5fQ2xW", 100),
+            Err(DeferredDataError::SignedMessage(
+                SignedMessageError::NonceAlreadyUsed("Mj5fQ2xW".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_remove_expired_nonces() {
+        NonceStorage::consume("expired1", 100).unwrap();
+        NonceStorage::consume("pending1", 200).unwrap();
+
+        assert_eq!(NonceStorage::remove_expired(200), 1);
+        assert!(!NonceStorage::is_consumed("expired1"));
+        assert!(NonceStorage::is_consumed("pending1"));
+    }
+}
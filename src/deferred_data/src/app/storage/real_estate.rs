@@ -3,7 +3,8 @@ use did::deferred::{DeferredDataError, DeferredDataResult, RealEstate, RealEstat
 use did::ID;
 
 use super::{
-    with_real_estate, with_real_estate_mut, with_real_estate_storage_mut, with_real_estates,
+    reencode_batch, with_real_estate, with_real_estate_mut, with_real_estate_storage_mut,
+    with_real_estates,
 };
 
 /// Real estate storage interface
@@ -53,6 +54,28 @@ impl RealEstateStorage {
         })
     }
 
+    /// Count the real estates which are not deleted
+    pub fn count() -> u64 {
+        with_real_estates(|real_estates| {
+            real_estates
+                .iter()
+                .filter(|(_, real_estate)| !real_estate.deleted)
+                .count() as u64
+        })
+    }
+
+    /// Count all the stored real estates, without decoding them
+    pub fn len() -> u64 {
+        with_real_estates(|real_estates| real_estates.len())
+    }
+
+    /// Re-encode up to `count` real estates following `after` with the current schema.
+    ///
+    /// Returns the number of re-encoded real estates and the id of the last one
+    pub fn migrate(after: Option<&ID>, count: usize) -> (u64, Option<ID>) {
+        with_real_estate_storage_mut(|real_estates| reencode_batch(real_estates, after, count))
+    }
+
     /// get real estate by filter
     pub fn get_real_estates_filter(filter: impl Fn(&RealEstate) -> bool) -> Vec<ID> {
         with_real_estates(|contracts| {
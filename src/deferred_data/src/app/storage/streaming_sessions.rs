@@ -0,0 +1,78 @@
+use did::deferred::StreamingSession;
+
+use super::STREAMING_SESSIONS;
+
+/// Storage of the sessions of the restricted documents served over HTTP, keyed by the nonce of the
+/// signed message which opened them
+pub struct StreamingSessionStorage;
+
+impl StreamingSessionStorage {
+    /// Open a streaming session for the signed message with the given nonce
+    pub fn open(nonce: &str, session: StreamingSession) {
+        STREAMING_SESSIONS.with_borrow_mut(|sessions| {
+            sessions.insert(nonce.to_string(), session);
+        });
+    }
+
+    /// Get the streaming session opened by the signed message with the given nonce
+    pub fn get(nonce: &str) -> Option<StreamingSession> {
+        STREAMING_SESSIONS.with_borrow(|sessions| sessions.get(&nonce.to_string()))
+    }
+
+    /// Remove the sessions expired before `expired_before` (nanoseconds).
+    ///
+    /// Returns the amount of removed sessions.
+    pub fn remove_expired(expired_before: u64) -> usize {
+        let expired = STREAMING_SESSIONS.with_borrow(|sessions| {
+            sessions
+                .iter()
+                .filter(|(_, session)| session.expires_at < expired_before)
+                .map(|(nonce, _)| nonce)
+                .collect::<Vec<_>>()
+        });
+        STREAMING_SESSIONS.with_borrow_mut(|sessions| {
+            for nonce in &expired {
+                sessions.remove(nonce);
+            }
+        });
+
+        expired.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn session(id: &str, expires_at: u64) -> StreamingSession {
+        StreamingSession {
+            id: id.to_string(),
+            contract_id: 1u64.into(),
+            document_id: 0,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_should_open_streaming_session() {
+        assert!(StreamingSessionStorage::get("Mj5fQ2xW").is_none());
+        StreamingSessionStorage::open("Mj5fQ2xW", session("a1", 100));
+        assert_eq!(
+            StreamingSessionStorage::get("Mj5fQ2xW"),
+            Some(session("a1", 100))
+        );
+    }
+
+    #[test]
+    fn test_should_remove_expired_streaming_sessions() {
+        StreamingSessionStorage::open("expired1", session("a1", 100));
+        StreamingSessionStorage::open("pending1", session("a2", 200));
+
+        assert_eq!(StreamingSessionStorage::remove_expired(200), 1);
+        assert!(StreamingSessionStorage::get("expired1").is_none());
+        assert!(StreamingSessionStorage::get("pending1").is_some());
+    }
+}
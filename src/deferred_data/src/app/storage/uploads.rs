@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use did::deferred::{
+    AgencyId, DataContractError, DeferredDataError, DeferredDataResult, DocumentUpload,
+    StorageUsage,
+};
+use sha2::{Digest as _, Sha256};
+
+use super::{ContractStorage, NEXT_UPLOAD_ID, UPLOADS, UPLOAD_CHUNKS};
+
+/// Storage of the contract documents being uploaded in chunks
+pub struct UploadStorage;
+
+impl UploadStorage {
+    /// Begin a new upload.
+    ///
+    /// Returns the ID of the upload.
+    pub fn begin(upload: DocumentUpload) -> DeferredDataResult<u64> {
+        let id = Self::next_upload_id()?;
+        UPLOADS.with_borrow_mut(|uploads| {
+            uploads.insert(id, upload);
+        });
+
+        Ok(id)
+    }
+
+    /// Get an upload
+    pub fn get(id: u64) -> DeferredDataResult<DocumentUpload> {
+        UPLOADS.with_borrow(|uploads| {
+            uploads.get(&id).ok_or(DeferredDataError::Contract(
+                DataContractError::UploadNotFound(id),
+            ))
+        })
+    }
+
+    /// Append a chunk to an upload.
+    ///
+    /// Fails if the received data would exceed the declared size of the document
+    pub fn append_chunk(id: u64, data: Vec<u8>) -> DeferredDataResult<()> {
+        let mut upload = Self::get(id)?;
+
+        let received = upload.received + data.len() as u64;
+        if received > upload.document.size {
+            return Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(upload.document.size, received),
+            ));
+        }
+
+        UPLOAD_CHUNKS.with_borrow_mut(|chunks| {
+            chunks.insert((id, upload.chunks), data);
+        });
+        upload.received = received;
+        upload.chunks += 1;
+        UPLOADS.with_borrow_mut(|uploads| {
+            uploads.insert(id, upload);
+        });
+
+        Ok(())
+    }
+
+    /// Compute the SHA-256 (hex) of the data received for an upload, reading a chunk at a time
+    pub fn sha256(id: u64) -> String {
+        let mut hasher = Sha256::new();
+        UPLOAD_CHUNKS.with_borrow(|chunks| {
+            for (_, chunk) in chunks.range((id, 0)..=(id, u64::MAX)) {
+                hasher.update(chunk);
+            }
+        });
+
+        hex::encode(hasher.finalize())
+    }
+
+    /// Get up to the first `len` bytes received for an upload, reading only the chunks
+    /// containing them
+    pub fn prefix(id: u64, len: usize) -> Vec<u8> {
+        let mut prefix = Vec::new();
+        UPLOAD_CHUNKS.with_borrow(|chunks| {
+            for (_, chunk) in chunks.range((id, 0)..=(id, u64::MAX)) {
+                if prefix.len() >= len {
+                    break;
+                }
+                let missing = len - prefix.len();
+                prefix.extend_from_slice(&chunk[..missing.min(chunk.len())]);
+            }
+        });
+
+        prefix
+    }
+
+    /// Get the data received for an upload, in the order the chunks were sent
+    pub fn data(id: u64) -> Vec<u8> {
+        UPLOAD_CHUNKS.with_borrow(|chunks| {
+            chunks
+                .range((id, 0)..=(id, u64::MAX))
+                .flat_map(|(_, chunk)| chunk)
+                .collect()
+        })
+    }
+
+    /// Remove an upload with its chunks
+    pub fn remove(id: u64) {
+        UPLOADS.with_borrow_mut(|uploads| uploads.remove(&id));
+        UPLOAD_CHUNKS.with_borrow_mut(|chunks| {
+            let keys = chunks
+                .range((id, 0)..=(id, u64::MAX))
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>();
+            for key in keys {
+                chunks.remove(&key);
+            }
+        });
+    }
+
+    /// Remove the uploads begun before `started_before` (nanoseconds).
+    ///
+    /// Returns the IDs of the removed uploads.
+    pub fn remove_expired(started_before: u64) -> Vec<u64> {
+        let expired = UPLOADS.with_borrow(|uploads| {
+            uploads
+                .iter()
+                .filter(|(_, upload)| upload.started_at < started_before)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        });
+        for id in &expired {
+            Self::remove(*id);
+        }
+
+        expired
+    }
+
+    /// Count the pending uploads
+    pub fn count() -> u64 {
+        UPLOADS.with_borrow(|uploads| uploads.len())
+    }
+
+    /// Get the storage the pending uploads of the contracts of the agency will use once
+    /// finalized, as declared when they began
+    pub fn pending_usage(agency: AgencyId) -> StorageUsage {
+        let uploads = UPLOADS.with_borrow(|uploads| {
+            uploads
+                .iter()
+                .map(|(_, upload)| (upload.contract_id, upload.document.size))
+                .collect::<Vec<_>>()
+        });
+
+        let mut agencies = HashMap::new();
+        uploads
+            .into_iter()
+            .filter(|(contract_id, _)| {
+                *agencies.entry(contract_id.clone()).or_insert_with(|| {
+                    ContractStorage::get_contract(contract_id).map(|contract| contract.agency)
+                }) == Some(agency)
+            })
+            .fold(StorageUsage::default(), |usage, (_, size)| {
+                usage.saturating_add(StorageUsage::document(size))
+            })
+    }
+
+    /// Get next upload ID and increment it
+    fn next_upload_id() -> DeferredDataResult<u64> {
+        NEXT_UPLOAD_ID.with_borrow_mut(|id| {
+            let next_id = *id.get();
+            id.set(next_id + 1u64)
+                .map_err(|_| DeferredDataError::StorageError)?;
+
+            Ok(next_id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use did::deferred::ContractDocument;
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::*;
+    use crate::app::test_utils::store_mock_contract;
+
+    fn mock_upload(size: u64, started_at: u64) -> DocumentUpload {
+        DocumentUpload {
+            contract_id: 1u64.into(),
+            document: ContractDocument {
+                access_list: vec![],
+                mime_type: "application/pdf".to_string(),
+                name: "deed.pdf".to_string(),
+                size,
+            },
+            sha256: String::default(),
+            uploaded_by: Principal::anonymous(),
+            started_at,
+            received: 0,
+            chunks: 0,
+        }
+    }
+
+    #[test]
+    fn test_should_append_chunks() {
+        let id = UploadStorage::begin(mock_upload(5, 0)).unwrap();
+        let other = UploadStorage::begin(mock_upload(1, 0)).unwrap();
+        assert_ne!(id, other);
+
+        UploadStorage::append_chunk(id, vec![1, 2]).unwrap();
+        UploadStorage::append_chunk(other, vec![9]).unwrap();
+        UploadStorage::append_chunk(id, vec![3, 4]).unwrap();
+        assert_eq!(
+            UploadStorage::append_chunk(id, vec![5, 6]),
+            Err(DeferredDataError::Contract(
+                DataContractError::DocumentSizeMismatch(5, 6)
+            ))
+        );
+        UploadStorage::append_chunk(id, vec![5]).unwrap();
+
+        let upload = UploadStorage::get(id).unwrap();
+        assert_eq!(upload.received, 5);
+        assert_eq!(upload.chunks, 3);
+        assert_eq!(UploadStorage::data(id), vec![1, 2, 3, 4, 5]);
+        assert_eq!(UploadStorage::data(other), vec![9]);
+        assert_eq!(
+            UploadStorage::sha256(id),
+            hex::encode(Sha256::digest([1, 2, 3, 4, 5]))
+        );
+        assert_eq!(UploadStorage::prefix(id, 3), vec![1, 2, 3]);
+        assert_eq!(UploadStorage::prefix(id, 10), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_should_get_pending_usage_of_agency() {
+        store_mock_contract(1, 2);
+        let agency = ContractStorage::get_contract(&1u64.into()).unwrap().agency;
+        UploadStorage::begin(mock_upload(5, 0)).unwrap();
+        UploadStorage::begin(mock_upload(3, 0)).unwrap();
+        let mut other_contract = mock_upload(7, 0);
+        other_contract.contract_id = 2u64.into();
+        UploadStorage::begin(other_contract).unwrap();
+
+        assert_eq!(
+            UploadStorage::pending_usage(agency),
+            StorageUsage {
+                documents: 2,
+                document_bytes: 8,
+            }
+        );
+        assert_eq!(
+            UploadStorage::pending_usage(AgencyId(agency.0 + 1)),
+            StorageUsage::default()
+        );
+    }
+
+    #[test]
+    fn test_should_remove_upload() {
+        let id = UploadStorage::begin(mock_upload(2, 0)).unwrap();
+        UploadStorage::append_chunk(id, vec![1, 2]).unwrap();
+
+        UploadStorage::remove(id);
+        assert_eq!(
+            UploadStorage::get(id),
+            Err(DeferredDataError::Contract(
+                DataContractError::UploadNotFound(id)
+            ))
+        );
+        assert!(UploadStorage::data(id).is_empty());
+        assert_eq!(
+            UploadStorage::append_chunk(id, vec![1]),
+            Err(DeferredDataError::Contract(
+                DataContractError::UploadNotFound(id)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_remove_expired_uploads() {
+        let expired = UploadStorage::begin(mock_upload(1, 100)).unwrap();
+        let pending = UploadStorage::begin(mock_upload(1, 200)).unwrap();
+        UploadStorage::append_chunk(expired, vec![1]).unwrap();
+
+        assert_eq!(UploadStorage::remove_expired(200), vec![expired]);
+        assert!(UploadStorage::get(expired).is_err());
+        assert!(UploadStorage::data(expired).is_empty());
+        assert!(UploadStorage::get(pending).is_ok());
+        assert_eq!(UploadStorage::count(), 1);
+    }
+}
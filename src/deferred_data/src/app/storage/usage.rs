@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::ops::Bound;
+
+use did::deferred::{
+    AgencyId, AgencyQuota, Contract, DataContractError, DeferredDataError, DeferredDataResult,
+    DocumentHistory, StorageUsage,
+};
+use did::{StorableNat, ID};
+
+use super::{
+    with_contracts, DocumentHistoryStorage, UploadStorage, AGENCY_QUOTAS, AGENCY_USAGE,
+    CONTRACT_USAGE,
+};
+
+/// Accounting of the storage used by the documents of the agencies, with their quotas.
+///
+/// The usage is kept for each contract and summed up for its agency. The usage of a contract
+/// stored before the accounting was introduced is computed from its documents the first time the
+/// contract is accounted.
+pub struct UsageStorage;
+
+impl UsageStorage {
+    /// Get the quota of the agency, if any
+    pub fn get_quota(agency: AgencyId) -> Option<AgencyQuota> {
+        AGENCY_QUOTAS.with_borrow(|quotas| quotas.get(&agency))
+    }
+
+    /// Set the quota of the agency; `None` removes it
+    pub fn set_quota(agency: AgencyId, quota: Option<AgencyQuota>) {
+        AGENCY_QUOTAS.with_borrow_mut(|quotas| match quota {
+            Some(quota) => {
+                quotas.insert(agency, quota);
+            }
+            None => {
+                quotas.remove(&agency);
+            }
+        })
+    }
+
+    /// Get the storage used by the documents of the agency
+    pub fn get_agency_usage(agency: AgencyId) -> StorageUsage {
+        AGENCY_USAGE
+            .with_borrow(|usage| usage.get(&agency))
+            .unwrap_or_default()
+    }
+
+    /// Get the storage used by the documents of every agency which has stored any
+    pub fn agencies_usage() -> Vec<(AgencyId, StorageUsage)> {
+        AGENCY_USAGE.with_borrow(|usage| usage.iter().collect())
+    }
+
+    /// Check whether the agency of the contract can store the provided usage on top of its own
+    /// and of the one of its pending uploads, so that uploads begun concurrently can't exceed the
+    /// quota once finalized.
+    ///
+    /// Returns [`DataContractError::AgencyQuotaExceeded`] otherwise
+    pub fn require_within_quota(
+        contract: &Contract,
+        usage: StorageUsage,
+    ) -> DeferredDataResult<()> {
+        let Some(quota) = Self::get_quota(contract.agency) else {
+            return Ok(());
+        };
+        Self::init_contract(contract);
+
+        let used = Self::get_agency_usage(contract.agency)
+            .saturating_add(UploadStorage::pending_usage(contract.agency));
+        if quota.allows(&used.saturating_add(usage)) {
+            Ok(())
+        } else {
+            Err(DeferredDataError::Contract(
+                DataContractError::AgencyQuotaExceeded(contract.agency, quota),
+            ))
+        }
+    }
+
+    /// Account the usage to the contract and to its agency.
+    ///
+    /// `contract` must be the contract before the change, since it may be used to compute the
+    /// usage of the contract if it hasn't been accounted yet
+    pub fn add(contract: &Contract, usage: StorageUsage) {
+        let current = Self::init_contract(contract);
+        Self::set_contract_usage(&contract.id, current.saturating_add(usage));
+        Self::update_agency_usage(contract.agency, |agency| agency.saturating_add(usage));
+    }
+
+    /// Remove the usage from the contract and from its agency.
+    ///
+    /// `contract` must be the contract before the change, as for [`UsageStorage::add`]
+    pub fn remove(contract: &Contract, usage: StorageUsage) {
+        let current = Self::init_contract(contract);
+        Self::set_contract_usage(&contract.id, current.saturating_sub(usage));
+        Self::update_agency_usage(contract.agency, |agency| agency.saturating_sub(usage));
+    }
+
+    /// Move the usage of the contract from its agency to the `to` agency
+    pub fn move_contract(contract: &Contract, to: AgencyId) {
+        let usage = Self::init_contract(contract);
+        Self::update_agency_usage(contract.agency, |agency| agency.saturating_sub(usage));
+        Self::update_agency_usage(to, |agency| agency.saturating_add(usage));
+    }
+
+    /// Get the usage of the versions of a document which haven't been purged, counting once the
+    /// versions with the same data
+    pub fn history_usage(history: &DocumentHistory) -> StorageUsage {
+        let mut data_ids = HashSet::new();
+        history
+            .versions
+            .iter()
+            .filter(|version| !version.purged && data_ids.insert(version.data_id))
+            .fold(StorageUsage::default(), |usage, version| {
+                usage.saturating_add(StorageUsage::document(version.document.size))
+            })
+    }
+
+    /// Account up to `count` contracts following the key `after`, if they haven't been accounted
+    /// yet.
+    ///
+    /// Returns the number of visited contracts and the key of the last one
+    pub fn migrate(after: Option<&ID>, count: usize) -> (u64, Option<ID>) {
+        let start = match after {
+            Some(key) => Bound::Excluded(StorableNat::from(key.clone())),
+            None => Bound::Unbounded,
+        };
+        let batch = with_contracts(|contracts| {
+            contracts
+                .range((start, Bound::Unbounded))
+                .take(count)
+                .map(|(_, contract)| contract)
+                .collect::<Vec<_>>()
+        });
+        for contract in &batch {
+            Self::init_contract(contract);
+        }
+
+        (
+            batch.len() as u64,
+            batch.last().map(|contract| contract.id.clone()),
+        )
+    }
+
+    /// Account the usage of the contract computed from its documents, unless it has already been
+    /// accounted.
+    ///
+    /// Returns the usage of the contract
+    fn init_contract(contract: &Contract) -> StorageUsage {
+        let key = StorableNat::from(contract.id.clone());
+        if let Some(usage) = CONTRACT_USAGE.with_borrow(|usage| usage.get(&key)) {
+            return usage;
+        }
+
+        let usage = contract.documents.iter().fold(
+            StorageUsage::default(),
+            |usage, (document_id, document)| {
+                let document_usage = match DocumentHistoryStorage::get(*document_id) {
+                    Some(history) => Self::history_usage(&history),
+                    None => StorageUsage::document(document.size),
+                };
+                usage.saturating_add(document_usage)
+            },
+        );
+        Self::set_contract_usage(&contract.id, usage);
+        Self::update_agency_usage(contract.agency, |agency| agency.saturating_add(usage));
+
+        usage
+    }
+
+    fn set_contract_usage(contract_id: &ID, usage: StorageUsage) {
+        CONTRACT_USAGE.with_borrow_mut(|contracts| {
+            contracts.insert(contract_id.clone().into(), usage);
+        });
+    }
+
+    fn update_agency_usage<F>(agency: AgencyId, f: F)
+    where
+        F: FnOnce(StorageUsage) -> StorageUsage,
+    {
+        AGENCY_USAGE.with_borrow_mut(|agencies| {
+            let usage = agencies.get(&agency).unwrap_or_default();
+            agencies.insert(agency, f(usage));
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::{ContractDocument, RestrictionLevel};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::with_mock_contract;
+
+    fn document(size: u64) -> ContractDocument {
+        ContractDocument {
+            access_list: vec![RestrictionLevel::Agent],
+            mime_type: "application/pdf".to_string(),
+            name: "contract.pdf".to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_should_account_usage_of_agency() {
+        let contract = with_mock_contract(1, 1, |contract| {
+            contract.agency = AgencyId(1);
+        });
+        UsageStorage::add(&contract, StorageUsage::document(10));
+        UsageStorage::add(&contract, StorageUsage::document(5));
+        assert_eq!(
+            UsageStorage::get_agency_usage(AgencyId(1)),
+            StorageUsage {
+                documents: 2,
+                document_bytes: 15,
+            }
+        );
+
+        UsageStorage::remove(&contract, StorageUsage::document(5));
+        assert_eq!(
+            UsageStorage::get_agency_usage(AgencyId(1)),
+            StorageUsage::document(10)
+        );
+
+        UsageStorage::move_contract(&contract, AgencyId(2));
+        assert_eq!(
+            UsageStorage::get_agency_usage(AgencyId(1)),
+            StorageUsage::default()
+        );
+        assert_eq!(
+            UsageStorage::get_agency_usage(AgencyId(2)),
+            StorageUsage::document(10)
+        );
+    }
+
+    #[test]
+    fn test_should_compute_usage_of_contract_not_accounted() {
+        let contract = with_mock_contract(1, 1, |contract| {
+            contract.agency = AgencyId(1);
+            contract.documents = vec![(1, document(10)), (2, document(20))];
+        });
+        UsageStorage::add(&contract, StorageUsage::document(5));
+        assert_eq!(
+            UsageStorage::get_agency_usage(AgencyId(1)),
+            StorageUsage {
+                documents: 3,
+                document_bytes: 35,
+            }
+        );
+
+        // the contract is accounted once
+        assert_eq!(UsageStorage::migrate(None, 10), (1, Some(ID::from(1u64))));
+        assert_eq!(UsageStorage::get_agency_usage(AgencyId(1)).documents, 3);
+    }
+
+    #[test]
+    fn test_should_check_quota() {
+        let contract = with_mock_contract(1, 1, |contract| {
+            contract.agency = AgencyId(1);
+        });
+        assert!(UsageStorage::require_within_quota(&contract, StorageUsage::document(100)).is_ok());
+
+        let quota = AgencyQuota {
+            max_documents: 10,
+            max_document_bytes: 100,
+        };
+        UsageStorage::set_quota(AgencyId(1), Some(quota));
+        assert_eq!(UsageStorage::get_quota(AgencyId(1)), Some(quota));
+        UsageStorage::add(&contract, StorageUsage::document(60));
+        assert!(UsageStorage::require_within_quota(&contract, StorageUsage::document(40)).is_ok());
+        assert_eq!(
+            UsageStorage::require_within_quota(&contract, StorageUsage::document(41)),
+            Err(DeferredDataError::Contract(
+                DataContractError::AgencyQuotaExceeded(AgencyId(1), quota)
+            ))
+        );
+
+        UsageStorage::set_quota(AgencyId(1), None);
+        assert!(UsageStorage::require_within_quota(&contract, StorageUsage::document(41)).is_ok());
+    }
+}
@@ -1,11 +1,17 @@
+use std::str::FromStr;
+
 use candid::Principal;
 use did::deferred::{
-    Agency, Continent, Contract, GenericValue, RealEstate, RestrictedProperty, RestrictionLevel,
-    Seller,
+    Agency, AgencyId, Continent, Contract, GenericValue, RealEstate, RestrictedProperty,
+    RestrictionLevel, Seller,
 };
 use did::H160;
+use ethers_core::abi::ethereum_types::H520;
+use image::codecs::jpeg::JpegEncoder;
+use image::{ImageBuffer, Rgb};
 
 use super::storage::ContractStorage;
+use crate::utils::canister_id;
 
 pub fn mock_contract(id: u64, installments: u64) -> Contract {
     Contract {
@@ -32,7 +38,7 @@ pub fn mock_contract(id: u64, installments: u64) -> Contract {
             },
         )],
         documents: vec![],
-        agency: mock_agency().owner,
+        agency: mock_agency().id,
         real_estate: 1u64.into(),
         expiration: "2078-01-01".to_string(),
         closed: false,
@@ -41,6 +47,7 @@ pub fn mock_contract(id: u64, installments: u64) -> Contract {
 
 pub fn mock_agency() -> Agency {
     Agency {
+        id: AgencyId(1),
         name: "Dummy Real estate".to_string(),
         address: "Via Delle Botteghe Scure".to_string(),
         city: "Rome".to_string(),
@@ -63,7 +70,7 @@ pub fn mock_agency() -> Agency {
 pub fn mock_real_estate() -> RealEstate {
     RealEstate {
         deleted: false,
-        agency: Principal::management_canister(),
+        agency: mock_agency().id,
         name: "name".to_string(),
         description: "description".to_string(),
         image: Some("image".to_string()),
@@ -116,6 +123,104 @@ where
     contract
 }
 
+/// Address of the private key `0xaaaa...aaaa`, which signs the mock messages
+pub const MOCK_SIGNER: &str = "0x8fd379246834eac74B8419FfdA202CF8051F7A03";
+
+/// A PDF document with the provided content after its header
+pub fn mock_pdf(content: &[u8]) -> Vec<u8> {
+    let mut data = b"%PDF-".to_vec();
+    data.extend_from_slice(content);
+
+    data
+}
+
+/// A plain JPEG image of the provided size
+pub fn mock_jpeg(width: u32, height: u32) -> Vec<u8> {
+    let image = ImageBuffer::from_pixel(width, height, Rgb([200u8, 100, 50]));
+    let mut data = Vec::new();
+    JpegEncoder::new(&mut data).encode_image(&image).unwrap();
+
+    data
+}
+
+/// EXIF metadata (big endian) with the orientation and a GPS IFD with the latitude
+pub fn mock_gps_exif() -> Vec<u8> {
+    let mut tiff = b"MM\0\x2a\0\0\0\x08".to_vec();
+    // IFD0: orientation and GPS IFD pointer
+    tiff.extend_from_slice(&[0, 2]);
+    tiff.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 1, 0, 0]);
+    tiff.extend_from_slice(&[0x88, 0x25, 0, 4, 0, 0, 0, 1, 0, 0, 0, 38]);
+    tiff.extend_from_slice(&[0, 0, 0, 0]);
+    // GPS IFD at 38: latitude ref and latitude (3 rationals at 68)
+    tiff.extend_from_slice(&[0, 2]);
+    tiff.extend_from_slice(&[0, 1, 0, 2, 0, 0, 0, 2, b'N', 0, 0, 0]);
+    tiff.extend_from_slice(&[0, 2, 0, 5, 0, 0, 0, 3, 0, 0, 0, 68]);
+    tiff.extend_from_slice(&[0, 0, 0, 0]);
+    tiff.extend_from_slice(&[0, 0, 0, 45, 0, 0, 0, 1, 0, 0, 0, 30, 0, 0, 0, 1, 0, 0, 0, 0]);
+    tiff.extend_from_slice(&[0, 0, 0, 1]);
+
+    tiff
+}
+
+/// A plain JPEG image of the provided size, with [`mock_gps_exif`] right after the SOI marker
+pub fn mock_jpeg_with_gps(width: u32, height: u32) -> Vec<u8> {
+    let mut app1 = b"Exif\0\0".to_vec();
+    app1.extend(mock_gps_exif());
+    let jpeg = mock_jpeg(width, height);
+
+    let mut data = jpeg[..2].to_vec();
+    data.extend_from_slice(&[0xFF, 0xE1]);
+    data.extend_from_slice(&(app1.len() as u16 + 2).to_be_bytes());
+    data.extend(app1);
+    data.extend_from_slice(&jpeg[2..]);
+
+    data
+}
+
+/// A Sign-In with Ethereum message of [`MOCK_SIGNER`] to access a contract on the canister
+pub fn mock_siwe_message(
+    contract_id: u64,
+    nonce: &str,
+    issued_at: &str,
+    expiration_time: &str,
+) -> String {
+    format!(
+        "{canister}.icp0.io wants you to sign in with your Ethereum account:\n\
+        {MOCK_SIGNER}\n\
+        \n\
+        Access the restricted data of the contract.\n\
+        \n\
+        URI: https://{canister}.icp0.io/contract/{contract_id}\n\
+        Version: 1\n\
+        Chain ID: 1\n\
+        Nonce: {nonce}\n\
+        Issued At: {issued_at}\n\
+        Expiration Time: {expiration_time}",
+        canister = canister_id()
+    )
+}
+
+/// A [`mock_siwe_message`] for the contract 1, which expires in 2100, with its signature
+pub fn mock_signed_message() -> (String, H520) {
+    let message = mock_siwe_message(
+        1,
+        "Mj5fQ2xWkp",
+        "2024-10-01T00:00:00Z",
+        "2100-01-01T00:00:00Z",
+    );
+    let signature = H520::from_str("0x2ffdc9e41dd7fa7680657fff70f0130acca05eafbc2546dc6e24bcf3966f71697ea5557688d1b32d5bcd2b5779bc3aebdfa1595ea79f068dba844fc89955f4d61c").unwrap();
+
+    (message, signature)
+}
+
 pub fn alice() -> Principal {
     Principal::from_text("be2us-64aaa-aaaaa-qaabq-cai").unwrap()
 }
+
+pub fn bob() -> Principal {
+    Principal::from_text("bs5l3-6b3zu-dpqyj-p2x4a-jyg4k-goneb-afof2-y5d62-skt67-3756q-dqe").unwrap()
+}
+
+pub fn charlie() -> Principal {
+    Principal::from_text("vuwfz-pyaaa-aaaal-ai5da-cai").unwrap()
+}
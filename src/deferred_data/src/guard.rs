@@ -0,0 +1,35 @@
+//! # Guard
+//!
+//! Authorization checks run by the canister endpoints before dispatching the call
+
+use did::deferred::{DataPermission, DeferredDataError, DeferredDataResult};
+
+use crate::app::{DeferredData, Inspect};
+use crate::utils::caller;
+
+/// Check whether the caller has been granted the provided permission.
+///
+/// Returns [`DeferredDataError::MissingPermission`] otherwise
+pub fn require_permission(permission: DataPermission) -> DeferredDataResult<()> {
+    let granted = match permission {
+        DataPermission::Owner => Inspect::inspect_is_owner(caller()),
+        DataPermission::Minter => Inspect::inspect_is_minter(caller()),
+    };
+
+    if granted {
+        Ok(())
+    } else {
+        Err(DeferredDataError::MissingPermission(permission))
+    }
+}
+
+/// Check whether the canister accepts writes.
+///
+/// Returns [`DeferredDataError::ReadOnly`] while the read-only mode is enabled
+pub fn require_writable() -> DeferredDataResult<()> {
+    if DeferredData::get_read_only_mode().is_some() {
+        Err(DeferredDataError::ReadOnly)
+    } else {
+        Ok(())
+    }
+}
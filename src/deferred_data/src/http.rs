@@ -1,9 +1,14 @@
 mod contract_filter;
+mod document;
 mod real_estate_filter;
 
 use std::str::FromStr;
 
-use did::{HttpRequest, HttpResponse};
+use did::deferred::{DeferredDataResult, ImageSize};
+use did::{
+    HttpRequest, HttpResponse, StreamingCallbackHttpResponse, StreamingCallbackToken,
+    METRICS_CONTENT_TYPE,
+};
 use ethers_core::abi::ethereum_types::H520;
 use num_traits::cast::ToPrimitive;
 use real_estate_filter::RealEstateFilters;
@@ -11,19 +16,42 @@ use route_recognizer::Router;
 use url::Url;
 
 use self::contract_filter::ContractFilters;
-use crate::app::{ContractStorage, DeferredData, RealEstateStorage, SignedMessage};
+use self::document::DocumentSource;
+use crate::app::{
+    AgencyStorage, ContractStorage, DeferredData, Metrics, RealEstateStorage, SignedMessage,
+};
+use crate::utils::random_id;
 
 const ROUTE_CONTRACTS: &str = "Contracts";
 const ROUTE_CONTRACT: &str = "Contract";
 const ROUTE_DOCUMENT: &str = "Document";
 const ROUTE_REAL_ESTATES: &str = "RealEstates";
 const ROUTE_REAL_ESTATE: &str = "RealEstate";
+const ROUTE_METRICS: &str = "Metrics";
+
+/// Query param of the streaming token with the id of the streaming session of the document
+const SESSION_PARAM: &str = "session";
 
 pub struct HttpApi;
 
 impl HttpApi {
     /// Handles an HTTP request
     pub async fn handle_http_request(req: HttpRequest) -> HttpResponse {
+        Self::handle(req, None)
+    }
+
+    /// Handles an HTTP request upgraded to an update call, which can consume the nonce of the
+    /// signed message and open a streaming session with a random id
+    pub async fn handle_http_request_update(req: HttpRequest) -> HttpResponse {
+        match random_id().await {
+            Ok(session) => Self::handle(req, Some(session)),
+            Err(err) => HttpResponse::internal_error(err),
+        }
+    }
+
+    /// Handles an HTTP request, which is an update call if the id of its streaming `session` is
+    /// set
+    fn handle(req: HttpRequest, session: Option<String>) -> HttpResponse {
         // handle CORS preflight request
         if req.method == "OPTIONS" {
             return HttpResponse::ok("".to_string());
@@ -45,16 +73,7 @@ impl HttpApi {
             return HttpResponse::bad_request(format!("Invalid URL: {url}"));
         };
 
-        let mut router = Router::new();
-        router.add("/contracts", ROUTE_CONTRACTS);
-        router.add("/contract/:id", ROUTE_CONTRACT);
-        router.add(
-            "/contract/:contract_id/document/:document_id",
-            ROUTE_DOCUMENT,
-        );
-        router.add("/real-estate", ROUTE_REAL_ESTATES);
-        router.add("/real-estate/:id", ROUTE_REAL_ESTATE);
-
+        let router = Self::router();
         let Ok(route_match) = router.recognize(url.path()) else {
             return HttpResponse::not_found();
         };
@@ -87,7 +106,12 @@ impl HttpApi {
                     return HttpResponse::bad_request("invalid document ID".to_string());
                 };
 
-                Self::get_contract_document(url, contract_id, document_id)
+                // the nonce of the signed message can only be consumed by an update call
+                if session.is_none() && Self::get_query_param(&url, "message").is_some() {
+                    return HttpResponse::upgrade_response();
+                }
+
+                Self::get_contract_document(&req, url, contract_id, document_id, session)
             }
             ROUTE_REAL_ESTATES => Self::get_real_estates(&url),
             ROUTE_REAL_ESTATE => {
@@ -99,10 +123,67 @@ impl HttpApi {
                 };
                 Self::get_real_estate(id)
             }
+            ROUTE_METRICS => Self::get_metrics(),
             _ => HttpResponse::not_found(),
         }
     }
 
+    /// Get the next chunk of a streamed contract document.
+    ///
+    /// The access to the document is checked again, with the signed message in the URL of the
+    /// token, whose chunk must be expected by the streaming session in the URL
+    pub fn http_request_streaming_callback(
+        token: StreamingCallbackToken,
+    ) -> Result<StreamingCallbackHttpResponse, String> {
+        let url = Url::parse(&token.url).map_err(|_| format!("Invalid URL: {}", token.url))?;
+
+        let router = Self::router();
+        let route_match = router
+            .recognize(url.path())
+            .map_err(|_| format!("no route for {}", url.path()))?;
+        if **route_match.handler() != ROUTE_DOCUMENT {
+            return Err(format!("{} is not a document", url.path()));
+        }
+        let params = route_match.params();
+        let contract_id = params
+            .find("contract_id")
+            .and_then(|id| id.parse::<u64>().ok())
+            .ok_or_else(|| "invalid contract ID".to_string())?;
+        let document_id = params
+            .find("document_id")
+            .and_then(|id| id.parse::<u64>().ok())
+            .ok_or_else(|| "invalid document ID".to_string())?;
+
+        let size = Self::image_size(&url)?;
+        // the signed message is resumed only within the session opened by the first response
+        let signed_message = match Self::get_query_param(&url, SESSION_PARAM) {
+            Some(session) => Self::signed_message(url, Some(session)),
+            None if Self::get_query_param(&url, "message").is_some() => {
+                return Err("missing streaming session".to_string());
+            }
+            None => None,
+        };
+        let document = Self::document_source(contract_id, document_id, size, signed_message)
+            .map_err(|err| err.to_string())?;
+
+        document::streaming_chunk(&document, token).map_err(|err| err.to_string())
+    }
+
+    fn router() -> Router<&'static str> {
+        let mut router = Router::new();
+        router.add("/contracts", ROUTE_CONTRACTS);
+        router.add("/contract/:id", ROUTE_CONTRACT);
+        router.add(
+            "/contract/:contract_id/document/:document_id",
+            ROUTE_DOCUMENT,
+        );
+        router.add("/real-estate", ROUTE_REAL_ESTATES);
+        router.add("/real-estate/:id", ROUTE_REAL_ESTATE);
+        router.add("/metrics", ROUTE_METRICS);
+
+        router
+    }
+
     fn get_contracts(url: &Url) -> HttpResponse {
         let filters = ContractFilters::from(url);
 
@@ -116,29 +197,102 @@ impl HttpApi {
     }
 
     fn get_contract(url: Url, id: u64) -> HttpResponse {
-        let signed_message = Self::signed_message(url);
+        let signed_message = Self::signed_message(url, None);
 
         DeferredData::get_contract(&id.into(), signed_message)
             .map(HttpResponse::ok)
             .unwrap_or_else(HttpResponse::not_found)
     }
 
-    fn get_contract_document(url: Url, contract_id: u64, document_id: u64) -> HttpResponse {
-        let signed_message = Self::signed_message(url);
+    /// Get a contract document.
+    ///
+    /// If the request is an update call, the session of a restricted document is opened with the
+    /// random id `session`, unless the signed message has already opened one, and its id is set
+    /// in the URL of the streaming token
+    fn get_contract_document(
+        req: &HttpRequest,
+        mut url: Url,
+        contract_id: u64,
+        document_id: u64,
+        session: Option<String>,
+    ) -> HttpResponse {
+        let size = match Self::image_size(&url) {
+            Ok(size) => size,
+            Err(err) => return HttpResponse::bad_request(err),
+        };
 
-        DeferredData::get_contract_document(contract_id.into(), document_id, signed_message)
-            .map(HttpResponse::ok)
-            .unwrap_or_else(|_| HttpResponse::not_found())
+        let signed_message = Self::signed_message(url.clone(), None);
+        let Ok(document) = Self::document_source(contract_id, document_id, size, signed_message)
+        else {
+            return HttpResponse::not_found();
+        };
+
+        if let Some(mut session) = session {
+            if let Some(signed_message) = Self::signed_message(url.clone(), None) {
+                session = match DeferredData::open_streaming_session(
+                    contract_id.into(),
+                    document_id,
+                    signed_message,
+                    session,
+                ) {
+                    Ok(session) => session,
+                    Err(err) => return HttpResponse::internal_error(err.to_string()),
+                };
+            }
+            url.query_pairs_mut().append_pair(SESSION_PARAM, &session);
+        }
+
+        document::document_response(req, &url, document)
+    }
+
+    /// Get a contract document, or its thumbnail or preview if `size` is set, checking the access
+    /// to it with the signed message.
+    ///
+    /// The data of the document is read only in the ranges which are sent
+    fn document_source(
+        contract_id: u64,
+        document_id: u64,
+        size: Option<ImageSize>,
+        signed_message: Option<SignedMessage>,
+    ) -> DeferredDataResult<DocumentSource> {
+        match size {
+            Some(size) => DeferredData::get_contract_document_preview(
+                contract_id.into(),
+                document_id,
+                size,
+                signed_message,
+            )
+            .map(DocumentSource::Loaded),
+            None => DeferredData::get_contract_document_metadata(
+                contract_id.into(),
+                document_id,
+                signed_message,
+            )
+            .map(|document| DocumentSource::Stored {
+                contract_id: contract_id.into(),
+                document_id,
+                document,
+            }),
+        }
+    }
+
+    /// Get the image size from the `size` query param, if any
+    fn image_size(url: &Url) -> Result<Option<ImageSize>, String> {
+        Self::get_query_param(url, "size")
+            .map(|size| ImageSize::from_str(&size))
+            .transpose()
     }
 
     fn get_real_estates(url: &Url) -> HttpResponse {
         let filters = RealEstateFilters::from(url);
 
-        let ids: Vec<u64> =
-            RealEstateStorage::get_real_estates_filter(|real_estate| filters.check(real_estate))
-                .into_iter()
-                .map(|x| x.0.to_u64().expect("Failed to convert ID"))
-                .collect::<Vec<u64>>();
+        // real estates of suspended agencies are not listed
+        let ids: Vec<u64> = RealEstateStorage::get_real_estates_filter(|real_estate| {
+            !AgencyStorage::is_suspended(real_estate.agency) && filters.check(real_estate)
+        })
+        .into_iter()
+        .map(|x| x.0.to_u64().expect("Failed to convert ID"))
+        .collect::<Vec<u64>>();
 
         HttpResponse::ok(ids)
     }
@@ -149,13 +303,21 @@ impl HttpApi {
             .unwrap_or_else(|_| HttpResponse::not_found())
     }
 
+    fn get_metrics() -> HttpResponse {
+        HttpResponse::ok_raw(Metrics::encode().into_bytes(), METRICS_CONTENT_TYPE)
+    }
+
     /// Get signed message from URL
-    fn signed_message(url: Url) -> Option<SignedMessage> {
+    fn signed_message(url: Url, session: Option<String>) -> Option<SignedMessage> {
         let message = Self::get_query_param(&url, "message")?;
         let signature =
             Self::get_query_param(&url, "signature").and_then(|s| H520::from_str(&s).ok())?;
 
-        Some(SignedMessage { message, signature })
+        Some(SignedMessage {
+            message,
+            signature,
+            session,
+        })
     }
 
     fn get_query_param(url: &Url, key: &str) -> Option<String> {
@@ -176,12 +338,15 @@ mod test {
     use std::collections::HashMap;
 
     use candid::{Nat, Principal};
-    use did::deferred::{ContractDocument, GenericValue, RestrictionLevel, Seller};
+    use did::deferred::{AgencyId, ContractDocument, GenericValue, RestrictionLevel, Seller};
     use did::H160;
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::app::test_utils::{mock_real_estate, store_mock_contract, store_mock_contract_with};
+    use crate::app::test_utils::{
+        mock_jpeg, mock_real_estate, mock_signed_message, store_mock_contract,
+        store_mock_contract_with, MOCK_SIGNER,
+    };
 
     #[tokio::test]
     async fn test_should_get_contract() {
@@ -234,6 +399,7 @@ mod test {
                 size: 4,
             },
             vec![0x01, 0x02, 0x03, 0x04],
+            Principal::anonymous(),
         )
         .expect("Failed to upload document");
 
@@ -251,12 +417,280 @@ mod test {
 
         let res = HttpApi::handle_http_request(req).await;
         assert_eq!(res.status_code, 200);
+        assert_eq!(
+            res.headers.get("content-type").map(|value| value.as_ref()),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            res.headers
+                .get("content-disposition")
+                .map(|value| value.as_ref()),
+            Some("inline; filename=\"document\"; filename*=UTF-8''document")
+        );
+        assert_eq!(res.body.to_vec(), vec![0x01, 0x02, 0x03, 0x04]);
+        assert!(res.streaming_strategy.is_none());
+    }
 
-        let document: did::deferred::ContractDocumentData =
-            serde_json::from_slice(&res.body).unwrap();
+    #[tokio::test]
+    async fn test_should_serve_range_requests_of_restricted_contract_document_within_its_session() {
+        let buyer = H160::from_hex_str(MOCK_SIGNER).unwrap();
+        store_mock_contract_with(1u64, 100u64, |contract| contract.buyers = vec![buyer]);
+        let document_id = ContractStorage::upload_contract_document(
+            &Nat::from(1u64),
+            ContractDocument {
+                access_list: vec![RestrictionLevel::Buyer],
+                mime_type: "application/pdf".to_string(),
+                name: "document".to_string(),
+                size: 4,
+            },
+            vec![0x01, 0x02, 0x03, 0x04],
+            Principal::anonymous(),
+        )
+        .expect("Failed to upload document");
 
-        assert_eq!(document.mime_type, "application/pdf".to_string());
-        assert_eq!(document.data, vec![0x01, 0x02, 0x03, 0x04]);
+        let (message, signature) = mock_signed_message();
+        let url = Url::parse_with_params(
+            &format!("http://localhost/contract/1/document/{document_id}"),
+            [
+                ("message", message),
+                ("signature", format!("{signature:#x}")),
+            ],
+        )
+        .unwrap();
+
+        let req = HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: url.to_string(),
+            headers: HashMap::default(),
+            body: Default::default(),
+        };
+
+        // the query call is upgraded to an update call, which consumes the nonce
+        let res = HttpApi::handle_http_request(req.clone()).await;
+        assert_eq!(res.upgrade, Some(true));
+        assert!(res.body.is_empty());
+
+        let res = HttpApi::handle_http_request_update(req.clone()).await;
+        assert_eq!(res.status_code, 200);
+        assert_eq!(res.body.to_vec(), vec![0x01, 0x02, 0x03, 0x04]);
+
+        // every request of the document with the message, such as the `Range` requests of a
+        // viewer, is served within the session opened by the first one, while the message is valid
+        for (range, body) in [
+            ("bytes=2-3", vec![0x03, 0x04]),
+            ("bytes=0-1", vec![0x01, 0x02]),
+            ("bytes=2-3", vec![0x03, 0x04]),
+        ] {
+            let mut req = req.clone();
+            req.headers
+                .insert(Cow::from("Range"), Cow::from(range.to_string()));
+            let res = HttpApi::handle_http_request_update(req).await;
+            assert_eq!(res.status_code, 206);
+            assert_eq!(res.body.to_vec(), body);
+        }
+        let res = HttpApi::handle_http_request_update(req).await;
+        assert_eq!(res.status_code, 200);
+
+        // the message can't be used for another restricted document
+        let other_id = ContractStorage::upload_contract_document(
+            &Nat::from(1u64),
+            ContractDocument {
+                access_list: vec![RestrictionLevel::Buyer],
+                mime_type: "application/pdf".to_string(),
+                name: "other".to_string(),
+                size: 4,
+            },
+            vec![0x05, 0x06, 0x07, 0x08],
+            Principal::anonymous(),
+        )
+        .expect("Failed to upload document");
+        let mut other_url = url.clone();
+        other_url.set_path(&format!("/contract/1/document/{other_id}"));
+        let res = HttpApi::handle_http_request_update(HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: other_url.to_string(),
+            headers: HashMap::default(),
+            body: Default::default(),
+        })
+        .await;
+        assert_eq!(res.status_code, 404);
+    }
+
+    #[tokio::test]
+    async fn test_should_stream_restricted_contract_document_only_within_its_session() {
+        let buyer = H160::from_hex_str(MOCK_SIGNER).unwrap();
+        store_mock_contract_with(1u64, 100u64, |contract| contract.buyers = vec![buyer]);
+        let size = document::STREAMING_CHUNK_SIZE * 2 + 10;
+        let data = (0..size).map(|i| i as u8).collect::<Vec<_>>();
+        let document_id = ContractStorage::upload_contract_document(
+            &Nat::from(1u64),
+            ContractDocument {
+                access_list: vec![RestrictionLevel::Buyer],
+                mime_type: "video/mp4".to_string(),
+                name: "tour.mp4".to_string(),
+                size,
+            },
+            data.clone(),
+            Principal::anonymous(),
+        )
+        .expect("Failed to upload document");
+
+        let (message, signature) = mock_signed_message();
+        let url = Url::parse_with_params(
+            &format!("http://localhost/contract/1/document/{document_id}"),
+            [
+                ("message", message),
+                ("signature", format!("{signature:#x}")),
+            ],
+        )
+        .unwrap();
+        let req = HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: url.to_string(),
+            headers: HashMap::default(),
+            body: Default::default(),
+        };
+
+        let res = HttpApi::handle_http_request_update(req.clone()).await;
+        assert_eq!(res.status_code, 200);
+        let Some(did::StreamingStrategy::Callback { token, .. }) = res.streaming_strategy else {
+            panic!("expected streaming strategy");
+        };
+        let session_url = Url::parse(&token.url).unwrap();
+        assert!(HttpApi::get_query_param(&session_url, SESSION_PARAM).is_some());
+
+        // a new request of the document is streamed within the same session
+        let res_again = HttpApi::handle_http_request_update(req).await;
+        assert_eq!(res_again.status_code, 200);
+        let Some(did::StreamingStrategy::Callback {
+            token: token_again, ..
+        }) = res_again.streaming_strategy
+        else {
+            panic!("expected streaming strategy");
+        };
+        assert_eq!(token_again.url, token.url);
+
+        // the token must carry the session of the document
+        assert!(
+            HttpApi::http_request_streaming_callback(StreamingCallbackToken {
+                url: url.to_string(),
+                ..token.clone()
+            })
+            .is_err()
+        );
+        let mut other_session = url.clone();
+        other_session
+            .query_pairs_mut()
+            .append_pair(SESSION_PARAM, &"00".repeat(32));
+        assert!(
+            HttpApi::http_request_streaming_callback(StreamingCallbackToken {
+                url: other_session.to_string(),
+                ..token.clone()
+            })
+            .is_err()
+        );
+
+        // the rest of the document is streamed within the session
+        let mut body = res.body.to_vec();
+        let mut next = Some(token);
+        while let Some(token) = next {
+            let chunk = HttpApi::http_request_streaming_callback(token).unwrap();
+            body.extend_from_slice(&chunk.body);
+            next = chunk.token;
+        }
+        assert_eq!(body, data);
+    }
+
+    #[tokio::test]
+    async fn test_should_get_contract_document_preview() {
+        store_mock_contract(1u64, 100u64);
+        let image = mock_jpeg(2048, 1024);
+        let document_id = ContractStorage::upload_contract_document(
+            &Nat::from(1u64),
+            ContractDocument {
+                access_list: vec![RestrictionLevel::Public],
+                mime_type: "image/jpeg".to_string(),
+                name: "house.jpg".to_string(),
+                size: image.len() as u64,
+            },
+            image,
+            Principal::anonymous(),
+        )
+        .expect("Failed to upload document");
+
+        let request = |size: &str| HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: format!("/contract/1/document/{document_id}?size={size}"),
+            headers: HashMap::default(),
+            body: Default::default(),
+        };
+
+        let res = HttpApi::handle_http_request(request("thumb")).await;
+        assert_eq!(res.status_code, 200);
+        assert_eq!(
+            res.headers.get("content-type").map(|value| value.as_ref()),
+            Some("image/jpeg")
+        );
+        let thumbnail = image::load_from_memory(&res.body).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (256, 128));
+
+        let res = HttpApi::handle_http_request(request("preview")).await;
+        assert_eq!(res.status_code, 200);
+        assert_eq!(image::load_from_memory(&res.body).unwrap().width(), 1024);
+
+        let res = HttpApi::handle_http_request(request("full")).await;
+        assert_eq!(res.status_code, 400);
+    }
+
+    #[tokio::test]
+    async fn test_should_stream_contract_document() {
+        store_mock_contract(1u64, 100u64);
+        let size = document::STREAMING_CHUNK_SIZE + 10;
+        let data = (0..size).map(|i| i as u8).collect::<Vec<_>>();
+        let document_id = ContractStorage::upload_contract_document(
+            &Nat::from(1u64),
+            ContractDocument {
+                access_list: vec![RestrictionLevel::Public],
+                mime_type: "video/mp4".to_string(),
+                name: "tour.mp4".to_string(),
+                size,
+            },
+            data.clone(),
+            Principal::anonymous(),
+        )
+        .expect("Failed to upload document");
+
+        let req = HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: format!("/contract/1/document/{document_id}"),
+            headers: HashMap::from([(Cow::from("range"), Cow::from("bytes=4-"))]),
+            body: Default::default(),
+        };
+
+        let res = HttpApi::handle_http_request(req).await;
+        assert_eq!(res.status_code, 206);
+        assert_eq!(
+            res.headers.get("content-range").map(|value| value.as_ref()),
+            Some(format!("bytes 4-{}/{size}", size - 1).as_str())
+        );
+        let Some(did::StreamingStrategy::Callback { token, .. }) = res.streaming_strategy else {
+            panic!("expected streaming strategy");
+        };
+
+        let chunk = HttpApi::http_request_streaming_callback(token).unwrap();
+        assert!(chunk.token.is_none());
+        let body = [res.body.to_vec(), chunk.body.to_vec()].concat();
+        assert_eq!(body, data[4..]);
+
+        // tokens of other routes are rejected
+        assert!(
+            HttpApi::http_request_streaming_callback(StreamingCallbackToken {
+                url: "http://localhost/contract/1".to_string(),
+                offset: 0,
+                end: 1,
+            })
+            .is_err()
+        );
     }
 
     #[tokio::test]
@@ -291,8 +725,7 @@ mod test {
                 address: H160::from_hex_str("0x253553366da8546fc250f225fe3d25d0c782303b").unwrap(),
                 quota: 100,
             }];
-            contract.agency = Principal::from_text("v5vof-zqaaa-aaaal-ai5cq-cai")
-                .expect("Failed to create principal");
+            contract.agency = AgencyId(1);
 
             // insert properties
             contract.properties = vec![
@@ -432,4 +865,54 @@ mod test {
 
         assert_eq!(real_estate, real_estate_from_storage);
     }
+
+    #[tokio::test]
+    async fn test_should_not_list_real_estates_of_suspended_agency() {
+        let real_estate = mock_real_estate();
+        let agency = real_estate.agency;
+        RealEstateStorage::insert(real_estate).unwrap();
+
+        let req = HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: "/real-estate".to_string(),
+            headers: HashMap::default(),
+            body: Default::default(),
+        };
+
+        let res = HttpApi::handle_http_request(req.clone()).await;
+        assert_eq!(res.status_code, 200);
+        let real_estates: Vec<Nat> = serde_json::from_slice(&res.body).unwrap();
+        assert_eq!(real_estates.len(), 1);
+
+        AgencyStorage::set_status(agency, did::deferred::AgencyStatus::Suspended);
+        let res = HttpApi::handle_http_request(req).await;
+        assert_eq!(res.status_code, 200);
+        let real_estates: Vec<Nat> = serde_json::from_slice(&res.body).unwrap();
+        assert!(real_estates.is_empty());
+    }
+    #[tokio::test]
+    async fn test_should_get_metrics() {
+        store_mock_contract(1, 2);
+        RealEstateStorage::insert(mock_real_estate()).unwrap();
+
+        let req = HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: "/metrics".to_string(),
+            headers: HashMap::default(),
+            body: Default::default(),
+        };
+
+        let res = HttpApi::handle_http_request(req).await;
+        assert_eq!(res.status_code, 200);
+        assert_eq!(
+            res.headers.get("content-type").map(|value| value.as_ref()),
+            Some(METRICS_CONTENT_TYPE)
+        );
+
+        let metrics = String::from_utf8(res.body.to_vec()).unwrap();
+        assert!(metrics.contains("deferred_data_cycles_balance 30000000000\n"));
+        assert!(metrics.contains("deferred_data_contracts{status=\"open\"} 1\n"));
+        assert!(metrics.contains("deferred_data_real_estates 1\n"));
+        assert!(metrics.contains("# TYPE deferred_data_documents gauge\n"));
+    }
 }
@@ -1,5 +1,4 @@
-use candid::Principal;
-use did::deferred::Contract;
+use did::deferred::{AgencyId, Contract};
 use did::H160;
 use url::Url;
 
@@ -50,7 +49,7 @@ enum ContractFilter {
     /// Buyer
     Buyer(H160),
     /// Agent
-    Agent(Principal),
+    Agent(AgencyId),
     /// Min price
     MinPrice(u64),
     /// Max price
@@ -176,7 +175,7 @@ impl From<&Url> for ContractFilters {
         for (name, value) in url.query_pairs() {
             match name.as_ref() {
                 FILTER_AGENT => {
-                    if let Ok(agent) = Principal::from_text(value) {
+                    if let Ok(agent) = value.parse() {
                         filters.push(ContractFilter::Agent(agent));
                     }
                 }
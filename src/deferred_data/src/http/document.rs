@@ -0,0 +1,391 @@
+//! Raw responses of the contract documents, with support of the `Range` header and streaming of
+//! the bodies which don't fit in a single response.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use did::deferred::{ContractDocument, ContractDocumentData, DeferredDataResult};
+use did::{
+    HttpRequest, HttpResponse, StreamingCallback, StreamingCallbackHttpResponse,
+    StreamingCallbackToken, StreamingStrategy, ID,
+};
+use serde_bytes::ByteBuf;
+use url::Url;
+
+use crate::app::DeferredData;
+use crate::utils::canister_id;
+
+const HTTP_OK: u16 = 200;
+const HTTP_PARTIAL_CONTENT: u16 = 206;
+const HTTP_RANGE_NOT_SATISFIABLE: u16 = 416;
+
+/// Maximum size of the body of a single response or streamed chunk
+pub const STREAMING_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Name of the query called by the HTTP gateway to get the next chunk of a document
+const STREAMING_CALLBACK: &str = "http_request_streaming_callback";
+
+/// A document served over HTTP
+pub enum DocumentSource {
+    /// A document whose data has been loaded, such as the preview of an image
+    Loaded(ContractDocumentData),
+    /// The current version of a contract document the caller can access, whose data is read from
+    /// the storage only in the ranges which are sent
+    Stored {
+        contract_id: ID,
+        document_id: u64,
+        document: ContractDocument,
+    },
+}
+
+impl DocumentSource {
+    fn mime_type(&self) -> &str {
+        match self {
+            Self::Loaded(data) => &data.mime_type,
+            Self::Stored { document, .. } => &document.mime_type,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::Loaded(data) => &data.name,
+            Self::Stored { document, .. } => &document.name,
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            Self::Loaded(data) => data.data.len() as u64,
+            Self::Stored { document, .. } => document.size,
+        }
+    }
+
+    /// Read the bytes of the document in the range `[start, end)`
+    fn read(&self, start: u64, end: u64) -> DeferredDataResult<Vec<u8>> {
+        match self {
+            Self::Loaded(data) => Ok(data.data[start as usize..end as usize].to_vec()),
+            Self::Stored {
+                contract_id,
+                document_id,
+                ..
+            } => DeferredData::read_contract_document(contract_id, *document_id, start, end),
+        }
+    }
+}
+
+/// Build the response with the raw bytes of the document, or with the requested range of them.
+///
+/// If the body exceeds [`STREAMING_CHUNK_SIZE`], only the first chunk is read and sent, and the
+/// rest is streamed through [`STREAMING_CALLBACK`].
+pub fn document_response(req: &HttpRequest, url: &Url, document: DocumentSource) -> HttpResponse {
+    let size = document.size();
+    let mut headers = HashMap::from([
+        (
+            "content-type".into(),
+            Cow::Owned(document.mime_type().to_string()),
+        ),
+        (
+            "content-disposition".into(),
+            Cow::Owned(content_disposition(document.name())),
+        ),
+        ("accept-ranges".into(), "bytes".into()),
+        // the browsers must not guess a type other than the validated one
+        ("x-content-type-options".into(), "nosniff".into()),
+    ]);
+
+    let range = req
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("range"))
+        .map(|(_, value)| value.as_ref());
+    let (status_code, start, end) = match range.map(|range| parse_range(range, size)) {
+        None | Some(Ok(None)) => (HTTP_OK, 0, size),
+        Some(Ok(Some((start, end)))) => {
+            headers.insert(
+                "content-range".into(),
+                Cow::Owned(format!("bytes {start}-{}/{size}", end - 1)),
+            );
+            (HTTP_PARTIAL_CONTENT, start, end)
+        }
+        Some(Err(())) => {
+            headers.insert(
+                "content-range".into(),
+                Cow::Owned(format!("bytes */{size}")),
+            );
+            return HttpResponse::new(
+                HTTP_RANGE_NOT_SATISFIABLE,
+                headers,
+                ByteBuf::default(),
+                None,
+            );
+        }
+    };
+
+    let chunk_end = end.min(start + STREAMING_CHUNK_SIZE);
+    let Ok(body) = document.read(start, chunk_end) else {
+        return HttpResponse::not_found();
+    };
+    let mut response = HttpResponse::new(status_code, headers, ByteBuf::from(body), None);
+    if chunk_end < end {
+        response.streaming_strategy = Some(StreamingStrategy::Callback {
+            callback: StreamingCallback::new(canister_id(), STREAMING_CALLBACK.to_string()),
+            token: StreamingCallbackToken {
+                url: url.to_string(),
+                offset: chunk_end,
+                end,
+            },
+        });
+    }
+
+    response
+}
+
+/// Get the chunk of the document requested by the streaming token, with the token of the next
+/// chunk if any.
+///
+/// Only the bytes of the chunk are read
+pub fn streaming_chunk(
+    document: &DocumentSource,
+    token: StreamingCallbackToken,
+) -> DeferredDataResult<StreamingCallbackHttpResponse> {
+    let end = token.end.min(document.size());
+    let start = token.offset.min(end);
+    let chunk_end = end.min(start + STREAMING_CHUNK_SIZE);
+
+    Ok(StreamingCallbackHttpResponse {
+        body: ByteBuf::from(document.read(start, chunk_end)?),
+        token: (chunk_end < end).then(|| StreamingCallbackToken {
+            offset: chunk_end,
+            ..token
+        }),
+    })
+}
+
+/// Parse the value of a `Range` header for a body of `size` bytes.
+///
+/// Returns the range as `(start, end)`, with `end` exclusive, or `None` if the header should be
+/// ignored and the whole body sent, as for multiple ranges or units other than bytes.
+/// Fails if the range can't be satisfied
+fn parse_range(range: &str, size: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(range) = range.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if range.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = range.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        // suffix range: the last bytes of the body
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().map_err(|_| ())?;
+            if suffix == 0 {
+                return Err(());
+            }
+            (size.saturating_sub(suffix), size)
+        }
+        (start, "") => (start.parse::<u64>().map_err(|_| ())?, size),
+        (start, end) => {
+            let start = start.parse::<u64>().map_err(|_| ())?;
+            let end = end.parse::<u64>().map_err(|_| ())?;
+            if end < start {
+                return Err(());
+            }
+            (start, size.min(end + 1))
+        }
+    };
+
+    if start >= size {
+        return Err(());
+    }
+
+    Ok(Some((start, end)))
+}
+
+/// Build the `Content-Disposition` header of a document, with an ASCII fallback of the file name
+/// and the file name percent-encoded as UTF-8
+fn content_disposition(name: &str) -> String {
+    let fallback = name
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '_',
+        })
+        .collect::<String>();
+    let encoded = name
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'!'
+            | b'#'
+            | b'$'
+            | b'&'
+            | b'+'
+            | b'-'
+            | b'.'
+            | b'^'
+            | b'_'
+            | b'`'
+            | b'|'
+            | b'~' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect::<String>();
+
+    format!("inline; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn request(range: Option<&str>) -> HttpRequest {
+        let mut headers = HashMap::default();
+        if let Some(range) = range {
+            headers.insert(Cow::from("Range"), Cow::from(range.to_string()));
+        }
+
+        HttpRequest {
+            method: Cow::from("GET"),
+            url: "/contract/1/document/0".to_string(),
+            headers,
+            body: Default::default(),
+        }
+    }
+
+    fn document(size: usize) -> DocumentSource {
+        DocumentSource::Loaded(ContractDocumentData {
+            data: (0..size).map(|i| i as u8).collect(),
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+        })
+    }
+
+    fn data(document: &DocumentSource) -> Vec<u8> {
+        document.read(0, document.size()).unwrap()
+    }
+
+    fn url() -> Url {
+        Url::parse("http://localhost/contract/1/document/0").unwrap()
+    }
+
+    #[test]
+    fn test_should_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok(Some((0, 100))));
+        assert_eq!(parse_range("bytes=500-", 1000), Ok(Some((500, 1000))));
+        assert_eq!(parse_range("bytes=-100", 1000), Ok(Some((900, 1000))));
+        assert_eq!(parse_range("bytes=-2000", 1000), Ok(Some((0, 1000))));
+        assert_eq!(parse_range("bytes=900-2000", 1000), Ok(Some((900, 1000))));
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), Ok(None));
+        assert_eq!(parse_range("items=0-1", 1000), Ok(None));
+        assert_eq!(parse_range("bytes=1000-", 1000), Err(()));
+        assert_eq!(parse_range("bytes=10-5", 1000), Err(()));
+        assert_eq!(parse_range("bytes=-0", 1000), Err(()));
+        assert_eq!(parse_range("bytes=a-b", 1000), Err(()));
+    }
+
+    #[test]
+    fn test_should_build_content_disposition() {
+        assert_eq!(
+            content_disposition("deed.pdf"),
+            "inline; filename=\"deed.pdf\"; filename*=UTF-8''deed.pdf"
+        );
+        assert_eq!(
+            content_disposition("atto \"rogito\".pdf"),
+            "inline; filename=\"atto _rogito_.pdf\"; filename*=UTF-8''atto%20%22rogito%22.pdf"
+        );
+        assert_eq!(
+            content_disposition("città.pdf"),
+            "inline; filename=\"citt_.pdf\"; filename*=UTF-8''citt%C3%A0.pdf"
+        );
+    }
+
+    #[test]
+    fn test_should_respond_with_raw_document() {
+        let response = document_response(&request(None), &url(), document(10));
+
+        assert_eq!(response.status_code, HTTP_OK);
+        assert_eq!(response.body.to_vec(), data(&document(10)));
+        assert_eq!(
+            response
+                .headers
+                .get("content-type")
+                .map(|value| value.as_ref()),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            response
+                .headers
+                .get("accept-ranges")
+                .map(|value| value.as_ref()),
+            Some("bytes")
+        );
+        assert_eq!(
+            response
+                .headers
+                .get("x-content-type-options")
+                .map(|value| value.as_ref()),
+            Some("nosniff")
+        );
+        assert!(response.streaming_strategy.is_none());
+    }
+
+    #[test]
+    fn test_should_respond_with_range() {
+        let response = document_response(&request(Some("bytes=2-4")), &url(), document(10));
+
+        assert_eq!(response.status_code, HTTP_PARTIAL_CONTENT);
+        assert_eq!(response.body.to_vec(), vec![2, 3, 4]);
+        assert_eq!(
+            response
+                .headers
+                .get("content-range")
+                .map(|value| value.as_ref()),
+            Some("bytes 2-4/10")
+        );
+
+        let response = document_response(&request(Some("bytes=10-")), &url(), document(10));
+        assert_eq!(response.status_code, HTTP_RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            response
+                .headers
+                .get("content-range")
+                .map(|value| value.as_ref()),
+            Some("bytes */10")
+        );
+    }
+
+    #[test]
+    fn test_should_stream_large_document() {
+        let size = STREAMING_CHUNK_SIZE as usize * 2 + 10;
+        let response = document_response(&request(Some("bytes=5-")), &url(), document(size));
+
+        assert_eq!(response.status_code, HTTP_PARTIAL_CONTENT);
+        assert_eq!(response.body.len(), STREAMING_CHUNK_SIZE as usize);
+        let Some(StreamingStrategy::Callback { callback, token }) = response.streaming_strategy
+        else {
+            panic!("expected streaming strategy");
+        };
+        assert_eq!(callback.0.method, STREAMING_CALLBACK);
+        assert_eq!(token.offset, STREAMING_CHUNK_SIZE + 5);
+        assert_eq!(token.end, size as u64);
+
+        let document = document(size);
+        let mut body = response.body.to_vec();
+        let mut token = Some(token);
+        while let Some(next) = token {
+            let chunk = streaming_chunk(&document, next).unwrap();
+            body.extend_from_slice(&chunk.body);
+            token = chunk.token;
+        }
+        assert_eq!(body, data(&document)[5..]);
+    }
+}
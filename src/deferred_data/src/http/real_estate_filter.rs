@@ -1,5 +1,4 @@
-use candid::Principal;
-use did::deferred::{Continent, RealEstate};
+use did::deferred::{AgencyId, Continent, RealEstate};
 use url::Url;
 
 const FILTER_AGENT: &str = "agent";
@@ -52,7 +51,7 @@ enum RealEstateFilter {
     Parking,
     EnergyClass(String),
     /// Check if the agent is...
-    Agent(Principal),
+    Agent(AgencyId),
     /// Position
     Position {
         latitude: f64,
@@ -170,7 +169,7 @@ impl From<&Url> for RealEstateFilters {
         for (name, value) in url.query_pairs() {
             match name.as_ref() {
                 FILTER_AGENT => {
-                    if let Ok(agent) = Principal::from_text(value) {
+                    if let Ok(agent) = value.parse() {
                         filters.push(RealEstateFilter::Agent(agent));
                     }
                 }
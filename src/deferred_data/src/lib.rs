@@ -1,15 +1,20 @@
 use candid::{candid_method, Nat, Principal};
 use did::deferred::{
-    Contract, ContractDocument, ContractDocumentData, DeferredDataInitData, DeferredDataResult,
-    GenericValue, RealEstate, RestrictedProperty,
+    audit_list, audit_text, AgencyId, AgencyQuota, AgencyReport, AgencyStatus, AgencyTransfer,
+    AgencyTransferAuditEntry, AgencyUsage, AuditEntry, AuditFilter, AuditIntegrity, AuditSummary,
+    CollectionDigest, Contract, ContractDocument, ContractDocumentData, DataPermission,
+    DeferredDataInitData, DeferredDataResult, DocumentHistory, DocumentLeaf, DocumentProof,
+    DocumentsAnchor, ExportChunk, ExportCollection, ExportCursor, GenericValue, MigrationStatus,
+    ReadOnlyMode, RealEstate, RestrictedProperty,
 };
-use did::{HttpRequest, HttpResponse, ID};
+use did::{HttpRequest, HttpResponse, StreamingCallbackHttpResponse, StreamingCallbackToken, ID};
 use ic_cdk::post_upgrade;
 use ic_cdk_macros::{init, query, update};
 
 mod app;
+mod audit;
+mod guard;
 mod http;
-mod inspect;
 mod utils;
 
 use app::DeferredData;
@@ -30,31 +35,238 @@ pub fn post_upgrade() {
 #[update]
 #[candid_method(update)]
 pub fn admin_set_minter(minter: Principal) -> DeferredDataResult<()> {
-    DeferredData::admin_set_minter(minter)
+    audit::record(
+        "admin_set_minter",
+        format!("{minter:?}"),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_set_minter(minter)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_sns_governance(governance: Option<Principal>) -> DeferredDataResult<()> {
+    audit::record(
+        "admin_set_sns_governance",
+        format!("{governance:?}"),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_set_sns_governance(governance)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_sns_governance() -> Option<Principal> {
+    DeferredData::get_sns_governance()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_read_only(reason: Option<String>) -> DeferredDataResult<()> {
+    audit::record(
+        "admin_set_read_only",
+        reason.as_deref().map(audit_text).unwrap_or_default(),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_set_read_only(reason)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_read_only_mode() -> Option<ReadOnlyMode> {
+    DeferredData::get_read_only_mode()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_allowed_mime_types(mime_types: Vec<String>) -> DeferredDataResult<()> {
+    audit::record(
+        "admin_set_allowed_mime_types",
+        audit_list(&mime_types),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_set_allowed_mime_types(mime_types)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_allowed_mime_types() -> Vec<String> {
+    DeferredData::get_allowed_mime_types()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_custom_domains(domains: Vec<String>) -> DeferredDataResult<()> {
+    audit::record(
+        "admin_set_custom_domains",
+        audit_list(&domains),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_set_custom_domains(domains)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_custom_domains() -> Vec<String> {
+    DeferredData::get_custom_domains()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_agency_quota(
+    agency: AgencyId,
+    quota: Option<AgencyQuota>,
+) -> DeferredDataResult<()> {
+    audit::record(
+        "admin_set_agency_quota",
+        format!("{agency:?}, {quota:?}"),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_set_agency_quota(agency, quota)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_ic_logs(pagination: Pagination) -> DeferredDataResult<Logs> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_ic_logs(pagination))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_audit_log(
+    filter: AuditFilter,
+    pagination: Pagination,
+) -> DeferredDataResult<Vec<AuditEntry>> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_audit_log(filter, pagination))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_verify_audit_log(pagination: Pagination) -> DeferredDataResult<AuditIntegrity> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_verify_audit_log(pagination))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_migration_status() -> DeferredDataResult<MigrationStatus> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_migration_status())
 }
 
 #[query]
 #[candid_method(query)]
-pub fn admin_ic_logs(pagination: Pagination) -> Logs {
-    DeferredData::admin_ic_logs(pagination)
+pub fn admin_export(
+    collection: ExportCollection,
+    after: Option<ExportCursor>,
+) -> DeferredDataResult<ExportChunk> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_export(collection, after))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_import(chunk: ExportChunk) -> DeferredDataResult<()> {
+    audit::record(
+        "admin_import",
+        format!(
+            "{:?}, {} records, {}",
+            chunk.collection,
+            chunk.records.len(),
+            chunk.checksum
+        ),
+        guard::require_permission(DataPermission::Owner)
+            .and_then(|()| DeferredData::admin_import(chunk)),
+    )
 }
 
 #[query]
 #[candid_method(query)]
-pub fn admin_cycles() -> Nat {
-    DeferredData::admin_cycles()
+pub fn admin_export_digest(
+    collection: ExportCollection,
+    after: Option<Vec<u8>>,
+) -> DeferredDataResult<CollectionDigest> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_export_digest(collection, after))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_verify_import(expected: CollectionDigest) -> DeferredDataResult<bool> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_verify_import(expected))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_cycles() -> DeferredDataResult<Nat> {
+    guard::require_permission(DataPermission::Owner)?;
+
+    Ok(DeferredData::admin_cycles())
 }
 
 #[update]
 #[candid_method(update)]
 pub fn minter_create_contract(data: Contract) -> DeferredDataResult<()> {
-    DeferredData::create_contract(data)
+    audit::record(
+        "minter_create_contract",
+        format!("{:?}, agency: {}", data.id, data.agency),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::create_contract(data)),
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub async fn minter_close_contract(contract_id: ID) -> DeferredDataResult<()> {
-    DeferredData::close_contract(contract_id)
+    audit::record(
+        "minter_close_contract",
+        format!("{contract_id:?}"),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::close_contract(contract_id)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn minter_get_contract_document_leaves(
+    contract_id: ID,
+) -> DeferredDataResult<Vec<DocumentLeaf>> {
+    guard::require_permission(DataPermission::Minter)?;
+
+    DeferredData::get_contract_document_leaves(contract_id)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn minter_set_documents_anchor(anchor: DocumentsAnchor) -> DeferredDataResult<()> {
+    audit::record(
+        "minter_set_documents_anchor",
+        anchor.audit_summary(),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::set_documents_anchor(anchor)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn verify_contract_document(
+    contract_id: ID,
+    document_id: u64,
+) -> DeferredDataResult<DocumentProof> {
+    DeferredData::verify_contract_document(contract_id, document_id)
 }
 
 #[query]
@@ -78,6 +290,25 @@ pub fn get_contract_document(
     DeferredData::get_contract_document(contract_id, document_id, None)
 }
 
+#[query]
+#[candid_method(query)]
+pub fn get_contract_document_version(
+    contract_id: ID,
+    document_id: u64,
+    version: u64,
+) -> DeferredDataResult<ContractDocumentData> {
+    DeferredData::get_contract_document_version(contract_id, document_id, version, None)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_contract_document_history(
+    contract_id: ID,
+    document_id: u64,
+) -> DeferredDataResult<DocumentHistory> {
+    DeferredData::get_contract_document_history(contract_id, document_id)
+}
+
 #[update]
 #[candid_method(update)]
 pub fn upload_contract_document(
@@ -85,7 +316,110 @@ pub fn upload_contract_document(
     document: ContractDocument,
     data: Vec<u8>,
 ) -> DeferredDataResult<u64> {
-    DeferredData::upload_contract_document(contract_id, document, data)
+    audit::record(
+        "upload_contract_document",
+        format!(
+            "{contract_id:?}, {}, {} bytes",
+            document.audit_summary(),
+            data.len()
+        ),
+        guard::require_writable()
+            .and_then(|()| DeferredData::upload_contract_document(contract_id, document, data)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn update_contract_document(
+    contract_id: ID,
+    document_id: u64,
+    document: ContractDocument,
+) -> DeferredDataResult<u64> {
+    audit::record(
+        "update_contract_document",
+        format!(
+            "{contract_id:?}, {document_id}, {}",
+            document.audit_summary()
+        ),
+        guard::require_writable().and_then(|()| {
+            DeferredData::update_contract_document(contract_id, document_id, document)
+        }),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn replace_contract_document(
+    contract_id: ID,
+    document_id: u64,
+    document: ContractDocument,
+    data: Vec<u8>,
+) -> DeferredDataResult<u64> {
+    audit::record(
+        "replace_contract_document",
+        format!(
+            "{contract_id:?}, {document_id}, {}, {} bytes",
+            document.audit_summary(),
+            data.len()
+        ),
+        guard::require_writable().and_then(|()| {
+            DeferredData::replace_contract_document(contract_id, document_id, document, data)
+        }),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn delete_contract_document(
+    contract_id: ID,
+    document_id: u64,
+    purge: bool,
+) -> DeferredDataResult<()> {
+    audit::record(
+        "delete_contract_document",
+        format!("{contract_id:?}, {document_id}, {purge}"),
+        guard::require_writable()
+            .and_then(|()| DeferredData::delete_contract_document(contract_id, document_id, purge)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn begin_contract_document_upload(
+    contract_id: ID,
+    document: ContractDocument,
+    sha256: String,
+) -> DeferredDataResult<u64> {
+    audit::record(
+        "begin_contract_document_upload",
+        format!(
+            "{contract_id:?}, {}, {}",
+            document.audit_summary(),
+            audit_text(&sha256)
+        ),
+        guard::require_writable().and_then(|()| {
+            DeferredData::begin_contract_document_upload(contract_id, document, sha256)
+        }),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn upload_contract_document_chunk(upload_id: u64, data: Vec<u8>) -> DeferredDataResult<()> {
+    guard::require_writable()?;
+
+    DeferredData::upload_contract_document_chunk(upload_id, data)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn finalize_contract_document_upload(upload_id: u64) -> DeferredDataResult<u64> {
+    audit::record(
+        "finalize_contract_document_upload",
+        format!("{upload_id}"),
+        guard::require_writable()
+            .and_then(|()| DeferredData::finalize_contract_document_upload(upload_id)),
+    )
 }
 
 #[update]
@@ -95,7 +429,16 @@ pub fn update_contract_property(
     key: String,
     value: GenericValue,
 ) -> DeferredDataResult<()> {
-    DeferredData::update_contract_property(contract_id, key, value)
+    audit::record(
+        "update_contract_property",
+        format!(
+            "{contract_id:?}, {}, {}",
+            audit_text(&key),
+            value.audit_summary()
+        ),
+        guard::require_writable()
+            .and_then(|()| DeferredData::update_contract_property(contract_id, key, value)),
+    )
 }
 
 #[update]
@@ -105,25 +448,136 @@ pub fn update_restricted_contract_property(
     key: String,
     value: RestrictedProperty,
 ) -> DeferredDataResult<()> {
-    DeferredData::update_restricted_contract_property(contract_id, key, value)
+    audit::record(
+        "update_restricted_contract_property",
+        format!(
+            "{contract_id:?}, {}, {}",
+            audit_text(&key),
+            audit_list(&value.access_list)
+        ),
+        guard::require_writable().and_then(|()| {
+            DeferredData::update_restricted_contract_property(contract_id, key, value)
+        }),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn minter_set_agency_member(
+    member: Principal,
+    agency: Option<AgencyId>,
+) -> DeferredDataResult<()> {
+    audit::record(
+        "minter_set_agency_member",
+        format!("{member:?}, {agency:?}"),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::set_agency_member(member, agency)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn minter_set_agency_status(
+    agency: AgencyId,
+    status: AgencyStatus,
+) -> DeferredDataResult<AgencyReport> {
+    audit::record(
+        "minter_set_agency_status",
+        format!("{agency:?}, {status:?}"),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::set_agency_status(agency, status)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn minter_remove_agency(agency: AgencyId) -> DeferredDataResult<AgencyReport> {
+    audit::record(
+        "minter_remove_agency",
+        format!("{agency:?}"),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::remove_agency(agency)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn minter_transfer_agency_records(
+    transfer: AgencyTransfer,
+) -> DeferredDataResult<AgencyTransferAuditEntry> {
+    audit::record(
+        "minter_transfer_agency_records",
+        transfer.audit_summary(),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::transfer_agency_records(transfer)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn minter_set_agency_quota(
+    agency: AgencyId,
+    quota: Option<AgencyQuota>,
+) -> DeferredDataResult<()> {
+    audit::record(
+        "minter_set_agency_quota",
+        format!("{agency:?}, {quota:?}"),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::set_agency_quota(agency, quota)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_transfers_audit(agency: AgencyId) -> Vec<AgencyTransferAuditEntry> {
+    DeferredData::get_agency_transfers_audit(agency)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_usage(agency: AgencyId) -> DeferredDataResult<AgencyUsage> {
+    DeferredData::get_agency_usage(agency)
 }
 
 #[update]
 #[candid_method(update)]
 pub fn minter_create_real_estate(real_estate: RealEstate) -> DeferredDataResult<ID> {
-    DeferredData::create_real_estate(real_estate)
+    audit::record(
+        "minter_create_real_estate",
+        real_estate.audit_summary(),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::create_real_estate(real_estate)),
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub fn minter_delete_real_estate(id: ID) -> DeferredDataResult<()> {
-    DeferredData::delete_real_estate(&id)
+    audit::record(
+        "minter_delete_real_estate",
+        format!("{id:?}"),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::delete_real_estate(&id)),
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub fn minter_update_real_estate(id: ID, real_estate: RealEstate) -> DeferredDataResult<()> {
-    DeferredData::update_real_estate(&id, real_estate)
+    audit::record(
+        "minter_update_real_estate",
+        format!("{id:?}, {}", real_estate.audit_summary()),
+        guard::require_permission(DataPermission::Minter)
+            .and_then(|()| guard::require_writable())
+            .and_then(|()| DeferredData::update_real_estate(&id, real_estate)),
+    )
 }
 
 #[query]
@@ -132,6 +586,46 @@ pub fn get_real_estate(id: ID) -> DeferredDataResult<RealEstate> {
     DeferredData::get_real_estate(&id)
 }
 
+// SNS generic function validators
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_minter(minter: Principal) -> Result<String, String> {
+    DeferredData::validate_admin_set_minter(minter).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_sns_governance(governance: Option<Principal>) -> Result<String, String> {
+    DeferredData::validate_admin_set_sns_governance(governance).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_read_only(reason: Option<String>) -> Result<String, String> {
+    DeferredData::validate_admin_set_read_only(reason).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_allowed_mime_types(mime_types: Vec<String>) -> Result<String, String> {
+    DeferredData::validate_admin_set_allowed_mime_types(mime_types).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_custom_domains(domains: Vec<String>) -> Result<String, String> {
+    DeferredData::validate_admin_set_custom_domains(domains).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_agency_quota(
+    agency: AgencyId,
+    quota: Option<AgencyQuota>,
+) -> Result<String, String> {
+    DeferredData::validate_admin_set_agency_quota(agency, quota).map_err(|err| err.to_string())
+}
+
 // HTTP endpoint
 #[query]
 #[candid_method(query)]
@@ -139,6 +633,20 @@ pub async fn http_request(req: HttpRequest) -> HttpResponse {
     http::HttpApi::handle_http_request(req).await
 }
 
+#[update]
+#[candid_method(update)]
+pub async fn http_request_update(req: HttpRequest) -> HttpResponse {
+    http::HttpApi::handle_http_request_update(req).await
+}
+
+#[query]
+#[candid_method(query)]
+pub fn http_request_streaming_callback(
+    token: StreamingCallbackToken,
+) -> StreamingCallbackHttpResponse {
+    http::HttpApi::http_request_streaming_callback(token).unwrap_or_else(|err| ic_cdk::trap(&err))
+}
+
 #[allow(dead_code)]
 fn main() {
     // The line below generates did types and service definition from the
@@ -159,3 +667,280 @@ pub fn getrandom_always_fail(_buf: &mut [u8]) -> Result<(), getrandom::Error> {
 }
 
 getrandom::register_custom_getrandom!(getrandom_always_fail);
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::{AgencyTransferStatus, AuditResult, DeferredDataError};
+    use ic_log::LogSettingsV2;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob, mock_contract, mock_real_estate};
+
+    fn missing_permission(permission: DataPermission) -> DeferredDataError {
+        DeferredDataError::MissingPermission(permission)
+    }
+
+    #[test]
+    fn test_admin_endpoints_should_require_owner() {
+        let expected = Err(missing_permission(DataPermission::Owner));
+
+        assert_eq!(admin_set_minter(alice()), expected);
+        assert_eq!(admin_set_sns_governance(Some(alice())), expected);
+        assert_eq!(admin_set_read_only(Some("migration".to_string())), expected);
+        assert_eq!(
+            admin_set_allowed_mime_types(vec!["text/plain".to_string()]),
+            expected
+        );
+        assert_eq!(admin_set_agency_quota(AgencyId(1), None), expected);
+        assert_eq!(
+            admin_set_custom_domains(vec!["docs.ekoke.io".to_string()]),
+            expected
+        );
+        assert_eq!(
+            admin_ic_logs(Pagination {
+                offset: 0,
+                count: 10
+            })
+            .map(|_| ()),
+            expected
+        );
+        assert_eq!(admin_cycles().map(|_| ()), expected);
+        assert_eq!(admin_migration_status().map(|_| ()), expected);
+        assert_eq!(
+            admin_audit_log(AuditFilter::default(), pagination()).map(|_| ()),
+            expected
+        );
+        assert_eq!(admin_verify_audit_log(pagination()).map(|_| ()), expected);
+        assert_eq!(
+            admin_export(ExportCollection::Contracts, None).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_import(ExportChunk::new(
+                ExportCollection::Contracts,
+                None,
+                vec![],
+                true
+            )),
+            expected
+        );
+        assert_eq!(
+            admin_export_digest(ExportCollection::Contracts, None).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_verify_import(CollectionDigest::compute(
+                ExportCollection::Contracts,
+                None,
+                Vec::<(Vec<u8>, Vec<u8>)>::new(),
+                None
+            ))
+            .map(|_| ()),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_minter_endpoints_should_require_minter() {
+        init_canister();
+        let expected = Err(missing_permission(DataPermission::Minter));
+
+        assert_eq!(minter_create_contract(mock_contract(1, 10)), expected);
+        assert_eq!(minter_close_contract(1u64.into()).await, expected);
+        assert_eq!(minter_set_agency_member(bob(), Some(AgencyId(1))), expected);
+        assert_eq!(
+            minter_set_agency_status(AgencyId(1), AgencyStatus::Suspended).map(|_| ()),
+            expected
+        );
+        assert_eq!(minter_remove_agency(AgencyId(1)).map(|_| ()), expected);
+        assert_eq!(minter_set_agency_quota(AgencyId(1), None), expected);
+        assert_eq!(
+            minter_transfer_agency_records(AgencyTransfer {
+                approved_at: None,
+                approved_by: None,
+                contracts: vec![],
+                from: AgencyId(1),
+                from_accepted_by: None,
+                id: 1,
+                real_estates: vec![],
+                requested_at: 0,
+                requested_by: alice(),
+                status: AgencyTransferStatus::Pending,
+                to: AgencyId(2),
+                to_accepted_by: None,
+            })
+            .map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            minter_create_real_estate(mock_real_estate()).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            minter_update_real_estate(1u64.into(), mock_real_estate()),
+            expected
+        );
+        assert_eq!(minter_delete_real_estate(1u64.into()), expected);
+        assert_eq!(
+            minter_get_contract_document_leaves(1u64.into()).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            minter_set_documents_anchor(DocumentsAnchor {
+                contract_id: 1u64.into(),
+                root: String::default(),
+                leaves: vec![],
+                transaction_hash: String::default(),
+                chain_id: 1,
+                anchored_at: 0,
+            }),
+            expected
+        );
+        assert_eq!(
+            update_contract_property(
+                1u64.into(),
+                "contract:name".to_string(),
+                GenericValue::Nat64Content(1)
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_should_dispatch_call_if_permission_is_granted() {
+        init_canister();
+
+        assert!(admin_cycles().is_ok());
+        assert!(admin_migration_status().unwrap().is_completed());
+        assert!(admin_set_minter(crate::utils::caller()).is_ok());
+        assert!(minter_create_real_estate(mock_real_estate()).is_ok());
+
+        let digest = admin_export_digest(ExportCollection::RealEstates, None).unwrap();
+        assert_eq!(digest.records, 1);
+        assert!(admin_verify_import(digest).unwrap());
+        assert!(admin_export(ExportCollection::RealEstates, None)
+            .unwrap()
+            .is_valid());
+    }
+
+    #[test]
+    fn test_should_recognize_sns_governance_as_owner() {
+        init(DeferredDataInitData {
+            log_settings: LogSettingsV2::default(),
+            minter: alice(),
+        });
+        // hand over the ownership checks to the SNS governance
+        assert!(admin_set_sns_governance(Some(bob())).is_ok());
+        assert_eq!(get_sns_governance(), Some(bob()));
+        assert!(crate::app::Inspect::inspect_is_owner(bob()));
+
+        assert_eq!(
+            validate_admin_set_minter(Principal::anonymous()),
+            Err("configuration error: the minter cannot be anonymous".to_string())
+        );
+        assert_eq!(
+            validate_admin_set_minter(bob()),
+            Ok(format!("Set minter to {bob}", bob = bob()))
+        );
+    }
+
+    #[test]
+    fn test_should_record_state_changing_calls_in_audit_log() {
+        init_canister();
+
+        assert!(admin_set_minter(bob()).is_ok());
+        assert!(minter_delete_real_estate(1u64.into()).is_err());
+
+        let entries = admin_audit_log(AuditFilter::default(), pagination()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].caller, crate::utils::caller());
+        assert_eq!(entries[0].method, "admin_set_minter");
+        assert_eq!(entries[0].args, bob().to_string());
+        assert_eq!(entries[0].result, AuditResult::Ok);
+        // denied calls are recorded too
+        assert_eq!(entries[1].method, "minter_delete_real_estate");
+        assert_eq!(
+            entries[1].result,
+            AuditResult::Err("missing permission: Minter".to_string())
+        );
+
+        // failed calls are recorded with the error
+        assert!(admin_set_sns_governance(Some(Principal::anonymous())).is_err());
+        let filter = AuditFilter {
+            method: Some("admin_set_sns_governance".to_string()),
+            ..Default::default()
+        };
+        let entries = admin_audit_log(filter, pagination()).unwrap();
+        assert_eq!(
+            entries[0].result,
+            AuditResult::Err(
+                "configuration error: the SNS governance cannot be anonymous".to_string()
+            )
+        );
+
+        let integrity = admin_verify_audit_log(pagination()).unwrap();
+        assert_eq!(integrity.entries, 3);
+        assert_eq!(integrity.first_invalid_entry, None);
+        assert_eq!(integrity.head, entries[0].hash);
+    }
+
+    #[test]
+    fn test_should_reject_writes_in_read_only_mode() {
+        init_canister();
+        assert!(admin_set_minter(crate::utils::caller()).is_ok());
+        assert!(admin_set_read_only(Some("migration".to_string())).is_ok());
+        assert_eq!(
+            get_read_only_mode().map(|mode| mode.reason),
+            Some("migration".to_string())
+        );
+
+        let expected = Err(DeferredDataError::ReadOnly);
+        assert_eq!(
+            minter_create_real_estate(mock_real_estate()).map(|_| ()),
+            expected
+        );
+        assert_eq!(minter_set_agency_member(bob(), Some(AgencyId(1))), expected);
+        assert_eq!(
+            upload_contract_document(
+                1u64.into(),
+                ContractDocument {
+                    access_list: vec![],
+                    mime_type: "application/pdf".to_string(),
+                    name: "contract.pdf".to_string(),
+                    size: 3,
+                },
+                vec![1, 2, 3]
+            )
+            .map(|_| ()),
+            expected
+        );
+        assert_eq!(upload_contract_document_chunk(0, vec![1]), expected);
+        assert_eq!(finalize_contract_document_upload(0), expected);
+        assert_eq!(delete_contract_document(1u64.into(), 0, true), expected);
+        // reads and admin endpoints are still available
+        assert!(get_contracts().is_empty());
+        assert!(admin_set_read_only(None).is_ok());
+        assert!(minter_create_real_estate(mock_real_estate()).is_ok());
+    }
+
+    fn pagination() -> Pagination {
+        Pagination {
+            offset: 0,
+            count: 10,
+        }
+    }
+
+    fn init_canister() {
+        init(DeferredDataInitData {
+            log_settings: LogSettingsV2 {
+                enable_console: true,
+                log_filter: "debug".to_string(),
+                in_memory_records: 128,
+                max_record_length: 1000,
+            },
+            minter: alice(),
+        });
+    }
+}
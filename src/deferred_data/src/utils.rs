@@ -1,5 +1,20 @@
 use candid::{Nat, Principal};
 
+/// Returns current time in nanoseconds
+pub fn time() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ic_cdk::api::time()
+    }
+}
+
 pub fn cycles() -> Nat {
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -22,3 +37,33 @@ pub fn caller() -> Principal {
         ic_cdk::caller()
     }
 }
+
+/// Returns the principal of this canister
+pub fn canister_id() -> Principal {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Principal::from_text("v5vof-zqaaa-aaaal-ai5cq-cai").unwrap()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ic_cdk::id()
+    }
+}
+
+/// Returns a random id (hex of 32 bytes), which can't be guessed by the callers
+pub async fn random_id() -> Result<String, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use sha2::{Digest as _, Sha256};
+
+        Ok(hex::encode(Sha256::digest(time().to_be_bytes())))
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let (bytes,) = ic_cdk::api::management_canister::main::raw_rand()
+            .await
+            .map_err(|(code, message)| format!("failed to get random bytes: {code:?} {message}"))?;
+
+        Ok(hex::encode(bytes))
+    }
+}
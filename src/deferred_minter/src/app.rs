@@ -2,8 +2,15 @@ use candid::{Nat, Principal};
 use contract_id::ContractId;
 use data_client::DeferredDataClient;
 use did::deferred::{
-    Agency, Contract, ContractError, ContractRegistration, DeferredMinterError,
-    DeferredMinterInitData, DeferredMinterResult, RealEstate, Role,
+    Agency, AgencyApplication, AgencyApplicationDocument, AgencyApplicationStatus, AgencyChange,
+    AgencyError, AgencyId, AgencyMember, AgencyQuota, AgencyReport, AgencyRole, AgencyStatus,
+    AgencyTransfer, AgencyTransferAuditEntry, AgencyTransferStatus, AgencyUpdate, AuditEntry,
+    AuditFilter, AuditIntegrity, ConfigurationChange, ConfigurationError, ConfigurationUpdate,
+    Contract, ContractError, ContractRegistration, DeferredMinterConfiguration,
+    DeferredMinterError, DeferredMinterInitData, DeferredMinterResult, DocumentsAnchor,
+    DocumentsMerkleTree, MigrationStatus, PausableOperation, PauseState, Permission, Proposal,
+    ProposalAction, ProposalConfig, ProposalError, ProposalStatus, RealEstate, RewardParameters,
+    RewardPoolAlert, RewardPoolStatus, Role, RoleDefinition,
 };
 use did::ID;
 use ethereum::{DeferredErc721, EvmRpcClient, RewardPool, Wallet};
@@ -11,27 +18,48 @@ use ic_log::did::Pagination;
 use ic_log::writer::Logs;
 use ic_log::{init_log, take_memory_records};
 
+mod agency_applications;
+mod agency_transfers;
 mod agents;
+mod audit_log;
 mod configuration;
 mod contract_id;
 mod data_client;
 mod ethereum;
 mod inspect;
 mod memory;
+mod metrics;
+mod migrations;
+mod pauses;
+mod proposals;
 mod reward;
+mod reward_pool_ledger;
 mod roles;
+mod sns;
 #[cfg(test)]
 pub mod test_utils;
 
+use self::agency_applications::AgencyApplications;
+use self::agency_transfers::AgencyTransfers;
 pub(crate) use self::agents::Agents;
+pub use self::audit_log::AuditLog;
 use self::configuration::Configuration;
 pub use self::inspect::Inspect;
+pub use self::metrics::Metrics;
+use self::migrations::Migrations;
+use self::pauses::Pauses;
+use self::proposals::Proposals;
 use self::reward::Reward;
+use self::reward_pool_ledger::RewardPoolLedger;
 use self::roles::RolesManager;
+pub use self::sns::SnsValidator;
 use crate::utils::{self, caller};
 
 #[derive(Default)]
-/// Deferred minter canister API
+/// Deferred minter canister API.
+///
+/// The permissions required by the `admin_` and `gas_station_` methods are checked by the endpoint
+/// guard before the call is dispatched here.
 pub struct DeferredMinter;
 
 impl DeferredMinter {
@@ -44,6 +72,10 @@ impl DeferredMinter {
             .expect("failed to set erc721 canister");
         Configuration::set_reward_pool_contract(init_args.reward_pool)
             .expect("failed to set reward pool canister");
+        Configuration::set_reward_pool_deploy_block(init_args.reward_pool_deploy_block)
+            .expect("failed to set reward pool deploy block");
+        RewardPoolLedger::reset(init_args.reward_pool_deploy_block)
+            .expect("failed to reset reward pool ledger");
         Configuration::set_ecdsa_key(init_args.ecdsa_key).expect("failed to set ecdsa key");
         Configuration::set_chain_id(init_args.chain_id).expect("failed to set chain id");
         Configuration::set_evm_rpc(init_args.evm_rpc).expect("failed to set evm rpc");
@@ -60,10 +92,102 @@ impl DeferredMinter {
         // set the log settings
         Configuration::set_log_settings(init_args.log_settings)
             .expect("failed to set log settings");
+
+        Migrations::init();
+
+        Self::set_timers();
+    }
+
+    /// Upgrade the deferred minter, applying the optional configuration update
+    pub fn post_upgrade(update: Option<ConfigurationUpdate>) {
+        // apply the update before initializing the log, so that the new log settings are used
+        let changes = match update {
+            Some(update) => {
+                Configuration::update(update, caller()).expect("invalid configuration update")
+            }
+            None => vec![],
+        };
+
+        if !cfg!(test) {
+            init_log(&Configuration::get_log_settings()).expect("failed to init log");
+        }
+        for change in changes {
+            log::info!("Configuration changed on upgrade: {change:?}");
+        }
+
+        // the reward pool ledger of older versions has never been synced
+        RewardPoolLedger::init_last_synced_block(Configuration::get_reward_pool_deploy_block())
+            .expect("failed to init reward pool last synced block");
+
+        // agencies registered before the agency ids were introduced are keyed by their wallet
+        Agents::migrate_agency_ids();
+        // migrate the collections stored with an older schema
+        Migrations::start();
+        // audit log entries recorded before the audit log was indexed
+        AuditLog::index_entries();
+
+        Self::set_timers();
+    }
+
+    /// Set the timer to periodically reconcile the reward pool ledger with the chain
+    fn set_timers() {
+        if cfg!(test) {
+            return;
+        }
+
+        ic_cdk_timers::set_timer_interval(reward_pool_ledger::SYNC_INTERVAL, || {
+            ic_cdk::spawn(async {
+                if let Err(err) =
+                    RewardPoolLedger::sync(&Self::reward_pool(), &Self::evm_rpc_client()).await
+                {
+                    log::error!("failed to sync reward pool ledger: {err}");
+                }
+            });
+        });
+        ic_cdk_timers::set_timer_interval(
+            agency_applications::APPLICATION_CLEANUP_INTERVAL,
+            || {
+                let expired = AgencyApplications::expire_applications(utils::time());
+                if expired > 0 {
+                    log::info!("{expired} agency applications expired");
+                }
+            },
+        );
+        ic_cdk_timers::set_timer_interval(agents::MEMBER_SYNC_INTERVAL, || {
+            ic_cdk::spawn(Self::sync_agency_members());
+        });
+        Self::schedule_member_sync();
+    }
+
+    /// Synchronize the queued agency memberships with the deferred data canister right away
+    fn schedule_member_sync() {
+        if cfg!(test) {
+            return;
+        }
+
+        ic_cdk_timers::set_timer(std::time::Duration::ZERO, || {
+            ic_cdk::spawn(Self::sync_agency_members());
+        });
     }
 
-    pub fn post_upgrade() {
-        init_log(&Configuration::get_log_settings()).expect("failed to init log");
+    /// Synchronize the memberships queued by the agency registrations and migrations with the
+    /// deferred data canister.
+    ///
+    /// The current membership of each queued principal is sent; the ones which fail stay queued
+    /// and are retried by the next synchronization.
+    async fn sync_agency_members() {
+        for (member, queued_for) in Agents::pending_member_syncs() {
+            let agency = Agents::get_membership(member).map(|membership| membership.agency);
+            match Self::deferred_data()
+                .set_agency_member(member, agency)
+                .await
+            {
+                Ok(()) => Agents::member_synced(member, queued_for),
+                Err(err) => {
+                    log::error!("failed to synchronize the agency membership of {member}: {err}")
+                }
+            }
+        }
     }
 
     /// Get the Ethereum address of the deferred minter
@@ -79,30 +203,266 @@ impl DeferredMinter {
         Agents::get_agencies()
     }
 
-    /// Get agency by wallet
-    pub fn get_agent(id: Principal) -> Option<Agency> {
-        Agents::get_agency_by_wallet(id)
+    /// Get agency by id
+    pub fn get_agency(id: AgencyId) -> Option<Agency> {
+        Agents::get_agency(id)
+    }
+
+    /// Get the agency the principal is a member of
+    pub fn get_member_agency(principal: Principal) -> Option<Agency> {
+        Agents::get_agency_by_member(principal)
+    }
+
+    /// Update the profile of the agency owned by the caller.
+    ///
+    /// `vat` and `owner` can only be changed by a custodian with `admin_register_agency`
+    pub fn update_agency(update: AgencyUpdate) -> DeferredMinterResult<()> {
+        let caller = caller();
+        if !Inspect::inspect_is_agency_owner(caller) {
+            return Err(AgencyError::NotTheOwner(caller).into());
+        }
+
+        let mut agency =
+            Agents::get_agency_by_member(caller).ok_or(AgencyError::NotAMember(caller))?;
+        update.apply(&mut agency);
+        Inspect::inspect_agency(&agency)?;
+
+        let id = agency.id;
+        Agents::update_agency(agency, caller)?;
+        log::info!("Agency {id} updated by {caller}");
+
+        Ok(())
+    }
+
+    /// Get the profile change history of the agency.
+    ///
+    /// Only a custodian or the owner of the agency can call this method
+    pub fn get_agency_history(agency: AgencyId) -> DeferredMinterResult<Vec<AgencyChange>> {
+        let caller = caller();
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Agents::get_agency(agency).is_some_and(|agency| agency.owner == caller)
+        {
+            return Err(AgencyError::NotTheOwner(caller).into());
+        }
+
+        Ok(Agents::get_agency_history(agency))
+    }
+
+    /// Submit an application to register the caller as an agency.
+    ///
+    /// The caller will be the owner of the agency once the application is approved by a custodian
+    pub fn submit_agency_application(
+        mut agency: Agency,
+        documents: Vec<AgencyApplicationDocument>,
+    ) -> DeferredMinterResult<u64> {
+        let applicant = caller();
+        if applicant == Principal::anonymous() {
+            return Err(DeferredMinterError::Unauthorized);
+        }
+        if Agents::get_membership(applicant).is_some() {
+            return Err(AgencyError::AgencyAlreadyRegistered(applicant).into());
+        }
+
+        agency.owner = applicant;
+        Inspect::inspect_agency(&agency)?;
+
+        let id = AgencyApplications::submit(applicant, agency, documents)?;
+        log::info!("Agency application {id} submitted by {applicant}");
+
+        Ok(id)
+    }
+
+    /// Get agency application by id.
+    ///
+    /// Only a custodian or the applicant can call this method
+    pub fn get_agency_application(id: u64) -> DeferredMinterResult<AgencyApplication> {
+        let caller = caller();
+        let application =
+            AgencyApplications::get_application(id).ok_or(AgencyError::ApplicationNotFound(id))?;
+
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && application.applicant != caller
+        {
+            return Err(AgencyError::NotTheApplicant(caller).into());
+        }
+
+        Ok(application)
+    }
+
+    /// Get the document at `index` of an agency application.
+    ///
+    /// Only a custodian or the applicant can call this method
+    pub fn get_agency_application_document(
+        id: u64,
+        index: u64,
+    ) -> DeferredMinterResult<AgencyApplicationDocument> {
+        // checks the permissions
+        Self::get_agency_application(id)?;
+
+        AgencyApplications::get_document(id, index)
+    }
+
+    /// Get the status of the agency
+    pub fn get_agency_status(agency: AgencyId) -> AgencyStatus {
+        Agents::get_agency_status(agency)
     }
 
-    /// Remove agency by wallet.
+    /// Remove agency by id.
     ///
-    /// Only a custodian can call this method or the caller must be the owner of the agency
-    pub fn remove_agency(wallet: Principal) -> DeferredMinterResult<()> {
-        if !Inspect::inspect_remove_agency(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Only a custodian can call this method or the caller must be an admin of the agency.
+    /// The agency can't be removed while it has open contracts, which must be closed or transferred first.
+    /// The real estates of the agency are deleted.
+    ///
+    /// The agency is removed from the minter only once it has been removed from the deferred data
+    /// canister, so a removal which fails halfway can be retried: every step is idempotent and the
+    /// agency stays suspended in the meantime.
+    pub async fn remove_agency(agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+        if !Inspect::inspect_remove_agency(caller(), agency) {
+            return Err(DeferredMinterError::MissingPermission(
+                Permission::ManageAgencies,
+            ));
+        }
+        let owner = Agents::get_agency(agency)
+            .ok_or(AgencyError::AgencyNotFound(agency))?
+            .owner;
+
+        // block the writes of the agency while it's being removed
+        let status = Agents::get_agency_status(agency);
+        Agents::set_agency_status(agency, AgencyStatus::Suspended);
+
+        // fails if the agency still has open contracts
+        let report = match Self::deferred_data().remove_agency(agency).await {
+            Ok(report) => report,
+            Err(err) => {
+                Agents::set_agency_status(agency, status);
+                return Err(err);
+            }
+        };
+
+        // revoke the access of the members to the agency data
+        for member in Agents::get_members(agency) {
+            Self::deferred_data()
+                .set_agency_member(member.principal, None)
+                .await?;
         }
 
-        Agents::remove_agency(wallet);
         // remove role
-        RolesManager::remove_role(wallet, Role::Agent)?;
+        RolesManager::remove_role(owner, Role::Agent)?;
+        Agents::remove_agency(agency);
+
+        log::info!(
+            "Agency removed: {agency}; deleted {} real estates",
+            report.real_estates.len()
+        );
+
+        Ok(report)
+    }
+
+    /// Get the members of the agency.
+    ///
+    /// Only a custodian or a member of the agency can call this method
+    pub fn get_agency_members(agency: AgencyId) -> DeferredMinterResult<Vec<AgencyMember>> {
+        let caller = caller();
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Inspect::inspect_is_agency_member(caller, agency)
+        {
+            return Err(AgencyError::NotAMember(caller).into());
+        }
+
+        Ok(Agents::get_members(agency))
+    }
+
+    /// Add a member to the agency or change the role of an existing member.
+    ///
+    /// Only a custodian or an admin of the agency can call this method
+    pub async fn add_agency_member(
+        agency: AgencyId,
+        member: Principal,
+        role: AgencyRole,
+    ) -> DeferredMinterResult<()> {
+        let caller = caller();
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Inspect::inspect_is_agency_admin(caller, agency)
+        {
+            return Err(AgencyError::NotAnAdmin(caller).into());
+        }
+        if Agents::get_agency(agency).is_none() {
+            return Err(AgencyError::AgencyNotFound(agency).into());
+        }
+
+        match Agents::get_membership(member) {
+            Some(membership) if membership.agency != agency => {
+                return Err(AgencyError::AlreadyMember(member).into());
+            }
+            Some(membership)
+                if membership.role == AgencyRole::Admin && role != AgencyRole::Admin =>
+            {
+                Self::check_not_last_admin(agency)?;
+            }
+            Some(_) => {}
+            None => {
+                Self::deferred_data()
+                    .set_agency_member(member, Some(agency))
+                    .await?;
+            }
+        }
+
+        Agents::set_member(agency, member, role);
+        log::info!("{member} is now {role:?} of agency {agency}");
+
+        Ok(())
+    }
+
+    /// Remove a member from the agency.
+    ///
+    /// Only a custodian or an admin of the agency can call this method.
+    /// The last admin of an agency can't be removed.
+    pub async fn remove_agency_member(
+        agency: AgencyId,
+        member: Principal,
+    ) -> DeferredMinterResult<()> {
+        let caller = caller();
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Inspect::inspect_is_agency_admin(caller, agency)
+        {
+            return Err(AgencyError::NotAnAdmin(caller).into());
+        }
+
+        let membership = Agents::get_membership(member)
+            .filter(|membership| membership.agency == agency)
+            .ok_or(AgencyError::NotAMember(member))?;
+        if membership.role == AgencyRole::Admin {
+            Self::check_not_last_admin(agency)?;
+        }
+
+        Self::deferred_data()
+            .set_agency_member(member, None)
+            .await?;
+        Agents::remove_member(member);
+        log::info!("{member} removed from agency {agency}");
 
-        log::info!("Agency removed: {wallet}",);
+        Ok(())
+    }
+
+    /// Returns an error if the agency has only one admin left
+    fn check_not_last_admin(agency: AgencyId) -> DeferredMinterResult<()> {
+        let admins = Agents::get_members(agency)
+            .into_iter()
+            .filter(|member| member.role == AgencyRole::Admin)
+            .count();
+        if admins <= 1 {
+            return Err(AgencyError::LastAdmin.into());
+        }
 
         Ok(())
     }
 
     /// Create a new contract
     pub async fn create_contract(data: ContractRegistration) -> DeferredMinterResult<ID> {
+        Pauses::require_active(&[
+            PausableOperation::ContractCreation,
+            PausableOperation::EvmTransactions,
+        ])?;
         // inspect
         Inspect::inspect_register_contract(caller(), &data)?;
         // get contract id
@@ -115,9 +475,9 @@ impl DeferredMinter {
             .get_real_estate(data.real_estate_id.clone())
             .await?;
         log::debug!("real estate: {real_estate:?}");
-        if real_estate.agency != caller() {
+        if !Inspect::inspect_can_operate_agency(caller(), real_estate.agency) {
             log::error!(
-                "real estate {} is not owned by the caller {}",
+                "real estate {} is not owned by the agency of the caller {}",
                 data.real_estate_id,
                 caller()
             );
@@ -129,19 +489,22 @@ impl DeferredMinter {
             "real estate id {} is owned by the caller",
             data.real_estate_id
         );
+        if Agents::is_suspended(real_estate.agency) {
+            return Err(AgencyError::AgencySuspended(real_estate.agency).into());
+        }
 
         // create contract
         let token_price = data.token_value;
-        let contract = Self::contract_from_registration(contract_id.clone(), data);
+        let contract =
+            Self::contract_from_registration(contract_id.clone(), data, real_estate.agency);
         log::debug!("contract data: {contract:?}");
 
         // get evm rpc client
         let evm_rpc_client = Self::evm_rpc_client();
 
         // get available reward balance
-        let reward_available_balance = Self::reward_pool()
-            .available_rewards(&evm_rpc_client)
-            .await?;
+        let reward_available_balance =
+            RewardPoolLedger::available_rewards(&Self::reward_pool(), &evm_rpc_client).await?;
         log::debug!("reward available balance: {reward_available_balance}");
 
         // get reward for token
@@ -164,6 +527,11 @@ impl DeferredMinter {
             .await?;
         log::debug!("contract created on Ethereum");
 
+        // record the reservation made on the reward pool
+        if let Some(reward) = token_reward {
+            RewardPoolLedger::reserve(contract_id.clone(), reward, contract.installments)?;
+        }
+
         // insert contract into the storage
         Self::deferred_data().create_contract(contract).await?;
         log::debug!("contract created on data canister");
@@ -177,18 +545,11 @@ impl DeferredMinter {
 
     /// Close a contract on both the ERC721 and the data canister
     pub async fn close_contract(contract_id: ID) -> DeferredMinterResult<()> {
-        if !Inspect::inspect_is_agent(caller()) && !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
-        }
-        // if we are an agent, we need to check whether we are the agency for the contract
-        if RolesManager::is_agent(caller()) {
-            log::debug!("caller is an agent");
-            let contract = Self::deferred_data().get_contract(&contract_id).await?;
-            if contract.agency != caller() {
-                log::debug!("caller is not the agency for the contract");
-                ic_cdk::trap("Unauthorized");
-            }
-        }
+        Pauses::require_active(&[
+            PausableOperation::ContractClosing,
+            PausableOperation::EvmTransactions,
+        ])?;
+        Self::require_can_operate_contract(&contract_id).await?;
 
         // close contract on erc721
         let evm_rpc_client = Self::evm_rpc_client();
@@ -206,15 +567,68 @@ impl DeferredMinter {
         Ok(())
     }
 
+    /// Anchor the Merkle root of the documents of a contract on the ERC721, then store the
+    /// anchor on the data canister, which serves the inclusion proofs of the documents
+    pub async fn anchor_contract_documents(
+        contract_id: ID,
+    ) -> DeferredMinterResult<DocumentsAnchor> {
+        Pauses::require_active(&[PausableOperation::EvmTransactions])?;
+        Self::require_can_operate_contract(&contract_id).await?;
+
+        let leaves = Self::deferred_data()
+            .get_contract_document_leaves(&contract_id)
+            .await?;
+        let tree = DocumentsMerkleTree::new(&leaves).ok_or(DeferredMinterError::Contract(
+            ContractError::ContractHasNoDocuments(contract_id.clone()),
+        ))?;
+        let root = tree.root();
+
+        // anchor root on erc721
+        let evm_rpc_client = Self::evm_rpc_client();
+        let transaction_hash = Self::deferred_erc721()
+            .anchor_documents(
+                &Self::wallet(),
+                &evm_rpc_client,
+                contract_id.clone(),
+                tree.root_hash(),
+            )
+            .await?;
+        log::debug!("anchored documents of contract {contract_id} on Ethereum");
+
+        let anchor = DocumentsAnchor {
+            contract_id: contract_id.clone(),
+            root,
+            leaves,
+            transaction_hash: format!("{transaction_hash:#x}"),
+            chain_id: Configuration::get_chain_id(),
+            anchored_at: utils::time(),
+        };
+        Self::deferred_data()
+            .set_documents_anchor(anchor.clone())
+            .await?;
+        log::info!(
+            "Documents of contract {contract_id} anchored with root {}",
+            anchor.root
+        );
+
+        Ok(anchor)
+    }
+
     /// Create a new real estate on the data canister
     pub async fn create_real_estate(real_estate: RealEstate) -> DeferredMinterResult<ID> {
+        Pauses::require_active(&[PausableOperation::RealEstateWrites])?;
         if !Inspect::inspect_is_agent(caller()) {
-            ic_cdk::trap("Unauthorized - not an agent");
+            return Err(DeferredMinterError::MissingPermission(
+                Permission::CreateContracts,
+            ));
         }
 
-        // validate if caller and agency are the same
-        if real_estate.agency != caller() {
-            ic_cdk::trap("Unauthorized - real estate agency differs from caller");
+        // validate if caller belongs to the agency
+        if !Inspect::inspect_can_operate_agency(caller(), real_estate.agency) {
+            return Err(AgencyError::CannotOperateAgency(caller(), real_estate.agency).into());
+        }
+        if Agents::is_suspended(real_estate.agency) {
+            return Err(AgencyError::AgencySuspended(real_estate.agency).into());
         }
 
         let real_estate_id = Self::deferred_data()
@@ -227,16 +641,22 @@ impl DeferredMinter {
 
     /// Delete a real estate
     pub async fn delete_real_estate(real_estate_id: ID) -> DeferredMinterResult<()> {
+        Pauses::require_active(&[PausableOperation::RealEstateWrites])?;
         if !Inspect::inspect_is_agent(caller()) {
-            ic_cdk::trap("Unauthorized");
+            return Err(DeferredMinterError::MissingPermission(
+                Permission::CreateContracts,
+            ));
         }
 
         // check if the real estate ID exists and is owned by the agency
         let real_estate = Self::deferred_data()
             .get_real_estate(real_estate_id.clone())
             .await?;
-        if real_estate.agency != caller() {
-            ic_cdk::trap("Unauthorized");
+        if !Inspect::inspect_can_operate_agency(caller(), real_estate.agency) {
+            return Err(AgencyError::CannotOperateAgency(caller(), real_estate.agency).into());
+        }
+        if Agents::is_suspended(real_estate.agency) {
+            return Err(AgencyError::AgencySuspended(real_estate.agency).into());
         }
 
         Self::deferred_data()
@@ -252,16 +672,28 @@ impl DeferredMinter {
         real_estate_id: ID,
         real_estate: RealEstate,
     ) -> DeferredMinterResult<()> {
+        Pauses::require_active(&[PausableOperation::RealEstateWrites])?;
         if !Inspect::inspect_is_agent(caller()) {
-            ic_cdk::trap("Unauthorized");
+            return Err(DeferredMinterError::MissingPermission(
+                Permission::CreateContracts,
+            ));
         }
 
         // check if the real estate ID exists and is owned by the agency
         let existing_real_estate = Self::deferred_data()
             .get_real_estate(real_estate_id.clone())
             .await?;
-        if existing_real_estate.agency != caller() {
-            ic_cdk::trap("Unauthorized");
+        if !Inspect::inspect_can_operate_agency(caller(), existing_real_estate.agency) {
+            return Err(
+                AgencyError::CannotOperateAgency(caller(), existing_real_estate.agency).into(),
+            );
+        }
+        // the real estate can't be moved to another agency
+        if real_estate.agency != existing_real_estate.agency {
+            return Err(AgencyError::CannotOperateAgency(caller(), real_estate.agency).into());
+        }
+        if Agents::is_suspended(existing_real_estate.agency) {
+            return Err(AgencyError::AgencySuspended(existing_real_estate.agency).into());
         }
 
         Self::deferred_data()
@@ -272,264 +704,1043 @@ impl DeferredMinter {
         Ok(())
     }
 
-    /// Update allowed currencies
-    pub fn admin_set_allowed_currencies(currencies: Vec<String>) {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
-        }
-
-        Configuration::set_allowed_currencies(currencies);
+    /// Propose to update the allowed currencies
+    pub fn admin_set_allowed_currencies(currencies: Vec<String>) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::SetAllowedCurrencies(currencies))
     }
 
-    /// Insert agency into the storage
-    pub fn admin_register_agency(wallet: Principal, mut agency: Agency) {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
-        }
-
+    /// Register an agency owned by `wallet`, which becomes its admin, and return its id.
+    ///
+    /// If `agency.id` is the id of a registered agency, the agency is updated instead and `wallet`
+    /// becomes its owner.
+    pub fn admin_register_agency(
+        wallet: Principal,
+        mut agency: Agency,
+    ) -> DeferredMinterResult<AgencyId> {
         // always set the wallet to the agency
         agency.owner = wallet;
-        Agents::insert_agency(wallet, agency);
+        let id = if Agents::get_agency(agency.id).is_some() {
+            let id = agency.id;
+            Agents::update_agency(agency, caller())?;
+            id
+        } else {
+            Agents::insert_agency(agency)?
+        };
         RolesManager::give_role(wallet, Role::Agent);
+        Self::schedule_member_sync();
+
+        log::info!("Agency registered: {id}, owned by {wallet}");
 
-        log::info!("Agency registered: {wallet}",);
+        Ok(id)
     }
 
-    /// Give role to the provied principal
-    pub fn admin_set_role(principal: Principal, role: Role) {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Suspend the agency.
+    ///
+    /// A suspended agency can't create contracts and real estates and its real estates are not listed.
+    /// Returns the records of the agency affected by the suspension.
+    pub async fn admin_suspend_agency(agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+        if Agents::get_agency(agency).is_none() {
+            return Err(AgencyError::AgencyNotFound(agency).into());
+        }
+        if Agents::is_suspended(agency) {
+            return Err(AgencyError::AgencySuspended(agency).into());
         }
-        RolesManager::give_role(principal, role);
 
-        log::info!("Role {role:?} given to {principal}",);
+        let report = Self::deferred_data()
+            .set_agency_status(agency, AgencyStatus::Suspended)
+            .await?;
+        Agents::set_agency_status(agency, AgencyStatus::Suspended);
+        log::info!(
+            "Agency {agency} suspended; {} real estates hidden and {} open contracts",
+            report.real_estates.len(),
+            report.open_contracts.len()
+        );
+
+        Ok(report)
     }
 
-    /// Remove role from principal.
+    /// Reactivate a suspended agency.
     ///
-    /// Fails if trying to remove the only custodian of the canister
-    pub fn admin_remove_role(principal: Principal, role: Role) -> DeferredMinterResult<()> {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Returns the records of the agency affected by the reactivation.
+    pub async fn admin_reactivate_agency(agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+        if !Agents::is_suspended(agency) {
+            return Err(AgencyError::AgencyNotSuspended(agency).into());
         }
 
-        RolesManager::remove_role(principal, role)?;
+        let report = Self::deferred_data()
+            .set_agency_status(agency, AgencyStatus::Active)
+            .await?;
+        Agents::set_agency_status(agency, AgencyStatus::Active);
+        log::info!("Agency {agency} reactivated");
+
+        Ok(report)
+    }
+
+    /// Set the storage quota of the agency on the data canister, or remove it if `None`.
+    ///
+    /// This is the only way to set a quota, and it's checked as its SNS proposal is.
+    /// The uploads which would exceed the quota are rejected by the data canister.
+    pub async fn admin_set_agency_quota(
+        agency: AgencyId,
+        quota: Option<AgencyQuota>,
+    ) -> DeferredMinterResult<()> {
+        SnsValidator::admin_set_agency_quota(agency, quota)?;
 
-        log::info!("Role {role:?} removed from {principal}",);
+        Self::deferred_data()
+            .set_agency_quota(agency, quota)
+            .await?;
+        log::info!("Agency {agency} storage quota set to {quota:?}");
 
         Ok(())
     }
 
-    /// Set custodians
-    pub fn admin_set_custodians(custodians: Vec<Principal>) -> DeferredMinterResult<()> {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Request the transfer of real estates and open contracts from an agency to another one.
+    ///
+    /// Only a custodian or an admin of one of the agencies can call this method;
+    /// the transfer is accepted on behalf of the agencies administered by the caller.
+    pub fn request_agency_transfer(
+        from: AgencyId,
+        to: AgencyId,
+        real_estates: Vec<ID>,
+        contracts: Vec<ID>,
+    ) -> DeferredMinterResult<u64> {
+        let caller = caller();
+        let from_accepted_by = Inspect::inspect_is_agency_admin(caller, from).then_some(caller);
+        let to_accepted_by = Inspect::inspect_is_agency_admin(caller, to).then_some(caller);
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && from_accepted_by.is_none()
+            && to_accepted_by.is_none()
+        {
+            return Err(AgencyError::NotAnAdmin(caller).into());
+        }
+
+        for agency in [from, to] {
+            if Agents::get_agency(agency).is_none() {
+                return Err(AgencyError::AgencyNotFound(agency).into());
+            }
+        }
+        if Agents::is_suspended(to) {
+            return Err(AgencyError::AgencySuspended(to).into());
         }
 
-        log::info!("Custodians set: {custodians:?}");
-        RolesManager::set_custodians(custodians)
+        let id = AgencyTransfers::request(
+            caller,
+            from,
+            to,
+            real_estates,
+            contracts,
+            from_accepted_by,
+            to_accepted_by,
+        )?;
+        log::info!("Agency transfer {id} from {from} to {to} requested by {caller}");
+
+        Ok(id)
     }
 
-    pub fn admin_cycles() -> Nat {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Accept a pending agency transfer on behalf of the agencies administered by the caller
+    pub fn accept_agency_transfer(id: u64) -> DeferredMinterResult<AgencyTransfer> {
+        let caller = caller();
+        let transfer =
+            AgencyTransfers::get_transfer(id).ok_or(AgencyError::TransferNotFound(id))?;
+
+        let mut accepted = None;
+        for agency in [transfer.from, transfer.to] {
+            if Inspect::inspect_is_agency_admin(caller, agency) {
+                accepted = Some(AgencyTransfers::accept(id, agency, caller)?);
+            }
         }
 
-        utils::cycles()
+        let transfer = accepted.ok_or(AgencyError::NotAnAdmin(caller))?;
+        log::info!("Agency transfer {id} accepted by {caller}");
+
+        Ok(transfer)
     }
 
-    pub fn admin_ic_logs(pagination: Pagination) -> Logs {
-        if !Inspect::inspect_is_custodian(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Reject a pending agency transfer.
+    ///
+    /// Only a custodian or an admin of one of the agencies can call this method
+    pub fn reject_agency_transfer(id: u64) -> DeferredMinterResult<()> {
+        let caller = caller();
+        let transfer =
+            AgencyTransfers::get_transfer(id).ok_or(AgencyError::TransferNotFound(id))?;
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Inspect::inspect_is_agency_admin(caller, transfer.from)
+            && !Inspect::inspect_is_agency_admin(caller, transfer.to)
+        {
+            return Err(AgencyError::NotAnAdmin(caller).into());
         }
 
-        take_memory_records(pagination.count, pagination.offset)
+        AgencyTransfers::reject(id)?;
+        log::info!("Agency transfer {id} rejected by {caller}");
+
+        Ok(())
     }
 
-    /// Set the gas price for the gas station
-    pub fn gas_station_set_gas_price(gas_price: u64) -> DeferredMinterResult<()> {
-        if !Inspect::inspect_is_gas_station(caller()) {
-            ic_cdk::trap("Unauthorized");
+    /// Approve an agency transfer accepted by both agencies, moving its records on the data canister
+    pub async fn admin_approve_agency_transfer(
+        id: u64,
+    ) -> DeferredMinterResult<AgencyTransferAuditEntry> {
+        let transfer =
+            AgencyTransfers::get_transfer(id).ok_or(AgencyError::TransferNotFound(id))?;
+        if transfer.status != AgencyTransferStatus::Pending {
+            return Err(AgencyError::TransferNotPending(id).into());
+        }
+        if !transfer.is_accepted() {
+            return Err(AgencyError::TransferNotAccepted(id).into());
+        }
+        if Agents::is_suspended(transfer.to) {
+            return Err(AgencyError::AgencySuspended(transfer.to).into());
         }
 
-        log::info!("Gas price set to {gas_price}");
+        let entry = Self::deferred_data()
+            .transfer_agency_records(transfer)
+            .await?;
+        AgencyTransfers::complete(id, caller())?;
+        log::info!(
+            "Agency transfer {id} completed: moved {} real estates and {} contracts from {} to {}",
+            entry.real_estates.len(),
+            entry.contracts.len(),
+            entry.from,
+            entry.to
+        );
 
-        Configuration::set_gas_price(gas_price)
+        Ok(entry)
     }
 
-    #[inline]
-    fn wallet() -> Wallet {
-        Wallet::new(
-            Configuration::get_ecdsa_key(),
-            Configuration::get_chain_id(),
-        )
-    }
+    /// Get an agency transfer.
+    ///
+    /// Only a custodian or a member of one of the agencies can call this method
+    pub fn get_agency_transfer(id: u64) -> DeferredMinterResult<AgencyTransfer> {
+        let caller = caller();
+        let transfer =
+            AgencyTransfers::get_transfer(id).ok_or(AgencyError::TransferNotFound(id))?;
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Inspect::inspect_is_agency_member(caller, transfer.from)
+            && !Inspect::inspect_is_agency_member(caller, transfer.to)
+        {
+            return Err(AgencyError::NotAMember(caller).into());
+        }
 
-    #[inline]
-    fn evm_rpc_client() -> EvmRpcClient {
-        EvmRpcClient::new(
-            Configuration::get_evm_rpc(),
-            Configuration::get_chain_id(),
-            Configuration::get_evm_rpc_api(),
-        )
+        Ok(transfer)
     }
 
-    #[inline]
-    fn deferred_erc721() -> DeferredErc721 {
-        DeferredErc721::from(Configuration::get_deferred_erc721_contract())
-    }
+    /// Get the transfers involving the agency.
+    ///
+    /// Only a custodian or a member of the agency can call this method
+    pub fn get_agency_transfers(agency: AgencyId) -> DeferredMinterResult<Vec<AgencyTransfer>> {
+        let caller = caller();
+        if !Inspect::inspect_has_permission(caller, Permission::ManageAgencies)
+            && !Inspect::inspect_is_agency_member(caller, agency)
+        {
+            return Err(AgencyError::NotAMember(caller).into());
+        }
 
-    #[inline]
-    fn reward_pool() -> RewardPool {
-        RewardPool::from(Configuration::get_reward_pool_contract())
+        Ok(AgencyTransfers::get_transfers(agency))
     }
 
-    #[inline]
-    fn deferred_data() -> DeferredDataClient {
-        DeferredDataClient::from(Configuration::get_deferred_data_canister())
+    /// Get the agency applications, optionally filtered by status
+    pub fn admin_agency_applications(
+        status: Option<AgencyApplicationStatus>,
+        pagination: Pagination,
+    ) -> Vec<AgencyApplication> {
+        AgencyApplications::get_applications(status, pagination)
     }
 
-    /// Create a contract from the registration data
-    fn contract_from_registration(contract_id: ID, data: ContractRegistration) -> Contract {
-        Contract {
-            id: contract_id,
-            r#type: data.r#type,
-            sellers: data.sellers,
-            buyers: data.buyers,
-            installments: data.installments,
-            value: data.value,
-            deposit: data.deposit,
-            currency: data.currency,
-            properties: data.properties,
-            restricted_properties: data.restricted_properties,
-            documents: vec![],
-            agency: caller(),
-            real_estate: data.real_estate_id,
-            expiration: data.expiration,
-            closed: false,
-        }
+    /// Add a review comment to an agency application
+    pub fn admin_comment_agency_application(id: u64, comment: String) -> DeferredMinterResult<()> {
+        AgencyApplications::comment(id, caller(), comment)
     }
-}
 
-#[cfg(test)]
-mod test {
+    /// Approve an agency application, registering the agency and giving the agent role to the applicant
+    pub fn admin_approve_agency_application(
+        id: u64,
+        comment: Option<String>,
+    ) -> DeferredMinterResult<()> {
+        let application =
+            AgencyApplications::get_application(id).ok_or(AgencyError::ApplicationNotFound(id))?;
+        if Agents::get_membership(application.applicant).is_some() {
+            return Err(AgencyError::AgencyAlreadyRegistered(application.applicant).into());
+        }
 
-    use did::deferred::{Continent, EcdsaKey, Seller};
-    use did::H160;
-    use ic_log::LogSettingsV2;
-    use pretty_assertions::assert_eq;
-    use test_utils::{alice, bob};
+        let application =
+            AgencyApplications::review(id, caller(), AgencyApplicationStatus::Approved, comment)?;
+        let wallet = application.applicant;
+        let agency = Agents::insert_agency(application.agency)?;
+        RolesManager::give_role(wallet, Role::Agent);
+        Self::schedule_member_sync();
 
-    use super::*;
-    use crate::app::test_utils::mock_real_estate;
+        log::info!("Agency application {id} approved; agency {agency} registered for {wallet}");
 
-    #[tokio::test]
-    async fn test_should_init_canister() {
-        init();
+        Ok(())
+    }
 
-        assert_eq!(
-            Configuration::get_allowed_currencies(),
-            vec!["USD".to_string()]
-        );
-        assert_eq!(Configuration::get_chain_id(), 1);
-        assert_eq!(Configuration::get_deferred_data_canister(), alice());
-        assert_eq!(
-            Configuration::get_deferred_erc721_contract(),
-            H160::from_hex_str("0xe57e761aa806c9afe7e06fb0601b17bec310f9c4").unwrap()
-        );
-        assert_eq!(Configuration::get_ecdsa_key(), EcdsaKey::Dfx);
-        assert_eq!(Configuration::get_evm_rpc(), bob());
-        assert_eq!(
-            Configuration::get_reward_pool_contract(),
-            H160::from_hex_str("0x7f4e8e4b4dabf7f5f6e7e7d3f9f5a6e7f6e7f6e7").unwrap()
-        );
-        assert_eq!(Configuration::get_gas_price(), 20_000_000_000);
+    /// Reject an agency application
+    pub fn admin_reject_agency_application(id: u64, comment: String) -> DeferredMinterResult<()> {
+        AgencyApplications::review(
+            id,
+            caller(),
+            AgencyApplicationStatus::Rejected,
+            Some(comment),
+        )?;
+        log::info!("Agency application {id} rejected");
 
-        assert!(RolesManager::is_custodian(caller()));
+        Ok(())
     }
 
+    /// Propose to give role to the provied principal.
+    ///
+    /// Fails if the role is a custom role which hasn't been defined
+    pub fn admin_set_role(principal: Principal, role: Role) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::SetRole { principal, role })
+    }
+
+    /// Propose to define a custom role granting the provided permissions, or to update the permissions of an existing one
+    pub fn admin_define_role(
+        name: String,
+        permissions: Vec<Permission>,
+    ) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::DefineRole { name, permissions })
+    }
+
+    /// Propose to delete a custom role, removing it from all the principals which had it
+    pub fn admin_delete_role(name: String) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::DeleteRole(name))
+    }
+
+    /// Get the predefined and the custom roles with the permissions they grant
+    pub fn get_roles() -> Vec<RoleDefinition> {
+        RolesManager::get_roles()
+    }
+
+    /// Get the permissions granted to the principal by its roles
+    pub fn get_permissions(principal: Principal) -> Vec<Permission> {
+        RolesManager::get_permissions(principal)
+    }
+
+    /// Propose to remove role from principal
+    pub fn admin_remove_role(principal: Principal, role: Role) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::RemoveRole { principal, role })
+    }
+
+    /// Get the current configuration
+    pub fn get_configuration() -> DeferredMinterConfiguration {
+        Configuration::get()
+    }
+
+    /// Propose to update the configuration
+    pub fn admin_update_configuration(
+        update: ConfigurationUpdate,
+    ) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::UpdateConfiguration(update))
+    }
+
+    /// Get the changes applied to the configuration, from the oldest one
+    pub fn admin_configuration_history(pagination: Pagination) -> Vec<ConfigurationChange> {
+        Configuration::get_history(pagination)
+    }
+
+    /// Get the current parameters of the contract reward formula
+    pub fn get_reward_parameters() -> RewardParameters {
+        Reward::parameters()
+    }
+
+    /// Propose to set the parameters of the contract reward formula
+    pub fn admin_set_reward_parameters(
+        parameters: RewardParameters,
+    ) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::SetRewardParameters(parameters))
+    }
+
+    /// Pause the operation on behalf of the caller custodian or of the SNS governance canister.
+    ///
+    /// Pausing is not granted by any permission, so custom roles can't pause the canister.
+    /// It takes effect immediately, while resuming requires a proposal
+    pub fn admin_pause(
+        operation: PausableOperation,
+        reason: String,
+    ) -> DeferredMinterResult<PauseState> {
+        let caller = Self::require_custodian()?;
+        Pauses::check_reason(&reason)?;
+
+        let state = Pauses::pause(operation, caller, reason);
+        log::warn!(
+            "Operation {operation:?} paused by {}: {}",
+            state.paused_by,
+            state.reason
+        );
+
+        Ok(state)
+    }
+
+    /// Propose to resume a paused operation
+    pub fn admin_resume(operation: PausableOperation) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::Resume(operation))
+    }
+
+    /// Get the paused operations
+    pub fn get_paused_operations() -> Vec<PauseState> {
+        Pauses::get_paused()
+    }
+
+    /// Propose to replace the custodians
+    pub fn admin_set_custodians(custodians: Vec<Principal>) -> DeferredMinterResult<Proposal> {
+        Self::admin_propose(ProposalAction::SetCustodians(custodians))
+    }
+
+    /// Propose an action on behalf of the caller custodian.
+    ///
+    /// The action is executed immediately if the approval of the proposer reaches the threshold
+    pub fn admin_propose(action: ProposalAction) -> DeferredMinterResult<Proposal> {
+        let caller = Self::require_custodian()?;
+        Proposals::validate_action(&action)?;
+
+        let proposal = Proposals::propose(caller, action)?;
+        log::info!(
+            "Proposal {} made by {caller}: {:?}",
+            proposal.id,
+            proposal.action
+        );
+
+        Self::execute_proposal_if_approved(proposal)
+    }
+
+    /// Approve a pending proposal, executing it if the threshold is reached
+    pub fn admin_approve_proposal(id: u64) -> DeferredMinterResult<Proposal> {
+        let caller = Self::require_custodian()?;
+
+        let proposal = Proposals::approve(id, caller)?;
+        log::info!("Proposal {id} approved by {caller}");
+
+        Self::execute_proposal_if_approved(proposal)
+    }
+
+    /// Reject a pending proposal
+    pub fn admin_reject_proposal(id: u64) -> DeferredMinterResult<Proposal> {
+        let caller = Self::require_custodian()?;
+
+        let proposal = Proposals::reject(id, caller)?;
+        log::info!("Proposal {id} rejected by {caller}");
+
+        Ok(proposal)
+    }
+
+    /// Get proposal by id
+    pub fn get_proposal(id: u64) -> Option<Proposal> {
+        Proposals::get_proposal(id)
+    }
+
+    /// Get the proposals history, optionally filtered by status, from the oldest one
+    pub fn get_proposals(status: Option<ProposalStatus>, pagination: Pagination) -> Vec<Proposal> {
+        Proposals::get_proposals(status, pagination)
+    }
+
+    /// Get the SNS governance canister allowed to govern the canister, if set
+    pub fn get_sns_governance() -> Option<Principal> {
+        RolesManager::get_sns_governance()
+    }
+
+    /// Get the approval threshold and the timeout of the proposals
+    pub fn get_proposal_config() -> ProposalConfig {
+        Proposals::config()
+    }
+
+    /// Returns the caller if it is a custodian or the SNS governance canister
+    fn require_custodian() -> DeferredMinterResult<Principal> {
+        let caller = caller();
+        if !RolesManager::is_custodian(caller) && !RolesManager::is_sns_governance(caller) {
+            return Err(ProposalError::NotACustodian(caller).into());
+        }
+
+        Ok(caller)
+    }
+
+    /// Execute the proposal if it has been approved by enough custodians or by the SNS governance
+    /// canister, which has already been voted by the DAO.
+    ///
+    /// Approvals given by principals which are not custodians anymore are not counted
+    fn execute_proposal_if_approved(proposal: Proposal) -> DeferredMinterResult<Proposal> {
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|principal| RolesManager::is_custodian(**principal))
+            .count();
+        let approved_by_sns = proposal
+            .approvals
+            .iter()
+            .any(|principal| RolesManager::is_sns_governance(*principal));
+        if !approved_by_sns && approvals < Proposals::config().threshold as usize {
+            return Ok(proposal);
+        }
+
+        match Self::execute_proposal_action(proposal.action.clone()) {
+            Ok(()) => {
+                log::info!("Proposal {} executed", proposal.id);
+                Proposals::close(proposal.id, ProposalStatus::Executed, None)
+            }
+            Err(err) => {
+                log::error!("Proposal {} failed: {err}", proposal.id);
+                Proposals::close(proposal.id, ProposalStatus::Failed, Some(err.to_string()))
+            }
+        }
+    }
+
+    fn execute_proposal_action(action: ProposalAction) -> DeferredMinterResult<()> {
+        Proposals::validate_action(&action)?;
+
+        match action {
+            ProposalAction::SetCustodians(custodians) => {
+                log::info!("Custodians set: {custodians:?}");
+                RolesManager::set_custodians(custodians)
+            }
+            ProposalAction::SetRole { principal, role } => {
+                RolesManager::give_defined_role(principal, role.clone())?;
+                log::info!("Role {role:?} given to {principal}");
+                Ok(())
+            }
+            ProposalAction::RemoveRole { principal, role } => {
+                Proposals::check_role_removal(principal, &role)?;
+                RolesManager::remove_role(principal, role.clone())?;
+                log::info!("Role {role:?} removed from {principal}");
+                Ok(())
+            }
+            ProposalAction::DefineRole { name, permissions } => {
+                RolesManager::define_role(name.clone(), permissions.clone())?;
+                log::info!("Role {name} defined with permissions {permissions:?}");
+                Ok(())
+            }
+            ProposalAction::DeleteRole(name) => {
+                RolesManager::delete_role(&name)?;
+                log::info!("Role {name} deleted");
+                Ok(())
+            }
+            ProposalAction::SetAllowedCurrencies(currencies) => {
+                log::info!("Allowed currencies set: {currencies:?}");
+                Configuration::set_allowed_currencies(currencies);
+                Ok(())
+            }
+            ProposalAction::SetProposalConfig(config) => {
+                log::info!("Proposal config set: {config:?}");
+                Proposals::set_config(config)
+            }
+            ProposalAction::SetSnsGovernance(governance) => {
+                log::info!("SNS governance set: {governance:?}");
+                RolesManager::set_sns_governance(governance)
+            }
+            ProposalAction::UpdateConfiguration(update) => {
+                for change in Configuration::update(update, caller())? {
+                    log::info!("Configuration changed: {change:?}");
+                }
+                Ok(())
+            }
+            ProposalAction::Resume(operation) => {
+                Pauses::resume(operation);
+                log::info!("Operation {operation:?} resumed");
+                Ok(())
+            }
+            ProposalAction::SetRewardParameters(parameters) => {
+                Reward::set_parameters(parameters)?;
+                log::info!("Reward parameters set: {parameters:?}");
+                Ok(())
+            }
+        }
+    }
+
+    pub fn admin_cycles() -> Nat {
+        utils::cycles()
+    }
+
+    pub fn admin_ic_logs(pagination: Pagination) -> Logs {
+        take_memory_records(pagination.count, pagination.offset)
+    }
+
+    /// Get the audit log entries matching the filter, from the oldest one
+    pub fn admin_audit_log(filter: AuditFilter, pagination: Pagination) -> Vec<AuditEntry> {
+        AuditLog::get_entries(&filter, pagination)
+    }
+
+    /// Verify the hash chain of the audit log entries in the provided page
+    pub fn admin_verify_audit_log(pagination: Pagination) -> AuditIntegrity {
+        AuditLog::verify(pagination)
+    }
+
+    /// Get the schema version of the stored collections and the pending migrations
+    pub fn admin_migration_status() -> MigrationStatus {
+        Migrations::status()
+    }
+
+    /// Get the minter view of the reward pool
+    pub fn admin_reward_pool_status() -> RewardPoolStatus {
+        RewardPoolLedger::status()
+    }
+
+    /// Get the alerts raised while reconciling the reward pool ledger with the chain
+    pub fn admin_reward_pool_alerts() -> Vec<RewardPoolAlert> {
+        RewardPoolLedger::alerts()
+    }
+
+    /// Reconcile the reward pool ledger with the chain
+    pub async fn admin_sync_reward_pool() -> DeferredMinterResult<()> {
+        RewardPoolLedger::sync(&Self::reward_pool(), &Self::evm_rpc_client()).await
+    }
+
+    /// Set the gas price for the gas station
+    pub fn gas_station_set_gas_price(gas_price: u64) -> DeferredMinterResult<()> {
+        log::info!("Gas price set to {gas_price}");
+
+        Configuration::set_gas_price(gas_price)
+    }
+
+    #[inline]
+    fn wallet() -> Wallet {
+        Wallet::new(
+            Configuration::get_ecdsa_key(),
+            Configuration::get_chain_id(),
+        )
+    }
+
+    #[inline]
+    fn evm_rpc_client() -> EvmRpcClient {
+        EvmRpcClient::new(
+            Configuration::get_evm_rpc(),
+            Configuration::get_chain_id(),
+            Configuration::get_evm_rpc_api(),
+        )
+    }
+
+    #[inline]
+    fn deferred_erc721() -> DeferredErc721 {
+        DeferredErc721::from(Configuration::get_deferred_erc721_contract())
+    }
+
+    #[inline]
+    fn reward_pool() -> RewardPool {
+        RewardPool::from(Configuration::get_reward_pool_contract())
+    }
+
+    #[inline]
+    fn deferred_data() -> DeferredDataClient {
+        DeferredDataClient::from(Configuration::get_deferred_data_canister())
+    }
+
+    /// Check whether the caller can operate the contract.
+    ///
+    /// Unless the caller can close any contract, it must be an agent of the agency of the contract
+    async fn require_can_operate_contract(contract_id: &ID) -> DeferredMinterResult<()> {
+        if Inspect::inspect_has_permission(caller(), Permission::CloseAnyContract) {
+            return Ok(());
+        }
+        if !Inspect::inspect_is_agent(caller()) {
+            return Err(DeferredMinterError::MissingPermission(
+                Permission::CloseAnyContract,
+            ));
+        }
+        log::debug!("caller is an agent");
+        let contract = Self::deferred_data().get_contract(contract_id).await?;
+        if !Inspect::inspect_can_operate_agency(caller(), contract.agency) {
+            log::debug!("caller is not the agency for the contract");
+            return Err(AgencyError::CannotOperateAgency(caller(), contract.agency).into());
+        }
+
+        Ok(())
+    }
+
+    /// Create a contract from the registration data
+    fn contract_from_registration(
+        contract_id: ID,
+        data: ContractRegistration,
+        agency: AgencyId,
+    ) -> Contract {
+        Contract {
+            id: contract_id,
+            r#type: data.r#type,
+            sellers: data.sellers,
+            buyers: data.buyers,
+            installments: data.installments,
+            value: data.value,
+            deposit: data.deposit,
+            currency: data.currency,
+            properties: data.properties,
+            restricted_properties: data.restricted_properties,
+            documents: vec![],
+            agency,
+            real_estate: data.real_estate_id,
+            expiration: data.expiration,
+            closed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::{AgencyId, Continent, EcdsaKey, Seller};
+    use did::H160;
+    use ic_log::LogSettingsV2;
+    use pretty_assertions::assert_eq;
+    use test_utils::{alice, bob, charlie};
+
+    use super::*;
+    use crate::app::test_utils::mock_real_estate;
+
     #[tokio::test]
-    #[should_panic]
-    async fn test_only_gas_station_should_set_gas_price() {
+    async fn test_should_init_canister() {
         init();
 
-        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
-        DeferredMinter::admin_set_role(caller(), Role::GasStation);
-        DeferredMinter::gas_station_set_gas_price(10_000_000_000).unwrap();
+        assert_eq!(
+            Configuration::get_allowed_currencies(),
+            vec!["USD".to_string()]
+        );
+        assert_eq!(Configuration::get_chain_id(), 1);
+        assert_eq!(Configuration::get_deferred_data_canister(), alice());
+        assert_eq!(
+            Configuration::get_deferred_erc721_contract(),
+            H160::from_hex_str("0xe57e761aa806c9afe7e06fb0601b17bec310f9c4").unwrap()
+        );
+        assert_eq!(Configuration::get_ecdsa_key(), EcdsaKey::Dfx);
+        assert_eq!(Configuration::get_evm_rpc(), bob());
+        assert_eq!(
+            Configuration::get_reward_pool_contract(),
+            H160::from_hex_str("0x7f4e8e4b4dabf7f5f6e7e7d3f9f5a6e7f6e7f6e7").unwrap()
+        );
+        assert_eq!(Configuration::get_gas_price(), 20_000_000_000);
+
+        assert!(RolesManager::is_custodian(caller()));
+    }
+
+    #[tokio::test]
+    async fn test_should_update_configuration_on_upgrade() {
+        init();
+
+        DeferredMinter::post_upgrade(None);
+        assert_eq!(Configuration::get_chain_id(), 1);
+
+        DeferredMinter::post_upgrade(Some(ConfigurationUpdate {
+            chain_id: Some(11155111),
+            evm_rpc: Some(charlie()),
+            ..Default::default()
+        }));
+        let configuration = DeferredMinter::get_configuration();
+        assert_eq!(configuration.chain_id, 11155111);
+        assert_eq!(configuration.evm_rpc, charlie());
+        assert_eq!(configuration.deferred_data, alice());
+
+        let history = DeferredMinter::admin_configuration_history(Pagination {
+            offset: 0,
+            count: 10,
+        });
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].old_value, bob().to_string());
+        assert_eq!(history[1].new_value, charlie().to_string());
+    }
+
+    #[tokio::test]
+    async fn test_should_update_configuration_with_proposal() {
+        init();
+
+        let update = ConfigurationUpdate {
+            evm_rpc_api: Some("https://api.ethereum.org".to_string()),
+            ..Default::default()
+        };
+        let proposal = DeferredMinter::admin_update_configuration(update).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert_eq!(
+            DeferredMinter::get_configuration().evm_rpc_api,
+            Some("https://api.ethereum.org".to_string())
+        );
+
+        assert!(
+            DeferredMinter::admin_update_configuration(ConfigurationUpdate::default()).is_err()
+        );
+        assert_eq!(
+            DeferredMinter::admin_update_configuration(ConfigurationUpdate {
+                deferred_erc721: Some(H160::zero()),
+                ..Default::default()
+            })
+            .unwrap_err(),
+            DeferredMinterError::Configuration(ConfigurationError::ZeroAddress(
+                "deferred erc721 contract".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_set_reward_parameters_with_proposal() {
+        init();
+
+        let parameters = RewardParameters {
+            rmc: DeferredMinter::get_reward_parameters().rmc / 2.0,
+            avidity: 0.5,
+            next_halving: crate::utils::time() + 1_000,
+        };
+        let proposal = DeferredMinter::admin_set_reward_parameters(parameters).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert_eq!(DeferredMinter::get_reward_parameters(), parameters);
+
+        assert!(matches!(
+            DeferredMinter::admin_set_reward_parameters(RewardParameters {
+                avidity: 2.0,
+                ..parameters
+            }),
+            Err(DeferredMinterError::Configuration(
+                ConfigurationError::InvalidRewardParameters(_)
+            ))
+        ));
+        assert_eq!(DeferredMinter::get_reward_parameters(), parameters);
     }
 
     #[tokio::test]
     async fn test_should_set_gas_price() {
         init();
 
-        DeferredMinter::admin_set_role(caller(), Role::GasStation);
+        DeferredMinter::admin_set_role(caller(), Role::GasStation).unwrap();
 
         DeferredMinter::gas_station_set_gas_price(10_000_000_000).unwrap();
 
         assert_eq!(Configuration::get_gas_price(), 10_000_000_000);
     }
 
+    #[tokio::test]
+    async fn test_should_give_custom_role() {
+        init();
+
+        let auditor = Role::Custom("auditor".to_string());
+        assert_eq!(
+            DeferredMinter::admin_set_role(bob(), auditor.clone()),
+            Err(DeferredMinterError::Configuration(
+                ConfigurationError::UnknownRole("auditor".to_string())
+            ))
+        );
+
+        DeferredMinter::admin_define_role("auditor".to_string(), vec![Permission::ReadLogs])
+            .unwrap();
+        DeferredMinter::admin_set_role(bob(), auditor.clone()).unwrap();
+        assert_eq!(
+            DeferredMinter::get_permissions(bob()),
+            vec![Permission::ReadLogs]
+        );
+        assert!(DeferredMinter::get_roles().contains(&RoleDefinition {
+            role: auditor,
+            permissions: vec![Permission::ReadLogs],
+        }));
+
+        DeferredMinter::admin_delete_role("auditor".to_string()).unwrap();
+        assert!(DeferredMinter::get_permissions(bob()).is_empty());
+    }
+
     #[tokio::test]
     async fn test_should_set_allowed_currencies() {
         init();
 
-        DeferredMinter::admin_set_allowed_currencies(vec!["EUR".to_string()]);
+        let proposal =
+            DeferredMinter::admin_set_allowed_currencies(vec!["EUR".to_string()]).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+
+        assert_eq!(
+            Configuration::get_allowed_currencies(),
+            vec!["EUR".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_execute_proposal_once_threshold_is_reached() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![caller(), alice(), bob()]).unwrap();
+        let proposal =
+            DeferredMinter::admin_propose(ProposalAction::SetProposalConfig(ProposalConfig {
+                threshold: 2,
+                timeout: 60_000_000_000,
+            }))
+            .unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert_eq!(DeferredMinter::get_proposal_config().threshold, 2);
+
+        // the approval of the proposer is not enough anymore
+        let proposal =
+            DeferredMinter::admin_set_allowed_currencies(vec!["EUR".to_string()]).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        assert_eq!(
+            Configuration::get_allowed_currencies(),
+            vec!["USD".to_string()]
+        );
+        assert_eq!(
+            DeferredMinter::admin_approve_proposal(proposal.id),
+            Err(ProposalError::AlreadyApproved(proposal.id).into())
+        );
 
+        // approved by another custodian
+        let proposal = Proposals::approve(proposal.id, alice()).unwrap();
+        let proposal = DeferredMinter::execute_proposal_if_approved(proposal).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
         assert_eq!(
             Configuration::get_allowed_currencies(),
             vec!["EUR".to_string()]
         );
+        assert_eq!(
+            DeferredMinter::get_proposals(Some(ProposalStatus::Executed), pagination()).len(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_reject_proposal() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![caller(), alice()]).unwrap();
+        Proposals::set_config(ProposalConfig {
+            threshold: 2,
+            timeout: 60_000_000_000,
+        })
+        .unwrap();
+
+        let proposal = DeferredMinter::admin_set_role(bob(), Role::GasStation).unwrap();
+        let proposal = DeferredMinter::admin_reject_proposal(proposal.id).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Rejected);
+        assert_eq!(proposal.rejected_by, Some(caller()));
+        assert!(!RolesManager::has_role(bob(), &Role::GasStation));
+        assert_eq!(
+            DeferredMinter::get_proposal(proposal.id),
+            Some(proposal.clone())
+        );
+        assert_eq!(
+            DeferredMinter::admin_approve_proposal(proposal.id),
+            Err(ProposalError::ProposalNotPending(proposal.id).into())
+        );
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn test_only_custodian_should_set_allowed_currencies() {
+    async fn test_should_not_propose_invalid_actions() {
         init();
 
+        assert_eq!(
+            DeferredMinter::admin_set_custodians(vec![]),
+            Err(ConfigurationError::CustodialsCantBeEmpty.into())
+        );
+        assert_eq!(
+            DeferredMinter::admin_set_allowed_currencies(vec!["TOOLONGCURRENCY".to_string()]),
+            Err(
+                ProposalError::InvalidProposal("invalid currency TOOLONGCURRENCY".to_string())
+                    .into()
+            )
+        );
+        assert_eq!(
+            DeferredMinter::admin_propose(ProposalAction::SetProposalConfig(ProposalConfig {
+                threshold: 2,
+                timeout: 60_000_000_000,
+            })),
+            Err(ProposalError::InvalidThreshold(2).into())
+        );
+        assert_eq!(
+            DeferredMinter::admin_propose(ProposalAction::SetProposalConfig(ProposalConfig {
+                threshold: 0,
+                timeout: 60_000_000_000,
+            })),
+            Err(ProposalError::InvalidThreshold(0).into())
+        );
+        assert!(DeferredMinter::get_proposals(None, pagination()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_should_only_allow_custodians_to_propose() {
+        init();
         DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
-        DeferredMinter::admin_set_allowed_currencies(vec!["EUR".to_string()]);
+
+        assert_eq!(
+            DeferredMinter::admin_set_role(bob(), Role::GasStation),
+            Err(ProposalError::NotACustodian(caller()).into())
+        );
+        assert_eq!(
+            DeferredMinter::admin_approve_proposal(1),
+            Err(ProposalError::NotACustodian(caller()).into())
+        );
+        assert_eq!(
+            DeferredMinter::admin_reject_proposal(1),
+            Err(ProposalError::NotACustodian(caller()).into())
+        );
     }
 
     #[tokio::test]
-    async fn test_should_register_agency() {
+    async fn test_should_not_remove_custodians_below_threshold() {
         init();
+        DeferredMinter::admin_set_custodians(vec![caller(), alice()]).unwrap();
+        Proposals::set_config(ProposalConfig {
+            threshold: 2,
+            timeout: 60_000_000_000,
+        })
+        .unwrap();
 
-        DeferredMinter::admin_register_agency(
-            bob(),
-            Agency {
-                name: "Agency".to_string(),
-                owner: bob(),
-                lat: None,
-                lng: None,
-                address: String::default(),
-                agent: String::default(),
-                city: String::default(),
-                continent: Continent::Antarctica,
-                country: String::default(),
-                email: String::default(),
-                logo: None,
-                mobile: String::default(),
-                region: String::default(),
-                vat: String::default(),
-                website: String::default(),
-                zip_code: String::default(),
-            },
+        assert_eq!(
+            DeferredMinter::admin_propose(ProposalAction::RemoveRole {
+                principal: alice(),
+                role: Role::Custodian,
+            }),
+            Err(ProposalError::InvalidThreshold(2).into())
         );
+        assert!(RolesManager::is_custodian(alice()));
+    }
 
-        let agencies = DeferredMinter::get_agencies();
-        assert_eq!(agencies.len(), 1);
-        assert_eq!(agencies[0].owner, bob());
+    #[tokio::test]
+    async fn test_should_remove_role_through_proposal() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![caller(), alice()]).unwrap();
+
+        let proposal = DeferredMinter::admin_remove_role(alice(), Role::Custodian).unwrap();
+        assert_eq!(
+            proposal.action,
+            ProposalAction::RemoveRole {
+                principal: alice(),
+                role: Role::Custodian,
+            }
+        );
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert!(!RolesManager::is_custodian(alice()));
+    }
+
+    #[tokio::test]
+    async fn test_should_not_change_roles_before_proposal_is_approved() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![caller(), alice()]).unwrap();
+        DeferredMinter::admin_define_role("auditor".to_string(), vec![Permission::ReadLogs])
+            .unwrap();
+        let auditor = Role::Custom("auditor".to_string());
+        DeferredMinter::admin_set_role(bob(), auditor.clone()).unwrap();
+        Proposals::set_config(ProposalConfig {
+            threshold: 2,
+            timeout: 60_000_000_000,
+        })
+        .unwrap();
+
+        // the permissions of an assigned role can't be changed by a single custodian
+        let proposal = DeferredMinter::admin_define_role(
+            "auditor".to_string(),
+            vec![Permission::ReadLogs, Permission::ManageRoles],
+        )
+        .unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        assert_eq!(
+            DeferredMinter::get_permissions(bob()),
+            vec![Permission::ReadLogs]
+        );
+
+        let proposal = DeferredMinter::admin_remove_role(bob(), auditor.clone()).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        let proposal = DeferredMinter::admin_delete_role("auditor".to_string()).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        assert!(RolesManager::has_role(bob(), &auditor));
+        assert!(RolesManager::is_defined(&auditor));
+
+        assert_eq!(
+            DeferredMinter::admin_delete_role("unknown".to_string()),
+            Err(ConfigurationError::UnknownRole("unknown".to_string()).into())
+        );
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn test_only_custodian_should_register_agency() {
+    async fn test_should_register_agency() {
         init();
 
-        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
-        DeferredMinter::admin_register_agency(
+        let id = DeferredMinter::admin_register_agency(
             bob(),
             Agency {
+                id: AgencyId::default(),
                 name: "Agency".to_string(),
                 owner: bob(),
                 lat: None,
@@ -547,16 +1758,36 @@ mod test {
                 website: String::default(),
                 zip_code: String::default(),
             },
+        )
+        .expect("failed to register agency");
+
+        let agencies = DeferredMinter::get_agencies();
+        assert_eq!(agencies.len(), 1);
+        assert_eq!(agencies[0].id, id);
+        assert_eq!(agencies[0].owner, bob());
+        assert_eq!(DeferredMinter::get_member_agency(bob()).unwrap().id, id);
+
+        // registering again with the id updates the agency
+        let mut agency = agencies[0].clone();
+        agency.name = "New name".to_string();
+        assert_eq!(
+            DeferredMinter::admin_register_agency(charlie(), agency),
+            Ok(id)
         );
+        let agency = DeferredMinter::get_agency(id).unwrap();
+        assert_eq!(agency.name, "New name");
+        assert_eq!(agency.owner, charlie());
+        assert_eq!(DeferredMinter::get_agencies().len(), 1);
     }
 
     #[tokio::test]
     async fn test_should_remove_agency() {
         init();
 
-        DeferredMinter::admin_register_agency(
+        let id = DeferredMinter::admin_register_agency(
             bob(),
             Agency {
+                id: AgencyId::default(),
                 name: "Agency".to_string(),
                 owner: bob(),
                 lat: None,
@@ -574,22 +1805,35 @@ mod test {
                 website: String::default(),
                 zip_code: String::default(),
             },
-        );
+        )
+        .unwrap();
 
-        DeferredMinter::remove_agency(bob()).expect("failed to remove agency");
+        let report = DeferredMinter::remove_agency(id)
+            .await
+            .expect("failed to remove agency");
+        assert_eq!(report.agency, id);
+        assert!(DeferredMinter::get_member_agency(bob()).is_none());
+        assert_eq!(DeferredMinter::get_agency_status(id), AgencyStatus::Active);
+        assert!(!RolesManager::has_role(bob(), &Role::Agent));
+
+        // already removed
+        assert_eq!(
+            DeferredMinter::remove_agency(id).await.unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::AgencyNotFound(id))
+        );
 
         let agencies = DeferredMinter::get_agencies();
         assert_eq!(agencies.len(), 0);
     }
 
     #[tokio::test]
-    #[should_panic]
     async fn test_only_custodian_should_remove_agency() {
         init();
 
-        DeferredMinter::admin_register_agency(
+        let id = DeferredMinter::admin_register_agency(
             bob(),
             Agency {
+                id: AgencyId::default(),
                 name: "Agency".to_string(),
                 owner: bob(),
                 lat: None,
@@ -607,10 +1851,412 @@ mod test {
                 website: String::default(),
                 zip_code: String::default(),
             },
+        )
+        .unwrap();
+
+        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
+        assert_eq!(
+            DeferredMinter::remove_agency(id).await.unwrap_err(),
+            DeferredMinterError::MissingPermission(Permission::ManageAgencies)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_update_agency() {
+        init();
+        let id = DeferredMinter::admin_register_agency(
+            caller(),
+            Agency {
+                name: "Agency".to_string(),
+                email: "info@agency.com".to_string(),
+                vat: "IT0123456789".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        DeferredMinter::update_agency(AgencyUpdate {
+            email: Some("sales@agency.com".to_string()),
+            website: Some("https://agency.com".to_string()),
+            lat: Some("45.46".to_string()),
+            lng: Some("9.19".to_string()),
+            ..Default::default()
+        })
+        .expect("failed to update agency");
+
+        let agency = DeferredMinter::get_agency(id).unwrap();
+        assert_eq!(agency.email, "sales@agency.com");
+        assert_eq!(agency.website, "https://agency.com");
+        assert_eq!(agency.vat, "IT0123456789");
+        assert_eq!(agency.owner, caller());
+
+        let history = DeferredMinter::get_agency_history(id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].author, caller());
+        assert_eq!(history[0].changes.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_should_not_update_agency_with_invalid_fields() {
+        init();
+        let id = DeferredMinter::admin_register_agency(
+            caller(),
+            Agency {
+                name: "Agency".to_string(),
+                email: "info@agency.com".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            DeferredMinter::update_agency(AgencyUpdate {
+                lat: Some("120".to_string()),
+                ..Default::default()
+            })
+            .unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::InvalidLatitude("120".to_string()))
+        );
+        assert!(DeferredMinter::get_agency_history(id).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_should_not_update_agency_if_not_owner() {
+        init();
+        DeferredMinter::admin_register_agency(bob(), Agency::default()).unwrap();
+
+        assert_eq!(
+            DeferredMinter::update_agency(AgencyUpdate::default()).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::NotTheOwner(caller()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_approve_agency_application() {
+        init();
+
+        let id = DeferredMinter::submit_agency_application(
+            Agency {
+                name: "Agency".to_string(),
+                email: "info@agency.com".to_string(),
+                ..Default::default()
+            },
+            vec![AgencyApplicationDocument {
+                data: vec![1, 2, 3],
+                mime_type: "application/pdf".to_string(),
+                name: "registration.pdf".to_string(),
+            }],
+        )
+        .expect("failed to submit application");
+
+        let queue = DeferredMinter::admin_agency_applications(
+            Some(AgencyApplicationStatus::Pending),
+            Pagination {
+                offset: 0,
+                count: 10,
+            },
+        );
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].id, id);
+        assert_eq!(queue[0].agency.owner, caller());
+        assert_eq!(queue[0].documents[0].size, 3);
+        assert_eq!(
+            DeferredMinter::get_agency_application_document(id, 0)
+                .unwrap()
+                .data,
+            vec![1, 2, 3]
+        );
+
+        DeferredMinter::admin_comment_agency_application(id, "looks good".to_string()).unwrap();
+        DeferredMinter::admin_approve_agency_application(id, None).unwrap();
+
+        let application = DeferredMinter::get_agency_application(id).unwrap();
+        assert_eq!(application.status, AgencyApplicationStatus::Approved);
+        assert_eq!(application.comments.len(), 1);
+        let agency = DeferredMinter::get_member_agency(caller()).unwrap();
+        assert_eq!(agency.owner, caller());
+        assert_eq!(
+            Agents::get_membership(caller()).unwrap().role,
+            AgencyRole::Admin
+        );
+        assert!(RolesManager::has_role(caller(), &Role::Agent));
+    }
+
+    #[tokio::test]
+    async fn test_should_reject_agency_application() {
+        init();
+
+        let id = DeferredMinter::submit_agency_application(
+            Agency {
+                name: "Agency".to_string(),
+                email: "info@agency.com".to_string(),
+                ..Default::default()
+            },
+            vec![],
+        )
+        .expect("failed to submit application");
+
+        DeferredMinter::admin_reject_agency_application(id, "missing documents".to_string())
+            .unwrap();
+
+        let application = DeferredMinter::get_agency_application(id).unwrap();
+        assert_eq!(application.status, AgencyApplicationStatus::Rejected);
+        assert_eq!(application.comments[0].comment, "missing documents");
+        assert!(DeferredMinter::get_member_agency(caller()).is_none());
+        assert!(!RolesManager::has_role(caller(), &Role::Agent));
+
+        assert_eq!(
+            DeferredMinter::admin_approve_agency_application(id, None).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::ApplicationAlreadyReviewed(id))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_not_submit_agency_application_if_registered() {
+        init();
+        register_agency();
+
+        assert_eq!(
+            DeferredMinter::submit_agency_application(Agency::default(), vec![]).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::AgencyAlreadyRegistered(caller()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_manage_agency_members() {
+        init();
+        let agency = register_agency();
+        let other = DeferredMinter::admin_register_agency(charlie(), Agency::default()).unwrap();
+
+        DeferredMinter::add_agency_member(agency, bob(), AgencyRole::Agent)
+            .await
+            .expect("failed to add member");
+        assert_eq!(DeferredMinter::get_agency_members(agency).unwrap().len(), 2);
+
+        // can't join another agency
+        assert_eq!(
+            DeferredMinter::add_agency_member(other, bob(), AgencyRole::Agent).await,
+            Err(DeferredMinterError::Agency(AgencyError::AlreadyMember(
+                bob()
+            )))
+        );
+
+        // the last admin can't be removed or demoted
+        assert_eq!(
+            DeferredMinter::remove_agency_member(agency, caller()).await,
+            Err(DeferredMinterError::Agency(AgencyError::LastAdmin))
+        );
+        assert_eq!(
+            DeferredMinter::add_agency_member(agency, caller(), AgencyRole::Agent).await,
+            Err(DeferredMinterError::Agency(AgencyError::LastAdmin))
+        );
+
+        DeferredMinter::remove_agency_member(agency, bob())
+            .await
+            .expect("failed to remove member");
+        assert_eq!(
+            DeferredMinter::remove_agency_member(agency, bob()).await,
+            Err(DeferredMinterError::Agency(AgencyError::NotAMember(bob())))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_agency_admin_should_add_members() {
+        init();
+        let agency = DeferredMinter::admin_register_agency(charlie(), Agency::default()).unwrap();
+
+        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
+        assert_eq!(
+            DeferredMinter::add_agency_member(agency, bob(), AgencyRole::Agent).await,
+            Err(DeferredMinterError::Agency(AgencyError::NotAnAdmin(
+                caller()
+            )))
+        );
+        assert_eq!(
+            DeferredMinter::get_agency_members(agency),
+            Err(DeferredMinterError::Agency(AgencyError::NotAMember(
+                caller()
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_suspend_agency() {
+        init();
+        let agency = register_agency();
+
+        let report = DeferredMinter::admin_suspend_agency(agency)
+            .await
+            .expect("failed to suspend agency");
+        assert_eq!(report.agency, agency);
+        assert_eq!(
+            DeferredMinter::get_agency_status(agency),
+            AgencyStatus::Suspended
+        );
+        assert_eq!(
+            DeferredMinter::admin_suspend_agency(agency).await,
+            Err(DeferredMinterError::Agency(AgencyError::AgencySuspended(
+                agency
+            )))
+        );
+
+        // suspended agency can't create or change listings and contracts
+        assert_eq!(
+            DeferredMinter::create_real_estate(mock_real_estate()).await,
+            Err(DeferredMinterError::Agency(AgencyError::AgencySuspended(
+                agency
+            )))
+        );
+        assert_eq!(
+            DeferredMinter::update_real_estate(1u64.into(), mock_real_estate()).await,
+            Err(DeferredMinterError::Agency(AgencyError::AgencySuspended(
+                agency
+            )))
+        );
+        assert_eq!(
+            DeferredMinter::delete_real_estate(1u64.into()).await,
+            Err(DeferredMinterError::Agency(AgencyError::AgencySuspended(
+                agency
+            )))
+        );
+        assert_eq!(
+            DeferredMinter::create_contract(ContractRegistration {
+                value: 400_000,
+                installments: 400_000 / 100,
+                currency: "USD".to_string(),
+                buyers: vec![
+                    H160::from_hex_str("0x7f4e8e4b4dabf7f5f6e7e7d3f9f5a6e7f6e7f6e7").unwrap()
+                ],
+                sellers: vec![Seller {
+                    address: H160::from_hex_str("0x7f4e8e4b4dabf7f5f6e7e7d3f9f5a6e7f6e7f6e7")
+                        .unwrap(),
+                    quota: 100,
+                }],
+                expiration: String::from("2050-01-01"),
+                token_value: 100,
+                ..Default::default()
+            })
+            .await,
+            Err(DeferredMinterError::Agency(AgencyError::AgencySuspended(
+                agency
+            )))
+        );
+
+        DeferredMinter::admin_reactivate_agency(agency)
+            .await
+            .expect("failed to reactivate agency");
+        assert_eq!(
+            DeferredMinter::get_agency_status(agency),
+            AgencyStatus::Active
+        );
+        assert_eq!(
+            DeferredMinter::admin_reactivate_agency(agency).await,
+            Err(DeferredMinterError::Agency(
+                AgencyError::AgencyNotSuspended(agency)
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_set_agency_quota() {
+        init();
+        let quota = AgencyQuota {
+            max_documents: 100,
+            max_document_bytes: 1 << 30,
+        };
+        assert_eq!(
+            DeferredMinter::admin_set_agency_quota(AgencyId(1), Some(quota)).await,
+            Err(DeferredMinterError::Agency(AgencyError::AgencyNotFound(
+                AgencyId(1)
+            )))
+        );
+
+        let agency = register_agency();
+        assert!(DeferredMinter::admin_set_agency_quota(agency, Some(quota))
+            .await
+            .is_ok());
+        assert!(DeferredMinter::admin_set_agency_quota(agency, None)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_should_transfer_agency_records() {
+        init();
+        let agency = register_agency();
+        let other = DeferredMinter::admin_register_agency(charlie(), Agency::default()).unwrap();
+
+        // caller is admin of the losing agency
+        let id = DeferredMinter::request_agency_transfer(
+            agency,
+            other,
+            vec![1u64.into()],
+            vec![2u64.into()],
+        )
+        .expect("failed to request transfer");
+        let transfer = DeferredMinter::get_agency_transfer(id).unwrap();
+        assert_eq!(transfer.from_accepted_by, Some(caller()));
+        assert_eq!(transfer.to_accepted_by, None);
+
+        assert_eq!(
+            DeferredMinter::admin_approve_agency_transfer(id).await,
+            Err(DeferredMinterError::Agency(
+                AgencyError::TransferNotAccepted(id)
+            ))
+        );
+
+        // charlie, the admin of the gaining agency, accepts
+        let transfer = AgencyTransfers::accept(id, other, charlie()).unwrap();
+        assert!(transfer.is_accepted());
+
+        let entry = DeferredMinter::admin_approve_agency_transfer(id)
+            .await
+            .expect("failed to approve transfer");
+        assert_eq!(entry.transfer_id, id);
+        assert_eq!(entry.to, other);
+        assert_eq!(
+            DeferredMinter::get_agency_transfers(other).unwrap()[0].status,
+            AgencyTransferStatus::Completed
         );
+    }
+
+    #[tokio::test]
+    async fn test_should_accept_agency_transfer_as_admin_of_gaining_agency() {
+        init();
+        let agency = DeferredMinter::admin_register_agency(bob(), Agency::default()).unwrap();
+        let other = register_agency();
+
+        // requested by bob, the admin of the losing agency
+        let id = AgencyTransfers::request(
+            bob(),
+            agency,
+            other,
+            vec![1u64.into()],
+            vec![],
+            Some(bob()),
+            None,
+        )
+        .unwrap();
+
+        let transfer = DeferredMinter::accept_agency_transfer(id).unwrap();
+        assert_eq!(transfer.from_accepted_by, Some(bob()));
+        assert_eq!(transfer.to_accepted_by, Some(caller()));
+        assert!(transfer.is_accepted());
+    }
 
+    #[tokio::test]
+    async fn test_only_agency_admin_should_request_transfer() {
+        init();
+        let agency = DeferredMinter::admin_register_agency(bob(), Agency::default()).unwrap();
+        let other = DeferredMinter::admin_register_agency(charlie(), Agency::default()).unwrap();
         DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
-        DeferredMinter::remove_agency(bob()).expect("failed to remove agency");
+
+        assert_eq!(
+            DeferredMinter::request_agency_transfer(agency, other, vec![1u64.into()], vec![]),
+            Err(DeferredMinterError::Agency(AgencyError::NotAnAdmin(
+                caller()
+            )))
+        );
     }
 
     #[tokio::test]
@@ -639,6 +2285,27 @@ mod test {
         assert_eq!(contract_id, 1u64);
 
         assert_eq!(ContractId::get_next_contract_id(), 2u64);
+
+        // reward should have been reserved
+        let reservation =
+            RewardPoolLedger::get_reservation(&contract_id).expect("reservation not recorded");
+        assert_eq!(reservation.tokens, 4_000);
+        let status = DeferredMinter::admin_reward_pool_status();
+        assert_eq!(status.pending_reserved, reservation.total());
+    }
+
+    #[tokio::test]
+    async fn test_should_sync_reward_pool() {
+        init();
+
+        DeferredMinter::admin_sync_reward_pool()
+            .await
+            .expect("failed to sync reward pool");
+
+        let status = DeferredMinter::admin_reward_pool_status();
+        assert_eq!(status.available_reward, 592_006_734_000_000);
+        assert_eq!(status.last_synced_block, 21_000_000);
+        assert!(DeferredMinter::admin_reward_pool_alerts().is_empty());
     }
 
     #[tokio::test]
@@ -650,6 +2317,22 @@ mod test {
             .expect("failed to close contract");
     }
 
+    #[tokio::test]
+    async fn test_should_anchor_contract_documents() {
+        init();
+
+        let anchor = DeferredMinter::anchor_contract_documents(1u64.into())
+            .await
+            .expect("failed to anchor contract documents");
+        assert_eq!(anchor.contract_id, Nat::from(1u64));
+        assert_eq!(anchor.leaves.len(), 2);
+        assert_eq!(
+            anchor.root,
+            DocumentsMerkleTree::new(&anchor.leaves).unwrap().root()
+        );
+        assert_eq!(anchor.transaction_hash, format!("0x{}", "00".repeat(32)));
+    }
+
     #[tokio::test]
     async fn test_should_create_real_estate() {
         init();
@@ -661,6 +2344,69 @@ mod test {
             .expect("failed to create real estate");
     }
 
+    #[tokio::test]
+    async fn test_should_pause_and_resume_operations() {
+        init();
+        register_agency();
+
+        let state = DeferredMinter::admin_pause(
+            PausableOperation::RealEstateWrites,
+            "compromised data canister".to_string(),
+        )
+        .unwrap();
+        assert_eq!(state.paused_by, caller());
+        assert_eq!(DeferredMinter::get_paused_operations(), vec![state]);
+        assert_eq!(
+            DeferredMinter::create_real_estate(mock_real_estate()).await,
+            Err(DeferredMinterError::Paused(
+                PausableOperation::RealEstateWrites
+            ))
+        );
+        assert_eq!(
+            DeferredMinter::delete_real_estate(1u64.into()).await,
+            Err(DeferredMinterError::Paused(
+                PausableOperation::RealEstateWrites
+            ))
+        );
+
+        // other operations are not affected, while paused transactions stop the contracts
+        DeferredMinter::admin_pause(PausableOperation::EvmTransactions, "bug".to_string()).unwrap();
+        assert_eq!(
+            DeferredMinter::close_contract(1u64.into()).await,
+            Err(DeferredMinterError::Paused(
+                PausableOperation::EvmTransactions
+            ))
+        );
+
+        let proposal = DeferredMinter::admin_resume(PausableOperation::RealEstateWrites).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert!(DeferredMinter::create_real_estate(mock_real_estate())
+            .await
+            .is_ok());
+        // can't resume an operation which is not paused
+        assert!(DeferredMinter::admin_resume(PausableOperation::RealEstateWrites).is_err());
+    }
+
+    #[test]
+    fn test_should_only_allow_custodians_and_sns_to_pause() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
+        RolesManager::define_role("operator".to_string(), vec![Permission::ManageConfig]).unwrap();
+        RolesManager::give_role(caller(), Role::Custom("operator".to_string()));
+
+        assert_eq!(
+            DeferredMinter::admin_pause(PausableOperation::ContractCreation, "bug".to_string()),
+            Err(ProposalError::NotACustodian(caller()).into())
+        );
+        assert!(DeferredMinter::get_paused_operations().is_empty());
+
+        RolesManager::set_sns_governance(Some(caller())).unwrap();
+        let state =
+            DeferredMinter::admin_pause(PausableOperation::ContractCreation, "bug".to_string())
+                .unwrap();
+        assert_eq!(state.paused_by, caller());
+    }
+
     #[tokio::test]
     async fn test_should_delete_real_estate() {
         init();
@@ -692,10 +2438,76 @@ mod test {
             .expect("failed to update real estate");
     }
 
-    fn register_agency() {
+    #[tokio::test]
+    async fn test_should_not_close_contract_without_permission() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
+
+        assert_eq!(
+            DeferredMinter::close_contract(1u64.into())
+                .await
+                .unwrap_err(),
+            DeferredMinterError::MissingPermission(Permission::CloseAnyContract)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_not_operate_real_estates_without_permission() {
+        init();
+        DeferredMinter::admin_set_custodians(vec![alice()]).unwrap();
+
+        let missing_permission =
+            DeferredMinterError::MissingPermission(Permission::CreateContracts);
+        assert_eq!(
+            DeferredMinter::create_real_estate(mock_real_estate())
+                .await
+                .unwrap_err(),
+            missing_permission
+        );
+        assert_eq!(
+            DeferredMinter::update_real_estate(1u64.into(), mock_real_estate())
+                .await
+                .unwrap_err(),
+            missing_permission
+        );
+        assert_eq!(
+            DeferredMinter::delete_real_estate(1u64.into())
+                .await
+                .unwrap_err(),
+            missing_permission
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_not_move_real_estate_to_another_agency() {
+        init();
+        register_agency();
+
+        let real_estate_id = DeferredMinter::create_real_estate(mock_real_estate())
+            .await
+            .expect("failed to create real estate");
+
+        let mut prop = mock_real_estate();
+        prop.agency = AgencyId(2);
+        assert_eq!(
+            DeferredMinter::update_real_estate(real_estate_id, prop)
+                .await
+                .unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::CannotOperateAgency(caller(), AgencyId(2)))
+        );
+    }
+
+    fn register_agency() -> AgencyId {
         let agency = Agency::default();
 
-        DeferredMinter::admin_register_agency(caller(), agency);
+        DeferredMinter::admin_register_agency(caller(), agency).expect("failed to register agency")
+    }
+
+    fn pagination() -> Pagination {
+        Pagination {
+            offset: 0,
+            count: 10,
+        }
     }
 
     fn init() {
@@ -710,6 +2522,7 @@ mod test {
             evm_rpc: bob(),
             evm_rpc_api: None,
             reward_pool: H160::from_hex_str("0x7f4e8e4b4dabf7f5f6e7e7d3f9f5a6e7f6e7f6e7").unwrap(),
+            reward_pool_deploy_block: 20_998_000,
             log_settings: LogSettingsV2 {
                 enable_console: true,
                 log_filter: "debug".to_string(),
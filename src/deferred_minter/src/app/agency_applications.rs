@@ -0,0 +1,466 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use did::deferred::{
+    Agency, AgencyApplication, AgencyApplicationComment, AgencyApplicationDocument,
+    AgencyApplicationDocumentInfo, AgencyApplicationStatus, AgencyError, DeferredMinterError,
+    DeferredMinterResult,
+};
+use ic_log::did::Pagination;
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
+
+use crate::app::memory::{
+    AGENCY_APPLICATIONS_MEMORY_ID, AGENCY_APPLICATION_DOCUMENTS_MEMORY_ID, MEMORY_MANAGER,
+    NEXT_AGENCY_APPLICATION_ID_MEMORY_ID,
+};
+use crate::utils::time;
+
+/// Maximum size of the documents attached to an application (1.5MB)
+pub const MAX_APPLICATION_DOCUMENTS_SIZE: u64 = 1_536 * 1024;
+/// Maximum number of applications waiting for a review
+const MAX_PENDING_APPLICATIONS: usize = 100;
+/// Maximum number of applications submitted by the same principal
+const MAX_APPLICATIONS_PER_APPLICANT: usize = 3;
+/// Time after which a pending application expires (30 days, nanoseconds)
+const APPLICATION_TIMEOUT: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+/// Interval between two removals of the expired applications (1 hour)
+pub const APPLICATION_CLEANUP_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(60 * 60);
+
+thread_local! {
+    /// Agency applications
+    static APPLICATIONS: RefCell<BTreeMap<u64, AgencyApplication, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_APPLICATIONS_MEMORY_ID))));
+
+    /// Documents of the applications, by application id and document index
+    static DOCUMENTS: RefCell<BTreeMap<(u64, u64), AgencyApplicationDocument, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_APPLICATION_DOCUMENTS_MEMORY_ID))));
+
+    /// Next application id
+    static NEXT_APPLICATION_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NEXT_AGENCY_APPLICATION_ID_MEMORY_ID)), 1).unwrap()
+    );
+}
+
+pub struct AgencyApplications;
+
+impl AgencyApplications {
+    /// Submit a new application for the provided applicant.
+    ///
+    /// Fails if the applicant already has a pending application or has submitted too many
+    /// applications, if too many applications are waiting for a review or if the documents are
+    /// too large
+    pub fn submit(
+        applicant: Principal,
+        agency: Agency,
+        documents: Vec<AgencyApplicationDocument>,
+    ) -> DeferredMinterResult<u64> {
+        // expired applications must not count against the limits
+        Self::expire_applications(time());
+
+        let (pending, submitted_by_applicant, pending_by_applicant) =
+            APPLICATIONS.with_borrow(|applications| {
+                applications.iter().fold(
+                    (0, 0, false),
+                    |(pending, submitted, applicant_pending), (_, application)| {
+                        let is_pending = application.status == AgencyApplicationStatus::Pending;
+                        let is_applicant = application.applicant == applicant;
+                        (
+                            pending + usize::from(is_pending),
+                            submitted + usize::from(is_applicant),
+                            applicant_pending || (is_applicant && is_pending),
+                        )
+                    },
+                )
+            });
+        if pending_by_applicant {
+            return Err(AgencyError::ApplicationAlreadyPending(applicant).into());
+        }
+        if submitted_by_applicant >= MAX_APPLICATIONS_PER_APPLICANT {
+            return Err(AgencyError::TooManyApplications(applicant).into());
+        }
+        if pending >= MAX_PENDING_APPLICATIONS {
+            return Err(AgencyError::TooManyPendingApplications.into());
+        }
+
+        let documents_size = documents
+            .iter()
+            .map(|document| document.data.len() as u64)
+            .sum::<u64>();
+        if documents_size > MAX_APPLICATION_DOCUMENTS_SIZE {
+            return Err(
+                AgencyError::ApplicationDocumentsTooLarge(MAX_APPLICATION_DOCUMENTS_SIZE).into(),
+            );
+        }
+
+        let id = NEXT_APPLICATION_ID.with_borrow(|cell| *cell.get());
+        NEXT_APPLICATION_ID
+            .with_borrow_mut(|cell| cell.set(id + 1))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        let infos = documents
+            .iter()
+            .map(AgencyApplicationDocumentInfo::from)
+            .collect();
+        DOCUMENTS.with_borrow_mut(|stored| {
+            for (index, document) in documents.into_iter().enumerate() {
+                stored.insert((id, index as u64), document);
+            }
+        });
+        APPLICATIONS.with_borrow_mut(|applications| {
+            applications.insert(
+                id,
+                AgencyApplication {
+                    agency,
+                    applicant,
+                    comments: vec![],
+                    documents: infos,
+                    id,
+                    reviewed_at: None,
+                    reviewer: None,
+                    status: AgencyApplicationStatus::Pending,
+                    submitted_at: time(),
+                },
+            );
+        });
+
+        Ok(id)
+    }
+
+    /// Get application by id
+    pub fn get_application(id: u64) -> Option<AgencyApplication> {
+        APPLICATIONS.with_borrow(|applications| applications.get(&id))
+    }
+
+    /// Get the document of the application at `index`
+    pub fn get_document(id: u64, index: u64) -> DeferredMinterResult<AgencyApplicationDocument> {
+        DOCUMENTS
+            .with_borrow(|documents| documents.get(&(id, index)))
+            .ok_or_else(|| AgencyError::ApplicationDocumentNotFound(id, index).into())
+    }
+
+    /// Get applications, optionally filtered by status, from the oldest one
+    pub fn get_applications(
+        status: Option<AgencyApplicationStatus>,
+        pagination: Pagination,
+    ) -> Vec<AgencyApplication> {
+        APPLICATIONS.with_borrow(|applications| {
+            applications
+                .iter()
+                .map(|(_, application)| application)
+                .filter(|application| status.map_or(true, |status| application.status == status))
+                .skip(pagination.offset)
+                .take(pagination.count)
+                .collect()
+        })
+    }
+
+    /// Add a review comment to the application
+    pub fn comment(id: u64, author: Principal, comment: String) -> DeferredMinterResult<()> {
+        Self::with_application_mut(id, |application| {
+            application.comments.push(AgencyApplicationComment {
+                author,
+                comment,
+                timestamp: time(),
+            });
+
+            Ok(())
+        })
+    }
+
+    /// Set the review outcome of a pending application and return the updated application
+    pub fn review(
+        id: u64,
+        reviewer: Principal,
+        status: AgencyApplicationStatus,
+        comment: Option<String>,
+    ) -> DeferredMinterResult<AgencyApplication> {
+        Self::with_application_mut(id, |application| {
+            if application.status != AgencyApplicationStatus::Pending {
+                return Err(AgencyError::ApplicationAlreadyReviewed(id).into());
+            }
+
+            let now = time();
+            if let Some(comment) = comment {
+                application.comments.push(AgencyApplicationComment {
+                    author: reviewer,
+                    comment,
+                    timestamp: now,
+                });
+            }
+            application.status = status;
+            application.reviewer = Some(reviewer);
+            application.reviewed_at = Some(now);
+
+            Ok(application.clone())
+        })
+        .inspect(|application| {
+            // the documents of a rejected application are not needed anymore
+            if application.status == AgencyApplicationStatus::Rejected {
+                Self::remove_documents(id);
+            }
+        })
+    }
+
+    /// Expire the applications pending since more than [`APPLICATION_TIMEOUT`] at `now` and
+    /// remove their documents.
+    ///
+    /// Returns the amount of expired applications
+    pub fn expire_applications(now: u64) -> usize {
+        let expired = APPLICATIONS.with_borrow(|applications| {
+            applications
+                .iter()
+                .filter(|(_, application)| {
+                    application.status == AgencyApplicationStatus::Pending
+                        && now.saturating_sub(application.submitted_at) > APPLICATION_TIMEOUT
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (id, mut application) in expired.iter().cloned() {
+            application.status = AgencyApplicationStatus::Expired;
+            APPLICATIONS.with_borrow_mut(|applications| {
+                applications.insert(id, application);
+            });
+            Self::remove_documents(id);
+        }
+
+        expired.len()
+    }
+
+    fn remove_documents(id: u64) {
+        DOCUMENTS.with_borrow_mut(|documents| {
+            let keys = documents
+                .range((id, 0)..=(id, u64::MAX))
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>();
+            for key in keys {
+                documents.remove(&key);
+            }
+        });
+    }
+
+    fn with_application_mut<F, T>(id: u64, f: F) -> DeferredMinterResult<T>
+    where
+        F: FnOnce(&mut AgencyApplication) -> DeferredMinterResult<T>,
+    {
+        APPLICATIONS.with_borrow_mut(|applications| {
+            let mut application = applications
+                .get(&id)
+                .ok_or(AgencyError::ApplicationNotFound(id))?;
+            let result = f(&mut application)?;
+            applications.insert(id, application);
+
+            Ok(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob, mock_agency};
+
+    #[test]
+    fn test_should_submit_application() {
+        let id = AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap();
+        assert_eq!(id, 1);
+
+        let application = AgencyApplications::get_application(id).unwrap();
+        assert_eq!(application.applicant, alice());
+        assert_eq!(application.status, AgencyApplicationStatus::Pending);
+
+        // cannot submit twice while pending
+        assert_eq!(
+            AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::ApplicationAlreadyPending(alice()))
+        );
+        assert_eq!(
+            AgencyApplications::submit(bob(), mock_agency(), vec![]).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_should_not_submit_application_with_too_large_documents() {
+        let documents = vec![AgencyApplicationDocument {
+            data: vec![0; MAX_APPLICATION_DOCUMENTS_SIZE as usize + 1],
+            mime_type: "application/pdf".to_string(),
+            name: "registration.pdf".to_string(),
+        }];
+
+        assert_eq!(
+            AgencyApplications::submit(alice(), mock_agency(), documents).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::ApplicationDocumentsTooLarge(
+                MAX_APPLICATION_DOCUMENTS_SIZE
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_review_application() {
+        let id = AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap();
+        AgencyApplications::comment(id, bob(), "please add the VAT".to_string()).unwrap();
+
+        let application = AgencyApplications::review(
+            id,
+            bob(),
+            AgencyApplicationStatus::Rejected,
+            Some("VAT missing".to_string()),
+        )
+        .unwrap();
+        assert_eq!(application.status, AgencyApplicationStatus::Rejected);
+        assert_eq!(application.reviewer, Some(bob()));
+        assert!(application.reviewed_at.is_some());
+        assert_eq!(application.comments.len(), 2);
+
+        // cannot be reviewed twice
+        assert_eq!(
+            AgencyApplications::review(id, bob(), AgencyApplicationStatus::Approved, None)
+                .unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::ApplicationAlreadyReviewed(id))
+        );
+        // a new application can be submitted after the rejection
+        assert!(AgencyApplications::submit(alice(), mock_agency(), vec![]).is_ok());
+    }
+
+    #[test]
+    fn test_should_limit_applications_per_applicant() {
+        for _ in 0..MAX_APPLICATIONS_PER_APPLICANT {
+            let id = AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap();
+            AgencyApplications::review(id, bob(), AgencyApplicationStatus::Rejected, None).unwrap();
+        }
+
+        assert_eq!(
+            AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::TooManyApplications(alice()))
+        );
+    }
+
+    #[test]
+    fn test_should_limit_pending_applications() {
+        for i in 0..MAX_PENDING_APPLICATIONS {
+            let applicant = Principal::from_slice(&(i as u64).to_be_bytes());
+            AgencyApplications::submit(applicant, mock_agency(), vec![]).unwrap();
+        }
+
+        assert_eq!(
+            AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::TooManyPendingApplications)
+        );
+    }
+
+    #[test]
+    fn test_should_get_application_documents() {
+        let id = AgencyApplications::submit(alice(), mock_agency(), mock_documents()).unwrap();
+
+        let application = AgencyApplications::get_application(id).unwrap();
+        assert_eq!(
+            application.documents,
+            vec![AgencyApplicationDocumentInfo {
+                mime_type: "application/pdf".to_string(),
+                name: "registration.pdf".to_string(),
+                size: 3,
+            }]
+        );
+        assert_eq!(
+            AgencyApplications::get_document(id, 0).unwrap().data,
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            AgencyApplications::get_document(id, 1).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::ApplicationDocumentNotFound(id, 1))
+        );
+    }
+
+    #[test]
+    fn test_should_remove_documents_of_rejected_application() {
+        let id = AgencyApplications::submit(alice(), mock_agency(), mock_documents()).unwrap();
+        AgencyApplications::review(id, bob(), AgencyApplicationStatus::Rejected, None).unwrap();
+
+        assert!(AgencyApplications::get_document(id, 0).is_err());
+    }
+
+    #[test]
+    fn test_should_expire_pending_applications() {
+        let expiring =
+            AgencyApplications::submit(alice(), mock_agency(), mock_documents()).unwrap();
+        let reviewed = AgencyApplications::submit(bob(), mock_agency(), vec![]).unwrap();
+        AgencyApplications::review(reviewed, alice(), AgencyApplicationStatus::Approved, None)
+            .unwrap();
+
+        assert_eq!(AgencyApplications::expire_applications(time()), 0);
+        assert_eq!(
+            AgencyApplications::expire_applications(time() + APPLICATION_TIMEOUT + 1),
+            1
+        );
+
+        assert_eq!(
+            AgencyApplications::get_application(expiring)
+                .unwrap()
+                .status,
+            AgencyApplicationStatus::Expired
+        );
+        assert!(AgencyApplications::get_document(expiring, 0).is_err());
+        assert_eq!(
+            AgencyApplications::get_application(reviewed)
+                .unwrap()
+                .status,
+            AgencyApplicationStatus::Approved
+        );
+        // the applicant can submit again
+        assert!(AgencyApplications::submit(alice(), mock_agency(), vec![]).is_ok());
+    }
+
+    #[test]
+    fn test_should_paginate_applications() {
+        for i in 0..5u64 {
+            let applicant = Principal::from_slice(&i.to_be_bytes());
+            let id = AgencyApplications::submit(applicant, mock_agency(), vec![]).unwrap();
+            if id % 2 == 0 {
+                AgencyApplications::review(id, bob(), AgencyApplicationStatus::Approved, None)
+                    .unwrap();
+            } else {
+                AgencyApplications::review(id, bob(), AgencyApplicationStatus::Rejected, None)
+                    .unwrap();
+            }
+        }
+        AgencyApplications::submit(alice(), mock_agency(), vec![]).unwrap();
+
+        let pending = AgencyApplications::get_applications(
+            Some(AgencyApplicationStatus::Pending),
+            Pagination {
+                offset: 0,
+                count: 10,
+            },
+        );
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, 6);
+
+        let page = AgencyApplications::get_applications(
+            None,
+            Pagination {
+                offset: 2,
+                count: 2,
+            },
+        );
+        assert_eq!(
+            page.iter()
+                .map(|application| application.id)
+                .collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+    }
+
+    fn mock_documents() -> Vec<AgencyApplicationDocument> {
+        vec![AgencyApplicationDocument {
+            data: vec![1, 2, 3],
+            mime_type: "application/pdf".to_string(),
+            name: "registration.pdf".to_string(),
+        }]
+    }
+}
@@ -0,0 +1,262 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use did::deferred::{
+    AgencyError, AgencyId, AgencyTransfer, AgencyTransferStatus, DeferredMinterError,
+    DeferredMinterResult,
+};
+use did::ID;
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
+
+use crate::app::memory::{
+    AGENCY_TRANSFERS_MEMORY_ID, MEMORY_MANAGER, NEXT_AGENCY_TRANSFER_ID_MEMORY_ID,
+};
+use crate::utils::time;
+
+thread_local! {
+    /// Transfers of records between agencies
+    static TRANSFERS: RefCell<BTreeMap<u64, AgencyTransfer, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_TRANSFERS_MEMORY_ID))));
+
+    /// Next transfer id
+    static NEXT_TRANSFER_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NEXT_AGENCY_TRANSFER_ID_MEMORY_ID)), 1).unwrap()
+    );
+}
+
+pub struct AgencyTransfers;
+
+impl AgencyTransfers {
+    /// Request a new transfer of real estates and contracts from `from` to `to`.
+    ///
+    /// `accepted_by_from` and `accepted_by_to` are set when the requester is an admin of the agency
+    pub fn request(
+        requested_by: Principal,
+        from: AgencyId,
+        to: AgencyId,
+        real_estates: Vec<ID>,
+        contracts: Vec<ID>,
+        from_accepted_by: Option<Principal>,
+        to_accepted_by: Option<Principal>,
+    ) -> DeferredMinterResult<u64> {
+        if from == to {
+            return Err(AgencyError::InvalidTransfer(
+                "the agencies of the transfer must be different".to_string(),
+            )
+            .into());
+        }
+        if real_estates.is_empty() && contracts.is_empty() {
+            return Err(
+                AgencyError::InvalidTransfer("the transfer has no records".to_string()).into(),
+            );
+        }
+
+        let id = NEXT_TRANSFER_ID.with_borrow(|cell| *cell.get());
+        NEXT_TRANSFER_ID
+            .with_borrow_mut(|cell| cell.set(id + 1))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        TRANSFERS.with_borrow_mut(|transfers| {
+            transfers.insert(
+                id,
+                AgencyTransfer {
+                    approved_at: None,
+                    approved_by: None,
+                    contracts,
+                    from,
+                    from_accepted_by,
+                    id,
+                    real_estates,
+                    requested_at: time(),
+                    requested_by,
+                    status: AgencyTransferStatus::Pending,
+                    to,
+                    to_accepted_by,
+                },
+            );
+        });
+
+        Ok(id)
+    }
+
+    /// Get transfer by id
+    pub fn get_transfer(id: u64) -> Option<AgencyTransfer> {
+        TRANSFERS.with_borrow(|transfers| transfers.get(&id))
+    }
+
+    /// Get the transfers involving the agency, from the oldest one
+    pub fn get_transfers(agency: AgencyId) -> Vec<AgencyTransfer> {
+        TRANSFERS.with_borrow(|transfers| {
+            transfers
+                .iter()
+                .map(|(_, transfer)| transfer)
+                .filter(|transfer| transfer.from == agency || transfer.to == agency)
+                .collect()
+        })
+    }
+
+    /// Accept the pending transfer on behalf of `agency`
+    pub fn accept(
+        id: u64,
+        agency: AgencyId,
+        accepted_by: Principal,
+    ) -> DeferredMinterResult<AgencyTransfer> {
+        Self::with_pending_transfer_mut(id, |transfer| {
+            if transfer.from == agency {
+                transfer.from_accepted_by = Some(accepted_by);
+            } else if transfer.to == agency {
+                transfer.to_accepted_by = Some(accepted_by);
+            } else {
+                return Err(AgencyError::NotPartOfTransfer(agency, id).into());
+            }
+
+            Ok(transfer.clone())
+        })
+    }
+
+    /// Reject the pending transfer
+    pub fn reject(id: u64) -> DeferredMinterResult<()> {
+        Self::with_pending_transfer_mut(id, |transfer| {
+            transfer.status = AgencyTransferStatus::Rejected;
+            Ok(())
+        })
+    }
+
+    /// Mark the pending transfer as completed.
+    ///
+    /// Fails if the transfer hasn't been accepted by both agencies
+    pub fn complete(id: u64, approved_by: Principal) -> DeferredMinterResult<AgencyTransfer> {
+        Self::with_pending_transfer_mut(id, |transfer| {
+            if !transfer.is_accepted() {
+                return Err(AgencyError::TransferNotAccepted(id).into());
+            }
+
+            transfer.status = AgencyTransferStatus::Completed;
+            transfer.approved_by = Some(approved_by);
+            transfer.approved_at = Some(time());
+
+            Ok(transfer.clone())
+        })
+    }
+
+    fn with_pending_transfer_mut<F, T>(id: u64, f: F) -> DeferredMinterResult<T>
+    where
+        F: FnOnce(&mut AgencyTransfer) -> DeferredMinterResult<T>,
+    {
+        TRANSFERS.with_borrow_mut(|transfers| {
+            let mut transfer = transfers
+                .get(&id)
+                .ok_or(AgencyError::TransferNotFound(id))?;
+            if transfer.status != AgencyTransferStatus::Pending {
+                return Err(AgencyError::TransferNotPending(id).into());
+            }
+            let result = f(&mut transfer)?;
+            transfers.insert(id, transfer);
+
+            Ok(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob, charlie};
+
+    #[test]
+    fn test_should_request_transfer() {
+        let id = AgencyTransfers::request(
+            alice(),
+            AgencyId(1),
+            AgencyId(2),
+            vec![1u64.into()],
+            vec![],
+            Some(alice()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(id, 1);
+
+        let transfer = AgencyTransfers::get_transfer(id).unwrap();
+        assert_eq!(transfer.status, AgencyTransferStatus::Pending);
+        assert_eq!(transfer.from_accepted_by, Some(alice()));
+        assert!(!transfer.is_accepted());
+
+        assert_eq!(AgencyTransfers::get_transfers(AgencyId(2)).len(), 1);
+        assert!(AgencyTransfers::get_transfers(AgencyId(3)).is_empty());
+    }
+
+    #[test]
+    fn test_should_not_request_invalid_transfer() {
+        assert!(matches!(
+            AgencyTransfers::request(
+                alice(),
+                AgencyId(1),
+                AgencyId(1),
+                vec![1u64.into()],
+                vec![],
+                None,
+                None
+            ),
+            Err(DeferredMinterError::Agency(AgencyError::InvalidTransfer(_)))
+        ));
+        assert!(matches!(
+            AgencyTransfers::request(
+                alice(),
+                AgencyId(1),
+                AgencyId(2),
+                vec![],
+                vec![],
+                None,
+                None
+            ),
+            Err(DeferredMinterError::Agency(AgencyError::InvalidTransfer(_)))
+        ));
+    }
+
+    #[test]
+    fn test_should_complete_transfer_once_accepted() {
+        let id = AgencyTransfers::request(
+            charlie(),
+            AgencyId(1),
+            AgencyId(2),
+            vec![],
+            vec![1u64.into()],
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            AgencyTransfers::complete(id, charlie()),
+            Err(DeferredMinterError::Agency(
+                AgencyError::TransferNotAccepted(id)
+            ))
+        );
+        assert_eq!(
+            AgencyTransfers::accept(id, AgencyId(3), charlie()),
+            Err(DeferredMinterError::Agency(AgencyError::NotPartOfTransfer(
+                AgencyId(3),
+                id
+            )))
+        );
+
+        AgencyTransfers::accept(id, AgencyId(1), alice()).unwrap();
+        AgencyTransfers::accept(id, AgencyId(2), bob()).unwrap();
+        let transfer = AgencyTransfers::complete(id, charlie()).unwrap();
+        assert_eq!(transfer.status, AgencyTransferStatus::Completed);
+        assert_eq!(transfer.approved_by, Some(charlie()));
+
+        // can't be changed anymore
+        assert_eq!(
+            AgencyTransfers::reject(id),
+            Err(DeferredMinterError::Agency(
+                AgencyError::TransferNotPending(id)
+            ))
+        );
+    }
+}
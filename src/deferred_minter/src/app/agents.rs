@@ -1,31 +1,97 @@
 use std::cell::RefCell;
+use std::ops::Bound;
 
 use candid::Principal;
-use did::deferred::Agency;
+use did::deferred::{
+    Agency, AgencyChange, AgencyError, AgencyHistory, AgencyId, AgencyMember, AgencyRole,
+    AgencyStatus, DeferredMinterError, DeferredMinterResult,
+};
 use did::StorablePrincipal;
 use ic_stable_structures::memory_manager::VirtualMemory;
-use ic_stable_structures::{BTreeMap, DefaultMemoryImpl};
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
 
-use crate::app::memory::{AGENCIES_MEMORY_ID, MEMORY_MANAGER};
+use crate::app::memory::{
+    AGENCIES_HISTORY_MEMORY_ID, AGENCIES_MEMORY_ID, AGENCIES_STATUS_MEMORY_ID,
+    AGENCY_MEMBERS_MEMORY_ID, LEGACY_AGENCIES_MEMORY_ID, MEMORY_MANAGER, NEXT_AGENCY_ID_MEMORY_ID,
+    PENDING_MEMBER_SYNCS_MEMORY_ID,
+};
+use crate::utils::time;
+
+/// Interval between two retries of the memberships which failed to be synchronized with the
+/// deferred data canister (1 hour)
+pub const MEMBER_SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
 
 thread_local! {
-    /// Agencies storage (1 wallet has 1 agency)
-    static AGENCIES: RefCell<BTreeMap<StorablePrincipal, Agency, VirtualMemory<DefaultMemoryImpl>>> =
+    /// Agencies storage (assoc between agency id and agency)
+    static AGENCIES: RefCell<BTreeMap<AgencyId, Agency, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCIES_MEMORY_ID))));
 
+    /// Agencies registered before the introduction of the agency ids (assoc between the wallet of the owner and agency)
+    static LEGACY_AGENCIES: RefCell<BTreeMap<StorablePrincipal, Agency, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(LEGACY_AGENCIES_MEMORY_ID))));
+
+    /// Agencies profile change history
+    static AGENCIES_HISTORY: RefCell<BTreeMap<AgencyId, AgencyHistory, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCIES_HISTORY_MEMORY_ID))));
+
+    /// Agency members (1 principal is member of at most 1 agency)
+    static AGENCY_MEMBERS: RefCell<BTreeMap<StorablePrincipal, AgencyMember, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCY_MEMBERS_MEMORY_ID))));
+
+    /// Status of the agencies which are not active
+    static AGENCIES_STATUS: RefCell<BTreeMap<AgencyId, AgencyStatus, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(AGENCIES_STATUS_MEMORY_ID))));
+
+    /// Sequence number of the next agency id
+    static NEXT_AGENCY_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NEXT_AGENCY_ID_MEMORY_ID)), 1).unwrap()
+    );
+
+    /// Members whose membership must be synchronized with the deferred data canister (assoc between member and agency)
+    static PENDING_MEMBER_SYNCS: RefCell<BTreeMap<StorablePrincipal, AgencyId, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(PENDING_MEMBER_SYNCS_MEMORY_ID))));
+
 }
 
 pub struct Agents;
 
 impl Agents {
-    pub fn insert_agency(wallet: Principal, agency: Agency) {
+    /// Register a new agency with a new id, which is returned.
+    ///
+    /// The owner becomes the admin of the agency and its membership is queued to be synchronized
+    /// with the deferred data canister.
+    /// Fails if the owner is already a member of an agency, which would leave the new agency
+    /// without an admin.
+    pub fn insert_agency(mut agency: Agency) -> DeferredMinterResult<AgencyId> {
+        if Self::get_membership(agency.owner).is_some() {
+            return Err(AgencyError::AlreadyMember(agency.owner).into());
+        }
+
+        let seq = NEXT_AGENCY_ID.with_borrow(|cell| *cell.get());
+        NEXT_AGENCY_ID
+            .with_borrow_mut(|cell| cell.set(seq + 1))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+        let id = AgencyId(seq);
+
+        agency.id = id;
+        let owner = agency.owner;
         AGENCIES.with_borrow_mut(|agencies| {
-            agencies.insert(wallet.into(), agency);
-        })
+            agencies.insert(id, agency);
+        });
+        Self::set_member(id, owner, AgencyRole::Admin);
+        Self::queue_member_sync(owner, id);
+
+        Ok(id)
+    }
+
+    /// Get agency by id
+    pub fn get_agency(id: AgencyId) -> Option<Agency> {
+        AGENCIES.with_borrow(|agencies| agencies.get(&id))
     }
 
-    pub fn get_agency_by_wallet(wallet: Principal) -> Option<Agency> {
-        AGENCIES.with_borrow(|agencies| agencies.get(&StorablePrincipal::from(wallet)).clone())
+    /// Get the agency the principal is a member of
+    pub fn get_agency_by_member(principal: Principal) -> Option<Agency> {
+        Self::get_membership(principal).and_then(|member| Self::get_agency(member.agency))
     }
 
     /// Get all agencies
@@ -33,44 +99,385 @@ impl Agents {
         AGENCIES.with_borrow(|agencies| agencies.iter().map(|(_, agency)| agency.clone()).collect())
     }
 
-    /// Remove agency by wallet
-    pub fn remove_agency(wallet: Principal) {
+    /// Count all the stored agencies, without decoding them
+    pub fn len() -> u64 {
+        AGENCIES.with_borrow(|agencies| agencies.len())
+    }
+
+    /// Re-encode up to `count` agencies following the agency `after` with the current schema.
+    ///
+    /// Returns the number of re-encoded agencies and the id of the last one
+    pub fn migrate(after: Option<AgencyId>, count: usize) -> (u64, Option<AgencyId>) {
+        AGENCIES.with_borrow_mut(|agencies| {
+            let start = match after {
+                Some(id) => Bound::Excluded(id),
+                None => Bound::Unbounded,
+            };
+            let batch = agencies
+                .range((start, Bound::Unbounded))
+                .take(count)
+                .collect::<Vec<_>>();
+            let migrated = batch.len() as u64;
+            let last = batch.last().map(|(id, _)| *id);
+            for (id, agency) in batch {
+                agencies.insert(id, agency);
+            }
+
+            (migrated, last)
+        })
+    }
+
+    /// Update a registered agency, recording the changed fields in its history.
+    ///
+    /// If the owner changes, the new owner becomes an admin of the agency.
+    /// Fails if the new owner is a member of another agency.
+    pub fn update_agency(agency: Agency, author: Principal) -> DeferredMinterResult<()> {
+        let id = agency.id;
+        let previous = Self::get_agency(id).ok_or(AgencyError::AgencyNotFound(id))?;
+
+        if agency.owner != previous.owner {
+            match Self::get_membership(agency.owner) {
+                Some(member) if member.agency != id => {
+                    return Err(AgencyError::AlreadyMember(agency.owner).into());
+                }
+                Some(_) => {}
+                None => Self::queue_member_sync(agency.owner, id),
+            }
+            Self::set_member(id, agency.owner, AgencyRole::Admin);
+        }
+
+        let changes = previous.diff(&agency);
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        AGENCIES.with_borrow_mut(|agencies| {
+            agencies.insert(id, agency);
+        });
+        AGENCIES_HISTORY.with_borrow_mut(|history| {
+            let mut agency_history = history.get(&id).unwrap_or_default();
+            agency_history.0.push(AgencyChange {
+                author,
+                changes,
+                timestamp: time(),
+            });
+            history.insert(id, agency_history);
+        });
+
+        Ok(())
+    }
+
+    /// Get the profile change history of the agency
+    pub fn get_agency_history(id: AgencyId) -> Vec<AgencyChange> {
+        AGENCIES_HISTORY.with_borrow(|history| {
+            history
+                .get(&id)
+                .map(|history| history.0)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Remove agency by id, together with its members
+    pub fn remove_agency(id: AgencyId) {
         AGENCIES.with_borrow_mut(|agencies| {
-            agencies.remove(&StorablePrincipal::from(wallet));
+            agencies.remove(&id);
+        });
+
+        for member in Self::get_members(id) {
+            Self::remove_member(member.principal);
+        }
+        Self::set_agency_status(id, AgencyStatus::Active);
+    }
+
+    /// Returns the amount of registered agencies
+    pub fn count_agencies() -> u64 {
+        AGENCIES.with_borrow(|agencies| agencies.len())
+    }
+
+    /// Returns the amount of suspended agencies
+    pub fn count_suspended() -> u64 {
+        AGENCIES_STATUS.with_borrow(|status| {
+            status
+                .iter()
+                .filter(|(_, status)| *status == AgencyStatus::Suspended)
+                .count() as u64
+        })
+    }
+
+    /// Get the status of the agency
+    pub fn get_agency_status(agency: AgencyId) -> AgencyStatus {
+        AGENCIES_STATUS.with_borrow(|status| status.get(&agency).unwrap_or_default())
+    }
+
+    /// Returns whether the agency is suspended
+    pub fn is_suspended(agency: AgencyId) -> bool {
+        Self::get_agency_status(agency) == AgencyStatus::Suspended
+    }
+
+    /// Set the status of the agency
+    pub fn set_agency_status(agency: AgencyId, status: AgencyStatus) {
+        AGENCIES_STATUS.with_borrow_mut(|agencies| match status {
+            AgencyStatus::Active => {
+                agencies.remove(&agency);
+            }
+            status => {
+                agencies.insert(agency, status);
+            }
+        });
+    }
+
+    /// Get the membership of the principal
+    pub fn get_membership(principal: Principal) -> Option<AgencyMember> {
+        AGENCY_MEMBERS.with_borrow(|members| members.get(&StorablePrincipal::from(principal)))
+    }
+
+    /// Get the members of the agency
+    pub fn get_members(agency: AgencyId) -> Vec<AgencyMember> {
+        AGENCY_MEMBERS.with_borrow(|members| {
+            members
+                .iter()
+                .map(|(_, member)| member)
+                .filter(|member| member.agency == agency)
+                .collect()
+        })
+    }
+
+    /// Set the principal as member of the agency with the provided role
+    pub fn set_member(agency: AgencyId, principal: Principal, role: AgencyRole) {
+        AGENCY_MEMBERS.with_borrow_mut(|members| {
+            members.insert(
+                principal.into(),
+                AgencyMember {
+                    agency,
+                    principal,
+                    role,
+                },
+            );
+        });
+    }
+
+    /// Remove the principal from its agency
+    pub fn remove_member(principal: Principal) {
+        AGENCY_MEMBERS.with_borrow_mut(|members| {
+            members.remove(&StorablePrincipal::from(principal));
+        });
+    }
+
+    /// Move the agencies stored before the introduction of the agency ids, which are keyed by the
+    /// wallet of their owner, to their legacy agency id, and make the owner the admin of the agency.
+    ///
+    /// The memberships of the owners are queued to be synchronized with the deferred data canister
+    pub fn migrate_agency_ids() {
+        let legacy = LEGACY_AGENCIES.with_borrow(|agencies| agencies.iter().collect::<Vec<_>>());
+
+        for (wallet, mut agency) in legacy {
+            let id = AgencyId::legacy(wallet.0);
+            agency.id = id;
+            let owner = agency.owner;
+            AGENCIES.with_borrow_mut(|agencies| {
+                agencies.insert(id, agency);
+            });
+            LEGACY_AGENCIES.with_borrow_mut(|agencies| {
+                agencies.remove(&wallet);
+            });
+            if Self::get_membership(owner).is_none() {
+                Self::set_member(id, owner, AgencyRole::Admin);
+                Self::queue_member_sync(owner, id);
+            }
+            log::info!("migrated agency of {}: agency id {id}", wallet.0);
+        }
+    }
+
+    /// Queue the membership of the principal to be synchronized with the deferred data canister
+    fn queue_member_sync(principal: Principal, agency: AgencyId) {
+        PENDING_MEMBER_SYNCS.with_borrow_mut(|pending| {
+            pending.insert(principal.into(), agency);
+        });
+    }
+
+    /// Get the principals whose membership must be synchronized with the deferred data canister,
+    /// with the agency they were queued for
+    pub fn pending_member_syncs() -> Vec<(Principal, AgencyId)> {
+        PENDING_MEMBER_SYNCS.with_borrow(|pending| {
+            pending
+                .iter()
+                .map(|(principal, agency)| (principal.0, agency))
+                .collect()
         })
     }
+
+    /// Mark the membership of the principal as synchronized with the deferred data canister,
+    /// unless it has been queued again for another agency in the meantime
+    pub fn member_synced(principal: Principal, agency: AgencyId) {
+        PENDING_MEMBER_SYNCS.with_borrow_mut(|pending| {
+            let key = StorablePrincipal::from(principal);
+            if pending.get(&key).is_some_and(|queued| queued == agency) {
+                pending.remove(&key);
+            }
+        });
+    }
 }
 
 #[cfg(test)]
 mod test {
 
+    use pretty_assertions::assert_eq;
+
     use super::*;
-    use crate::app::test_utils::{alice, mock_agency};
+    use crate::app::test_utils::{alice, bob, mock_agency};
 
     #[test]
     fn test_should_store_and_retrieve_agency() {
-        let wallet = alice();
-        Agents::insert_agency(wallet, mock_agency());
+        let id = Agents::insert_agency(mock_agency()).unwrap();
+
+        let agency = Agents::get_agency(id).expect("Agency should be stored");
+        assert_eq!(agency.id, id);
+        assert_eq!(id, AgencyId(1));
+        assert_eq!(Agents::get_agency_by_member(alice()), Some(agency));
+        assert_eq!(Agents::pending_member_syncs(), vec![(alice(), id)]);
 
-        assert!(
-            Agents::get_agency_by_wallet(wallet).is_some(),
-            "Agency should be stored"
+        Agents::member_synced(alice(), id);
+        assert!(Agents::pending_member_syncs().is_empty());
+    }
+
+    #[test]
+    fn test_should_not_insert_agency_if_owner_is_member() {
+        let id = Agents::insert_agency(mock_agency()).unwrap();
+
+        // alice would be the admin of two agencies, leaving the second one without admins
+        assert_eq!(
+            Agents::insert_agency(mock_agency()).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::AlreadyMember(alice()))
         );
-        assert!(
-            Agents::get_agency_by_wallet(Principal::anonymous()).is_none(),
-            "Agency should not be stored"
-        )
+        assert_eq!(Agents::count_agencies(), 1);
+        assert_eq!(Agents::get_membership(alice()).unwrap().agency, id);
     }
 
     #[test]
     fn test_should_remove_agency() {
-        let wallet = alice();
-        Agents::insert_agency(wallet, mock_agency());
-        Agents::remove_agency(wallet);
+        let id = Agents::insert_agency(mock_agency()).unwrap();
+        Agents::remove_agency(id);
+
+        assert!(Agents::get_agency(id).is_none(), "Agency should be removed");
+    }
+
+    #[test]
+    fn test_should_record_agency_history() {
+        let id = Agents::insert_agency(mock_agency()).unwrap();
+
+        let mut agency = Agents::get_agency(id).unwrap();
+        agency.email = "info@agency.com".to_string();
+        Agents::update_agency(agency.clone(), bob()).unwrap();
+        // no changes, no history entry
+        Agents::update_agency(agency, bob()).unwrap();
+
+        assert_eq!(Agents::get_agency(id).unwrap().email, "info@agency.com");
+        let history = Agents::get_agency_history(id);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].author, bob());
+        assert_eq!(history[0].changes.len(), 1);
+        assert_eq!(history[0].changes[0].field, "email");
+        assert_eq!(history[0].changes[0].previous, "email");
+        assert_eq!(history[0].changes[0].current, "info@agency.com");
+    }
+
+    #[test]
+    fn test_should_make_new_owner_admin() {
+        let id = Agents::insert_agency(mock_agency()).unwrap();
 
-        assert!(
-            Agents::get_agency_by_wallet(wallet).is_none(),
-            "Agency should be removed"
+        let mut agency = Agents::get_agency(id).unwrap();
+        agency.owner = bob();
+        Agents::update_agency(agency, bob()).unwrap();
+
+        assert_eq!(
+            Agents::get_membership(bob()).unwrap(),
+            AgencyMember {
+                agency: id,
+                principal: bob(),
+                role: AgencyRole::Admin,
+            }
         );
     }
+
+    #[test]
+    fn test_should_not_update_unregistered_agency() {
+        let agency = Agency {
+            id: AgencyId(100),
+            ..mock_agency()
+        };
+
+        assert_eq!(
+            Agents::update_agency(agency, bob()).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::AgencyNotFound(AgencyId(100)))
+        );
+    }
+
+    #[test]
+    fn test_should_set_agency_status() {
+        let id = Agents::insert_agency(mock_agency()).unwrap();
+        assert_eq!(Agents::get_agency_status(id), AgencyStatus::Active);
+
+        Agents::set_agency_status(id, AgencyStatus::Suspended);
+        assert!(Agents::is_suspended(id));
+        assert_eq!(Agents::count_agencies(), 1);
+        assert_eq!(Agents::count_suspended(), 1);
+
+        // removing the agency resets its status
+        Agents::remove_agency(id);
+        assert!(!Agents::is_suspended(id));
+        assert_eq!(Agents::count_agencies(), 0);
+        assert_eq!(Agents::count_suspended(), 0);
+    }
+
+    #[test]
+    fn test_should_manage_agency_members() {
+        let id = Agents::insert_agency(mock_agency()).unwrap();
+        assert_eq!(
+            Agents::get_membership(alice()).unwrap(),
+            AgencyMember {
+                agency: id,
+                principal: alice(),
+                role: AgencyRole::Admin,
+            }
+        );
+
+        Agents::set_member(id, bob(), AgencyRole::ReadOnly);
+        assert_eq!(Agents::get_members(id).len(), 2);
+        assert_eq!(
+            Agents::get_membership(bob()).unwrap().role,
+            AgencyRole::ReadOnly
+        );
+
+        Agents::remove_agency(id);
+        assert!(Agents::get_membership(alice()).is_none());
+        assert!(Agents::get_membership(bob()).is_none());
+    }
+
+    #[test]
+    fn test_should_migrate_agency_ids() {
+        // stored before the agency ids, keyed by the wallet of the owner
+        LEGACY_AGENCIES.with_borrow_mut(|agencies| {
+            agencies.insert(alice().into(), mock_agency());
+        });
+
+        Agents::migrate_agency_ids();
+
+        let id = AgencyId::legacy(alice());
+        assert!(LEGACY_AGENCIES.with_borrow(|agencies| agencies.is_empty()));
+        assert_eq!(Agents::get_agency(id).unwrap().id, id);
+        assert_eq!(Agents::get_agency(id).unwrap().owner, alice());
+        assert_eq!(
+            Agents::get_membership(alice()).unwrap(),
+            AgencyMember {
+                agency: id,
+                principal: alice(),
+                role: AgencyRole::Admin,
+            }
+        );
+        assert_eq!(Agents::pending_member_syncs(), vec![(alice(), id)]);
+
+        // migrating again has no effect
+        Agents::migrate_agency_ids();
+        assert_eq!(Agents::count_agencies(), 1);
+    }
 }
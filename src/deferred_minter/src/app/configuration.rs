@@ -4,19 +4,28 @@ use std::cell::RefCell;
 use std::str::FromStr as _;
 
 use candid::Principal;
-use did::deferred::{DeferredMinterError, DeferredMinterResult, EcdsaKey};
+use did::deferred::{
+    ConfigurationChange, ConfigurationError, ConfigurationUpdate, DeferredMinterConfiguration,
+    DeferredMinterError, DeferredMinterResult, EcdsaKey,
+};
 use did::{StorableLogSettings, StorablePrincipal, H160};
+use ic_log::did::Pagination;
 use ic_log::LogSettingsV2;
 use ic_stable_structures::memory_manager::VirtualMemory;
-use ic_stable_structures::{DefaultMemoryImpl, StableCell, StableVec};
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell, StableVec};
+use url::Url;
 
 use self::currency::Currency;
+use crate::app::ethereum::EvmRpcClient;
 use crate::app::memory::{
-    ALLOWED_CURRENCIES_MEMORY_ID, CHAIN_ID_MEMORY_ID, DEFERRED_DATA_CANISTER_MEMORY_ID,
-    DEFERRED_ERC721_CONTRACT_MEMORY_ID, ECDSA_KEY_MEMORY_ID, EVM_CUSTOM_RPC_API_MEMORY_ID,
-    EVM_GAS_PRICE_MEMORY_ID, EVM_RPC_MEMORY_ID, LOG_SETTINGS_MEMORY_ID, MEMORY_MANAGER,
-    REWARD_POOL_CONTRACT_MEMORY_ID,
+    ALLOWED_CURRENCIES_MEMORY_ID, CHAIN_ID_MEMORY_ID, CONFIGURATION_HISTORY_MEMORY_ID,
+    DEFERRED_DATA_CANISTER_MEMORY_ID, DEFERRED_ERC721_CONTRACT_MEMORY_ID, ECDSA_KEY_MEMORY_ID,
+    EVM_CUSTOM_RPC_API_MEMORY_ID, EVM_GAS_PRICE_MEMORY_ID, EVM_RPC_MEMORY_ID,
+    LOG_SETTINGS_MEMORY_ID, MEMORY_MANAGER, REWARD_POOL_CONTRACT_MEMORY_ID,
+    REWARD_POOL_DEPLOY_BLOCK_MEMORY_ID,
 };
+use crate::app::reward_pool_ledger::RewardPoolLedger;
+use crate::utils::time;
 
 const DEFAULT_GAS_PRICE: u64 = 20_000_000_000;
 
@@ -36,6 +45,11 @@ thread_local! {
         RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_CONTRACT_MEMORY_ID)), H160::zero()).unwrap()
     );
 
+    /// Block where the reward pool contract has been deployed
+    static REWARD_POOL_DEPLOY_BLOCK: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_DEPLOY_BLOCK_MEMORY_ID)), 0).unwrap()
+    );
+
     /// Allowed currencies
     static ALLOWED_CURRENCIES: RefCell<StableVec<Currency, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(StableVec::new(MEMORY_MANAGER.with(|mm| mm.get(ALLOWED_CURRENCIES_MEMORY_ID))).unwrap()
@@ -70,6 +84,10 @@ thread_local! {
         RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(LOG_SETTINGS_MEMORY_ID)), StorableLogSettings::default()).unwrap()
     );
 
+    /// Changes of the configuration applied by the updates
+    static CONFIGURATION_HISTORY: RefCell<BTreeMap<u64, ConfigurationChange, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(CONFIGURATION_HISTORY_MEMORY_ID))));
+
 }
 
 pub struct Configuration;
@@ -114,6 +132,19 @@ impl Configuration {
         Ok(())
     }
 
+    pub fn get_reward_pool_deploy_block() -> u64 {
+        REWARD_POOL_DEPLOY_BLOCK.with_borrow(|cell| *cell.get())
+    }
+
+    pub fn set_reward_pool_deploy_block(block: u64) -> DeferredMinterResult<()> {
+        REWARD_POOL_DEPLOY_BLOCK.with_borrow_mut(|cell| {
+            cell.set(block)
+                .map_err(|_| DeferredMinterError::StorageError)
+        })?;
+
+        Ok(())
+    }
+
     /// Set allowed currencies
     pub fn set_allowed_currencies(currencies: Vec<String>) {
         let currencies = currencies
@@ -133,6 +164,11 @@ impl Configuration {
         })
     }
 
+    /// Returns whether the currency code can be set as an allowed currency
+    pub fn is_valid_currency(currency: &str) -> bool {
+        Currency::from_str(currency).is_ok()
+    }
+
     /// Get allowed currencies
     pub fn get_allowed_currencies() -> Vec<String> {
         ALLOWED_CURRENCIES
@@ -226,6 +262,242 @@ impl Configuration {
     pub fn get_log_settings() -> LogSettingsV2 {
         LOG_SETTINGS.with_borrow(|cell| cell.get().0.clone())
     }
+
+    /// Get the whole configuration
+    pub fn get() -> DeferredMinterConfiguration {
+        DeferredMinterConfiguration {
+            allowed_currencies: Self::get_allowed_currencies(),
+            chain_id: Self::get_chain_id(),
+            deferred_data: Self::get_deferred_data_canister(),
+            deferred_erc721: Self::get_deferred_erc721_contract(),
+            ecdsa_key: Self::get_ecdsa_key(),
+            evm_rpc: Self::get_evm_rpc(),
+            evm_rpc_api: Self::get_evm_rpc_api(),
+            gas_price: Self::get_gas_price(),
+            log_settings: Self::get_log_settings(),
+            reward_pool: Self::get_reward_pool_contract(),
+            reward_pool_deploy_block: Self::get_reward_pool_deploy_block(),
+        }
+    }
+
+    /// Check whether the update can be applied to the current configuration
+    pub fn validate_update(update: &ConfigurationUpdate) -> DeferredMinterResult<()> {
+        if update.deferred_data == Some(Principal::anonymous()) {
+            return Err(ConfigurationError::AnonymousPrincipal(
+                "deferred data canister".to_string(),
+            )
+            .into());
+        }
+        if update.evm_rpc == Some(Principal::anonymous()) {
+            return Err(
+                ConfigurationError::AnonymousPrincipal("evm rpc canister".to_string()).into(),
+            );
+        }
+        if update.deferred_erc721 == Some(H160::zero()) {
+            return Err(
+                ConfigurationError::ZeroAddress("deferred erc721 contract".to_string()).into(),
+            );
+        }
+        if update.reward_pool == Some(H160::zero()) {
+            return Err(ConfigurationError::ZeroAddress("reward pool contract".to_string()).into());
+        }
+
+        if let Some(api) = update.evm_rpc_api.as_deref().filter(|api| !api.is_empty()) {
+            let url = Url::parse(api)
+                .map_err(|err| ConfigurationError::InvalidEvmRpcApi(err.to_string()))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(ConfigurationError::InvalidEvmRpcApi(format!(
+                    "unsupported scheme {}",
+                    url.scheme()
+                ))
+                .into());
+            }
+        }
+
+        // the chain must be reachable with the resulting rpc settings
+        if update.chain_id.is_some() || update.evm_rpc_api.is_some() {
+            let chain_id = update.chain_id.unwrap_or_else(Self::get_chain_id);
+            let custom_rpc = match update.evm_rpc_api.as_deref() {
+                Some(api) => !api.is_empty(),
+                None => Self::get_evm_rpc_api().is_some(),
+            };
+            if chain_id == 0 || (!custom_rpc && !EvmRpcClient::is_chain_supported(chain_id)) {
+                return Err(ConfigurationError::UnsupportedChainId(chain_id).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate and apply the update, recording the old and the new value of each changed field.
+    ///
+    /// Every field is validated and compared with the current configuration before any of them is
+    /// written. Changing the reward pool contract, the chain or the deploy block of the reward pool
+    /// restarts the reconciliation of the reward pool ledger from the deploy block.
+    ///
+    /// Returns the applied changes
+    pub fn update(
+        update: ConfigurationUpdate,
+        author: Principal,
+    ) -> DeferredMinterResult<Vec<ConfigurationChange>> {
+        Self::validate_update(&update)?;
+
+        let current = Self::get();
+        let changes = [
+            update.chain_id.and_then(|chain_id| {
+                Self::change(
+                    "chain_id",
+                    current.chain_id.to_string(),
+                    chain_id.to_string(),
+                    author,
+                )
+            }),
+            update.deferred_data.and_then(|deferred_data| {
+                Self::change(
+                    "deferred_data",
+                    current.deferred_data.to_string(),
+                    deferred_data.to_string(),
+                    author,
+                )
+            }),
+            update.deferred_erc721.and_then(|deferred_erc721| {
+                Self::change(
+                    "deferred_erc721",
+                    current.deferred_erc721.to_string(),
+                    deferred_erc721.to_string(),
+                    author,
+                )
+            }),
+            update.ecdsa_key.and_then(|ecdsa_key| {
+                Self::change(
+                    "ecdsa_key",
+                    current.ecdsa_key.to_string(),
+                    ecdsa_key.to_string(),
+                    author,
+                )
+            }),
+            update.evm_rpc.and_then(|evm_rpc| {
+                Self::change(
+                    "evm_rpc",
+                    current.evm_rpc.to_string(),
+                    evm_rpc.to_string(),
+                    author,
+                )
+            }),
+            update.evm_rpc_api.as_ref().and_then(|evm_rpc_api| {
+                Self::change(
+                    "evm_rpc_api",
+                    current.evm_rpc_api.clone().unwrap_or_default(),
+                    evm_rpc_api.clone(),
+                    author,
+                )
+            }),
+            update.log_settings.as_ref().and_then(|log_settings| {
+                Self::change(
+                    "log_settings",
+                    format!("{:?}", current.log_settings),
+                    format!("{log_settings:?}"),
+                    author,
+                )
+            }),
+            update.reward_pool.and_then(|reward_pool| {
+                Self::change(
+                    "reward_pool",
+                    current.reward_pool.to_string(),
+                    reward_pool.to_string(),
+                    author,
+                )
+            }),
+            update
+                .reward_pool_deploy_block
+                .and_then(|reward_pool_deploy_block| {
+                    Self::change(
+                        "reward_pool_deploy_block",
+                        current.reward_pool_deploy_block.to_string(),
+                        reward_pool_deploy_block.to_string(),
+                        author,
+                    )
+                }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if let Some(chain_id) = update.chain_id {
+            Self::set_chain_id(chain_id)?;
+        }
+        if let Some(deferred_data) = update.deferred_data {
+            Self::set_deferred_data_canister(deferred_data)?;
+        }
+        if let Some(deferred_erc721) = update.deferred_erc721 {
+            Self::set_deferred_erc721_contract(deferred_erc721)?;
+        }
+        if let Some(ecdsa_key) = update.ecdsa_key {
+            Self::set_ecdsa_key(ecdsa_key)?;
+        }
+        if let Some(evm_rpc) = update.evm_rpc {
+            Self::set_evm_rpc(evm_rpc)?;
+        }
+        if let Some(evm_rpc_api) = update.evm_rpc_api {
+            Self::set_evm_rpc_api(evm_rpc_api)?;
+        }
+        if let Some(log_settings) = update.log_settings {
+            Self::set_log_settings(log_settings)?;
+        }
+        if let Some(reward_pool) = update.reward_pool {
+            Self::set_reward_pool_contract(reward_pool)?;
+        }
+        if let Some(reward_pool_deploy_block) = update.reward_pool_deploy_block {
+            Self::set_reward_pool_deploy_block(reward_pool_deploy_block)?;
+        }
+
+        // the ledger has been reconciled with the events of another contract or chain
+        if changes.iter().any(|change| {
+            matches!(
+                change.field.as_str(),
+                "chain_id" | "reward_pool" | "reward_pool_deploy_block"
+            )
+        }) {
+            RewardPoolLedger::reset(Self::get_reward_pool_deploy_block())?;
+        }
+
+        CONFIGURATION_HISTORY.with_borrow_mut(|history| {
+            for change in &changes {
+                let id = history.last_key_value().map(|(id, _)| id + 1).unwrap_or(0);
+                history.insert(id, change.clone());
+            }
+        });
+
+        Ok(changes)
+    }
+
+    /// Get the changes applied to the configuration, from the oldest one
+    pub fn get_history(pagination: Pagination) -> Vec<ConfigurationChange> {
+        CONFIGURATION_HISTORY.with_borrow(|history| {
+            history
+                .iter()
+                .map(|(_, change)| change)
+                .skip(pagination.offset)
+                .take(pagination.count)
+                .collect()
+        })
+    }
+
+    /// Returns the change of `field`, if the value has changed
+    fn change(
+        field: &str,
+        old_value: String,
+        new_value: String,
+        author: Principal,
+    ) -> Option<ConfigurationChange> {
+        (old_value != new_value).then(|| ConfigurationChange {
+            author,
+            field: field.to_string(),
+            new_value,
+            old_value,
+            timestamp: time(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -274,6 +546,13 @@ mod test {
         assert_eq!(Configuration::get_reward_pool_contract(), address);
     }
 
+    #[test]
+    fn test_should_get_and_set_reward_pool_deploy_block() {
+        assert_eq!(Configuration::get_reward_pool_deploy_block(), 0);
+        assert!(Configuration::set_reward_pool_deploy_block(21_000_000).is_ok());
+        assert_eq!(Configuration::get_reward_pool_deploy_block(), 21_000_000);
+    }
+
     #[test]
     fn test_should_set_and_get_allowed_currencies() {
         assert!(Configuration::get_allowed_currencies().is_empty());
@@ -323,4 +602,148 @@ mod test {
         assert!(Configuration::set_log_settings(settings.clone()).is_ok());
         assert_eq!(Configuration::get_log_settings(), settings);
     }
+
+    #[test]
+    fn test_should_validate_configuration_update() {
+        assert_eq!(
+            Configuration::validate_update(&ConfigurationUpdate {
+                deferred_data: Some(Principal::anonymous()),
+                ..Default::default()
+            }),
+            Err(
+                ConfigurationError::AnonymousPrincipal("deferred data canister".to_string()).into()
+            )
+        );
+        assert_eq!(
+            Configuration::validate_update(&ConfigurationUpdate {
+                reward_pool: Some(H160::zero()),
+                ..Default::default()
+            }),
+            Err(ConfigurationError::ZeroAddress("reward pool contract".to_string()).into())
+        );
+        assert!(Configuration::validate_update(&ConfigurationUpdate {
+            evm_rpc_api: Some("not an url".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(Configuration::validate_update(&ConfigurationUpdate {
+            evm_rpc_api: Some("ftp://api.ethereum.org".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+        assert_eq!(
+            Configuration::validate_update(&ConfigurationUpdate {
+                chain_id: Some(137),
+                ..Default::default()
+            }),
+            Err(ConfigurationError::UnsupportedChainId(137).into())
+        );
+        // any chain can be reached with a custom rpc api
+        assert!(Configuration::validate_update(&ConfigurationUpdate {
+            chain_id: Some(137),
+            evm_rpc_api: Some("https://polygon-rpc.com".to_string()),
+            ..Default::default()
+        })
+        .is_ok());
+        // removing the custom rpc api requires a supported chain
+        assert!(Configuration::set_evm_rpc_api("https://polygon-rpc.com".to_string()).is_ok());
+        assert!(Configuration::set_chain_id(137).is_ok());
+        assert_eq!(
+            Configuration::validate_update(&ConfigurationUpdate {
+                evm_rpc_api: Some(String::new()),
+                ..Default::default()
+            }),
+            Err(ConfigurationError::UnsupportedChainId(137).into())
+        );
+    }
+
+    #[test]
+    fn test_should_update_configuration_and_record_changes() {
+        let author = Principal::management_canister();
+        let address = H160::from_hex_str("0xE46A267b65Ed8CBAeBA9AdC3171063179b642E7A").unwrap();
+        assert!(Configuration::set_chain_id(1).is_ok());
+
+        let changes = Configuration::update(
+            ConfigurationUpdate {
+                chain_id: Some(11155111),
+                deferred_erc721: Some(address),
+                // unchanged values are not recorded
+                ecdsa_key: Some(Configuration::get_ecdsa_key()),
+                ..Default::default()
+            },
+            author,
+        )
+        .unwrap();
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "chain_id");
+        assert_eq!(changes[0].old_value, "1");
+        assert_eq!(changes[0].new_value, "11155111");
+        assert_eq!(changes[0].author, author);
+        assert_eq!(changes[1].field, "deferred_erc721");
+        assert_eq!(changes[1].old_value, H160::zero().to_string());
+        assert_eq!(changes[1].new_value, address.to_string());
+
+        let configuration = Configuration::get();
+        assert_eq!(configuration.chain_id, 11155111);
+        assert_eq!(configuration.deferred_erc721, address);
+
+        Configuration::update(
+            ConfigurationUpdate {
+                evm_rpc_api: Some("https://api.ethereum.org".to_string()),
+                ..Default::default()
+            },
+            author,
+        )
+        .unwrap();
+        let history = Configuration::get_history(Pagination {
+            offset: 0,
+            count: 10,
+        });
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].field, "evm_rpc_api");
+        assert_eq!(history[2].old_value, "");
+
+        // invalid updates are not applied
+        assert!(Configuration::update(
+            ConfigurationUpdate {
+                chain_id: Some(1),
+                evm_rpc: Some(Principal::anonymous()),
+                ..Default::default()
+            },
+            author,
+        )
+        .is_err());
+        assert_eq!(Configuration::get_chain_id(), 11155111);
+    }
+
+    #[test]
+    fn test_should_reset_reward_pool_ledger_on_reward_pool_change() {
+        let author = Principal::management_canister();
+        RewardPoolLedger::reset(21_000_001).unwrap();
+
+        // changes which don't affect the reward pool keep the ledger
+        Configuration::update(
+            ConfigurationUpdate {
+                evm_rpc_api: Some("https://api.ethereum.org".to_string()),
+                ..Default::default()
+            },
+            author,
+        )
+        .unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 21_000_000);
+
+        Configuration::update(
+            ConfigurationUpdate {
+                reward_pool: Some(
+                    H160::from_hex_str("0xE46A267b65Ed8CBAeBA9AdC3171063179b642E7A").unwrap(),
+                ),
+                reward_pool_deploy_block: Some(22_000_000),
+                ..Default::default()
+            },
+            author,
+        )
+        .unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 21_999_999);
+    }
 }
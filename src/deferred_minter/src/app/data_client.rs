@@ -1,12 +1,11 @@
 use candid::Principal;
 use did::deferred::{
+    AgencyId, AgencyQuota, AgencyReport, AgencyStatus, AgencyTransfer, AgencyTransferAuditEntry,
     Contract, ContractError, DeferredDataResult, DeferredMinterError, DeferredMinterResult,
-    GenericValue, RealEstate, Seller,
+    DocumentLeaf, DocumentsAnchor, GenericValue, RealEstate, Seller,
 };
 use did::{H160, ID};
 
-use crate::utils::caller;
-
 pub struct DeferredDataClient {
     principal: Principal,
 }
@@ -42,7 +41,7 @@ impl DeferredDataClient {
                 restricted_properties: vec![],
                 documents: vec![],
                 real_estate: 1u64.into(),
-                agency: caller(),
+                agency: AgencyId(1),
                 expiration: "2078-01-01".to_string(),
                 closed: false,
             });
@@ -95,6 +94,171 @@ impl DeferredDataClient {
         result.map_err(DeferredMinterError::DataCanister)
     }
 
+    /// Set or unset the agency of a member on the data canister
+    /// Get the leaves of the Merkle tree of the documents of a contract
+    pub async fn get_contract_document_leaves(
+        &self,
+        contract_id: &ID,
+    ) -> DeferredMinterResult<Vec<DocumentLeaf>> {
+        if cfg!(test) {
+            return Ok(vec![
+                DocumentLeaf {
+                    document_id: 0,
+                    sha256: "01".repeat(32),
+                },
+                DocumentLeaf {
+                    document_id: 1,
+                    sha256: "02".repeat(32),
+                },
+            ]);
+        }
+
+        let (leaves,) = ic_cdk::call::<_, (DeferredDataResult<Vec<DocumentLeaf>>,)>(
+            self.principal,
+            "minter_get_contract_document_leaves",
+            (contract_id.clone(),),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        leaves.map_err(DeferredMinterError::DataCanister)
+    }
+
+    /// Store the anchor of the documents of a contract on data canister
+    pub async fn set_documents_anchor(&self, anchor: DocumentsAnchor) -> DeferredMinterResult<()> {
+        if cfg!(test) {
+            return Ok(());
+        }
+
+        let (result,) = ic_cdk::call::<_, (DeferredDataResult<()>,)>(
+            self.principal,
+            "minter_set_documents_anchor",
+            (anchor,),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        result.map_err(DeferredMinterError::DataCanister)
+    }
+
+    pub async fn set_agency_member(
+        &self,
+        member: Principal,
+        agency: Option<AgencyId>,
+    ) -> DeferredMinterResult<()> {
+        if cfg!(test) {
+            return Ok(());
+        }
+
+        let (result,) = ic_cdk::call::<_, (DeferredDataResult<()>,)>(
+            self.principal,
+            "minter_set_agency_member",
+            (member, agency),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        result.map_err(DeferredMinterError::DataCanister)
+    }
+
+    /// Set the status of the agency on the data canister.
+    ///
+    /// Returns the records of the agency affected by the change
+    pub async fn set_agency_status(
+        &self,
+        agency: AgencyId,
+        status: AgencyStatus,
+    ) -> DeferredMinterResult<AgencyReport> {
+        if cfg!(test) {
+            return Ok(AgencyReport {
+                agency,
+                real_estates: vec![1u64.into()],
+                open_contracts: vec![],
+            });
+        }
+
+        let (result,) = ic_cdk::call::<_, (DeferredDataResult<AgencyReport>,)>(
+            self.principal,
+            "minter_set_agency_status",
+            (agency, status),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        result.map_err(DeferredMinterError::DataCanister)
+    }
+
+    /// Set the storage quota of the agency on the data canister, or remove it if `None`
+    pub async fn set_agency_quota(
+        &self,
+        agency: AgencyId,
+        quota: Option<AgencyQuota>,
+    ) -> DeferredMinterResult<()> {
+        if cfg!(test) {
+            return Ok(());
+        }
+
+        let (result,) = ic_cdk::call::<_, (DeferredDataResult<()>,)>(
+            self.principal,
+            "minter_set_agency_quota",
+            (agency, quota),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        result.map_err(DeferredMinterError::DataCanister)
+    }
+
+    /// Remove the agency from the data canister, deleting its real estates.
+    ///
+    /// Fails if the agency still has open contracts
+    pub async fn remove_agency(&self, agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+        if cfg!(test) {
+            return Ok(AgencyReport {
+                agency,
+                real_estates: vec![1u64.into()],
+                open_contracts: vec![],
+            });
+        }
+
+        let (result,) = ic_cdk::call::<_, (DeferredDataResult<AgencyReport>,)>(
+            self.principal,
+            "minter_remove_agency",
+            (agency,),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        result.map_err(DeferredMinterError::DataCanister)
+    }
+
+    /// Move the records of an approved transfer to the gaining agency on the data canister
+    pub async fn transfer_agency_records(
+        &self,
+        transfer: AgencyTransfer,
+    ) -> DeferredMinterResult<AgencyTransferAuditEntry> {
+        if cfg!(test) {
+            return Ok(AgencyTransferAuditEntry {
+                contracts: transfer.contracts,
+                from: transfer.from,
+                real_estates: transfer.real_estates,
+                timestamp: 0,
+                to: transfer.to,
+                transfer_id: transfer.id,
+            });
+        }
+
+        let (result,) = ic_cdk::call::<_, (DeferredDataResult<AgencyTransferAuditEntry>,)>(
+            self.principal,
+            "minter_transfer_agency_records",
+            (transfer,),
+        )
+        .await
+        .map_err(|(code, err)| did::deferred::DeferredMinterError::CanisterCall(code, err))?;
+
+        result.map_err(DeferredMinterError::DataCanister)
+    }
+
     pub async fn create_real_estate(&self, real_estate: RealEstate) -> DeferredMinterResult<ID> {
         if cfg!(test) {
             return Ok(1u64.into());
@@ -151,7 +315,7 @@ impl DeferredDataClient {
         if cfg!(test) {
             return Ok(RealEstate {
                 deleted: false,
-                agency: caller(),
+                agency: AgencyId(1),
                 name: "name".to_string(),
                 description: "description".to_string(),
                 image: Some("image".to_string()),
@@ -4,6 +4,6 @@ mod reward_pool;
 mod wallet;
 
 pub use deferred::DeferredErc721;
-pub use evm_rpc_client::EvmRpcClient;
-pub use reward_pool::RewardPool;
+pub use evm_rpc_client::{EvmRpcClient, LogEntry};
+pub use reward_pool::{RewardPool, RewardPoolEvent};
 pub use wallet::Wallet;
@@ -1,8 +1,12 @@
-use abi::{self, CloseContractCall, CreateContractCall, CreateContractRequest, SellerRequest};
+use abi::{
+    self, AnchorDocumentsCall, CloseContractCall, CreateContractCall, CreateContractRequest,
+    SellerRequest,
+};
 use did::deferred::{Contract, DeferredMinterResult};
 use did::{H160, ID};
 use ethers_core::abi::AbiEncode;
-use ethers_core::types::{Bytes, TransactionRequest};
+use ethers_core::types::{Bytes, TransactionRequest, H256};
+use ethers_core::utils::keccak256;
 use num_traits::cast::ToPrimitive;
 
 use super::evm_rpc_client::EvmRpcClient;
@@ -13,6 +17,8 @@ use crate::app::configuration::Configuration;
 const CREATE_CONTRACT_GAS: u64 = 700_000;
 /// Gas required for `closeContract`
 const CLOSE_CONTRACT_GAS: u64 = 80_000;
+/// Gas required for `anchorDocuments`
+const ANCHOR_DOCUMENTS_GAS: u64 = 50_000;
 
 pub struct DeferredErc721 {
     address: H160,
@@ -69,7 +75,9 @@ impl DeferredErc721 {
         let payload = abi::DeferredCalls::CreateContract(CreateContractCall { request }).encode();
 
         self.send_tx(wallet, evm_rpc_client, payload.into(), CREATE_CONTRACT_GAS)
-            .await
+            .await?;
+
+        Ok(())
     }
 
     /// Close a contract on the Deferred Erc721 contract
@@ -92,6 +100,36 @@ impl DeferredErc721 {
         .encode();
 
         self.send_tx(wallet, evm_rpc_client, payload.into(), CLOSE_CONTRACT_GAS)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Anchor the Merkle root of the documents of a contract on the Deferred Erc721 contract,
+    /// emitting the `DocumentsAnchored` event.
+    ///
+    /// Returns the hash of the transaction.
+    pub async fn anchor_documents(
+        &self,
+        wallet: &Wallet,
+        evm_rpc_client: &EvmRpcClient,
+        contract_id: ID,
+        documents_root: [u8; 32],
+    ) -> DeferredMinterResult<H256> {
+        if cfg!(test) {
+            return Ok(H256::zero());
+        }
+
+        let contract_id = contract_id.0.to_u64().expect("Contract ID is too large");
+        log::debug!("Anchoring documents of contract_id {contract_id}");
+
+        let payload = abi::DeferredCalls::AnchorDocuments(AnchorDocumentsCall {
+            contract_id: contract_id.into(),
+            documents_root,
+        })
+        .encode();
+
+        self.send_tx(wallet, evm_rpc_client, payload.into(), ANCHOR_DOCUMENTS_GAS)
             .await
     }
 
@@ -101,7 +139,7 @@ impl DeferredErc721 {
         evm_rpc_client: &EvmRpcClient,
         payload: Bytes,
         gas: u64,
-    ) -> DeferredMinterResult<()> {
+    ) -> DeferredMinterResult<H256> {
         let eth_address = wallet.address().await?;
         log::debug!("Sending tx from {eth_address}");
         let nonce = evm_rpc_client.get_next_nonce(eth_address).await?;
@@ -123,10 +161,11 @@ impl DeferredErc721 {
         let signed_tx = wallet.sign_transaction(tx).await?;
         log::debug!("Signed tx: {signed_tx}");
 
-        log::debug!("Sending out tx");
+        let tx_hash = H256::from(keccak256(&signed_tx));
+        log::debug!("Sending out tx {tx_hash:#x}");
         evm_rpc_client.eth_send_raw_transaction(signed_tx).await?;
 
-        Ok(())
+        Ok(tx_hash)
     }
 }
 
@@ -174,4 +213,15 @@ mod test {
             .await
             .expect("Failed to create contract");
     }
+
+    #[tokio::test]
+    async fn test_should_anchor_documents() {
+        let wallet = Wallet::new(EcdsaKey::Dfx, 1);
+        let evm_rpc_client = EvmRpcClient::new(alice(), 1, None);
+
+        DeferredErc721::from(H160::zero())
+            .anchor_documents(&wallet, &evm_rpc_client, 1u64.into(), [1; 32])
+            .await
+            .expect("Failed to anchor documents");
+    }
 }
@@ -4,18 +4,24 @@ use candid::Principal;
 use did::deferred::{DeferredMinterError, DeferredMinterResult};
 use did::H160;
 use ethers_core::types::{Bytes, U256};
+pub use evm_rpc_did::LogEntry;
 use evm_rpc_did::{
-    BlockTag, CallArgs, CallResult, EthMainnetService, EthSepoliaService, GetTransactionCountArgs,
-    GetTransactionCountResult, MultiCallResult, MultiGetTransactionCountResult, RpcConfig,
-    RpcError, RpcService, SendRawTransactionResult, SendRawTransactionStatus, TransactionRequest,
+    BlockTag, CallArgs, CallResult, EthMainnetService, EthSepoliaService, GetBlockByNumberResult,
+    GetLogsArgs, GetLogsResult, GetTransactionCountArgs, GetTransactionCountResult,
+    MultiCallResult, MultiGetBlockByNumberResult, MultiGetLogsResult,
+    MultiGetTransactionCountResult, RpcConfig, RpcError, RpcService, SendRawTransactionResult,
+    SendRawTransactionStatus, TransactionRequest,
 };
 use num_traits::cast::ToPrimitive;
 
 use self::evm_rpc_did::{MultiSendRawTransactionResult, RpcApi, RpcServices};
+use crate::app::metrics::Metrics;
 
 const MAINNET_CHAIN_ID: u64 = 1;
 const SEPOLIA_CHAIN_ID: u64 = 11155111;
 const GET_NEXT_NONCE_SAMPLE_PAYLOAD: &str = r#"{"jsonrpc":"2.0","id":1,"method":"eth_getTransactionCount","params":["0xBf380C52C18d5ead99ea719b6FCfbbA551Df2F7F", "pending"]}"#;
+const GET_LATEST_BLOCK_PAYLOAD: &str =
+    r#"{"jsonrpc":"2.0","id":1,"method":"eth_getBlockByNumber","params":["latest",false]}"#;
 
 pub struct EvmRpcClient {
     chain_id: u64,
@@ -32,8 +38,62 @@ impl EvmRpcClient {
         }
     }
 
+    /// Returns whether the chain is served by the default providers of the EVM RPC canister.
+    ///
+    /// Any other chain requires a custom RPC api
+    pub fn is_chain_supported(chain_id: u64) -> bool {
+        matches!(chain_id, MAINNET_CHAIN_ID | SEPOLIA_CHAIN_ID)
+    }
+
     /// Get next nonce for the given address
     pub async fn get_next_nonce(&self, address: H160) -> DeferredMinterResult<U256> {
+        let result = self.get_transaction_count(address).await;
+        Metrics::record_evm_rpc_call("eth_getTransactionCount", &result);
+
+        result
+    }
+
+    /// Call contract function
+    pub async fn eth_call(&self, to: &H160, data: Bytes) -> DeferredMinterResult<String> {
+        let result = self.call(to, data).await;
+        Metrics::record_evm_rpc_call("eth_call", &result);
+
+        result
+    }
+
+    /// Get the number of the latest block
+    pub async fn eth_block_number(&self) -> DeferredMinterResult<u64> {
+        let result = self.get_latest_block_number().await;
+        Metrics::record_evm_rpc_call("eth_getBlockByNumber", &result);
+
+        result
+    }
+
+    /// Get the logs emitted by the contract at `address` with one of the provided `topics`,
+    /// from `from_block` to `to_block` (both included).
+    pub async fn eth_get_logs(
+        &self,
+        address: &H160,
+        topics: Vec<String>,
+        from_block: u64,
+        to_block: u64,
+    ) -> DeferredMinterResult<Vec<LogEntry>> {
+        let result = self.get_logs(address, topics, from_block, to_block).await;
+        Metrics::record_evm_rpc_call("eth_getLogs", &result);
+
+        result
+    }
+
+    /// Send raw transaction to Ethereum network
+    pub async fn eth_send_raw_transaction(&self, tx: Bytes) -> DeferredMinterResult<()> {
+        let result = self.send_raw_transaction(tx).await;
+        Metrics::record_evm_rpc_call("eth_sendRawTransaction", &result);
+        Metrics::record_transaction(&result);
+
+        result
+    }
+
+    async fn get_transaction_count(&self, address: H160) -> DeferredMinterResult<U256> {
         if cfg!(test) {
             return Ok(U256::zero());
         }
@@ -75,8 +135,7 @@ impl EvmRpcClient {
         }
     }
 
-    /// Call contract function
-    pub async fn eth_call(&self, to: &H160, data: Bytes) -> DeferredMinterResult<String> {
+    async fn call(&self, to: &H160, data: Bytes) -> DeferredMinterResult<String> {
         if cfg!(test) {
             return Ok(
                 "0000000000000000000000000000000000000000000000000000000000003039".to_string(),
@@ -132,8 +191,103 @@ impl EvmRpcClient {
         }
     }
 
-    /// Send raw transaction to Ethereum network
-    pub async fn eth_send_raw_transaction(&self, tx: Bytes) -> DeferredMinterResult<()> {
+    async fn get_latest_block_number(&self) -> DeferredMinterResult<u64> {
+        if cfg!(test) {
+            return Ok(21_000_000);
+        }
+
+        let services = self.services();
+        let rpc_config: Option<RpcConfig> = None;
+
+        let cycles_cost = self.get_request_cost(GET_LATEST_BLOCK_PAYLOAD).await?;
+        log::debug!("estimated cost for get latest block: {cycles_cost}",);
+
+        let (result,) =
+            ic_cdk::api::call::call_with_payment128::<_, (MultiGetBlockByNumberResult,)>(
+                self.principal,
+                "eth_getBlockByNumber",
+                (services, rpc_config, BlockTag::Latest),
+                cycles_cost,
+            )
+            .await
+            .map_err(|(code, msg)| DeferredMinterError::CanisterCall(code, msg))?;
+
+        log::debug!("get latest block result: {result:?}",);
+
+        match result {
+            MultiGetBlockByNumberResult::Consistent(GetBlockByNumberResult::Ok(block)) => {
+                block.number.0.to_u64().ok_or_else(|| {
+                    DeferredMinterError::EvmRpc("Block number is too large".to_string())
+                })
+            }
+            MultiGetBlockByNumberResult::Consistent(GetBlockByNumberResult::Err(err)) => Err(
+                DeferredMinterError::EvmRpc(format!("Failed to get latest block: {:?}", err)),
+            ),
+            MultiGetBlockByNumberResult::Inconsistent(_) => Err(DeferredMinterError::EvmRpc(
+                "Failed to get latest block with inconsistent result".to_string(),
+            )),
+        }
+    }
+
+    async fn get_logs(
+        &self,
+        address: &H160,
+        topics: Vec<String>,
+        from_block: u64,
+        to_block: u64,
+    ) -> DeferredMinterResult<Vec<LogEntry>> {
+        if cfg!(test) {
+            return Ok(vec![]);
+        }
+
+        let services = self.services();
+        let rpc_config: Option<RpcConfig> = None;
+
+        let request_as_str = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"eth_getLogs","params":[{{"address":"{}","fromBlock":"{from_block:#x}","toBlock":"{to_block:#x}","topics":[[{}]]}}]}}"#,
+            address.to_hex_str(),
+            topics
+                .iter()
+                .map(|topic| format!(r#""{topic}""#))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        let cycles_cost = self.get_request_cost(&request_as_str).await?;
+        log::debug!("estimated cost for get logs: {cycles_cost}",);
+
+        let (result,) = ic_cdk::api::call::call_with_payment128::<_, (MultiGetLogsResult,)>(
+            self.principal,
+            "eth_getLogs",
+            (
+                services,
+                rpc_config,
+                GetLogsArgs {
+                    fromBlock: Some(BlockTag::Number(from_block.into())),
+                    toBlock: Some(BlockTag::Number(to_block.into())),
+                    addresses: vec![address.to_hex_str()],
+                    topics: Some(vec![topics]),
+                },
+            ),
+            cycles_cost,
+        )
+        .await
+        .map_err(|(code, msg)| DeferredMinterError::CanisterCall(code, msg))?;
+
+        log::debug!("get logs result: {result:?}",);
+
+        match result {
+            MultiGetLogsResult::Consistent(GetLogsResult::Ok(logs)) => Ok(logs),
+            MultiGetLogsResult::Consistent(GetLogsResult::Err(err)) => Err(
+                DeferredMinterError::EvmRpc(format!("Failed to get logs: {:?}", err)),
+            ),
+            MultiGetLogsResult::Inconsistent(_) => Err(DeferredMinterError::EvmRpc(
+                "Failed to get logs with inconsistent result".to_string(),
+            )),
+        }
+    }
+
+    async fn send_raw_transaction(&self, tx: Bytes) -> DeferredMinterResult<()> {
         if cfg!(test) {
             return Ok(());
         }
@@ -233,3 +233,57 @@ pub enum MultiCallResult {
     Consistent(CallResult),
     Inconsistent(Vec<(RpcService, CallResult)>),
 }
+
+#[derive(Debug, CandidType, Serialize)]
+pub struct GetLogsArgs {
+    #[allow(non_snake_case)]
+    pub fromBlock: Option<BlockTag>,
+    #[allow(non_snake_case)]
+    pub toBlock: Option<BlockTag>,
+    pub addresses: Vec<String>,
+    pub topics: Option<Vec<Vec<String>>>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct LogEntry {
+    pub transactionHash: Option<String>,
+    pub blockNumber: Option<candid::Nat>,
+    pub data: String,
+    pub blockHash: Option<String>,
+    pub transactionIndex: Option<candid::Nat>,
+    pub topics: Vec<String>,
+    pub address: String,
+    pub logIndex: Option<candid::Nat>,
+    pub removed: bool,
+}
+
+#[derive(Debug, CandidType, Deserialize)]
+pub enum GetLogsResult {
+    Ok(Vec<LogEntry>),
+    Err(RpcError),
+}
+
+#[derive(Debug, CandidType, Deserialize)]
+pub enum MultiGetLogsResult {
+    Consistent(GetLogsResult),
+    Inconsistent(Vec<(RpcService, GetLogsResult)>),
+}
+
+/// Block returned by `eth_getBlockByNumber`; the other fields of the record are ignored
+#[derive(Debug, CandidType, Deserialize)]
+pub struct Block {
+    pub number: candid::Nat,
+}
+
+#[derive(Debug, CandidType, Deserialize)]
+pub enum GetBlockByNumberResult {
+    Ok(Block),
+    Err(RpcError),
+}
+
+#[derive(Debug, CandidType, Deserialize)]
+pub enum MultiGetBlockByNumberResult {
+    Consistent(GetBlockByNumberResult),
+    Inconsistent(Vec<(RpcService, GetBlockByNumberResult)>),
+}
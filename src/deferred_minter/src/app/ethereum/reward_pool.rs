@@ -1,9 +1,32 @@
+use std::str::FromStr as _;
+
 use abi::{self, AvailableRewardCall};
 use did::deferred::{DeferredMinterError, DeferredMinterResult};
 use did::H160;
-use ethers_core::abi::{AbiDecode, AbiEncode};
+use ethers_core::abi::{AbiDecode, AbiEncode, ParamType, Token};
+use ethers_core::types::{Bytes, H256};
+use ethers_core::utils::keccak256;
+use num_traits::cast::ToPrimitive;
+
+use super::evm_rpc_client::{EvmRpcClient, LogEntry};
 
-use super::evm_rpc_client::EvmRpcClient;
+/// Signature of the `PoolReserved` event
+const POOL_RESERVED_EVENT: &str = "PoolReserved(uint256,uint256)";
+/// Signature of the `RewardSent` event
+const REWARD_SENT_EVENT: &str = "RewardSent(address,uint256)";
+
+/// An event emitted by the reward pool contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewardPoolEvent {
+    /// `reward` for each of the `tokens` has been reserved
+    PoolReserved {
+        reward: u128,
+        tokens: u64,
+        block: Option<u64>,
+    },
+    /// `amount` of reward has been sent to a user
+    RewardSent { amount: u128, block: Option<u64> },
+}
 
 pub struct RewardPool {
     address: H160,
@@ -34,6 +57,84 @@ impl RewardPool {
 
         Ok(available.available.as_u128())
     }
+
+    /// Get the `PoolReserved` and `RewardSent` events emitted by the reward pool from `from_block`
+    /// to `to_block` (both included)
+    pub async fn events(
+        &self,
+        client: &EvmRpcClient,
+        from_block: u64,
+        to_block: u64,
+    ) -> DeferredMinterResult<Vec<RewardPoolEvent>> {
+        let topics = vec![
+            Self::event_topic(POOL_RESERVED_EVENT),
+            Self::event_topic(REWARD_SENT_EVENT),
+        ];
+
+        let logs = client
+            .eth_get_logs(&self.address, topics, from_block, to_block)
+            .await?;
+        log::debug!("reward pool logs: {logs:?}");
+
+        let mut events = Vec::with_capacity(logs.len());
+        for log in logs.into_iter().filter(|log| !log.removed) {
+            if let Some(event) = Self::decode_event(log)? {
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Decode a log emitted by the reward pool.
+    ///
+    /// Returns `None` if the log is not a `PoolReserved` or a `RewardSent` event
+    fn decode_event(log: LogEntry) -> DeferredMinterResult<Option<RewardPoolEvent>> {
+        let Some(topic) = log.topics.first() else {
+            return Ok(None);
+        };
+        let block = log.blockNumber.and_then(|block| block.0.to_u64());
+        let data = Bytes::from_str(&log.data)
+            .map_err(|err| DeferredMinterError::FailedToDecodeOutput(err.to_string()))?;
+
+        if topic.eq_ignore_ascii_case(&Self::event_topic(POOL_RESERVED_EVENT)) {
+            let tokens =
+                ethers_core::abi::decode(&[ParamType::Uint(256), ParamType::Uint(256)], &data)
+                    .map_err(|err| DeferredMinterError::FailedToDecodeOutput(err.to_string()))?;
+            match tokens.as_slice() {
+                [Token::Uint(reward), Token::Uint(tokens)] => {
+                    Ok(Some(RewardPoolEvent::PoolReserved {
+                        reward: reward.as_u128(),
+                        tokens: tokens.as_u64(),
+                        block,
+                    }))
+                }
+                _ => Err(DeferredMinterError::FailedToDecodeOutput(
+                    "invalid PoolReserved event data".to_string(),
+                )),
+            }
+        } else if topic.eq_ignore_ascii_case(&Self::event_topic(REWARD_SENT_EVENT)) {
+            let tokens =
+                ethers_core::abi::decode(&[ParamType::Address, ParamType::Uint(256)], &data)
+                    .map_err(|err| DeferredMinterError::FailedToDecodeOutput(err.to_string()))?;
+            match tokens.as_slice() {
+                [Token::Address(_), Token::Uint(amount)] => Ok(Some(RewardPoolEvent::RewardSent {
+                    amount: amount.as_u128(),
+                    block,
+                })),
+                _ => Err(DeferredMinterError::FailedToDecodeOutput(
+                    "invalid RewardSent event data".to_string(),
+                )),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get the topic of the event with the provided signature
+    fn event_topic(signature: &str) -> String {
+        format!("{:?}", H256::from(keccak256(signature)))
+    }
 }
 
 #[cfg(test)]
@@ -55,4 +156,68 @@ mod test {
 
         assert_eq!(reward_pool, 592006734000000);
     }
+
+    #[test]
+    fn test_should_decode_pool_reserved_event() {
+        let data = ethers_core::abi::encode(&[
+            Token::Uint(500_000u64.into()),
+            Token::Uint(4_000u64.into()),
+        ]);
+        let log = mock_log(
+            RewardPool::event_topic(POOL_RESERVED_EVENT),
+            Bytes::from(data).to_string(),
+        );
+
+        let event = RewardPool::decode_event(log).unwrap().unwrap();
+        assert_eq!(
+            event,
+            RewardPoolEvent::PoolReserved {
+                reward: 500_000,
+                tokens: 4_000,
+                block: Some(10),
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_decode_reward_sent_event() {
+        let data = ethers_core::abi::encode(&[
+            Token::Address(ethers_core::types::H160::zero()),
+            Token::Uint(1_000u64.into()),
+        ]);
+        let log = mock_log(
+            RewardPool::event_topic(REWARD_SENT_EVENT),
+            Bytes::from(data).to_string(),
+        );
+
+        let event = RewardPool::decode_event(log).unwrap().unwrap();
+        assert_eq!(
+            event,
+            RewardPoolEvent::RewardSent {
+                amount: 1_000,
+                block: Some(10),
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_ignore_unknown_event() {
+        let log = mock_log(format!("{:?}", H256::zero()), "0x".to_string());
+
+        assert!(RewardPool::decode_event(log).unwrap().is_none());
+    }
+
+    fn mock_log(topic: String, data: String) -> LogEntry {
+        LogEntry {
+            transactionHash: None,
+            blockNumber: Some(10u64.into()),
+            data,
+            blockHash: None,
+            transactionIndex: None,
+            topics: vec![topic],
+            address: "0x2CE04Fd64DB0372F6fb4B7a542f0F9196feE5663".to_string(),
+            logIndex: None,
+            removed: false,
+        }
+    }
 }
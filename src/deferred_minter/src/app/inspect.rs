@@ -2,11 +2,15 @@
 //!
 //! Deferred inspect message handler
 
+use std::str::FromStr as _;
+
 use candid::Principal;
 use did::deferred::{
-    ContractError, ContractRegistration, DeferredMinterError, DeferredMinterResult,
+    Agency, AgencyError, AgencyId, AgencyRole, ContractError, ContractRegistration,
+    DeferredMinterError, DeferredMinterResult, Permission,
 };
 use did::H160;
+use url::Url;
 
 use super::agents::Agents;
 use super::configuration::Configuration;
@@ -15,18 +19,35 @@ use super::roles::RolesManager;
 pub struct Inspect;
 
 impl Inspect {
-    /// Returns whether caller is custodian of the canister
-    pub fn inspect_is_custodian(caller: Principal) -> bool {
-        RolesManager::is_custodian(caller)
+    /// Returns whether the roles of the caller grant the provided permission.
+    ///
+    /// This is the guard every privileged endpoint goes through
+    pub fn inspect_has_permission(caller: Principal, permission: Permission) -> bool {
+        RolesManager::has_permission(caller, permission)
     }
 
-    /// Returns whether caller is gas station
-    pub fn inspect_is_gas_station(caller: Principal) -> bool {
-        RolesManager::is_gas_station(caller) || RolesManager::is_custodian(caller)
+    /// Returns whether caller has been granted the permission to create contracts
+    /// or is a member of an agency who can create contracts and real estates
+    pub fn inspect_is_agent(caller: Principal) -> bool {
+        RolesManager::has_permission(caller, Permission::CreateContracts)
+            || Agents::get_membership(caller).is_some_and(|member| member.role.can_write())
     }
 
-    pub fn inspect_is_agent(caller: Principal) -> bool {
-        RolesManager::is_agent(caller)
+    /// Returns whether caller can create and modify the contracts and real estates of the agency
+    pub fn inspect_can_operate_agency(caller: Principal, agency: AgencyId) -> bool {
+        Agents::get_membership(caller)
+            .is_some_and(|member| member.agency == agency && member.role.can_write())
+    }
+
+    /// Returns whether caller is a member of the agency, with any role
+    pub fn inspect_is_agency_member(caller: Principal, agency: AgencyId) -> bool {
+        Agents::get_membership(caller).is_some_and(|member| member.agency == agency)
+    }
+
+    /// Returns whether caller is an admin of the agency
+    pub fn inspect_is_agency_admin(caller: Principal, agency: AgencyId) -> bool {
+        Agents::get_membership(caller)
+            .is_some_and(|member| member.agency == agency && member.role == AgencyRole::Admin)
     }
 
     /// Inspect register contract parameters:
@@ -117,9 +138,80 @@ impl Inspect {
         Ok(())
     }
 
-    /// Inspect whether caller is custodian or owner of the agency
-    pub fn inspect_remove_agency(caller: Principal) -> bool {
-        RolesManager::is_custodian(caller) || Agents::get_agency_by_wallet(caller).is_some()
+    /// Inspect whether caller can manage agencies or is admin of the agency
+    pub fn inspect_remove_agency(caller: Principal, agency: AgencyId) -> bool {
+        Self::inspect_has_permission(caller, Permission::ManageAgencies)
+            || Self::inspect_is_agency_admin(caller, agency)
+    }
+
+    /// Inspect whether caller is the owner of the agency it is a member of
+    pub fn inspect_is_agency_owner(caller: Principal) -> bool {
+        Agents::get_agency_by_member(caller).is_some_and(|agency| agency.owner == caller)
+    }
+
+    /// Inspect agency profile fields:
+    ///
+    /// - name and email cannot be empty
+    /// - email must be a valid email address
+    /// - website and logo, if set, must be valid http(s) URLs
+    /// - latitude must be in range [-90, 90] and longitude in range [-180, 180]
+    pub fn inspect_agency(agency: &Agency) -> DeferredMinterResult<()> {
+        if agency.name.trim().is_empty() {
+            return Err(AgencyError::EmptyField("name".to_string()).into());
+        }
+        if agency.email.trim().is_empty() {
+            return Err(AgencyError::EmptyField("email".to_string()).into());
+        }
+
+        if !Self::is_valid_email(&agency.email) {
+            return Err(AgencyError::InvalidEmail(agency.email.clone()).into());
+        }
+
+        if !agency.website.is_empty() && !Self::is_valid_url(&agency.website) {
+            return Err(AgencyError::InvalidUrl(agency.website.clone()).into());
+        }
+        if let Some(logo) = agency.logo.as_deref() {
+            if !Self::is_valid_url(logo) {
+                return Err(AgencyError::InvalidUrl(logo.to_string()).into());
+            }
+        }
+
+        if let Some(lat) = agency.lat.as_deref() {
+            if !f64::from_str(lat).is_ok_and(|lat| (-90.0..=90.0).contains(&lat)) {
+                return Err(AgencyError::InvalidLatitude(lat.to_string()).into());
+            }
+        }
+        if let Some(lng) = agency.lng.as_deref() {
+            if !f64::from_str(lng).is_ok_and(|lng| (-180.0..=180.0).contains(&lng)) {
+                return Err(AgencyError::InvalidLongitude(lng.to_string()).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the provided string has the syntax `local@domain.tld`
+    fn is_valid_email(email: &str) -> bool {
+        if email.chars().any(char::is_whitespace) {
+            return false;
+        }
+
+        let Some((local, domain)) = email.split_once('@') else {
+            return false;
+        };
+
+        !local.is_empty()
+            && !domain.contains('@')
+            && domain
+                .split('.')
+                .all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+            && domain.contains('.')
+    }
+
+    /// Returns whether the provided string is a valid http(s) URL
+    fn is_valid_url(url: &str) -> bool {
+        Url::parse(url)
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
     }
 }
 
@@ -127,6 +219,7 @@ impl Inspect {
 mod test {
 
     use did::deferred::{Role, Seller};
+    use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::app::test_utils::{self, alice, bob, charlie};
@@ -136,11 +229,15 @@ mod test {
         assert!(RolesManager::set_custodians(vec![caller]).is_ok());
 
         // register agency
-        Agents::insert_agency(bob(), test_utils::mock_agency());
-        assert!(Inspect::inspect_remove_agency(caller));
-        assert!(Inspect::inspect_remove_agency(bob()));
+        let agency =
+            Agents::insert_agency(test_utils::with_mock_agency(|a| a.owner = bob())).unwrap();
+        Agents::insert_agency(test_utils::with_mock_agency(|a| a.owner = charlie())).unwrap();
+        assert!(Inspect::inspect_remove_agency(caller, agency));
+        assert!(Inspect::inspect_remove_agency(bob(), agency));
+        assert!(!Inspect::inspect_remove_agency(charlie(), agency));
         assert!(!Inspect::inspect_remove_agency(
-            Principal::management_canister()
+            Principal::management_canister(),
+            agency
         ));
     }
 
@@ -508,12 +605,107 @@ mod test {
         .is_err());
     }
 
+    #[test]
+    fn test_should_inspect_agency_owner() {
+        let agency =
+            Agents::insert_agency(test_utils::with_mock_agency(|a| a.owner = alice())).unwrap();
+        Agents::set_member(agency, bob(), AgencyRole::Admin);
+
+        assert!(Inspect::inspect_is_agency_owner(alice()));
+        assert!(!Inspect::inspect_is_agency_owner(bob()));
+        assert!(!Inspect::inspect_is_agency_owner(charlie()));
+    }
+
+    #[test]
+    fn test_should_inspect_agency() {
+        let valid = test_utils::with_mock_agency(|agency| {
+            agency.email = "info@agency.com".to_string();
+            agency.website = "https://agency.com".to_string();
+            agency.logo = Some("https://agency.com/logo.png".to_string());
+            agency.lat = Some("41.9".to_string());
+            agency.lng = Some("12.5".to_string());
+        });
+        assert!(Inspect::inspect_agency(&valid).is_ok());
+
+        let mut agency = valid.clone();
+        agency.name = " ".to_string();
+        assert_eq!(
+            Inspect::inspect_agency(&agency).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::EmptyField("name".to_string()))
+        );
+
+        for email in [
+            "info",
+            "info@",
+            "@agency.com",
+            "info@agency",
+            "in fo@agency.com",
+        ] {
+            let mut agency = valid.clone();
+            agency.email = email.to_string();
+            assert_eq!(
+                Inspect::inspect_agency(&agency).unwrap_err(),
+                DeferredMinterError::Agency(AgencyError::InvalidEmail(email.to_string()))
+            );
+        }
+
+        let mut agency = valid.clone();
+        agency.website = "ftp://agency.com".to_string();
+        assert!(matches!(
+            Inspect::inspect_agency(&agency).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::InvalidUrl(_))
+        ));
+
+        let mut agency = valid.clone();
+        agency.logo = Some("logo.png".to_string());
+        assert!(matches!(
+            Inspect::inspect_agency(&agency).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::InvalidUrl(_))
+        ));
+
+        let mut agency = valid.clone();
+        agency.lat = Some("91".to_string());
+        assert!(matches!(
+            Inspect::inspect_agency(&agency).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::InvalidLatitude(_))
+        ));
+
+        let mut agency = valid.clone();
+        agency.lng = Some("east".to_string());
+        assert!(matches!(
+            Inspect::inspect_agency(&agency).unwrap_err(),
+            DeferredMinterError::Agency(AgencyError::InvalidLongitude(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_inspect_agency_membership() {
+        let agency = Agents::insert_agency(test_utils::mock_agency()).unwrap();
+        Agents::set_member(agency, bob(), AgencyRole::Agent);
+        Agents::set_member(agency, charlie(), AgencyRole::ReadOnly);
+
+        assert!(Inspect::inspect_is_agent(alice()));
+        assert!(Inspect::inspect_is_agent(bob()));
+        assert!(!Inspect::inspect_is_agent(charlie()));
+
+        assert!(Inspect::inspect_can_operate_agency(alice(), agency));
+        assert!(Inspect::inspect_can_operate_agency(bob(), agency));
+        assert!(!Inspect::inspect_can_operate_agency(charlie(), agency));
+        assert!(!Inspect::inspect_can_operate_agency(bob(), AgencyId(2)));
+
+        assert!(Inspect::inspect_is_agency_member(charlie(), agency));
+        assert!(Inspect::inspect_is_agency_admin(alice(), agency));
+        assert!(!Inspect::inspect_is_agency_admin(bob(), agency));
+    }
+
     #[test]
     fn test_should_inspect_admin() {
         RolesManager::give_role(alice(), Role::Custodian);
 
-        assert!(Inspect::inspect_is_custodian(alice()));
-        assert!(!Inspect::inspect_is_custodian(bob()));
+        for permission in Permission::all() {
+            assert!(Inspect::inspect_has_permission(alice(), permission));
+            assert!(!Inspect::inspect_has_permission(bob(), permission));
+        }
     }
 
     #[test]
@@ -521,8 +713,32 @@ mod test {
         RolesManager::give_role(alice(), Role::Custodian);
         RolesManager::give_role(bob(), Role::GasStation);
 
-        assert!(Inspect::inspect_is_gas_station(alice()));
-        assert!(Inspect::inspect_is_gas_station(bob()));
-        assert!(!Inspect::inspect_is_gas_station(charlie()));
+        assert!(Inspect::inspect_has_permission(alice(), Permission::SetGas));
+        assert!(Inspect::inspect_has_permission(bob(), Permission::SetGas));
+        assert!(!Inspect::inspect_has_permission(
+            bob(),
+            Permission::ManageConfig
+        ));
+        assert!(!Inspect::inspect_has_permission(
+            charlie(),
+            Permission::SetGas
+        ));
+    }
+
+    #[test]
+    fn test_should_inspect_custom_role() {
+        assert!(
+            RolesManager::define_role("auditor".to_string(), vec![Permission::ReadLogs]).is_ok()
+        );
+        RolesManager::give_role(alice(), Role::Custom("auditor".to_string()));
+
+        assert!(Inspect::inspect_has_permission(
+            alice(),
+            Permission::ReadLogs
+        ));
+        assert!(!Inspect::inspect_has_permission(
+            alice(),
+            Permission::ManageRoles
+        ));
     }
 }
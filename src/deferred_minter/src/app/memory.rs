@@ -1,7 +1,19 @@
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager as IcMemoryManager};
-use ic_stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::{DefaultMemoryImpl, Memory as _};
 
-pub const AGENCIES_MEMORY_ID: MemoryId = MemoryId::new(10);
+/// Size of a stable memory page
+const WASM_PAGE_SIZE: u64 = 65536;
+
+pub const LEGACY_AGENCIES_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const AGENCIES_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub const AGENCY_APPLICATIONS_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const NEXT_AGENCY_APPLICATION_ID_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const AGENCY_MEMBERS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const AGENCIES_STATUS_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const AGENCY_TRANSFERS_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const NEXT_AGENCY_TRANSFER_ID_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const AGENCY_APPLICATION_DOCUMENTS_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const NEXT_AGENCY_ID_MEMORY_ID: MemoryId = MemoryId::new(19);
 
 pub const ALLOWED_CURRENCIES_MEMORY_ID: MemoryId = MemoryId::new(20);
 pub const DEFERRED_DATA_CANISTER_MEMORY_ID: MemoryId = MemoryId::new(21);
@@ -15,6 +27,8 @@ pub const EVM_GAS_PRICE_MEMORY_ID: MemoryId = MemoryId::new(28);
 pub const LOG_SETTINGS_MEMORY_ID: MemoryId = MemoryId::new(29);
 
 pub const ROLES_MEMORY_ID: MemoryId = MemoryId::new(30);
+pub const CUSTOM_ROLES_MEMORY_ID: MemoryId = MemoryId::new(31);
+pub const SNS_GOVERNANCE_MEMORY_ID: MemoryId = MemoryId::new(32);
 
 pub const ETH_WALLET_ADDRESS_MEMORY_ID: MemoryId = MemoryId::new(40);
 pub const ETH_WALLET_PUBKEY_MEMORY_ID: MemoryId = MemoryId::new(41);
@@ -29,7 +43,53 @@ pub const CPM_MEMORY_ID: MemoryId = MemoryId::new(63);
 pub const LAST_CPM_MEMORY_ID: MemoryId = MemoryId::new(64);
 pub const LAST_MONTH_MEMORY_ID: MemoryId = MemoryId::new(65);
 
+// Reward pool ledger
+pub const REWARD_POOL_AVAILABLE_MEMORY_ID: MemoryId = MemoryId::new(70);
+pub const REWARD_POOL_AVAILABLE_UPDATED_AT_MEMORY_ID: MemoryId = MemoryId::new(71);
+pub const REWARD_POOL_RESERVATIONS_MEMORY_ID: MemoryId = MemoryId::new(72);
+pub const REWARD_POOL_RESERVED_ON_CHAIN_MEMORY_ID: MemoryId = MemoryId::new(73);
+pub const REWARD_POOL_REWARDS_SENT_MEMORY_ID: MemoryId = MemoryId::new(74);
+pub const REWARD_POOL_LAST_SYNCED_BLOCK_MEMORY_ID: MemoryId = MemoryId::new(75);
+pub const REWARD_POOL_ALERTS_MEMORY_ID: MemoryId = MemoryId::new(76);
+pub const REWARD_POOL_DEPLOY_BLOCK_MEMORY_ID: MemoryId = MemoryId::new(77);
+
+// Custodian proposals
+pub const PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(80);
+pub const NEXT_PROPOSAL_ID_MEMORY_ID: MemoryId = MemoryId::new(81);
+pub const PROPOSAL_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(82);
+
+// Audit log
+pub const AUDIT_LOG_MEMORY_ID: MemoryId = MemoryId::new(90);
+pub const AUDIT_LOG_BY_CALLER_MEMORY_ID: MemoryId = MemoryId::new(91);
+pub const AUDIT_LOG_BY_METHOD_MEMORY_ID: MemoryId = MemoryId::new(92);
+
+// Configuration history
+pub const CONFIGURATION_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(100);
+
+// Paused operations
+pub const PAUSED_OPERATIONS_MEMORY_ID: MemoryId = MemoryId::new(110);
+
+// Schema migrations
+pub const SCHEMA_VERSIONS_MEMORY_ID: MemoryId = MemoryId::new(120);
+pub const MIGRATIONS_MEMORY_ID: MemoryId = MemoryId::new(121);
+
+// Agency members to synchronize with the deferred data canister
+pub const PENDING_MEMBER_SYNCS_MEMORY_ID: MemoryId = MemoryId::new(130);
+
+// Agencies keyed by their id
+pub const AGENCIES_MEMORY_ID: MemoryId = MemoryId::new(140);
+
 thread_local! {
     /// Memory manager
     pub static MEMORY_MANAGER: IcMemoryManager<DefaultMemoryImpl> = IcMemoryManager::init(DefaultMemoryImpl::default());
 }
+
+/// Returns the size in bytes of the stable memory of each [`MemoryId`] in use
+pub fn stable_memory_sizes() -> Vec<(u8, u64)> {
+    MEMORY_MANAGER.with(|mm| {
+        (0..u8::MAX)
+            .map(|id| (id, mm.get(MemoryId::new(id)).size() * WASM_PAGE_SIZE))
+            .filter(|(_, size)| *size > 0)
+            .collect()
+    })
+}
@@ -0,0 +1,208 @@
+//! # Metrics
+//!
+//! Operational metrics of the minter, exported in the Prometheus text exposition format.
+//!
+//! The EVM RPC and transaction counters are kept on the heap, so they are reset on upgrade.
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+
+use did::deferred::DeferredMinterResult;
+use did::MetricsEncoder;
+use num_traits::cast::ToPrimitive;
+
+use crate::app::contract_id::ContractId;
+use crate::app::memory::stable_memory_sizes;
+use crate::app::reward::Reward;
+use crate::app::Agents;
+use crate::utils::cycles;
+
+thread_local! {
+    /// EVM RPC calls by method
+    static EVM_RPC_CALLS: RefCell<BTreeMap<&'static str, u64>> = RefCell::new(BTreeMap::new());
+
+    /// Failed EVM RPC calls by method
+    static EVM_RPC_FAILURES: RefCell<BTreeMap<&'static str, u64>> = RefCell::new(BTreeMap::new());
+
+    /// Transactions accepted by the EVM RPC
+    static TRANSACTIONS_SENT: Cell<u64> = const { Cell::new(0) };
+
+    /// Transactions rejected by the EVM RPC
+    static TRANSACTIONS_REJECTED: Cell<u64> = const { Cell::new(0) };
+}
+
+pub struct Metrics;
+
+impl Metrics {
+    /// Record the outcome of a call to the EVM RPC `method`
+    pub fn record_evm_rpc_call<T>(method: &'static str, result: &DeferredMinterResult<T>) {
+        EVM_RPC_CALLS.with_borrow_mut(|calls| *calls.entry(method).or_default() += 1);
+        if result.is_err() {
+            EVM_RPC_FAILURES.with_borrow_mut(|failures| *failures.entry(method).or_default() += 1);
+        }
+    }
+
+    /// Record the outcome of sending a transaction.
+    ///
+    /// The minter doesn't wait for the receipts, so a transaction is rejected when the RPC doesn't
+    /// accept it, which includes the transactions reverted on the estimation.
+    pub fn record_transaction(result: &DeferredMinterResult<()>) {
+        let counter = if result.is_ok() {
+            &TRANSACTIONS_SENT
+        } else {
+            &TRANSACTIONS_REJECTED
+        };
+        counter.with(|count| count.set(count.get() + 1));
+    }
+
+    /// Encode the metrics in the Prometheus text exposition format
+    pub fn encode() -> String {
+        let mut encoder = MetricsEncoder::default();
+        let contracts = ContractId::get_next_contract_id()
+            .0
+            .to_u64()
+            .unwrap_or_default()
+            .saturating_sub(1);
+
+        encoder
+            .gauge(
+                "deferred_minter_cycles_balance",
+                "Cycles balance of the canister",
+                cycles().0.to_f64().unwrap_or_default(),
+            )
+            .gauge_vec(
+                "deferred_minter_stable_memory_bytes",
+                "Size of the stable memory by memory id",
+                "memory_id",
+                stable_memory_sizes()
+                    .into_iter()
+                    .map(|(id, size)| (id.to_string(), size as f64)),
+            )
+            .gauge(
+                "deferred_minter_agencies",
+                "Amount of registered agencies",
+                Agents::count_agencies() as f64,
+            )
+            .gauge(
+                "deferred_minter_agencies_suspended",
+                "Amount of suspended agencies",
+                Agents::count_suspended() as f64,
+            )
+            .counter(
+                "deferred_minter_contracts_total",
+                "Amount of created contracts",
+                contracts as f64,
+            )
+            .counter_vec(
+                "deferred_minter_evm_rpc_calls_total",
+                "EVM RPC calls by method",
+                "method",
+                EVM_RPC_CALLS.with_borrow(|calls| Self::samples(calls)),
+            )
+            .counter_vec(
+                "deferred_minter_evm_rpc_failures_total",
+                "Failed EVM RPC calls by method",
+                "method",
+                EVM_RPC_FAILURES.with_borrow(|failures| Self::samples(failures)),
+            )
+            .counter(
+                "deferred_minter_transactions_sent_total",
+                "Transactions accepted by the EVM RPC",
+                TRANSACTIONS_SENT.get() as f64,
+            )
+            .counter(
+                "deferred_minter_transactions_rejected_total",
+                "Transactions rejected or reverted on submission by the EVM RPC",
+                TRANSACTIONS_REJECTED.get() as f64,
+            )
+            .gauge(
+                "deferred_minter_reward_rmc",
+                "Reward multiplier coefficient",
+                Reward::rmc(),
+            )
+            .gauge(
+                "deferred_minter_reward_avidity",
+                "Reward avidity",
+                Reward::avidity(),
+            )
+            .gauge(
+                "deferred_minter_reward_cpm",
+                "Contracts created in the current month",
+                Reward::cpm() as f64,
+            )
+            .gauge(
+                "deferred_minter_reward_last_cpm",
+                "Contracts created in the previous month",
+                Reward::last_cpm() as f64,
+            )
+            .gauge(
+                "deferred_minter_reward_next_halving_timestamp_seconds",
+                "Time of the next RMC halving",
+                (Reward::next_halving() / 1_000_000_000) as f64,
+            )
+            .gauge(
+                "deferred_minter_reward_min",
+                "Minimum reward of a contract",
+                Reward::min_reward() as f64,
+            );
+
+        encoder.finish()
+    }
+
+    fn samples(counters: &BTreeMap<&'static str, u64>) -> Vec<(&'static str, f64)> {
+        counters
+            .iter()
+            .map(|(method, count)| (*method, *count as f64))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::DeferredMinterError;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_count_evm_rpc_calls() {
+        Metrics::record_evm_rpc_call("eth_call", &Ok(()));
+        Metrics::record_evm_rpc_call::<()>(
+            "eth_call",
+            &Err(DeferredMinterError::EvmRpc("error".to_string())),
+        );
+        Metrics::record_evm_rpc_call("eth_getLogs", &Ok(()));
+
+        let metrics = Metrics::encode();
+        assert!(metrics.contains("deferred_minter_evm_rpc_calls_total{method=\"eth_call\"} 2\n"));
+        assert!(metrics.contains("deferred_minter_evm_rpc_calls_total{method=\"eth_getLogs\"} 1\n"));
+        assert!(metrics.contains("deferred_minter_evm_rpc_failures_total{method=\"eth_call\"} 1\n"));
+        assert!(!metrics.contains("deferred_minter_evm_rpc_failures_total{method=\"eth_getLogs\"}"));
+    }
+
+    #[test]
+    fn test_should_count_transactions() {
+        Metrics::record_transaction(&Ok(()));
+        Metrics::record_transaction(&Ok(()));
+        Metrics::record_transaction(&Err(DeferredMinterError::EvmRpc("reverted".to_string())));
+
+        let metrics = Metrics::encode();
+        assert!(metrics.contains("deferred_minter_transactions_sent_total 2\n"));
+        assert!(metrics.contains("deferred_minter_transactions_rejected_total 1\n"));
+    }
+
+    #[test]
+    fn test_should_encode_reward_parameters() {
+        let metrics = Metrics::encode();
+        assert!(metrics.contains(&format!("deferred_minter_reward_rmc {}\n", Reward::rmc())));
+        assert!(metrics.contains("deferred_minter_reward_min 1000\n"));
+        assert_eq!(
+            metrics
+                .lines()
+                .filter(|line| line.starts_with("# TYPE"))
+                .count(),
+            15
+        );
+    }
+}
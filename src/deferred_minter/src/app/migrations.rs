@@ -0,0 +1,230 @@
+//! # Migrations
+//!
+//! Versioned schema migrations of the collections of Candid-encoded records.
+//!
+//! A schema version is stored alongside each collection. On upgrade, the collections whose
+//! version is behind the one listed in [`COLLECTIONS`] are queued for migration, and their records
+//! are migrated in batches of [`MIGRATION_BATCH_SIZE`], one batch per timer tick.
+//!
+//! Migrating a record consists of decoding it and encoding it again with the current schema.
+//! To make an incompatible change to a record, keep its previous definition and make the
+//! `Storable::from_bytes` of the record fall back to decoding and converting it, then bump the
+//! target version of the collection in [`COLLECTIONS`].
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use did::deferred::{AgencyId, CollectionSchema, MigrationProgress, MigrationStatus};
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, Storable as _};
+
+use super::Agents;
+use crate::app::memory::{MEMORY_MANAGER, MIGRATIONS_MEMORY_ID, SCHEMA_VERSIONS_MEMORY_ID};
+use crate::utils::time;
+
+const AGENCIES: &str = "agencies";
+
+/// Collections with the schema version of the records written by this wasm
+const COLLECTIONS: &[(&str, u32)] = &[(AGENCIES, 1)];
+
+/// Number of records migrated on each tick
+const MIGRATION_BATCH_SIZE: usize = 100;
+
+thread_local! {
+    /// Schema version of the records stored in each collection
+    static SCHEMA_VERSIONS: RefCell<BTreeMap<String, u32, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(SCHEMA_VERSIONS_MEMORY_ID))));
+
+    /// Pending migrations by collection
+    static MIGRATIONS: RefCell<BTreeMap<String, MigrationProgress, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(MIGRATIONS_MEMORY_ID))));
+}
+
+pub struct Migrations;
+
+impl Migrations {
+    /// Set every collection at its target schema version, on a fresh install
+    pub fn init() {
+        SCHEMA_VERSIONS.with_borrow_mut(|versions| {
+            for (collection, target) in COLLECTIONS {
+                versions.insert(collection.to_string(), *target);
+            }
+        });
+    }
+
+    /// Queue the migration of the collections behind their target schema version and schedule
+    /// the first batch.
+    ///
+    /// The collections stored before the schema versions were introduced are at version 0.
+    /// A migration left pending by the previous wasm is restarted from the first record
+    pub fn start() {
+        for (collection, target) in COLLECTIONS {
+            let version = Self::version(collection);
+            if version > *target {
+                log::warn!(
+                    "{collection} schema version {version} is newer than the supported one {target}"
+                );
+                continue;
+            }
+            if version == *target {
+                continue;
+            }
+
+            log::info!("Migrating {collection} from schema version {version} to {target}");
+            let progress = MigrationProgress {
+                collection: collection.to_string(),
+                from_version: version,
+                to_version: *target,
+                migrated: 0,
+                total: collection_len(collection),
+                started_at: time(),
+                cursor: None,
+            };
+            MIGRATIONS.with_borrow_mut(|migrations| {
+                migrations.insert(collection.to_string(), progress);
+            });
+        }
+
+        Self::schedule();
+    }
+
+    /// Migrate the next batch of records of the first pending migration.
+    ///
+    /// Once all the records of the collection have been migrated, its schema version is set to
+    /// the target one. The next tick is scheduled as long as there are pending migrations
+    pub fn tick() {
+        let Some((collection, mut progress)) =
+            MIGRATIONS.with_borrow(|migrations| migrations.first_key_value())
+        else {
+            return;
+        };
+
+        let after = progress
+            .cursor
+            .as_deref()
+            .map(|key| AgencyId::from_bytes(Cow::Borrowed(key)));
+        let (migrated, last) = migrate_batch(&collection, after);
+        progress.migrated += migrated;
+
+        if migrated < MIGRATION_BATCH_SIZE as u64 {
+            log::info!(
+                "Migrated {} {collection} to schema version {}",
+                progress.migrated,
+                progress.to_version
+            );
+            MIGRATIONS.with_borrow_mut(|migrations| migrations.remove(&collection));
+            SCHEMA_VERSIONS.with_borrow_mut(|versions| {
+                versions.insert(collection, progress.to_version);
+            });
+        } else {
+            progress.cursor = last.map(|key| key.to_bytes().into_owned());
+            MIGRATIONS.with_borrow_mut(|migrations| migrations.insert(collection, progress));
+        }
+
+        Self::schedule();
+    }
+
+    /// Get the schema version of the collections and the pending migrations
+    pub fn status() -> MigrationStatus {
+        MigrationStatus {
+            collections: COLLECTIONS
+                .iter()
+                .map(|(collection, target)| CollectionSchema {
+                    collection: collection.to_string(),
+                    version: Self::version(collection),
+                    target_version: *target,
+                })
+                .collect(),
+            pending: MIGRATIONS.with_borrow(|migrations| {
+                migrations.iter().map(|(_, progress)| progress).collect()
+            }),
+        }
+    }
+
+    fn version(collection: &str) -> u32 {
+        SCHEMA_VERSIONS
+            .with_borrow(|versions| versions.get(&collection.to_string()))
+            .unwrap_or_default()
+    }
+
+    /// Schedule the next tick if there are pending migrations
+    fn schedule() {
+        if cfg!(test) || MIGRATIONS.with_borrow(|migrations| migrations.is_empty()) {
+            return;
+        }
+
+        ic_cdk_timers::set_timer(Duration::ZERO, Self::tick);
+    }
+}
+
+fn collection_len(collection: &str) -> u64 {
+    match collection {
+        AGENCIES => Agents::len(),
+        _ => 0,
+    }
+}
+
+/// Migrate the batch of records of the collection following the key `after`.
+///
+/// Returns the number of migrated records and the key of the last one
+fn migrate_batch(collection: &str, after: Option<AgencyId>) -> (u64, Option<AgencyId>) {
+    match collection {
+        AGENCIES => Agents::migrate(after, MIGRATION_BATCH_SIZE),
+        _ => {
+            log::warn!("Unknown collection {collection} in the pending migrations");
+            (0, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use candid::Principal;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::with_mock_agency;
+
+    #[test]
+    fn test_should_set_target_versions_on_init() {
+        Migrations::init();
+        Migrations::start();
+
+        let status = Migrations::status();
+        assert!(status.is_completed());
+        assert_eq!(
+            status.collections,
+            vec![CollectionSchema {
+                collection: AGENCIES.to_string(),
+                version: 1,
+                target_version: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_should_migrate_agencies_in_batches() {
+        for id in 0..150u64 {
+            let wallet = Principal::from_slice(&id.to_be_bytes());
+            Agents::insert_agency(with_mock_agency(|agency| agency.owner = wallet)).unwrap();
+        }
+
+        Migrations::start();
+        let status = Migrations::status();
+        assert_eq!(status.pending.len(), 1);
+        assert_eq!(status.pending[0].from_version, 0);
+        assert_eq!(status.pending[0].to_version, 1);
+        assert_eq!(status.pending[0].total, 150);
+
+        Migrations::tick();
+        let progress = Migrations::status().pending[0].clone();
+        assert_eq!(progress.migrated, 100);
+        assert!(progress.cursor.is_some());
+
+        Migrations::tick();
+        assert!(Migrations::status().is_completed());
+        assert_eq!(Agents::get_agencies().len(), 150);
+    }
+}
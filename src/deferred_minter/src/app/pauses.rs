@@ -0,0 +1,161 @@
+//! # Pauses
+//!
+//! Circuit breaker of the minter: the custodians can pause a class of operations, which then fails
+//! with [`DeferredMinterError::Paused`] until it's resumed.
+
+use std::cell::RefCell;
+
+use candid::Principal;
+use did::deferred::{
+    DeferredMinterError, DeferredMinterResult, PausableOperation, PauseState, ProposalError,
+    MAX_PAUSE_REASON_LEN,
+};
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl};
+
+use crate::app::memory::{MEMORY_MANAGER, PAUSED_OPERATIONS_MEMORY_ID};
+use crate::utils::time;
+
+thread_local! {
+    /// Paused operations
+    static PAUSED_OPERATIONS: RefCell<BTreeMap<PausableOperation, PauseState, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(PAUSED_OPERATIONS_MEMORY_ID))));
+}
+
+pub struct Pauses;
+
+impl Pauses {
+    /// Pause the operation.
+    ///
+    /// If the operation is already paused, the reason and the principal who paused it first are kept
+    pub fn pause(operation: PausableOperation, paused_by: Principal, reason: String) -> PauseState {
+        if let Some(state) = Self::get(operation) {
+            return state;
+        }
+
+        let state = PauseState {
+            operation,
+            paused_by,
+            paused_at: time(),
+            reason,
+        };
+        PAUSED_OPERATIONS.with_borrow_mut(|paused| paused.insert(operation, state.clone()));
+
+        state
+    }
+
+    /// Check the reason of a pause, which must be non-empty and at most [`MAX_PAUSE_REASON_LEN`]
+    /// characters long
+    pub fn check_reason(reason: &str) -> DeferredMinterResult<()> {
+        if reason.trim().is_empty() {
+            return Err(
+                ProposalError::InvalidProposal("the pause reason is empty".to_string()).into(),
+            );
+        }
+        if reason.chars().count() > MAX_PAUSE_REASON_LEN {
+            return Err(ProposalError::InvalidProposal(format!(
+                "the pause reason is longer than {MAX_PAUSE_REASON_LEN} characters"
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Resume the operation, returning the state it had been paused with
+    pub fn resume(operation: PausableOperation) -> Option<PauseState> {
+        PAUSED_OPERATIONS.with_borrow_mut(|paused| paused.remove(&operation))
+    }
+
+    /// Get the pause state of the operation, if paused
+    pub fn get(operation: PausableOperation) -> Option<PauseState> {
+        PAUSED_OPERATIONS.with_borrow(|paused| paused.get(&operation))
+    }
+
+    /// Get the paused operations
+    pub fn get_paused() -> Vec<PauseState> {
+        PAUSED_OPERATIONS.with_borrow(|paused| paused.iter().map(|(_, state)| state).collect())
+    }
+
+    /// Returns [`DeferredMinterError::Paused`] with the first of the operations which is paused
+    pub fn require_active(operations: &[PausableOperation]) -> DeferredMinterResult<()> {
+        match operations
+            .iter()
+            .find(|operation| Self::get(**operation).is_some())
+        {
+            Some(operation) => Err(DeferredMinterError::Paused(*operation)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob};
+
+    #[test]
+    fn test_should_pause_and_resume_operation() {
+        let state = Pauses::pause(
+            PausableOperation::ContractCreation,
+            alice(),
+            "bug".to_string(),
+        );
+        assert_eq!(state.operation, PausableOperation::ContractCreation);
+        assert_eq!(state.paused_by, alice());
+        assert_eq!(state.reason, "bug");
+        assert_eq!(Pauses::get_paused(), vec![state.clone()]);
+
+        // pausing again keeps the first state
+        assert_eq!(
+            Pauses::pause(
+                PausableOperation::ContractCreation,
+                bob(),
+                "again".to_string()
+            ),
+            state
+        );
+
+        assert_eq!(
+            Pauses::resume(PausableOperation::ContractCreation),
+            Some(state)
+        );
+        assert!(Pauses::get_paused().is_empty());
+        assert_eq!(Pauses::resume(PausableOperation::ContractCreation), None);
+    }
+
+    #[test]
+    fn test_should_check_pause_reason() {
+        assert!(Pauses::check_reason("compromised contract").is_ok());
+        assert_eq!(
+            Pauses::check_reason(" "),
+            Err(ProposalError::InvalidProposal("the pause reason is empty".to_string()).into())
+        );
+        assert!(Pauses::check_reason(&"a".repeat(MAX_PAUSE_REASON_LEN)).is_ok());
+        assert!(Pauses::check_reason(&"a".repeat(MAX_PAUSE_REASON_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_should_require_active_operations() {
+        assert!(Pauses::require_active(&PausableOperation::all()).is_ok());
+
+        Pauses::pause(
+            PausableOperation::EvmTransactions,
+            alice(),
+            "compromised".to_string(),
+        );
+        assert_eq!(
+            Pauses::require_active(&[
+                PausableOperation::ContractCreation,
+                PausableOperation::EvmTransactions
+            ]),
+            Err(DeferredMinterError::Paused(
+                PausableOperation::EvmTransactions
+            ))
+        );
+        assert!(Pauses::require_active(&[PausableOperation::RealEstateWrites]).is_ok());
+    }
+}
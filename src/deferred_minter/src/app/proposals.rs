@@ -0,0 +1,424 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use did::deferred::{
+    ConfigurationError, DeferredMinterError, DeferredMinterResult, Proposal, ProposalAction,
+    ProposalConfig, ProposalError, ProposalStatus, Role,
+};
+use ic_log::did::Pagination;
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
+
+use super::configuration::Configuration;
+use super::pauses::Pauses;
+use super::reward::Reward;
+use super::roles::RolesManager;
+use crate::app::memory::{
+    MEMORY_MANAGER, NEXT_PROPOSAL_ID_MEMORY_ID, PROPOSALS_MEMORY_ID, PROPOSAL_CONFIG_MEMORY_ID,
+};
+use crate::utils::time;
+
+thread_local! {
+    /// Custodian proposals
+    static PROPOSALS: RefCell<BTreeMap<u64, Proposal, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(PROPOSALS_MEMORY_ID))));
+
+    /// Next proposal id
+    static NEXT_PROPOSAL_ID: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(NEXT_PROPOSAL_ID_MEMORY_ID)), 1).unwrap()
+    );
+
+    /// Approval threshold and timeout of the proposals
+    static PROPOSAL_CONFIG: RefCell<StableCell<ProposalConfig, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(PROPOSAL_CONFIG_MEMORY_ID)), ProposalConfig::default()).unwrap()
+    );
+}
+
+pub struct Proposals;
+
+impl Proposals {
+    /// Get the proposals configuration
+    pub fn config() -> ProposalConfig {
+        PROPOSAL_CONFIG.with_borrow(|cell| *cell.get())
+    }
+
+    /// Set the proposals configuration.
+    ///
+    /// The timeout is only applied to the proposals made after the change
+    pub fn set_config(config: ProposalConfig) -> DeferredMinterResult<()> {
+        PROPOSAL_CONFIG
+            .with_borrow_mut(|cell| cell.set(config))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        Ok(())
+    }
+
+    /// Check whether the action can be executed with the current state of the canister
+    pub fn validate_action(action: &ProposalAction) -> DeferredMinterResult<()> {
+        match action {
+            ProposalAction::SetCustodians(custodians) => {
+                if custodians.is_empty() {
+                    return Err(ConfigurationError::CustodialsCantBeEmpty.into());
+                }
+                if custodians.contains(&Principal::anonymous()) {
+                    return Err(ConfigurationError::AnonymousCustodial.into());
+                }
+                let threshold = Self::config().threshold;
+                let mut custodians = custodians.clone();
+                custodians.sort();
+                custodians.dedup();
+                if custodians.len() < threshold as usize {
+                    return Err(ProposalError::InvalidThreshold(threshold).into());
+                }
+            }
+            ProposalAction::SetRole { role, .. } => {
+                if let Role::Custom(name) = role {
+                    if !RolesManager::is_defined(role) {
+                        return Err(ConfigurationError::UnknownRole(name.clone()).into());
+                    }
+                }
+            }
+            ProposalAction::RemoveRole { principal, role } => {
+                Self::check_role_removal(*principal, role)?;
+            }
+            ProposalAction::DefineRole { name, .. } => {
+                RolesManager::check_role_name(name)?;
+            }
+            ProposalAction::DeleteRole(name) => {
+                if !RolesManager::is_defined(&Role::Custom(name.clone())) {
+                    return Err(ConfigurationError::UnknownRole(name.clone()).into());
+                }
+            }
+            ProposalAction::SetAllowedCurrencies(currencies) => {
+                if let Some(currency) = currencies
+                    .iter()
+                    .find(|currency| !Configuration::is_valid_currency(currency))
+                {
+                    return Err(ProposalError::InvalidProposal(format!(
+                        "invalid currency {currency}"
+                    ))
+                    .into());
+                }
+            }
+            ProposalAction::SetProposalConfig(config) => {
+                if config.threshold == 0
+                    || config.threshold as usize > RolesManager::get_custodians().len()
+                {
+                    return Err(ProposalError::InvalidThreshold(config.threshold).into());
+                }
+                if config.timeout == 0 {
+                    return Err(ProposalError::InvalidProposal(
+                        "the proposal timeout cannot be zero".to_string(),
+                    )
+                    .into());
+                }
+            }
+            ProposalAction::SetSnsGovernance(governance) => {
+                if *governance == Some(Principal::anonymous()) {
+                    return Err(ProposalError::InvalidProposal(
+                        "the SNS governance cannot be anonymous".to_string(),
+                    )
+                    .into());
+                }
+            }
+            ProposalAction::UpdateConfiguration(update) => {
+                if update.is_empty() {
+                    return Err(ProposalError::InvalidProposal(
+                        "the configuration update is empty".to_string(),
+                    )
+                    .into());
+                }
+                Configuration::validate_update(update)?;
+            }
+            ProposalAction::Resume(operation) => {
+                if Pauses::get(*operation).is_none() {
+                    return Err(ProposalError::InvalidProposal(format!(
+                        "operation {operation:?} is not paused"
+                    ))
+                    .into());
+                }
+            }
+            ProposalAction::SetRewardParameters(parameters) => {
+                Reward::validate_parameters(parameters)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the role can be removed from the principal without leaving the canister
+    /// without custodians or with less custodians than the proposal threshold
+    pub fn check_role_removal(principal: Principal, role: &Role) -> DeferredMinterResult<()> {
+        if role != &Role::Custodian || !RolesManager::is_custodian(principal) {
+            return Ok(());
+        }
+
+        let custodians = RolesManager::get_custodians().len();
+        if custodians == 1 {
+            return Err(ConfigurationError::CustodialsCantBeEmpty.into());
+        }
+        let threshold = Self::config().threshold;
+        if custodians <= threshold as usize {
+            return Err(ProposalError::InvalidThreshold(threshold).into());
+        }
+
+        Ok(())
+    }
+
+    /// Make a new proposal. The proposer is the first custodian approving it
+    pub fn propose(
+        proposed_by: Principal,
+        action: ProposalAction,
+    ) -> DeferredMinterResult<Proposal> {
+        let id = NEXT_PROPOSAL_ID.with_borrow(|cell| *cell.get());
+        NEXT_PROPOSAL_ID
+            .with_borrow_mut(|cell| cell.set(id + 1))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        let proposed_at = time();
+        let proposal = Proposal {
+            id,
+            action,
+            approvals: vec![proposed_by],
+            expires_at: proposed_at.saturating_add(Self::config().timeout),
+            closed_at: None,
+            rejected_by: None,
+            failure_reason: None,
+            proposed_at,
+            proposed_by,
+            status: ProposalStatus::Pending,
+        };
+        PROPOSALS.with_borrow_mut(|proposals| {
+            proposals.insert(id, proposal.clone());
+        });
+
+        Ok(proposal)
+    }
+
+    /// Get proposal by id
+    pub fn get_proposal(id: u64) -> Option<Proposal> {
+        PROPOSALS.with_borrow(|proposals| proposals.get(&id).map(Self::with_expiration))
+    }
+
+    /// Get proposals, optionally filtered by status, from the oldest one
+    pub fn get_proposals(status: Option<ProposalStatus>, pagination: Pagination) -> Vec<Proposal> {
+        PROPOSALS.with_borrow(|proposals| {
+            proposals
+                .iter()
+                .map(|(_, proposal)| Self::with_expiration(proposal))
+                .filter(|proposal| status.map_or(true, |status| proposal.status == status))
+                .skip(pagination.offset)
+                .take(pagination.count)
+                .collect()
+        })
+    }
+
+    /// Add the approval of a custodian to the pending proposal
+    pub fn approve(id: u64, approved_by: Principal) -> DeferredMinterResult<Proposal> {
+        Self::with_pending_proposal_mut(id, |proposal| {
+            if proposal.approvals.contains(&approved_by) {
+                return Err(ProposalError::AlreadyApproved(id).into());
+            }
+            proposal.approvals.push(approved_by);
+
+            Ok(proposal.clone())
+        })
+    }
+
+    /// Reject the pending proposal
+    pub fn reject(id: u64, rejected_by: Principal) -> DeferredMinterResult<Proposal> {
+        Self::with_pending_proposal_mut(id, |proposal| {
+            proposal.status = ProposalStatus::Rejected;
+            proposal.rejected_by = Some(rejected_by);
+            proposal.closed_at = Some(time());
+
+            Ok(proposal.clone())
+        })
+    }
+
+    /// Close the pending proposal after its execution
+    pub fn close(
+        id: u64,
+        status: ProposalStatus,
+        failure_reason: Option<String>,
+    ) -> DeferredMinterResult<Proposal> {
+        Self::with_pending_proposal_mut(id, |proposal| {
+            proposal.status = status;
+            proposal.failure_reason = failure_reason;
+            proposal.closed_at = Some(time());
+
+            Ok(proposal.clone())
+        })
+    }
+
+    /// Returns the proposal with the [`ProposalStatus::Expired`] status if it has expired
+    fn with_expiration(mut proposal: Proposal) -> Proposal {
+        if proposal.is_expired(time()) {
+            proposal.status = ProposalStatus::Expired;
+        }
+
+        proposal
+    }
+
+    fn with_pending_proposal_mut<F, T>(id: u64, f: F) -> DeferredMinterResult<T>
+    where
+        F: FnOnce(&mut Proposal) -> DeferredMinterResult<T>,
+    {
+        PROPOSALS.with_borrow_mut(|proposals| {
+            let mut proposal = proposals
+                .get(&id)
+                .ok_or(ProposalError::ProposalNotFound(id))?;
+            if proposal.is_expired(time()) {
+                proposal.status = ProposalStatus::Expired;
+                proposal.closed_at = Some(proposal.expires_at);
+                proposals.insert(id, proposal);
+                return Err(ProposalError::ProposalExpired(id).into());
+            }
+            if proposal.status != ProposalStatus::Pending {
+                return Err(ProposalError::ProposalNotPending(id).into());
+            }
+            let result = f(&mut proposal)?;
+            proposals.insert(id, proposal);
+
+            Ok(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob, charlie};
+
+    fn pagination() -> Pagination {
+        Pagination {
+            offset: 0,
+            count: 10,
+        }
+    }
+
+    #[test]
+    fn test_should_propose() {
+        let proposal =
+            Proposals::propose(alice(), ProposalAction::SetCustodians(vec![bob()])).unwrap();
+        assert_eq!(proposal.id, 1);
+        assert_eq!(proposal.approvals, vec![alice()]);
+        assert_eq!(proposal.status, ProposalStatus::Pending);
+        assert_eq!(
+            proposal.expires_at,
+            proposal.proposed_at + Proposals::config().timeout
+        );
+
+        assert_eq!(Proposals::get_proposal(1), Some(proposal));
+        assert!(Proposals::get_proposal(2).is_none());
+        assert_eq!(
+            Proposals::get_proposals(Some(ProposalStatus::Pending), pagination()).len(),
+            1
+        );
+        assert!(Proposals::get_proposals(Some(ProposalStatus::Executed), pagination()).is_empty());
+    }
+
+    #[test]
+    fn test_should_validate_action() {
+        RolesManager::set_custodians(vec![alice()]).unwrap();
+
+        assert!(Proposals::validate_action(&ProposalAction::SetCustodians(vec![bob()])).is_ok());
+        assert_eq!(
+            Proposals::validate_action(&ProposalAction::SetCustodians(vec![])),
+            Err(ConfigurationError::CustodialsCantBeEmpty.into())
+        );
+        assert_eq!(
+            Proposals::validate_action(&ProposalAction::SetRole {
+                principal: bob(),
+                role: Role::Custom("auditor".to_string())
+            }),
+            Err(ConfigurationError::UnknownRole("auditor".to_string()).into())
+        );
+        assert_eq!(
+            Proposals::validate_action(&ProposalAction::SetProposalConfig(ProposalConfig {
+                threshold: 2,
+                timeout: 1,
+            })),
+            Err(ProposalError::InvalidThreshold(2).into())
+        );
+        assert!(Proposals::validate_action(&ProposalAction::SetSnsGovernance(Some(bob()))).is_ok());
+        assert!(
+            Proposals::validate_action(&ProposalAction::SetSnsGovernance(Some(
+                Principal::anonymous()
+            )))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_should_approve_proposal() {
+        let id = Proposals::propose(alice(), ProposalAction::SetCustodians(vec![bob()]))
+            .unwrap()
+            .id;
+
+        assert_eq!(
+            Proposals::approve(id, alice()),
+            Err(ProposalError::AlreadyApproved(id).into())
+        );
+        let proposal = Proposals::approve(id, bob()).unwrap();
+        assert_eq!(proposal.approvals, vec![alice(), bob()]);
+
+        let proposal = Proposals::close(id, ProposalStatus::Executed, None).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert!(proposal.closed_at.is_some());
+
+        // can't be changed anymore
+        assert_eq!(
+            Proposals::approve(id, charlie()),
+            Err(ProposalError::ProposalNotPending(id).into())
+        );
+        assert_eq!(
+            Proposals::approve(2, charlie()),
+            Err(ProposalError::ProposalNotFound(2).into())
+        );
+    }
+
+    #[test]
+    fn test_should_reject_proposal() {
+        let id = Proposals::propose(alice(), ProposalAction::SetCustodians(vec![bob()]))
+            .unwrap()
+            .id;
+
+        let proposal = Proposals::reject(id, bob()).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Rejected);
+        assert_eq!(proposal.rejected_by, Some(bob()));
+        assert_eq!(
+            Proposals::reject(id, bob()),
+            Err(ProposalError::ProposalNotPending(id).into())
+        );
+    }
+
+    #[test]
+    fn test_should_expire_proposal() {
+        Proposals::set_config(ProposalConfig {
+            threshold: 2,
+            timeout: 0,
+        })
+        .unwrap();
+        let id = Proposals::propose(alice(), ProposalAction::SetCustodians(vec![bob()]))
+            .unwrap()
+            .id;
+        crate::utils::advance_time(1);
+
+        assert_eq!(
+            Proposals::get_proposal(id).unwrap().status,
+            ProposalStatus::Expired
+        );
+        assert_eq!(
+            Proposals::approve(id, bob()),
+            Err(ProposalError::ProposalExpired(id).into())
+        );
+        assert_eq!(
+            Proposals::get_proposals(Some(ProposalStatus::Expired), pagination()).len(),
+            1
+        );
+    }
+}
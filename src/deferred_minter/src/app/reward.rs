@@ -4,6 +4,9 @@
 
 use std::cell::RefCell;
 
+use did::deferred::{
+    ConfigurationError, DeferredMinterError, DeferredMinterResult, RewardParameters,
+};
 use ic_stable_structures::memory_manager::VirtualMemory;
 use ic_stable_structures::{DefaultMemoryImpl, StableCell};
 
@@ -15,8 +18,10 @@ use crate::utils::time;
 
 /// Initial "reward multiplier coefficient" value
 const INITIAL_RMC: f64 = 0.0000042;
-/// Initial "avidity" value
+/// Initial "avidity" value, which is also the maximum one
 const INITIAL_AVIDITY: f64 = 1.0;
+/// Minimum "avidity" value
+const MIN_AVIDITY: f64 = 0.1;
 /// Minimum reward
 const MIN_REWARD: u128 = 1_000;
 /// Base token price
@@ -114,6 +119,88 @@ impl Reward {
         Some(reward)
     }
 
+    /// Current "reward multiplier coefficient"
+    pub fn rmc() -> f64 {
+        RMC.with_borrow(|rmc| *rmc.get())
+    }
+
+    /// Current avidity
+    pub fn avidity() -> f64 {
+        AVIDITY.with_borrow(|avidity| *avidity.get())
+    }
+
+    /// Contracts created in the current month
+    pub fn cpm() -> u64 {
+        CPM.with_borrow(|cpm| *cpm.get())
+    }
+
+    /// Contracts created in the previous month
+    pub fn last_cpm() -> u64 {
+        LAST_CPM.with_borrow(|last_cpm| *last_cpm.get())
+    }
+
+    /// Time of the next RMC halving (nanoseconds)
+    pub fn next_halving() -> u64 {
+        NEXT_HALVING.with_borrow(|halving| *halving.get())
+    }
+
+    /// Minimum reward of a contract
+    pub fn min_reward() -> u128 {
+        MIN_REWARD
+    }
+
+    /// Get the parameters of the reward formula
+    pub fn parameters() -> RewardParameters {
+        RewardParameters {
+            rmc: Self::rmc(),
+            avidity: Self::avidity(),
+            next_halving: Self::next_halving(),
+        }
+    }
+
+    /// Check the reward parameters.
+    ///
+    /// The RMC can't be raised above its initial value, the avidity must stay in the range
+    /// it is adjusted in and the next halving must be in the future
+    pub fn validate_parameters(parameters: &RewardParameters) -> DeferredMinterResult<()> {
+        if !(parameters.rmc > 0.0 && parameters.rmc <= INITIAL_RMC) {
+            return Err(ConfigurationError::InvalidRewardParameters(format!(
+                "rmc must be greater than 0 and at most {INITIAL_RMC}"
+            ))
+            .into());
+        }
+        if !(MIN_AVIDITY..=INITIAL_AVIDITY).contains(&parameters.avidity) {
+            return Err(ConfigurationError::InvalidRewardParameters(format!(
+                "avidity must be between {MIN_AVIDITY} and {INITIAL_AVIDITY}"
+            ))
+            .into());
+        }
+        if parameters.next_halving <= time() {
+            return Err(ConfigurationError::InvalidRewardParameters(
+                "the next halving must be in the future".to_string(),
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Set the parameters of the reward formula
+    pub fn set_parameters(parameters: RewardParameters) -> DeferredMinterResult<()> {
+        Self::validate_parameters(&parameters)?;
+
+        RMC.with_borrow_mut(|rmc| rmc.set(parameters.rmc))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+        AVIDITY
+            .with_borrow_mut(|avidity| avidity.set(parameters.avidity))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+        NEXT_HALVING
+            .with_borrow_mut(|halving| halving.set(parameters.next_halving))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        Ok(())
+    }
+
     /// Get the next RMC halving time. 4 years from now.
     #[inline]
     fn next_rmc_halving() -> u64 {
@@ -163,7 +250,7 @@ impl Reward {
             avidity + 0.1
         };
         // calculate final avidity
-        let new_avidity = 0.1_f64.max(new_avidity.min(1.0));
+        let new_avidity = MIN_AVIDITY.max(new_avidity.min(INITIAL_AVIDITY));
 
         // set new avidity
         AVIDITY.with_borrow_mut(|avidity| {
@@ -326,4 +413,51 @@ mod test {
         Reward::adjust_avidity();
         assert_eq!(AVIDITY.with_borrow(|avidity| *avidity.get()), 0.1);
     }
+
+    #[test]
+    fn test_should_set_reward_parameters() {
+        let parameters = RewardParameters {
+            rmc: INITIAL_RMC / 2.0,
+            avidity: 0.5,
+            next_halving: time() + 1_000,
+        };
+        assert!(Reward::set_parameters(parameters).is_ok());
+        assert_eq!(Reward::parameters(), parameters);
+    }
+
+    #[test]
+    fn test_should_not_set_invalid_reward_parameters() {
+        let valid = Reward::parameters();
+        for parameters in [
+            RewardParameters { rmc: 0.0, ..valid },
+            RewardParameters {
+                rmc: INITIAL_RMC * 2.0,
+                ..valid
+            },
+            RewardParameters {
+                rmc: f64::NAN,
+                ..valid
+            },
+            RewardParameters {
+                avidity: 0.05,
+                ..valid
+            },
+            RewardParameters {
+                avidity: 1.5,
+                ..valid
+            },
+            RewardParameters {
+                next_halving: time(),
+                ..valid
+            },
+        ] {
+            assert!(matches!(
+                Reward::set_parameters(parameters),
+                Err(DeferredMinterError::Configuration(
+                    ConfigurationError::InvalidRewardParameters(_)
+                ))
+            ));
+        }
+        assert_eq!(Reward::parameters(), valid);
+    }
 }
@@ -0,0 +1,628 @@
+//! # Reward pool ledger
+//!
+//! Keeps a local view of the reward pool, so that the minter doesn't need to query the
+//! `availableReward` of the reward pool contract for each contract it creates.
+//!
+//! The ledger tracks the rewards reserved by the minter for each contract and periodically
+//! reconciles them with the `PoolReserved` and `RewardSent` events emitted on chain,
+//! raising an alert whenever the two views drift apart.
+
+use std::cell::RefCell;
+
+use did::deferred::{
+    DeferredMinterError, DeferredMinterResult, RewardPoolAlert, RewardPoolAlertKind,
+    RewardPoolStatus, RewardReservation, RewardReservationStatus,
+};
+use did::{StorableNat, ID};
+use ic_stable_structures::memory_manager::VirtualMemory;
+use ic_stable_structures::{BTreeMap, DefaultMemoryImpl, StableCell};
+
+use super::ethereum::{EvmRpcClient, RewardPool, RewardPoolEvent};
+use crate::app::memory::{
+    MEMORY_MANAGER, REWARD_POOL_ALERTS_MEMORY_ID, REWARD_POOL_AVAILABLE_MEMORY_ID,
+    REWARD_POOL_AVAILABLE_UPDATED_AT_MEMORY_ID, REWARD_POOL_LAST_SYNCED_BLOCK_MEMORY_ID,
+    REWARD_POOL_RESERVATIONS_MEMORY_ID, REWARD_POOL_RESERVED_ON_CHAIN_MEMORY_ID,
+    REWARD_POOL_REWARDS_SENT_MEMORY_ID,
+};
+use crate::utils::time;
+
+/// Maximum age of the cached available reward before it's refreshed from the chain (30 minutes)
+const AVAILABLE_REWARD_MAX_AGE: u64 = 30 * 60 * 1_000_000_000;
+/// Time after which a reservation not seen on chain is considered missing (1 hour)
+const RESERVATION_TIMEOUT: u64 = 60 * 60 * 1_000_000_000;
+/// Interval between two reconciliations with the chain (10 minutes)
+pub const SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+/// Maximum amount of blocks scanned for events by a single reconciliation
+const MAX_SYNC_BLOCK_RANGE: u64 = 5_000;
+
+thread_local! {
+    /// Last available reward read from the reward pool
+    static AVAILABLE_REWARD: RefCell<StableCell<u128, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_AVAILABLE_MEMORY_ID)), 0).unwrap()
+    );
+
+    /// Time of the last available reward refresh
+    static AVAILABLE_REWARD_UPDATED_AT: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_AVAILABLE_UPDATED_AT_MEMORY_ID)), 0).unwrap()
+    );
+
+    /// Rewards reserved by the minter for each contract
+    static RESERVATIONS: RefCell<BTreeMap<StorableNat, RewardReservation, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_RESERVATIONS_MEMORY_ID))));
+
+    /// Rewards reserved on chain according to the `PoolReserved` events
+    static RESERVED_ON_CHAIN: RefCell<StableCell<u128, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_RESERVED_ON_CHAIN_MEMORY_ID)), 0).unwrap()
+    );
+
+    /// Rewards sent according to the `RewardSent` events
+    static REWARDS_SENT: RefCell<StableCell<u128, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_REWARDS_SENT_MEMORY_ID)), 0).unwrap()
+    );
+
+    /// Last block scanned for reward pool events
+    static LAST_SYNCED_BLOCK: RefCell<StableCell<u64, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_LAST_SYNCED_BLOCK_MEMORY_ID)), 0).unwrap()
+    );
+
+    /// Alerts raised during the reconciliation
+    static ALERTS: RefCell<BTreeMap<u64, RewardPoolAlert, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(BTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(REWARD_POOL_ALERTS_MEMORY_ID))));
+}
+
+pub struct RewardPoolLedger;
+
+impl RewardPoolLedger {
+    /// Get the reward available for new contracts.
+    ///
+    /// The value read from the chain is cached and refreshed only when older than
+    /// [`AVAILABLE_REWARD_MAX_AGE`]; rewards reserved by the minter after it has been read are
+    /// subtracted from it.
+    pub async fn available_rewards(
+        reward_pool: &RewardPool,
+        client: &EvmRpcClient,
+    ) -> DeferredMinterResult<u128> {
+        let updated_at = AVAILABLE_REWARD_UPDATED_AT.with_borrow(|cell| *cell.get());
+        if updated_at == 0 || time().saturating_sub(updated_at) > AVAILABLE_REWARD_MAX_AGE {
+            Self::refresh_available_reward(reward_pool, client).await?;
+        }
+
+        Ok(Self::local_available_reward())
+    }
+
+    /// Record the reward reserved for a contract
+    pub fn reserve(contract_id: ID, reward: u128, tokens: u64) -> DeferredMinterResult<()> {
+        let reservation = RewardReservation {
+            contract_id: contract_id.clone(),
+            reward,
+            tokens,
+            reserved_at: time(),
+            status: RewardReservationStatus::Pending,
+        };
+        log::debug!("reserving reward for contract {contract_id}: {reservation:?}");
+
+        RESERVATIONS.with_borrow_mut(|reservations| {
+            reservations.insert(contract_id.into(), reservation);
+        });
+
+        Ok(())
+    }
+
+    /// Get the reservation for the provided contract
+    pub fn get_reservation(contract_id: &ID) -> Option<RewardReservation> {
+        RESERVATIONS
+            .with_borrow(|reservations| reservations.get(&StorableNat::from(contract_id.clone())))
+    }
+
+    /// Reconcile the ledger with the events emitted by the reward pool.
+    ///
+    /// At most [`MAX_SYNC_BLOCK_RANGE`] blocks after the last synced block are scanned; the
+    /// following ones are scanned by the next reconciliations
+    pub async fn sync(reward_pool: &RewardPool, client: &EvmRpcClient) -> DeferredMinterResult<()> {
+        let last_synced_block = LAST_SYNCED_BLOCK.with_borrow(|cell| *cell.get());
+        let latest_block = client.eth_block_number().await?;
+
+        if latest_block > last_synced_block {
+            let from_block = last_synced_block + 1;
+            let to_block = latest_block.min(last_synced_block + MAX_SYNC_BLOCK_RANGE);
+            log::debug!("syncing reward pool ledger from block {from_block} to {to_block}");
+
+            let events = reward_pool.events(client, from_block, to_block).await?;
+            Self::apply_events(events)?;
+            Self::set_last_synced_block(to_block)?;
+        }
+        Self::expire_reservations();
+
+        Self::refresh_available_reward(reward_pool, client).await?;
+
+        Ok(())
+    }
+
+    /// Restart the reconciliation from the block where the reward pool has been deployed.
+    ///
+    /// The totals of the events are reset, since the events are scanned again, and the confirmed
+    /// reservations are marked as missing until their `PoolReserved` event is scanned again
+    pub fn reset(deploy_block: u64) -> DeferredMinterResult<()> {
+        RESERVED_ON_CHAIN
+            .with_borrow_mut(|cell| cell.set(0))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+        REWARDS_SENT
+            .with_borrow_mut(|cell| cell.set(0))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        let confirmed = RESERVATIONS.with_borrow(|reservations| {
+            reservations
+                .iter()
+                .filter(|(_, reservation)| {
+                    matches!(
+                        reservation.status,
+                        RewardReservationStatus::Confirmed { .. }
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        RESERVATIONS.with_borrow_mut(|reservations| {
+            for (key, mut reservation) in confirmed {
+                reservation.status = RewardReservationStatus::Missing;
+                reservations.insert(key, reservation);
+            }
+        });
+
+        Self::set_last_synced_block(deploy_block.saturating_sub(1))
+    }
+
+    /// Start the reconciliation from the block where the reward pool has been deployed, if no
+    /// block has been synced yet, as for the canisters installed before the ledger was introduced
+    pub fn init_last_synced_block(deploy_block: u64) -> DeferredMinterResult<()> {
+        if LAST_SYNCED_BLOCK.with_borrow(|cell| *cell.get()) != 0 {
+            return Ok(());
+        }
+        log::info!("starting reward pool sync from deploy block {deploy_block}");
+
+        Self::set_last_synced_block(deploy_block.saturating_sub(1))
+    }
+
+    /// Get the status of the ledger
+    pub fn status() -> RewardPoolStatus {
+        let (pending_reserved, reserved_by_minter) = RESERVATIONS.with_borrow(|reservations| {
+            reservations
+                .iter()
+                .fold((0u128, 0u128), |(pending, total), (_, reservation)| {
+                    let amount = reservation.total();
+                    match reservation.status {
+                        RewardReservationStatus::Pending => (pending + amount, total + amount),
+                        _ => (pending, total + amount),
+                    }
+                })
+        });
+
+        RewardPoolStatus {
+            available_reward: AVAILABLE_REWARD.with_borrow(|cell| *cell.get()),
+            available_reward_updated_at: AVAILABLE_REWARD_UPDATED_AT
+                .with_borrow(|cell| *cell.get()),
+            pending_reserved,
+            reserved_by_minter,
+            reserved_on_chain: RESERVED_ON_CHAIN.with_borrow(|cell| *cell.get()),
+            rewards_sent: REWARDS_SENT.with_borrow(|cell| *cell.get()),
+            last_synced_block: LAST_SYNCED_BLOCK.with_borrow(|cell| *cell.get()),
+            alerts: ALERTS.with_borrow(|alerts| alerts.len()),
+        }
+    }
+
+    /// Get the alerts raised during the reconciliation
+    pub fn alerts() -> Vec<RewardPoolAlert> {
+        ALERTS.with_borrow(|alerts| alerts.iter().map(|(_, alert)| alert).collect())
+    }
+
+    /// Available reward read from the chain minus the rewards reserved after it has been read.
+    ///
+    /// The reservations made before are already counted by the value read from the chain
+    fn local_available_reward() -> u128 {
+        let updated_at = AVAILABLE_REWARD_UPDATED_AT.with_borrow(|cell| *cell.get());
+        let pending = RESERVATIONS.with_borrow(|reservations| {
+            reservations
+                .iter()
+                .filter(|(_, reservation)| {
+                    reservation.status == RewardReservationStatus::Pending
+                        && reservation.reserved_at > updated_at
+                })
+                .map(|(_, reservation)| reservation.total())
+                .sum::<u128>()
+        });
+
+        AVAILABLE_REWARD
+            .with_borrow(|cell| *cell.get())
+            .saturating_sub(pending)
+    }
+
+    /// Read the available reward from the chain and cache it
+    async fn refresh_available_reward(
+        reward_pool: &RewardPool,
+        client: &EvmRpcClient,
+    ) -> DeferredMinterResult<()> {
+        let available = reward_pool.available_rewards(client).await?;
+        Self::set_available_reward(available)
+    }
+
+    /// Cache the available reward read from the chain.
+    ///
+    /// Since the available reward can only decrease, an alert is raised if it has increased
+    fn set_available_reward(available: u128) -> DeferredMinterResult<()> {
+        let previous_updated_at = AVAILABLE_REWARD_UPDATED_AT.with_borrow(|cell| *cell.get());
+        let previous = AVAILABLE_REWARD.with_borrow(|cell| *cell.get());
+        if previous_updated_at != 0 && available > previous {
+            Self::raise_alert(RewardPoolAlertKind::AvailableRewardIncreased {
+                previous,
+                current: available,
+            });
+        }
+
+        AVAILABLE_REWARD
+            .with_borrow_mut(|cell| cell.set(available))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+        AVAILABLE_REWARD_UPDATED_AT
+            .with_borrow_mut(|cell| cell.set(time()))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        log::debug!("reward pool available reward cached: {available}");
+
+        Ok(())
+    }
+
+    fn set_last_synced_block(block: u64) -> DeferredMinterResult<()> {
+        LAST_SYNCED_BLOCK
+            .with_borrow_mut(|cell| cell.set(block))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        Ok(())
+    }
+
+    /// Apply the reward pool events to the ledger
+    fn apply_events(events: Vec<RewardPoolEvent>) -> DeferredMinterResult<()> {
+        let mut reserved_on_chain = RESERVED_ON_CHAIN.with_borrow(|cell| *cell.get());
+        let mut rewards_sent = REWARDS_SENT.with_borrow(|cell| *cell.get());
+
+        for event in events {
+            match event {
+                RewardPoolEvent::PoolReserved {
+                    reward,
+                    tokens,
+                    block,
+                } => {
+                    reserved_on_chain += reward * tokens as u128;
+                    Self::confirm_reservation(reward, tokens, block);
+                }
+                RewardPoolEvent::RewardSent { amount, .. } => {
+                    rewards_sent += amount;
+                }
+            }
+        }
+
+        RESERVED_ON_CHAIN
+            .with_borrow_mut(|cell| cell.set(reserved_on_chain))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+        REWARDS_SENT
+            .with_borrow_mut(|cell| cell.set(rewards_sent))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        Ok(())
+    }
+
+    /// Confirm the oldest pending (or missing) reservation matching the `PoolReserved` event.
+    ///
+    /// If no reservation matches, an alert is raised
+    fn confirm_reservation(reward: u128, tokens: u64, block: Option<u64>) {
+        let matching = RESERVATIONS.with_borrow(|reservations| {
+            reservations
+                .iter()
+                .filter(|(_, reservation)| {
+                    reservation.reward == reward
+                        && reservation.tokens == tokens
+                        && !matches!(
+                            reservation.status,
+                            RewardReservationStatus::Confirmed { .. }
+                        )
+                })
+                .min_by_key(|(_, reservation)| reservation.reserved_at)
+        });
+
+        match matching {
+            Some((key, mut reservation)) => {
+                log::debug!(
+                    "reservation for contract {} confirmed at block {block:?}",
+                    reservation.contract_id
+                );
+                reservation.status = RewardReservationStatus::Confirmed {
+                    block: block.unwrap_or_default(),
+                };
+                RESERVATIONS.with_borrow_mut(|reservations| {
+                    reservations.insert(key, reservation);
+                });
+            }
+            None => Self::raise_alert(RewardPoolAlertKind::UnknownReservation {
+                reward,
+                tokens,
+                block,
+            }),
+        }
+    }
+
+    /// Mark the pending reservations older than [`RESERVATION_TIMEOUT`] as missing
+    fn expire_reservations() {
+        let now = time();
+        let expired = RESERVATIONS.with_borrow(|reservations| {
+            reservations
+                .iter()
+                .filter(|(_, reservation)| {
+                    reservation.status == RewardReservationStatus::Pending
+                        && now.saturating_sub(reservation.reserved_at) > RESERVATION_TIMEOUT
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (key, mut reservation) in expired {
+            Self::raise_alert(RewardPoolAlertKind::MissingReservation {
+                contract_id: reservation.contract_id.clone(),
+            });
+            reservation.status = RewardReservationStatus::Missing;
+            RESERVATIONS.with_borrow_mut(|reservations| {
+                reservations.insert(key, reservation);
+            });
+        }
+    }
+
+    fn raise_alert(kind: RewardPoolAlertKind) {
+        log::error!("reward pool drift detected: {kind:?}");
+
+        ALERTS.with_borrow_mut(|alerts| {
+            let id = alerts.len();
+            alerts.insert(
+                id,
+                RewardPoolAlert {
+                    timestamp: time(),
+                    kind,
+                },
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::H160;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::alice;
+
+    #[tokio::test]
+    async fn test_should_cache_available_rewards() {
+        let available = RewardPoolLedger::available_rewards(&reward_pool(), &client())
+            .await
+            .unwrap();
+        assert_eq!(available, 592_006_734_000_000);
+
+        // reserve a reward; the cached value is used
+        RewardPoolLedger::reserve(1u64.into(), 1_000, 4_000).unwrap();
+        let available = RewardPoolLedger::available_rewards(&reward_pool(), &client())
+            .await
+            .unwrap();
+        assert_eq!(available, 592_006_734_000_000 - 4_000_000);
+
+        let status = RewardPoolLedger::status();
+        assert_eq!(status.available_reward, 592_006_734_000_000);
+        assert_eq!(status.pending_reserved, 4_000_000);
+        assert_eq!(status.reserved_by_minter, 4_000_000);
+    }
+
+    #[test]
+    fn test_should_confirm_reservation_from_event() {
+        RewardPoolLedger::reserve(1u64.into(), 1_000, 4_000).unwrap();
+        RewardPoolLedger::reserve(2u64.into(), 2_000, 4_000).unwrap();
+
+        RewardPoolLedger::apply_events(vec![
+            RewardPoolEvent::PoolReserved {
+                reward: 2_000,
+                tokens: 4_000,
+                block: Some(100),
+            },
+            RewardPoolEvent::RewardSent {
+                amount: 500,
+                block: Some(101),
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(
+            RewardPoolLedger::get_reservation(&2u64.into())
+                .unwrap()
+                .status,
+            RewardReservationStatus::Confirmed { block: 100 }
+        );
+        assert_eq!(
+            RewardPoolLedger::get_reservation(&1u64.into())
+                .unwrap()
+                .status,
+            RewardReservationStatus::Pending
+        );
+
+        let status = RewardPoolLedger::status();
+        assert_eq!(status.pending_reserved, 4_000_000);
+        assert_eq!(status.reserved_on_chain, 8_000_000);
+        assert_eq!(status.rewards_sent, 500);
+        assert!(RewardPoolLedger::alerts().is_empty());
+    }
+
+    #[test]
+    fn test_should_not_subtract_reservations_read_from_chain() {
+        RewardPoolLedger::set_available_reward(10_000_000).unwrap();
+        RewardPoolLedger::reserve(1u64.into(), 1_000, 4_000).unwrap();
+        assert_eq!(RewardPoolLedger::local_available_reward(), 6_000_000);
+
+        // the refreshed value already counts the reservation
+        RewardPoolLedger::set_available_reward(6_000_000).unwrap();
+        assert_eq!(RewardPoolLedger::local_available_reward(), 6_000_000);
+    }
+
+    #[test]
+    fn test_should_reset_totals_and_confirmed_reservations() {
+        let events = || {
+            vec![
+                RewardPoolEvent::PoolReserved {
+                    reward: 1_000,
+                    tokens: 4_000,
+                    block: Some(100),
+                },
+                RewardPoolEvent::RewardSent {
+                    amount: 500,
+                    block: Some(101),
+                },
+            ]
+        };
+        RewardPoolLedger::reserve(1u64.into(), 1_000, 4_000).unwrap();
+        RewardPoolLedger::apply_events(events()).unwrap();
+
+        RewardPoolLedger::reset(100).unwrap();
+        let status = RewardPoolLedger::status();
+        assert_eq!(status.last_synced_block, 99);
+        assert_eq!(status.reserved_on_chain, 0);
+        assert_eq!(status.rewards_sent, 0);
+        assert_eq!(
+            RewardPoolLedger::get_reservation(&1u64.into())
+                .unwrap()
+                .status,
+            RewardReservationStatus::Missing
+        );
+
+        // the events scanned again are counted once
+        RewardPoolLedger::apply_events(events()).unwrap();
+        let status = RewardPoolLedger::status();
+        assert_eq!(status.reserved_on_chain, 4_000_000);
+        assert_eq!(status.rewards_sent, 500);
+        assert_eq!(
+            RewardPoolLedger::get_reservation(&1u64.into())
+                .unwrap()
+                .status,
+            RewardReservationStatus::Confirmed { block: 100 }
+        );
+        assert!(RewardPoolLedger::alerts().is_empty());
+    }
+
+    #[test]
+    fn test_should_init_last_synced_block_once() {
+        RewardPoolLedger::init_last_synced_block(21_000_000).unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 20_999_999);
+
+        RewardPoolLedger::init_last_synced_block(22_000_000).unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 20_999_999);
+    }
+
+    #[tokio::test]
+    async fn test_should_sync_from_deploy_block_to_latest_block() {
+        // the latest block of the mocked client is 21_000_000
+        RewardPoolLedger::reset(20_998_000).unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 20_997_999);
+
+        // the last synced block advances even if no event is found
+        RewardPoolLedger::sync(&reward_pool(), &client())
+            .await
+            .unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 21_000_000);
+
+        // nothing to scan
+        RewardPoolLedger::sync(&reward_pool(), &client())
+            .await
+            .unwrap();
+        assert_eq!(RewardPoolLedger::status().last_synced_block, 21_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_should_scan_bounded_block_range() {
+        RewardPoolLedger::reset(10_000_000).unwrap();
+
+        RewardPoolLedger::sync(&reward_pool(), &client())
+            .await
+            .unwrap();
+        assert_eq!(
+            RewardPoolLedger::status().last_synced_block,
+            9_999_999 + MAX_SYNC_BLOCK_RANGE
+        );
+    }
+
+    #[test]
+    fn test_should_alert_on_unknown_reservation() {
+        RewardPoolLedger::apply_events(vec![RewardPoolEvent::PoolReserved {
+            reward: 2_000,
+            tokens: 4_000,
+            block: Some(100),
+        }])
+        .unwrap();
+
+        let alerts = RewardPoolLedger::alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].kind,
+            RewardPoolAlertKind::UnknownReservation {
+                reward: 2_000,
+                tokens: 4_000,
+                block: Some(100),
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_alert_on_missing_reservation() {
+        RewardPoolLedger::reserve(1u64.into(), 1_000, 4_000).unwrap();
+        RESERVATIONS.with_borrow_mut(|reservations| {
+            let key = StorableNat::from(ID::from(1u64));
+            let mut reservation = reservations.get(&key).unwrap();
+            reservation.reserved_at = time() - RESERVATION_TIMEOUT - 1;
+            reservations.insert(key, reservation);
+        });
+
+        RewardPoolLedger::expire_reservations();
+
+        assert_eq!(
+            RewardPoolLedger::get_reservation(&1u64.into())
+                .unwrap()
+                .status,
+            RewardReservationStatus::Missing
+        );
+        let alerts = RewardPoolLedger::alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].kind,
+            RewardPoolAlertKind::MissingReservation {
+                contract_id: 1u64.into()
+            }
+        );
+        assert_eq!(RewardPoolLedger::status().pending_reserved, 0);
+    }
+
+    #[test]
+    fn test_should_alert_if_available_reward_increases() {
+        RewardPoolLedger::set_available_reward(1_000).unwrap();
+        RewardPoolLedger::set_available_reward(500).unwrap();
+        assert!(RewardPoolLedger::alerts().is_empty());
+
+        RewardPoolLedger::set_available_reward(2_000).unwrap();
+        let alerts = RewardPoolLedger::alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].kind,
+            RewardPoolAlertKind::AvailableRewardIncreased {
+                previous: 500,
+                current: 2_000
+            }
+        );
+    }
+
+    fn reward_pool() -> RewardPool {
+        RewardPool::from(H160::from_hex_str("0x2CE04Fd64DB0372F6fb4B7a542f0F9196feE5663").unwrap())
+    }
+
+    fn client() -> EvmRpcClient {
+        EvmRpcClient::new(alice(), 1, None)
+    }
+}
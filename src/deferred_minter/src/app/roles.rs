@@ -3,35 +3,183 @@
 use std::cell::RefCell;
 
 use candid::Principal;
-use did::deferred::{ConfigurationError, DeferredMinterError, DeferredMinterResult, Role, Roles};
+use did::deferred::{
+    ConfigurationError, DeferredMinterError, DeferredMinterResult, Permission, Permissions, Role,
+    RoleDefinition, Roles,
+};
 use did::StorablePrincipal;
 use ic_stable_structures::memory_manager::VirtualMemory;
-use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap};
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
 
-use crate::app::memory::{MEMORY_MANAGER, ROLES_MEMORY_ID};
+use crate::app::memory::{
+    CUSTOM_ROLES_MEMORY_ID, MEMORY_MANAGER, ROLES_MEMORY_ID, SNS_GOVERNANCE_MEMORY_ID,
+};
 
 thread_local! {
     /// Principals that can manage the canister
     static CANISTER_ROLES: RefCell<StableBTreeMap<StorablePrincipal, Roles, VirtualMemory<DefaultMemoryImpl>>> =
         RefCell::new(StableBTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(ROLES_MEMORY_ID)))
     );
+
+    /// Permissions granted by the roles defined by the custodians
+    static CUSTOM_ROLES: RefCell<StableBTreeMap<String, Permissions, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableBTreeMap::new(MEMORY_MANAGER.with(|mm| mm.get(CUSTOM_ROLES_MEMORY_ID)))
+    );
+
+    /// SNS governance canister allowed to govern the canister through proposals
+    static SNS_GOVERNANCE: RefCell<StableCell<StorablePrincipal, VirtualMemory<DefaultMemoryImpl>>> =
+        RefCell::new(StableCell::new(MEMORY_MANAGER.with(|mm| mm.get(SNS_GOVERNANCE_MEMORY_ID)), Principal::anonymous().into()).unwrap()
+    );
 }
 pub struct RolesManager;
 
 impl RolesManager {
     /// Returns whether principal is custodian
     pub fn is_custodian(principal: Principal) -> bool {
-        Self::with_principal(principal, |roles| roles.0.contains(&Role::Custodian)).unwrap_or(false)
+        Self::has_role(principal, &Role::Custodian)
+    }
+
+    /// Returns whether principal is the SNS governance canister
+    pub fn is_sns_governance(principal: Principal) -> bool {
+        Self::get_sns_governance() == Some(principal)
+    }
+
+    /// Get the SNS governance canister, if set
+    pub fn get_sns_governance() -> Option<Principal> {
+        let principal = SNS_GOVERNANCE.with_borrow(|cell| cell.get().0);
+        (principal != Principal::anonymous()).then_some(principal)
+    }
+
+    /// Set the SNS governance canister, or unset it if `None`
+    pub fn set_sns_governance(principal: Option<Principal>) -> DeferredMinterResult<()> {
+        SNS_GOVERNANCE
+            .with_borrow_mut(|cell| cell.set(principal.unwrap_or(Principal::anonymous()).into()))
+            .map_err(|_| DeferredMinterError::StorageError)?;
+
+        Ok(())
+    }
+
+    /// Returns whether principal has been given the provided role
+    pub fn has_role(principal: Principal, role: &Role) -> bool {
+        Self::with_principal(principal, |roles| roles.0.contains(role)).unwrap_or(false)
+    }
+
+    /// Returns whether any of the roles of the principal grants the provided permission
+    pub fn has_permission(principal: Principal, permission: Permission) -> bool {
+        Self::get_permissions(principal).contains(&permission)
+    }
+
+    /// Get the permissions granted to the principal by its roles.
+    ///
+    /// The SNS governance canister is granted every permission
+    pub fn get_permissions(principal: Principal) -> Vec<Permission> {
+        if Self::is_sns_governance(principal) {
+            return Permission::all();
+        }
+
+        let mut permissions = Self::with_principal(principal, |roles| {
+            roles
+                .0
+                .iter()
+                .flat_map(Self::role_permissions)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+        permissions.sort();
+        permissions.dedup();
+
+        permissions
+    }
+
+    /// Get the permissions granted by the role.
+    ///
+    /// An undefined custom role grants no permission
+    fn role_permissions(role: &Role) -> Vec<Permission> {
+        match role {
+            Role::Custom(name) => CUSTOM_ROLES
+                .with_borrow(|roles| roles.get(name))
+                .map(|permissions| permissions.0)
+                .unwrap_or_default(),
+            role => role.predefined_permissions().unwrap_or_default(),
+        }
     }
 
-    pub fn is_gas_station(principal: Principal) -> bool {
-        Self::with_principal(principal, |roles| roles.0.contains(&Role::GasStation))
-            .unwrap_or(false)
+    /// Get the predefined and the custom roles with their permissions
+    pub fn get_roles() -> Vec<RoleDefinition> {
+        let predefined = Role::predefined().into_iter().map(|role| RoleDefinition {
+            permissions: Self::role_permissions(&role),
+            role,
+        });
+        let custom = CUSTOM_ROLES.with_borrow(|roles| {
+            roles
+                .iter()
+                .map(|(name, permissions)| RoleDefinition {
+                    role: Role::Custom(name),
+                    permissions: permissions.0,
+                })
+                .collect::<Vec<_>>()
+        });
+
+        predefined.chain(custom).collect()
+    }
+
+    /// Define a custom role or update the permissions of an existing one.
+    ///
+    /// The name can't be empty or be the name of a predefined role
+    pub fn define_role(name: String, permissions: Vec<Permission>) -> DeferredMinterResult<()> {
+        Self::check_role_name(&name)?;
+
+        CUSTOM_ROLES.with_borrow_mut(|roles| roles.insert(name, permissions.into()));
+
+        Ok(())
+    }
+
+    /// Check whether the name can be given to a custom role
+    pub fn check_role_name(name: &str) -> DeferredMinterResult<()> {
+        let is_predefined = Role::predefined()
+            .iter()
+            .any(|role| format!("{role:?}").eq_ignore_ascii_case(name.trim()));
+        if name.trim().is_empty() || name.trim() != name || is_predefined {
+            return Err(DeferredMinterError::Configuration(
+                ConfigurationError::InvalidRoleName(name.to_string()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Delete a custom role, removing it from all the principals which had it
+    pub fn delete_role(name: &str) -> DeferredMinterResult<()> {
+        if CUSTOM_ROLES
+            .with_borrow_mut(|roles| roles.remove(&name.to_string()))
+            .is_none()
+        {
+            return Err(DeferredMinterError::Configuration(
+                ConfigurationError::UnknownRole(name.to_string()),
+            ));
+        }
+
+        let role = Role::Custom(name.to_string());
+        CANISTER_ROLES.with_borrow_mut(|roles_map| {
+            let holders = roles_map
+                .iter()
+                .filter(|(_, roles)| roles.0.contains(&role))
+                .collect::<Vec<_>>();
+            for (principal, mut roles) in holders {
+                roles.0.retain(|r| r != &role);
+                roles_map.insert(principal, roles);
+            }
+        });
+
+        Ok(())
     }
 
-    /// Returns whether principal is agent
-    pub fn is_agent(principal: Principal) -> bool {
-        Self::with_principal(principal, |roles| roles.0.contains(&Role::Agent)).unwrap_or(false)
+    /// Returns whether the role is either predefined or a defined custom role
+    pub fn is_defined(role: &Role) -> bool {
+        match role {
+            Role::Custom(name) => CUSTOM_ROLES.with_borrow(|roles| roles.contains_key(name)),
+            _ => true,
+        }
     }
 
     /// Get canister custodians
@@ -95,6 +243,23 @@ impl RolesManager {
         });
     }
 
+    /// Give a certain principal the provided role.
+    ///
+    /// Fails if the role is a custom role which hasn't been defined
+    pub fn give_defined_role(principal: Principal, role: Role) -> DeferredMinterResult<()> {
+        if let Role::Custom(name) = &role {
+            if !Self::is_defined(&role) {
+                return Err(DeferredMinterError::Configuration(
+                    ConfigurationError::UnknownRole(name.clone()),
+                ));
+            }
+        }
+
+        Self::give_role(principal, role);
+
+        Ok(())
+    }
+
     /// Remove a role from the provided role.
     /// Fails if trying to remove the only custodian of the canister
     pub fn remove_role(principal: Principal, role: Role) -> DeferredMinterResult<()> {
@@ -196,8 +361,15 @@ mod test {
             Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
                 .unwrap();
         RolesManager::give_role(principal, Role::GasStation);
-        assert!(RolesManager::is_gas_station(principal));
-        assert!(!RolesManager::is_gas_station(Principal::anonymous()));
+        assert!(RolesManager::has_permission(principal, Permission::SetGas));
+        assert!(!RolesManager::has_permission(
+            principal,
+            Permission::ManageAgencies
+        ));
+        assert!(!RolesManager::has_permission(
+            Principal::anonymous(),
+            Permission::SetGas
+        ));
     }
 
     #[test]
@@ -205,9 +377,15 @@ mod test {
         let principal =
             Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
                 .unwrap();
-        assert!(!RolesManager::is_agent(principal));
+        assert!(!RolesManager::has_permission(
+            principal,
+            Permission::CreateContracts
+        ));
         RolesManager::give_role(principal, Role::Agent);
-        assert!(RolesManager::is_agent(principal));
+        assert!(RolesManager::has_permission(
+            principal,
+            Permission::CreateContracts
+        ));
     }
 
     #[test]
@@ -215,12 +393,17 @@ mod test {
         let principal =
             Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
                 .unwrap();
-        assert!(!RolesManager::is_agent(principal));
         RolesManager::give_role(principal, Role::Agent);
-        assert!(RolesManager::is_agent(principal));
+        assert!(RolesManager::has_permission(
+            principal,
+            Permission::CreateContracts
+        ));
 
         assert!(RolesManager::remove_role(principal, Role::Agent).is_ok());
-        assert!(!RolesManager::is_agent(principal));
+        assert!(!RolesManager::has_permission(
+            principal,
+            Permission::CreateContracts
+        ));
     }
 
     #[test]
@@ -234,4 +417,115 @@ mod test {
         assert!(RolesManager::remove_role(principal, Role::Custodian).is_err());
         assert!(RolesManager::is_custodian(principal));
     }
+
+    #[test]
+    fn test_should_grant_every_permission_to_custodians() {
+        let principal =
+            Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
+                .unwrap();
+        assert!(RolesManager::set_custodians(vec![principal]).is_ok());
+        assert_eq!(RolesManager::get_permissions(principal), Permission::all());
+    }
+
+    #[test]
+    fn test_should_grant_every_permission_to_sns_governance() {
+        let governance = Principal::management_canister();
+        assert!(RolesManager::get_sns_governance().is_none());
+        assert!(RolesManager::get_permissions(governance).is_empty());
+
+        assert!(RolesManager::set_sns_governance(Some(governance)).is_ok());
+        assert_eq!(RolesManager::get_sns_governance(), Some(governance));
+        assert!(RolesManager::is_sns_governance(governance));
+        assert_eq!(RolesManager::get_permissions(governance), Permission::all());
+        // it is not a custodian
+        assert!(!RolesManager::is_custodian(governance));
+
+        assert!(RolesManager::set_sns_governance(None).is_ok());
+        assert!(!RolesManager::is_sns_governance(governance));
+        assert!(RolesManager::get_permissions(governance).is_empty());
+    }
+
+    #[test]
+    fn test_should_define_custom_role() {
+        let principal =
+            Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
+                .unwrap();
+        let auditor = Role::Custom("auditor".to_string());
+
+        assert!(RolesManager::give_defined_role(principal, auditor.clone()).is_err());
+        assert!(RolesManager::define_role(
+            "auditor".to_string(),
+            vec![Permission::ReadLogs, Permission::ReadLogs]
+        )
+        .is_ok());
+        assert!(RolesManager::give_defined_role(principal, auditor.clone()).is_ok());
+        RolesManager::give_role(principal, Role::GasStation);
+
+        assert_eq!(
+            RolesManager::get_permissions(principal),
+            vec![Permission::SetGas, Permission::ReadLogs]
+        );
+        assert_eq!(
+            RolesManager::get_roles().last().unwrap(),
+            &RoleDefinition {
+                role: auditor,
+                permissions: vec![Permission::ReadLogs],
+            }
+        );
+
+        // update permissions
+        assert!(
+            RolesManager::define_role("auditor".to_string(), vec![Permission::ManageConfig])
+                .is_ok()
+        );
+        assert!(RolesManager::has_permission(
+            principal,
+            Permission::ManageConfig
+        ));
+        assert!(!RolesManager::has_permission(
+            principal,
+            Permission::ReadLogs
+        ));
+    }
+
+    #[test]
+    fn test_should_not_define_role_with_invalid_name() {
+        for name in ["", " auditor", "Custodian", "gasstation"] {
+            assert_eq!(
+                RolesManager::define_role(name.to_string(), vec![Permission::ReadLogs]),
+                Err(DeferredMinterError::Configuration(
+                    ConfigurationError::InvalidRoleName(name.to_string())
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_should_delete_custom_role() {
+        let principal =
+            Principal::from_text("zrrb4-gyxmq-nx67d-wmbky-k6xyt-byhmw-tr5ct-vsxu4-nuv2g-6rr65-aae")
+                .unwrap();
+        let auditor = Role::Custom("auditor".to_string());
+        assert!(
+            RolesManager::define_role("auditor".to_string(), vec![Permission::ReadLogs]).is_ok()
+        );
+        assert!(RolesManager::give_defined_role(principal, auditor.clone()).is_ok());
+        RolesManager::give_role(principal, Role::Agent);
+
+        assert!(RolesManager::delete_role("auditor").is_ok());
+        assert!(!RolesManager::has_permission(
+            principal,
+            Permission::ReadLogs
+        ));
+        assert_eq!(
+            RolesManager::with_principal(principal, |roles| roles.clone()),
+            Some(vec![Role::Agent].into())
+        );
+        assert_eq!(
+            RolesManager::delete_role("auditor"),
+            Err(DeferredMinterError::Configuration(
+                ConfigurationError::UnknownRole("auditor".to_string())
+            ))
+        );
+    }
 }
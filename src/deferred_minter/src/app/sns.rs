@@ -0,0 +1,429 @@
+//! # SNS
+//!
+//! Validation of the SNS generic function proposals.
+//!
+//! Every admin method has a `validate_` companion which the SNS governance canister calls when a
+//! proposal is submitted. The validator checks the arguments without changing the state and
+//! renders the payload shown to the voters.
+
+use candid::Principal;
+use did::deferred::{
+    Agency, AgencyApplicationStatus, AgencyError, AgencyId, AgencyQuota, AgencyTransferStatus,
+    ConfigurationUpdate, DeferredMinterError, DeferredMinterResult, PausableOperation, Permission,
+    ProposalAction, ProposalError, ProposalStatus, RewardParameters, Role,
+};
+
+use super::agency_applications::AgencyApplications;
+use super::agency_transfers::AgencyTransfers;
+use super::agents::Agents;
+use super::pauses::Pauses;
+use super::proposals::Proposals;
+use super::roles::RolesManager;
+use super::Inspect;
+
+pub struct SnsValidator;
+
+impl SnsValidator {
+    pub fn admin_register_agency(
+        wallet: Principal,
+        agency: &Agency,
+    ) -> DeferredMinterResult<String> {
+        Inspect::inspect_agency(agency)?;
+        if Agents::get_membership(wallet).is_some_and(|member| member.agency != agency.id) {
+            return Err(AgencyError::AlreadyMember(wallet).into());
+        }
+
+        if Agents::get_agency(agency.id).is_some() {
+            Ok(format!(
+                "Update agency {} ({}) with wallet {wallet}",
+                agency.name, agency.id
+            ))
+        } else {
+            Ok(format!(
+                "Register agency {} with wallet {wallet}",
+                agency.name
+            ))
+        }
+    }
+
+    pub fn admin_suspend_agency(agency: AgencyId) -> DeferredMinterResult<String> {
+        if Agents::get_agency(agency).is_none() {
+            return Err(AgencyError::AgencyNotFound(agency).into());
+        }
+        if Agents::is_suspended(agency) {
+            return Err(AgencyError::AgencySuspended(agency).into());
+        }
+
+        Ok(format!("Suspend agency {agency}"))
+    }
+
+    pub fn admin_reactivate_agency(agency: AgencyId) -> DeferredMinterResult<String> {
+        if !Agents::is_suspended(agency) {
+            return Err(AgencyError::AgencyNotSuspended(agency).into());
+        }
+
+        Ok(format!("Reactivate agency {agency}"))
+    }
+
+    pub fn admin_set_agency_quota(
+        agency: AgencyId,
+        quota: Option<AgencyQuota>,
+    ) -> DeferredMinterResult<String> {
+        if Agents::get_agency(agency).is_none() {
+            return Err(AgencyError::AgencyNotFound(agency).into());
+        }
+
+        Ok(AgencyQuota::describe_change(agency, quota.as_ref()))
+    }
+
+    pub fn admin_approve_agency_transfer(id: u64) -> DeferredMinterResult<String> {
+        let transfer =
+            AgencyTransfers::get_transfer(id).ok_or(AgencyError::TransferNotFound(id))?;
+        if transfer.status != AgencyTransferStatus::Pending {
+            return Err(AgencyError::TransferNotPending(id).into());
+        }
+        if !transfer.is_accepted() {
+            return Err(AgencyError::TransferNotAccepted(id).into());
+        }
+        if Agents::is_suspended(transfer.to) {
+            return Err(AgencyError::AgencySuspended(transfer.to).into());
+        }
+
+        Ok(format!(
+            "Approve agency transfer {id} of real estates {:?} and contracts {:?} from {} to {}",
+            transfer.real_estates, transfer.contracts, transfer.from, transfer.to
+        ))
+    }
+
+    pub fn admin_comment_agency_application(
+        id: u64,
+        comment: &str,
+    ) -> DeferredMinterResult<String> {
+        AgencyApplications::get_application(id).ok_or(AgencyError::ApplicationNotFound(id))?;
+
+        Ok(format!("Comment agency application {id}: {comment}"))
+    }
+
+    pub fn admin_approve_agency_application(
+        id: u64,
+        comment: Option<&str>,
+    ) -> DeferredMinterResult<String> {
+        let application = Self::pending_application(id)?;
+        if Agents::get_membership(application).is_some() {
+            return Err(AgencyError::AgencyAlreadyRegistered(application).into());
+        }
+
+        Ok(format!(
+            "Approve agency application {id} of {application}: {}",
+            comment.unwrap_or_default()
+        ))
+    }
+
+    pub fn admin_reject_agency_application(id: u64, comment: &str) -> DeferredMinterResult<String> {
+        let application = Self::pending_application(id)?;
+
+        Ok(format!(
+            "Reject agency application {id} of {application}: {comment}"
+        ))
+    }
+
+    pub fn admin_set_role(principal: Principal, role: Role) -> DeferredMinterResult<String> {
+        let payload = format!("Give role {role:?} to {principal}");
+        Self::admin_propose(ProposalAction::SetRole { principal, role })?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_remove_role(principal: Principal, role: Role) -> DeferredMinterResult<String> {
+        let payload = format!("Remove role {role:?} from {principal}");
+        Self::admin_propose(ProposalAction::RemoveRole { principal, role })?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_define_role(
+        name: String,
+        permissions: Vec<Permission>,
+    ) -> DeferredMinterResult<String> {
+        let payload = format!("Define role {name} with permissions {permissions:?}");
+        Self::admin_propose(ProposalAction::DefineRole { name, permissions })?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_delete_role(name: String) -> DeferredMinterResult<String> {
+        let payload = format!("Delete role {name}");
+        Self::admin_propose(ProposalAction::DeleteRole(name))?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_set_allowed_currencies(currencies: Vec<String>) -> DeferredMinterResult<String> {
+        let payload = format!("Set allowed currencies to {currencies:?}");
+        Self::admin_propose(ProposalAction::SetAllowedCurrencies(currencies))?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_set_custodians(custodians: Vec<Principal>) -> DeferredMinterResult<String> {
+        let payload = format!("Set custodians to {custodians:?}");
+        Self::admin_propose(ProposalAction::SetCustodians(custodians))?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_update_configuration(update: ConfigurationUpdate) -> DeferredMinterResult<String> {
+        let payload = format!("Update configuration: {update:?}");
+        Self::admin_propose(ProposalAction::UpdateConfiguration(update))?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_pause(operation: PausableOperation, reason: &str) -> DeferredMinterResult<String> {
+        Pauses::check_reason(reason)?;
+        if Pauses::get(operation).is_some() {
+            return Err(DeferredMinterError::Paused(operation));
+        }
+
+        Ok(format!(
+            "Pause {operation} until resumed by a proposal. Reason: {reason}"
+        ))
+    }
+
+    pub fn admin_set_reward_parameters(
+        parameters: RewardParameters,
+    ) -> DeferredMinterResult<String> {
+        let payload = format!("Set reward parameters: {parameters:?}");
+        Self::admin_propose(ProposalAction::SetRewardParameters(parameters))?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_resume(operation: PausableOperation) -> DeferredMinterResult<String> {
+        let payload = format!("Resume operation {operation:?}");
+        Self::admin_propose(ProposalAction::Resume(operation))?;
+
+        Ok(payload)
+    }
+
+    pub fn admin_propose(action: ProposalAction) -> DeferredMinterResult<String> {
+        Proposals::validate_action(&action)?;
+
+        Ok(format!("Propose {action:?}"))
+    }
+
+    pub fn admin_approve_proposal(id: u64) -> DeferredMinterResult<String> {
+        let action = Self::pending_proposal(id)?;
+
+        Ok(format!("Approve proposal {id}: {action:?}"))
+    }
+
+    pub fn admin_reject_proposal(id: u64) -> DeferredMinterResult<String> {
+        let action = Self::pending_proposal(id)?;
+
+        Ok(format!("Reject proposal {id}: {action:?}"))
+    }
+
+    pub fn admin_sync_reward_pool() -> DeferredMinterResult<String> {
+        Ok("Reconcile the reward pool ledger with the chain".to_string())
+    }
+
+    pub fn gas_station_set_gas_price(gas_price: u64) -> DeferredMinterResult<String> {
+        Ok(format!("Set gas price to {gas_price} wei"))
+    }
+
+    /// Returns the applicant of the pending application
+    fn pending_application(id: u64) -> DeferredMinterResult<Principal> {
+        let application =
+            AgencyApplications::get_application(id).ok_or(AgencyError::ApplicationNotFound(id))?;
+        if application.status != AgencyApplicationStatus::Pending {
+            return Err(AgencyError::ApplicationAlreadyReviewed(id).into());
+        }
+
+        Ok(application.applicant)
+    }
+
+    /// Returns the action of the pending proposal
+    fn pending_proposal(id: u64) -> DeferredMinterResult<ProposalAction> {
+        let proposal = Proposals::get_proposal(id).ok_or(ProposalError::ProposalNotFound(id))?;
+        match proposal.status {
+            ProposalStatus::Pending => Ok(proposal.action),
+            ProposalStatus::Expired => Err(ProposalError::ProposalExpired(id).into()),
+            _ => Err(ProposalError::ProposalNotPending(id).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::ConfigurationError;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob};
+
+    #[test]
+    fn test_should_validate_register_agency() {
+        let agency = Agency {
+            name: "Dummy Real estate".to_string(),
+            email: "info@dummy.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            SnsValidator::admin_register_agency(alice(), &agency).unwrap(),
+            format!("Register agency Dummy Real estate with wallet {}", alice())
+        );
+        assert_eq!(
+            SnsValidator::admin_register_agency(alice(), &Agency::default()),
+            Err(AgencyError::EmptyField("name".to_string()).into())
+        );
+
+        let id = Agents::insert_agency(Agency {
+            owner: alice(),
+            ..agency.clone()
+        })
+        .unwrap();
+        assert_eq!(
+            SnsValidator::admin_register_agency(alice(), &agency),
+            Err(AgencyError::AlreadyMember(alice()).into())
+        );
+        assert_eq!(
+            SnsValidator::admin_register_agency(alice(), &Agency { id, ..agency }).unwrap(),
+            format!(
+                "Update agency Dummy Real estate ({id}) with wallet {}",
+                alice()
+            )
+        );
+    }
+
+    #[test]
+    fn test_should_validate_agency_status() {
+        assert_eq!(
+            SnsValidator::admin_suspend_agency(AgencyId(1)),
+            Err(AgencyError::AgencyNotFound(AgencyId(1)).into())
+        );
+        assert_eq!(
+            SnsValidator::admin_reactivate_agency(AgencyId(1)),
+            Err(AgencyError::AgencyNotSuspended(AgencyId(1)).into())
+        );
+        assert_eq!(
+            SnsValidator::admin_set_agency_quota(AgencyId(1), None),
+            Err(AgencyError::AgencyNotFound(AgencyId(1)).into())
+        );
+    }
+
+    #[test]
+    fn test_should_validate_pause() {
+        assert_eq!(
+            SnsValidator::admin_pause(PausableOperation::ContractCreation, "bug").unwrap(),
+            "Pause contract creation until resumed by a proposal. Reason: bug"
+        );
+        assert!(SnsValidator::admin_pause(PausableOperation::ContractCreation, "").is_err());
+
+        Pauses::pause(
+            PausableOperation::ContractCreation,
+            alice(),
+            "bug".to_string(),
+        );
+        assert_eq!(
+            SnsValidator::admin_pause(PausableOperation::ContractCreation, "again"),
+            Err(DeferredMinterError::Paused(
+                PausableOperation::ContractCreation
+            ))
+        );
+    }
+
+    #[test]
+    fn test_should_validate_roles() {
+        RolesManager::set_custodians(vec![alice()]).unwrap();
+
+        assert_eq!(
+            SnsValidator::admin_set_role(bob(), Role::GasStation).unwrap(),
+            format!("Give role GasStation to {}", bob())
+        );
+        assert_eq!(
+            SnsValidator::admin_set_role(bob(), Role::Custom("auditor".to_string())),
+            Err(ConfigurationError::UnknownRole("auditor".to_string()).into())
+        );
+        assert_eq!(
+            SnsValidator::admin_delete_role("auditor".to_string()),
+            Err(ConfigurationError::UnknownRole("auditor".to_string()).into())
+        );
+        assert_eq!(
+            SnsValidator::admin_define_role("custodian".to_string(), vec![Permission::ReadLogs]),
+            Err(ConfigurationError::InvalidRoleName("custodian".to_string()).into())
+        );
+        assert_eq!(
+            SnsValidator::admin_remove_role(alice(), Role::Custodian),
+            Err(ConfigurationError::CustodialsCantBeEmpty.into())
+        );
+        assert_eq!(
+            SnsValidator::admin_remove_role(bob(), Role::GasStation).unwrap(),
+            format!("Remove role GasStation from {}", bob())
+        );
+        assert_eq!(
+            SnsValidator::admin_define_role("auditor".to_string(), vec![Permission::ReadLogs])
+                .unwrap(),
+            "Define role auditor with permissions [ReadLogs]"
+        );
+    }
+
+    #[test]
+    fn test_should_validate_reward_parameters() {
+        let parameters = RewardParameters {
+            rmc: 0.000001,
+            avidity: 0.5,
+            next_halving: crate::utils::time() + 1_000,
+        };
+        assert_eq!(
+            SnsValidator::admin_set_reward_parameters(parameters).unwrap(),
+            format!("Set reward parameters: {parameters:?}")
+        );
+        assert!(matches!(
+            SnsValidator::admin_set_reward_parameters(RewardParameters {
+                next_halving: 0,
+                ..parameters
+            }),
+            Err(DeferredMinterError::Configuration(
+                ConfigurationError::InvalidRewardParameters(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_should_validate_without_changing_state() {
+        RolesManager::set_custodians(vec![alice()]).unwrap();
+
+        assert!(SnsValidator::admin_set_custodians(vec![bob()]).is_ok());
+        assert!(SnsValidator::admin_set_allowed_currencies(vec!["EUR".to_string()]).is_ok());
+        assert_eq!(RolesManager::get_custodians(), vec![alice()]);
+        assert!(Proposals::get_proposal(1).is_none());
+
+        assert!(matches!(
+            SnsValidator::admin_set_allowed_currencies(vec!["TOOLONGCURRENCY".to_string()]),
+            Err(DeferredMinterError::Proposal(
+                ProposalError::InvalidProposal(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_should_validate_proposal_review() {
+        assert_eq!(
+            SnsValidator::admin_approve_proposal(1),
+            Err(ProposalError::ProposalNotFound(1).into())
+        );
+
+        let id = Proposals::propose(alice(), ProposalAction::SetCustodians(vec![bob()]))
+            .unwrap()
+            .id;
+        assert!(SnsValidator::admin_approve_proposal(id).is_ok());
+
+        Proposals::reject(id, alice()).unwrap();
+        assert_eq!(
+            SnsValidator::admin_reject_proposal(id),
+            Err(ProposalError::ProposalNotPending(id).into())
+        );
+    }
+}
@@ -1,12 +1,10 @@
 use candid::Principal;
 use did::deferred::{
-    Agency, Continent, Contract, GenericValue, RealEstate, RestrictedProperty, RestrictionLevel,
-    Seller,
+    Agency, AgencyId, Continent, Contract, GenericValue, RealEstate, RestrictedProperty,
+    RestrictionLevel, Seller,
 };
 use did::H160;
 
-use crate::utils::caller;
-
 pub fn mock_contract(id: u64, installments: u64) -> Contract {
     Contract {
         id: id.into(),
@@ -33,7 +31,7 @@ pub fn mock_contract(id: u64, installments: u64) -> Contract {
         )],
         documents: vec![],
         real_estate: 1u64.into(),
-        agency: mock_agency().owner,
+        agency: mock_agency().id,
         expiration: "2078-01-01".to_string(),
         closed: false,
     }
@@ -41,6 +39,7 @@ pub fn mock_contract(id: u64, installments: u64) -> Contract {
 
 pub fn mock_agency() -> Agency {
     Agency {
+        id: AgencyId(1),
         name: "Dummy Real estate".to_string(),
         address: "Via Delle Botteghe Scure".to_string(),
         city: "Rome".to_string(),
@@ -63,7 +62,7 @@ pub fn mock_agency() -> Agency {
 pub fn mock_real_estate() -> RealEstate {
     RealEstate {
         deleted: false,
-        agency: caller(),
+        agency: mock_agency().id,
         name: "name".to_string(),
         description: "description".to_string(),
         image: Some("image".to_string()),
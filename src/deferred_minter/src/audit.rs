@@ -0,0 +1,20 @@
+//! # Audit
+//!
+//! Records the state-changing calls dispatched by the canister endpoints into the audit log
+
+use std::fmt::Display;
+
+use crate::app::AuditLog;
+use crate::utils::caller;
+
+/// Record the call of `method` with the provided arguments summary and its result.
+///
+/// The result is returned as it is, so the endpoint can return it
+pub fn record<T, E>(method: &str, args: String, result: Result<T, E>) -> Result<T, E>
+where
+    E: Display,
+{
+    AuditLog::append(caller(), method, &args, (&result).into());
+
+    result
+}
@@ -0,0 +1,19 @@
+//! # Guard
+//!
+//! Authorization checks run by the canister endpoints before dispatching the call
+
+use did::deferred::{DeferredMinterError, DeferredMinterResult, Permission};
+
+use crate::app::Inspect;
+use crate::utils::caller;
+
+/// Check whether the roles of the caller grant the provided permission.
+///
+/// Returns [`DeferredMinterError::MissingPermission`] otherwise
+pub fn require_permission(permission: Permission) -> DeferredMinterResult<()> {
+    if Inspect::inspect_has_permission(caller(), permission) {
+        Ok(())
+    } else {
+        Err(DeferredMinterError::MissingPermission(permission))
+    }
+}
@@ -1,15 +1,16 @@
 mod agents;
 
 use agents::{Filters, FILTER_PAGINATION_LIMIT, FILTER_PAGINATION_OFFSET};
-use candid::Principal;
-use did::{HttpRequest, HttpResponse};
+use did::deferred::AgencyId;
+use did::{HttpRequest, HttpResponse, METRICS_CONTENT_TYPE};
 use route_recognizer::Router;
 use url::Url;
 
-use crate::app::DeferredMinter;
+use crate::app::{DeferredMinter, Metrics};
 
 const ROUTE_AGENTS: &str = "Agents";
 const ROUTE_AGENT: &str = "Agent";
+const ROUTE_METRICS: &str = "Metrics";
 
 struct Pagination {
     offset: usize,
@@ -40,6 +41,7 @@ impl HttpApi {
         let mut router = Router::new();
         router.add("/agents", ROUTE_AGENTS);
         router.add("/agent/:id", ROUTE_AGENT);
+        router.add("/metrics", ROUTE_METRICS);
 
         let Ok(route_match) = router.recognize(url.path()) else {
             return HttpResponse::not_found();
@@ -53,11 +55,12 @@ impl HttpApi {
                 let Some(id) = params.find("id") else {
                     return HttpResponse::bad_request("missing agent id".to_string());
                 };
-                let Ok(id) = Principal::from_text(id) else {
+                let Ok(id) = id.parse::<AgencyId>() else {
                     return HttpResponse::bad_request("invalid agent id".to_string());
                 };
                 Self::get_agent(id)
             }
+            ROUTE_METRICS => Self::get_metrics(),
 
             _ => HttpResponse::not_found(),
         }
@@ -89,14 +92,18 @@ impl HttpApi {
         )
     }
 
-    fn get_agent(id: Principal) -> HttpResponse {
-        let Some(agent) = DeferredMinter::get_agent(id) else {
+    fn get_agent(id: AgencyId) -> HttpResponse {
+        let Some(agent) = DeferredMinter::get_agency(id) else {
             return HttpResponse::not_found();
         };
 
         HttpResponse::ok(agent)
     }
 
+    fn get_metrics() -> HttpResponse {
+        HttpResponse::ok_raw(Metrics::encode().into_bytes(), METRICS_CONTENT_TYPE)
+    }
+
     /// Extracts pagination from URL
     fn get_pagination(url: &Url) -> Option<Pagination> {
         let offset = url
@@ -129,8 +136,8 @@ mod test {
 
     #[tokio::test]
     async fn test_should_get_agencies() {
-        let agent = mock_agency();
-        Agents::insert_agency(agent.owner, agent.clone());
+        let mut agent = mock_agency();
+        agent.id = Agents::insert_agency(agent.clone()).unwrap();
 
         let url = Url::parse("http://localhost/agents").unwrap();
 
@@ -152,12 +159,12 @@ mod test {
 
     #[tokio::test]
     async fn test_should_get_agencies_with_filters() {
-        let agent = with_mock_agency(|agent| {
+        let mut agent = with_mock_agency(|agent| {
             agent.name = "Dummy Real estate".to_string();
             agent.city = "Rome".to_string();
             agent.continent = did::deferred::Continent::Europe;
         });
-        Agents::insert_agency(agent.owner, agent.clone());
+        agent.id = Agents::insert_agency(agent.clone()).unwrap();
 
         let url =
             Url::parse("http://localhost/agents?name=Dummy&city=Rome&continent=Europe").unwrap();
@@ -179,10 +186,10 @@ mod test {
 
     #[tokio::test]
     async fn test_should_get_agent() {
-        let agent = mock_agency();
-        Agents::insert_agency(agent.owner, agent.clone());
+        let mut agent = mock_agency();
+        agent.id = Agents::insert_agency(agent.clone()).unwrap();
 
-        let url = Url::parse(&format!("http://localhost/agent/{}", agent.owner)).unwrap();
+        let url = Url::parse(&format!("http://localhost/agent/{}", agent.id)).unwrap();
 
         let req = HttpRequest {
             method: Cow::from("GET".to_string()),
@@ -201,7 +208,7 @@ mod test {
 
     #[tokio::test]
     async fn test_should_return_not_found() {
-        let url = Url::parse("http://localhost/agent/uf6dk-hyaaa-aaaaq-qaaaq-cai").unwrap();
+        let url = Url::parse("http://localhost/agent/42").unwrap();
 
         let req = HttpRequest {
             method: Cow::from("GET".to_string()),
@@ -214,6 +221,31 @@ mod test {
         assert_eq!(res.status_code, 404);
     }
 
+    #[tokio::test]
+    async fn test_should_get_metrics() {
+        Agents::insert_agency(mock_agency()).unwrap();
+
+        let req = HttpRequest {
+            method: Cow::from("GET".to_string()),
+            url: "/metrics".to_string(),
+            headers: HashMap::default(),
+            body: Default::default(),
+        };
+
+        let res = HttpApi::handle_http_request(req).await;
+        assert_eq!(res.status_code, 200);
+        assert_eq!(
+            res.headers.get("content-type").map(|value| value.as_ref()),
+            Some(METRICS_CONTENT_TYPE)
+        );
+
+        let metrics = String::from_utf8(res.body.to_vec()).unwrap();
+        assert!(metrics.contains("deferred_minter_cycles_balance 30000000000\n"));
+        assert!(metrics.contains("deferred_minter_agencies 1\n"));
+        assert!(metrics.contains("deferred_minter_stable_memory_bytes{memory_id=\"140\"}"));
+        assert!(metrics.contains("# TYPE deferred_minter_evm_rpc_calls_total counter\n"));
+    }
+
     #[tokio::test]
     async fn test_should_get_pagination_from_url() {
         let url = Url::parse("http://localhost/agents?offset=10&limit=20").unwrap();
@@ -6,18 +6,26 @@
 
 use candid::{candid_method, Nat, Principal};
 use did::deferred::{
-    Agency, ContractRegistration, DeferredMinterInitData, DeferredMinterResult, RealEstate, Role,
+    audit_list, audit_text, Agency, AgencyApplication, AgencyApplicationDocument,
+    AgencyApplicationStatus, AgencyChange, AgencyId, AgencyMember, AgencyQuota, AgencyReport,
+    AgencyRole, AgencyStatus, AgencyTransfer, AgencyTransferAuditEntry, AgencyUpdate, AuditEntry,
+    AuditFilter, AuditIntegrity, AuditSummary, ConfigurationChange, ConfigurationUpdate,
+    ContractRegistration, DeferredMinterConfiguration, DeferredMinterInitData,
+    DeferredMinterResult, DocumentsAnchor, MigrationStatus, PausableOperation, PauseState,
+    Permission, Proposal, ProposalAction, ProposalConfig, ProposalStatus, RealEstate,
+    RewardParameters, RewardPoolAlert, RewardPoolStatus, Role, RoleDefinition,
 };
 use did::{HttpRequest, HttpResponse, ID};
 use ic_cdk::post_upgrade;
 use ic_cdk_macros::{init, query, update};
 
 mod app;
+mod audit;
+mod guard;
 mod http;
-mod inspect;
 mod utils;
 
-use app::DeferredMinter;
+use app::{DeferredMinter, SnsValidator};
 use ic_log::did::Pagination;
 use ic_log::writer::Logs;
 
@@ -27,8 +35,8 @@ pub fn init(init_data: DeferredMinterInitData) {
 }
 
 #[post_upgrade]
-pub fn post_upgrade() {
-    DeferredMinter::post_upgrade();
+pub fn post_upgrade(update: Option<ConfigurationUpdate>) {
+    DeferredMinter::post_upgrade(update);
 }
 
 #[update]
@@ -40,31 +48,64 @@ pub async fn get_eth_address() -> DeferredMinterResult<String> {
 #[update]
 #[candid_method(update)]
 pub async fn create_contract(data: ContractRegistration) -> DeferredMinterResult<ID> {
-    DeferredMinter::create_contract(data).await
+    audit::record(
+        "create_contract",
+        format!(
+            "{:?}, real estate: {:?}, value: {} {}",
+            data.r#type, data.real_estate_id, data.value, data.currency
+        ),
+        DeferredMinter::create_contract(data).await,
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub async fn close_contract(contract_id: ID) -> DeferredMinterResult<()> {
-    DeferredMinter::close_contract(contract_id).await
+    audit::record(
+        "close_contract",
+        format!("{contract_id:?}"),
+        DeferredMinter::close_contract(contract_id).await,
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn anchor_contract_documents(contract_id: ID) -> DeferredMinterResult<DocumentsAnchor> {
+    audit::record(
+        "anchor_contract_documents",
+        format!("{contract_id:?}"),
+        DeferredMinter::anchor_contract_documents(contract_id).await,
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub async fn create_real_estate(real_estate: RealEstate) -> DeferredMinterResult<ID> {
-    DeferredMinter::create_real_estate(real_estate).await
+    audit::record(
+        "create_real_estate",
+        real_estate.audit_summary(),
+        DeferredMinter::create_real_estate(real_estate).await,
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub async fn update_real_estate(id: ID, real_estate: RealEstate) -> DeferredMinterResult<()> {
-    DeferredMinter::update_real_estate(id, real_estate).await
+    audit::record(
+        "update_real_estate",
+        format!("{id:?}, {}", real_estate.audit_summary()),
+        DeferredMinter::update_real_estate(id, real_estate).await,
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub async fn delete_real_estate(id: ID) -> DeferredMinterResult<()> {
-    DeferredMinter::delete_real_estate(id).await
+    audit::record(
+        "delete_real_estate",
+        format!("{id:?}"),
+        DeferredMinter::delete_real_estate(id).await,
+    )
 }
 
 #[query]
@@ -75,62 +116,736 @@ pub fn get_agencies() -> Vec<Agency> {
 
 #[query]
 #[candid_method(query)]
-pub fn get_agency(id: Principal) -> Option<Agency> {
-    DeferredMinter::get_agent(id)
+pub fn get_agency(id: AgencyId) -> Option<Agency> {
+    DeferredMinter::get_agency(id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_member_agency(principal: Principal) -> Option<Agency> {
+    DeferredMinter::get_member_agency(principal)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_register_agency(wallet: Principal, agency: Agency) -> DeferredMinterResult<AgencyId> {
+    let args = format!("{wallet}, {}", agency.audit_summary());
+    audit::record(
+        "admin_register_agency",
+        args,
+        guard::require_permission(Permission::ManageAgencies)
+            .and_then(|()| DeferredMinter::admin_register_agency(wallet, agency)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_history(agency: AgencyId) -> DeferredMinterResult<Vec<AgencyChange>> {
+    DeferredMinter::get_agency_history(agency)
+}
+
+#[update]
+#[candid_method(update)]
+pub fn update_agency(update: AgencyUpdate) -> DeferredMinterResult<()> {
+    audit::record(
+        "update_agency",
+        update.audit_summary(),
+        DeferredMinter::update_agency(update),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn submit_agency_application(
+    agency: Agency,
+    documents: Vec<AgencyApplicationDocument>,
+) -> DeferredMinterResult<u64> {
+    audit::record(
+        "submit_agency_application",
+        format!("{}, {} documents", agency.audit_summary(), documents.len()),
+        DeferredMinter::submit_agency_application(agency, documents),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_application(id: u64) -> DeferredMinterResult<AgencyApplication> {
+    DeferredMinter::get_agency_application(id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_application_document(
+    id: u64,
+    index: u64,
+) -> DeferredMinterResult<AgencyApplicationDocument> {
+    DeferredMinter::get_agency_application_document(id, index)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_agency_applications(
+    status: Option<AgencyApplicationStatus>,
+    pagination: Pagination,
+) -> DeferredMinterResult<Vec<AgencyApplication>> {
+    guard::require_permission(Permission::ManageAgencies)?;
+
+    Ok(DeferredMinter::admin_agency_applications(
+        status, pagination,
+    ))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_comment_agency_application(id: u64, comment: String) -> DeferredMinterResult<()> {
+    audit::record(
+        "admin_comment_agency_application",
+        format!("{id}, {}", audit_text(&comment)),
+        guard::require_permission(Permission::ManageAgencies)
+            .and_then(|()| DeferredMinter::admin_comment_agency_application(id, comment)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_approve_agency_application(
+    id: u64,
+    comment: Option<String>,
+) -> DeferredMinterResult<()> {
+    audit::record(
+        "admin_approve_agency_application",
+        format!(
+            "{id}, {}",
+            comment.as_deref().map(audit_text).unwrap_or_default()
+        ),
+        guard::require_permission(Permission::ManageAgencies)
+            .and_then(|()| DeferredMinter::admin_approve_agency_application(id, comment)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_reject_agency_application(id: u64, comment: String) -> DeferredMinterResult<()> {
+    audit::record(
+        "admin_reject_agency_application",
+        format!("{id}, {}", audit_text(&comment)),
+        guard::require_permission(Permission::ManageAgencies)
+            .and_then(|()| DeferredMinter::admin_reject_agency_application(id, comment)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn remove_agency(agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+    audit::record(
+        "remove_agency",
+        format!("{agency:?}"),
+        DeferredMinter::remove_agency(agency).await,
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn request_agency_transfer(
+    from: AgencyId,
+    to: AgencyId,
+    real_estates: Vec<ID>,
+    contracts: Vec<ID>,
+) -> DeferredMinterResult<u64> {
+    audit::record(
+        "request_agency_transfer",
+        format!(
+            "{from}, {to}, {}, {}",
+            audit_list(&real_estates),
+            audit_list(&contracts)
+        ),
+        DeferredMinter::request_agency_transfer(from, to, real_estates, contracts),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn accept_agency_transfer(id: u64) -> DeferredMinterResult<AgencyTransfer> {
+    audit::record(
+        "accept_agency_transfer",
+        format!("{id:?}"),
+        DeferredMinter::accept_agency_transfer(id),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn reject_agency_transfer(id: u64) -> DeferredMinterResult<()> {
+    audit::record(
+        "reject_agency_transfer",
+        format!("{id:?}"),
+        DeferredMinter::reject_agency_transfer(id),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn admin_approve_agency_transfer(
+    id: u64,
+) -> DeferredMinterResult<AgencyTransferAuditEntry> {
+    audit::record(
+        "admin_approve_agency_transfer",
+        format!("{id:?}"),
+        async {
+            guard::require_permission(Permission::ManageAgencies)?;
+            DeferredMinter::admin_approve_agency_transfer(id).await
+        }
+        .await,
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_transfer(id: u64) -> DeferredMinterResult<AgencyTransfer> {
+    DeferredMinter::get_agency_transfer(id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_transfers(agency: AgencyId) -> DeferredMinterResult<Vec<AgencyTransfer>> {
+    DeferredMinter::get_agency_transfers(agency)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_status(agency: AgencyId) -> AgencyStatus {
+    DeferredMinter::get_agency_status(agency)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn admin_suspend_agency(agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+    audit::record(
+        "admin_suspend_agency",
+        format!("{agency:?}"),
+        async {
+            guard::require_permission(Permission::ManageAgencies)?;
+            DeferredMinter::admin_suspend_agency(agency).await
+        }
+        .await,
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn admin_reactivate_agency(agency: AgencyId) -> DeferredMinterResult<AgencyReport> {
+    audit::record(
+        "admin_reactivate_agency",
+        format!("{agency:?}"),
+        async {
+            guard::require_permission(Permission::ManageAgencies)?;
+            DeferredMinter::admin_reactivate_agency(agency).await
+        }
+        .await,
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn admin_set_agency_quota(
+    agency: AgencyId,
+    quota: Option<AgencyQuota>,
+) -> DeferredMinterResult<()> {
+    audit::record(
+        "admin_set_agency_quota",
+        format!("{agency:?}, {quota:?}"),
+        async {
+            guard::require_permission(Permission::ManageAgencies)?;
+            DeferredMinter::admin_set_agency_quota(agency, quota).await
+        }
+        .await,
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_agency_members(agency: AgencyId) -> DeferredMinterResult<Vec<AgencyMember>> {
+    DeferredMinter::get_agency_members(agency)
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn add_agency_member(
+    agency: AgencyId,
+    member: Principal,
+    role: AgencyRole,
+) -> DeferredMinterResult<()> {
+    audit::record(
+        "add_agency_member",
+        format!("{agency:?}, {member:?}, {role:?}"),
+        DeferredMinter::add_agency_member(agency, member, role).await,
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn remove_agency_member(agency: AgencyId, member: Principal) -> DeferredMinterResult<()> {
+    audit::record(
+        "remove_agency_member",
+        format!("{agency:?}, {member:?}"),
+        DeferredMinter::remove_agency_member(agency, member).await,
+    )
 }
 
 #[update]
 #[candid_method(update)]
-pub fn admin_register_agency(wallet: Principal, agency: Agency) {
-    DeferredMinter::admin_register_agency(wallet, agency)
+pub fn admin_set_role(principal: Principal, role: Role) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_set_role",
+        format!("{principal:?}, {role:?}"),
+        guard::require_permission(Permission::ManageRoles)
+            .and_then(|()| DeferredMinter::admin_set_role(principal, role)),
+    )
 }
 
 #[update]
 #[candid_method(update)]
-pub fn remove_agency(wallet: Principal) -> DeferredMinterResult<()> {
-    DeferredMinter::remove_agency(wallet)
+pub fn admin_remove_role(principal: Principal, role: Role) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_remove_role",
+        format!("{principal:?}, {role:?}"),
+        guard::require_permission(Permission::ManageRoles)
+            .and_then(|()| DeferredMinter::admin_remove_role(principal, role)),
+    )
 }
 
 #[update]
 #[candid_method(update)]
-pub fn admin_set_role(principal: Principal, role: Role) {
-    DeferredMinter::admin_set_role(principal, role)
+pub fn admin_define_role(
+    name: String,
+    permissions: Vec<Permission>,
+) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_define_role",
+        format!("{}, {}", audit_text(&name), audit_list(&permissions)),
+        guard::require_permission(Permission::ManageRoles)
+            .and_then(|()| DeferredMinter::admin_define_role(name, permissions)),
+    )
 }
 
 #[update]
 #[candid_method(update)]
-pub fn admin_remove_role(principal: Principal, role: Role) -> DeferredMinterResult<()> {
-    DeferredMinter::admin_remove_role(principal, role)
+pub fn admin_delete_role(name: String) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_delete_role",
+        audit_text(&name),
+        guard::require_permission(Permission::ManageRoles)
+            .and_then(|()| DeferredMinter::admin_delete_role(name)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_roles() -> Vec<RoleDefinition> {
+    DeferredMinter::get_roles()
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_permissions(principal: Principal) -> Vec<Permission> {
+    DeferredMinter::get_permissions(principal)
 }
 
 #[update]
 #[candid_method(update)]
-pub fn admin_set_allowed_currencies(currencies: Vec<String>) {
-    DeferredMinter::admin_set_allowed_currencies(currencies)
+pub fn admin_set_allowed_currencies(currencies: Vec<String>) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_set_allowed_currencies",
+        audit_list(&currencies),
+        guard::require_permission(Permission::ManageCurrencies)
+            .and_then(|()| DeferredMinter::admin_set_allowed_currencies(currencies)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_configuration() -> DeferredMinterResult<DeferredMinterConfiguration> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::get_configuration())
 }
 
 #[update]
 #[candid_method(update)]
-pub fn admin_set_custodians(custodians: Vec<Principal>) -> DeferredMinterResult<()> {
-    DeferredMinter::admin_set_custodians(custodians)
+pub fn admin_update_configuration(update: ConfigurationUpdate) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_update_configuration",
+        update.audit_summary(),
+        guard::require_permission(Permission::ManageConfig)
+            .and_then(|()| DeferredMinter::admin_update_configuration(update)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_configuration_history(
+    pagination: Pagination,
+) -> DeferredMinterResult<Vec<ConfigurationChange>> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_configuration_history(pagination))
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_pause(
+    operation: PausableOperation,
+    reason: String,
+) -> DeferredMinterResult<PauseState> {
+    audit::record(
+        "admin_pause",
+        format!("{operation:?}, {}", audit_text(&reason)),
+        DeferredMinter::admin_pause(operation, reason),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_reward_parameters() -> RewardParameters {
+    DeferredMinter::get_reward_parameters()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_reward_parameters(parameters: RewardParameters) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_set_reward_parameters",
+        format!("{parameters:?}"),
+        guard::require_permission(Permission::ManageConfig)
+            .and_then(|()| DeferredMinter::admin_set_reward_parameters(parameters)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_resume(operation: PausableOperation) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_resume",
+        format!("{operation:?}"),
+        guard::require_permission(Permission::ManageConfig)
+            .and_then(|()| DeferredMinter::admin_resume(operation)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_paused_operations() -> Vec<PauseState> {
+    DeferredMinter::get_paused_operations()
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_set_custodians(custodians: Vec<Principal>) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_set_custodians",
+        audit_list(&custodians),
+        guard::require_permission(Permission::ManageRoles)
+            .and_then(|()| DeferredMinter::admin_set_custodians(custodians)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_propose(action: ProposalAction) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_propose",
+        action.audit_summary(),
+        guard::require_permission(action.permission())
+            .and_then(|()| DeferredMinter::admin_propose(action)),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_approve_proposal(id: u64) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_approve_proposal",
+        format!("{id:?}"),
+        DeferredMinter::admin_approve_proposal(id),
+    )
+}
+
+#[update]
+#[candid_method(update)]
+pub fn admin_reject_proposal(id: u64) -> DeferredMinterResult<Proposal> {
+    audit::record(
+        "admin_reject_proposal",
+        format!("{id:?}"),
+        DeferredMinter::admin_reject_proposal(id),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_proposal(id: u64) -> Option<Proposal> {
+    DeferredMinter::get_proposal(id)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_proposals(status: Option<ProposalStatus>, pagination: Pagination) -> Vec<Proposal> {
+    DeferredMinter::get_proposals(status, pagination)
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_proposal_config() -> ProposalConfig {
+    DeferredMinter::get_proposal_config()
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_cycles() -> DeferredMinterResult<Nat> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_cycles())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_ic_logs(pagination: Pagination) -> DeferredMinterResult<Logs> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_ic_logs(pagination))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_audit_log(
+    filter: AuditFilter,
+    pagination: Pagination,
+) -> DeferredMinterResult<Vec<AuditEntry>> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_audit_log(filter, pagination))
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_verify_audit_log(pagination: Pagination) -> DeferredMinterResult<AuditIntegrity> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_verify_audit_log(pagination))
 }
 
 #[query]
 #[candid_method(query)]
-pub fn admin_cycles() -> Nat {
-    DeferredMinter::admin_cycles()
+pub fn admin_migration_status() -> DeferredMinterResult<MigrationStatus> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_migration_status())
 }
 
 #[query]
 #[candid_method(query)]
-pub fn admin_ic_logs(pagination: Pagination) -> Logs {
-    DeferredMinter::admin_ic_logs(pagination)
+pub fn admin_reward_pool_status() -> DeferredMinterResult<RewardPoolStatus> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_reward_pool_status())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn admin_reward_pool_alerts() -> DeferredMinterResult<Vec<RewardPoolAlert>> {
+    guard::require_permission(Permission::ReadLogs)?;
+
+    Ok(DeferredMinter::admin_reward_pool_alerts())
+}
+
+#[update]
+#[candid_method(update)]
+pub async fn admin_sync_reward_pool() -> DeferredMinterResult<()> {
+    audit::record(
+        "admin_sync_reward_pool",
+        String::new(),
+        async {
+            guard::require_permission(Permission::ManageConfig)?;
+            DeferredMinter::admin_sync_reward_pool().await
+        }
+        .await,
+    )
 }
 
 #[update]
 #[candid_method(update)]
 pub fn gas_station_set_gas_price(gas_price: u64) -> DeferredMinterResult<()> {
-    DeferredMinter::gas_station_set_gas_price(gas_price)
+    audit::record(
+        "gas_station_set_gas_price",
+        format!("{gas_price:?}"),
+        guard::require_permission(Permission::SetGas)
+            .and_then(|()| DeferredMinter::gas_station_set_gas_price(gas_price)),
+    )
+}
+
+#[query]
+#[candid_method(query)]
+pub fn get_sns_governance() -> Option<Principal> {
+    DeferredMinter::get_sns_governance()
+}
+
+// SNS generic function validators
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_register_agency(wallet: Principal, agency: Agency) -> Result<String, String> {
+    SnsValidator::admin_register_agency(wallet, &agency).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_suspend_agency(agency: AgencyId) -> Result<String, String> {
+    SnsValidator::admin_suspend_agency(agency).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_reactivate_agency(agency: AgencyId) -> Result<String, String> {
+    SnsValidator::admin_reactivate_agency(agency).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_agency_quota(
+    agency: AgencyId,
+    quota: Option<AgencyQuota>,
+) -> Result<String, String> {
+    SnsValidator::admin_set_agency_quota(agency, quota).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_approve_agency_transfer(id: u64) -> Result<String, String> {
+    SnsValidator::admin_approve_agency_transfer(id).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_comment_agency_application(
+    id: u64,
+    comment: String,
+) -> Result<String, String> {
+    SnsValidator::admin_comment_agency_application(id, &comment).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_approve_agency_application(
+    id: u64,
+    comment: Option<String>,
+) -> Result<String, String> {
+    SnsValidator::admin_approve_agency_application(id, comment.as_deref())
+        .map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_reject_agency_application(
+    id: u64,
+    comment: String,
+) -> Result<String, String> {
+    SnsValidator::admin_reject_agency_application(id, &comment).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_role(principal: Principal, role: Role) -> Result<String, String> {
+    SnsValidator::admin_set_role(principal, role).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_remove_role(principal: Principal, role: Role) -> Result<String, String> {
+    SnsValidator::admin_remove_role(principal, role).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_define_role(
+    name: String,
+    permissions: Vec<Permission>,
+) -> Result<String, String> {
+    SnsValidator::admin_define_role(name, permissions).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_delete_role(name: String) -> Result<String, String> {
+    SnsValidator::admin_delete_role(name).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_allowed_currencies(currencies: Vec<String>) -> Result<String, String> {
+    SnsValidator::admin_set_allowed_currencies(currencies).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_custodians(custodians: Vec<Principal>) -> Result<String, String> {
+    SnsValidator::admin_set_custodians(custodians).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_update_configuration(update: ConfigurationUpdate) -> Result<String, String> {
+    SnsValidator::admin_update_configuration(update).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_pause(
+    operation: PausableOperation,
+    reason: String,
+) -> Result<String, String> {
+    SnsValidator::admin_pause(operation, &reason).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_set_reward_parameters(
+    parameters: RewardParameters,
+) -> Result<String, String> {
+    SnsValidator::admin_set_reward_parameters(parameters).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_resume(operation: PausableOperation) -> Result<String, String> {
+    SnsValidator::admin_resume(operation).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_propose(action: ProposalAction) -> Result<String, String> {
+    SnsValidator::admin_propose(action).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_approve_proposal(id: u64) -> Result<String, String> {
+    SnsValidator::admin_approve_proposal(id).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_reject_proposal(id: u64) -> Result<String, String> {
+    SnsValidator::admin_reject_proposal(id).map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_admin_sync_reward_pool() -> Result<String, String> {
+    SnsValidator::admin_sync_reward_pool().map_err(|err| err.to_string())
+}
+
+#[query]
+#[candid_method(query)]
+pub fn validate_gas_station_set_gas_price(gas_price: u64) -> Result<String, String> {
+    SnsValidator::gas_station_set_gas_price(gas_price).map_err(|err| err.to_string())
 }
 
 // HTTP endpoint
@@ -160,3 +875,325 @@ pub fn getrandom_always_fail(_buf: &mut [u8]) -> Result<(), getrandom::Error> {
 }
 
 getrandom::register_custom_getrandom!(getrandom_always_fail);
+
+#[cfg(test)]
+mod test {
+
+    use did::deferred::{
+        AuditResult, DeferredMinterError, EcdsaKey, ProposalError, DEFAULT_PROPOSAL_THRESHOLD,
+    };
+    use did::H160;
+    use ic_log::LogSettingsV2;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::app::test_utils::{alice, bob};
+
+    fn missing_permission(permission: Permission) -> DeferredMinterError {
+        DeferredMinterError::MissingPermission(permission)
+    }
+
+    fn pagination() -> Pagination {
+        Pagination {
+            offset: 0,
+            count: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agency_endpoints_should_require_manage_agencies() {
+        let expected = Err(missing_permission(Permission::ManageAgencies));
+
+        assert_eq!(admin_register_agency(bob(), Agency::default()), expected);
+        assert_eq!(
+            admin_agency_applications(None, pagination()).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_comment_agency_application(1, "comment".to_string()),
+            expected
+        );
+        assert_eq!(admin_approve_agency_application(1, None), expected);
+        assert_eq!(
+            admin_reject_agency_application(1, "comment".to_string()),
+            expected
+        );
+        assert_eq!(
+            admin_suspend_agency(AgencyId(1)).await.map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_reactivate_agency(AgencyId(1)).await.map(|_| ()),
+            expected
+        );
+        assert_eq!(admin_set_agency_quota(AgencyId(1), None).await, expected);
+        assert_eq!(admin_approve_agency_transfer(1).await.map(|_| ()), expected);
+    }
+
+    #[test]
+    fn test_role_endpoints_should_require_manage_roles() {
+        let expected = Err(missing_permission(Permission::ManageRoles));
+
+        assert_eq!(
+            admin_set_role(bob(), Role::GasStation).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_remove_role(bob(), Role::GasStation).map(|_| ()),
+            expected
+        );
+        assert_eq!(admin_set_custodians(vec![bob()]).map(|_| ()), expected);
+        assert_eq!(
+            admin_propose(ProposalAction::SetCustodians(vec![bob()])).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_define_role("auditor".to_string(), vec![Permission::ReadLogs]).map(|_| ()),
+            expected
+        );
+        assert_eq!(
+            admin_delete_role("auditor".to_string()).map(|_| ()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_should_require_manage_currencies_to_set_allowed_currencies() {
+        assert_eq!(
+            admin_set_allowed_currencies(vec!["EUR".to_string()]).map(|_| ()),
+            Err(missing_permission(Permission::ManageCurrencies))
+        );
+    }
+
+    #[test]
+    fn test_monitoring_endpoints_should_require_read_logs() {
+        let expected = Err(missing_permission(Permission::ReadLogs));
+
+        assert_eq!(admin_cycles().map(|_| ()), expected);
+        assert_eq!(admin_ic_logs(pagination()).map(|_| ()), expected);
+        assert_eq!(
+            admin_audit_log(AuditFilter::default(), pagination()).map(|_| ()),
+            expected
+        );
+        assert_eq!(admin_verify_audit_log(pagination()).map(|_| ()), expected);
+        assert_eq!(admin_migration_status().map(|_| ()), expected);
+        assert_eq!(admin_reward_pool_status().map(|_| ()), expected);
+        assert_eq!(admin_reward_pool_alerts().map(|_| ()), expected);
+        assert_eq!(get_configuration().map(|_| ()), expected);
+        assert_eq!(
+            admin_configuration_history(pagination()).map(|_| ()),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_require_manage_config_to_sync_reward_pool() {
+        assert_eq!(
+            admin_sync_reward_pool().await,
+            Err(missing_permission(Permission::ManageConfig))
+        );
+    }
+
+    #[test]
+    fn test_should_require_manage_config_to_propose_config_change() {
+        assert_eq!(
+            admin_propose(ProposalAction::SetProposalConfig(ProposalConfig::default())).map(|_| ()),
+            Err(missing_permission(Permission::ManageConfig))
+        );
+        assert_eq!(
+            admin_update_configuration(ConfigurationUpdate::default()).map(|_| ()),
+            Err(missing_permission(Permission::ManageConfig))
+        );
+    }
+
+    #[test]
+    fn test_should_require_custodian_to_pause_and_manage_config_to_resume() {
+        assert_eq!(
+            admin_pause(PausableOperation::ContractCreation, "bug".to_string()).map(|_| ()),
+            Err(ProposalError::NotACustodian(crate::utils::caller()).into())
+        );
+        assert_eq!(
+            admin_resume(PausableOperation::ContractCreation).map(|_| ()),
+            Err(missing_permission(Permission::ManageConfig))
+        );
+    }
+
+    #[test]
+    fn test_should_require_manage_config_to_set_reward_parameters() {
+        assert_eq!(
+            admin_set_reward_parameters(get_reward_parameters()).map(|_| ()),
+            Err(missing_permission(Permission::ManageConfig))
+        );
+    }
+
+    #[test]
+    fn test_should_require_set_gas_to_set_gas_price() {
+        assert_eq!(
+            gas_station_set_gas_price(10_000_000_000),
+            Err(missing_permission(Permission::SetGas))
+        );
+    }
+
+    #[test]
+    fn test_should_dispatch_call_if_permission_is_granted() {
+        init_canister();
+
+        assert!(admin_cycles().is_ok());
+        assert!(admin_migration_status().unwrap().is_completed());
+        assert!(admin_set_allowed_currencies(vec!["EUR".to_string()]).is_ok());
+
+        // lose every permission but the gas station ones
+        let caller = crate::utils::caller();
+        assert!(admin_set_custodians(vec![caller, alice()]).is_ok());
+        assert!(admin_set_role(caller, Role::GasStation).is_ok());
+        assert!(admin_propose(ProposalAction::RemoveRole {
+            principal: caller,
+            role: Role::Custodian,
+        })
+        .is_ok());
+        assert!(gas_station_set_gas_price(10_000_000_000).is_ok());
+        assert_eq!(
+            admin_cycles(),
+            Err(missing_permission(Permission::ReadLogs))
+        );
+    }
+
+    #[test]
+    fn test_should_recognize_sns_governance_as_privileged_caller() {
+        init_canister();
+        let caller = crate::utils::caller();
+
+        assert!(admin_propose(ProposalAction::SetSnsGovernance(Some(caller))).is_ok());
+        assert_eq!(get_sns_governance(), Some(caller));
+
+        // the proposals of the governance are executed without the custodians approval
+        assert!(admin_set_custodians(vec![alice(), bob()]).is_ok());
+        assert_eq!(get_proposal_config().threshold, DEFAULT_PROPOSAL_THRESHOLD);
+        let proposal = admin_set_allowed_currencies(vec!["EUR".to_string()]).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Executed);
+        assert!(admin_cycles().is_ok());
+        assert_eq!(get_permissions(caller), Permission::all());
+    }
+
+    #[test]
+    fn test_should_record_state_changing_calls_in_audit_log() {
+        init_canister();
+        let caller = crate::utils::caller();
+
+        assert!(admin_set_allowed_currencies(vec!["EUR".to_string()]).is_ok());
+        assert!(admin_approve_proposal(100).is_err());
+        // queries are not recorded
+        assert!(get_proposal_config().threshold > 0);
+
+        let entries = admin_audit_log(AuditFilter::default(), pagination()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].caller, caller);
+        assert_eq!(entries[0].method, "admin_set_allowed_currencies");
+        assert_eq!(entries[0].args, "[\"EUR\"]");
+        assert_eq!(entries[0].result, AuditResult::Ok);
+        assert_eq!(entries[1].method, "admin_approve_proposal");
+        assert_eq!(
+            entries[1].result,
+            AuditResult::Err("proposal error: proposal 100 not found".to_string())
+        );
+
+        let filter = AuditFilter {
+            method: Some("admin_approve_proposal".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(admin_audit_log(filter, pagination()).unwrap().len(), 1);
+
+        let integrity = admin_verify_audit_log(pagination()).unwrap();
+        assert_eq!(integrity.entries, 2);
+        assert_eq!(integrity.first_invalid_entry, None);
+        assert_eq!(integrity.head, entries[1].hash);
+    }
+
+    #[test]
+    fn test_should_record_denied_calls_in_audit_log() {
+        // the caller has no permission before the canister is initialized
+        assert_eq!(
+            admin_set_allowed_currencies(vec!["EUR".to_string()]).map(|_| ()),
+            Err(missing_permission(Permission::ManageCurrencies))
+        );
+        init_canister();
+
+        let entries = admin_audit_log(AuditFilter::default(), pagination()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].caller, crate::utils::caller());
+        assert_eq!(entries[0].method, "admin_set_allowed_currencies");
+        assert_eq!(
+            entries[0].result,
+            AuditResult::Err("missing permission: ManageCurrencies".to_string())
+        );
+    }
+
+    #[test]
+    fn test_should_validate_sns_proposals() {
+        init_canister();
+
+        assert_eq!(
+            validate_admin_set_allowed_currencies(vec!["EUR".to_string()]),
+            Ok("Set allowed currencies to [\"EUR\"]".to_string())
+        );
+        assert_eq!(
+            validate_admin_set_custodians(vec![]),
+            Err("configuration error: there must be at least one custodial".to_string())
+        );
+        assert_eq!(
+            validate_gas_station_set_gas_price(10_000_000_000),
+            Ok("Set gas price to 10000000000 wei".to_string())
+        );
+        assert!(validate_admin_sync_reward_pool().is_ok());
+        assert!(validate_admin_approve_proposal(1).is_err());
+        assert_eq!(
+            validate_admin_update_configuration(ConfigurationUpdate {
+                chain_id: Some(137),
+                ..Default::default()
+            }),
+            Err("configuration error: unsupported chain id 137".to_string())
+        );
+        assert!(validate_admin_update_configuration(ConfigurationUpdate {
+            chain_id: Some(11155111),
+            ..Default::default()
+        })
+        .is_ok());
+        assert_eq!(
+            validate_admin_pause(PausableOperation::EvmTransactions, "bug".to_string()),
+            Ok("Pause EVM transactions until resumed by a proposal. Reason: bug".to_string())
+        );
+        assert_eq!(
+            validate_admin_pause(PausableOperation::EvmTransactions, String::new()),
+            Err("proposal error: invalid proposal: the pause reason is empty".to_string())
+        );
+        assert_eq!(
+            validate_admin_resume(PausableOperation::EvmTransactions),
+            Err(
+                "proposal error: invalid proposal: operation EvmTransactions is not paused"
+                    .to_string()
+            )
+        );
+    }
+
+    fn init_canister() {
+        init(DeferredMinterInitData {
+            allowed_currencies: vec!["USD".to_string()],
+            chain_id: 1,
+            custodians: vec![crate::utils::caller()],
+            deferred_data: alice(),
+            deferred_erc721: H160::from_hex_str("0xe57e761aa806c9afe7e06fb0601b17bec310f9c4")
+                .unwrap(),
+            ecdsa_key: EcdsaKey::Dfx,
+            evm_rpc: bob(),
+            evm_rpc_api: None,
+            reward_pool: H160::from_hex_str("0x7f4e8e4b4dabf7f5f6e7e7d3f9f5a6e7f6e7f6e7").unwrap(),
+            reward_pool_deploy_block: 20_998_000,
+            log_settings: LogSettingsV2 {
+                enable_console: true,
+                log_filter: "debug".to_string(),
+                in_memory_records: 128,
+                max_record_length: 1000,
+            },
+        });
+    }
+}
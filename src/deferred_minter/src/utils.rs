@@ -1,18 +1,31 @@
+use std::cell::Cell;
+
 use candid::{Nat, Principal};
 use time::{Date, OffsetDateTime};
 
+thread_local! {
+    /// Offset added to the system time by the tests to move the clock forward (nanoseconds)
+    static TEST_TIME_OFFSET: Cell<u64> = const { Cell::new(0) };
+}
+
 /// Returns current time in nanoseconds
 pub fn time() -> u64 {
     if cfg!(test) {
         let time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap();
-        return time.as_nanos() as u64;
+        return time.as_nanos() as u64 + TEST_TIME_OFFSET.with(Cell::get);
     }
 
     ic_cdk::api::time()
 }
 
+/// Move the clock returned by [`time`] forward in the tests
+#[cfg(test)]
+pub fn advance_time(nanos: u64) {
+    TEST_TIME_OFFSET.with(|offset| offset.set(offset.get() + nanos));
+}
+
 pub fn cycles() -> Nat {
     if cfg!(test) {
         return Nat::from(30_000_000_000_u64);
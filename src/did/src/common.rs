@@ -6,12 +6,17 @@ mod h160;
 mod http;
 mod id;
 mod log_settings;
+mod metrics;
 mod nat;
 mod principal;
 
 pub use h160::H160;
-pub use http::{HttpRequest, HttpResponse};
+pub use http::{
+    HttpRequest, HttpResponse, StreamingCallback, StreamingCallbackHttpResponse,
+    StreamingCallbackToken, StreamingStrategy,
+};
 pub use id::ID;
 pub use log_settings::StorableLogSettings;
+pub use metrics::{MetricsEncoder, METRICS_CONTENT_TYPE};
 pub use nat::StorableNat;
 pub use principal::StorablePrincipal;
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
-use candid::CandidType;
+use candid::{define_function, CandidType};
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
 
@@ -22,6 +22,8 @@ pub struct HttpResponse {
     pub body: ByteBuf,
     /// Whether the query call should be upgraded to an update call.
     pub upgrade: Option<bool>,
+    /// The strategy to stream the rest of the body, when it doesn't fit in a single response.
+    pub streaming_strategy: Option<StreamingStrategy>,
 }
 
 impl HttpResponse {
@@ -51,6 +53,7 @@ impl HttpResponse {
             headers,
             body,
             upgrade,
+            streaming_strategy: None,
         }
     }
 
@@ -66,6 +69,7 @@ impl HttpResponse {
             headers: HashMap::from([("content-type".into(), "application/json".into())]),
             body,
             upgrade: None,
+            streaming_strategy: None,
         }
     }
 
@@ -81,6 +85,7 @@ impl HttpResponse {
             headers: HashMap::from([("content-type".into(), "application/json".into())]),
             body,
             upgrade: None,
+            streaming_strategy: None,
         }
     }
 
@@ -91,6 +96,7 @@ impl HttpResponse {
             headers: HashMap::from([("content-type".into(), "application/json".into())]),
             body: ByteBuf::from("Not Found".as_bytes()),
             upgrade: None,
+            streaming_strategy: None,
         }
     }
 
@@ -111,6 +117,16 @@ impl HttpResponse {
         )
     }
 
+    /// Returns an OK response with the given body as it is, with the provided content type.
+    pub fn ok_raw(body: Vec<u8>, content_type: &'static str) -> Self {
+        Self::new(
+            HTTP_OK,
+            HashMap::from([("content-type".into(), content_type.into())]),
+            ByteBuf::from(body),
+            None,
+        )
+    }
+
     /// Upgrade response to update call.
     pub fn upgrade_response() -> Self {
         Self::new(
@@ -122,6 +138,39 @@ impl HttpResponse {
     }
 }
 
+// The query called by the HTTP gateway to get the next chunk of a streamed body.
+define_function!(pub StreamingCallback : (StreamingCallbackToken) -> (StreamingCallbackHttpResponse) query);
+
+/// The strategy to stream a HTTP response body in multiple chunks.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum StreamingStrategy {
+    /// The HTTP gateway calls the callback with the token to get the next chunk.
+    Callback {
+        callback: StreamingCallback,
+        token: StreamingCallbackToken,
+    },
+}
+
+/// The token passed to the streaming callback to get the next chunk of a body.
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct StreamingCallbackToken {
+    /// The URL of the streamed resource, with its query string.
+    pub url: String,
+    /// The offset of the next chunk in the resource.
+    pub offset: u64,
+    /// The offset where the streamed body ends (exclusive).
+    pub end: u64,
+}
+
+/// The response of the streaming callback.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct StreamingCallbackHttpResponse {
+    /// The chunk of the body.
+    pub body: ByteBuf,
+    /// The token to get the next chunk; `None` if this is the last one.
+    pub token: Option<StreamingCallbackToken>,
+}
+
 /// The important components of an HTTP request.
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct HttpRequest {
@@ -0,0 +1,139 @@
+use std::fmt::Write as _;
+
+/// Content type of the Prometheus text exposition format
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Encoder of metrics in the Prometheus text exposition format
+#[derive(Debug, Default)]
+pub struct MetricsEncoder {
+    buf: String,
+}
+
+impl MetricsEncoder {
+    /// Encode a gauge, which is a value that can go up and down
+    pub fn gauge(&mut self, name: &str, help: &str, value: f64) -> &mut Self {
+        self.header(name, help, "gauge");
+        self.sample(name, None, value);
+
+        self
+    }
+
+    /// Encode a counter, which is a value that can only go up (or be reset)
+    pub fn counter(&mut self, name: &str, help: &str, value: f64) -> &mut Self {
+        self.header(name, help, "counter");
+        self.sample(name, None, value);
+
+        self
+    }
+
+    /// Encode a gauge with a sample for each value of `label`
+    pub fn gauge_vec<L>(
+        &mut self,
+        name: &str,
+        help: &str,
+        label: &str,
+        samples: impl IntoIterator<Item = (L, f64)>,
+    ) -> &mut Self
+    where
+        L: AsRef<str>,
+    {
+        self.header(name, help, "gauge");
+        for (label_value, value) in samples {
+            self.sample(name, Some((label, label_value.as_ref())), value);
+        }
+
+        self
+    }
+
+    /// Encode a counter with a sample for each value of `label`
+    pub fn counter_vec<L>(
+        &mut self,
+        name: &str,
+        help: &str,
+        label: &str,
+        samples: impl IntoIterator<Item = (L, f64)>,
+    ) -> &mut Self
+    where
+        L: AsRef<str>,
+    {
+        self.header(name, help, "counter");
+        for (label_value, value) in samples {
+            self.sample(name, Some((label, label_value.as_ref())), value);
+        }
+
+        self
+    }
+
+    /// Returns the encoded metrics
+    pub fn finish(self) -> String {
+        self.buf
+    }
+
+    fn header(&mut self, name: &str, help: &str, metric_type: &str) {
+        let _ = writeln!(self.buf, "# HELP {name} {help}");
+        let _ = writeln!(self.buf, "# TYPE {name} {metric_type}");
+    }
+
+    fn sample(&mut self, name: &str, label: Option<(&str, &str)>, value: f64) {
+        match label {
+            Some((label, label_value)) => {
+                let label_value = label_value
+                    .replace('\\', r"\\")
+                    .replace('"', r#"\""#)
+                    .replace('\n', r"\n");
+                let _ = writeln!(self.buf, "{name}{{{label}=\"{label_value}\"}} {value}");
+            }
+            None => {
+                let _ = writeln!(self.buf, "{name} {value}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_metrics() {
+        let mut encoder = MetricsEncoder::default();
+        encoder
+            .gauge("cycles", "Cycles balance", 30_000_000_000.0)
+            .counter("calls_total", "Calls", 2.0)
+            .counter_vec(
+                "rpc_calls_total",
+                "RPC calls",
+                "method",
+                [("eth_call", 3.0), ("eth_\"get\"Logs", 1.0)],
+            )
+            .gauge_vec::<&str>("empty", "No samples", "label", []);
+
+        assert_eq!(
+            encoder.finish(),
+            r#"# HELP cycles Cycles balance
+# TYPE cycles gauge
+cycles 30000000000
+# HELP calls_total Calls
+# TYPE calls_total counter
+calls_total 2
+# HELP rpc_calls_total RPC calls
+# TYPE rpc_calls_total counter
+rpc_calls_total{method="eth_call"} 3
+rpc_calls_total{method="eth_\"get\"Logs"} 1
+# HELP empty No samples
+# TYPE empty gauge
+"#
+        );
+    }
+
+    #[test]
+    fn test_should_encode_fractional_values() {
+        let mut encoder = MetricsEncoder::default();
+        encoder.gauge("rmc", "Reward multiplier coefficient", 0.0000042);
+
+        assert!(encoder.finish().ends_with("rmc 0.0000042\n"));
+    }
+}
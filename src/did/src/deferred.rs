@@ -1,26 +1,49 @@
 //! Types associated to the "Deferred" canister
 
 mod agency;
+mod audit;
 mod contract;
 mod data;
+mod migration;
 mod minter;
 mod real_estate;
 
 pub type DeferredMinterResult<T> = Result<T, DeferredMinterError>;
 pub type DeferredDataResult<T> = Result<T, DeferredDataError>;
 
-pub use self::agency::{Agency, AgencyId, Continent};
+pub use self::agency::{
+    Agency, AgencyApplication, AgencyApplicationComment, AgencyApplicationDocument,
+    AgencyApplicationDocumentInfo, AgencyApplicationStatus, AgencyChange, AgencyFieldChange,
+    AgencyHistory, AgencyId, AgencyMember, AgencyReport, AgencyRole, AgencyStatus, AgencyTransfer,
+    AgencyTransferAuditEntry, AgencyTransferStatus, AgencyUpdate, Continent,
+};
+pub use self::audit::{
+    audit_list, audit_text, AuditEntry, AuditFilter, AuditIntegrity, AuditResult, AuditSummary,
+    StableAuditLog, AUDIT_GENESIS_HASH, AUDIT_MAX_ARGS_LEN, AUDIT_MAX_ENTRIES,
+    AUDIT_MAX_LIST_ITEMS, AUDIT_MAX_TEXT_LEN,
+};
 pub use self::contract::{
     Contract, ContractDocument, ContractDocumentData, ContractDocuments, ContractProperties,
-    ContractRegistration, ContractType, GenericValue, RestrictedContractProperties,
+    ContractRegistration, ContractType, ContractV1, GenericValue, RestrictedContractProperties,
     RestrictedProperty, RestrictionLevel, Seller, ID,
 };
 pub use self::data::{
-    ConfigurationError as DataConfigurationError, ContractError as DataContractError,
-    DeferredDataError, DeferredDataInitData, RealEstateError,
+    AgencyQuota, AgencyUsage, CollectionDigest, ConfigurationError as DataConfigurationError,
+    ContractError as DataContractError, DeferredDataError, DeferredDataInitData, DocumentHistory,
+    DocumentImage, DocumentLeaf, DocumentPreviews, DocumentProof, DocumentUpload, DocumentVersion,
+    DocumentsAnchor, DocumentsMerkleTree, ExportChunk, ExportCollection, ExportCursor,
+    ExportRecord, ImageSize, ImportError, Permission as DataPermission, ReadOnlyMode,
+    RealEstateError, SignedMessageError, StorableDomains, StorableMimeTypes, StorableReadOnlyMode,
+    StorageUsage, StreamingSession, DEFAULT_ALLOWED_MIME_TYPES,
 };
+pub use self::migration::{CollectionSchema, MigrationProgress, MigrationStatus};
 pub use self::minter::{
-    CloseContractError, ConfigurationError, ContractError, DeferredMinterError,
-    DeferredMinterInitData, EcdsaError, EcdsaKey, Role, Roles,
+    AgencyError, CloseContractError, ConfigurationChange, ConfigurationError, ConfigurationUpdate,
+    ContractError, DeferredMinterConfiguration, DeferredMinterError, DeferredMinterInitData,
+    EcdsaError, EcdsaKey, PausableOperation, PauseState, Permission, Permissions, Proposal,
+    ProposalAction, ProposalConfig, ProposalError, ProposalStatus, RewardParameters,
+    RewardPoolAlert, RewardPoolAlertKind, RewardPoolStatus, RewardReservation,
+    RewardReservationStatus, Role, RoleDefinition, Roles, DEFAULT_PROPOSAL_THRESHOLD,
+    DEFAULT_PROPOSAL_TIMEOUT, MAX_PAUSE_REASON_LEN,
 };
-pub use self::real_estate::RealEstate;
+pub use self::real_estate::{RealEstate, RealEstateV1};
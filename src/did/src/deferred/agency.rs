@@ -1,17 +1,171 @@
+mod application;
+mod transfer;
+
 use std::fmt;
 use std::str::FromStr;
 
 use candid::{CandidType, Decode, Encode, Principal};
+use ethers_core::utils::keccak256;
 use ic_stable_structures::storable::Bound;
 use ic_stable_structures::Storable;
 use serde::{Deserialize, Serialize};
 
-/// Unique identifier for an agency
-pub type AgencyId = Principal;
+pub use self::application::{
+    AgencyApplication, AgencyApplicationComment, AgencyApplicationDocument,
+    AgencyApplicationDocumentInfo, AgencyApplicationStatus,
+};
+pub use self::transfer::{AgencyTransfer, AgencyTransferAuditEntry, AgencyTransferStatus};
+use crate::ID;
+
+/// Unique identifier for an agency.
+///
+/// It's assigned by the minter when the agency is registered, so it's not the principal of any
+/// wallet: the principals operating on behalf of the agency are its [`AgencyMember`]s. It doesn't
+/// change when the agency members change.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    CandidType,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub struct AgencyId(pub u64);
+
+/// Bit set in the ids of the agencies registered before the introduction of the agency ids, which
+/// the sequence numbers assigned by the minter never reach
+const LEGACY_AGENCY_ID_BIT: u64 = 1 << 63;
+
+impl AgencyId {
+    /// Returns the id of an agency registered before the introduction of the agency ids, when the
+    /// agency was identified by the principal of its wallet
+    pub fn legacy(wallet: Principal) -> Self {
+        let hash = keccak256(wallet.as_slice());
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&hash[..8]);
+
+        Self(u64::from_be_bytes(bytes) | LEGACY_AGENCY_ID_BIT)
+    }
+}
+
+impl From<u64> for AgencyId {
+    fn from(seq: u64) -> Self {
+        Self(seq)
+    }
+}
+
+impl fmt::Display for AgencyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for AgencyId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl Storable for AgencyId {
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 8,
+        is_fixed_size: true,
+    };
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        self.0.to_be_bytes().to_vec().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        let mut id = [0; 8];
+        id.copy_from_slice(&bytes);
+
+        Self(u64::from_be_bytes(id))
+    }
+}
+
+/// Role of a member within an agency
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AgencyRole {
+    /// Can manage the agency members, contracts and real estates
+    Admin,
+    /// Can manage the agency contracts and real estates
+    Agent,
+    /// Can only read the agency data
+    ReadOnly,
+}
+
+impl AgencyRole {
+    /// Returns whether the role allows to create and modify contracts and real estates
+    pub fn can_write(&self) -> bool {
+        matches!(self, Self::Admin | Self::Agent)
+    }
+}
+
+/// A principal operating on behalf of an agency
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyMember {
+    pub agency: AgencyId,
+    pub principal: Principal,
+    pub role: AgencyRole,
+}
+
+impl Storable for AgencyMember {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Status of an agency
+#[derive(Clone, Copy, Debug, Default, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AgencyStatus {
+    #[default]
+    Active,
+    /// The agency can't create new contracts and real estates and its real estates are not listed
+    Suspended,
+}
+
+impl Storable for AgencyStatus {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Records of an agency affected by a change of its status or by its removal
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyReport {
+    pub agency: AgencyId,
+    /// Real estates of the agency which haven't been deleted
+    pub real_estates: Vec<ID>,
+    /// Contracts of the agency which haven't been closed
+    pub open_contracts: Vec<ID>,
+}
 
 /// A sell contract for a building
 #[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
 pub struct Agency {
+    /// Set by the minter when the agency is registered
+    pub id: AgencyId,
     pub address: String,
     pub agent: String,
     pub city: String,
@@ -49,10 +203,212 @@ impl Default for Agency {
             name: Default::default(),
             owner: Principal::anonymous(),
             continent: Continent::Europe,
+            id: AgencyId::default(),
+        }
+    }
+}
+
+/// An [`Agency`] stored before the introduction of the agency ids, when it was identified by the
+/// wallet of its owner
+#[derive(Clone, Debug, CandidType, Deserialize)]
+struct AgencyV1 {
+    address: String,
+    agent: String,
+    city: String,
+    continent: Continent,
+    country: String,
+    email: String,
+    lat: Option<String>,
+    lng: Option<String>,
+    logo: Option<String>,
+    mobile: String,
+    name: String,
+    owner: Principal,
+    region: String,
+    vat: String,
+    website: String,
+    zip_code: String,
+}
+
+impl From<AgencyV1> for Agency {
+    fn from(agency: AgencyV1) -> Self {
+        Self {
+            id: AgencyId::legacy(agency.owner),
+            address: agency.address,
+            agent: agency.agent,
+            city: agency.city,
+            continent: agency.continent,
+            country: agency.country,
+            email: agency.email,
+            lat: agency.lat,
+            lng: agency.lng,
+            logo: agency.logo,
+            mobile: agency.mobile,
+            name: agency.name,
+            owner: agency.owner,
+            region: agency.region,
+            vat: agency.vat,
+            website: agency.website,
+            zip_code: agency.zip_code,
+        }
+    }
+}
+
+impl Agency {
+    /// Get the fields which differ between `self` and `current`
+    pub fn diff(&self, current: &Agency) -> Vec<AgencyFieldChange> {
+        let mut changes = vec![];
+        let mut push = |field: &str, previous: String, current: String| {
+            if previous != current {
+                changes.push(AgencyFieldChange {
+                    field: field.to_string(),
+                    previous,
+                    current,
+                });
+            }
+        };
+
+        push("address", self.address.clone(), current.address.clone());
+        push("agent", self.agent.clone(), current.agent.clone());
+        push("city", self.city.clone(), current.city.clone());
+        push(
+            "continent",
+            self.continent.to_string(),
+            current.continent.to_string(),
+        );
+        push("country", self.country.clone(), current.country.clone());
+        push("email", self.email.clone(), current.email.clone());
+        push(
+            "lat",
+            self.lat.clone().unwrap_or_default(),
+            current.lat.clone().unwrap_or_default(),
+        );
+        push(
+            "lng",
+            self.lng.clone().unwrap_or_default(),
+            current.lng.clone().unwrap_or_default(),
+        );
+        push(
+            "logo",
+            self.logo.clone().unwrap_or_default(),
+            current.logo.clone().unwrap_or_default(),
+        );
+        push("mobile", self.mobile.clone(), current.mobile.clone());
+        push("name", self.name.clone(), current.name.clone());
+        push("owner", self.owner.to_text(), current.owner.to_text());
+        push("region", self.region.clone(), current.region.clone());
+        push("vat", self.vat.clone(), current.vat.clone());
+        push("website", self.website.clone(), current.website.clone());
+        push("zip_code", self.zip_code.clone(), current.zip_code.clone());
+
+        changes
+    }
+}
+
+/// Agency profile fields which can be updated by the agency owner.
+///
+/// `None` leaves the field unchanged. `vat` and `owner` can only be changed by a custodian.
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct AgencyUpdate {
+    pub address: Option<String>,
+    pub agent: Option<String>,
+    pub city: Option<String>,
+    pub continent: Option<Continent>,
+    pub country: Option<String>,
+    pub email: Option<String>,
+    pub lat: Option<String>,
+    pub lng: Option<String>,
+    pub logo: Option<String>,
+    pub mobile: Option<String>,
+    pub name: Option<String>,
+    pub region: Option<String>,
+    pub website: Option<String>,
+    pub zip_code: Option<String>,
+}
+
+impl AgencyUpdate {
+    /// Apply the update to the provided agency
+    pub fn apply(self, agency: &mut Agency) {
+        if let Some(address) = self.address {
+            agency.address = address;
+        }
+        if let Some(agent) = self.agent {
+            agency.agent = agent;
+        }
+        if let Some(city) = self.city {
+            agency.city = city;
+        }
+        if let Some(continent) = self.continent {
+            agency.continent = continent;
+        }
+        if let Some(country) = self.country {
+            agency.country = country;
+        }
+        if let Some(email) = self.email {
+            agency.email = email;
+        }
+        if let Some(lat) = self.lat {
+            agency.lat = Some(lat);
+        }
+        if let Some(lng) = self.lng {
+            agency.lng = Some(lng);
+        }
+        if let Some(logo) = self.logo {
+            agency.logo = Some(logo);
+        }
+        if let Some(mobile) = self.mobile {
+            agency.mobile = mobile;
+        }
+        if let Some(name) = self.name {
+            agency.name = name;
+        }
+        if let Some(region) = self.region {
+            agency.region = region;
+        }
+        if let Some(website) = self.website {
+            agency.website = website;
+        }
+        if let Some(zip_code) = self.zip_code {
+            agency.zip_code = zip_code;
         }
     }
 }
 
+/// A change to a field of the agency profile
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyFieldChange {
+    pub field: String,
+    pub previous: String,
+    pub current: String,
+}
+
+/// An entry of the agency profile change history
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyChange {
+    /// Principal who made the change
+    pub author: Principal,
+    /// Changed fields
+    pub changes: Vec<AgencyFieldChange>,
+    /// Time of the change (nanoseconds)
+    pub timestamp: u64,
+}
+
+/// Change history of an agency profile
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyHistory(pub Vec<AgencyChange>);
+
+impl Storable for AgencyHistory {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize, Serialize, Copy, PartialEq, Eq)]
 pub enum Continent {
     Africa,
@@ -104,7 +460,7 @@ impl Storable for Agency {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(&bytes, Self).unwrap()
+        Decode!(&bytes, Self).unwrap_or_else(|_| Decode!(&bytes, AgencyV1).unwrap().into())
     }
 }
 
@@ -134,10 +490,101 @@ mod test {
             agent: "Agent".to_string(),
             logo: None,
             owner: Principal::anonymous(),
+            id: AgencyId(1),
         };
         let data = Encode!(&agency).unwrap();
         let decoded = Decode!(&data, Agency).unwrap();
 
         assert_eq!(agency, decoded);
     }
+
+    #[test]
+    fn test_should_decode_agency_stored_before_agency_ids() {
+        let wallet = Principal::from_text("be2us-64aaa-aaaaa-qaabq-cai").unwrap();
+        let legacy = AgencyV1 {
+            address: "Address".to_string(),
+            agent: "Agent".to_string(),
+            city: "City".to_string(),
+            continent: Continent::Europe,
+            country: "Country".to_string(),
+            email: "Email".to_string(),
+            lat: None,
+            lng: None,
+            logo: None,
+            mobile: "Mobile".to_string(),
+            name: "Agency".to_string(),
+            owner: wallet,
+            region: "Region".to_string(),
+            vat: "VAT".to_string(),
+            website: "Website".to_string(),
+            zip_code: "Zip".to_string(),
+        };
+        let data = Encode!(&legacy).unwrap();
+        let decoded = Agency::from_bytes(data.into());
+
+        assert_eq!(decoded.id, AgencyId::legacy(wallet));
+        assert_eq!(decoded.owner, wallet);
+        assert_eq!(decoded.name, "Agency");
+    }
+
+    #[test]
+    fn test_should_derive_legacy_agency_ids() {
+        let wallet = Principal::from_text("be2us-64aaa-aaaaa-qaabq-cai").unwrap();
+        let other = Principal::from_text("v5vof-zqaaa-aaaal-ai5cq-cai").unwrap();
+
+        assert_eq!(AgencyId::legacy(wallet), AgencyId::legacy(wallet));
+        assert_ne!(AgencyId::legacy(wallet), AgencyId::legacy(other));
+        assert!(AgencyId::legacy(wallet).0 >= LEGACY_AGENCY_ID_BIT);
+    }
+
+    #[test]
+    fn test_should_encode_agency_id() {
+        let id = AgencyId(42);
+
+        assert_eq!(AgencyId::from_bytes(id.to_bytes()), id);
+        assert_eq!(id.to_string().parse::<AgencyId>().unwrap(), id);
+        assert_eq!(
+            Decode!(&Encode!(&id).unwrap(), u64).unwrap(),
+            42,
+            "the agency id is a nat64 on the wire"
+        );
+    }
+
+    #[test]
+    fn test_should_apply_update_and_diff() {
+        let previous = Agency {
+            name: "Agency".to_string(),
+            email: "info@agency.com".to_string(),
+            vat: "VAT".to_string(),
+            ..Default::default()
+        };
+        let mut current = previous.clone();
+        AgencyUpdate {
+            email: Some("sales@agency.com".to_string()),
+            lat: Some("45.0".to_string()),
+            name: Some("Agency".to_string()),
+            ..Default::default()
+        }
+        .apply(&mut current);
+
+        assert_eq!(current.email, "sales@agency.com");
+        assert_eq!(current.lat.as_deref(), Some("45.0"));
+        assert_eq!(current.vat, "VAT");
+
+        assert_eq!(
+            previous.diff(&current),
+            vec![
+                AgencyFieldChange {
+                    field: "email".to_string(),
+                    previous: "info@agency.com".to_string(),
+                    current: "sales@agency.com".to_string(),
+                },
+                AgencyFieldChange {
+                    field: "lat".to_string(),
+                    previous: String::new(),
+                    current: "45.0".to_string(),
+                },
+            ]
+        );
+    }
 }
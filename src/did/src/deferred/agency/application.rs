@@ -0,0 +1,135 @@
+use candid::{CandidType, Decode, Encode, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+use serde::{Deserialize, Serialize};
+
+use super::Agency;
+
+/// Application submitted by an agency to be registered on the minter
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct AgencyApplication {
+    /// Agency data
+    pub agency: Agency,
+    /// Principal who submitted the application; it will be the agency owner
+    pub applicant: Principal,
+    /// Review comments
+    pub comments: Vec<AgencyApplicationComment>,
+    /// Documents provided to support the application (e.g. company registration). Their content
+    /// is read one at a time, by index
+    pub documents: Vec<AgencyApplicationDocumentInfo>,
+    /// Application id
+    pub id: u64,
+    /// Time of the review (nanoseconds)
+    pub reviewed_at: Option<u64>,
+    /// Custodian who approved or rejected the application
+    pub reviewer: Option<Principal>,
+    pub status: AgencyApplicationStatus,
+    /// Time of the submission (nanoseconds)
+    pub submitted_at: u64,
+}
+
+impl Storable for AgencyApplication {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// A document attached to an [`AgencyApplication`]
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyApplicationDocument {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    pub name: String,
+}
+
+impl Storable for AgencyApplicationDocument {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Name, type and size of a document attached to an [`AgencyApplication`]
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyApplicationDocumentInfo {
+    pub mime_type: String,
+    pub name: String,
+    /// Size of the document (bytes)
+    pub size: u64,
+}
+
+impl From<&AgencyApplicationDocument> for AgencyApplicationDocumentInfo {
+    fn from(document: &AgencyApplicationDocument) -> Self {
+        Self {
+            mime_type: document.mime_type.clone(),
+            name: document.name.clone(),
+            size: document.data.len() as u64,
+        }
+    }
+}
+
+/// A review comment left by a custodian on an [`AgencyApplication`]
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyApplicationComment {
+    pub author: Principal,
+    pub comment: String,
+    /// Time of the comment (nanoseconds)
+    pub timestamp: u64,
+}
+
+/// Review status of an [`AgencyApplication`]
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AgencyApplicationStatus {
+    Pending,
+    Approved,
+    Rejected,
+    /// Not reviewed in time; its documents have been removed
+    Expired,
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_agency_application() {
+        let application = AgencyApplication {
+            agency: Agency::default(),
+            applicant: Principal::management_canister(),
+            comments: vec![AgencyApplicationComment {
+                author: Principal::anonymous(),
+                comment: "missing VAT".to_string(),
+                timestamp: 10,
+            }],
+            documents: vec![AgencyApplicationDocumentInfo {
+                mime_type: "application/pdf".to_string(),
+                name: "registration.pdf".to_string(),
+                size: 3,
+            }],
+            id: 1,
+            reviewed_at: None,
+            reviewer: None,
+            status: AgencyApplicationStatus::Pending,
+            submitted_at: 5,
+        };
+
+        let data = application.to_bytes();
+        let decoded = AgencyApplication::from_bytes(data);
+        assert_eq!(application, decoded);
+    }
+}
@@ -0,0 +1,94 @@
+use candid::{CandidType, Decode, Encode, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+use serde::{Deserialize, Serialize};
+
+use super::AgencyId;
+use crate::ID;
+
+/// Transfer of real estates and open contracts from an agency to another one.
+///
+/// The transfer is executed once both agencies have accepted it and a custodian has approved it
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyTransfer {
+    /// Time of the custodian approval (nanoseconds)
+    pub approved_at: Option<u64>,
+    /// Custodian who approved or rejected the transfer
+    pub approved_by: Option<Principal>,
+    /// Open contracts to transfer
+    pub contracts: Vec<ID>,
+    /// Agency losing the records
+    pub from: AgencyId,
+    /// Admin of the losing agency who accepted the transfer
+    pub from_accepted_by: Option<Principal>,
+    /// Transfer id
+    pub id: u64,
+    /// Real estates to transfer
+    pub real_estates: Vec<ID>,
+    /// Time of the request (nanoseconds)
+    pub requested_at: u64,
+    /// Principal who requested the transfer
+    pub requested_by: Principal,
+    pub status: AgencyTransferStatus,
+    /// Agency gaining the records
+    pub to: AgencyId,
+    /// Admin of the gaining agency who accepted the transfer
+    pub to_accepted_by: Option<Principal>,
+}
+
+impl AgencyTransfer {
+    /// Returns whether both agencies have accepted the transfer
+    pub fn is_accepted(&self) -> bool {
+        self.from_accepted_by.is_some() && self.to_accepted_by.is_some()
+    }
+}
+
+impl Storable for AgencyTransfer {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Status of an [`AgencyTransfer`]
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AgencyTransferStatus {
+    /// Waiting for the agencies acceptance and the custodian approval
+    Pending,
+    /// The records have been moved to the gaining agency
+    Completed,
+    /// The transfer has been rejected by an agency or by a custodian
+    Rejected,
+}
+
+/// Audit entry written by the data canister when records are moved between agencies
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AgencyTransferAuditEntry {
+    /// Contracts moved to the gaining agency
+    pub contracts: Vec<ID>,
+    pub from: AgencyId,
+    /// Real estates moved to the gaining agency
+    pub real_estates: Vec<ID>,
+    /// Time of the transfer (nanoseconds)
+    pub timestamp: u64,
+    pub to: AgencyId,
+    /// Id of the [`AgencyTransfer`] on the minter
+    pub transfer_id: u64,
+}
+
+impl Storable for AgencyTransferAuditEntry {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
@@ -0,0 +1,352 @@
+mod log;
+mod summary;
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ethers_core::utils::keccak256;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+pub use self::log::{StableAuditLog, AUDIT_MAX_ENTRIES};
+pub use self::summary::{
+    audit_list, audit_text, AuditSummary, AUDIT_MAX_LIST_ITEMS, AUDIT_MAX_TEXT_LEN,
+};
+
+/// Hash preceding the first entry of the audit log
+pub const AUDIT_GENESIS_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+/// Maximum length of the arguments summary stored in an [`AuditEntry`]
+pub const AUDIT_MAX_ARGS_LEN: usize = 512;
+
+/// Entry of the append-only audit log of the state-changing calls.
+///
+/// Each entry is chained to the previous one through its hash, so editing or removing an entry
+/// breaks the chain from that entry on
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Summary of the arguments of the call
+    pub args: String,
+    pub caller: Principal,
+    /// Hash of the entry (hex)
+    pub hash: String,
+    /// Entry id, starting from 1
+    pub id: u64,
+    /// Called method
+    pub method: String,
+    /// Hash of the previous entry (hex)
+    pub prev_hash: String,
+    pub result: AuditResult,
+    /// Time of the call (nanoseconds)
+    pub timestamp: u64,
+}
+
+impl AuditEntry {
+    /// Create a new entry chained to the entry with the provided hash.
+    ///
+    /// The arguments summary is truncated to [`AUDIT_MAX_ARGS_LEN`] characters
+    pub fn new(
+        id: u64,
+        caller: Principal,
+        method: &str,
+        args: &str,
+        result: AuditResult,
+        timestamp: u64,
+        prev_hash: String,
+    ) -> Self {
+        let mut entry = Self {
+            args: args.chars().take(AUDIT_MAX_ARGS_LEN).collect(),
+            caller,
+            hash: String::default(),
+            id,
+            method: method.to_string(),
+            prev_hash,
+            result,
+            timestamp,
+        };
+        entry.hash = entry.compute_hash();
+
+        entry
+    }
+
+    /// Compute the hash of the entry, including the hash of the previous entry
+    pub fn compute_hash(&self) -> String {
+        let mut data = Vec::new();
+        data.extend_from_slice(&self.id.to_be_bytes());
+        push_field(&mut data, self.caller.as_slice());
+        push_field(&mut data, self.method.as_bytes());
+        push_field(&mut data, self.args.as_bytes());
+        match &self.result {
+            AuditResult::Ok => data.push(0),
+            AuditResult::Err(err) => {
+                data.push(1);
+                push_field(&mut data, err.as_bytes());
+            }
+        }
+        data.extend_from_slice(&self.timestamp.to_be_bytes());
+        push_field(&mut data, self.prev_hash.as_bytes());
+
+        hex::encode(keccak256(data))
+    }
+
+    /// Returns whether the entry has not been modified and follows the entry with the provided hash
+    pub fn is_valid(&self, prev_hash: &str) -> bool {
+        self.prev_hash == prev_hash && self.hash == self.compute_hash()
+    }
+}
+
+/// Push a length-prefixed field, so that the boundaries of the fields are part of the hash
+fn push_field(data: &mut Vec<u8>, field: &[u8]) {
+    data.extend_from_slice(&(field.len() as u64).to_be_bytes());
+    data.extend_from_slice(field);
+}
+
+impl Storable for AuditEntry {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Result of the call recorded by an [`AuditEntry`]
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum AuditResult {
+    Ok,
+    /// The call failed with the provided error
+    Err(String),
+}
+
+impl<T, E> From<&Result<T, E>> for AuditResult
+where
+    E: std::fmt::Display,
+{
+    fn from(result: &Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Self::Ok,
+            Err(err) => Self::Err(err.to_string()),
+        }
+    }
+}
+
+/// Filter of the audit log entries. Each field is ignored when `None`
+#[derive(Clone, Debug, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AuditFilter {
+    pub caller: Option<Principal>,
+    pub method: Option<String>,
+    /// Entries recorded at or after this time (nanoseconds)
+    pub from: Option<u64>,
+    /// Entries recorded at or before this time (nanoseconds)
+    pub to: Option<u64>,
+}
+
+impl AuditFilter {
+    /// Returns whether the entry matches the filter
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.caller.map_or(true, |caller| entry.caller == caller)
+            && self
+                .method
+                .as_ref()
+                .map_or(true, |method| &entry.method == method)
+            && self.from.map_or(true, |from| entry.timestamp >= from)
+            && self.to.map_or(true, |to| entry.timestamp <= to)
+    }
+}
+
+/// Outcome of the verification of the audit log hash chain
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AuditIntegrity {
+    /// Amount of verified entries
+    pub entries: u64,
+    /// First entry which has been modified or doesn't follow the previous one
+    pub first_invalid_entry: Option<u64>,
+    /// Hash of the last entry of the log
+    pub head: String,
+}
+
+impl AuditIntegrity {
+    /// Verify the hash chain of consecutive entries, where the first entry must have the provided id
+    /// and follow the entry with the provided hash.
+    ///
+    /// `head` is the hash of the last entry of the whole log
+    pub fn verify(
+        entries: impl Iterator<Item = AuditEntry>,
+        first_id: u64,
+        mut prev_hash: String,
+        head: String,
+    ) -> Self {
+        let mut verified = 0;
+        let mut first_invalid_entry = None;
+        for (expected_id, entry) in (first_id..).zip(entries) {
+            verified += 1;
+            if entry.id != expected_id || !entry.is_valid(&prev_hash) {
+                first_invalid_entry = Some(expected_id);
+                break;
+            }
+            prev_hash = entry.hash;
+        }
+
+        Self {
+            entries: verified,
+            first_invalid_entry,
+            head,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(id: u64, prev_hash: String) -> AuditEntry {
+        AuditEntry::new(
+            id,
+            Principal::management_canister(),
+            "admin_set_minter",
+            "aaaaa-aa",
+            AuditResult::Ok,
+            1_000,
+            prev_hash,
+        )
+    }
+
+    #[test]
+    fn test_should_encode_audit_entry() {
+        let entry = entry(1, AUDIT_GENESIS_HASH.to_string());
+
+        let data = entry.to_bytes();
+        let decoded = AuditEntry::from_bytes(data);
+        assert_eq!(entry, decoded);
+    }
+
+    #[test]
+    fn test_should_chain_entries() {
+        let first = entry(1, AUDIT_GENESIS_HASH.to_string());
+        let second = entry(2, first.hash.clone());
+
+        assert_eq!(first.hash.len(), 64);
+        assert_ne!(first.hash, second.hash);
+        assert!(first.is_valid(AUDIT_GENESIS_HASH));
+        assert!(second.is_valid(&first.hash));
+        assert!(!second.is_valid(AUDIT_GENESIS_HASH));
+
+        let mut tampered = second.clone();
+        tampered.result = AuditResult::Err("unauthorized caller".to_string());
+        assert!(!tampered.is_valid(&first.hash));
+    }
+
+    #[test]
+    fn test_should_verify_chain() {
+        let first = entry(1, AUDIT_GENESIS_HASH.to_string());
+        let second = entry(2, first.hash.clone());
+        let third = entry(3, second.hash.clone());
+        let head = third.hash.clone();
+
+        let entries = vec![first.clone(), second.clone(), third.clone()];
+        assert_eq!(
+            AuditIntegrity::verify(
+                entries.into_iter(),
+                1,
+                AUDIT_GENESIS_HASH.to_string(),
+                head.clone()
+            ),
+            AuditIntegrity {
+                entries: 3,
+                first_invalid_entry: None,
+                head: head.clone(),
+            }
+        );
+
+        // verify from the middle of the log
+        assert_eq!(
+            AuditIntegrity::verify(
+                vec![third.clone()].into_iter(),
+                3,
+                second.hash.clone(),
+                head.clone()
+            )
+            .first_invalid_entry,
+            None
+        );
+
+        // removed entry
+        assert_eq!(
+            AuditIntegrity::verify(
+                vec![first.clone(), third.clone()].into_iter(),
+                1,
+                AUDIT_GENESIS_HASH.to_string(),
+                head.clone()
+            )
+            .first_invalid_entry,
+            Some(2)
+        );
+
+        // modified entry
+        let mut tampered = second.clone();
+        tampered.args = "bbbbb-bb".to_string();
+        assert_eq!(
+            AuditIntegrity::verify(
+                vec![first, tampered, third].into_iter(),
+                1,
+                AUDIT_GENESIS_HASH.to_string(),
+                head
+            )
+            .first_invalid_entry,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_should_truncate_args() {
+        let entry = AuditEntry::new(
+            1,
+            Principal::anonymous(),
+            "upload_contract_document",
+            &"a".repeat(AUDIT_MAX_ARGS_LEN * 2),
+            AuditResult::Ok,
+            0,
+            AUDIT_GENESIS_HASH.to_string(),
+        );
+        assert_eq!(entry.args.len(), AUDIT_MAX_ARGS_LEN);
+    }
+
+    #[test]
+    fn test_should_filter_entries() {
+        let entry = entry(1, AUDIT_GENESIS_HASH.to_string());
+
+        assert!(AuditFilter::default().matches(&entry));
+        assert!(AuditFilter {
+            caller: Some(Principal::management_canister()),
+            method: Some("admin_set_minter".to_string()),
+            from: Some(1_000),
+            to: Some(1_000),
+        }
+        .matches(&entry));
+        assert!(!AuditFilter {
+            caller: Some(Principal::anonymous()),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditFilter {
+            method: Some("admin_cycles".to_string()),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditFilter {
+            from: Some(1_001),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditFilter {
+            to: Some(999),
+            ..Default::default()
+        }
+        .matches(&entry));
+    }
+}
@@ -0,0 +1,410 @@
+use candid::Principal;
+use ethers_core::utils::keccak256;
+use ic_log::did::Pagination;
+use ic_stable_structures::{BTreeMap, Memory};
+
+use super::{AuditEntry, AuditFilter, AuditIntegrity, AuditResult, AUDIT_GENESIS_HASH};
+
+/// Maximum amount of entries kept by the audit log. The oldest entries are removed beyond it
+pub const AUDIT_MAX_ENTRIES: u64 = 100_000;
+
+/// Append-only audit log of the state-changing calls, chained by hash and stored in the stable
+/// memory.
+///
+/// The log keeps the last [`AUDIT_MAX_ENTRIES`] entries and is indexed by caller and by method,
+/// so that the filtered reads don't scan the whole log
+pub struct StableAuditLog<M: Memory> {
+    /// Entries by id
+    entries: BTreeMap<u64, AuditEntry, M>,
+    /// Entry ids by caller
+    by_caller: BTreeMap<(Principal, u64), (), M>,
+    /// Entry ids by hash of the method
+    by_method: BTreeMap<(u64, u64), (), M>,
+    max_entries: u64,
+}
+
+impl<M: Memory> StableAuditLog<M> {
+    /// Load the audit log from the memories of the entries and of the indexes
+    pub fn new(entries: M, by_caller: M, by_method: M) -> Self {
+        Self {
+            entries: BTreeMap::new(entries),
+            by_caller: BTreeMap::new(by_caller),
+            by_method: BTreeMap::new(by_method),
+            max_entries: AUDIT_MAX_ENTRIES,
+        }
+    }
+
+    /// Set the maximum amount of entries kept by the log
+    pub fn with_max_entries(mut self, max_entries: u64) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Append a new entry chained to the last one, removing the oldest entries beyond the maximum
+    pub fn append(
+        &mut self,
+        caller: Principal,
+        method: &str,
+        args: &str,
+        result: AuditResult,
+        timestamp: u64,
+    ) -> AuditEntry {
+        let (id, prev_hash) = self
+            .entries
+            .last_key_value()
+            .map(|(id, last)| (id + 1, last.hash))
+            .unwrap_or((1, AUDIT_GENESIS_HASH.to_string()));
+        let entry = AuditEntry::new(id, caller, method, args, result, timestamp, prev_hash);
+
+        self.entries.insert(id, entry.clone());
+        self.by_caller.insert((caller, id), ());
+        self.by_method.insert((method_key(method), id), ());
+
+        while self.entries.len() > self.max_entries {
+            let Some((oldest, removed)) = self.entries.pop_first() else {
+                break;
+            };
+            self.by_caller.remove(&(removed.caller, oldest));
+            self.by_method
+                .remove(&(method_key(&removed.method), oldest));
+        }
+
+        entry
+    }
+
+    /// Index the entries appended before the log was indexed, returning how many have been indexed
+    pub fn index_entries(&mut self) -> u64 {
+        if !self.by_caller.is_empty() {
+            return 0;
+        }
+
+        let mut indexed = 0;
+        for (id, entry) in self.entries.iter() {
+            self.by_caller.insert((entry.caller, id), ());
+            self.by_method.insert((method_key(&entry.method), id), ());
+            indexed += 1;
+        }
+
+        indexed
+    }
+
+    /// Get the entries matching the filter, from the oldest one.
+    ///
+    /// The entries are read through the caller or the method index when the filter sets them,
+    /// and only between the first and the last entry of the time range
+    pub fn get_entries(&self, filter: &AuditFilter, pagination: Pagination) -> Vec<AuditEntry> {
+        let Some((first_id, last_id)) = self.id_range(filter) else {
+            return vec![];
+        };
+
+        let ids: Box<dyn Iterator<Item = u64> + '_> = if let Some(caller) = filter.caller {
+            Box::new(
+                self.by_caller
+                    .range((caller, first_id)..=(caller, last_id))
+                    .map(|((_, id), _)| id),
+            )
+        } else if let Some(method) = &filter.method {
+            let key = method_key(method);
+            Box::new(
+                self.by_method
+                    .range((key, first_id)..=(key, last_id))
+                    .map(|((_, id), _)| id),
+            )
+        } else {
+            Box::new(first_id..=last_id)
+        };
+
+        ids.filter_map(|id| self.entries.get(&id))
+            .filter(|entry| filter.matches(entry))
+            .skip(pagination.offset)
+            .take(pagination.count)
+            .collect()
+    }
+
+    /// Verify the hash chain of the entries in the provided page, counted from the oldest entry
+    /// kept by the log.
+    ///
+    /// The oldest entry kept is chained to the hash it has recorded, since the entries preceding
+    /// it have been removed
+    pub fn verify(&self, pagination: Pagination) -> AuditIntegrity {
+        let head = self
+            .entries
+            .last_key_value()
+            .map(|(_, entry)| entry.hash)
+            .unwrap_or(AUDIT_GENESIS_HASH.to_string());
+        let Some((oldest_id, oldest)) = self.entries.first_key_value() else {
+            return AuditIntegrity::verify(std::iter::empty(), 1, head.clone(), head);
+        };
+
+        let first_id = oldest_id + pagination.offset as u64;
+        let prev_hash = if first_id == 1 {
+            AUDIT_GENESIS_HASH.to_string()
+        } else if first_id == oldest_id {
+            oldest.prev_hash
+        } else {
+            self.entries
+                .get(&(first_id - 1))
+                .map(|entry| entry.hash)
+                .unwrap_or(AUDIT_GENESIS_HASH.to_string())
+        };
+
+        AuditIntegrity::verify(
+            self.entries
+                .range(first_id..)
+                .map(|(_, entry)| entry)
+                .take(pagination.count),
+            first_id,
+            prev_hash,
+            head,
+        )
+    }
+
+    /// Returns the ids of the first and of the last entry in the time range of the filter.
+    ///
+    /// The entries are appended with increasing timestamps, so the range is found by bisection
+    fn id_range(&self, filter: &AuditFilter) -> Option<(u64, u64)> {
+        let (oldest, _) = self.entries.first_key_value()?;
+        let (newest, _) = self.entries.last_key_value()?;
+
+        let first_id = match filter.from {
+            Some(from) => self.partition_point(oldest, newest + 1, |timestamp| timestamp < from),
+            None => oldest,
+        };
+        let last_id = match filter.to {
+            Some(to) => self.partition_point(oldest, newest + 1, |timestamp| timestamp <= to),
+            None => newest + 1,
+        };
+
+        (first_id < last_id).then_some((first_id, last_id - 1))
+    }
+
+    /// Returns the first id in `start..end` whose entry doesn't satisfy the predicate on its
+    /// timestamp, or `end` if all of them satisfy it
+    fn partition_point<F>(&self, mut start: u64, mut end: u64, predicate: F) -> u64
+    where
+        F: Fn(u64) -> bool,
+    {
+        while start < end {
+            let mid = start + (end - start) / 2;
+            let timestamp = self
+                .entries
+                .get(&mid)
+                .map(|entry| entry.timestamp)
+                .unwrap_or_default();
+            if predicate(timestamp) {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+
+        start
+    }
+}
+
+/// Key of the method in the method index
+fn method_key(method: &str) -> u64 {
+    let hash = keccak256(method.as_bytes());
+    u64::from_be_bytes(hash[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::VectorMemory;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn audit_log() -> StableAuditLog<VectorMemory> {
+        StableAuditLog::new(
+            VectorMemory::default(),
+            VectorMemory::default(),
+            VectorMemory::default(),
+        )
+    }
+
+    fn alice() -> Principal {
+        Principal::from_text("be2us-64aaa-aaaaa-qaabq-cai").unwrap()
+    }
+
+    fn bob() -> Principal {
+        Principal::from_text("bs5l3-6b3zu-dpqyj-p2x4a-jyg4k-goneb-afof2-y5d62-skt67-3756q-dqe")
+            .unwrap()
+    }
+
+    fn pagination() -> Pagination {
+        Pagination {
+            offset: 0,
+            count: 100,
+        }
+    }
+
+    #[test]
+    fn test_should_append_chained_entries() {
+        let mut log = audit_log();
+        let first = log.append(alice(), "admin_set_role", "bob", AuditResult::Ok, 1);
+        let second = log.append(
+            bob(),
+            "admin_set_custodians",
+            "[]",
+            AuditResult::Err("unauthorized caller".to_string()),
+            2,
+        );
+
+        assert_eq!(first.id, 1);
+        assert_eq!(first.prev_hash, AUDIT_GENESIS_HASH);
+        assert_eq!(second.id, 2);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(
+            log.get_entries(&AuditFilter::default(), pagination()),
+            vec![first, second]
+        );
+    }
+
+    #[test]
+    fn test_should_filter_entries() {
+        let mut log = audit_log();
+        log.append(alice(), "admin_set_role", "bob", AuditResult::Ok, 10);
+        log.append(bob(), "admin_set_role", "alice", AuditResult::Ok, 20);
+        log.append(alice(), "admin_suspend_agency", "bob", AuditResult::Ok, 30);
+
+        let filter = AuditFilter {
+            caller: Some(alice()),
+            ..Default::default()
+        };
+        assert_eq!(log.get_entries(&filter, pagination()).len(), 2);
+
+        let filter = AuditFilter {
+            method: Some("admin_set_role".to_string()),
+            ..Default::default()
+        };
+        let entries = log.get_entries(&filter, pagination());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].caller, bob());
+
+        let filter = AuditFilter {
+            caller: Some(alice()),
+            method: Some("admin_suspend_agency".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(log.get_entries(&filter, pagination())[0].id, 3);
+
+        let filter = AuditFilter {
+            from: Some(15),
+            to: Some(30),
+            ..Default::default()
+        };
+        let entries = log.get_entries(&filter, pagination());
+        assert_eq!(
+            entries.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        let filter = AuditFilter {
+            from: Some(31),
+            ..Default::default()
+        };
+        assert!(log.get_entries(&filter, pagination()).is_empty());
+        let filter = AuditFilter {
+            to: Some(9),
+            ..Default::default()
+        };
+        assert!(log.get_entries(&filter, pagination()).is_empty());
+
+        assert_eq!(
+            log.get_entries(
+                &AuditFilter::default(),
+                Pagination {
+                    offset: 2,
+                    count: 10
+                }
+            )[0]
+            .id,
+            3
+        );
+    }
+
+    #[test]
+    fn test_should_remove_oldest_entries() {
+        let mut log = audit_log().with_max_entries(3);
+        for timestamp in 1..=5 {
+            log.append(alice(), "admin_set_role", "bob", AuditResult::Ok, timestamp);
+        }
+
+        let entries = log.get_entries(&AuditFilter::default(), pagination());
+        assert_eq!(
+            entries.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+        let filter = AuditFilter {
+            caller: Some(alice()),
+            ..Default::default()
+        };
+        assert_eq!(log.get_entries(&filter, pagination()).len(), 3);
+        assert_eq!(log.by_caller.len(), 3);
+        assert_eq!(log.by_method.len(), 3);
+
+        let integrity = log.verify(pagination());
+        assert_eq!(integrity.entries, 3);
+        assert_eq!(integrity.first_invalid_entry, None);
+        assert_eq!(integrity.head, entries[2].hash);
+    }
+
+    #[test]
+    fn test_should_index_entries_appended_before_indexing() {
+        let mut log = audit_log();
+        log.append(alice(), "admin_set_role", "bob", AuditResult::Ok, 1);
+        log.append(bob(), "admin_set_role", "alice", AuditResult::Ok, 2);
+        log.by_caller = BTreeMap::new(VectorMemory::default());
+        log.by_method = BTreeMap::new(VectorMemory::default());
+
+        assert_eq!(log.index_entries(), 2);
+        assert_eq!(log.index_entries(), 0);
+        let filter = AuditFilter {
+            caller: Some(bob()),
+            method: Some("admin_set_role".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(log.get_entries(&filter, pagination())[0].id, 2);
+    }
+
+    #[test]
+    fn test_should_detect_tampering() {
+        let mut log = audit_log();
+        for timestamp in 1..=5 {
+            log.append(alice(), "admin_set_role", "bob", AuditResult::Ok, timestamp);
+        }
+        let head = log.get_entries(&AuditFilter::default(), pagination())[4]
+            .hash
+            .clone();
+
+        assert_eq!(
+            log.verify(pagination()),
+            AuditIntegrity {
+                entries: 5,
+                first_invalid_entry: None,
+                head: head.clone(),
+            }
+        );
+
+        let mut tampered = log.entries.get(&3).unwrap();
+        tampered.caller = bob();
+        log.entries.insert(3, tampered.clone());
+        assert_eq!(log.verify(pagination()).first_invalid_entry, Some(3));
+        // entries before the tampered one are still valid
+        assert_eq!(
+            log.verify(Pagination {
+                offset: 0,
+                count: 2
+            })
+            .first_invalid_entry,
+            None
+        );
+
+        // rewriting the hash of the tampered entry breaks the following one
+        tampered.hash = tampered.compute_hash();
+        log.entries.insert(3, tampered);
+        assert_eq!(log.verify(pagination()).first_invalid_entry, Some(4));
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use crate::deferred::{
+    Agency, AgencyTransfer, AgencyUpdate, ConfigurationUpdate, ContractDocument, DocumentsAnchor,
+    GenericValue, ProposalAction, RealEstate,
+};
+
+/// Maximum length of a free text argument in an audit summary (characters)
+pub const AUDIT_MAX_TEXT_LEN: usize = 64;
+/// Maximum amount of items of a list argument listed in an audit summary
+pub const AUDIT_MAX_LIST_ITEMS: usize = 5;
+
+/// Short summary of a call argument, recorded in the audit log in place of its full representation
+pub trait AuditSummary {
+    fn audit_summary(&self) -> String;
+}
+
+/// Summary of a free text argument, truncated to [`AUDIT_MAX_TEXT_LEN`] characters
+pub fn audit_text(text: &str) -> String {
+    if text.chars().count() <= AUDIT_MAX_TEXT_LEN {
+        return format!("{text:?}");
+    }
+
+    let truncated = text.chars().take(AUDIT_MAX_TEXT_LEN).collect::<String>();
+    format!("{truncated:?}…")
+}
+
+/// Summary of a list argument, listing at most [`AUDIT_MAX_LIST_ITEMS`] items
+pub fn audit_list<T>(items: &[T]) -> String
+where
+    T: fmt::Debug,
+{
+    let listed = items
+        .iter()
+        .take(AUDIT_MAX_LIST_ITEMS)
+        .map(|item| format!("{item:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if items.len() > AUDIT_MAX_LIST_ITEMS {
+        format!(
+            "[{listed}, … (+{} more)]",
+            items.len() - AUDIT_MAX_LIST_ITEMS
+        )
+    } else {
+        format!("[{listed}]")
+    }
+}
+
+/// Summary of the fields set by a partial update
+fn audit_fields(fields: &[(&str, bool)]) -> String {
+    let fields = fields
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(field, _)| *field)
+        .collect::<Vec<_>>();
+
+    format!("fields: {}", fields.join(", "))
+}
+
+impl AuditSummary for Agency {
+    fn audit_summary(&self) -> String {
+        format!("agency {} ({})", audit_text(&self.name), self.id)
+    }
+}
+
+impl AuditSummary for AgencyUpdate {
+    fn audit_summary(&self) -> String {
+        audit_fields(&[
+            ("address", self.address.is_some()),
+            ("agent", self.agent.is_some()),
+            ("city", self.city.is_some()),
+            ("continent", self.continent.is_some()),
+            ("country", self.country.is_some()),
+            ("email", self.email.is_some()),
+            ("lat", self.lat.is_some()),
+            ("lng", self.lng.is_some()),
+            ("logo", self.logo.is_some()),
+            ("mobile", self.mobile.is_some()),
+            ("name", self.name.is_some()),
+            ("region", self.region.is_some()),
+            ("website", self.website.is_some()),
+            ("zip_code", self.zip_code.is_some()),
+        ])
+    }
+}
+
+impl AuditSummary for AgencyTransfer {
+    fn audit_summary(&self) -> String {
+        format!(
+            "transfer {} from {} to {}: {} real estates, {} contracts",
+            self.id,
+            self.from,
+            self.to,
+            self.real_estates.len(),
+            self.contracts.len()
+        )
+    }
+}
+
+impl AuditSummary for ConfigurationUpdate {
+    fn audit_summary(&self) -> String {
+        audit_fields(&[
+            ("chain_id", self.chain_id.is_some()),
+            ("deferred_data", self.deferred_data.is_some()),
+            ("deferred_erc721", self.deferred_erc721.is_some()),
+            ("ecdsa_key", self.ecdsa_key.is_some()),
+            ("evm_rpc", self.evm_rpc.is_some()),
+            ("evm_rpc_api", self.evm_rpc_api.is_some()),
+            ("log_settings", self.log_settings.is_some()),
+            ("reward_pool", self.reward_pool.is_some()),
+            (
+                "reward_pool_deploy_block",
+                self.reward_pool_deploy_block.is_some(),
+            ),
+        ])
+    }
+}
+
+impl AuditSummary for ContractDocument {
+    fn audit_summary(&self) -> String {
+        format!(
+            "document {} ({}, {} bytes, access {})",
+            audit_text(&self.name),
+            audit_text(&self.mime_type),
+            self.size,
+            audit_list(&self.access_list)
+        )
+    }
+}
+
+impl AuditSummary for DocumentsAnchor {
+    fn audit_summary(&self) -> String {
+        format!(
+            "contract {:?}, root {}, {} leaves, transaction {}",
+            self.contract_id,
+            audit_text(&self.root),
+            self.leaves.len(),
+            audit_text(&self.transaction_hash)
+        )
+    }
+}
+
+impl AuditSummary for GenericValue {
+    fn audit_summary(&self) -> String {
+        audit_text(&self.to_string())
+    }
+}
+
+impl AuditSummary for ProposalAction {
+    fn audit_summary(&self) -> String {
+        match self {
+            Self::SetCustodians(custodians) => format!("SetCustodians {}", audit_list(custodians)),
+            Self::SetRole { principal, role } => format!("SetRole {role:?} to {principal}"),
+            Self::RemoveRole { principal, role } => {
+                format!("RemoveRole {role:?} from {principal}")
+            }
+            Self::DefineRole { name, permissions } => {
+                format!("DefineRole {} with {permissions:?}", audit_text(name))
+            }
+            Self::DeleteRole(name) => format!("DeleteRole {}", audit_text(name)),
+            Self::SetAllowedCurrencies(currencies) => {
+                format!("SetAllowedCurrencies {}", audit_list(currencies))
+            }
+            Self::SetProposalConfig(config) => format!("SetProposalConfig {config:?}"),
+            Self::SetSnsGovernance(governance) => format!("SetSnsGovernance {governance:?}"),
+            Self::UpdateConfiguration(update) => {
+                format!("UpdateConfiguration {}", update.audit_summary())
+            }
+            Self::Resume(operation) => format!("Resume {operation:?}"),
+            Self::SetRewardParameters(parameters) => format!("SetRewardParameters {parameters:?}"),
+        }
+    }
+}
+
+impl AuditSummary for RealEstate {
+    fn audit_summary(&self) -> String {
+        format!(
+            "real estate {} of agency {}",
+            audit_text(&self.name),
+            self.agency
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::deferred::AgencyId;
+
+    #[test]
+    fn test_should_truncate_text() {
+        assert_eq!(audit_text("bug"), "\"bug\"");
+        assert_eq!(
+            audit_text(&"a".repeat(AUDIT_MAX_TEXT_LEN + 10)),
+            format!("{:?}…", "a".repeat(AUDIT_MAX_TEXT_LEN))
+        );
+    }
+
+    #[test]
+    fn test_should_bound_list() {
+        assert_eq!(audit_list::<u64>(&[]), "[]");
+        assert_eq!(audit_list(&[1, 2]), "[1, 2]");
+        assert_eq!(
+            audit_list(&(1..=8).collect::<Vec<u64>>()),
+            "[1, 2, 3, 4, 5, … (+3 more)]"
+        );
+    }
+
+    #[test]
+    fn test_should_summarize_arguments() {
+        let agency = Agency {
+            id: AgencyId(42),
+            name: "Dummy Real estate".to_string(),
+            address: "a".repeat(10_000),
+            ..Default::default()
+        };
+        assert_eq!(agency.audit_summary(), "agency \"Dummy Real estate\" (42)");
+
+        let update = ConfigurationUpdate {
+            chain_id: Some(1),
+            reward_pool_deploy_block: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(
+            update.audit_summary(),
+            "fields: chain_id, reward_pool_deploy_block"
+        );
+        assert_eq!(
+            ProposalAction::UpdateConfiguration(update).audit_summary(),
+            "UpdateConfiguration fields: chain_id, reward_pool_deploy_block"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use candid::{CandidType, Decode, Deserialize, Encode};
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_stable_structures::storable::Bound;
 use ic_stable_structures::Storable;
 use serde::Serialize;
@@ -79,8 +79,70 @@ impl Storable for Contract {
         Encode!(&self).unwrap().into()
     }
 
+    /// The contracts stored before the introduction of the agency ids reference their agency
+    /// by its wallet, which is converted to its legacy agency id
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(&bytes, Self).unwrap()
+        Decode!(&bytes, Self)
+            .or_else(|_| Decode!(&bytes, ContractV1).map(Contract::from))
+            .unwrap()
+    }
+}
+
+/// A [`Contract`] stored before the introduction of the agency ids, when its agency was
+/// identified by the wallet of its owner
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct ContractV1 {
+    /// Contract ID
+    pub id: ID,
+    /// Contract type
+    pub r#type: ContractType,
+    /// The contractors selling the building with their quota
+    pub sellers: Vec<Seller>,
+    /// Contract buyers. Those who must pay
+    pub buyers: Vec<H160>,
+    /// Number of installments
+    pub installments: u64,
+    /// Contract value value
+    pub value: u64,
+    /// Deposit fiat value (already paid)
+    pub deposit: u64,
+    /// Currency symbol
+    pub currency: String,
+    /// Data associated to the contract
+    pub properties: ContractProperties,
+    /// Restricted data associated to the contract
+    pub restricted_properties: RestrictedContractProperties,
+    /// Documents associated to the contract
+    pub documents: ContractDocuments,
+    /// Agency id
+    pub agency: Principal,
+    /// Real estate id
+    pub real_estate: ID,
+    /// Contract expiration date YYYY-MM-DD
+    pub expiration: String,
+    /// If the contract is closed
+    pub closed: bool,
+}
+
+impl From<ContractV1> for Contract {
+    fn from(contract: ContractV1) -> Self {
+        Self {
+            id: contract.id,
+            r#type: contract.r#type,
+            sellers: contract.sellers,
+            buyers: contract.buyers,
+            installments: contract.installments,
+            value: contract.value,
+            deposit: contract.deposit,
+            currency: contract.currency,
+            properties: contract.properties,
+            restricted_properties: contract.restricted_properties,
+            documents: contract.documents,
+            agency: AgencyId::legacy(contract.agency),
+            real_estate: contract.real_estate,
+            expiration: contract.expiration,
+            closed: contract.closed,
+        }
     }
 }
 
@@ -196,7 +258,6 @@ impl Default for ContractRegistration {
 #[cfg(test)]
 mod test {
 
-    use candid::Principal;
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -238,7 +299,7 @@ mod test {
                     value: GenericValue::TextContent("Secret".to_string()),
                 },
             )],
-            agency: Principal::management_canister(),
+            agency: AgencyId(1),
             real_estate: 1u64.into(),
             expiration: "2040-01-01".to_string(),
             closed: false,
@@ -255,4 +316,32 @@ mod test {
         assert_eq!(contract.installments, decoded_contract.installments);
         assert_eq!(contract.agency, decoded_contract.agency);
     }
+
+    #[test]
+    fn test_should_decode_contract_stored_before_agency_ids() {
+        let wallet = Principal::from_text("be2us-64aaa-aaaaa-qaabq-cai").unwrap();
+        let legacy = ContractV1 {
+            id: ID::from(1_u64),
+            r#type: ContractType::Sell,
+            sellers: vec![],
+            buyers: vec![],
+            installments: 2,
+            value: 250_000,
+            deposit: 50_000,
+            currency: "EUR".to_string(),
+            properties: vec![],
+            restricted_properties: vec![],
+            documents: vec![],
+            agency: wallet,
+            real_estate: 1u64.into(),
+            expiration: "2040-01-01".to_string(),
+            closed: false,
+        };
+        let data = Encode!(&legacy).unwrap();
+        let decoded = Contract::from_bytes(data.into());
+
+        assert_eq!(decoded.agency, AgencyId::legacy(wallet));
+        assert_eq!(decoded.id, ID::from(1_u64));
+        assert_eq!(decoded.value, 250_000);
+    }
 }
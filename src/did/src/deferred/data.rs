@@ -1,9 +1,43 @@
+mod anchor;
+mod document;
+mod domain;
 mod error;
+mod export;
+mod mime;
+mod preview;
+mod streaming;
+mod upload;
+mod usage;
 
-use candid::{CandidType, Deserialize, Principal};
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_log::LogSettingsV2;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
 
-pub use self::error::{ConfigurationError, ContractError, DeferredDataError, RealEstateError};
+pub use self::anchor::{DocumentLeaf, DocumentProof, DocumentsAnchor, DocumentsMerkleTree};
+pub use self::document::{DocumentHistory, DocumentVersion};
+pub use self::domain::StorableDomains;
+pub use self::error::{
+    ConfigurationError, ContractError, DeferredDataError, ImportError, RealEstateError,
+    SignedMessageError,
+};
+pub use self::export::{
+    CollectionDigest, ExportChunk, ExportCollection, ExportCursor, ExportRecord,
+};
+pub use self::mime::{StorableMimeTypes, DEFAULT_ALLOWED_MIME_TYPES};
+pub use self::preview::{DocumentImage, DocumentPreviews, ImageSize};
+pub use self::streaming::StreamingSession;
+pub use self::upload::DocumentUpload;
+pub use self::usage::{AgencyQuota, AgencyUsage, StorageUsage};
+
+/// Permissions required to call the privileged endpoints of the deferred data canister
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum Permission {
+    /// Granted to the owner of the canister, required by the `admin_` endpoints
+    Owner,
+    /// Granted to the minter canister, required by the `minter_` endpoints and to modify contracts
+    Minter,
+}
 
 /// These are the arguments which are taken by the deferred data canister at creation
 #[derive(Debug, Clone, CandidType, Deserialize)]
@@ -13,3 +47,55 @@ pub struct DeferredDataInitData {
     /// minter canister
     pub minter: Principal,
 }
+
+/// Read-only mode of the deferred data canister, in which every write is rejected
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ReadOnlyMode {
+    /// Principal who enabled the read-only mode
+    pub enabled_by: Principal,
+    /// Time when the read-only mode has been enabled (nanoseconds)
+    pub enabled_at: u64,
+    /// Reason why the read-only mode has been enabled
+    pub reason: String,
+}
+
+/// Storable wrapper of the read-only mode, which is `None` when the canister is writable
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorableReadOnlyMode(pub Option<ReadOnlyMode>);
+
+impl Storable for StorableReadOnlyMode {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self.0).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Self(Decode!(&bytes, Option<ReadOnlyMode>).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_read_only_mode() {
+        let mode = StorableReadOnlyMode(Some(ReadOnlyMode {
+            enabled_by: Principal::management_canister(),
+            enabled_at: 1_000,
+            reason: "migration".to_string(),
+        }));
+
+        let data = mode.to_bytes();
+        let decoded = StorableReadOnlyMode::from_bytes(data);
+        assert_eq!(mode, decoded);
+
+        let mode = StorableReadOnlyMode::default();
+        assert_eq!(StorableReadOnlyMode::from_bytes(mode.to_bytes()), mode);
+    }
+}
@@ -0,0 +1,259 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ethers_core::utils::keccak256;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use crate::ID;
+
+/// A leaf of the Merkle tree of the documents of a contract
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DocumentLeaf {
+    pub document_id: u64,
+    /// SHA-256 of the data of the document (hex)
+    pub sha256: String,
+}
+
+impl DocumentLeaf {
+    /// Hash of the leaf, as `keccak256(keccak256(abi.encode(uint256 documentId, bytes32 sha256)))`,
+    /// so that the tree can be verified with the OpenZeppelin `MerkleProof` library.
+    ///
+    /// Returns `None` if the SHA-256 is not a valid 32 bytes hex string
+    pub fn hash(&self) -> Option<[u8; 32]> {
+        let sha256 = hex::decode(&self.sha256).ok()?;
+        if sha256.len() != 32 {
+            return None;
+        }
+
+        let mut encoded = [0u8; 64];
+        encoded[24..32].copy_from_slice(&self.document_id.to_be_bytes());
+        encoded[32..].copy_from_slice(&sha256);
+
+        Some(keccak256(keccak256(encoded)))
+    }
+}
+
+/// The Merkle root of the documents of a contract, anchored on Ethereum
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DocumentsAnchor {
+    pub contract_id: ID,
+    /// Merkle root of the leaves (0x-prefixed hex)
+    pub root: String,
+    /// Leaves of the tree, in order
+    pub leaves: Vec<DocumentLeaf>,
+    /// Hash of the transaction which emitted the `DocumentsAnchored` event (0x-prefixed hex)
+    pub transaction_hash: String,
+    pub chain_id: u64,
+    /// Time when the root was anchored (nanoseconds)
+    pub anchored_at: u64,
+}
+
+impl Storable for DocumentsAnchor {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// The proof that a document is included in the anchored Merkle root of its contract
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DocumentProof {
+    pub document_id: u64,
+    /// SHA-256 of the data of the document when it was anchored (hex)
+    pub sha256: String,
+    /// Hashes of the siblings from the leaf to the root (0x-prefixed hex)
+    pub proof: Vec<String>,
+    /// Anchored Merkle root (0x-prefixed hex)
+    pub root: String,
+    /// Hash of the anchoring transaction (0x-prefixed hex)
+    pub transaction_hash: String,
+    pub chain_id: u64,
+    /// Time when the root was anchored (nanoseconds)
+    pub anchored_at: u64,
+}
+
+/// Merkle tree of the documents of a contract.
+///
+/// The pairs of nodes are hashed sorted, and a node without sibling is moved to the upper layer
+/// as it is.
+pub struct DocumentsMerkleTree {
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl DocumentsMerkleTree {
+    /// Build the tree of the leaves.
+    ///
+    /// Returns `None` if there are no leaves or any of them has an invalid hash
+    pub fn new(leaves: &[DocumentLeaf]) -> Option<Self> {
+        let leaves = leaves
+            .iter()
+            .map(DocumentLeaf::hash)
+            .collect::<Option<Vec<_>>>()?;
+        if leaves.is_empty() {
+            return None;
+        }
+
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let next = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        Some(Self { layers })
+    }
+
+    /// Merkle root of the tree
+    pub fn root_hash(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// Merkle root of the tree (0x-prefixed hex)
+    pub fn root(&self) -> String {
+        to_hex(&self.root_hash())
+    }
+
+    /// Proof of the inclusion of the leaf at `index`, as the hashes of its siblings (0x-prefixed
+    /// hex)
+    pub fn proof(&self, mut index: usize) -> Vec<String> {
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(to_hex(sibling));
+            }
+            index /= 2;
+        }
+
+        proof
+    }
+
+    /// Verify the proof of the inclusion of a leaf into the tree with the given root
+    pub fn verify(leaf: &DocumentLeaf, proof: &[String], root: &str) -> bool {
+        let Some(mut hash) = leaf.hash() else {
+            return false;
+        };
+        for sibling in proof {
+            let Some(sibling) = from_hex(sibling) else {
+                return false;
+            };
+            hash = hash_pair(&hash, &sibling);
+        }
+
+        to_hex(&hash) == root
+    }
+}
+
+/// Hash a pair of nodes, sorted
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+
+    keccak256(data)
+}
+
+fn to_hex(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn from_hex(hash: &str) -> Option<[u8; 32]> {
+    hex::decode(hash.trim_start_matches("0x"))
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn leaves(count: u64) -> Vec<DocumentLeaf> {
+        (0..count)
+            .map(|document_id| DocumentLeaf {
+                document_id,
+                sha256: hex::encode([document_id as u8; 32]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_should_hash_leaf() {
+        let leaf = DocumentLeaf {
+            document_id: 1,
+            sha256: "ab".repeat(32),
+        };
+        let mut encoded = vec![0u8; 31];
+        encoded.push(1);
+        encoded.extend_from_slice(&[0xab; 32]);
+        assert_eq!(leaf.hash(), Some(keccak256(keccak256(encoded))));
+
+        let invalid = DocumentLeaf {
+            document_id: 1,
+            sha256: "ab".repeat(16),
+        };
+        assert_eq!(invalid.hash(), None);
+    }
+
+    #[test]
+    fn test_should_build_tree_and_verify_proofs() {
+        assert!(DocumentsMerkleTree::new(&[]).is_none());
+
+        for count in 1..=7 {
+            let leaves = leaves(count);
+            let tree = DocumentsMerkleTree::new(&leaves).unwrap();
+            let root = tree.root();
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index);
+                assert!(DocumentsMerkleTree::verify(leaf, &proof, &root));
+
+                let tampered = DocumentLeaf {
+                    document_id: leaf.document_id,
+                    sha256: hex::encode([0xff; 32]),
+                };
+                assert!(!DocumentsMerkleTree::verify(&tampered, &proof, &root));
+            }
+        }
+    }
+
+    #[test]
+    fn test_should_use_leaf_hash_as_root_of_single_leaf() {
+        let leaves = leaves(1);
+        let tree = DocumentsMerkleTree::new(&leaves).unwrap();
+
+        assert_eq!(tree.root(), to_hex(&leaves[0].hash().unwrap()));
+        assert!(tree.proof(0).is_empty());
+    }
+
+    #[test]
+    fn test_should_encode_documents_anchor() {
+        let leaves = leaves(2);
+        let anchor = DocumentsAnchor {
+            contract_id: 1u64.into(),
+            root: DocumentsMerkleTree::new(&leaves).unwrap().root(),
+            leaves,
+            transaction_hash: format!("0x{}", "00".repeat(32)),
+            chain_id: 1,
+            anchored_at: 1_000,
+        };
+
+        let data = anchor.to_bytes();
+        let decoded = DocumentsAnchor::from_bytes(data);
+        assert_eq!(anchor, decoded);
+    }
+}
@@ -0,0 +1,139 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use crate::deferred::ContractDocument;
+use crate::ID;
+
+/// A version of a contract document
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub struct DocumentVersion {
+    /// Number of the version, starting from 1
+    pub version: u64,
+    /// ID of the data of the version in the documents storage
+    pub data_id: u64,
+    /// Document metadata at this version
+    pub document: ContractDocument,
+    /// Time when the version was created (nanoseconds); 0 if unknown
+    pub created_at: u64,
+    /// Principal who created the version; anonymous if unknown
+    pub created_by: Principal,
+    /// Whether the data of the version has been purged from the documents storage
+    pub purged: bool,
+}
+
+/// The version history of a contract document.
+///
+/// The document keeps the ID of its first version for its whole lifetime, while the last
+/// version is the current one.
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub struct DocumentHistory {
+    /// Contract the document belongs to
+    pub contract_id: ID,
+    /// Versions of the document, from the oldest to the current one
+    pub versions: Vec<DocumentVersion>,
+    /// Time when the document was deleted (nanoseconds), if deleted
+    pub deleted_at: Option<u64>,
+}
+
+impl DocumentHistory {
+    /// Get the current version of the document
+    pub fn current(&self) -> Option<&DocumentVersion> {
+        self.versions.last()
+    }
+
+    /// Get a version of the document by its number
+    pub fn version(&self, version: u64) -> Option<&DocumentVersion> {
+        self.versions.iter().find(|entry| entry.version == version)
+    }
+
+    /// Push a new version of the document.
+    ///
+    /// Returns the number of the new version.
+    pub fn push(
+        &mut self,
+        data_id: u64,
+        document: ContractDocument,
+        created_at: u64,
+        created_by: Principal,
+    ) -> u64 {
+        let version = self.versions.len() as u64 + 1;
+        self.versions.push(DocumentVersion {
+            version,
+            data_id,
+            document,
+            created_at,
+            created_by,
+            purged: false,
+        });
+
+        version
+    }
+}
+
+impl Storable for DocumentHistory {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn document(size: u64) -> ContractDocument {
+        ContractDocument {
+            access_list: vec![],
+            mime_type: "application/pdf".to_string(),
+            name: "deed.pdf".to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_should_push_document_versions() {
+        let mut history = DocumentHistory {
+            contract_id: 1u64.into(),
+            versions: vec![],
+            deleted_at: None,
+        };
+        assert!(history.current().is_none());
+
+        assert_eq!(
+            history.push(3, document(10), 1_000, Principal::anonymous()),
+            1
+        );
+        assert_eq!(
+            history.push(7, document(20), 2_000, Principal::anonymous()),
+            2
+        );
+
+        assert_eq!(history.current().unwrap().data_id, 7);
+        assert_eq!(history.version(1).unwrap().data_id, 3);
+        assert!(history.version(3).is_none());
+    }
+
+    #[test]
+    fn test_should_encode_document_history() {
+        let mut history = DocumentHistory {
+            contract_id: 1u64.into(),
+            versions: vec![],
+            deleted_at: Some(3_000),
+        };
+        history.push(3, document(10), 1_000, Principal::management_canister());
+
+        let data = history.to_bytes();
+        let decoded = DocumentHistory::from_bytes(data);
+        assert_eq!(history, decoded);
+    }
+}
@@ -0,0 +1,33 @@
+use candid::{Decode, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+/// Storable wrapper of the custom domains of the deferred data canister
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorableDomains(pub Vec<String>);
+
+impl Storable for StorableDomains {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self.0).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Self(Decode!(&bytes, Vec<String>).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_domains() {
+        let domains = StorableDomains(vec!["docs.ekoke.io".to_string()]);
+        assert_eq!(StorableDomains::from_bytes(domains.to_bytes()), domains);
+    }
+}
@@ -2,12 +2,16 @@ use candid::{CandidType, Deserialize};
 use ic_cdk::api::call::RejectionCode;
 use thiserror::Error;
 
+use super::{AgencyQuota, ExportCollection, Permission};
+use crate::deferred::AgencyId;
 use crate::ID;
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
 pub enum DeferredDataError {
     #[error("unauthorized caller")]
     Unauthorized,
+    #[error("missing permission: {0:?}")]
+    MissingPermission(Permission),
     #[error("contract error: {0}")]
     Contract(ContractError),
     #[error("real estate error: {0}")]
@@ -20,6 +24,12 @@ pub enum DeferredDataError {
     CanisterCall(RejectionCode, String),
     #[error("invalid signature")]
     InvalidSignature,
+    #[error("the canister is in read-only mode")]
+    ReadOnly,
+    #[error("import error: {0}")]
+    Import(ImportError),
+    #[error("signed message error: {0}")]
+    SignedMessage(#[from] SignedMessageError),
 }
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
@@ -32,12 +42,73 @@ pub enum ContractError {
     DocumentNotFound(u64),
     #[error("document size mismatch provided size: {0}, actual size: {1}")]
     DocumentSizeMismatch(u64, u64),
+    #[error("document hash mismatch provided hash: {0}, actual hash: {1}")]
+    DocumentHashMismatch(String, String),
+    #[error("invalid document hash {0}: expected a SHA-256 of 64 hex characters")]
+    InvalidDocumentHash(String),
+    #[error("document upload {0} not found")]
+    UploadNotFound(u64),
+    #[error("version {1} of document {0} not found")]
+    DocumentVersionNotFound(u64, u64),
+    #[error("the data of version {1} of document {0} has been purged")]
+    DocumentPurged(u64, u64),
+    #[error("document {0} has not been anchored")]
+    DocumentNotAnchored(u64),
+    #[error("documents root mismatch provided root: {0}, actual root: {1}")]
+    DocumentsRootMismatch(String, String),
+    #[error("the agency still has open contracts: {0:?}")]
+    AgencyHasOpenContracts(Vec<ID>),
+    #[error("contract {0} doesn't belong to the agency")]
+    NotOwnedByAgency(ID),
+    #[error("agency {0} would exceed its storage quota {1:?}")]
+    AgencyQuotaExceeded(AgencyId, AgencyQuota),
+    #[error("document {0} has no previews")]
+    DocumentPreviewNotFound(u64),
+    #[error("documents of type {0} are not allowed")]
+    MimeTypeNotAllowed(String),
+    #[error("document type mismatch provided type: {0}, actual type: {1}")]
+    MimeTypeMismatch(String, String),
+    #[error("agency {0} not found")]
+    AgencyNotFound(AgencyId),
 }
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
 pub enum RealEstateError {
     #[error("the provided real estate ID ({0}) doesn't exist in the canister storage")]
     NotFound(ID),
+    #[error("real estate {0} doesn't belong to the agency")]
+    NotOwnedByAgency(ID),
+}
+
+#[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
+pub enum ImportError {
+    #[error("the checksum of the chunk doesn't match its content")]
+    InvalidChecksum,
+    #[error("collection {0:?} is not empty")]
+    CollectionNotEmpty(ExportCollection),
+    #[error("the chunk doesn't follow the last imported record of {0:?}")]
+    UnexpectedChunk(ExportCollection),
+    #[error("invalid record in collection {0:?}")]
+    InvalidRecord(ExportCollection),
+}
+
+/// Errors of the messages signed by the sellers and the buyers to access the restricted data
+#[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
+pub enum SignedMessageError {
+    #[error("malformed message: {0}")]
+    Malformed(String),
+    #[error("the message is addressed to another domain: {0}")]
+    DomainMismatch(String),
+    #[error("the message doesn't grant access to contract {0}")]
+    ContractMismatch(ID),
+    #[error("the message is not signed by {0}")]
+    SignerMismatch(String),
+    #[error("the message is not valid yet")]
+    NotYetValid,
+    #[error("the message has expired")]
+    Expired,
+    #[error("nonce {0} has already been used")]
+    NonceAlreadyUsed(String),
 }
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
@@ -46,4 +117,10 @@ pub enum ConfigurationError {
     AnonymousOwner,
     #[error("the minter cannot be anonymous")]
     AnonymousMinter,
+    #[error("the SNS governance cannot be anonymous")]
+    AnonymousSnsGovernance,
+    #[error("invalid MIME type: {0}")]
+    InvalidMimeType(String),
+    #[error("invalid domain: {0}")]
+    InvalidDomain(String),
 }
@@ -0,0 +1,372 @@
+use candid::{CandidType, Deserialize};
+use ethers_core::utils::keccak256;
+
+/// Collections of the deferred data canister which can be exported and imported
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum ExportCollection {
+    Contracts,
+    /// The documents stored by ID, before their data was deduplicated
+    Documents,
+    RealEstates,
+    /// The ID assigned to the next uploaded document, exported as a single record
+    NextDocumentId,
+    /// The version history of the contract documents
+    DocumentHistory,
+    /// The SHA-256 of the stored documents
+    DocumentHashes,
+    /// The anchored Merkle roots of the documents of the contracts
+    DocumentAnchors,
+    /// The data of the documents stored whole, by SHA-256
+    DocumentContents,
+    /// The amount of documents referencing each data, by SHA-256
+    DocumentReferences,
+    /// The storage quotas of the agencies
+    AgencyQuotas,
+    /// The storage used by the documents of each agency
+    AgencyUsage,
+    /// The storage used by the documents of each contract
+    ContractUsage,
+    /// The thumbnails and previews of the image documents
+    DocumentPreviews,
+    /// The data of the documents in chunks, by SHA-256 and chunk index
+    DocumentContentChunks,
+    /// The agency of each member, kept in sync by the minter
+    AgencyMembers,
+    /// The status of the agencies which are not active
+    AgenciesStatus,
+    /// The audit log of the records moved between agencies
+    AgencyTransfersAudit,
+    /// The nonces of the signed messages which have been used, so that they can't be replayed on
+    /// the restored canister
+    ConsumedNonces,
+    /// The schema version of each collection, replacing the ones set by the install
+    SchemaVersions,
+    /// The MIME types allowed for the uploaded documents, exported as a single record
+    AllowedMimeTypes,
+    /// The custom domains of the canister, exported as a single record
+    CustomDomains,
+}
+
+impl ExportCollection {
+    /// All the exportable collections
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Contracts,
+            Self::Documents,
+            Self::RealEstates,
+            Self::NextDocumentId,
+            Self::DocumentHistory,
+            Self::DocumentHashes,
+            Self::DocumentAnchors,
+            Self::DocumentContents,
+            Self::DocumentReferences,
+            Self::AgencyQuotas,
+            Self::AgencyUsage,
+            Self::ContractUsage,
+            Self::DocumentPreviews,
+            Self::DocumentContentChunks,
+            Self::AgencyMembers,
+            Self::AgenciesStatus,
+            Self::AgencyTransfersAudit,
+            Self::ConsumedNonces,
+            Self::SchemaVersions,
+            Self::AllowedMimeTypes,
+            Self::CustomDomains,
+        ]
+    }
+}
+
+/// An exported record, with its key and value encoded as they are stored in stable memory.
+///
+/// A value bigger than a chunk is exported across several chunks, each with a part of it
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ExportRecord {
+    pub key: Vec<u8>,
+    /// The encoded value, or the part of it starting at `offset`
+    pub value: Vec<u8>,
+    /// Offset of `value` in the encoded value
+    pub offset: u64,
+    /// Size of the whole encoded value
+    pub size: u64,
+}
+
+impl ExportRecord {
+    /// Create a record with the whole encoded value
+    pub fn new(key: Vec<u8>, value: Vec<u8>) -> Self {
+        Self {
+            key,
+            offset: 0,
+            size: value.len() as u64,
+            value,
+        }
+    }
+
+    /// Whether the record carries the whole encoded value
+    pub fn is_whole(&self) -> bool {
+        self.offset == 0 && self.is_end()
+    }
+
+    /// Whether the record carries the end of the encoded value
+    pub fn is_end(&self) -> bool {
+        self.offset + self.value.len() as u64 == self.size
+    }
+}
+
+/// Position in an exported collection, following the record with the provided key
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ExportCursor {
+    pub key: Vec<u8>,
+    /// Offset in the value of the record, if it has been exported only up to there
+    pub offset: Option<u64>,
+}
+
+/// A chunk of records of an exported collection, in key order
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ExportChunk {
+    pub collection: ExportCollection,
+    /// Position of the chunk; `None` for the first chunk of the collection
+    pub after: Option<ExportCursor>,
+    /// The records of the chunk, or the part of a single record if its value is bigger than a
+    /// chunk
+    pub records: Vec<ExportRecord>,
+    /// Whether this is the last chunk of the collection
+    pub last: bool,
+    /// Checksum of the chunk (keccak256, hex)
+    pub checksum: String,
+}
+
+impl ExportChunk {
+    /// Create a new chunk, computing its checksum
+    pub fn new(
+        collection: ExportCollection,
+        after: Option<ExportCursor>,
+        records: Vec<ExportRecord>,
+        last: bool,
+    ) -> Self {
+        let mut chunk = Self {
+            collection,
+            after,
+            records,
+            last,
+            checksum: String::default(),
+        };
+        chunk.checksum = chunk.compute_checksum();
+
+        chunk
+    }
+
+    /// Compute the checksum of the chunk, covering the collection, the position and the records
+    pub fn compute_checksum(&self) -> String {
+        let mut data = Vec::new();
+        push_field(&mut data, format!("{:?}", self.collection).as_bytes());
+        match &self.after {
+            Some(after) => {
+                data.push(1);
+                push_field(&mut data, &after.key);
+                match after.offset {
+                    Some(offset) => {
+                        data.push(1);
+                        data.extend_from_slice(&offset.to_be_bytes());
+                    }
+                    None => data.push(0),
+                }
+            }
+            None => data.push(0),
+        }
+        for record in &self.records {
+            push_field(&mut data, &record.key);
+            push_field(&mut data, &record.value);
+            data.extend_from_slice(&record.offset.to_be_bytes());
+            data.extend_from_slice(&record.size.to_be_bytes());
+        }
+        data.push(self.last as u8);
+
+        hex::encode(keccak256(data))
+    }
+
+    /// Whether the checksum matches the content of the chunk
+    pub fn is_valid(&self) -> bool {
+        self.checksum == self.compute_checksum()
+    }
+
+    /// Position to request the chunk following this one from, if any
+    pub fn next(&self) -> Option<ExportCursor> {
+        if self.last {
+            return None;
+        }
+
+        self.records.last().map(|record| ExportCursor {
+            key: record.key.clone(),
+            offset: (!record.is_end()).then_some(record.offset + record.value.len() as u64),
+        })
+    }
+}
+
+/// Number of records and hash of a page of a collection, to compare the state of two canisters.
+///
+/// The digest of a collection is computed a page at a time, so that it fits the instruction limit
+/// of a query
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CollectionDigest {
+    pub collection: ExportCollection,
+    /// Key of the record preceding the page; `None` for the first page of the collection
+    pub after: Option<Vec<u8>>,
+    pub records: u64,
+    /// Hash chained over the records of the page in key order (keccak256, hex)
+    pub hash: String,
+    /// Key to request the next page from; `None` if this is the last page of the collection
+    pub next: Option<Vec<u8>>,
+}
+
+impl CollectionDigest {
+    /// Compute the digest of a page of records of a collection, provided in key order
+    pub fn compute<K, V>(
+        collection: ExportCollection,
+        after: Option<Vec<u8>>,
+        records: impl IntoIterator<Item = (K, V)>,
+        next: Option<Vec<u8>>,
+    ) -> Self
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let mut count = 0;
+        let mut hash = [0u8; 32];
+        for (key, value) in records {
+            let mut data = hash.to_vec();
+            push_field(&mut data, key.as_ref());
+            push_field(&mut data, value.as_ref());
+            hash = keccak256(data);
+            count += 1;
+        }
+
+        Self {
+            collection,
+            after,
+            records: count,
+            hash: hex::encode(hash),
+            next,
+        }
+    }
+}
+
+/// Push a length-prefixed field, so that the boundaries between fields are unambiguous
+fn push_field(data: &mut Vec<u8>, field: &[u8]) {
+    data.extend_from_slice(&(field.len() as u64).to_be_bytes());
+    data.extend_from_slice(field);
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::*;
+
+    fn records() -> Vec<ExportRecord> {
+        vec![
+            ExportRecord::new(vec![1], vec![1, 2, 3]),
+            ExportRecord::new(vec![2], vec![4, 5, 6]),
+        ]
+    }
+
+    #[test]
+    fn test_should_verify_chunk_checksum() {
+        let mut chunk = ExportChunk::new(ExportCollection::Documents, None, records(), false);
+        assert!(chunk.is_valid());
+        assert_eq!(
+            chunk.next(),
+            Some(ExportCursor {
+                key: vec![2],
+                offset: None
+            })
+        );
+
+        chunk.records[1].value = vec![4, 5, 7];
+        assert!(!chunk.is_valid());
+
+        let after = ExportCursor {
+            key: vec![2],
+            offset: None,
+        };
+        let chunk = ExportChunk::new(ExportCollection::Documents, Some(after), vec![], true);
+        assert!(chunk.is_valid());
+        assert_eq!(chunk.next(), None);
+    }
+
+    #[test]
+    fn test_should_continue_split_record() {
+        let part = ExportRecord {
+            key: vec![3],
+            value: vec![1, 2],
+            offset: 2,
+            size: 6,
+        };
+        assert!(!part.is_whole());
+        assert!(!part.is_end());
+
+        let mut chunk = ExportChunk::new(ExportCollection::Documents, None, vec![part], false);
+        assert_eq!(
+            chunk.next(),
+            Some(ExportCursor {
+                key: vec![3],
+                offset: Some(4)
+            })
+        );
+
+        // the offset is covered by the checksum
+        chunk.records[0].offset = 0;
+        assert!(!chunk.is_valid());
+
+        let end = ExportRecord {
+            key: vec![3],
+            value: vec![5, 6],
+            offset: 4,
+            size: 6,
+        };
+        assert!(end.is_end());
+        let chunk = ExportChunk::new(ExportCollection::Documents, None, vec![end], false);
+        assert_eq!(
+            chunk.next(),
+            Some(ExportCursor {
+                key: vec![3],
+                offset: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_should_compute_collection_digest() {
+        let records = records();
+        let digest = CollectionDigest::compute(
+            ExportCollection::Documents,
+            None,
+            records
+                .iter()
+                .map(|record| (record.key.as_slice(), record.value.as_slice())),
+            None,
+        );
+        assert_eq!(digest.records, 2);
+
+        let other = CollectionDigest::compute(
+            ExportCollection::Documents,
+            None,
+            records
+                .iter()
+                .rev()
+                .map(|record| (record.key.as_slice(), record.value.as_slice())),
+            None,
+        );
+        assert_eq!(other.records, 2);
+        assert_ne!(digest.hash, other.hash);
+
+        let empty = CollectionDigest::compute(
+            ExportCollection::Documents,
+            None,
+            Vec::<(Vec<u8>, Vec<u8>)>::new(),
+            None,
+        );
+        assert_eq!(empty.records, 0);
+        assert_eq!(empty.hash, hex::encode([0u8; 32]));
+    }
+}
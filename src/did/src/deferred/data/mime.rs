@@ -0,0 +1,67 @@
+use candid::{Decode, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+/// MIME types of the documents which can be uploaded until the owner sets the allowlist: PDF,
+/// images and office documents
+pub const DEFAULT_ALLOWED_MIME_TYPES: &[&str] = &[
+    "application/pdf",
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "application/msword",
+    "application/vnd.ms-excel",
+    "application/vnd.ms-powerpoint",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/vnd.oasis.opendocument.text",
+    "application/vnd.oasis.opendocument.spreadsheet",
+    "application/vnd.oasis.opendocument.presentation",
+];
+
+/// Storable wrapper of the MIME types allowed for the uploaded documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorableMimeTypes(pub Vec<String>);
+
+impl Default for StorableMimeTypes {
+    fn default() -> Self {
+        Self(
+            DEFAULT_ALLOWED_MIME_TYPES
+                .iter()
+                .map(|mime_type| mime_type.to_string())
+                .collect(),
+        )
+    }
+}
+
+impl Storable for StorableMimeTypes {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self.0).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Self(Decode!(&bytes, Vec<String>).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_mime_types() {
+        let mime_types = StorableMimeTypes::default();
+        assert_eq!(mime_types.0.len(), DEFAULT_ALLOWED_MIME_TYPES.len());
+        assert_eq!(
+            StorableMimeTypes::from_bytes(mime_types.to_bytes()),
+            mime_types
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::str::FromStr;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+/// Size of an image derived from an image document
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ImageSize {
+    Thumbnail,
+    Preview,
+}
+
+impl ImageSize {
+    /// Maximum width and height of the image, in pixels
+    pub fn max_dimension(&self) -> u32 {
+        match self {
+            Self::Thumbnail => 256,
+            Self::Preview => 1024,
+        }
+    }
+}
+
+impl FromStr for ImageSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "thumb" => Ok(Self::Thumbnail),
+            "preview" => Ok(Self::Preview),
+            _ => Err(format!("unknown image size {s}")),
+        }
+    }
+}
+
+/// An image derived from an image document, resized to fit its [`ImageSize`]
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DocumentImage {
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// The images derived from an image document when it's uploaded
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DocumentPreviews {
+    pub thumbnail: DocumentImage,
+    pub preview: DocumentImage,
+}
+
+impl DocumentPreviews {
+    /// Get the image of the provided size
+    pub fn into_image(self, size: ImageSize) -> DocumentImage {
+        match size {
+            ImageSize::Thumbnail => self.thumbnail,
+            ImageSize::Preview => self.preview,
+        }
+    }
+}
+
+impl Storable for DocumentPreviews {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn image(width: u32) -> DocumentImage {
+        DocumentImage {
+            mime_type: "image/jpeg".to_string(),
+            width,
+            height: width / 2,
+            data: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_should_parse_image_size() {
+        assert_eq!(ImageSize::from_str("thumb"), Ok(ImageSize::Thumbnail));
+        assert_eq!(ImageSize::from_str("preview"), Ok(ImageSize::Preview));
+        assert!(ImageSize::from_str("full").is_err());
+    }
+
+    #[test]
+    fn test_should_encode_previews() {
+        let previews = DocumentPreviews {
+            thumbnail: image(256),
+            preview: image(1024),
+        };
+        assert_eq!(
+            DocumentPreviews::from_bytes(previews.to_bytes()),
+            previews.clone()
+        );
+        assert_eq!(previews.into_image(ImageSize::Preview), image(1024));
+    }
+}
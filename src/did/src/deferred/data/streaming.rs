@@ -0,0 +1,78 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use crate::ID;
+
+/// The access to a restricted contract document over HTTP, opened by the request which consumed
+/// the nonce of the signed message.
+///
+/// Every request of the document with the signed message, such as the `Range` requests of a
+/// player, is served until the session expires, while the chunks of the streamed bodies are
+/// served only to the streaming tokens carrying its id
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct StreamingSession {
+    /// Random id of the session (hex), which can't be guessed from the signed message
+    pub id: String,
+    /// Contract of the document
+    pub contract_id: ID,
+    /// Document which the session grants access to
+    pub document_id: u64,
+    /// Expiration time of the signed message (nanoseconds), after which the session is removed
+    pub expires_at: u64,
+}
+
+impl StreamingSession {
+    /// Whether the session grants access to the document of the contract
+    pub fn grants(&self, contract_id: &ID, document_id: u64) -> bool {
+        &self.contract_id == contract_id && self.document_id == document_id
+    }
+}
+
+impl Storable for StreamingSession {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn session() -> StreamingSession {
+        StreamingSession {
+            id: "ab".repeat(32),
+            contract_id: 1u64.into(),
+            document_id: 2,
+            expires_at: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_should_encode_streaming_session() {
+        let session = session();
+
+        let data = session.to_bytes();
+        let decoded = StreamingSession::from_bytes(data);
+        assert_eq!(session, decoded);
+    }
+
+    #[test]
+    fn test_should_grant_access_to_the_document_of_the_session() {
+        let session = session();
+
+        assert!(session.grants(&1u64.into(), 2));
+        assert!(!session.grants(&1u64.into(), 3));
+        assert!(!session.grants(&2u64.into(), 2));
+    }
+}
@@ -0,0 +1,68 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use crate::deferred::ContractDocument;
+use crate::ID;
+
+/// A contract document being uploaded in chunks, until the upload is finalized
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub struct DocumentUpload {
+    /// Contract the document is uploaded to
+    pub contract_id: ID,
+    /// Document metadata, with the declared size of the document
+    pub document: ContractDocument,
+    /// Declared SHA-256 of the document (hex)
+    pub sha256: String,
+    /// Principal who began the upload; the only one allowed to send the chunks
+    pub uploaded_by: Principal,
+    /// Time when the upload began (nanoseconds)
+    pub started_at: u64,
+    /// Number of bytes received so far
+    pub received: u64,
+    /// Number of chunks received so far
+    pub chunks: u64,
+}
+
+impl Storable for DocumentUpload {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_document_upload() {
+        let upload = DocumentUpload {
+            contract_id: 1u64.into(),
+            document: ContractDocument {
+                access_list: vec![],
+                mime_type: "application/pdf".to_string(),
+                name: "deed.pdf".to_string(),
+                size: 4_000_000,
+            },
+            sha256: "00".repeat(32),
+            uploaded_by: Principal::management_canister(),
+            started_at: 1_000,
+            received: 2_000_000,
+            chunks: 1,
+        };
+
+        let data = upload.to_bytes();
+        let decoded = DocumentUpload::from_bytes(data);
+        assert_eq!(upload, decoded);
+    }
+}
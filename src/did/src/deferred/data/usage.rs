@@ -0,0 +1,161 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use crate::deferred::AgencyId;
+
+/// Storage used by the documents of an agency or of a contract.
+///
+/// Each version of a document with its own data is counted until it's purged, even if other
+/// documents share the same data
+#[derive(Clone, Copy, Debug, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub documents: u64,
+    pub document_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Usage of a single document of `size` bytes
+    pub fn document(size: u64) -> Self {
+        Self {
+            documents: 1,
+            document_bytes: size,
+        }
+    }
+
+    /// Sum of the two usages
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self {
+            documents: self.documents.saturating_add(other.documents),
+            document_bytes: self.document_bytes.saturating_add(other.document_bytes),
+        }
+    }
+
+    /// Difference of the two usages, stopping at zero
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self {
+            documents: self.documents.saturating_sub(other.documents),
+            document_bytes: self.document_bytes.saturating_sub(other.document_bytes),
+        }
+    }
+}
+
+impl Storable for StorageUsage {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Storage quota of an agency
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AgencyQuota {
+    /// Maximum amount of documents, counting each version with its own data
+    pub max_documents: u64,
+    /// Maximum amount of bytes of the documents
+    pub max_document_bytes: u64,
+}
+
+impl AgencyQuota {
+    /// Returns whether the usage is within the quota
+    pub fn allows(&self, usage: &StorageUsage) -> bool {
+        usage.documents <= self.max_documents && usage.document_bytes <= self.max_document_bytes
+    }
+
+    /// Describe setting the quota of `agency` to `quota`, or removing it, for the proposal payload
+    pub fn describe_change(agency: AgencyId, quota: Option<&Self>) -> String {
+        match quota {
+            Some(quota) => format!(
+                "Set storage quota of agency {agency} to {} documents and {} bytes",
+                quota.max_documents, quota.max_document_bytes
+            ),
+            None => format!("Remove storage quota of agency {agency}"),
+        }
+    }
+}
+
+impl Storable for AgencyQuota {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Storage usage of an agency, with its quota
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AgencyUsage {
+    pub agency: AgencyId,
+    pub usage: StorageUsage,
+    /// `None` if the agency has no quota
+    pub quota: Option<AgencyQuota>,
+    /// Open contracts of the agency
+    pub contracts: u64,
+    /// Real estates of the agency which haven't been deleted
+    pub real_estates: u64,
+    /// Estimated cycles burned each day to store the documents of the agency
+    pub storage_cycles_per_day: u64,
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_sum_and_subtract_usage() {
+        let usage = StorageUsage::document(10).saturating_add(StorageUsage::document(5));
+        assert_eq!(
+            usage,
+            StorageUsage {
+                documents: 2,
+                document_bytes: 15,
+            }
+        );
+        assert_eq!(
+            usage.saturating_sub(StorageUsage::document(5)),
+            StorageUsage::document(10)
+        );
+        assert_eq!(
+            StorageUsage::document(5).saturating_sub(usage),
+            StorageUsage::default()
+        );
+    }
+
+    #[test]
+    fn test_should_check_quota() {
+        let quota = AgencyQuota {
+            max_documents: 2,
+            max_document_bytes: 100,
+        };
+        assert!(quota.allows(&StorageUsage::document(100)));
+        assert!(!quota.allows(&StorageUsage::document(101)));
+        assert!(!quota.allows(&StorageUsage {
+            documents: 3,
+            document_bytes: 3,
+        }));
+    }
+
+    #[test]
+    fn test_should_encode_usage_and_quota() {
+        let usage = StorageUsage::document(10);
+        assert_eq!(StorageUsage::from_bytes(usage.to_bytes()), usage);
+
+        let quota = AgencyQuota {
+            max_documents: 2,
+            max_document_bytes: 100,
+        };
+        assert_eq!(AgencyQuota::from_bytes(quota.to_bytes()), quota);
+    }
+}
@@ -0,0 +1,104 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+/// Schema version of a collection of records stored by a canister
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CollectionSchema {
+    /// Name of the collection
+    pub collection: String,
+    /// Schema version of the records currently stored
+    pub version: u32,
+    /// Schema version of the records written by the installed wasm
+    pub target_version: u32,
+}
+
+/// Progress of the migration of a collection to a newer schema version
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct MigrationProgress {
+    /// Name of the migrated collection
+    pub collection: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Number of records already migrated
+    pub migrated: u64,
+    /// Number of records in the collection when the migration started
+    pub total: u64,
+    /// Time when the migration started (nanoseconds)
+    pub started_at: u64,
+    /// Encoded key of the last migrated record; `None` if no record has been migrated yet
+    pub cursor: Option<Vec<u8>>,
+}
+
+impl Storable for MigrationProgress {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Schema versions of the collections of a canister and the migrations still to be completed
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub collections: Vec<CollectionSchema>,
+    /// Migrations still running, in the order they are processed
+    pub pending: Vec<MigrationProgress>,
+}
+
+impl MigrationStatus {
+    /// Whether every collection is at its target schema version
+    pub fn is_completed(&self) -> bool {
+        self.pending.is_empty()
+            && self
+                .collections
+                .iter()
+                .all(|schema| schema.version == schema.target_version)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_migration_progress() {
+        let progress = MigrationProgress {
+            collection: "contracts".to_string(),
+            from_version: 0,
+            to_version: 1,
+            migrated: 50,
+            total: 120,
+            started_at: 1_000,
+            cursor: Some(vec![0, 50]),
+        };
+
+        let data = progress.to_bytes();
+        let decoded = MigrationProgress::from_bytes(data);
+        assert_eq!(progress, decoded);
+    }
+
+    #[test]
+    fn test_should_tell_whether_migration_is_completed() {
+        let mut status = MigrationStatus {
+            collections: vec![CollectionSchema {
+                collection: "contracts".to_string(),
+                version: 0,
+                target_version: 1,
+            }],
+            pending: vec![],
+        };
+        assert!(!status.is_completed());
+
+        status.collections[0].version = 1;
+        assert!(status.is_completed());
+    }
+}
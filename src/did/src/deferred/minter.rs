@@ -1,4 +1,8 @@
+mod configuration;
 mod error;
+mod pause;
+mod proposal;
+mod reward_pool;
 
 use std::fmt;
 
@@ -7,8 +11,21 @@ use ic_log::LogSettingsV2;
 use ic_stable_structures::storable::Bound;
 use ic_stable_structures::Storable;
 
+pub use self::configuration::{
+    ConfigurationChange, ConfigurationUpdate, DeferredMinterConfiguration,
+};
 pub use self::error::{
-    CloseContractError, ConfigurationError, ContractError, DeferredMinterError, EcdsaError,
+    AgencyError, CloseContractError, ConfigurationError, ContractError, DeferredMinterError,
+    EcdsaError, ProposalError,
+};
+pub use self::pause::{PausableOperation, PauseState, MAX_PAUSE_REASON_LEN};
+pub use self::proposal::{
+    Proposal, ProposalAction, ProposalConfig, ProposalStatus, DEFAULT_PROPOSAL_THRESHOLD,
+    DEFAULT_PROPOSAL_TIMEOUT,
+};
+pub use self::reward_pool::{
+    RewardParameters, RewardPoolAlert, RewardPoolAlertKind, RewardPoolStatus, RewardReservation,
+    RewardReservationStatus,
 };
 use crate::H160;
 
@@ -35,6 +52,8 @@ pub struct DeferredMinterInitData {
     pub log_settings: LogSettingsV2,
     /// Ethereum address of reward pool contract
     pub reward_pool: H160,
+    /// Block where the reward pool contract has been deployed. Its events are read from here
+    pub reward_pool_deploy_block: u64,
 }
 
 #[repr(u8)]
@@ -66,22 +85,104 @@ impl fmt::Display for EcdsaKey {
     }
 }
 
-/// Deferred user roles. Defines permissions
-#[derive(Clone, Copy, Debug, PartialEq, Eq, CandidType, Deserialize)]
+/// A single operation which can be granted to a principal through its roles
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize)]
+pub enum Permission {
+    /// Register, suspend and remove agencies and review their applications and transfers
+    ManageAgencies,
+    /// Set the currencies allowed for contracts
+    ManageCurrencies,
+    /// Give and remove roles and define custom roles
+    ManageRoles,
+    /// Manage the canister configuration and the reward pool ledger
+    ManageConfig,
+    /// Set the gas price
+    SetGas,
+    /// Read the canister logs, configuration, cycles and reward pool status
+    ReadLogs,
+    /// Close any contract, regardless of the agency which created it
+    CloseAnyContract,
+    /// Create contracts and real estates for the agency the principal belongs to
+    CreateContracts,
+}
+
+impl Permission {
+    /// All the available permissions
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::ManageAgencies,
+            Self::ManageCurrencies,
+            Self::ManageRoles,
+            Self::ManageConfig,
+            Self::SetGas,
+            Self::ReadLogs,
+            Self::CloseAnyContract,
+            Self::CreateContracts,
+        ]
+    }
+}
+
+/// List of permissions granted by a custom role
+#[derive(Clone, Debug, Default, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Permissions(pub Vec<Permission>);
+
+impl From<Vec<Permission>> for Permissions {
+    fn from(mut permissions: Vec<Permission>) -> Self {
+        permissions.sort();
+        permissions.dedup();
+        Self(permissions)
+    }
+}
+
+impl Storable for Permissions {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Vec<Permission>).unwrap().into()
+    }
+}
+
+/// Deferred user roles. Each role is a set of permissions.
+///
+/// The predefined roles grant a fixed set of permissions, while the permissions of custom roles
+/// are defined by the custodians.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
 pub enum Role {
-    /// Administrator, follows DIP721 standard
+    /// Administrator, follows DIP721 standard. Has every permission
     Custodian,
     /// A user who can create contracts, but cannot sign them
     Agent,
     /// A user who can set the gas price
     GasStation,
+    /// A role defined by the custodians, identified by its name
+    Custom(String),
+}
+
+impl Role {
+    /// Permissions granted by a predefined role.
+    ///
+    /// Returns `None` for custom roles, whose permissions are stored in the canister
+    pub fn predefined_permissions(&self) -> Option<Vec<Permission>> {
+        match self {
+            Self::Custodian => Some(Permission::all()),
+            Self::Agent => Some(vec![Permission::CreateContracts]),
+            Self::GasStation => Some(vec![Permission::SetGas]),
+            Self::Custom(_) => None,
+        }
+    }
+
+    /// The predefined roles
+    pub fn predefined() -> Vec<Self> {
+        vec![Self::Custodian, Self::Agent, Self::GasStation]
+    }
 }
 
 impl Storable for Role {
-    const BOUND: Bound = Bound::Bounded {
-        max_size: 8,
-        is_fixed_size: true,
-    };
+    const BOUND: Bound = Bound::Unbounded;
 
     fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
         Encode!(&self).unwrap().into()
@@ -92,6 +193,13 @@ impl Storable for Role {
     }
 }
 
+/// A role with the permissions it grants
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub struct RoleDefinition {
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+}
+
 /// List of roles
 #[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
 pub struct Roles(pub Vec<Role>);
@@ -130,4 +238,55 @@ mod test {
         let decoded_role = Roles::from_bytes(data);
         assert_eq!(role, decoded_role);
     }
+
+    #[test]
+    fn test_should_decode_roles_stored_before_custom_roles() {
+        #[derive(CandidType)]
+        enum LegacyRole {
+            Custodian,
+            Agent,
+            GasStation,
+        }
+
+        let data = Encode!(&vec![
+            LegacyRole::Custodian,
+            LegacyRole::Agent,
+            LegacyRole::GasStation
+        ])
+        .unwrap();
+        let decoded = Roles::from_bytes(data.into());
+        assert_eq!(
+            decoded,
+            vec![Role::Custodian, Role::Agent, Role::GasStation].into()
+        );
+    }
+
+    #[test]
+    fn test_should_encode_permissions() {
+        let permissions: Permissions =
+            vec![Permission::SetGas, Permission::ReadLogs, Permission::SetGas].into();
+        assert_eq!(
+            permissions,
+            Permissions(vec![Permission::SetGas, Permission::ReadLogs])
+        );
+
+        let data = permissions.to_bytes();
+        assert_eq!(Permissions::from_bytes(data), permissions);
+    }
+
+    #[test]
+    fn test_should_get_predefined_permissions() {
+        assert_eq!(
+            Role::Custodian.predefined_permissions(),
+            Some(Permission::all())
+        );
+        assert_eq!(
+            Role::GasStation.predefined_permissions(),
+            Some(vec![Permission::SetGas])
+        );
+        assert_eq!(
+            Role::Custom("auditor".to_string()).predefined_permissions(),
+            None
+        );
+    }
 }
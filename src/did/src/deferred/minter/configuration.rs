@@ -0,0 +1,123 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_log::LogSettingsV2;
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use super::EcdsaKey;
+use crate::H160;
+
+/// Current configuration of the deferred minter canister
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DeferredMinterConfiguration {
+    /// The list of allowed currencies
+    pub allowed_currencies: Vec<String>,
+    /// Ethereum chain id
+    pub chain_id: u64,
+    /// Principal of deferred-data canister
+    pub deferred_data: Principal,
+    /// Ethereum address of deferred-erc721 contract
+    pub deferred_erc721: H160,
+    /// ethereum ecdsa key
+    pub ecdsa_key: EcdsaKey,
+    /// Principal of evm-rpc canister
+    pub evm_rpc: Principal,
+    /// Custom evm rpc api
+    pub evm_rpc_api: Option<String>,
+    /// Gas price (wei)
+    pub gas_price: u64,
+    /// Log settings
+    pub log_settings: LogSettingsV2,
+    /// Ethereum address of reward pool contract
+    pub reward_pool: H160,
+    /// Block where the reward pool contract has been deployed
+    pub reward_pool_deploy_block: u64,
+}
+
+/// Partial update of the deferred minter configuration. Each field is left unchanged when `None`
+#[derive(Debug, Default, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ConfigurationUpdate {
+    /// Ethereum chain id
+    pub chain_id: Option<u64>,
+    /// Principal of deferred-data canister
+    pub deferred_data: Option<Principal>,
+    /// Ethereum address of deferred-erc721 contract
+    pub deferred_erc721: Option<H160>,
+    /// ethereum ecdsa key
+    pub ecdsa_key: Option<EcdsaKey>,
+    /// Principal of evm-rpc canister
+    pub evm_rpc: Option<Principal>,
+    /// Custom evm rpc api. An empty string removes the custom api
+    pub evm_rpc_api: Option<String>,
+    /// Log settings. They are applied on the next upgrade, unless they are set by the upgrade
+    pub log_settings: Option<LogSettingsV2>,
+    /// Ethereum address of reward pool contract
+    pub reward_pool: Option<H160>,
+    /// Block where the reward pool contract has been deployed
+    pub reward_pool_deploy_block: Option<u64>,
+}
+
+impl ConfigurationUpdate {
+    /// Returns whether the update doesn't change any field
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Change of a configuration field, with the old and the new value
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ConfigurationChange {
+    /// Principal who applied the change
+    pub author: Principal,
+    /// Changed field
+    pub field: String,
+    pub new_value: String,
+    pub old_value: String,
+    /// Time of the change (nanoseconds)
+    pub timestamp: u64,
+}
+
+impl Storable for ConfigurationChange {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_configuration_change() {
+        let change = ConfigurationChange {
+            author: Principal::management_canister(),
+            field: "chain_id".to_string(),
+            new_value: "11155111".to_string(),
+            old_value: "1".to_string(),
+            timestamp: 1_000,
+        };
+
+        let data = change.to_bytes();
+        let decoded = ConfigurationChange::from_bytes(data);
+        assert_eq!(change, decoded);
+    }
+
+    #[test]
+    fn test_should_tell_whether_update_is_empty() {
+        assert!(ConfigurationUpdate::default().is_empty());
+        assert!(!ConfigurationUpdate {
+            chain_id: Some(1),
+            ..Default::default()
+        }
+        .is_empty());
+    }
+}
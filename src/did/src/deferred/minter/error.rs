@@ -1,20 +1,30 @@
-use candid::{CandidType, Deserialize};
+use candid::{CandidType, Deserialize, Principal};
 use ic_cdk::api::call::RejectionCode;
 use thiserror::Error;
 
+use super::{PausableOperation, Permission};
 use crate::deferred::data::DeferredDataError;
+use crate::deferred::AgencyId;
 use crate::ID;
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
 pub enum DeferredMinterError {
     #[error("unauthorized caller")]
     Unauthorized,
+    #[error("missing permission: {0:?}")]
+    MissingPermission(Permission),
     #[error("contract error: {0}")]
     Contract(ContractError),
+    #[error("agency error: {0}")]
+    Agency(#[from] AgencyError),
     #[error("close contract error: {0}")]
     CloseContract(#[from] CloseContractError),
     #[error("configuration error: {0}")]
     Configuration(#[from] ConfigurationError),
+    #[error("proposal error: {0}")]
+    Proposal(#[from] ProposalError),
+    #[error("operation {0:?} is paused")]
+    Paused(PausableOperation),
     #[error("storage error")]
     StorageError,
     #[error("inter-canister call error: ({0:?}): {1}")]
@@ -57,6 +67,68 @@ pub enum ContractError {
     CurrencyNotAllowed(String),
     #[error("you cannot operate to this real estate")]
     BadRealEstateId,
+    #[error("contract {0} has no documents")]
+    ContractHasNoDocuments(ID),
+}
+
+#[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
+pub enum AgencyError {
+    #[error("agency {0} not found")]
+    AgencyNotFound(AgencyId),
+    #[error("agency {0} is already registered")]
+    AgencyAlreadyRegistered(Principal),
+    #[error("{0} is already a member of an agency")]
+    AlreadyMember(Principal),
+    #[error("{0} is not a member of the agency")]
+    NotAMember(Principal),
+    #[error("{0} is not an admin of the agency")]
+    NotAnAdmin(Principal),
+    #[error("{0} is not the owner of the agency")]
+    NotTheOwner(Principal),
+    #[error("{0} cannot operate on the records of agency {1}")]
+    CannotOperateAgency(Principal, AgencyId),
+    #[error("the agency must have at least one admin")]
+    LastAdmin,
+    #[error("agency {0} is suspended")]
+    AgencySuspended(AgencyId),
+    #[error("agency {0} is not suspended")]
+    AgencyNotSuspended(AgencyId),
+    #[error("agency transfer {0} not found")]
+    TransferNotFound(u64),
+    #[error("agency transfer {0} is not pending")]
+    TransferNotPending(u64),
+    #[error("agency transfer {0} hasn't been accepted by both agencies")]
+    TransferNotAccepted(u64),
+    #[error("invalid agency transfer: {0}")]
+    InvalidTransfer(String),
+    #[error("agency {0} is not part of agency transfer {1}")]
+    NotPartOfTransfer(AgencyId, u64),
+    #[error("agency application {0} not found")]
+    ApplicationNotFound(u64),
+    #[error("{0} is not the applicant of the agency application")]
+    NotTheApplicant(Principal),
+    #[error("agency application {0} has already been reviewed")]
+    ApplicationAlreadyReviewed(u64),
+    #[error("a pending agency application already exists for {0}")]
+    ApplicationAlreadyPending(Principal),
+    #[error("agency application documents exceed the maximum size of {0} bytes")]
+    ApplicationDocumentsTooLarge(u64),
+    #[error("agency application {0} has no document {1}")]
+    ApplicationDocumentNotFound(u64, u64),
+    #[error("there are too many pending agency applications; try again later")]
+    TooManyPendingApplications,
+    #[error("{0} has already submitted the maximum number of agency applications")]
+    TooManyApplications(Principal),
+    #[error("agency field {0} cannot be empty")]
+    EmptyField(String),
+    #[error("invalid email: {0}")]
+    InvalidEmail(String),
+    #[error("invalid url: {0}")]
+    InvalidUrl(String),
+    #[error("invalid latitude: {0}")]
+    InvalidLatitude(String),
+    #[error("invalid longitude: {0}")]
+    InvalidLongitude(String),
 }
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
@@ -65,6 +137,38 @@ pub enum ConfigurationError {
     CustodialsCantBeEmpty,
     #[error("the canister custodial cannot be anonymous")]
     AnonymousCustodial,
+    #[error("the role {0} is not defined")]
+    UnknownRole(String),
+    #[error("invalid role name: {0}")]
+    InvalidRoleName(String),
+    #[error("the {0} cannot be anonymous")]
+    AnonymousPrincipal(String),
+    #[error("the {0} address cannot be zero")]
+    ZeroAddress(String),
+    #[error("unsupported chain id {0}")]
+    UnsupportedChainId(u64),
+    #[error("invalid evm rpc api: {0}")]
+    InvalidEvmRpcApi(String),
+    #[error("invalid reward parameters: {0}")]
+    InvalidRewardParameters(String),
+}
+
+#[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
+pub enum ProposalError {
+    #[error("proposal {0} not found")]
+    ProposalNotFound(u64),
+    #[error("proposal {0} is not pending")]
+    ProposalNotPending(u64),
+    #[error("proposal {0} has expired")]
+    ProposalExpired(u64),
+    #[error("proposal {0} has already been approved by the caller")]
+    AlreadyApproved(u64),
+    #[error("{0} is not a custodian")]
+    NotACustodian(Principal),
+    #[error("invalid proposal threshold: {0}")]
+    InvalidThreshold(u32),
+    #[error("invalid proposal: {0}")]
+    InvalidProposal(String),
 }
 
 #[derive(Clone, Debug, Error, CandidType, PartialEq, Eq, Deserialize)]
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+/// Maximum length of the reason of a pause (characters)
+pub const MAX_PAUSE_REASON_LEN: usize = 256;
+
+/// Class of operations of the minter which can be paused by the custodians
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize)]
+pub enum PausableOperation {
+    /// Creation of new contracts
+    ContractCreation,
+    /// Closing of the contracts
+    ContractClosing,
+    /// Creation, update and deletion of real estates
+    RealEstateWrites,
+    /// Transactions sent to the Ethereum network
+    EvmTransactions,
+}
+
+impl PausableOperation {
+    /// All the pausable operations
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::ContractCreation,
+            Self::ContractClosing,
+            Self::RealEstateWrites,
+            Self::EvmTransactions,
+        ]
+    }
+}
+
+impl fmt::Display for PausableOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self {
+            Self::ContractCreation => "contract creation",
+            Self::ContractClosing => "contract closing",
+            Self::RealEstateWrites => "real estate writes",
+            Self::EvmTransactions => "EVM transactions",
+        };
+
+        write!(f, "{operation}")
+    }
+}
+
+impl Storable for PausableOperation {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// A paused operation, with who paused it and why
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct PauseState {
+    pub operation: PausableOperation,
+    /// Principal who paused the operation
+    pub paused_by: Principal,
+    /// Time when the operation has been paused (nanoseconds)
+    pub paused_at: u64,
+    /// Reason why the operation has been paused
+    pub reason: String,
+}
+
+impl Storable for PauseState {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_pause_state() {
+        let state = PauseState {
+            operation: PausableOperation::EvmTransactions,
+            paused_by: Principal::management_canister(),
+            paused_at: 1_000,
+            reason: "compromised contract".to_string(),
+        };
+
+        let data = state.to_bytes();
+        let decoded = PauseState::from_bytes(data);
+        assert_eq!(state, decoded);
+
+        for operation in PausableOperation::all() {
+            assert_eq!(
+                PausableOperation::from_bytes(operation.to_bytes()),
+                operation
+            );
+        }
+    }
+
+    #[test]
+    fn test_should_display_pausable_operation() {
+        assert_eq!(
+            PausableOperation::RealEstateWrites.to_string(),
+            "real estate writes"
+        );
+        assert_eq!(
+            PausableOperation::EvmTransactions.to_string(),
+            "EVM transactions"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use super::{ConfigurationUpdate, PausableOperation, Permission, RewardParameters, Role};
+
+/// Default amount of custodian approvals required to execute a proposal
+pub const DEFAULT_PROPOSAL_THRESHOLD: u32 = 1;
+/// Default time after which a pending proposal expires (nanoseconds): 7 days
+pub const DEFAULT_PROPOSAL_TIMEOUT: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+/// A sensitive admin operation which must be approved by the custodians before being executed
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub struct Proposal {
+    /// Proposal id
+    pub id: u64,
+    /// Operation executed once the proposal is approved
+    pub action: ProposalAction,
+    /// Custodians who approved the proposal, including the proposer
+    pub approvals: Vec<Principal>,
+    /// Time after which the proposal can't be approved anymore (nanoseconds)
+    pub expires_at: u64,
+    /// Time when the proposal has been executed, rejected or has failed (nanoseconds)
+    pub closed_at: Option<u64>,
+    /// Custodian who rejected the proposal
+    pub rejected_by: Option<Principal>,
+    /// Reason why the execution of the proposal failed
+    pub failure_reason: Option<String>,
+    /// Time of the proposal (nanoseconds)
+    pub proposed_at: u64,
+    /// Custodian who made the proposal
+    pub proposed_by: Principal,
+    pub status: ProposalStatus,
+}
+
+impl Proposal {
+    /// Returns whether the proposal is still pending, but can't be approved anymore
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.status == ProposalStatus::Pending && now > self.expires_at
+    }
+}
+
+impl Storable for Proposal {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Operation carried by a [`Proposal`]
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub enum ProposalAction {
+    /// Replace the custodians of the canister
+    SetCustodians(Vec<Principal>),
+    /// Give a role to a principal
+    SetRole { principal: Principal, role: Role },
+    /// Remove a role from a principal
+    RemoveRole { principal: Principal, role: Role },
+    /// Define a custom role granting the provided permissions, or update the permissions of an existing one
+    DefineRole {
+        name: String,
+        permissions: Vec<Permission>,
+    },
+    /// Delete a custom role, removing it from the principals which had it
+    DeleteRole(String),
+    /// Set the currencies allowed for contracts
+    SetAllowedCurrencies(Vec<String>),
+    /// Change the approval threshold and the timeout of the proposals
+    SetProposalConfig(ProposalConfig),
+    /// Set the SNS governance canister, or unset it if `None`
+    SetSnsGovernance(Option<Principal>),
+    /// Update the canister configuration
+    UpdateConfiguration(ConfigurationUpdate),
+    /// Resume a paused operation
+    Resume(PausableOperation),
+    /// Change the parameters of the contract reward formula
+    SetRewardParameters(RewardParameters),
+}
+
+impl ProposalAction {
+    /// Permission required to propose the action
+    pub fn permission(&self) -> Permission {
+        match self {
+            Self::SetCustodians(_)
+            | Self::SetRole { .. }
+            | Self::RemoveRole { .. }
+            | Self::DefineRole { .. }
+            | Self::DeleteRole(_)
+            | Self::SetSnsGovernance(_) => Permission::ManageRoles,
+            Self::SetAllowedCurrencies(_) => Permission::ManageCurrencies,
+            Self::SetProposalConfig(_)
+            | Self::UpdateConfiguration(_)
+            | Self::Resume(_)
+            | Self::SetRewardParameters(_) => Permission::ManageConfig,
+        }
+    }
+}
+
+/// Status of a [`Proposal`]
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ProposalStatus {
+    /// Waiting for the custodians approval
+    Pending,
+    /// The threshold has been reached and the action has been executed
+    Executed,
+    /// The threshold has been reached, but the action has failed
+    Failed,
+    /// The proposal has been rejected by a custodian
+    Rejected,
+    /// The threshold hasn't been reached before the timeout
+    Expired,
+}
+
+/// Configuration of the custodian proposals
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ProposalConfig {
+    /// Amount of custodian approvals required to execute a proposal
+    pub threshold: u32,
+    /// Time after which a pending proposal expires (nanoseconds)
+    pub timeout: u64,
+}
+
+impl Default for ProposalConfig {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_PROPOSAL_THRESHOLD,
+            timeout: DEFAULT_PROPOSAL_TIMEOUT,
+        }
+    }
+}
+
+impl Storable for ProposalConfig {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_proposal() {
+        let proposal = Proposal {
+            id: 1,
+            action: ProposalAction::SetRole {
+                principal: Principal::management_canister(),
+                role: Role::Custom("auditor".to_string()),
+            },
+            approvals: vec![Principal::anonymous()],
+            expires_at: 2_000,
+            closed_at: None,
+            rejected_by: None,
+            failure_reason: None,
+            proposed_at: 1_000,
+            proposed_by: Principal::anonymous(),
+            status: ProposalStatus::Pending,
+        };
+
+        let data = proposal.to_bytes();
+        let decoded = Proposal::from_bytes(data);
+        assert_eq!(proposal, decoded);
+
+        assert!(!decoded.is_expired(2_000));
+        assert!(decoded.is_expired(2_001));
+    }
+
+    #[test]
+    fn test_should_get_action_permission() {
+        assert_eq!(
+            ProposalAction::SetCustodians(vec![]).permission(),
+            Permission::ManageRoles
+        );
+        assert_eq!(
+            ProposalAction::RemoveRole {
+                principal: Principal::anonymous(),
+                role: Role::Custodian,
+            }
+            .permission(),
+            Permission::ManageRoles
+        );
+        assert_eq!(
+            ProposalAction::DefineRole {
+                name: "auditor".to_string(),
+                permissions: vec![Permission::ReadLogs],
+            }
+            .permission(),
+            Permission::ManageRoles
+        );
+        assert_eq!(
+            ProposalAction::DeleteRole("auditor".to_string()).permission(),
+            Permission::ManageRoles
+        );
+        assert_eq!(
+            ProposalAction::SetAllowedCurrencies(vec![]).permission(),
+            Permission::ManageCurrencies
+        );
+        assert_eq!(
+            ProposalAction::SetProposalConfig(ProposalConfig::default()).permission(),
+            Permission::ManageConfig
+        );
+        assert_eq!(
+            ProposalAction::Resume(PausableOperation::EvmTransactions).permission(),
+            Permission::ManageConfig
+        );
+        assert_eq!(
+            ProposalAction::SetRewardParameters(RewardParameters {
+                rmc: 0.0000042,
+                avidity: 1.0,
+                next_halving: 0,
+            })
+            .permission(),
+            Permission::ManageConfig
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+
+use crate::ID;
+
+/// Reward reserved by the minter on the reward pool for a contract
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RewardReservation {
+    /// Contract the reward has been reserved for
+    pub contract_id: ID,
+    /// Reward for each token
+    pub reward: u128,
+    /// Amount of tokens of the contract
+    pub tokens: u64,
+    /// Time when the reservation has been made (nanoseconds)
+    pub reserved_at: u64,
+    /// Reconciliation status of the reservation with the chain
+    pub status: RewardReservationStatus,
+}
+
+impl RewardReservation {
+    /// Total amount of reward tokens reserved for the contract
+    pub fn total(&self) -> u128 {
+        self.reward * self.tokens as u128
+    }
+}
+
+impl Storable for RewardReservation {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+/// Reconciliation status of a [`RewardReservation`]
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum RewardReservationStatus {
+    /// The `PoolReserved` event for the reservation hasn't been seen yet
+    Pending,
+    /// The `PoolReserved` event has been found at the given block
+    Confirmed { block: u64 },
+    /// No `PoolReserved` event has been found within the confirmation timeout
+    Missing,
+}
+
+/// An alert raised when the minter view of the reward pool and the chain drift apart
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RewardPoolAlert {
+    /// Time when the alert has been raised (nanoseconds)
+    pub timestamp: u64,
+    pub kind: RewardPoolAlertKind,
+}
+
+impl Storable for RewardPoolAlert {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Encode!(&self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(&bytes, Self).unwrap()
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum RewardPoolAlertKind {
+    /// A `PoolReserved` event has been emitted which doesn't match any reservation of the minter
+    UnknownReservation {
+        reward: u128,
+        tokens: u64,
+        block: Option<u64>,
+    },
+    /// The reservation made for the contract has never been seen on chain
+    MissingReservation { contract_id: ID },
+    /// The available reward on chain has increased, which should never happen
+    AvailableRewardIncreased { previous: u128, current: u128 },
+}
+
+/// Minter view of the reward pool
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RewardPoolStatus {
+    /// Last `availableReward` read from the reward pool
+    pub available_reward: u128,
+    /// Time of the last `availableReward` refresh (nanoseconds)
+    pub available_reward_updated_at: u64,
+    /// Reward reserved by the minter and not yet seen on chain
+    pub pending_reserved: u128,
+    /// Reward reserved by the minter since the ledger has been created
+    pub reserved_by_minter: u128,
+    /// Reward reserved on chain according to the `PoolReserved` events
+    pub reserved_on_chain: u128,
+    /// Reward sent to users according to the `RewardSent` events
+    pub rewards_sent: u128,
+    /// Last block scanned for reward pool events
+    pub last_synced_block: u64,
+    /// Amount of alerts raised
+    pub alerts: u64,
+}
+
+/// Parameters of the contract reward formula, which can be changed through a proposal
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq)]
+pub struct RewardParameters {
+    /// Reward multiplier coefficient, halved every 4 years
+    pub rmc: f64,
+    /// Avidity, adjusted every month according to the contracts created
+    pub avidity: f64,
+    /// Time of the next RMC halving (nanoseconds)
+    pub next_halving: u64,
+}
+
+#[cfg(test)]
+mod test {
+
+    use ic_stable_structures::Storable as _;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_should_encode_reward_reservation() {
+        let reservation = RewardReservation {
+            contract_id: 1u64.into(),
+            reward: 500_000,
+            tokens: 4_000,
+            reserved_at: 1_000,
+            status: RewardReservationStatus::Confirmed { block: 10 },
+        };
+
+        let data = reservation.to_bytes();
+        let decoded = RewardReservation::from_bytes(data);
+        assert_eq!(reservation, decoded);
+        assert_eq!(decoded.total(), 2_000_000_000);
+    }
+}
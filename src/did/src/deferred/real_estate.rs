@@ -1,4 +1,4 @@
-use candid::{CandidType, Decode, Deserialize, Encode};
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_stable_structures::storable::Bound;
 use ic_stable_structures::Storable;
 use serde::Serialize;
@@ -73,15 +73,115 @@ impl Storable for RealEstate {
         Encode!(&self).unwrap().into()
     }
 
+    /// The real estates stored before the introduction of the agency ids reference their agency
+    /// by its wallet, which is converted to its legacy agency id
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(&bytes, Self).unwrap()
+        Decode!(&bytes, Self)
+            .or_else(|_| Decode!(&bytes, RealEstateV1).map(RealEstate::from))
+            .unwrap()
+    }
+}
+
+/// A [`RealEstate`] stored before the introduction of the agency ids, when its agency was
+/// identified by the wallet of its owner
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct RealEstateV1 {
+    /// Mark as deleted
+    pub deleted: bool,
+    /// agency
+    pub agency: Principal,
+    /// name
+    pub name: String,
+    /// description
+    pub description: String,
+    /// image URL
+    pub image: Option<String>,
+    /// address
+    pub address: Option<String>,
+    /// country
+    pub country: Option<String>,
+    /// continent
+    pub continent: Option<Continent>,
+    /// region
+    pub region: Option<String>,
+    /// city
+    pub city: Option<String>,
+    /// zone
+    pub zone: Option<String>,
+    /// postal code
+    pub zip_code: Option<String>,
+    /// latitude
+    pub latitude: Option<f64>,
+    /// longitude
+    pub longitude: Option<f64>,
+    /// square meters
+    pub square_meters: Option<u64>,
+    /// number of rooms
+    pub rooms: Option<u64>,
+    /// number of bathrooms
+    pub bathrooms: Option<u64>,
+    /// number of bedrooms
+    pub bedrooms: Option<u64>,
+    /// floors
+    pub floors: Option<u64>,
+    /// year of construction
+    pub year_of_construction: Option<u64>,
+    /// garden
+    pub garden: Option<bool>,
+    /// balconies
+    pub balconies: Option<u64>,
+    /// pool
+    pub pool: Option<bool>,
+    /// garage
+    pub garage: Option<bool>,
+    /// parking
+    pub parking: Option<bool>,
+    /// elevator
+    pub elevator: Option<bool>,
+    /// energy class
+    pub energy_class: Option<String>,
+    /// youtube url
+    pub youtube: Option<String>,
+}
+
+impl From<RealEstateV1> for RealEstate {
+    fn from(real_estate: RealEstateV1) -> Self {
+        Self {
+            deleted: real_estate.deleted,
+            agency: AgencyId::legacy(real_estate.agency),
+            name: real_estate.name,
+            description: real_estate.description,
+            image: real_estate.image,
+            address: real_estate.address,
+            country: real_estate.country,
+            continent: real_estate.continent,
+            region: real_estate.region,
+            city: real_estate.city,
+            zone: real_estate.zone,
+            zip_code: real_estate.zip_code,
+            latitude: real_estate.latitude,
+            longitude: real_estate.longitude,
+            square_meters: real_estate.square_meters,
+            rooms: real_estate.rooms,
+            bathrooms: real_estate.bathrooms,
+            bedrooms: real_estate.bedrooms,
+            floors: real_estate.floors,
+            year_of_construction: real_estate.year_of_construction,
+            garden: real_estate.garden,
+            balconies: real_estate.balconies,
+            pool: real_estate.pool,
+            garage: real_estate.garage,
+            parking: real_estate.parking,
+            elevator: real_estate.elevator,
+            energy_class: real_estate.energy_class,
+            youtube: real_estate.youtube,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    use candid::Principal;
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -90,7 +190,7 @@ mod test {
     fn test_should_encode_and_decode_real_estate() {
         let real_estate = RealEstate {
             deleted: false,
-            agency: Principal::management_canister(),
+            agency: AgencyId(1),
             name: "name".to_string(),
             description: "description".to_string(),
             image: Some("image".to_string()),
@@ -124,4 +224,45 @@ mod test {
 
         assert_eq!(real_estate, decoded);
     }
+
+    #[test]
+    fn test_should_decode_real_estate_stored_before_agency_ids() {
+        let wallet = Principal::from_text("be2us-64aaa-aaaaa-qaabq-cai").unwrap();
+        let legacy = RealEstateV1 {
+            deleted: false,
+            agency: wallet,
+            name: "name".to_string(),
+            description: "description".to_string(),
+            image: None,
+            address: None,
+            country: None,
+            continent: None,
+            region: None,
+            city: None,
+            zone: None,
+            zip_code: None,
+            latitude: None,
+            longitude: None,
+            square_meters: Some(100),
+            rooms: None,
+            bathrooms: None,
+            bedrooms: None,
+            floors: None,
+            year_of_construction: None,
+            garden: None,
+            balconies: None,
+            pool: None,
+            garage: None,
+            parking: None,
+            elevator: None,
+            energy_class: None,
+            youtube: None,
+        };
+        let data = Encode!(&legacy).unwrap();
+        let decoded = RealEstate::from_bytes(data.into());
+
+        assert_eq!(decoded.agency, AgencyId::legacy(wallet));
+        assert_eq!(decoded.name, "name");
+        assert_eq!(decoded.square_meters, Some(100));
+    }
 }
@@ -6,5 +6,7 @@ mod common;
 pub mod deferred;
 
 pub use common::{
-    HttpRequest, HttpResponse, StorableLogSettings, StorableNat, StorablePrincipal, H160, ID,
+    HttpRequest, HttpResponse, MetricsEncoder, StorableLogSettings, StorableNat, StorablePrincipal,
+    StreamingCallback, StreamingCallbackHttpResponse, StreamingCallbackToken, StreamingStrategy,
+    H160, ID, METRICS_CONTENT_TYPE,
 };
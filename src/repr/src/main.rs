@@ -1,7 +1,7 @@
 use candid::Principal;
 use did::deferred::{
-    Agency, Continent, Contract, ContractDocument, ContractType, GenericValue, RestrictedProperty,
-    RestrictionLevel,
+    Agency, AgencyId, Continent, Contract, ContractDocument, ContractType, GenericValue,
+    RestrictedProperty, RestrictionLevel,
 };
 
 fn main() -> anyhow::Result<()> {
@@ -12,6 +12,7 @@ fn main() -> anyhow::Result<()> {
 
 fn repr_contract() -> anyhow::Result<()> {
     let agency = Agency {
+        id: AgencyId(1),
         address: "Via Roma 1".to_string(),
         name: "Agency".to_string(),
         agent: "Miriam".to_string(),
@@ -106,7 +107,7 @@ fn repr_contract() -> anyhow::Result<()> {
         expiration: "1970-01-01".to_string(),
         properties,
         restricted_properties,
-        agency: agency.owner,
+        agency: agency.id,
         id: 1u64.into(),
         real_estate: 2u64.into(),
         documents,